@@ -0,0 +1,130 @@
+//! Benchmarks of read locking of graphics object storages.
+//!
+//! Compares storing graphics objects of each type in its own slotmap behind a read-write lock
+//! (so creation of a pipeline locks four of them one after another)
+//! with a single lock of the whole context.
+//! No GPU is needed: objects are mocked with plain data.
+
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use slotmap::{DefaultKey, SlotMap};
+
+const OBJECTS: usize = 1000;
+const READERS: usize = 4;
+
+/// Mock of graphics object which is stored in the slotmap.
+#[derive(Default)]
+struct Object {
+    handle: u64,
+}
+
+type Storage = SlotMap<DefaultKey, Object>;
+
+/// Objects which are used to create a pipeline: device, render pass, shaders and layout.
+struct Keys([DefaultKey; 4]);
+
+/// Four storages, each behind its own lock.
+struct NestedLocks {
+    storages: [RwLock<Storage>; 4],
+}
+
+/// Four storages behind a single lock.
+struct SingleLock {
+    storages: RwLock<[Storage; 4]>,
+}
+
+fn storages() -> ([Storage; 4], Keys) {
+    let mut storages: [Storage; 4] = Default::default();
+    let mut keys = [DefaultKey::default(); 4];
+    for (storage, key) in storages.iter_mut().zip(&mut keys) {
+        for handle in 0..OBJECTS as u64 {
+            *key = storage.insert(Object { handle });
+        }
+    }
+    (storages, Keys(keys))
+}
+
+impl NestedLocks {
+    /// Locks all storages one after another, like creation of a pipeline does.
+    fn read(&self, keys: &Keys) -> u64 {
+        let [a, b, c, d] = &self.storages;
+        let a = a.read().unwrap();
+        let b = b.read().unwrap();
+        let c = c.read().unwrap();
+        let d = d.read().unwrap();
+        a[keys.0[0]].handle + b[keys.0[1]].handle + c[keys.0[2]].handle + d[keys.0[3]].handle
+    }
+}
+
+impl SingleLock {
+    /// Locks the whole context once.
+    fn read(&self, keys: &Keys) -> u64 {
+        let storages = self.storages.read().unwrap();
+        let [a, b, c, d] = &*storages;
+        a[keys.0[0]].handle + b[keys.0[1]].handle + c[keys.0[2]].handle + d[keys.0[3]].handle
+    }
+}
+
+/// Runs `iterations` reads split between [`READERS`] threads and returns elapsed time.
+fn contended<F>(iterations: u64, read: F) -> Duration
+where
+    F: Fn() -> u64 + Send + Sync + 'static,
+{
+    let read = Arc::new(read);
+    let per_thread = iterations / READERS as u64 + 1;
+    let start = Instant::now();
+    let threads: Vec<_> = (0..READERS)
+        .map(|_| {
+            let read = read.clone();
+            thread::spawn(move || {
+                for _ in 0..per_thread {
+                    black_box(read());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    start.elapsed()
+}
+
+fn locks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graphics locks");
+
+    let (storages, keys) = self::storages();
+    let nested = Arc::new(NestedLocks {
+        storages: storages.map(RwLock::new),
+    });
+    let keys = Arc::new(keys);
+    group.bench_function("nested four locks", |b| {
+        b.iter(|| nested.read(&keys));
+    });
+    group.bench_function("nested four locks, 4 readers", |b| {
+        b.iter_custom(|iterations| {
+            let (nested, keys) = (nested.clone(), keys.clone());
+            self::contended(iterations, move || nested.read(&keys))
+        });
+    });
+
+    let (storages, _) = self::storages();
+    let single = Arc::new(SingleLock {
+        storages: RwLock::new(storages),
+    });
+    group.bench_function("single lock", |b| {
+        b.iter(|| single.read(&keys));
+    });
+    group.bench_function("single lock, 4 readers", |b| {
+        b.iter_custom(|iterations| {
+            let (single, keys) = (single.clone(), keys.clone());
+            self::contended(iterations, move || single.read(&keys))
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, locks);
+criterion_main!(benches);
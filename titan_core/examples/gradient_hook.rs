@@ -0,0 +1,294 @@
+//! Example of render hooks which draw animated gradient under the scene.
+//!
+//! First hook renders fullscreen gradient into offscreen image before the main pass,
+//! then second hook draws this image inside the main pass before game objects.
+//! Gradient could not be drawn into the swapchain image directly before the main pass,
+//! because the main pass clears it.
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use titan_core::prelude::*;
+use vulkano::command_buffer::SubpassContents;
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::view::ImageView;
+use vulkano::image::AttachmentImage;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
+use vulkano::render_pass::{Framebuffer, FramebufferAbstract, Subpass};
+use vulkano::sampler::Sampler;
+
+/// Format of the offscreen gradient image.
+const GRADIENT_FORMAT: Format = Format::R8G8B8A8_UNORM;
+
+mod vertex {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+            #version 450
+
+            layout(location = 0) out vec2 outUV;
+
+            void main() {
+                vec2 uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+                gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+                outUV = uv;
+            }
+        ",
+    }
+}
+
+mod gradient {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+            #version 450
+
+            layout(push_constant) uniform PushConstants {
+                float time;
+            } constants;
+
+            layout(location = 0) in vec2 inUV;
+
+            layout(location = 0) out vec4 outColor;
+
+            void main() {
+                vec3 top = 0.5 + 0.5 * cos(constants.time + vec3(0.0, 2.0, 4.0));
+                vec3 bottom = vec3(0.02, 0.02, 0.05);
+                outColor = vec4(mix(top, bottom, inUV.y), 1.0);
+            }
+        ",
+    }
+}
+
+mod composite {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+            #version 450
+
+            layout(binding = 0, set = 0) uniform sampler2D gradient;
+
+            layout(location = 0) in vec2 inUV;
+
+            layout(location = 0) out vec4 outColor;
+
+            void main() {
+                outColor = texture(gradient, inUV);
+            }
+        ",
+    }
+}
+
+/// Gradient image which is shared between both hooks.
+type SharedImage = Arc<Mutex<Option<Arc<ImageView<Arc<AttachmentImage>>>>>>;
+
+fn viewport(extent: [u32; 2]) -> Viewport {
+    Viewport {
+        origin: [0.0, 0.0],
+        dimensions: [extent[0] as f32, extent[1] as f32],
+        depth_range: 0.0..1.0,
+    }
+}
+
+/// Renders the gradient into offscreen image before the main pass.
+struct GradientHook {
+    started_at: Instant,
+    image: SharedImage,
+    pipeline: Option<Arc<GraphicsPipeline>>,
+    framebuffer: Option<Arc<dyn FramebufferAbstract + Send + Sync>>,
+}
+
+impl RenderHook for GradientHook {
+    fn prepare(&mut self, device: &Arc<Device>, frame: &FrameContext) -> Result<(), HookError> {
+        let pipeline = match &self.pipeline {
+            Some(pipeline) => pipeline.clone(),
+            None => {
+                let render_pass = vulkano::single_pass_renderpass! {
+                    device.clone(),
+                    attachments: {
+                        // Each pixel is overwritten, so there is no need to clear.
+                        color: {
+                            load: DontCare,
+                            store: Store,
+                            format: GRADIENT_FORMAT,
+                            samples: 1,
+                        }
+                    },
+                    pass: {
+                        color: [color],
+                        depth_stencil: {}
+                    }
+                }?;
+                let subpass = Subpass::from(Arc::new(render_pass), 0).unwrap();
+                let vert_shader_module = vertex::Shader::load(device.clone())?;
+                let frag_shader_module = gradient::Shader::load(device.clone())?;
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_shader(vert_shader_module.main_entry_point(), ())
+                    .fragment_shader(frag_shader_module.main_entry_point(), ())
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .render_pass(subpass)
+                    .build(device.clone())?;
+                let pipeline = Arc::new(pipeline);
+                self.pipeline = Some(pipeline.clone());
+                pipeline
+            }
+        };
+
+        // Image is recreated after resize.
+        let mut image = self.image.lock().unwrap();
+        if image.is_none() || self.framebuffer.is_none() {
+            let view = ImageView::new(AttachmentImage::sampled(
+                device.clone(),
+                frame.extent,
+                GRADIENT_FORMAT,
+            )?)?;
+            let render_pass = pipeline.subpass().render_pass().clone();
+            let framebuffer = Framebuffer::start(render_pass).add(view.clone())?.build()?;
+            self.framebuffer = Some(Arc::new(framebuffer));
+            *image = Some(view);
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        commands: &mut HookCommands,
+        frame: &FrameContext,
+    ) -> Result<(), HookError> {
+        let builder = match commands {
+            HookCommands::Primary(builder) => builder,
+            HookCommands::Secondary(_) => {
+                return Err("gradient must be rendered outside of the main pass".into())
+            }
+        };
+        let pipeline = self.pipeline.clone().ok_or("hook was not prepared")?;
+        let framebuffer = self.framebuffer.clone().ok_or("hook was not prepared")?;
+        let push_constants = gradient::ty::PushConstants {
+            time: self.started_at.elapsed().as_secs_f32(),
+        };
+        builder
+            .begin_render_pass(framebuffer, SubpassContents::Inline, vec![ClearValue::None])?
+            .set_viewport(0, std::iter::once(self::viewport(frame.extent)))
+            .bind_pipeline_graphics(pipeline.clone())
+            .push_constants(pipeline.layout().clone(), 0, push_constants)
+            .draw(3, 1, 0, 0)?
+            .end_render_pass()?;
+        Ok(())
+    }
+
+    fn on_resize(&mut self, _extent: [u32; 2]) {
+        self.framebuffer = None;
+    }
+}
+
+/// Draws the gradient image under game objects inside the main pass.
+struct CompositeHook {
+    image: SharedImage,
+    sampler: Option<Arc<Sampler>>,
+    pipeline: Option<Arc<GraphicsPipeline>>,
+    descriptor_set: Option<(
+        Arc<ImageView<Arc<AttachmentImage>>>,
+        Arc<PersistentDescriptorSet>,
+    )>,
+}
+
+impl RenderHook for CompositeHook {
+    fn prepare(&mut self, device: &Arc<Device>, frame: &FrameContext) -> Result<(), HookError> {
+        // Subpass of the main pass is changed with antialiasing mode.
+        let outdated = self.pipeline.as_ref().map_or(true, |pipeline| {
+            let subpass = pipeline.subpass();
+            !Arc::ptr_eq(subpass.render_pass(), frame.subpass.render_pass())
+                || subpass.index() != frame.subpass.index()
+        });
+        if outdated {
+            let vert_shader_module = vertex::Shader::load(device.clone())?;
+            let frag_shader_module = composite::Shader::load(device.clone())?;
+            let pipeline = GraphicsPipeline::start()
+                .vertex_shader(vert_shader_module.main_entry_point(), ())
+                .fragment_shader(frag_shader_module.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .render_pass(frame.subpass.clone())
+                .build(device.clone())?;
+            self.pipeline = Some(Arc::new(pipeline));
+            self.descriptor_set = None;
+        }
+        if self.sampler.is_none() {
+            self.sampler = Some(Sampler::simple_repeat_linear_no_mipmap(device.clone()));
+        }
+
+        let image = self
+            .image
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("gradient image is missing")?;
+        let current = self
+            .descriptor_set
+            .as_ref()
+            .map_or(false, |(view, _)| Arc::ptr_eq(view, &image));
+        if !current {
+            let pipeline = self.pipeline.as_ref().unwrap();
+            let layout = pipeline.layout().descriptor_set_layouts()[0].clone();
+            let mut builder = PersistentDescriptorSet::start(layout);
+            builder.add_sampled_image(image.clone(), self.sampler.clone().unwrap())?;
+            self.descriptor_set = Some((image, Arc::new(builder.build()?)));
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        commands: &mut HookCommands,
+        frame: &FrameContext,
+    ) -> Result<(), HookError> {
+        let builder = match commands {
+            HookCommands::Secondary(builder) => builder,
+            HookCommands::Primary(_) => {
+                return Err("gradient must be composited inside the main pass".into())
+            }
+        };
+        let pipeline = self.pipeline.clone().ok_or("hook was not prepared")?;
+        let (_, descriptor_set) = self.descriptor_set.clone().ok_or("hook was not prepared")?;
+        builder
+            .set_viewport(0, std::iter::once(self::viewport(frame.extent)))
+            .bind_pipeline_graphics(pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .draw(3, 1, 0, 0)?;
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let version = Version::new(0, 1, 0);
+    let config = Config::new("gradient_hook".to_string(), version, cfg!(debug_assertions));
+    let mut application = titan_core::init(config)?;
+
+    let image = SharedImage::default();
+    let gradient = GradientHook {
+        started_at: Instant::now(),
+        image: image.clone(),
+        pipeline: None,
+        framebuffer: None,
+    };
+    let composite = CompositeHook {
+        image,
+        sampler: None,
+        pipeline: None,
+        descriptor_set: None,
+    };
+    application.add_render_hook(HookStage::BeforeMainPass, Box::new(gradient));
+    application.add_render_hook(HookStage::InsideMainPass, Box::new(composite));
+
+    application.run(|_| ())
+}
@@ -0,0 +1,148 @@
+//! JNI bridge which forwards log messages from Java side into global logger.
+//!
+//! Java class `native.Logger` is expected to declare these functions as `native`.
+//! Each logging function accepts message and optional target:
+//! if target is `null`, [`DEFAULT_TARGET`] is used instead.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use jni::objects::{JClass, JString};
+use jni::sys::jint;
+use jni::JNIEnv;
+use log::{Level, LevelFilter};
+
+/// Target of log messages which were sent by Java side without explicit target.
+pub const DEFAULT_TARGET: &str = "titan-rs";
+
+/// Max level of messages which will be forwarded into global logger.
+///
+/// Stored as `usize` representation of [`LevelFilter`].
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Trace as usize);
+
+/// Converts raw level from Java side into [`LevelFilter`].
+///
+/// Values less than zero are clamped to [`LevelFilter::Off`],
+/// values greater than five are clamped to [`LevelFilter::Trace`].
+fn level_filter(level: jint) -> LevelFilter {
+    match level {
+        i32::MIN..=0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Current max level of messages which will be forwarded into global logger.
+fn max_level() -> LevelFilter {
+    let level = MAX_LEVEL.load(Ordering::Relaxed);
+    self::level_filter(level as jint)
+}
+
+/// Converts Java string into Rust string.
+///
+/// Returns `None` if Java string is `null` or cannot be converted.
+fn to_string(env: &JNIEnv, string: JString) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    env.get_string(string).ok().map(String::from)
+}
+
+/// Forwards message from Java side into global logger.
+fn handle_log(env: JNIEnv, level: Level, message: JString, target: JString) {
+    if level > self::max_level() {
+        return;
+    }
+    let message = match self::to_string(&env, message) {
+        Some(message) => message,
+        None => return,
+    };
+    let target = self::to_string(&env, target);
+    let target = target.as_deref().unwrap_or(DEFAULT_TARGET);
+    log::log!(target: target, level, "{}", message);
+}
+
+/// Initializes Android logger backend, so messages will reach logcat
+/// (and clients of the [diagnostics server](crate::app::diagnostics), if it is enabled).
+///
+/// Has no effect if global logger was already initialized
+/// or if `android-logger` feature is disabled.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_init(_env: JNIEnv, _class: JClass) {
+    #[cfg(feature = "android-logger")]
+    {
+        let logger = android_logger::AndroidLogger::new(
+            android_logger::Config::default()
+                .with_min_level(Level::Trace)
+                .with_tag(DEFAULT_TARGET),
+        );
+        // Error means that global logger was already initialized.
+        let _ = crate::app::DiagnosticsLogger::init(logger, LevelFilter::Trace);
+    }
+}
+
+/// Sets max level of messages which will be forwarded into global logger.
+///
+/// Invalid level values are clamped (see [`level_filter`]).
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_setMaxLevel(_env: JNIEnv, _class: JClass, level: jint) {
+    let level = self::level_filter(level);
+    MAX_LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+/// Forwards trace message from Java side into global logger.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_trace(
+    env: JNIEnv,
+    _class: JClass,
+    message: JString,
+    target: JString,
+) {
+    self::handle_log(env, Level::Trace, message, target)
+}
+
+/// Forwards debug message from Java side into global logger.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_debug(
+    env: JNIEnv,
+    _class: JClass,
+    message: JString,
+    target: JString,
+) {
+    self::handle_log(env, Level::Debug, message, target)
+}
+
+/// Forwards info message from Java side into global logger.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_info(
+    env: JNIEnv,
+    _class: JClass,
+    message: JString,
+    target: JString,
+) {
+    self::handle_log(env, Level::Info, message, target)
+}
+
+/// Forwards warn message from Java side into global logger.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_warn(
+    env: JNIEnv,
+    _class: JClass,
+    message: JString,
+    target: JString,
+) {
+    self::handle_log(env, Level::Warn, message, target)
+}
+
+/// Forwards error message from Java side into global logger.
+#[no_mangle]
+pub extern "system" fn Java_native_Logger_error(
+    env: JNIEnv,
+    _class: JClass,
+    message: JString,
+    target: JString,
+) {
+    self::handle_log(env, Level::Error, message, target)
+}
@@ -0,0 +1,3 @@
+//! Android-specific utilities of game engine.
+
+pub mod logger;
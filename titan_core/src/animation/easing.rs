@@ -0,0 +1,95 @@
+//! Easing functions and interpolation modes of keyframe segments.
+
+use std::f32::consts::PI;
+
+/// Easing function which changes the rate of animation over time.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    /// Oscillates with growing amplitude before reaching the end.
+    ElasticIn,
+    /// Overshoots the end and oscillates with decaying amplitude.
+    ElasticOut,
+}
+
+impl Easing {
+    /// Maps progress of animation (clamped to `[0; 1]`) to the interpolation weight.
+    ///
+    /// Result is 0 at the start and 1 at the end, but elastic easings could leave this range
+    /// in the middle.
+    ///
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Self::ElasticIn => {
+                if t == 0.0 || t == 1.0 {
+                    return t;
+                }
+                -(2.0f32).powf(10.0 * t - 10.0) * ((10.0 * t - 10.75) * ELASTIC_PERIOD).sin()
+            }
+            Self::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    return t;
+                }
+                (2.0f32).powf(-10.0 * t) * ((10.0 * t - 0.75) * ELASTIC_PERIOD).sin() + 1.0
+            }
+        }
+    }
+}
+
+/// Angular frequency of oscillations of elastic easings.
+const ELASTIC_PERIOD: f32 = 2.0 * PI / 3.0;
+
+/// How values are interpolated between the keyframe and the next one.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// Value of the keyframe is held until the next keyframe.
+    Step,
+    /// Value changes with constant rate.
+    #[default]
+    Linear,
+    /// Value changes smoothly, starting and ending with zero rate
+    /// (cubic Hermite curve with flat tangents).
+    Cubic,
+    /// Value changes according to the easing function.
+    Eased(Easing),
+}
+
+impl Interpolation {
+    /// Interpolation weight of the segment at given progress (from 0 to 1).
+    pub fn weight(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Step => 0.0,
+            Self::Linear => t,
+            Self::Cubic => t * t * (3.0 - 2.0 * t),
+            Self::Eased(easing) => easing.apply(t),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Linear interpolation of values which could be animated.
+
+use palette::Srgba;
+use ultraviolet::{Mat3, Mat4, Rotor3, Vec2, Vec3};
+
+use crate::math::Color;
+
+/// Values which could be interpolated by [`Track`](super::Track).
+pub trait Lerp: Sized {
+    /// Interpolates between `self` (when `t` is 0) and `other` (when `t` is 1).
+    ///
+    /// Values of `t` outside of `[0; 1]` are allowed (for example, by elastic easing).
+    ///
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+/// Color is interpolated component-wise (including alpha) in sRGB space.
+impl Lerp for Srgba {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Srgba::new(
+            self.red.lerp(&other.red, t),
+            self.green.lerp(&other.green, t),
+            self.blue.lerp(&other.blue, t),
+            self.alpha.lerp(&other.alpha, t),
+        )
+    }
+}
+
+/// Color is interpolated component-wise (including alpha) in linear space,
+/// so there are no dark bands in the middle of interpolation.
+impl Lerp for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Color::rgba(
+            self.red.lerp(&other.red, t),
+            self.green.lerp(&other.green, t),
+            self.blue.lerp(&other.blue, t),
+            self.alpha.lerp(&other.alpha, t),
+        )
+    }
+}
+
+/// Matrix is decomposed into translation, rotation and scale, which are interpolated separately
+/// (rotation along the shortest path), so rotating objects are not distorted in the middle.
+///
+/// Matrix is expected to be an affine transform without shear.
+///
+impl Lerp for Mat4 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let (translation, rotation, scale) = self::decompose(self);
+        let (other_translation, other_rotation, other_scale) = self::decompose(other);
+
+        let translation = translation.lerp(&other_translation, t);
+        let scale = scale.lerp(&other_scale, t);
+        // Rotors `r` and `-r` represent the same rotation, so choose the closest one.
+        let other_rotation = if rotation.dot(other_rotation) < 0.0 {
+            other_rotation * -1.0
+        } else {
+            other_rotation
+        };
+        let rotation = (rotation + (other_rotation - rotation) * t).normalized();
+
+        Mat4::from_translation(translation)
+            * rotation.into_matrix().into_homogeneous()
+            * Mat4::from_nonuniform_scale(scale)
+    }
+}
+
+/// Splits affine transform matrix into translation, rotation and scale.
+fn decompose(matrix: &Mat4) -> (Vec3, Rotor3, Vec3) {
+    let translation = matrix.extract_translation();
+    let [x, y, z] = [0, 1, 2].map(|index| matrix.cols[index].truncated());
+    let scale = Vec3::new(x.mag(), y.mag(), z.mag());
+    let normalize = |axis: Vec3, scale: f32| {
+        if scale > f32::EPSILON {
+            axis / scale
+        } else {
+            axis
+        }
+    };
+    let rotation = Mat3::new(
+        normalize(x, scale.x),
+        normalize(y, scale.y),
+        normalize(z, scale.z),
+    );
+    (translation, rotation.into_rotor3(), scale)
+}
@@ -0,0 +1,285 @@
+//! Property animation with keyframe tracks and easing functions.
+//!
+//! [`Animator`] should be advanced by the delta time of each update of the application,
+//! for example in the callback of [`Application::run`](crate::app::Application::run):
+//!
+//! ```no_run
+//! use titan_core::animation::{Animator, Easing, Playback, Track};
+//! use titan_core::window::Event;
+//!
+//! let mut animator = Animator::new();
+//! let fade = animator.play(Track::tween(1.0f32, 0.0, 2.0, Easing::QuadOut), Playback::Once);
+//! let callback = move |event: Event| {
+//!     if let Event::Update(delta_time) = event {
+//!         animator.update(delta_time);
+//!         let alpha = animator.sample::<f32>(fade);
+//!     }
+//! };
+//! ```
+
+use std::any::Any;
+use std::time::Duration;
+
+use slotmap::{new_key_type, SlotMap};
+
+pub use easing::{Easing, Interpolation};
+pub use lerp::Lerp;
+pub use track::{Keyframe, Track};
+
+mod easing;
+mod lerp;
+mod track;
+
+mod tests;
+
+new_key_type! {
+    /// Identifier of the animation played by [`Animator`].
+    pub struct AnimationId;
+}
+
+/// How animation continues after the end of its track.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Playback {
+    /// Animation is played once and then holds the last value.
+    #[default]
+    Once,
+    /// Animation is repeated from the start.
+    Loop,
+    /// Animation is played forward and backward alternately.
+    PingPong,
+}
+
+/// Callback which is called when the animation is finished.
+type FinishCallback = Box<dyn FnMut(AnimationId) + Send + Sync>;
+
+/// Track played by the animator with its playback state.
+struct Animation {
+    /// Track of the animation (`Track<T>` for some `T`).
+    track: Box<dyn Any + Send + Sync>,
+    duration: f32,
+    playback: Playback,
+    elapsed: f32,
+    paused: bool,
+    finished: bool,
+    on_finish: Option<FinishCallback>,
+}
+
+impl Animation {
+    /// Time of the track at current playback position.
+    fn local_time(&self) -> f32 {
+        let duration = self.duration;
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.playback {
+            Playback::Once => self.elapsed.min(duration),
+            Playback::Loop => self.elapsed % duration,
+            Playback::PingPong => {
+                let time = self.elapsed % (2.0 * duration);
+                if time <= duration {
+                    time
+                } else {
+                    2.0 * duration - time
+                }
+            }
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.playback == Playback::Once && self.elapsed >= self.duration
+    }
+}
+
+/// Plays animations and advances them by the delta time of each update.
+///
+/// Animator could be stored in the game state or as a component of the ECS world.
+///
+pub struct Animator {
+    animations: SlotMap<AnimationId, Animation>,
+    time_scale: f32,
+    paused: bool,
+}
+
+impl Animator {
+    /// Creates new animator without animations.
+    pub fn new() -> Self {
+        Self {
+            animations: SlotMap::with_key(),
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+
+    /// Starts playing the track with given playback mode.
+    pub fn play<T>(&mut self, track: Track<T>, playback: Playback) -> AnimationId
+    where
+        T: Lerp + Clone + Send + Sync + 'static,
+    {
+        let animation = Animation {
+            duration: track.duration(),
+            track: Box::new(track),
+            playback,
+            elapsed: 0.0,
+            paused: false,
+            finished: false,
+            on_finish: None,
+        };
+        self.animations.insert(animation)
+    }
+
+    /// Sets callback which is called when the animation is finished.
+    ///
+    /// Only animations played with [`Playback::Once`] are finished.
+    ///
+    /// Returns `false` if there is no such animation.
+    ///
+    pub fn on_finish<F>(&mut self, id: AnimationId, callback: F) -> bool
+    where
+        F: FnMut(AnimationId) + Send + Sync + 'static,
+    {
+        match self.animations.get_mut(id) {
+            Some(animation) => {
+                animation.on_finish = Some(Box::new(callback));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advances all animations which are not paused by the delta time
+    /// multiplied by the time scale.
+    ///
+    /// Finished animations are not removed, so they could still be sampled.
+    ///
+    /// Returns identifiers of animations which were finished during this update
+    /// (after their callbacks were called).
+    ///
+    pub fn update(&mut self, delta_time: Duration) -> Vec<AnimationId> {
+        if self.paused {
+            return Vec::new();
+        }
+        let delta = delta_time.as_secs_f32() * self.time_scale;
+        let mut finished = Vec::new();
+        for (id, animation) in &mut self.animations {
+            if animation.paused || animation.finished {
+                continue;
+            }
+            animation.elapsed += delta;
+            if animation.is_over() {
+                animation.finished = true;
+                if let Some(callback) = &mut animation.on_finish {
+                    callback(id);
+                }
+                finished.push(id);
+            }
+        }
+        finished
+    }
+
+    /// Samples current value of the animation.
+    ///
+    /// Returns `None` if there is no such animation, its track has no keyframes
+    /// or its values are not of type `T`.
+    ///
+    pub fn sample<T>(&self, id: AnimationId) -> Option<T>
+    where
+        T: Lerp + Clone + 'static,
+    {
+        let animation = self.animations.get(id)?;
+        let track = animation.track.downcast_ref::<Track<T>>()?;
+        track.sample(animation.local_time())
+    }
+
+    /// Time of the track at current playback position of the animation (in seconds).
+    pub fn time(&self, id: AnimationId) -> Option<f32> {
+        let animation = self.animations.get(id)?;
+        Some(animation.local_time())
+    }
+
+    /// Seeks the animation to given time (in seconds from the start of playback).
+    ///
+    /// Seeking restarts finished animation.
+    /// If the time is beyond the end of the track, animation played once
+    /// is finished on the next update.
+    ///
+    /// Returns `false` if there is no such animation.
+    ///
+    pub fn set_time(&mut self, id: AnimationId, time: f32) -> bool {
+        match self.animations.get_mut(id) {
+            Some(animation) => {
+                animation.elapsed = time.max(0.0);
+                animation.finished = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pauses the animation. Returns `false` if there is no such animation.
+    pub fn pause(&mut self, id: AnimationId) -> bool {
+        self.set_animation_paused(id, true)
+    }
+
+    /// Resumes the paused animation. Returns `false` if there is no such animation.
+    pub fn resume(&mut self, id: AnimationId) -> bool {
+        self.set_animation_paused(id, false)
+    }
+
+    fn set_animation_paused(&mut self, id: AnimationId, paused: bool) -> bool {
+        match self.animations.get_mut(id) {
+            Some(animation) => {
+                animation.paused = paused;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the animation was finished.
+    pub fn is_finished(&self, id: AnimationId) -> bool {
+        self.animations
+            .get(id)
+            .map(|animation| animation.finished)
+            .unwrap_or_default()
+    }
+
+    /// Stops and removes the animation. Returns `false` if there is no such animation.
+    pub fn stop(&mut self, id: AnimationId) -> bool {
+        self.animations.remove(id).is_some()
+    }
+
+    /// Multiplier of the delta time of all animations.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets multiplier of the delta time of all animations (negative scale is clamped to 0).
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0)
+    }
+
+    /// Returns `true` if all animations are paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes all animations.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused
+    }
+
+    /// Count of animations which were not stopped.
+    pub fn len(&self) -> usize {
+        self.animations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,254 @@
+#![cfg(test)]
+
+use std::f32::consts::FRAC_PI_2;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use palette::Srgba;
+use ultraviolet::{Mat4, Rotor3, Vec2, Vec3};
+
+use crate::math::Color;
+
+use super::*;
+
+const QUARTER: Duration = Duration::from_millis(250);
+const HALF: Duration = Duration::from_millis(500);
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-5,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+/// Track 0 → 10 (linear) → 20 (step) → 40 at times 0, 1, 2 and 3.
+fn track() -> Track<f32> {
+    Track::new()
+        .with_keyframe(0.0, 0.0, Interpolation::Linear)
+        .with_keyframe(1.0, 10.0, Interpolation::Step)
+        .with_keyframe(2.0, 20.0, Interpolation::Cubic)
+        .with_keyframe(3.0, 40.0, Interpolation::Linear)
+}
+
+#[test]
+fn test_track_sample() {
+    let track = track();
+    assert_eq!(track.duration(), 3.0);
+    assert_eq!(track.sample(-1.0), Some(0.0));
+    assert_eq!(track.sample(0.0), Some(0.0));
+    assert_eq!(track.sample(0.5), Some(5.0));
+    // Exactly on keyframes their values are returned.
+    assert_eq!(track.sample(1.0), Some(10.0));
+    assert_eq!(track.sample(1.999), Some(10.0));
+    assert_eq!(track.sample(2.0), Some(20.0));
+    assert_eq!(track.sample(2.5), Some(30.0));
+    assert_eq!(track.sample(3.0), Some(40.0));
+    assert_eq!(track.sample(10.0), Some(40.0));
+
+    assert_eq!(Track::<f32>::new().sample(0.0), None);
+}
+
+#[test]
+fn test_track_push_unsorted() {
+    let mut track = Track::new();
+    track.push(2.0, 2.0f32, Interpolation::Linear);
+    track.push(0.0, 0.0, Interpolation::Linear);
+    track.push(1.0, 1.0, Interpolation::Linear);
+    let times: Vec<_> = track.keyframes().iter().map(|k| k.time).collect();
+    assert_eq!(times, [0.0, 1.0, 2.0]);
+    assert_eq!(track.sample(1.5), Some(1.5));
+}
+
+#[test]
+fn test_easing() {
+    let easings = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+    ];
+    for easing in easings {
+        assert_close(easing.apply(0.0), 0.0);
+        assert_close(easing.apply(1.0), 1.0);
+        assert_close(easing.apply(2.0), 1.0);
+    }
+    assert_close(Easing::QuadIn.apply(0.5), 0.25);
+    assert_close(Easing::QuadOut.apply(0.5), 0.75);
+    assert_close(Easing::QuadInOut.apply(0.25), 0.125);
+    assert_close(Easing::CubicIn.apply(0.5), 0.125);
+    assert_close(Easing::CubicOut.apply(0.5), 0.875);
+    assert_close(Easing::CubicInOut.apply(0.75), 0.9375);
+    // Elastic out overshoots the end.
+    assert!(Easing::ElasticOut.apply(0.1) > 1.0);
+
+    let track = Track::tween(0.0f32, 8.0, 2.0, Easing::QuadIn);
+    assert_eq!(track.sample(1.0), Some(2.0));
+}
+
+#[test]
+fn test_lerp() {
+    assert_eq!(1.0f32.lerp(&3.0, 0.5), 2.0);
+    assert_eq!(
+        Vec2::new(0.0, 2.0).lerp(&Vec2::new(2.0, 4.0), 0.5),
+        Vec2::new(1.0, 3.0)
+    );
+    assert_eq!(
+        Vec3::zero().lerp(&Vec3::new(4.0, 8.0, -4.0), 0.25),
+        Vec3::new(1.0, 2.0, -1.0)
+    );
+    let color = Srgba::new(0.0, 0.5, 1.0, 1.0).lerp(&Srgba::new(1.0, 0.5, 0.0, 0.0), 0.5);
+    assert_eq!(color, Srgba::new(0.5, 0.5, 0.5, 0.5));
+
+    let color = Color::RED.lerp(&Color::BLUE.with_alpha(0.0), 0.5);
+    assert_eq!(color, Color::rgba(0.5, 0.0, 0.5, 0.5));
+}
+
+#[test]
+fn test_lerp_mat4() {
+    let from = Mat4::identity();
+    let to = Mat4::from_translation(Vec3::new(2.0, 0.0, 0.0))
+        * Rotor3::from_rotation_xy(FRAC_PI_2)
+            .into_matrix()
+            .into_homogeneous()
+        * Mat4::from_scale(3.0);
+    let middle = from.lerp(&to, 0.5);
+
+    let expected = Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0))
+        * Rotor3::from_rotation_xy(FRAC_PI_2 / 2.0)
+            .into_matrix()
+            .into_homogeneous()
+        * Mat4::from_scale(2.0);
+    for (actual, expected) in middle.cols.iter().zip(expected.cols.iter()) {
+        assert_close(actual.x, expected.x);
+        assert_close(actual.y, expected.y);
+        assert_close(actual.z, expected.z);
+        assert_close(actual.w, expected.w);
+    }
+    for (actual, expected) in to.lerp(&from, 0.0).cols.iter().zip(to.cols.iter()) {
+        assert_close((*actual - *expected).mag(), 0.0);
+    }
+}
+
+#[test]
+fn test_animator_once() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut animator = Animator::new();
+    let id = animator.play(track(), Playback::Once);
+    let counter = finished.clone();
+    animator.on_finish(id, move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    assert_eq!(animator.sample::<f32>(id), Some(0.0));
+    assert!(animator.update(HALF).is_empty());
+    assert_eq!(animator.sample::<f32>(id), Some(5.0));
+    assert!(animator.update(HALF).is_empty());
+    assert_eq!(animator.sample::<f32>(id), Some(10.0));
+    animator.update(HALF);
+    animator.update(HALF);
+    assert_eq!(animator.sample::<f32>(id), Some(20.0));
+    animator.update(HALF);
+    assert_eq!(animator.sample::<f32>(id), Some(30.0));
+    assert_eq!(animator.update(HALF), [id]);
+    assert_eq!(animator.sample::<f32>(id), Some(40.0));
+    assert!(animator.is_finished(id));
+
+    // Finished animation holds its last value.
+    assert!(animator.update(HALF).is_empty());
+    assert_eq!(animator.sample::<f32>(id), Some(40.0));
+    assert_eq!(finished.load(Ordering::Relaxed), 1);
+
+    // Wrong type of values.
+    assert_eq!(animator.sample::<Vec2>(id), None);
+    assert!(animator.stop(id));
+    assert_eq!(animator.sample::<f32>(id), None);
+}
+
+#[test]
+fn test_animator_loop_and_ping_pong() {
+    let mut animator = Animator::new();
+    let track = Track::new()
+        .with_keyframe(0.0, 0.0f32, Interpolation::Linear)
+        .with_keyframe(1.0, 1.0, Interpolation::Linear);
+    let looped = animator.play(track.clone(), Playback::Loop);
+    let ping_pong = animator.play(track, Playback::PingPong);
+
+    let mut samples = Vec::new();
+    for _ in 0..8 {
+        assert!(animator.update(QUARTER).is_empty());
+        samples.push((
+            animator.sample::<f32>(looped).unwrap(),
+            animator.sample::<f32>(ping_pong).unwrap(),
+        ));
+    }
+    assert_eq!(
+        samples,
+        [
+            (0.25, 0.25),
+            (0.5, 0.5),
+            (0.75, 0.75),
+            (0.0, 1.0),
+            (0.25, 0.75),
+            (0.5, 0.5),
+            (0.75, 0.25),
+            (0.0, 0.0),
+        ]
+    );
+    assert!(!animator.is_finished(looped));
+    assert!(!animator.is_finished(ping_pong));
+}
+
+#[test]
+fn test_animator_seek() {
+    let mut animator = Animator::new();
+    let id = animator.play(track(), Playback::Once);
+    assert!(animator.set_time(id, 2.5));
+    assert_eq!(animator.sample::<f32>(id), Some(30.0));
+    assert_eq!(animator.update(HALF), [id]);
+
+    // Seeking restarts finished animation.
+    animator.set_time(id, 1.0);
+    assert!(!animator.is_finished(id));
+    assert_eq!(animator.time(id), Some(1.0));
+    assert_eq!(animator.sample::<f32>(id), Some(10.0));
+
+    // Seeking to the end finishes animation on the next update.
+    animator.set_time(id, 3.0);
+    assert_eq!(animator.update(Duration::ZERO), [id]);
+}
+
+#[test]
+fn test_animator_time_scale_and_pause() {
+    let mut animator = Animator::new();
+    let first = animator.play(track(), Playback::Once);
+    let second = animator.play(track(), Playback::Once);
+
+    animator.set_time_scale(2.0);
+    animator.update(QUARTER);
+    assert_eq!(animator.time(first), Some(0.5));
+
+    animator.pause(second);
+    animator.update(QUARTER);
+    assert_eq!(animator.time(first), Some(1.0));
+    assert_eq!(animator.time(second), Some(0.5));
+    animator.resume(second);
+
+    animator.set_paused(true);
+    animator.update(HALF);
+    assert_eq!(animator.time(first), Some(1.0));
+    assert_eq!(animator.time(second), Some(0.5));
+
+    animator.set_paused(false);
+    animator.set_time_scale(0.5);
+    animator.update(HALF);
+    assert_eq!(animator.time(first), Some(1.25));
+    assert_eq!(animator.time(second), Some(0.75));
+}
@@ -0,0 +1,114 @@
+//! Keyframe tracks of animated values.
+
+use super::{Easing, Interpolation, Lerp};
+
+/// Value of the track at given time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe<T> {
+    /// Time of the keyframe (in seconds from the start of the track).
+    pub time: f32,
+    pub value: T,
+    /// Interpolation between this keyframe and the next one.
+    pub interpolation: Interpolation,
+}
+
+/// Sequence of keyframes sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T> Track<T> {
+    /// Creates new track without keyframes.
+    pub fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Creates track which changes value from `from` to `to`
+    /// during `duration` (in seconds) with given easing.
+    pub fn tween(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Self::new()
+            .with_keyframe(0.0, from, Interpolation::Eased(easing))
+            .with_keyframe(duration, to, Interpolation::Step)
+    }
+
+    /// Adds keyframe to the track (see [`Track::push`]).
+    pub fn with_keyframe(mut self, time: f32, value: T, interpolation: Interpolation) -> Self {
+        self.push(time, value, interpolation);
+        self
+    }
+
+    /// Adds keyframe to the track.
+    ///
+    /// Keyframe is placed after all keyframes with the same time,
+    /// so it overrides them from this time on.
+    ///
+    pub fn push(&mut self, time: f32, value: T, interpolation: Interpolation) {
+        let time = time.max(0.0);
+        let index = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let keyframe = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+        self.keyframes.insert(index, keyframe)
+    }
+
+    /// Keyframes of the track sorted by time.
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// Time of the last keyframe (in seconds).
+    pub fn duration(&self) -> f32 {
+        self.keyframes
+            .last()
+            .map(|keyframe| keyframe.time)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+}
+
+impl<T> Track<T>
+where
+    T: Lerp + Clone,
+{
+    /// Samples the value of the track at given time (in seconds).
+    ///
+    /// Exactly at the time of the keyframe its value is returned.
+    /// Before the first keyframe and after the last one the value is clamped.
+    ///
+    /// Returns `None` if the track has no keyframes.
+    ///
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let index = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let (from, to) = match index {
+            0 => return self.keyframes.first().map(|first| first.value.clone()),
+            index if index == self.keyframes.len() => {
+                return self.keyframes.last().map(|last| last.value.clone())
+            }
+            index => (&self.keyframes[index - 1], &self.keyframes[index]),
+        };
+        let t = (time - from.time) / (to.time - from.time);
+        let weight = from.interpolation.weight(t);
+        if weight == 0.0 {
+            return Some(from.value.clone());
+        }
+        Some(from.value.lerp(&to.value, weight))
+    }
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
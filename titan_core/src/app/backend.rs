@@ -0,0 +1,461 @@
+//! Backends of the window which are used by the application.
+
+use std::cell::Cell;
+use std::sync::Arc;
+
+use egui::{ClippedMesh, Texture};
+use winit::error::ExternalError;
+use winit::event_loop::{EventLoop, EventLoopProxy};
+use winit::window::{Window, WindowId};
+
+use crate::{
+    app::startup::Startup,
+    camera::ActiveCamera,
+    config::Config,
+    graphics::{
+        error::{AntialiasingError, RenderError},
+        AaMode, FrameStats, ImageSubresource, PauseControl, PipelineCacheError, ReadbackError,
+        ReadbackImage, ReadbackTicket, Renderer, RendererCreationError, SecondaryWindowRenderError,
+    },
+    window::{self, input::Key, monitor, MonitorInfo, Size},
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Backend of the window which is used by [`Application`](super::Application).
+///
+/// This trait is sealed: it is implemented by [`WinitBackend`] and [`NullWindowBackend`] only.
+pub trait WindowBackend: private::Sealed {
+    /// Identifier of the window.
+    fn window_id(&self) -> WindowId;
+
+    /// Underlying window, if any.
+    fn window(&self) -> Option<&Window>;
+
+    /// Size of the window (in physical pixels).
+    fn inner_size(&self) -> Size;
+
+    /// Scale factor which is used to convert physical pixels of the window into logical ones.
+    fn scale_factor(&self) -> f64;
+
+    fn set_visible(&self, visible: bool);
+
+    /// Requests the window to be redrawn in the current iteration of the main loop.
+    fn request_redraw(&self);
+
+    /// Notifies backend that the window was resized.
+    fn request_resize(&mut self);
+
+    /// Grabs (or releases) and hides (or shows) the cursor.
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError>;
+
+    /// Returns information about all monitors which are available to the window.
+    fn monitors(&self) -> Vec<MonitorInfo>;
+
+    /// Statistics of frames rendered by this backend.
+    fn stats(&self) -> &FrameStats;
+
+    /// Handle which pauses rendering of frames or steps them one at a time.
+    fn pause_control(&self) -> PauseControl;
+
+    /// Renders new frame with given UI.
+    ///
+    /// Frame is not rendered while paused, unless a step was requested
+    /// or the window was resized.
+    ///
+    fn render(&mut self, ui: (Vec<ClippedMesh>, Arc<Texture>)) -> Result<(), RenderError>;
+
+    /// Handle to the camera from which the scene is rendered (see [`Renderer::active_camera`]).
+    fn active_camera(&self) -> ActiveCamera;
+
+    /// Antialiasing mode of the scene.
+    fn antialiasing(&self) -> AaMode;
+
+    /// Changes antialiasing mode of the scene (see [`Renderer::set_antialiasing`]).
+    fn set_antialiasing(&mut self, mode: AaMode) -> Result<(), AntialiasingError>;
+
+    /// Changes scale of the scene resolution (see [`Renderer::set_render_scale`]).
+    fn set_render_scale(&mut self, scale: f32) -> Result<(), AntialiasingError>;
+
+    /// Requests readback of the next rendered frame (see [`Renderer::readback_image`]).
+    fn readback_frame(&mut self) -> Result<ReadbackTicket, ReadbackError>;
+
+    /// Saves pipeline cache so it is used on the next launch
+    /// (see [`Renderer::save_pipeline_cache`]).
+    fn save_pipeline_cache(&self) -> Result<(), PipelineCacheError>;
+
+    /// Finds secondary window by identifier of its underlying window.
+    fn find_window(&self, window_id: WindowId) -> Option<window::WindowId>;
+
+    /// Notifies backend that the secondary window was resized.
+    fn request_window_resize(&mut self, id: window::WindowId);
+
+    /// Presents next frame of each secondary window.
+    fn render_windows(&mut self) -> Result<(), SecondaryWindowRenderError>;
+
+    /// Proxy which wakes up the main loop, if it is driven by the platform.
+    fn event_loop_proxy(&self) -> Option<EventLoopProxy<()>>;
+}
+
+/// Backend which renders into the real window with Vulkan API.
+///
+/// In headless mode (see [`Config::with_headless`]) there is neither a window
+/// nor an event loop: frames are rendered into offscreen images instead.
+pub struct WinitBackend {
+    pub(crate) renderer: Renderer,
+    pub(crate) event_loop: Option<EventLoop<()>>,
+    /// Redraw request of headless mode, in which there is no window to request it from.
+    redraw_requested: Cell<bool>,
+}
+
+impl WinitBackend {
+    pub(crate) fn new(
+        config: &Config,
+        event_loop: EventLoop<()>,
+        startup: &Startup,
+    ) -> Result<Self, RendererCreationError> {
+        let renderer = Renderer::new(config, &event_loop, startup)?;
+        Ok(Self {
+            renderer,
+            event_loop: Some(event_loop),
+            redraw_requested: Cell::new(false),
+        })
+    }
+
+    /// Creates backend which renders into offscreen images without any window.
+    pub(crate) fn headless(
+        config: &Config,
+        startup: &Startup,
+    ) -> Result<Self, RendererCreationError> {
+        let renderer = Renderer::headless(config, startup)?;
+        Ok(Self {
+            renderer,
+            event_loop: None,
+            redraw_requested: Cell::new(false),
+        })
+    }
+
+    /// Returns `true` if redraw was requested since the last call in headless mode.
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.replace(false)
+    }
+}
+
+impl private::Sealed for WinitBackend {}
+
+impl WindowBackend for WinitBackend {
+    fn window_id(&self) -> WindowId {
+        match self.renderer.window() {
+            Some(window) => window.id(),
+            // Safety: identifier is never compared with identifiers of real windows.
+            None => unsafe { WindowId::dummy() },
+        }
+    }
+
+    fn window(&self) -> Option<&Window> {
+        self.renderer.window()
+    }
+
+    fn inner_size(&self) -> Size {
+        match self.renderer.window() {
+            Some(window) => {
+                let size: (u32, u32) = window.inner_size().into();
+                size.into()
+            }
+            None => self.renderer.frame_size(),
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.renderer
+            .window()
+            .map_or(1.0, |window| window.scale_factor())
+    }
+
+    fn set_visible(&self, visible: bool) {
+        if let Some(window) = self.renderer.window() {
+            window.set_visible(visible)
+        }
+    }
+
+    fn request_redraw(&self) {
+        match self.renderer.window() {
+            Some(window) => window.request_redraw(),
+            None => self.redraw_requested.set(true),
+        }
+    }
+
+    fn request_resize(&mut self) {
+        self.renderer.request_resize()
+    }
+
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        if let Some(window) = self.renderer.window() {
+            window.set_cursor_grab(grab)?;
+            window.set_cursor_visible(!grab);
+        }
+        Ok(())
+    }
+
+    fn monitors(&self) -> Vec<MonitorInfo> {
+        self.renderer
+            .window()
+            .map(monitor::monitors)
+            .unwrap_or_default()
+    }
+
+    fn stats(&self) -> &FrameStats {
+        self.renderer.stats()
+    }
+
+    fn pause_control(&self) -> PauseControl {
+        self.renderer.pause_control()
+    }
+
+    fn render(&mut self, ui: (Vec<ClippedMesh>, Arc<Texture>)) -> Result<(), RenderError> {
+        self.renderer.render(Some(ui))
+    }
+
+    fn active_camera(&self) -> ActiveCamera {
+        self.renderer.active_camera()
+    }
+
+    fn antialiasing(&self) -> AaMode {
+        self.renderer.antialiasing()
+    }
+
+    fn set_antialiasing(&mut self, mode: AaMode) -> Result<(), AntialiasingError> {
+        self.renderer.set_antialiasing(mode)
+    }
+
+    fn set_render_scale(&mut self, scale: f32) -> Result<(), AntialiasingError> {
+        self.renderer.set_render_scale(scale)
+    }
+
+    fn readback_frame(&mut self) -> Result<ReadbackTicket, ReadbackError> {
+        let subresource = ImageSubresource::default();
+        self.renderer
+            .readback_image(ReadbackImage::Frame, subresource)
+    }
+
+    fn save_pipeline_cache(&self) -> Result<(), PipelineCacheError> {
+        self.renderer.save_pipeline_cache()
+    }
+
+    fn find_window(&self, window_id: WindowId) -> Option<window::WindowId> {
+        self.renderer.find_window(window_id)
+    }
+
+    fn request_window_resize(&mut self, id: window::WindowId) {
+        self.renderer.request_window_resize(id)
+    }
+
+    fn render_windows(&mut self) -> Result<(), SecondaryWindowRenderError> {
+        self.renderer.render_windows()
+    }
+
+    fn event_loop_proxy(&self) -> Option<EventLoopProxy<()>> {
+        self.event_loop.as_ref().map(EventLoop::create_proxy)
+    }
+}
+
+/// Event which is generated by [`NullWindowBackend`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScriptedEvent {
+    /// One iteration of the main loop in which a frame is drawn.
+    Frame,
+    /// The window was resized.
+    Resize(Size),
+    /// Keyboard key was pressed and released.
+    Key(Key),
+    /// The window was closed by the user.
+    Close,
+}
+
+/// Sequence of events which are generated by [`NullWindowBackend`]
+/// after the window was created.
+///
+/// Main loop is exited after the last event.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScriptedEvents {
+    events: Vec<ScriptedEvent>,
+}
+
+impl ScriptedEvents {
+    /// Creates an empty sequence of events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same sequence with given count of frames in the end.
+    pub fn frames(mut self, count: u32) -> Self {
+        let frames = (0..count).map(|_| ScriptedEvent::Frame);
+        self.events.extend(frames);
+        self
+    }
+
+    /// Same sequence with resizing of the window in the end.
+    pub fn resize(mut self, size: Size) -> Self {
+        self.events.push(ScriptedEvent::Resize(size));
+        self
+    }
+
+    /// Same sequence with press and release of the key in the end.
+    pub fn key(mut self, key: Key) -> Self {
+        self.events.push(ScriptedEvent::Key(key));
+        self
+    }
+
+    /// Same sequence with closing of the window in the end.
+    pub fn close(mut self) -> Self {
+        self.events.push(ScriptedEvent::Close);
+        self
+    }
+
+    /// Events of this sequence in order.
+    pub fn events(&self) -> &[ScriptedEvent] {
+        &self.events
+    }
+}
+
+/// Size of the window of [`NullWindowBackend`].
+pub const NULL_WINDOW_SIZE: Size = Size::new(800, 600);
+
+/// Backend which has neither a real window nor a device.
+///
+/// It generates scripted events instead of the platform, and frames are only counted,
+/// so the main loop of the application could be run without display or GPU (for example, in tests).
+pub struct NullWindowBackend {
+    size: Size,
+    redraw_requested: Cell<bool>,
+    resize_requested: bool,
+    stats: FrameStats,
+    pause: PauseControl,
+    antialiasing: AaMode,
+    camera: ActiveCamera,
+    pub(crate) script: ScriptedEvents,
+}
+
+impl NullWindowBackend {
+    pub(crate) fn new(script: ScriptedEvents) -> Self {
+        Self {
+            size: NULL_WINDOW_SIZE,
+            redraw_requested: Cell::new(false),
+            resize_requested: false,
+            stats: FrameStats::default(),
+            pause: PauseControl::default(),
+            antialiasing: AaMode::Off,
+            camera: ActiveCamera::default(),
+            script,
+        }
+    }
+
+    pub(crate) fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    /// Returns `true` if redraw was requested since the last call.
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.replace(false)
+    }
+}
+
+impl private::Sealed for NullWindowBackend {}
+
+impl WindowBackend for NullWindowBackend {
+    fn window_id(&self) -> WindowId {
+        // Safety: identifier is never compared with identifiers of real windows.
+        unsafe { WindowId::dummy() }
+    }
+
+    fn window(&self) -> Option<&Window> {
+        None
+    }
+
+    fn inner_size(&self) -> Size {
+        self.size
+    }
+
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
+
+    fn set_visible(&self, _visible: bool) {}
+
+    fn request_redraw(&self) {
+        self.redraw_requested.set(true)
+    }
+
+    fn request_resize(&mut self) {
+        self.resize_requested = true;
+    }
+
+    fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    fn monitors(&self) -> Vec<MonitorInfo> {
+        Vec::new()
+    }
+
+    fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    fn pause_control(&self) -> PauseControl {
+        self.pause.clone()
+    }
+
+    fn render(&mut self, _ui: (Vec<ClippedMesh>, Arc<Texture>)) -> Result<(), RenderError> {
+        let resized = std::mem::take(&mut self.resize_requested);
+        if self.pause.should_render(resized) {
+            self.pause.frame_rendered();
+            self.stats.frames += 1;
+        }
+        Ok(())
+    }
+
+    fn active_camera(&self) -> ActiveCamera {
+        self.camera.clone()
+    }
+
+    fn antialiasing(&self) -> AaMode {
+        self.antialiasing
+    }
+
+    fn set_antialiasing(&mut self, mode: AaMode) -> Result<(), AntialiasingError> {
+        self.antialiasing = mode.normalized();
+        Ok(())
+    }
+
+    fn set_render_scale(&mut self, _scale: f32) -> Result<(), AntialiasingError> {
+        Ok(())
+    }
+
+    fn readback_frame(&mut self) -> Result<ReadbackTicket, ReadbackError> {
+        // There are no frame images without a device.
+        Err(ReadbackError::UnsupportedImage)
+    }
+
+    fn save_pipeline_cache(&self) -> Result<(), PipelineCacheError> {
+        // There are no pipelines without a device.
+        Ok(())
+    }
+
+    fn find_window(&self, _window_id: WindowId) -> Option<window::WindowId> {
+        // Secondary windows have no underlying windows which could send events.
+        None
+    }
+
+    fn request_window_resize(&mut self, _id: window::WindowId) {}
+
+    fn render_windows(&mut self) -> Result<(), SecondaryWindowRenderError> {
+        Ok(())
+    }
+
+    fn event_loop_proxy(&self) -> Option<EventLoopProxy<()>> {
+        None
+    }
+}
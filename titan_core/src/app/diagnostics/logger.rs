@@ -0,0 +1,45 @@
+//! Logger which sends log records to diagnostics clients.
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Logger which passes log records to the inner logger
+/// and also queues them for clients of running diagnostics servers.
+///
+/// Records are queued without blocking on the network,
+/// so the logger could be used from any thread.
+///
+pub struct DiagnosticsLogger {
+    inner: Box<dyn Log>,
+}
+
+impl DiagnosticsLogger {
+    /// Creates logger which wraps given logger.
+    pub fn new(inner: impl Log + 'static) -> Self {
+        let inner = Box::new(inner);
+        Self { inner }
+    }
+
+    /// Installs logger which wraps given logger as the global logger.
+    pub fn init(inner: impl Log + 'static, level: LevelFilter) -> Result<(), SetLoggerError> {
+        // Logger lives until the end of the program anyway.
+        let logger = Box::leak(Box::new(Self::new(inner)));
+        log::set_logger(logger)?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for DiagnosticsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.log(record);
+        super::publish_log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
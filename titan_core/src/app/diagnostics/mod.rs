@@ -0,0 +1,716 @@
+//! Opt-in server which streams diagnostics of the application over the network.
+//!
+//! When enabled by [`Config::with_diagnostics_port`](crate::config::Config::with_diagnostics_port),
+//! the application listens on the TCP port (of the loopback interface by default)
+//! and streams newline-delimited JSON messages to all connected clients:
+//!
+//! - `log`: log records, if [`DiagnosticsLogger`] is installed as the global logger;
+//! - `stats`: [`FrameStats`] after each rendered frame;
+//! - `resources`: counts of live graphics objects (and entities of the world) once per second;
+//! - `reply`: results of commands;
+//! - `screenshot`: PNG image of the frame encoded with base64.
+//!
+//! Clients send [commands](DiagnosticsCommand) back as lines of text.
+//! Plain TCP is used (not WebSocket), so the server could be reached with `nc`
+//! or through `adb forward` from the development machine.
+//!
+//! Messages are queued by the main loop without blocking: when the queue is full,
+//! the oldest message is dropped and counted. Sockets are served by one of threads
+//! for blocking tasks of the task pool.
+
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use image::{DynamicImage, ImageOutputFormat};
+use thiserror::Error;
+
+use crate::{
+    graphics::{
+        self, capture::json::Json, FrameStats, ImageReadbackInfo, ReadbackTicket, ReadbackWaitError,
+    },
+    task::TaskPool,
+};
+
+pub use logger::DiagnosticsLogger;
+
+mod logger;
+mod tests;
+
+/// Max count of messages which wait to be sent; the oldest ones are dropped after it.
+pub const DIAGNOSTICS_QUEUE_CAPACITY: usize = 1024;
+
+/// Interval between iterations of the server which accept clients and send messages.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Max size of unsent data of one client: slower clients are disconnected.
+const CLIENT_BUFFER_LIMIT: usize = 16 << 20;
+
+/// Max length of the command line (in bytes).
+const MAX_COMMAND_LEN: usize = 1024;
+
+/// Max duration of sending queued messages when the server is shut down.
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Interval between messages with counts of live resources.
+const RESOURCES_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Servers which receive log records from [`DiagnosticsLogger`].
+static LOG_SINKS: Mutex<Vec<Weak<Shared>>> = Mutex::new(Vec::new());
+
+/// Command which is sent by the client of the diagnostics server.
+///
+/// Commands are lines of text: `pause`, `resume`, `step`, `screenshot`
+/// and `set_render_scale <scale>`. They are applied by the main loop
+/// through the same handles as keys and overlays.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiagnosticsCommand {
+    /// Pauses rendering of frames (see [`PauseControl`](crate::graphics::PauseControl)).
+    Pause,
+    /// Resumes rendering of frames.
+    Resume,
+    /// Renders one frame while paused.
+    Step,
+    /// Reads back the next rendered frame and sends it as PNG image.
+    Screenshot,
+    /// Changes scale of the scene resolution relative to the window.
+    SetRenderScale(f32),
+}
+
+impl DiagnosticsCommand {
+    /// Name of the command as it is sent by the client.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Step => "step",
+            Self::Screenshot => "screenshot",
+            Self::SetRenderScale(_) => "set_render_scale",
+        }
+    }
+}
+
+impl FromStr for DiagnosticsCommand {
+    type Err = CommandParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or(CommandParseError::Empty)?;
+        let argument = words.next();
+        let command = match (name, argument) {
+            ("pause", None) => Self::Pause,
+            ("resume", None) => Self::Resume,
+            ("step", None) => Self::Step,
+            ("screenshot", None) => Self::Screenshot,
+            ("set_render_scale", Some(argument)) => {
+                let scale = argument
+                    .parse()
+                    .ok()
+                    .filter(|scale: &f32| scale.is_finite());
+                let scale = scale.ok_or_else(|| CommandParseError::InvalidArgument {
+                    command: "set_render_scale",
+                    argument: argument.to_string(),
+                })?;
+                Self::SetRenderScale(scale)
+            }
+            ("set_render_scale", None) => {
+                return Err(CommandParseError::MissingArgument("set_render_scale"))
+            }
+            ("pause" | "resume" | "step" | "screenshot", Some(_)) => {
+                return Err(CommandParseError::UnexpectedArgument(name.to_string()))
+            }
+            _ => return Err(CommandParseError::Unknown(name.to_string())),
+        };
+        if words.next().is_some() {
+            return Err(CommandParseError::UnexpectedArgument(name.to_string()));
+        }
+        Ok(command)
+    }
+}
+
+/// Error that can happen when command of the client is parsed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CommandParseError {
+    #[error("command is empty")]
+    Empty,
+
+    #[error("unknown command `{0}`")]
+    Unknown(String),
+
+    #[error("command `{0}` requires an argument")]
+    MissingArgument(&'static str),
+
+    #[error("command `{0}` has too many arguments")]
+    UnexpectedArgument(String),
+
+    #[error("invalid argument `{argument}` of command `{command}`")]
+    InvalidArgument {
+        command: &'static str,
+        argument: String,
+    },
+}
+
+/// Counters of the diagnostics server.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DiagnosticsStats {
+    /// Count of clients which are connected now.
+    pub clients: usize,
+    /// Count of messages which were taken from the queue to be sent.
+    pub sent: u64,
+    /// Count of messages which were dropped because the queue was full.
+    pub dropped: u64,
+    /// Count of valid commands which were received from clients.
+    pub commands: u64,
+}
+
+/// Bounded queue of messages which drops the oldest message when it is full.
+struct Outbox {
+    messages: Mutex<VecDeque<String>>,
+    capacity: usize,
+    dropped: AtomicU64,
+}
+
+impl Outbox {
+    fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, message: String) {
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= self.capacity {
+            messages.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        messages.push_back(message);
+    }
+
+    fn drain(&self) -> Vec<String> {
+        self.messages.lock().unwrap().drain(..).collect()
+    }
+
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// State which is shared by the server thread, the main loop and the logger.
+struct Shared {
+    outbox: Outbox,
+    commands: Mutex<Vec<DiagnosticsCommand>>,
+    shutdown: AtomicBool,
+    clients: AtomicUsize,
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl Shared {
+    fn new(capacity: usize) -> Self {
+        Self {
+            outbox: Outbox::new(capacity),
+            commands: Mutex::new(Vec::new()),
+            shutdown: AtomicBool::new(false),
+            clients: AtomicUsize::new(0),
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Handle to the diagnostics server of the application.
+///
+/// Clones share one outbox, so messages sent through any of them reach all clients in order.
+#[derive(Clone)]
+pub struct DiagnosticsHandle {
+    shared: Arc<Shared>,
+    address: SocketAddr,
+}
+
+impl DiagnosticsHandle {
+    /// Address which the server listens on
+    /// (with the actual port, if port 0 was requested).
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Current counters of the server.
+    pub fn stats(&self) -> DiagnosticsStats {
+        let shared = &self.shared;
+        DiagnosticsStats {
+            clients: shared.clients.load(Ordering::Relaxed),
+            sent: shared.sent.load(Ordering::Relaxed),
+            dropped: shared.outbox.dropped(),
+            commands: shared.received.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Queues custom message of given type with given fields for all clients.
+    pub fn send<'a>(&self, kind: &str, fields: impl IntoIterator<Item = (&'a str, String)>) {
+        let fields = fields.into_iter().map(|(key, value)| (key, value.into()));
+        self.publish(self::message(kind, fields));
+    }
+
+    pub(crate) fn publish(&self, message: Json) {
+        self.shared.outbox.push(message.to_compact_string());
+    }
+}
+
+/// Server which is owned by the application and shut down with it.
+pub(crate) struct DiagnosticsServer {
+    handle: DiagnosticsHandle,
+    tasks: TaskPool,
+    screenshots: Vec<ReadbackTicket>,
+    resources_published_at: Option<Instant>,
+}
+
+impl DiagnosticsServer {
+    /// Binds the listener and starts serving clients on the thread of the pool.
+    pub fn start(address: SocketAddr, tasks: &TaskPool) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let shared = Arc::new(Shared::new(DIAGNOSTICS_QUEUE_CAPACITY));
+        {
+            let mut sinks = LOG_SINKS.lock().unwrap();
+            sinks.retain(|sink| sink.strong_count() > 0);
+            sinks.push(Arc::downgrade(&shared));
+        }
+        let server = shared.clone();
+        tasks.spawn_blocking(move || self::serve(listener, server));
+        log::info!("diagnostics server listens on {}", address);
+        let handle = DiagnosticsHandle { shared, address };
+        Ok(Self {
+            handle,
+            tasks: tasks.clone(),
+            screenshots: Vec::new(),
+            resources_published_at: None,
+        })
+    }
+
+    pub fn handle(&self) -> &DiagnosticsHandle {
+        &self.handle
+    }
+
+    /// Takes commands which were received since the last call.
+    pub fn take_commands(&self) -> Vec<DiagnosticsCommand> {
+        std::mem::take(&mut *self.handle.shared.commands.lock().unwrap())
+    }
+
+    /// Queues messages of the rendered frame: its statistics, counts of resources
+    /// (if the interval has elapsed) and screenshots which were read back.
+    ///
+    /// Count of entities is requested only when resources are published.
+    ///
+    pub fn publish_frame(&mut self, stats: &FrameStats, entities: impl FnOnce() -> Option<usize>) {
+        let dropped = self.handle.shared.outbox.dropped();
+        self.handle.publish(self::stats_message(stats, dropped));
+
+        let now = Instant::now();
+        let due = match self.resources_published_at {
+            Some(published_at) => now.duration_since(published_at) >= RESOURCES_INTERVAL,
+            None => true,
+        };
+        if due {
+            self.resources_published_at = Some(now);
+            self.handle
+                .publish(self::resources_message(stats, entities()));
+        }
+
+        let frame = stats.frames;
+        let handle = &self.handle;
+        let tasks = &self.tasks;
+        self.screenshots.retain(|ticket| {
+            let data = match ticket.wait(Duration::ZERO) {
+                Ok(data) => data,
+                Err(ReadbackWaitError::NotSubmitted | ReadbackWaitError::Timeout) => return true,
+                Err(error) => {
+                    let message = self::message("error", [("message", error.to_string().into())]);
+                    handle.publish(message);
+                    return false;
+                }
+            };
+            let info = ticket.image_info();
+            let handle = handle.clone();
+            // PNG encoding takes a while, so it does not stall the main loop.
+            tasks.spawn(move || {
+                let message = info
+                    .ok_or_else(|| "frame image was not read back".to_string())
+                    .and_then(|info| self::screenshot_message(frame, info, data))
+                    .unwrap_or_else(|error| self::message("error", [("message", error.into())]));
+                handle.publish(message);
+            });
+            false
+        });
+    }
+
+    /// Waits for readback of the next rendered frame to send it to clients.
+    pub fn add_screenshot(&mut self, ticket: ReadbackTicket) {
+        self.screenshots.push(ticket);
+    }
+
+    /// Queues reply to the command which was applied by the main loop.
+    pub fn reply(&self, command: DiagnosticsCommand, result: Result<(), String>) {
+        self.handle
+            .publish(self::reply_message(command.name(), result));
+    }
+
+    /// Requests the server to send queued messages and close all connections.
+    ///
+    /// Server thread finishes soon after it, so the pool could wait for it.
+    ///
+    pub fn shutdown(&self) {
+        self.handle.shared.shutdown.store(true, Ordering::Release);
+    }
+}
+
+impl Drop for DiagnosticsServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Queues log record for all running servers.
+fn publish_log(record: &log::Record) {
+    let sinks: Vec<_> = {
+        let sinks = LOG_SINKS.lock().unwrap();
+        sinks.iter().filter_map(Weak::upgrade).collect()
+    };
+    if sinks.is_empty() {
+        return;
+    }
+    let message = self::message(
+        "log",
+        [
+            ("level", record.level().as_str().into()),
+            ("target", record.target().into()),
+            ("message", record.args().to_string().into()),
+        ],
+    )
+    .to_compact_string();
+    for sink in sinks {
+        sink.outbox.push(message.clone());
+    }
+}
+
+/// Creates message of given type with given fields.
+pub(crate) fn message<'a>(kind: &str, fields: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+    let type_field = std::iter::once(("type", kind.into()));
+    Json::object(type_field.chain(fields))
+}
+
+fn reply_message(command: &str, result: Result<(), String>) -> Json {
+    let mut fields = vec![("command", command.into()), ("ok", result.is_ok().into())];
+    if let Err(error) = result {
+        fields.push(("error", error.into()));
+    }
+    self::message("reply", fields)
+}
+
+/// Creates message with statistics of the frame
+/// and count of diagnostics messages which were dropped so far.
+fn stats_message(stats: &FrameStats, dropped: u64) -> Json {
+    let culling = stats.culling;
+    let streaming = stats.streaming;
+    let transient = stats.transient;
+    let descriptors = stats.descriptors;
+    let pipeline_stats = stats.pipeline_stats.map(|pipeline_stats| {
+        Json::object([
+            ("vertices", pipeline_stats.input_assembly_vertices.into()),
+            (
+                "primitives",
+                pipeline_stats.input_assembly_primitives.into(),
+            ),
+            (
+                "vertex_invocations",
+                pipeline_stats.vertex_shader_invocations.into(),
+            ),
+            (
+                "clipped_primitives",
+                pipeline_stats.clipping_primitives.into(),
+            ),
+            (
+                "fragment_invocations",
+                pipeline_stats.fragment_shader_invocations.into(),
+            ),
+        ])
+    });
+    self::message(
+        "stats",
+        [
+            ("frames", stats.frames.into()),
+            (
+                "frame_time_ms",
+                (stats.frame_time.as_secs_f64() * 1000.0).into(),
+            ),
+            ("frames_skipped", stats.frames_skipped.into()),
+            ("swapchain_recreations", stats.swapchain_recreations.into()),
+            (
+                "viewport_draws",
+                Json::array(stats.viewport_draws.iter().copied()),
+            ),
+            (
+                "culling",
+                Json::object([
+                    ("submitted", culling.submitted.into()),
+                    ("culled", culling.culled.into()),
+                    ("hidden", culling.hidden.into()),
+                ]),
+            ),
+            (
+                "streaming",
+                Json::object([
+                    ("uploaded_bytes", streaming.uploaded_bytes.into()),
+                    ("evicted_bytes", streaming.evicted_bytes.into()),
+                    ("queue_depth", streaming.queue_depth.into()),
+                    ("resident_bytes", streaming.resident_bytes.into()),
+                ]),
+            ),
+            (
+                "transient",
+                Json::object([
+                    ("bytes", transient.bytes.into()),
+                    ("capacity", transient.capacity.into()),
+                    ("blocks", transient.blocks.into()),
+                ]),
+            ),
+            (
+                "descriptors",
+                Json::object([
+                    ("sets_allocated", descriptors.sets_allocated.into()),
+                    ("pools", descriptors.pools.into()),
+                    ("writes", descriptors.writes.into()),
+                ]),
+            ),
+            ("pipeline_stats", pipeline_stats.into()),
+            ("dropped_messages", dropped.into()),
+        ],
+    )
+}
+
+/// Creates message with counts of live graphics objects per type
+/// and count of entities of the last world snapshot, if any.
+pub(crate) fn resources_message(stats: &FrameStats, entities: Option<usize>) -> Json {
+    let resources = stats
+        .resources
+        .iter()
+        .map(|resources| (resources.name, resources.keys.len().into()));
+    self::message(
+        "resources",
+        [
+            ("resources", Json::object(resources)),
+            ("entities", entities.into()),
+        ],
+    )
+}
+
+/// Creates message with PNG image of the frame which was read back.
+fn screenshot_message(frame: u64, info: ImageReadbackInfo, data: Vec<u8>) -> Result<Json, String> {
+    let image = graphics::rgba_image(info, data).map_err(|error| error.to_string())?;
+    let (width, height) = image.dimensions();
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(image)
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|error| error.to_string())?;
+    let message = self::message(
+        "screenshot",
+        [
+            ("frame", frame.into()),
+            ("width", width.into()),
+            ("height", height.into()),
+            ("png", self::base64(&png).into()),
+        ],
+    );
+    Ok(message)
+}
+
+/// Encodes bytes with standard base64 alphabet and padding.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (index, &byte)| {
+            word | (byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (word >> (18 - 6 * index)) & 0x3f;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Connection of one client.
+struct Client {
+    stream: TcpStream,
+    peer: SocketAddr,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    closed: bool,
+}
+
+impl Client {
+    fn new(stream: TcpStream, peer: SocketAddr) -> Self {
+        Self {
+            stream,
+            peer,
+            input: Vec::new(),
+            output: Vec::new(),
+            closed: false,
+        }
+    }
+
+    fn queue(&mut self, message: &str) {
+        self.output.extend_from_slice(message.as_bytes());
+        self.output.push(b'\n');
+    }
+
+    /// Reads available data, returning complete lines.
+    fn read_lines(&mut self) -> Vec<String> {
+        let mut buffer = [0; 1024];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(len) => self.input.extend_from_slice(&buffer[..len]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        while let Some(end) = self.input.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.input.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+        if self.input.len() > MAX_COMMAND_LEN {
+            self.input.clear();
+            let error = format!("command is longer than {} bytes", MAX_COMMAND_LEN);
+            let message = self::message("error", [("message", error.into())]);
+            self.queue(&message.to_compact_string());
+        }
+        lines
+    }
+
+    /// Writes as much of queued data as the socket accepts without blocking.
+    fn write(&mut self) {
+        while !self.output.is_empty() && !self.closed {
+            match self.stream.write(&self.output) {
+                Ok(0) => self.closed = true,
+                Ok(len) => {
+                    self.output.drain(..len);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+}
+
+/// Serves clients until the server is shut down.
+fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    let mut clients: Vec<Client> = Vec::new();
+    loop {
+        // Messages which were queued before shutdown are still sent.
+        let shutdown = shared.shutdown.load(Ordering::Acquire);
+        loop {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    if let Err(error) = stream.set_nonblocking(true) {
+                        log::warn!("failed to set up diagnostics client {}: {}", peer, error);
+                        continue;
+                    }
+                    log::info!("diagnostics client {} connected", peer);
+                    clients.push(Client::new(stream, peer));
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::warn!("failed to accept diagnostics client: {}", error);
+                    break;
+                }
+            }
+        }
+
+        let messages = shared.outbox.drain();
+        let count = messages.len() as u64;
+        for client in &mut clients {
+            for message in &messages {
+                client.queue(message);
+            }
+            for line in client.read_lines() {
+                match line.parse() {
+                    Ok(command) => {
+                        shared.commands.lock().unwrap().push(command);
+                        shared.received.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(error) => {
+                        let error: CommandParseError = error;
+                        let message = self::reply_message(&line, Err(error.to_string()));
+                        client.queue(&message.to_compact_string());
+                    }
+                }
+            }
+            client.write();
+            if client.output.len() > CLIENT_BUFFER_LIMIT {
+                client.closed = true;
+            }
+        }
+        shared.sent.fetch_add(count, Ordering::Relaxed);
+        let before = clients.len();
+        let disconnected: Vec<_> = clients
+            .iter()
+            .filter(|client| client.closed)
+            .map(|client| client.peer)
+            .collect();
+        clients.retain(|client| !client.closed);
+        shared.clients.store(clients.len(), Ordering::Relaxed);
+        if before != clients.len() {
+            log::info!("diagnostics clients {:?} disconnected", disconnected);
+        }
+
+        if shutdown {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    // Data which was queued is sent until the deadline, then connections are closed.
+    let deadline = Instant::now() + FLUSH_TIMEOUT;
+    while clients
+        .iter()
+        .any(|client| !client.output.is_empty() && !client.closed)
+        && Instant::now() < deadline
+    {
+        clients.iter_mut().for_each(Client::write);
+        thread::sleep(Duration::from_millis(1));
+    }
+    for client in &clients {
+        let _ = client.stream.shutdown(Shutdown::Both);
+    }
+    shared.clients.store(0, Ordering::Relaxed);
+    log::info!("diagnostics server was shut down");
+}
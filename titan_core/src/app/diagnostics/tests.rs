@@ -0,0 +1,129 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_parse_commands() {
+    let cases = [
+        ("pause", DiagnosticsCommand::Pause),
+        (" resume ", DiagnosticsCommand::Resume),
+        ("step", DiagnosticsCommand::Step),
+        ("screenshot", DiagnosticsCommand::Screenshot),
+        (
+            "set_render_scale 0.5",
+            DiagnosticsCommand::SetRenderScale(0.5),
+        ),
+    ];
+    for (line, expected) in cases {
+        assert_eq!(line.parse(), Ok(expected));
+    }
+
+    let errors = [
+        ("", CommandParseError::Empty),
+        ("jump", CommandParseError::Unknown("jump".to_string())),
+        (
+            "set_render_scale",
+            CommandParseError::MissingArgument("set_render_scale"),
+        ),
+        (
+            "set_render_scale 0.5 1",
+            CommandParseError::UnexpectedArgument("set_render_scale".to_string()),
+        ),
+        (
+            "set_render_scale NaN",
+            CommandParseError::InvalidArgument {
+                command: "set_render_scale",
+                argument: "NaN".to_string(),
+            },
+        ),
+        (
+            "pause now",
+            CommandParseError::UnexpectedArgument("pause".to_string()),
+        ),
+    ];
+    for (line, expected) in errors {
+        assert_eq!(line.parse::<DiagnosticsCommand>(), Err(expected));
+    }
+}
+
+#[test]
+fn test_outbox_drops_oldest() {
+    let outbox = Outbox::new(2);
+    for message in ["first", "second", "third"] {
+        outbox.push(message.to_string());
+    }
+    assert_eq!(outbox.dropped(), 1);
+    assert_eq!(outbox.drain(), ["second", "third"]);
+    assert!(outbox.drain().is_empty());
+}
+
+#[test]
+fn test_messages() {
+    let reply = reply_message("set_render_scale", Err("out of range".to_string()));
+    assert_eq!(
+        reply.to_compact_string(),
+        r#"{"type":"reply","command":"set_render_scale","ok":false,"error":"out of range"}"#,
+    );
+
+    let stats = FrameStats {
+        frames: 3,
+        ..Default::default()
+    };
+    let message = stats_message(&stats, 2).to_compact_string();
+    assert!(message.starts_with(r#"{"type":"stats","frames":3,"#));
+    assert!(message.ends_with(r#""pipeline_stats":null,"dropped_messages":2}"#));
+    assert!(!message.contains('\n'));
+}
+
+#[test]
+fn test_base64() {
+    let cases = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foobar", "Zm9vYmFy"),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(base64(bytes.as_bytes()), expected);
+    }
+}
+
+#[test]
+fn test_server() {
+    use std::io::{BufRead, BufReader};
+
+    let tasks = TaskPool::new(1, 1);
+    let address = (std::net::Ipv4Addr::LOCALHOST, 0).into();
+    let server = DiagnosticsServer::start(address, &tasks).unwrap();
+    let handle = server.handle().clone();
+    assert!(handle.address().ip().is_loopback());
+
+    let mut stream = TcpStream::connect(handle.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream.write_all(b"step\njump\n").unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while handle.stats().clients == 0 && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    handle.send("custom", [("answer", "42".to_string())]);
+
+    // Invalid commands are replied to the client which sent them.
+    let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        received.push(lines.next().unwrap().unwrap());
+    }
+    received.sort();
+    assert_eq!(received[0], r#"{"type":"custom","answer":"42"}"#);
+    assert!(received[1].starts_with(r#"{"type":"reply","command":"jump","ok":false"#));
+    assert_eq!(server.take_commands(), [DiagnosticsCommand::Step]);
+    assert_eq!(handle.stats().commands, 1);
+
+    // Connections are closed on shutdown.
+    server.shutdown();
+    assert!(lines.next().is_none());
+    assert!(tasks.shutdown(Duration::from_secs(1)).is_empty());
+}
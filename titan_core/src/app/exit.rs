@@ -0,0 +1,67 @@
+//! Utilities for explicit shutdown of game engine.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::graphics::{error::RenderError, ResourceList};
+
+/// Reason why main loop of the application was exited.
+#[derive(Debug)]
+pub enum ExitCause {
+    /// Window of the application was closed by the user.
+    WindowClosed,
+    /// Exit was requested by [`ExitHandle::exit`].
+    Requested,
+    /// Rendering error occurred.
+    Error(RenderError),
+}
+
+/// Report of the application which is returned after its main loop was exited.
+#[derive(Debug)]
+pub struct ExitReport {
+    /// Reason why main loop was exited.
+    pub cause: ExitCause,
+    /// Graphics objects of each type which were still alive at teardown
+    /// (were not released by the user).
+    pub leaks: Vec<ResourceList>,
+}
+
+impl ExitReport {
+    /// Returns `true` if no graphics objects were alive at teardown.
+    pub fn is_clean(&self) -> bool {
+        self.leaks.iter().all(|leaks| leaks.keys.is_empty())
+    }
+
+    /// Logs objects which were alive at teardown.
+    pub(crate) fn log_leaks(&self) {
+        for leaks in self.leaks.iter().filter(|leaks| !leaks.keys.is_empty()) {
+            log::warn!(
+                "{} {} object(s) leaked at exit: {:?}",
+                leaks.keys.len(),
+                leaks.name,
+                leaks.keys,
+            );
+        }
+    }
+}
+
+/// Handle which requests main loop of the application to exit.
+///
+/// Clones share one request flag. Handle is not `Send`,
+/// so it is meant for the callback which runs on the main thread.
+#[derive(Debug, Default, Clone)]
+pub struct ExitHandle {
+    requested: Rc<Cell<bool>>,
+}
+
+impl ExitHandle {
+    /// Requests main loop to exit after current iteration.
+    pub fn exit(&self) {
+        self.requested.set(true)
+    }
+
+    /// Returns `true` if exit was requested.
+    pub fn requested(&self) -> bool {
+        self.requested.get()
+    }
+}
@@ -0,0 +1,152 @@
+//! Inspector of the world content which is shown by the resource inspector overlay.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use titan_ecs::{Entity, World, WorldSnapshot};
+
+use crate::graphics::Visibility;
+
+/// Default interval between snapshots of the world.
+pub const DEFAULT_INSPECTOR_INTERVAL: Duration = Duration::from_millis(500);
+
+struct InspectorState {
+    interval: Duration,
+    /// Whether the overlay shows the snapshot since the last update.
+    requested: bool,
+    refreshed_at: Option<Instant>,
+    snapshot: Option<WorldSnapshot>,
+    /// Entities which were hidden when the snapshot was captured.
+    hidden: HashSet<Entity>,
+    /// Visibility which was toggled in the overlay, but not applied to the world yet.
+    toggles: Vec<(Entity, bool)>,
+}
+
+/// Visibility of entities which is shown and toggled by the overlay.
+pub(crate) struct VisibilityView<'a> {
+    hidden: &'a HashSet<Entity>,
+    toggles: &'a mut Vec<(Entity, bool)>,
+}
+
+impl VisibilityView<'_> {
+    /// Visibility of the entity, including toggles which were not applied yet.
+    pub fn is_visible(&self, entity: Entity) -> bool {
+        let toggled = self
+            .toggles
+            .iter()
+            .rev()
+            .find(|(toggled, _)| *toggled == entity);
+        match toggled {
+            Some(&(_, visible)) => visible,
+            None => !self.hidden.contains(&entity),
+        }
+    }
+
+    /// Requests visibility of the entity to be changed by [`WorldInspector::apply_visibility`].
+    pub fn set_visible(&mut self, entity: Entity, visible: bool) {
+        self.toggles.push((entity, visible));
+    }
+}
+
+/// Handle which captures snapshots of the world for [resource inspector](super::RESOURCES_OVERLAY).
+///
+/// World is owned by the user, so [`WorldInspector::update`] should be called on each update.
+/// Snapshot is captured only while the overlay shows it, no more often than the interval.
+///
+#[derive(Clone)]
+pub struct WorldInspector {
+    state: Arc<Mutex<InspectorState>>,
+}
+
+impl Default for WorldInspector {
+    fn default() -> Self {
+        let state = InspectorState {
+            interval: DEFAULT_INSPECTOR_INTERVAL,
+            requested: false,
+            refreshed_at: None,
+            snapshot: None,
+            hidden: HashSet::new(),
+            toggles: Vec::new(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+}
+
+impl WorldInspector {
+    /// Sets interval between snapshots of the world.
+    pub fn set_interval(&self, interval: Duration) {
+        self.state.lock().unwrap().interval = interval;
+    }
+
+    /// Interval between snapshots of the world.
+    pub fn interval(&self) -> Duration {
+        self.state.lock().unwrap().interval
+    }
+
+    /// Captures snapshot of the world if the overlay shows it and the interval has elapsed.
+    ///
+    /// Returns `true` if snapshot was captured.
+    ///
+    pub fn update(&self, world: &World) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let interval = state.interval;
+        let elapsed = match state.refreshed_at {
+            Some(refreshed_at) => refreshed_at.elapsed() >= interval,
+            None => true,
+        };
+        if !state.requested || !elapsed {
+            return false;
+        }
+        state.snapshot = Some(world.debug_snapshot());
+        state.hidden = world
+            .query::<Visibility>()
+            .filter(|(_, visibility)| !visibility.visible)
+            .map(|(entity, _)| entity)
+            .collect();
+        state.refreshed_at = Some(Instant::now());
+        state.requested = false;
+        true
+    }
+
+    /// Applies visibility of entities which was toggled in the overlay since the last call.
+    ///
+    /// It should be called on each update before entities are culled,
+    /// so toggled entities are shown or hidden in the same frame.
+    /// Returns count of entities which visibility was set.
+    ///
+    pub fn apply_visibility(&self, world: &mut World) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let mut count = 0;
+        for (entity, visible) in std::mem::take(&mut state.toggles) {
+            // Entity could be despawned since the snapshot was captured.
+            if !world.contains(entity) {
+                continue;
+            }
+            world.insert(entity, Visibility { visible });
+            if visible {
+                state.hidden.remove(&entity);
+            } else {
+                state.hidden.insert(entity);
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Requests the next snapshot and provides the last captured one (if any)
+    /// with visibility of its entities to the closure.
+    pub(crate) fn show<R>(&self, f: impl FnOnce(Option<&WorldSnapshot>, VisibilityView) -> R) -> R {
+        let mut state = self.state.lock().unwrap();
+        state.requested = true;
+        let InspectorState {
+            snapshot,
+            hidden,
+            toggles,
+            ..
+        } = &mut *state;
+        f(snapshot.as_ref(), VisibilityView { hidden, toggles })
+    }
+}
@@ -1,22 +1,88 @@
 //! Utilities for engine initialization.
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
 
-use egui::TextureId;
+use bytemuck::Pod;
+use egui::{CtxRef, TextureId};
 use egui_winit_platform::{Platform, PlatformDescriptor};
 use image::RgbaImage;
 use thiserror::Error;
-use ultraviolet::{Mat4, Vec3};
-use winit::event::{Event, StartCause, WindowEvent};
-use winit::event_loop::{ControlFlow, EventLoop};
+use winit::dpi::PhysicalSize;
+use winit::event::{
+    DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, ModifiersState, StartCause,
+    WindowEvent,
+};
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
 use winit::window::Window;
 
 use crate::{
-    config::Config,
-    graphics::{camera::CameraUBO, error::ImageRegisterError, Renderer, RendererCreationError},
-    window::{Event as MyEvent, Size},
+    asset::AssetServer,
+    camera::{ActiveCamera, Camera},
+    config::{Config, MAX_RENDER_SCALE, MIN_RENDER_SCALE},
+    graphics::{
+        error::{AntialiasingError, ImageRegisterError, ReadPixelsError, ShutdownError},
+        particles::error::ParticleSystemCreationError,
+        texture, AaMode, ClipStack, CullingReport, DrawQueue, FrameStats, HookStage,
+        ImageSubresource, IndexBuffer, IndexBufferError, IndirectBufferCreationError,
+        IndirectBufferId, IndirectDraw, IndirectDrawError, IndirectDrawList,
+        MappedBufferCreationError, MemoryStats, Mesh, MeshCreationError, MeshId, ParticleEmitter,
+        ParticleParams, PassOps, PauseControl, QualityController, QualityMonitor, ReadbackError,
+        ReadbackImage, ReadbackTicket, RenderHook, RendererCreationError, SamplerDesc, Screenshot,
+        ShaderWatcher, SpriteBatch, SpriteTextureId, StreamingConfig, StreamingManager,
+        TextureData, UniformBuffer, Vertex, Viewport, ViewportError, ViewportList,
+    },
+    math::Color,
+    settings::{EngineSettings, SettingsStore},
+    task::{TaskPool, DEFAULT_BLOCKING_THREADS},
+    text::TextBrush,
+    window::{
+        display::DisplayRequest, input::Key, manager::WindowRequest, monitor, Clipboard,
+        ClipboardError, CustomEvent, DisplayControl, Event as MyEvent, EventProxy, Input,
+        MonitorError, MonitorId, MonitorInfo, ScreenSpace, Size, VideoMode,
+        WindowEvent as MyWindowEvent, WindowId, WindowManager,
+    },
+};
+
+#[cfg(feature = "scene")]
+use crate::scene::{Scene, SceneError};
+#[cfg(feature = "gamepad")]
+use crate::window::gamepad::Gamepads;
+
+pub use backend::{
+    NullWindowBackend, ScriptedEvent, ScriptedEvents, WindowBackend, WinitBackend, NULL_WINDOW_SIZE,
+};
+use diagnostics::DiagnosticsServer;
+pub use diagnostics::{
+    CommandParseError, DiagnosticsCommand, DiagnosticsHandle, DiagnosticsLogger, DiagnosticsStats,
+    DIAGNOSTICS_QUEUE_CAPACITY,
 };
+pub use exit::{ExitCause, ExitHandle, ExitReport};
+#[cfg(feature = "inspector")]
+pub use inspector::{WorldInspector, DEFAULT_INSPECTOR_INTERVAL};
+use overlay::Overlays;
+pub use overlay::{OverlayFn, OverlayLevel, FPS_OVERLAY, RESOURCES_OVERLAY, STATS_OVERLAY};
+use pacing::FrameLimiter;
+use startup::Startup;
+pub use startup::{CancellationToken, Cancelled, ProgressSink, GRAPHICS_PHASE, PRELOAD_PHASE};
+use timestep::FixedTimestep;
+pub use timestep::{FixedClock, MAX_FIXED_STEPS};
+
+mod backend;
+pub mod diagnostics;
+mod exit;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod overlay;
+mod pacing;
+pub(crate) mod startup;
+mod timestep;
+
+mod tests;
 
 pub type Result<T> = std::result::Result<T, AppCreationError>;
 
@@ -27,185 +93,1764 @@ pub enum AppCreationError {
 
     #[error("graphics initialization error: {0}")]
     Graphics(#[from] RendererCreationError),
+
+    #[error("application creation was cancelled")]
+    Cancelled(#[from] Cancelled),
+
+    #[cfg(feature = "scene")]
+    #[error("failed to load scene: {0}")]
+    Scene(#[from] SceneError),
 }
 
 /// Type which represents duration between two frames.
 pub type DeltaTime = Duration;
 
+/// Interval between checks whether monitors were connected or disconnected.
+const MONITORS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval between checks whether files of watched shaders were modified.
+const SHADERS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Max duration of waiting for tasks of the task pool when the application is closed.
+const TASKS_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether application instance was created by [`init`] and was not torn down yet.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Max delta time of updates while rendering is paused
+/// (unless it is zero, see [`Config::with_zero_delta_when_paused`]).
+pub const MAX_PAUSED_DELTA: DeltaTime = Duration::from_millis(100);
+
 /// General context of game engine.
 ///
-/// Can be created using [`init`] function.
+/// Can be created using [`init`] function, or with [`Application::with_null_window`]
+/// to run main loop without display or GPU.
 ///
-pub struct Application {
-    _config: Config,
-    renderer: Renderer,
+pub struct Application<B = WinitBackend> {
+    config: Config,
+    backend: B,
     egui: Option<Platform>,
-    event_loop: Option<EventLoop<()>>,
+    input: Input,
+    clipboard: Clipboard,
+    cursor_grab: bool,
+    overlays: Overlays,
+    #[cfg(feature = "inspector")]
+    inspector: WorldInspector,
+    tasks: TaskPool,
+    quality: Option<QualityController>,
+    settings: Option<SettingsStore>,
+    monitor_ids: Vec<MonitorId>,
+    monitors_polled_at: Instant,
+    shader_watcher: ShaderWatcher,
+    shaders_polled_at: Instant,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+    start_time: Instant,
+    exit_handle: ExitHandle,
+    fixed_timestep: Option<FixedTimestep>,
+    frame_limiter: Option<FrameLimiter>,
+    exit_cause: Option<ExitCause>,
+    preloaded: HashMap<PathBuf, TextureId>,
+    #[cfg(feature = "scene")]
+    scene: Option<Scene>,
+    diagnostics: Option<DiagnosticsServer>,
+    assets: Option<AssetServer>,
+    windows: WindowManager,
+    display: DisplayControl,
+    events: EventProxy,
+    /// Custom events sent by [`EventProxy`] which were not delivered yet.
+    custom_events: Receiver<CustomEvent>,
+    /// If the main loop was started (so it is not started again by the next run).
+    started: bool,
 }
 
 impl Application {
     fn new(config: Config) -> Result<Self> {
+        let settings_path = config.settings_path();
+        let stored = settings_path.as_deref().and_then(EngineSettings::load);
+        if config.headless() {
+            // There are neither monitors nor the window which geometry could be stored.
+            let startup = Startup::from_config(&config);
+            let backend = match WinitBackend::headless(&config, &startup) {
+                Err(RendererCreationError::Cancelled(cancelled)) => return Err(cancelled.into()),
+                backend => backend?,
+            };
+            let mut app = Self::with_backend(config, backend, None, None);
+            app.preload(&startup)?;
+            #[cfg(feature = "scene")]
+            app.load_scene()?;
+            return Ok(app);
+        }
         let event_loop = EventLoop::with_user_event();
-        let renderer = Renderer::new(&config, &event_loop)?;
+        // Stored geometry is applied before the window is created,
+        // but it is moved onto the monitors which are connected now.
+        let config = match &stored {
+            Some(stored) => {
+                let monitors: Vec<_> = monitor::event_loop_monitors(&event_loop)
+                    .iter()
+                    .map(Into::into)
+                    .collect();
+                config.with_stored(&stored.clamped(&monitors))
+            }
+            None => config,
+        };
+        let startup = Startup::from_config(&config);
+        let backend = match WinitBackend::new(&config, event_loop, &startup) {
+            Err(RendererCreationError::Cancelled(cancelled)) => return Err(cancelled.into()),
+            backend => backend?,
+        };
+        let mut app = Self::with_backend(config, backend, settings_path, stored);
+        app.preload(&startup)?;
+        #[cfg(feature = "scene")]
+        app.load_scene()?;
+        Ok(app)
+    }
 
-        let window = renderer.window();
-        let size = window.inner_size();
-        let egui = Platform::new(PlatformDescriptor {
-            physical_width: size.width,
-            physical_height: size.height,
-            scale_factor: window.scale_factor(),
-            ..Default::default()
+    /// Loads the scene of [`Config::with_scene`], if it is set.
+    #[cfg(feature = "scene")]
+    fn load_scene(&mut self) -> Result<()> {
+        if let Some(path) = self.config.scene() {
+            let scene = Scene::load(path)?;
+            log::info!(
+                "scene {} with {} entities was loaded",
+                path.display(),
+                scene.len()
+            );
+            self.scene = Some(scene);
+        }
+        Ok(())
+    }
+
+    /// Loads files of [`Config::with_preload`] as UI textures.
+    ///
+    /// Window is shown while they are loaded: it is cleared with the clear color
+    /// and its title displays progress. Closing the window cancels startup.
+    /// Files which could not be loaded are skipped.
+    ///
+    fn preload(&mut self, startup: &Startup) -> Result<()> {
+        let paths = self.config.preload().to_vec();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        self.backend.set_visible(true);
+        let name = self.config.title().to_string();
+        let total = paths.len();
+        let mut done = 0;
+        let result = startup.run_items(PRELOAD_PHASE, paths, |path| {
+            let texture = texture::load(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|texture| {
+                    let renderer = &mut self.backend.renderer;
+                    let sampler = SamplerDesc::linear();
+                    let texture_id = renderer.register_ui_texture(texture, sampler);
+                    texture_id.map_err(|error| error.to_string())
+                });
+            match texture {
+                Ok(texture_id) => {
+                    self.preloaded.insert(path, texture_id);
+                }
+                Err(error) => log::warn!("failed to preload {}: {}", path.display(), error),
+            }
+            done += 1;
+
+            self.set_title(&format!("{} - loading ({}/{})", name, done, total));
+            if let Err(error) = self.backend.renderer.render(None) {
+                log::warn!("failed to render frame while loading: {}", error);
+            }
+            let events = match self.backend.event_loop.as_mut() {
+                Some(event_loop) => self::pump_startup_events(event_loop),
+                None => StartupEvents::default(),
+            };
+            if events.resized {
+                self.backend.renderer.request_resize();
+            }
+            if events.close_requested {
+                startup.token().cancel();
+            }
         });
+        self.set_title(&name);
+        result.map_err(AppCreationError::from)
+    }
 
-        Ok(Self {
-            renderer,
-            egui: Some(egui),
-            _config: config,
-            event_loop: Some(event_loop),
-        })
+    /// Returns underlying window of this application, if it is not headless.
+    pub(crate) fn window(&self) -> Option<&Window> {
+        self.backend.renderer.window()
+    }
+
+    /// Sets title of the window.
+    pub fn set_title(&self, title: &str) {
+        if let Some(window) = self.window() {
+            window.set_title(title)
+        }
+    }
+
+    /// Makes the window fullscreen on the monitor with given identifier.
+    ///
+    /// Exclusive fullscreen with given video mode is used if it was provided,
+    /// otherwise borderless fullscreen is used.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if monitor was not found
+    /// or video mode is not supported by the monitor.
+    ///
+    pub fn set_fullscreen_on(
+        &self,
+        monitor_id: MonitorId,
+        video_mode: Option<VideoMode>,
+    ) -> std::result::Result<(), MonitorError> {
+        match self.window() {
+            Some(window) => {
+                monitor::set_fullscreen_on(window, monitor_id, video_mode)?;
+                self.display.set_mode(monitor::fullscreen_mode(window));
+                Ok(())
+            }
+            // There are no monitors in headless mode.
+            None => Err(MonitorError::NotFound(monitor_id)),
+        }
+    }
+
+    /// Exits fullscreen mode of the window.
+    pub fn set_windowed(&self) {
+        if let Some(window) = self.window() {
+            window.set_fullscreen(None);
+            self.display.set_mode(None);
+        }
+    }
+
+    /// Returns `true` if frames are rendered into offscreen images
+    /// instead of the window (see [`Config::with_headless`]).
+    pub fn is_headless(&self) -> bool {
+        self.backend.renderer.is_headless()
+    }
+
+    /// Reads pixels of the last rendered frame in headless mode
+    /// (see [`Renderer::read_pixels`](crate::graphics::Renderer::read_pixels)).
+    pub fn read_pixels(&mut self) -> std::result::Result<RgbaImage, ReadPixelsError> {
+        self.backend.renderer.read_pixels()
+    }
+
+    /// Captures the next rendered frame as RGBA image
+    /// (see [`Renderer::capture_frame`](crate::graphics::Renderer::capture_frame)).
+    ///
+    /// In headless mode, the image could be waited for after [`Application::render_frames`]:
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn test(mut application: titan_core::app::Application) -> Result<(), Box<dyn std::error::Error>> {
+    /// let capture = application.capture_frame()?;
+    /// application.render_frames(1, |_| ());
+    /// capture.save_png("frame.png", Duration::from_secs(1))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Otherwise, it should be polled by [`Screenshot::try_image`] on later updates.
+    ///
+    pub fn capture_frame(&mut self) -> std::result::Result<Screenshot, ReadbackError> {
+        self.backend.readback_frame().map(Screenshot::new)
+    }
+
+    /// Runs main loop of headless mode until given count of frames is rendered
+    /// or exit is requested, and returns without destroying it.
+    ///
+    /// Could be called multiple times, so pixels of each frame could be read
+    /// by [`Application::read_pixels`] in between (for example, in screenshot tests).
+    /// [`Event::Created`](MyEvent::Created) is delivered only on the first call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the application is not headless.
+    ///
+    pub fn render_frames(&mut self, count: u32, mut callback: impl FnMut(MyEvent)) {
+        assert!(
+            self.is_headless(),
+            "frames could be rendered on demand only in headless mode"
+        );
+        self.run_headless(Some(count), &mut callback);
     }
 
-    /// Returns underlying window of this application.
-    pub fn window(&self) -> &Window {
-        self.renderer.window()
+    /// Runs main loop without the platform in headless mode.
+    ///
+    /// Frames are rendered one after another until exit is requested
+    /// (or until given count of frames is rendered).
+    ///
+    fn run_headless(&mut self, frames: Option<u32>, callback: &mut impl FnMut(MyEvent)) {
+        let window_id = self.backend.window_id();
+        // Exit could be requested while previous frames were rendered on demand.
+        let mut control_flow = match self.exit_cause {
+            Some(_) => ControlFlow::Exit,
+            None => ControlFlow::Poll,
+        };
+        if !self.started {
+            let init = Event::NewEvents(StartCause::Init);
+            self.handle_event(init, &mut control_flow, callback);
+        }
+        let mut remaining = frames;
+        while control_flow != ControlFlow::Exit && remaining != Some(0) {
+            self.fail_window_requests(callback);
+            self.handle_event(Event::MainEventsCleared, &mut control_flow, callback);
+            if self.backend.take_redraw_request() {
+                let event = Event::RedrawRequested(window_id);
+                self.handle_event(event, &mut control_flow, callback);
+            }
+            if let Some(remaining) = remaining.as_mut() {
+                *remaining -= 1;
+            }
+        }
+        if frames.is_none() {
+            self.exit(&mut control_flow, ExitCause::Requested);
+            self.handle_event(Event::LoopDestroyed, &mut control_flow, callback);
+        }
     }
 
+    /// Fails requests of [`WindowManager`] in headless mode, where windows could not be created.
+    fn fail_window_requests(&mut self, callback: &mut impl FnMut(MyEvent)) {
+        for request in self.windows.take_requests() {
+            if let WindowRequest::Create(id, desc) = request {
+                log::error!(
+                    "window \"{}\" could not be created in headless mode",
+                    desc.title
+                );
+                self.windows.failed(id);
+                let event = MyWindowEvent::Failed;
+                callback(MyEvent::Window { id, event });
+            }
+        }
+    }
+
+    /// Registers an image to be drawn in UI with linear filtering.
     pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
     ) -> std::result::Result<TextureId, ImageRegisterError> {
-        self.renderer.register_ui_image(image)
+        self.register_ui_image_with_sampler(image, SamplerDesc::linear())
+    }
+
+    /// Registers an image to be drawn in UI with given sampler.
+    pub fn register_ui_image_with_sampler(
+        &mut self,
+        image: &RgbaImage,
+        sampler: SamplerDesc,
+    ) -> std::result::Result<TextureId, ImageRegisterError> {
+        self.backend.renderer.register_ui_image(image, sampler)
+    }
+
+    /// Registers decoded texture (for example, compressed one loaded from KTX2 file
+    /// with [`texture::load`](crate::graphics::texture::load)) to be drawn in UI with given sampler.
+    pub fn register_ui_texture(
+        &mut self,
+        texture: TextureData,
+        sampler: SamplerDesc,
+    ) -> std::result::Result<TextureId, ImageRegisterError> {
+        self.backend.renderer.register_ui_texture(texture, sampler)
+    }
+
+    /// Unregisters an image which was registered to be drawn in UI.
+    pub fn unregister_ui_image(&mut self, texture_id: TextureId) {
+        self.backend.renderer.unregister_ui_image(texture_id)
+    }
+
+    /// Returns handle which queues text to be drawn in the next frame
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn text_brush(&self) -> TextBrush {
+        self.backend.renderer.text_brush()
+    }
+
+    /// Returns handle which queues sprites to be drawn over the scene in the next frame
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn sprite_batch(&self) -> SpriteBatch {
+        self.backend.renderer.sprite_batch()
+    }
+
+    /// Registers an image to be drawn by sprites with linear filtering.
+    pub fn register_sprite_image(
+        &mut self,
+        image: &RgbaImage,
+    ) -> std::result::Result<SpriteTextureId, ImageRegisterError> {
+        self.register_sprite_image_with_sampler(image, SamplerDesc::linear())
+    }
+
+    /// Registers an image to be drawn by sprites with given sampler
+    /// (for example, nearest one for pixel art).
+    pub fn register_sprite_image_with_sampler(
+        &mut self,
+        image: &RgbaImage,
+        sampler: SamplerDesc,
+    ) -> std::result::Result<SpriteTextureId, ImageRegisterError> {
+        self.backend.renderer.register_sprite_image(image, sampler)
+    }
+
+    /// Unregisters an image which was registered to be drawn by sprites.
+    pub fn unregister_sprite_image(&mut self, texture_id: SpriteTextureId) {
+        self.backend.renderer.unregister_sprite_image(texture_id)
+    }
+
+    /// Replaces viewports in which the scene is rendered from their cameras (for split-screen).
+    ///
+    /// Viewports are drawn in array order. If there are no viewports,
+    /// the scene is rendered into the whole window.
+    ///
+    pub fn set_viewports(
+        &mut self,
+        viewports: &[Viewport],
+    ) -> std::result::Result<(), ViewportError> {
+        self.backend.renderer.set_viewports(viewports)
+    }
+
+    /// Returns handle to the list of viewports
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn viewports(&self) -> ViewportList {
+        self.backend.renderer.viewports()
+    }
+
+    /// Returns handle through which results of frustum culling are shown in frame statistics
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn culling_report(&self) -> CullingReport {
+        self.backend.renderer.culling_report()
+    }
+
+    /// Returns handle which submits draws to be drawn in the next frame without ECS
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn draw_queue(&self) -> DrawQueue {
+        self.backend.renderer.draw_queue()
+    }
+
+    /// Returns handle to the stack of rectangles which clip submitted draws and queued text
+    /// (it is shared by [`DrawQueue`] and [`TextBrush`]).
+    pub fn clip_stack(&self) -> ClipStack {
+        self.backend.renderer.clip_stack()
+    }
+
+    /// Changes antialiasing mode of the scene.
+    ///
+    /// Render passes and pipelines which depend on the mode are recreated
+    /// after the device becomes idle, so it should not be called on each frame.
+    ///
+    pub fn set_antialiasing(&mut self, mode: AaMode) -> std::result::Result<(), AntialiasingError> {
+        self.backend.renderer.set_antialiasing(mode)
+    }
+
+    /// Antialiasing mode of the scene.
+    pub fn antialiasing(&self) -> AaMode {
+        self.backend.renderer.antialiasing()
+    }
+
+    /// Dumps everything which is recorded for the next rendered frame into the file as JSON
+    /// (see [`Renderer::dump_next_frame`](crate::graphics::Renderer::dump_next_frame)).
+    pub fn dump_next_frame(&mut self, path: &Path) {
+        self.backend.renderer.dump_next_frame(path)
+    }
+
+    /// Requests readback of the range of the indirect buffer (in bytes)
+    /// at the end of the next rendered frame.
+    pub fn readback_buffer(
+        &mut self,
+        id: IndirectBufferId,
+        range: Range<u64>,
+    ) -> std::result::Result<ReadbackTicket, ReadbackError> {
+        self.backend.renderer.readback_buffer(id, range)
+    }
+
+    /// Requests readback of the subresource of the image at the end of the next rendered frame.
+    pub fn readback_image(
+        &mut self,
+        image: ReadbackImage,
+        subresource: ImageSubresource,
+    ) -> std::result::Result<ReadbackTicket, ReadbackError> {
+        self.backend.renderer.readback_image(image, subresource)
+    }
+
+    /// Returns handle which injects faults into the renderer (for testing of timeouts).
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injector(&self) -> crate::graphics::FaultInjector {
+        self.backend.renderer.fault_injector()
+    }
+
+    /// Sets color which the scene is cleared with since the next frame.
+    pub fn set_clear_color(&mut self, color: impl Into<Color>) {
+        self.backend.renderer.set_clear_color(color)
+    }
+
+    /// Color which the scene is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.backend.renderer.clear_color()
+    }
+
+    /// Changes load and store operations of color and depth of the scene
+    /// (see [`Renderer::set_pass_ops`](crate::graphics::Renderer::set_pass_ops)).
+    pub fn set_pass_ops(&mut self, ops: PassOps) -> std::result::Result<(), AntialiasingError> {
+        self.backend.renderer.set_pass_ops(ops)
+    }
+
+    /// Load and store operations of color and depth of the scene.
+    pub fn pass_ops(&self) -> PassOps {
+        self.backend.renderer.pass_ops()
+    }
+
+    /// Adds user-defined render pass which will be run on each frame at given stage.
+    pub fn add_render_hook(&mut self, stage: HookStage, hook: Box<dyn RenderHook>) {
+        self.backend.renderer.add_hook(stage, hook)
+    }
+
+    /// Returns `true` if all commands of indirect draw are drawn with one draw call
+    /// (`multi_draw_indirect` feature is supported by the device).
+    pub fn supports_multi_draw_indirect(&self) -> bool {
+        self.backend.renderer.supports_multi_draw_indirect()
+    }
+
+    /// Statistics of device memory which is allocated by the renderer
+    /// (see [`Renderer::memory_stats`](crate::graphics::Renderer::memory_stats)).
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.backend.renderer.memory_stats()
+    }
+
+    /// Creates new uniform buffer with the initial value
+    /// (see [`Renderer::create_uniform_buffer`](crate::graphics::Renderer::create_uniform_buffer)).
+    pub fn create_uniform_buffer<T>(
+        &mut self,
+        value: T,
+    ) -> std::result::Result<UniformBuffer<T>, MappedBufferCreationError>
+    where
+        T: Pod + Send + Sync,
+    {
+        self.backend.renderer.create_uniform_buffer(value)
+    }
+
+    /// Creates index buffer with indices of mesh with given count of vertices
+    /// (see [`Renderer::create_index_buffer`](crate::graphics::Renderer::create_index_buffer)).
+    pub fn create_index_buffer(
+        &self,
+        indices: &[u32],
+        vertex_count: usize,
+    ) -> std::result::Result<IndexBuffer, IndexBufferError> {
+        self.backend
+            .renderer
+            .create_index_buffer(indices, vertex_count)
+    }
+
+    /// Creates mesh with given vertices and indices of triangles
+    /// (see [`Renderer::create_mesh`](crate::graphics::Renderer::create_mesh)).
+    pub fn create_mesh(
+        &mut self,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> std::result::Result<MeshId, MeshCreationError> {
+        self.backend.renderer.create_mesh(vertices, indices)
+    }
+
+    /// Mesh which was created by [`Application::create_mesh`], if it was not removed.
+    pub fn mesh(&self, id: MeshId) -> Option<&Mesh> {
+        self.backend.renderer.mesh(id)
+    }
+
+    /// Removes the mesh.
+    ///
+    /// Returns `true` if mesh was present.
+    ///
+    pub fn remove_mesh(&mut self, id: MeshId) -> bool {
+        self.backend.renderer.remove_mesh(id)
+    }
+
+    /// Creates new buffer with draw commands of the list for indirect drawing.
+    pub fn create_indirect_buffer(
+        &mut self,
+        list: &IndirectDrawList,
+    ) -> std::result::Result<IndirectBufferId, IndirectBufferCreationError> {
+        self.backend.renderer.create_indirect_buffer(list)
+    }
+
+    /// Removes buffer for indirect drawing.
+    ///
+    /// Returns `true` if buffer was present.
+    ///
+    pub fn remove_indirect_buffer(&mut self, id: IndirectBufferId) -> bool {
+        self.backend.renderer.remove_indirect_buffer(id)
+    }
+
+    /// Sets indirect draw which is used to draw game objects on each frame.
+    ///
+    /// If indirect draw is `None`, game objects are drawn directly.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if buffer was not found
+    /// or draw commands are out of its bounds.
+    ///
+    pub fn set_indirect_draw(
+        &mut self,
+        draw: Option<IndirectDraw>,
+    ) -> std::result::Result<(), IndirectDrawError> {
+        self.backend.renderer.set_indirect_draw(draw)
+    }
+
+    /// Enables GPU particle system which will be simulated and drawn on each frame.
+    ///
+    /// Returns handle which could queue particle spawns
+    /// (it could be moved into the callback of [`Application::run`]).
+    ///
+    pub fn enable_particles(
+        &mut self,
+        max_particles: u32,
+        params: ParticleParams,
+    ) -> std::result::Result<ParticleEmitter, ParticleSystemCreationError> {
+        self.backend
+            .renderer
+            .enable_particles(max_particles, params)
+    }
+
+    /// Enables streaming of textures from disk with given budgets.
+    ///
+    /// Returns handle which registers streamable textures
+    /// (it could be moved into the callback of [`Application::run`]).
+    ///
+    pub fn enable_streaming(&mut self, config: StreamingConfig) -> StreamingManager {
+        let tasks = self.tasks.clone();
+        self.backend.renderer.enable_streaming(config, tasks)
+    }
+
+    /// Enables streaming of textures with given budgets and loading of other assets in background.
+    ///
+    /// Returns server which resolves asset references into handles
+    /// (it could be moved into the callback of [`Application::run`]).
+    /// When loading of the asset is finished, [`MyEvent::Asset`] is delivered.
+    ///
+    pub fn enable_assets(&mut self, config: StreamingConfig) -> AssetServer {
+        let assets = AssetServer::new(self.enable_streaming(config));
+        self.assets = Some(assets.clone());
+        assets
     }
 
     /// Starts execution of game engine.
+    ///
+    /// This function never returns: process is exited after the main loop.
+    /// Use [`Application::run_until_exit`] to get control back.
+    ///
     pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
-        let event_loop = self.event_loop.take().unwrap();
-
-        let mut start_time = Instant::now();
-        event_loop.run(move |event, _, control_flow| {
+        let event_loop = match self.backend.event_loop.take() {
+            Some(event_loop) => event_loop,
+            None => {
+                self.run_headless(None, &mut callback);
+                if let Err(error) = self.backend.renderer.shutdown() {
+                    log::error!("failed to shut down renderer: {}", error);
+                }
+                std::process::exit(0);
+            }
+        };
+        event_loop.run(move |event, target, control_flow| {
             // Have the closure take ownership of `self`.
             // `event_loop.run` never returns, therefore we must do this to ensure
             // the resources are properly cleaned up.
-            let _ = &self;
+            if matches!(event, Event::MainEventsCleared) && *control_flow != ControlFlow::Exit {
+                self.apply_window_requests(target, &mut callback);
+            }
+            self.handle_event(event, control_flow, &mut callback);
+        })
+    }
 
-            *control_flow = ControlFlow::Poll;
+    /// Starts execution of game engine and returns after the main loop was exited.
+    ///
+    /// After the main loop, waits until the device finishes all work
+    /// and destroys all graphics objects. Objects which were still alive
+    /// (were not released by the user) are logged and returned in the report.
+    /// After that, new instance could be created with [`init`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if renderer could not be shut down properly.
+    ///
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "android",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn run_until_exit(
+        mut self,
+        mut callback: impl FnMut(MyEvent),
+    ) -> std::result::Result<ExitReport, ShutdownError> {
+        use winit::platform::run_return::EventLoopExtRunReturn;
 
-            // Take `Platform` object from `self` to workaround about borrow checker.
-            let mut egui = self.egui.take().unwrap();
+        match self.backend.event_loop.take() {
+            Some(mut event_loop) => {
+                event_loop.run_return(|event, target, control_flow| {
+                    if matches!(event, Event::MainEventsCleared)
+                        && *control_flow != ControlFlow::Exit
+                    {
+                        self.apply_window_requests(target, &mut callback);
+                    }
+                    self.handle_event(event, control_flow, &mut callback);
+                });
+            }
+            None => self.run_headless(None, &mut callback),
+        }
 
-            // Have this closure to early return if needed (for example if error is occurred).
-            // Closure is needed because `label_break_value` feature is unstable.
-            let action = || {
-                egui.handle_event(&event);
-                egui.update_time(start_time.elapsed().as_secs_f64());
+        let leaks = self.backend.renderer.shutdown();
+        let cause = self.exit_cause.take().unwrap_or(ExitCause::Requested);
+        // New instance could be created only after this one is torn down completely.
+        drop(self);
+        INITIALIZED.store(false, Ordering::SeqCst);
 
-                let window = self.window();
-                match event {
-                    Event::NewEvents(StartCause::Init) => {
-                        start_time = Instant::now();
-                        callback(MyEvent::Created);
-                        window.set_visible(true);
-                    }
-                    Event::WindowEvent { event, window_id } if window_id == window.id() => {
-                        match event {
-                            WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                            WindowEvent::Resized(size) => {
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
-                                    return;
-                                }
-                                if let Err(error) = self.renderer.resize() {
-                                    log::error!("window resizing error: {}", error);
-                                    *control_flow = ControlFlow::Exit;
-                                    return;
-                                }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
-                            }
-                            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                                let size = *new_inner_size;
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
-                                    return;
-                                }
-                                if let Err(error) = self.renderer.resize() {
-                                    log::error!("window resizing error: {}", error);
-                                    *control_flow = ControlFlow::Exit;
-                                    return;
-                                }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
-                            }
-                            _ => (),
+        let report = ExitReport {
+            cause,
+            leaks: leaks?,
+        };
+        report.log_leaks();
+        Ok(report)
+    }
+
+    /// Creates and closes secondary windows which were requested by [`WindowManager`].
+    fn apply_window_requests(
+        &mut self,
+        target: &EventLoopWindowTarget<()>,
+        callback: &mut impl FnMut(MyEvent),
+    ) {
+        for request in self.windows.take_requests() {
+            match request {
+                WindowRequest::Create(id, desc) => {
+                    let renderer = &mut self.backend.renderer;
+                    let event = match renderer.create_window(target, id, &desc) {
+                        Ok(()) => {
+                            self.windows.opened(id);
+                            MyWindowEvent::Created
+                        }
+                        Err(error) => {
+                            log::error!("failed to create window \"{}\": {}", desc.title, error);
+                            self.windows.failed(id);
+                            MyWindowEvent::Failed
                         }
+                    };
+                    callback(MyEvent::Window { id, event });
+                }
+                WindowRequest::Close(id) => {
+                    if self.backend.renderer.close_window(id) {
+                        let event = MyWindowEvent::Closed;
+                        callback(MyEvent::Window { id, event });
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Application<NullWindowBackend> {
+    /// Creates an application which has neither a real window nor a device.
+    ///
+    /// Instead of the platform, scripted events are generated by [`NullWindowBackend`],
+    /// so main loop could be run in tests without display or GPU.
+    ///
+    /// Engine settings are persisted only into the file which was set explicitly
+    /// by [`Config::with_settings_path`].
+    ///
+    pub fn with_null_window(config: Config, events: ScriptedEvents) -> Self {
+        let settings_path = config.explicit_settings_path().map(Path::to_owned);
+        let stored = settings_path.as_deref().and_then(EngineSettings::load);
+        let config = match &stored {
+            Some(stored) => config.with_stored(stored),
+            None => config,
+        };
+        let backend = NullWindowBackend::new(events);
+        Self::with_backend(config, backend, settings_path, stored)
+    }
+
+    /// Runs main loop with scripted events and returns after the last of them.
+    ///
+    /// Scripted events are handled the same way as events of the real window.
+    ///
+    pub fn run_until_exit(mut self, mut callback: impl FnMut(MyEvent)) -> ExitReport {
+        let window_id = self.backend.window_id();
+        let script = std::mem::take(&mut self.backend.script);
+        let mut control_flow = ControlFlow::Poll;
+
+        let init = Event::NewEvents(StartCause::Init);
+        self.handle_event(init, &mut control_flow, &mut callback);
+        for scripted in script.events() {
+            if control_flow == ControlFlow::Exit {
+                break;
+            }
+            match *scripted {
+                ScriptedEvent::Frame => {
+                    self.apply_window_requests(&mut callback);
+                    let event = Event::MainEventsCleared;
+                    self.handle_event(event, &mut control_flow, &mut callback);
+                    if self.backend.take_redraw_request() {
+                        let event = Event::RedrawRequested(window_id);
+                        self.handle_event(event, &mut control_flow, &mut callback);
+                    }
+                }
+                ScriptedEvent::Resize(size) => {
+                    self.backend.set_size(size);
+                    let event = Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::Resized(PhysicalSize::new(size.width, size.height)),
+                    };
+                    self.handle_event(event, &mut control_flow, &mut callback);
+                }
+                ScriptedEvent::Key(key) => {
+                    for state in [ElementState::Pressed, ElementState::Released] {
+                        #[allow(deprecated)]
+                        let input = KeyboardInput {
+                            scancode: 0,
+                            state,
+                            virtual_keycode: Some(key.into()),
+                            modifiers: ModifiersState::empty(),
+                        };
+                        let event = Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::KeyboardInput {
+                                // Safety: dummy identifier is only compared with other ones.
+                                device_id: unsafe { DeviceId::dummy() },
+                                input,
+                                is_synthetic: false,
+                            },
+                        };
+                        self.handle_event(event, &mut control_flow, &mut callback);
                     }
-                    Event::MainEventsCleared => {
-                        let size = window.inner_size();
+                }
+                ScriptedEvent::Close => {
+                    let event = Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::CloseRequested,
+                    };
+                    self.handle_event(event, &mut control_flow, &mut callback);
+                }
+            }
+        }
+        // Main loop is exited after the last scripted event.
+        self.exit(&mut control_flow, ExitCause::Requested);
+        self.handle_event(Event::LoopDestroyed, &mut control_flow, &mut callback);
+
+        let cause = self.exit_cause.take().unwrap_or(ExitCause::Requested);
+        ExitReport {
+            cause,
+            leaks: Vec::new(),
+        }
+    }
+
+    /// Applies requests of [`WindowManager`] without creating any real windows:
+    /// secondary windows are opened immediately and never send events of their own.
+    fn apply_window_requests(&mut self, callback: &mut impl FnMut(MyEvent)) {
+        for request in self.windows.take_requests() {
+            let (id, event) = match request {
+                WindowRequest::Create(id, _) => {
+                    self.windows.opened(id);
+                    (id, MyWindowEvent::Created)
+                }
+                WindowRequest::Close(id) => (id, MyWindowEvent::Closed),
+            };
+            callback(MyEvent::Window { id, event });
+        }
+    }
+}
+
+impl<B: WindowBackend> Application<B> {
+    fn with_backend(
+        config: Config,
+        backend: B,
+        settings_path: Option<PathBuf>,
+        stored: Option<EngineSettings>,
+    ) -> Self {
+        let size = backend.inner_size();
+        let egui = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor: backend.scale_factor(),
+            ..Default::default()
+        });
+
+        #[cfg(feature = "inspector")]
+        let inspector = WorldInspector::default();
+        let tasks = TaskPool::new(config.worker_threads(), DEFAULT_BLOCKING_THREADS);
+        let (events, custom_events) = EventProxy::new(backend.event_loop_proxy());
+        let quality = config
+            .adaptive_quality()
+            .map(|quality| QualityController::new(quality.target_frame_time()));
+        let quality_monitor = quality
+            .as_ref()
+            .map(QualityController::monitor)
+            .unwrap_or_default();
+        let level = stored
+            .map(|stored| stored.overlay_level)
+            .unwrap_or_else(|| config.overlay_level());
+        let settings = settings_path.map(|path| {
+            let current = EngineSettings {
+                window_position: config.window_position(),
+                window_size: config.window_size(),
+                maximized: config.maximized(),
+                fullscreen: config.fullscreen(),
+                vsync: config.vsync(),
+                render_scale: config.render_scale(),
+                overlay_level: level,
+            };
+            SettingsStore::new(path, current, stored)
+        });
+        let diagnostics = config.diagnostics_address().and_then(|address| {
+            DiagnosticsServer::start(address, &tasks)
+                .map_err(|error| {
+                    log::warn!(
+                        "failed to start diagnostics server on {}: {}",
+                        address,
+                        error
+                    )
+                })
+                .ok()
+        });
+        #[cfg(feature = "gamepad")]
+        let gamepads = Gamepads::new(config.gamepad_deadzone());
+        let overlays = Overlays::with_builtins(
+            level,
+            backend.pause_control(),
+            quality_monitor,
+            #[cfg(feature = "inspector")]
+            inspector.clone(),
+        );
+        Self {
+            backend,
+            egui: Some(egui),
+            input: Input::default(),
+            clipboard: Clipboard::default(),
+            cursor_grab: false,
+            overlays,
+            #[cfg(feature = "inspector")]
+            inspector,
+            tasks,
+            quality,
+            settings,
+            monitor_ids: Vec::new(),
+            monitors_polled_at: Instant::now(),
+            shader_watcher: ShaderWatcher::new(),
+            shaders_polled_at: Instant::now(),
+            #[cfg(feature = "gamepad")]
+            gamepads,
+            start_time: Instant::now(),
+            exit_handle: ExitHandle::default(),
+            fixed_timestep: config.fixed_timestep().map(FixedTimestep::new),
+            frame_limiter: config.max_fps().map(FrameLimiter::new),
+            exit_cause: None,
+            preloaded: HashMap::new(),
+            #[cfg(feature = "scene")]
+            scene: None,
+            diagnostics,
+            assets: None,
+            windows: WindowManager::default(),
+            display: DisplayControl::default(),
+            events,
+            custom_events,
+            started: false,
+            config,
+        }
+    }
+
+    /// Texture of the file which was loaded by [`Config::with_preload`], if it was loaded.
+    pub fn preloaded(&self, path: impl AsRef<Path>) -> Option<TextureId> {
+        self.preloaded.get(path.as_ref()).copied()
+    }
+
+    /// Scene of the file which was loaded by [`Config::with_scene`], if it was set.
+    ///
+    /// Scene is spawned into the world of the game by [`Scene::instantiate_with`]
+    /// (or [`Scene::instantiate`] if it has no components of the game).
+    ///
+    #[cfg(feature = "scene")]
+    pub fn scene(&self) -> Option<&Scene> {
+        self.scene.as_ref()
+    }
+
+    /// Statistics of frames rendered by this application.
+    pub fn frame_stats(&self) -> &FrameStats {
+        self.backend.stats()
+    }
+
+    /// Returns handle to the camera from which the scene is rendered into the whole window
+    /// (it could be moved into the callback of [`Application::run`]).
+    ///
+    /// Viewports are rendered from their own cameras, so it is used only if there are no viewports.
+    ///
+    pub fn active_camera(&self) -> ActiveCamera {
+        self.backend.active_camera()
+    }
+
+    /// Sets camera from which the scene is rendered into the whole window since the next frame.
+    pub fn set_active_camera(&self, camera: Camera) {
+        self.backend.active_camera().set(camera)
+    }
+
+    /// Returns handle which creates and closes secondary windows at runtime
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn window_manager(&self) -> WindowManager {
+        self.windows.clone()
+    }
+
+    /// Returns handle which sends custom events into the main loop from any thread
+    /// (they are delivered as [`Event::Custom`](MyEvent::Custom)).
+    pub fn event_proxy(&self) -> EventProxy {
+        self.events.clone()
+    }
+
+    /// Returns handle of the watcher which reloads shaders when their files change.
+    ///
+    /// Watcher is polled between frames (twice per second at most),
+    /// so render hooks see reloaded shaders before the next frame is recorded.
+    ///
+    pub fn shader_watcher(&self) -> ShaderWatcher {
+        self.shader_watcher.clone()
+    }
+
+    /// Reloads watched shaders which files were modified, if it is time to check them.
+    fn poll_shaders(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.shaders_polled_at) < SHADERS_POLL_INTERVAL {
+            return;
+        }
+        self.shaders_polled_at = now;
+        self.shader_watcher.poll();
+    }
+
+    /// Returns handle which switches the window between fullscreen and windowed mode
+    /// while the application is running.
+    ///
+    /// Handle could be moved into the callback of [`Application::run`].
+    ///
+    pub fn display_control(&self) -> DisplayControl {
+        self.display.clone()
+    }
+
+    /// Applies the last request of [`DisplayControl`], if any.
+    ///
+    /// Platform does not always report new size of the window after its mode was changed,
+    /// so swapchain recreation is requested and new size is delivered here.
+    fn apply_display_request(&mut self, callback: &mut impl FnMut(MyEvent)) {
+        let request = match self.display.take_request() {
+            Some(request) => request,
+            None => return,
+        };
+        let window = match self.backend.window() {
+            Some(window) => window,
+            None => {
+                log::warn!("display mode could not be changed in headless mode");
+                return;
+            }
+        };
+        let result = match request {
+            DisplayRequest::Fullscreen(monitor_id, mode) => {
+                monitor::set_fullscreen(window, monitor_id, mode)
+            }
+            DisplayRequest::Windowed => {
+                window.set_fullscreen(None);
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            log::warn!("failed to change display mode: {}", error);
+            return;
+        }
+        self.display.set_mode(monitor::fullscreen_mode(window));
+
+        let size = self.backend.inner_size();
+        if size.width == 0 || size.height == 0 {
+            callback(MyEvent::Resized(Size::default()));
+            return;
+        }
+        self.backend.request_resize();
+        callback(MyEvent::Resized(size));
+    }
+
+    /// Delivers custom events sent by [`EventProxy`] in order of sending.
+    fn deliver_custom_events(&self, callback: &mut impl FnMut(MyEvent)) {
+        for event in self.custom_events.try_iter() {
+            callback(MyEvent::Custom(event));
+        }
+    }
+
+    /// Size of the window (in physical pixels).
+    pub fn window_size(&self) -> Size {
+        self.backend.inner_size()
+    }
+
+    /// Scale factor which is used to convert physical pixels of the window into logical ones.
+    pub fn scale_factor(&self) -> f64 {
+        self.backend.scale_factor()
+    }
+
+    /// Converter between coordinate spaces of the window in its current state.
+    pub fn screen_space(&self) -> ScreenSpace {
+        ScreenSpace::new(self.window_size(), self.scale_factor())
+    }
+
+    /// Returns information about all monitors which are available to the window.
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.backend.monitors()
+    }
+
+    /// Returns monitors if some of them were connected or disconnected since the last call.
+    ///
+    /// Platform does not notify about monitor changes,
+    /// so monitors are polled no more often than [`MONITORS_POLL_INTERVAL`].
+    ///
+    fn poll_monitors(&mut self) -> Option<Vec<MonitorInfo>> {
+        let now = Instant::now();
+        if now.duration_since(self.monitors_polled_at) < MONITORS_POLL_INTERVAL {
+            return None;
+        }
+        self.monitors_polled_at = now;
+
+        let monitors = self.monitors();
+        let monitor_ids: Vec<_> = monitors.iter().map(|monitor| monitor.id).collect();
+        if monitor_ids == self.monitor_ids {
+            return None;
+        }
+        self.monitor_ids = monitor_ids;
+        Some(monitors)
+    }
+
+    /// Returns handle to the system clipboard.
+    ///
+    /// Handle could be moved into the callback of [`Application::run`].
+    ///
+    pub fn clipboard(&self) -> Clipboard {
+        self.clipboard.clone()
+    }
+
+    /// Returns text contents of the system clipboard.
+    pub fn clipboard_get(&self) -> std::result::Result<String, ClipboardError> {
+        self.clipboard.get()
+    }
+
+    /// Replaces contents of the system clipboard with the text.
+    pub fn clipboard_set(&self, text: &str) -> std::result::Result<(), ClipboardError> {
+        self.clipboard.set(text)
+    }
+
+    /// Adds an overlay which will be drawn each frame after the user UI
+    /// in ascending order of priority.
+    ///
+    /// Overlay with the same name is replaced by the new one.
+    /// Built-in overlays are [`FPS_OVERLAY`], [`STATS_OVERLAY`] and [`RESOURCES_OVERLAY`].
+    ///
+    pub fn add_overlay(
+        &mut self,
+        name: &str,
+        priority: i32,
+        f: impl FnMut(&CtxRef, &FrameStats) + 'static,
+    ) {
+        self.overlays.add(name, priority, Box::new(f));
+    }
+
+    /// Removes an overlay by its name.
+    ///
+    /// Returns `true` if overlay was present.
+    ///
+    pub fn remove_overlay(&mut self, name: &str) -> bool {
+        self.overlays.remove(name)
+    }
+
+    /// Enables or disables an overlay by its name.
+    ///
+    /// Returns `true` if overlay was present.
+    ///
+    pub fn set_overlay_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.overlays.set_enabled(name, enabled)
+    }
+
+    /// Returns `Some(true)` if overlay with the name is present and enabled.
+    pub fn overlay_enabled(&self, name: &str) -> Option<bool> {
+        self.overlays.is_enabled(name)
+    }
+
+    /// Level at which built-in overlays are shown.
+    pub fn overlay_level(&self) -> OverlayLevel {
+        self.overlays.level()
+    }
+
+    /// Shows built-in overlays of given level.
+    ///
+    /// Level is also cycled with the key bound by [`Config::with_overlay_key`]
+    /// and persisted with other engine settings (see [`Config::with_settings_path`]).
+    ///
+    pub fn set_overlay_level(&mut self, level: OverlayLevel) {
+        self.overlays.set_level(level);
+        if let Some(settings) = &mut self.settings {
+            settings.update(|settings| settings.overlay_level = level);
+        }
+    }
+
+    /// Engine settings of the application which are persisted across runs, if any.
+    pub fn settings(&self) -> Option<&EngineSettings> {
+        self.settings.as_ref().map(SettingsStore::settings)
+    }
+
+    /// Records current geometry of the window into the persisted settings.
+    ///
+    /// Size and position are recorded only while the window is neither maximized,
+    /// fullscreen nor minimized, so the window is restored from its normal state.
+    ///
+    fn store_window_geometry(&mut self) {
+        let settings = match &mut self.settings {
+            Some(settings) => settings,
+            None => return,
+        };
+        let size = self.backend.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        let state = self.backend.window().map(|window| {
+            let position = window.outer_position().ok();
+            let position = position.map(|position| [position.x, position.y]);
+            (
+                window.is_maximized(),
+                window.fullscreen().is_some(),
+                position,
+            )
+        });
+        settings.update(|settings| {
+            if let Some((maximized, fullscreen, _)) = state {
+                settings.maximized = maximized;
+                settings.fullscreen = fullscreen;
+            }
+            if settings.maximized || settings.fullscreen {
+                return;
+            }
+            settings.window_size = Some(size);
+            if let Some((_, _, Some(position))) = state {
+                settings.window_position = Some(position);
+            }
+        });
+    }
+
+    /// Pool of threads which is shared by all subsystems of the application.
+    ///
+    /// Pool could be inserted into the ECS world as a resource,
+    /// so parallel systems of the schedule are run on it.
+    ///
+    pub fn task_pool(&self) -> TaskPool {
+        self.tasks.clone()
+    }
+
+    /// Returns handle which pauses rendering of frames or steps them one at a time
+    /// (it could be moved into the callback of [`Application::run`]).
+    ///
+    /// Pause and step buttons are also shown in [`STATS_OVERLAY`],
+    /// and keys could be bound to them with [`Config::with_pause_key`]
+    /// and [`Config::with_step_key`].
+    ///
+    pub fn pause_control(&self) -> PauseControl {
+        self.backend.pause_control()
+    }
+
+    /// Returns handle to the diagnostics server, if it was enabled
+    /// by [`Config::with_diagnostics_port`] and started successfully.
+    pub fn diagnostics(&self) -> Option<DiagnosticsHandle> {
+        self.diagnostics
+            .as_ref()
+            .map(|server| server.handle().clone())
+    }
+
+    /// Applies commands which were received by the diagnostics server
+    /// and replies to the clients with their results.
+    fn apply_diagnostics_commands(&mut self) {
+        let commands = match &self.diagnostics {
+            Some(server) => server.take_commands(),
+            None => return,
+        };
+        for command in commands {
+            let result = self.apply_diagnostics_command(command);
+            if let Err(error) = &result {
+                log::warn!("diagnostics command `{}` failed: {}", command.name(), error);
+            }
+            if let Some(server) = &self.diagnostics {
+                server.reply(command, result);
+            }
+        }
+    }
+
+    fn apply_diagnostics_command(
+        &mut self,
+        command: DiagnosticsCommand,
+    ) -> std::result::Result<(), String> {
+        let pause = self.backend.pause_control();
+        match command {
+            DiagnosticsCommand::Pause => pause.set_paused(true),
+            DiagnosticsCommand::Resume => pause.set_paused(false),
+            DiagnosticsCommand::Step => pause.step_frame(),
+            DiagnosticsCommand::Screenshot => {
+                let ticket = self
+                    .backend
+                    .readback_frame()
+                    .map_err(|error| error.to_string())?;
+                if let Some(server) = &mut self.diagnostics {
+                    server.add_screenshot(ticket);
+                }
+            }
+            DiagnosticsCommand::SetRenderScale(scale) => {
+                if !(MIN_RENDER_SCALE..=MAX_RENDER_SCALE).contains(&scale) {
+                    return Err(format!(
+                        "render scale must be from {} to {}",
+                        MIN_RENDER_SCALE, MAX_RENDER_SCALE,
+                    ));
+                }
+                self.backend
+                    .set_render_scale(scale)
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends statistics of the rendered frame to clients of the diagnostics server.
+    fn publish_diagnostics(&mut self) {
+        let server = match &mut self.diagnostics {
+            Some(server) => server,
+            None => return,
+        };
+        #[cfg(feature = "inspector")]
+        let inspector = &self.inspector;
+        // Snapshot of the world is requested as if the resources overlay was shown.
+        server.publish_frame(self.backend.stats(), || {
+            #[cfg(feature = "inspector")]
+            let entities = inspector.show(|snapshot, _| snapshot.map(|snapshot| snapshot.len()));
+            #[cfg(not(feature = "inspector"))]
+            let entities = None;
+            entities
+        });
+    }
+
+    /// Handle which captures snapshots of the world for [`RESOURCES_OVERLAY`].
+    #[cfg(feature = "inspector")]
+    pub fn world_inspector(&self) -> WorldInspector {
+        self.inspector.clone()
+    }
+
+    /// Grabs (or releases) the cursor if it was requested through [`Input`].
+    fn apply_cursor_grab(&mut self) {
+        let cursor_grab = self.input.cursor_grab();
+        if cursor_grab == self.cursor_grab {
+            return;
+        }
+        if let Err(error) = self.backend.set_cursor_grab(cursor_grab) {
+            log::warn!("cursor grab error: {}", error);
+            return;
+        }
+        self.cursor_grab = cursor_grab;
+    }
+
+    /// Returns handle which requests main loop of the application to exit
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn exit_handle(&self) -> ExitHandle {
+        self.exit_handle.clone()
+    }
+
+    /// Returns handle which tells how far the current frame is between two fixed updates,
+    /// or `None` if fixed timestep is disabled (see [`Config::with_fixed_timestep`]).
+    pub fn fixed_clock(&self) -> Option<FixedClock> {
+        self.fixed_timestep.as_ref().map(FixedTimestep::clock)
+    }
+
+    /// Registers callback which is invoked with new quality in range from 0 to 1
+    /// when it is changed by adaptive quality (at most once per second).
+    ///
+    /// Callback is never invoked if adaptive quality is disabled
+    /// (see [`Config::with_adaptive_quality`]).
+    ///
+    pub fn on_quality_changed(&mut self, callback: impl FnMut(f32) + 'static) {
+        if let Some(quality) = &mut self.quality {
+            quality.on_quality_changed(callback)
+        }
+    }
+
+    /// Returns handle which reads current quality and its recent history
+    /// (it could be moved into the callback of [`Application::run`]).
+    pub fn quality_monitor(&self) -> QualityMonitor {
+        self.quality
+            .as_ref()
+            .map(QualityController::monitor)
+            .unwrap_or_default()
+    }
+
+    /// Feeds time of the frame into adaptive quality
+    /// and applies changed quality to enabled built-in consumers.
+    fn update_quality(&mut self, frame_time: Duration, disturbed: bool) {
+        let (controller, config) = match (&mut self.quality, self.config.adaptive_quality()) {
+            (Some(controller), Some(config)) => (controller, config),
+            _ => return,
+        };
+        let quality = match controller.update(frame_time, disturbed) {
+            Some(quality) => quality,
+            None => return,
+        };
+        if let Some(scale) = config.render_scale(quality) {
+            if let Err(error) = self.backend.set_render_scale(scale) {
+                log::warn!("failed to adapt render scale: {}", error);
+            }
+        }
+        let current = self.backend.antialiasing();
+        let mode = config.antialiasing(quality, current, self.config.antialiasing());
+        if mode != current {
+            if let Err(error) = self.backend.set_antialiasing(mode) {
+                log::warn!("failed to adapt antialiasing mode: {}", error);
+            }
+        }
+    }
+
+    /// Key which was just pressed, ignoring repeated presses of held keys.
+    ///
+    /// Must be called before input state is updated with the event.
+    ///
+    fn pressed_key(&self, input: &KeyboardInput) -> Option<Key> {
+        match input.virtual_keycode.map(Key::from) {
+            Some(key) if input.state == ElementState::Pressed && !self.input.key_pressed(key) => {
+                Some(key)
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles keys which are bound to built-in actions of the engine:
+    /// pauses (or resumes) rendering, steps a frame or cycles levels of built-in overlays.
+    fn handle_bound_keys(&mut self, input: &KeyboardInput) {
+        let key = match self.pressed_key(input) {
+            Some(key) => key,
+            None => return,
+        };
+        if self.config.overlay_key() == Some(key) {
+            self.set_overlay_level(self.overlay_level().next());
+        }
+        let pause = self.backend.pause_control();
+        if self.config.pause_key() == Some(key) {
+            pause.toggle();
+        }
+        if self.config.step_key() == Some(key) {
+            pause.step_frame();
+        }
+    }
+
+    /// Delta time of the update after the frame which took given time.
+    ///
+    /// While paused, delta time is zero or clamped (see [`Config::with_zero_delta_when_paused`]).
+    /// Stepped frames are always clamped, so the game advances one frame at a time.
+    ///
+    fn delta_time(&self, frame_time: Duration, rendered: bool) -> DeltaTime {
+        if !self.backend.pause_control().is_paused() {
+            return frame_time;
+        }
+        if rendered || !self.config.zero_delta_when_paused() {
+            return frame_time.min(MAX_PAUSED_DELTA);
+        }
+        Duration::ZERO
+    }
+
+    /// Exits the main loop with given cause.
+    fn exit(&mut self, control_flow: &mut ControlFlow, cause: ExitCause) {
+        *control_flow = ControlFlow::Exit;
+        self.exit_cause.get_or_insert(cause);
+    }
+
+    /// Handles event of the main loop.
+    fn handle_event(
+        &mut self,
+        event: Event<'_, ()>,
+        control_flow: &mut ControlFlow,
+        callback: &mut impl FnMut(MyEvent),
+    ) {
+        if *control_flow == ControlFlow::Exit {
+            // Main loop is exiting, so only its destruction is handled.
+            if !matches!(event, Event::LoopDestroyed) {
+                return;
+            }
+        } else {
+            *control_flow = ControlFlow::Poll;
+        }
+
+        // Take `Platform` object from `self` to workaround about borrow checker.
+        let mut egui = self.egui.take().unwrap();
+        self.handle_event_with(&mut egui, event, control_flow, callback);
+        // Assign `Platform` object back to `self`.
+        self.egui = Some(egui);
+    }
+
+    /// Handles event of the secondary window.
+    fn handle_window_event(
+        &mut self,
+        id: WindowId,
+        event: &WindowEvent<'_>,
+        callback: &mut impl FnMut(MyEvent),
+    ) {
+        let event = match event {
+            WindowEvent::Resized(size) => {
+                self.backend.request_window_resize(id);
+                MyWindowEvent::Resized((size.width, size.height).into())
+            }
+            WindowEvent::CloseRequested => MyWindowEvent::CloseRequested,
+            _ => return,
+        };
+        callback(MyEvent::Window { id, event });
+    }
+
+    /// Handles event of the main loop with `Platform` object taken from `self`.
+    fn handle_event_with(
+        &mut self,
+        egui: &mut Platform,
+        event: Event<'_, ()>,
+        control_flow: &mut ControlFlow,
+        callback: &mut impl FnMut(MyEvent),
+    ) {
+        // Only events of the main window are handled by UI and input.
+        if let Event::WindowEvent { window_id, event } = &event {
+            if let Some(id) = self.backend.find_window(*window_id) {
+                self.handle_window_event(id, event, callback);
+                return;
+            }
+        }
+        egui.handle_event(&event);
+        egui.update_time(self.start_time.elapsed().as_secs_f64());
+        match &event {
+            Event::WindowEvent { event, .. } => {
+                if let WindowEvent::KeyboardInput { input, .. } = event {
+                    self.handle_bound_keys(input);
+                }
+                self.input.handle_window_event(event)
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => self.input.handle_mouse_motion(*delta),
+            _ => (),
+        }
+
+        let id = self.backend.window_id();
+        match event {
+            Event::NewEvents(StartCause::Init) => {
+                self.started = true;
+                self.start_time = Instant::now();
+                callback(MyEvent::Created);
+                self.backend.set_visible(true);
+                let monitors = self.monitors();
+                self.monitor_ids = monitors.iter().map(|monitor| monitor.id).collect();
+                self.display.set_monitors(monitors);
+                if let Some(window) = self.backend.window() {
+                    self.display.set_mode(monitor::fullscreen_mode(window));
+                }
+                self.store_window_geometry();
+            }
+            Event::WindowEvent { event, window_id } if window_id == id => {
+                match event {
+                    WindowEvent::CloseRequested => self.exit(control_flow, ExitCause::WindowClosed),
+                    WindowEvent::Resized(size) => {
                         if size.width == 0 || size.height == 0 {
+                            callback(MyEvent::Resized(Size::default()));
                             return;
                         }
-                        window.request_redraw();
+                        self.backend.request_resize();
+                        // Keep presenting frames while window is being resized.
+                        self.backend.request_redraw();
+                        self.store_window_geometry();
+                        // Fullscreen could also be exited by the platform (for example, by a hotkey).
+                        if let Some(window) = self.backend.window() {
+                            self.display.set_mode(monitor::fullscreen_mode(window));
+                        }
+                        let size = (size.width, size.height);
+                        callback(MyEvent::Resized(size.into()));
                     }
-                    Event::RedrawRequested(window_id) if window_id == window.id() => {
-                        let size = window.inner_size();
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    } => {
+                        // UI is rescaled by `Platform`, and renderer takes
+                        // scale factor from the window on each frame.
+                        let size = *new_inner_size;
+                        let new_size = (size.width, size.height).into();
+                        callback(MyEvent::ScaleFactorChanged {
+                            scale: scale_factor,
+                            new_size,
+                        });
                         if size.width == 0 || size.height == 0 {
+                            callback(MyEvent::Resized(Size::default()));
                             return;
                         }
-                        let frame_start = Instant::now();
-
-                        egui.begin_frame();
-                        let context = egui.context();
-                        callback(MyEvent::UI(context.clone()));
-                        let (_output, shapes) = egui.end_frame(Some(window));
-                        let meshes = context.tessellate(shapes);
-                        let texture = context.texture();
-
-                        if let Err(error) = self.renderer.render(Some((meshes, texture))) {
-                            log::error!("rendering error: {}", error);
-                            *control_flow = ControlFlow::Exit;
-                            return;
-                        }
-                        let delta_time = Instant::now().duration_since(frame_start);
-                        callback(MyEvent::Update(delta_time));
-
-                        let ubo = {
-                            let duration = Instant::now().duration_since(start_time);
-                            let elapsed = duration.as_millis() as f32;
-
-                            use ultraviolet::projection::perspective_vk as perspective;
-                            let projection = perspective(
-                                45f32.to_radians(),
-                                (size.width as f32) / (size.height as f32),
-                                1.0,
-                                10.0,
-                            );
-                            let model = Mat4::from_rotation_z(elapsed * 0.1f32.to_radians());
-                            let view = Mat4::look_at(
-                                Vec3::new(2.0, 2.0, 2.0),
-                                Vec3::zero(),
-                                Vec3::unit_z(),
-                            );
-                            CameraUBO::new(projection, model, view)
-                        };
-                        self.renderer.set_camera_ubo(ubo);
-                    }
-                    Event::LoopDestroyed => {
-                        callback(MyEvent::Destroyed);
-                        log::info!("closing this application");
+                        self.backend.request_resize();
+                        self.backend.request_redraw();
+                        callback(MyEvent::Resized(new_size));
                     }
+                    WindowEvent::Moved(_) => self.store_window_geometry(),
+                    WindowEvent::DroppedFile(path) => callback(MyEvent::DroppedFile(path)),
+                    WindowEvent::HoveredFile(path) => callback(MyEvent::HoveredFile(path)),
+                    WindowEvent::HoveredFileCancelled => callback(MyEvent::HoveredFileCancelled),
                     _ => (),
                 }
-            };
-            action();
+            }
+            Event::MainEventsCleared => {
+                if self.exit_handle.requested() {
+                    self.exit(control_flow, ExitCause::Requested);
+                    return;
+                }
+                if let Some(monitors) = self.poll_monitors() {
+                    self.display.set_monitors(monitors.clone());
+                    callback(MyEvent::MonitorsChanged(monitors));
+                }
+                self.apply_display_request(callback);
+                self.poll_shaders();
+                #[cfg(feature = "gamepad")]
+                if let Some(gamepads) = &mut self.gamepads {
+                    for event in gamepads.poll() {
+                        callback(event);
+                    }
+                }
+                if let Some(assets) = &self.assets {
+                    for event in assets.take_events() {
+                        callback(MyEvent::Asset(event));
+                    }
+                }
+                // Events are also delivered here if the main loop is not driven by the platform.
+                self.deliver_custom_events(callback);
+                if let Some(settings) = &mut self.settings {
+                    settings.save_if_due();
+                }
+                // Commands are applied before the frame, so pauses and steps take effect in it.
+                self.apply_diagnostics_commands();
+                let size = self.backend.inner_size();
+                if size.width == 0 || size.height == 0 {
+                    return;
+                }
+                self.backend.request_redraw();
+            }
+            Event::RedrawRequested(window_id) if window_id == id => {
+                let size = self.backend.inner_size();
+                if size.width == 0 || size.height == 0 {
+                    return;
+                }
+                // Time which was slept by the limiter is a part of delta time, not of frame time.
+                let waited = match &mut self.frame_limiter {
+                    Some(limiter) => limiter.wait(),
+                    None => Duration::ZERO,
+                };
+                let frame_start = Instant::now();
 
-            // Assign `Platform` object back to `self`.
-            self.egui = Some(egui);
-        })
+                egui.begin_frame();
+                let context = egui.context();
+                callback(MyEvent::UI(context.clone()));
+                self.overlays.draw(&context, self.backend.stats());
+                // Copying and pasting of UI are handled by `Platform` itself.
+                let (_, shapes) = egui.end_frame(self.backend.window());
+                let meshes = context.tessellate(shapes);
+                let texture = context.texture();
+
+                let stats = self.backend.stats();
+                let counters = (stats.frames_skipped, stats.swapchain_recreations);
+                let frames = stats.frames;
+                if let Err(error) = self.backend.render((meshes, texture)) {
+                    log::error!("rendering error: {}", error);
+                    self.exit(control_flow, ExitCause::Error(error));
+                    return;
+                }
+                // Secondary windows are not critical, so their errors do not exit the main loop.
+                if let Err(error) = self.backend.render_windows() {
+                    log::error!("rendering error of secondary window: {}", error);
+                }
+                let stats = self.backend.stats();
+                let rendered = stats.frames != frames;
+                // Hitches of skipped frames, swapchain recreations and pauses are not measured.
+                let disturbed = !rendered
+                    || counters != (stats.frames_skipped, stats.swapchain_recreations)
+                    || self.backend.pause_control().is_paused();
+                let frame_time = Instant::now().duration_since(frame_start);
+                let delta_time = self.delta_time(frame_time + waited, rendered);
+                if rendered {
+                    self.publish_diagnostics();
+                }
+                self.update_quality(frame_time, disturbed);
+                callback(MyEvent::Input(self.input.clone()));
+                if let Some(timestep) = &mut self.fixed_timestep {
+                    let steps = timestep.advance(delta_time);
+                    for _ in 0..steps {
+                        callback(MyEvent::FixedUpdate(timestep.step()));
+                    }
+                }
+                callback(MyEvent::Update(delta_time));
+                self.input.end_frame();
+                self.apply_cursor_grab();
+            }
+            Event::UserEvent(()) => self.deliver_custom_events(callback),
+            Event::LoopDestroyed => {
+                callback(MyEvent::Destroyed);
+                if let Some(settings) = &mut self.settings {
+                    settings.flush();
+                }
+                if let Err(error) = self.backend.save_pipeline_cache() {
+                    log::warn!("failed to save pipeline cache: {}", error);
+                }
+                // Server thread is one of tasks, so it is stopped before the pool.
+                if let Some(server) = &self.diagnostics {
+                    server.shutdown();
+                }
+                // Still running tasks are logged by the pool.
+                self.tasks.shutdown(TASKS_SHUTDOWN_TIMEOUT);
+                log::info!("closing this application");
+            }
+            _ => (),
+        }
     }
 }
 
+/// Events of the window which were received between items of startup.
+#[derive(Debug, Copy, Clone, Default)]
+struct StartupEvents {
+    close_requested: bool,
+    resized: bool,
+}
+
+/// Handles pending events of the window without running the main loop.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn pump_startup_events(event_loop: &mut EventLoop<()>) -> StartupEvents {
+    use winit::platform::run_return::EventLoopExtRunReturn;
+
+    let mut events = StartupEvents::default();
+    event_loop.run_return(|event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => events.close_requested = true,
+                WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                    events.resized = true
+                }
+                _ => (),
+            },
+            Event::MainEventsCleared => *control_flow = ControlFlow::Exit,
+            _ => (),
+        }
+    });
+    events
+}
+
+/// Events could not be handled outside of the main loop on this platform.
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+fn pump_startup_events(_event_loop: &mut EventLoop<()>) -> StartupEvents {
+    StartupEvents::default()
+}
+
 /// Creates a unique [`Application`] instance.
-/// If application instance was created earlier, function call will return an error.
+/// If application instance was created earlier and was not torn down
+/// by [`Application::run_until_exit`], function call will return an error.
 ///
 /// # Errors
 ///
@@ -216,21 +1861,15 @@ pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
 /// This function could panic if invoked **not on main thread**.
 ///
 pub fn init(config: Config) -> Result<Application> {
-    static FLAG: AtomicBool = AtomicBool::new(false);
-    const UNINITIALIZED: bool = false;
-    const INITIALIZED: bool = true;
-
-    let initialized = FLAG
-        .compare_exchange(
-            UNINITIALIZED,
-            INITIALIZED,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        )
-        .unwrap();
-
-    if initialized {
+    if INITIALIZED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
         return Err(AppCreationError::Initialized);
     }
-    Application::new(config)
+    let application = Application::new(config);
+    if application.is_err() {
+        INITIALIZED.store(false, Ordering::SeqCst);
+    }
+    application
 }
@@ -0,0 +1,489 @@
+//! Utilities for UI overlays which are drawn on top of the user UI.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use egui::{pos2, vec2, Align2, Area, Button, CtxRef, Sense, Shape, Ui, Window};
+
+#[cfg(feature = "inspector")]
+use super::inspector::{VisibilityView, WorldInspector};
+use crate::graphics::{FrameStats, PauseControl, QualityMonitor};
+
+/// Name of built-in overlay which shows FPS in the top right corner of the window.
+pub const FPS_OVERLAY: &str = "fps";
+
+/// Name of built-in overlay which shows FPS and other frame statistics
+/// with buttons which pause rendering and step frames.
+pub const STATS_OVERLAY: &str = "stats";
+
+/// Count of the last frames which times are shown on the graph of [`STATS_OVERLAY`].
+pub(crate) const FRAME_TIME_HISTORY: usize = 120;
+
+/// Name of built-in overlay which lists live graphics objects with their keys
+/// (and entities of the world, if `WorldInspector` is updated).
+pub const RESOURCES_OVERLAY: &str = "resources";
+
+/// Level of built-in overlays, which is cycled with the key bound by
+/// [`Config::with_overlay_key`](crate::config::Config::with_overlay_key).
+///
+/// Each level shows one of built-in overlays, and overlays which are not shown
+/// are not drawn at all.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OverlayLevel {
+    /// No built-in overlays are shown.
+    Off,
+    /// Only FPS is shown in the corner of the window ([`FPS_OVERLAY`]).
+    Fps,
+    /// Window with frame statistics is shown ([`STATS_OVERLAY`]).
+    Stats,
+    /// Window with live resources is shown ([`RESOURCES_OVERLAY`]).
+    Resources,
+}
+
+impl OverlayLevel {
+    /// Level which follows this one when levels are cycled.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Fps,
+            Self::Fps => Self::Stats,
+            Self::Stats => Self::Resources,
+            Self::Resources => Self::Off,
+        }
+    }
+
+    /// Name of the level in the settings file.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Fps => "fps",
+            Self::Stats => "stats",
+            Self::Resources => "resources",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let levels = [Self::Off, Self::Fps, Self::Stats, Self::Resources];
+        levels.into_iter().find(|level| level.name() == name)
+    }
+}
+
+impl Default for OverlayLevel {
+    fn default() -> Self {
+        Self::Stats
+    }
+}
+
+/// Type of closure which draws an overlay.
+pub type OverlayFn = Box<dyn FnMut(&CtxRef, &FrameStats)>;
+
+struct Overlay {
+    name: String,
+    priority: i32,
+    enabled: bool,
+    draw: OverlayFn,
+}
+
+/// Collection of overlays sorted by their priority.
+#[derive(Default)]
+pub(crate) struct Overlays {
+    overlays: Vec<Overlay>,
+    level: OverlayLevel,
+}
+
+impl Overlays {
+    /// Creates collection with built-in overlays which are shown at given level.
+    pub fn with_builtins(
+        level: OverlayLevel,
+        pause: PauseControl,
+        quality: QualityMonitor,
+        #[cfg(feature = "inspector")] inspector: WorldInspector,
+    ) -> Self {
+        let mut overlays = Self::default();
+        overlays.add(FPS_OVERLAY, i32::MAX - 2, self::fps_overlay());
+        overlays.add(
+            STATS_OVERLAY,
+            i32::MAX - 1,
+            self::stats_overlay(pause, quality),
+        );
+        overlays.add(
+            RESOURCES_OVERLAY,
+            i32::MAX,
+            self::resources_overlay(
+                #[cfg(feature = "inspector")]
+                inspector,
+            ),
+        );
+        overlays.set_level(level);
+        overlays
+    }
+
+    /// Level at which built-in overlays are shown.
+    pub fn level(&self) -> OverlayLevel {
+        self.level
+    }
+
+    /// Shows built-in overlays of given level, hiding all other built-in overlays.
+    pub fn set_level(&mut self, level: OverlayLevel) {
+        self.level = level;
+        self.set_enabled(FPS_OVERLAY, level == OverlayLevel::Fps);
+        self.set_enabled(STATS_OVERLAY, level == OverlayLevel::Stats);
+        self.set_enabled(RESOURCES_OVERLAY, level == OverlayLevel::Resources);
+    }
+
+    /// Adds an overlay, replacing existing one with the same name.
+    ///
+    /// Overlays with the same priority are drawn in order of their addition.
+    pub fn add(&mut self, name: &str, priority: i32, draw: OverlayFn) {
+        self.remove(name);
+        let index = self
+            .overlays
+            .iter()
+            .position(|overlay| overlay.priority > priority)
+            .unwrap_or(self.overlays.len());
+        let overlay = Overlay {
+            name: name.to_string(),
+            priority,
+            enabled: true,
+            draw,
+        };
+        self.overlays.insert(index, overlay);
+    }
+
+    /// Removes an overlay by its name.
+    ///
+    /// Returns `true` if overlay was present.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.overlays.len();
+        self.overlays.retain(|overlay| overlay.name != name);
+        self.overlays.len() != len
+    }
+
+    /// Enables or disables an overlay by its name.
+    ///
+    /// Returns `true` if overlay was present.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self
+            .overlays
+            .iter_mut()
+            .find(|overlay| overlay.name == name)
+        {
+            Some(overlay) => {
+                overlay.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `Some(true)` if overlay with the name is present and enabled.
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.overlays
+            .iter()
+            .find(|overlay| overlay.name == name)
+            .map(|overlay| overlay.enabled)
+    }
+
+    /// Draws all enabled overlays in ascending order of their priority.
+    pub fn draw(&mut self, context: &CtxRef, stats: &FrameStats) {
+        let overlays = self.overlays.iter_mut().filter(|overlay| overlay.enabled);
+        for overlay in overlays {
+            (overlay.draw)(context, stats);
+        }
+    }
+}
+
+/// Times of the last rendered frames (in milliseconds), oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct FrameTimes {
+    times: VecDeque<f32>,
+    last_frames: u64,
+}
+
+impl FrameTimes {
+    /// Records time of the last frame, if it was not recorded yet.
+    ///
+    /// Only last [`FRAME_TIME_HISTORY`] frames are kept.
+    ///
+    pub fn record(&mut self, stats: &FrameStats) {
+        if stats.frames == self.last_frames {
+            return;
+        }
+        self.last_frames = stats.frames;
+        if self.times.len() == FRAME_TIME_HISTORY {
+            self.times.pop_front();
+        }
+        self.times
+            .push_back(stats.frame_time.as_secs_f32() * 1000.0);
+    }
+
+    /// Recorded frame times, oldest first.
+    pub fn times(&self) -> impl ExactSizeIterator<Item = f32> + '_ {
+        self.times.iter().copied()
+    }
+
+    /// The longest of recorded frame times, or zero if nothing was recorded.
+    pub fn max(&self) -> f32 {
+        self.times().fold(0.0, f32::max)
+    }
+}
+
+/// Draws graph of frame times, scaled so the longest frame reaches the top.
+fn frame_time_graph(ui: &mut Ui, frame_times: &FrameTimes) {
+    const SIZE: [f32; 2] = [2.0 * FRAME_TIME_HISTORY as f32, 48.0];
+
+    let (rect, _) = ui.allocate_exact_size(vec2(SIZE[0], SIZE[1]), Sense::hover());
+    let visuals = &ui.visuals().widgets.noninteractive;
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, visuals.bg_stroke);
+    let max = frame_times.max();
+    if max <= 0.0 {
+        return;
+    }
+    let step = rect.width() / (FRAME_TIME_HISTORY - 1) as f32;
+    let points = frame_times
+        .times()
+        .enumerate()
+        .map(|(index, time)| {
+            let x = rect.left() + index as f32 * step;
+            pos2(x, rect.bottom() - time / max * rect.height())
+        })
+        .collect();
+    painter.add(Shape::line(points, visuals.fg_stroke));
+}
+
+/// Creates overlay which shows FPS in the top right corner of the window.
+///
+/// Text is formatted once per update period rather than on each frame.
+///
+fn fps_overlay() -> OverlayFn {
+    const UPDATE_PERIOD: Duration = Duration::from_millis(500);
+
+    let mut last_update = Instant::now();
+    let mut last_frames = 0;
+    let mut text = String::from("FPS: -");
+    let draw = move |context: &CtxRef, stats: &FrameStats| {
+        let elapsed = last_update.elapsed();
+        if elapsed >= UPDATE_PERIOD {
+            let frames = stats.frames.saturating_sub(last_frames);
+            let fps = frames as f64 / elapsed.as_secs_f64();
+            text = format!("FPS: {:.0}", fps);
+            last_frames = stats.frames;
+            last_update = Instant::now();
+        }
+
+        Area::new(FPS_OVERLAY)
+            .anchor(Align2::RIGHT_TOP, [-8.0, 8.0])
+            .interactable(false)
+            .show(context, |ui| {
+                ui.label(text.as_str());
+            });
+    };
+    Box::new(draw)
+}
+
+/// Creates overlay which shows FPS, graph of frame times and other frame statistics
+/// with buttons which pause rendering and step frames.
+fn stats_overlay(pause: PauseControl, quality: QualityMonitor) -> OverlayFn {
+    const UPDATE_PERIOD: Duration = Duration::from_secs(1);
+
+    let mut last_update = Instant::now();
+    let mut last_frames = 0;
+    let mut fps = 0.0;
+    let mut frame_times = FrameTimes::default();
+    let draw = move |context: &CtxRef, stats: &FrameStats| {
+        frame_times.record(stats);
+        let elapsed = last_update.elapsed();
+        if elapsed >= UPDATE_PERIOD {
+            let frames = stats.frames.saturating_sub(last_frames);
+            fps = frames as f64 / elapsed.as_secs_f64();
+            last_frames = stats.frames;
+            last_update = Instant::now();
+        }
+
+        Window::new("Stats").resizable(false).show(context, |ui| {
+            ui.horizontal(|ui| {
+                let paused = pause.is_paused();
+                if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                    pause.toggle();
+                }
+                if ui.add(Button::new("Step").enabled(paused)).clicked() {
+                    pause.step_frame();
+                }
+            });
+            ui.label(format!("FPS: {:.1}", fps));
+            let frame_time = stats.frame_time.as_secs_f64() * 1000.0;
+            ui.label(format!(
+                "frame time: {:.3} ms (max {:.3} ms)",
+                frame_time,
+                frame_times.max(),
+            ));
+            self::frame_time_graph(ui, &frame_times);
+            ui.label(format!("frames: {}", stats.frames));
+            let recreations = stats.swapchain_recreations;
+            ui.label(format!("swapchain recreations: {}", recreations));
+            let draws: u32 = stats.viewport_draws.iter().sum();
+            ui.label(format!(
+                "draw calls: {} ({:?} per viewport)",
+                draws, stats.viewport_draws,
+            ));
+            let memory = &stats.memory;
+            ui.label(format!(
+                "device memory: {} B of {} B in {} block(s), peak {} B",
+                memory.used_bytes, memory.reserved_bytes, memory.blocks, memory.peak_used_bytes,
+            ));
+            if let Some(value) = quality.quality() {
+                let history = quality.history();
+                let min = history.iter().copied().fold(value, f32::min);
+                let max = history.iter().copied().fold(value, f32::max);
+                ui.label(format!(
+                    "quality: {:.2} (from {:.2} to {:.2} in {} frames)",
+                    value,
+                    min,
+                    max,
+                    history.len(),
+                ));
+            }
+            let culling = stats.culling;
+            ui.label(format!(
+                "entities: {} submitted, {} culled, {} hidden",
+                culling.submitted, culling.culled, culling.hidden,
+            ));
+            let streaming = &stats.streaming;
+            ui.label(format!(
+                "streaming: {} B uploaded, {} B evicted, {} queued",
+                streaming.uploaded_bytes, streaming.evicted_bytes, streaming.queue_depth,
+            ));
+            let transient = &stats.transient;
+            ui.label(format!(
+                "transient: {} B of {} B in {} block(s)",
+                transient.bytes, transient.capacity, transient.blocks,
+            ));
+            if let Some(pipeline_stats) = &stats.pipeline_stats {
+                ui.label(format!(
+                    "vertices: {}, primitives: {}",
+                    pipeline_stats.input_assembly_vertices,
+                    pipeline_stats.input_assembly_primitives,
+                ));
+                ui.label(format!(
+                    "vertex shader invocations: {}",
+                    pipeline_stats.vertex_shader_invocations,
+                ));
+                ui.label(format!(
+                    "clipped primitives: {}",
+                    pipeline_stats.clipping_primitives,
+                ));
+                ui.label(format!(
+                    "fragment shader invocations: {}",
+                    pipeline_stats.fragment_shader_invocations,
+                ));
+            }
+            if let Some(gpu_timings) = &stats.gpu_timings {
+                let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+                ui.label(format!(
+                    "GPU time: {:.3} ms (main pass {:.3} ms, post-process {:.3} ms)",
+                    millis(gpu_timings.total()),
+                    millis(gpu_timings.main_pass),
+                    millis(gpu_timings.post_pass),
+                ));
+            }
+        });
+    };
+    Box::new(draw)
+}
+
+/// Tab of the resource inspector.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum InspectorTab {
+    Resources,
+    #[cfg(feature = "inspector")]
+    World,
+}
+
+/// Creates overlay which lists live graphics objects per type with their keys
+/// and, in the second tab, entities of the world with their components.
+fn resources_overlay(#[cfg(feature = "inspector")] inspector: WorldInspector) -> OverlayFn {
+    #[cfg_attr(not(feature = "inspector"), allow(unused_mut))]
+    let mut tab = InspectorTab::Resources;
+    let draw = move |context: &CtxRef, stats: &FrameStats| {
+        Window::new("Resources").show(context, |ui| {
+            #[cfg(feature = "inspector")]
+            {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut tab, InspectorTab::Resources, "Resources");
+                    ui.selectable_value(&mut tab, InspectorTab::World, "World");
+                });
+                ui.separator();
+            }
+            match tab {
+                InspectorTab::Resources => self::resources_tab(ui, stats),
+                #[cfg(feature = "inspector")]
+                InspectorTab::World => {
+                    inspector.show(|snapshot, visibility| self::world_tab(ui, snapshot, visibility))
+                }
+            }
+        });
+    };
+    Box::new(draw)
+}
+
+/// Lists live graphics objects per type with their keys.
+fn resources_tab(ui: &mut Ui, stats: &FrameStats) {
+    for resources in &stats.resources {
+        let heading = format!("{} ({})", resources.name, resources.keys.len());
+        ui.collapsing(heading, |ui| {
+            for key in &resources.keys {
+                ui.label(key.as_str());
+            }
+        });
+    }
+}
+
+/// Lists entities of the world snapshot with their components.
+///
+/// Only first entities are listed, so huge worlds do not slow down the UI.
+/// Visibility of each entity could be toggled with its checkbox.
+///
+#[cfg(feature = "inspector")]
+fn world_tab(
+    ui: &mut Ui,
+    snapshot: Option<&titan_ecs::WorldSnapshot>,
+    mut visibility: VisibilityView,
+) {
+    use egui::{CollapsingHeader, ScrollArea};
+    use slotmap::Key;
+
+    const MAX_ENTITIES: usize = 1000;
+
+    let snapshot = match snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            ui.label("no snapshot: call WorldInspector::update on each update");
+            return;
+        }
+    };
+    ui.label(format!("entities: {}", snapshot.len()));
+    ScrollArea::auto_sized().show(ui, |ui| {
+        for entity in snapshot.entities.iter().take(MAX_ENTITIES) {
+            let key = format!("{:?}", entity.entity.data());
+            let heading = match &entity.name {
+                Some(name) => format!("{} ({})", name, key),
+                None => key,
+            };
+            CollapsingHeader::new(heading)
+                .id_source(entity.entity)
+                .show(ui, |ui| {
+                    let mut visible = visibility.is_visible(entity.entity);
+                    if ui.checkbox(&mut visible, "visible").changed() {
+                        visibility.set_visible(entity.entity, visible);
+                    }
+                    for component in &entity.components {
+                        ui.label(*component);
+                    }
+                });
+        }
+        if snapshot.len() > MAX_ENTITIES {
+            ui.label(format!("... and {} more", snapshot.len() - MAX_ENTITIES));
+        }
+    });
+}
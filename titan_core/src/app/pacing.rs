@@ -0,0 +1,45 @@
+//! Limiting of frame rate on the CPU side.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Limiter which spaces beginnings of frames by the interval of max frame rate.
+///
+/// Frames which begin late are not compensated by the next ones,
+/// so frame rate never bursts above the limit.
+///
+#[derive(Debug)]
+pub(crate) struct FrameLimiter {
+    interval: Duration,
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Creates new limiter with given max frame rate, which must not be zero.
+    pub fn new(max_fps: u32) -> Self {
+        assert_ne!(max_fps, 0, "max frame rate must not be zero");
+        Self {
+            interval: Duration::from_secs(1) / max_fps,
+            next_frame: None,
+        }
+    }
+
+    /// Returns how long to wait from given time before the frame begins,
+    /// scheduling the next frame after it.
+    pub fn delay(&mut self, now: Instant) -> Duration {
+        let start = self
+            .next_frame
+            .map_or(now, |next_frame| next_frame.max(now));
+        self.next_frame = Some(start + self.interval);
+        start - now
+    }
+
+    /// Sleeps until the frame could begin, returning duration of sleep.
+    pub fn wait(&mut self) -> Duration {
+        let delay = self.delay(Instant::now());
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+        delay
+    }
+}
@@ -0,0 +1,134 @@
+//! Progress reporting and cancellation of long-running work during startup.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::config::Config;
+
+/// Phase of startup in which graphics objects of the renderer are created.
+pub const GRAPHICS_PHASE: &str = "graphics";
+
+/// Phase of startup in which files of [`Config::with_preload`] are loaded.
+pub const PRELOAD_PHASE: &str = "preload";
+
+/// Callback which receives progress of startup.
+///
+/// It is called with the name of the current phase, count of items of the phase
+/// which were done and total count of items of the phase: once before the first item
+/// and after each item.
+///
+#[derive(Clone)]
+pub struct ProgressSink(Arc<ProgressFn>);
+
+type ProgressFn = dyn Fn(&str, usize, usize) + Send + Sync;
+
+impl ProgressSink {
+    /// Creates progress sink from the callback.
+    pub fn new(callback: impl Fn(&str, usize, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Reports that `done` of `total` items of the phase were done.
+    pub fn report(&self, phase: &str, done: usize, total: usize) {
+        (self.0)(phase, done, total)
+    }
+}
+
+impl fmt::Debug for ProgressSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressSink").finish_non_exhaustive()
+    }
+}
+
+/// Token which cancels startup of the application.
+///
+/// Token is checked between items of startup, so item which is being processed
+/// is finished before startup is stopped. Clones of the token share its state.
+///
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates token which was not cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of startup.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+
+    /// Returns `true` if cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Error which is returned when startup was cancelled.
+#[derive(Debug, Copy, Clone, Error)]
+#[error("startup was cancelled")]
+pub struct Cancelled;
+
+/// Progress sink and cancellation token of startup of the application.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Startup {
+    sink: Option<ProgressSink>,
+    token: CancellationToken,
+}
+
+impl Startup {
+    pub fn new(sink: Option<ProgressSink>, token: CancellationToken) -> Self {
+        Self { sink, token }
+    }
+
+    /// Startup with progress sink and cancellation token of the configuration.
+    pub fn from_config(config: &Config) -> Self {
+        let token = config.cancellation().cloned().unwrap_or_default();
+        Self::new(config.progress().cloned(), token)
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Reports progress of the phase, then checks whether startup was cancelled.
+    pub fn step(&self, phase: &str, done: usize, total: usize) -> Result<(), Cancelled> {
+        if let Some(sink) = &self.sink {
+            sink.report(phase, done, total);
+        }
+        if self.token.is_cancelled() {
+            log::info!(
+                "startup was cancelled in phase {} at {}/{}",
+                phase,
+                done,
+                total
+            );
+            return Err(Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Processes items of the phase one by one, reporting progress after each of them.
+    ///
+    /// Items which were not processed before cancellation are dropped.
+    ///
+    pub fn run_items<T>(
+        &self,
+        phase: &str,
+        items: impl IntoIterator<Item = T>,
+        mut process: impl FnMut(T),
+    ) -> Result<(), Cancelled> {
+        let items: Vec<_> = items.into_iter().collect();
+        let total = items.len();
+        self.step(phase, 0, total)?;
+        for (index, item) in items.into_iter().enumerate() {
+            process(item);
+            self.step(phase, index + 1, total)?;
+        }
+        Ok(())
+    }
+}
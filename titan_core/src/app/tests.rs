@@ -0,0 +1,619 @@
+#![cfg(test)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ultraviolet::Vec3;
+
+use crate::config::ENGINE_VERSION;
+use crate::graphics::{QualityConfig, QualityMonitor};
+use crate::window::input::Key;
+use crate::window::{WindowDesc, WindowState};
+
+use super::*;
+
+fn application(events: ScriptedEvents) -> Application<NullWindowBackend> {
+    let config = Config::new("test".to_owned(), ENGINE_VERSION.clone(), false);
+    Application::with_null_window(config, events)
+}
+
+/// Short names of events in order of their delivery.
+fn event_names(app: Application<NullWindowBackend>) -> (Vec<&'static str>, ExitReport) {
+    let mut names = Vec::new();
+    let report = app.run_until_exit(|event| {
+        let name = match event {
+            MyEvent::Created => "created",
+            MyEvent::Resized(_) => "resized",
+            MyEvent::UI(_) => "ui",
+            MyEvent::Input(_) => "input",
+            MyEvent::FixedUpdate(_) => "fixed",
+            MyEvent::Update(_) => "update",
+            MyEvent::Destroyed => "destroyed",
+            _ => return,
+        };
+        names.push(name);
+    });
+    (names, report)
+}
+
+#[test]
+fn test_event_order() {
+    let app = application(ScriptedEvents::new().frames(2));
+    let (names, report) = event_names(app);
+    let expected = [
+        "created",
+        "ui",
+        "input",
+        "update",
+        "ui",
+        "input",
+        "update",
+        "destroyed",
+    ];
+    assert_eq!(names, expected);
+    assert!(matches!(report.cause, ExitCause::Requested));
+    assert!(report.is_clean());
+}
+
+#[test]
+fn test_fixed_timestep() {
+    let step = Duration::from_millis(10);
+    let mut timestep = timestep::FixedTimestep::new(step);
+    let clock = timestep.clock();
+    assert_eq!(clock.step(), step);
+
+    assert_eq!(timestep.advance(Duration::from_millis(4)), 0);
+    assert!((clock.alpha() - 0.4).abs() < 1e-5);
+    // Time of the previous frame is accumulated.
+    assert_eq!(timestep.advance(Duration::from_millis(21)), 2);
+    assert!((clock.alpha() - 0.5).abs() < 1e-5);
+
+    // Time which could not be caught up is dropped.
+    let steps = timestep.advance(step * (MAX_FIXED_STEPS + 5));
+    assert_eq!(steps, MAX_FIXED_STEPS);
+    assert_eq!(clock.alpha(), 0.0);
+    assert_eq!(timestep.advance(Duration::from_millis(9)), 0);
+}
+
+#[test]
+fn test_fixed_update_events() {
+    let app = application(ScriptedEvents::new().frames(2));
+    assert!(app.fixed_clock().is_none());
+    let (names, _) = event_names(app);
+    assert!(!names.contains(&"fixed"));
+
+    // Any frame is longer than the step, so fixed updates are run on each frame.
+    let config = Config::new("test".to_owned(), ENGINE_VERSION.clone(), false)
+        .with_fixed_timestep(Duration::from_nanos(1));
+    let app = Application::with_null_window(config, ScriptedEvents::new().frames(2));
+    assert!(app.fixed_clock().is_some());
+    let (names, _) = event_names(app);
+    let frames: Vec<_> = names
+        .split(|&name| name == "update")
+        .map(|frame| frame.iter().filter(|&&name| name == "fixed").count())
+        .collect();
+    assert_eq!(frames.len(), 3);
+    for &count in &frames[..2] {
+        assert!(count > 0 && count <= MAX_FIXED_STEPS as usize);
+    }
+    // Fixed updates are run between input and update.
+    let input = names.iter().position(|&name| name == "input").unwrap();
+    assert_eq!(names[input + 1], "fixed");
+}
+
+#[test]
+fn test_frame_limiter() {
+    let mut limiter = FrameLimiter::new(50);
+    let interval = Duration::from_millis(20);
+    let start = Instant::now();
+    // First frame begins right away.
+    assert_eq!(limiter.delay(start), Duration::ZERO);
+    let now = start + Duration::from_millis(5);
+    assert_eq!(limiter.delay(now), interval - Duration::from_millis(5));
+
+    // Late frame begins right away and is not compensated by the next one.
+    let late = start + Duration::from_millis(100);
+    assert_eq!(limiter.delay(late), Duration::ZERO);
+    assert_eq!(limiter.delay(late), interval);
+}
+
+#[test]
+fn test_close_stops_frames() {
+    let app = application(ScriptedEvents::new().frames(1).close().frames(3));
+    let (names, report) = event_names(app);
+    let updates = names.iter().filter(|&&name| name == "update").count();
+    assert_eq!(updates, 1);
+    assert_eq!(names.last(), Some(&"destroyed"));
+    assert!(matches!(report.cause, ExitCause::WindowClosed));
+}
+
+#[test]
+fn test_exit_handle() {
+    let app = application(ScriptedEvents::new().frames(5));
+    let exit_handle = app.exit_handle();
+    let mut updates = 0;
+    let report = app.run_until_exit(|event| {
+        if let MyEvent::Update(_) = event {
+            updates += 1;
+            if updates == 2 {
+                exit_handle.exit();
+            }
+        }
+    });
+    assert_eq!(updates, 2);
+    assert!(matches!(report.cause, ExitCause::Requested));
+}
+
+#[test]
+fn test_resize() {
+    let events = ScriptedEvents::new()
+        .resize(Size::new(1024, 768))
+        .frames(1)
+        .resize(Size::new(0, 0))
+        .frames(1);
+    let app = application(events);
+    let mut sizes = Vec::new();
+    let mut updates = 0;
+    app.run_until_exit(|event| match event {
+        MyEvent::Resized(size) => sizes.push(size),
+        MyEvent::Update(_) => updates += 1,
+        _ => (),
+    });
+    assert_eq!(sizes, [Size::new(1024, 768), Size::default()]);
+    // Frame is not rendered while the window is minimized.
+    assert_eq!(updates, 1);
+}
+
+#[test]
+fn test_null_window() {
+    let app = application(ScriptedEvents::new().frames(3));
+    assert_eq!(app.window_size(), NULL_WINDOW_SIZE);
+    assert_eq!(app.frame_stats().frames, 0);
+    assert!(app.monitors().is_empty());
+}
+
+#[test]
+fn test_adaptive_quality_disabled() {
+    let app = application(ScriptedEvents::new().frames(1));
+    assert_eq!(app.quality_monitor().quality(), None);
+
+    let config = Config::new("test".to_owned(), ENGINE_VERSION.clone(), false)
+        .with_adaptive_quality(QualityConfig::new(Duration::from_millis(16)));
+    let app = Application::with_null_window(config, ScriptedEvents::new().frames(1));
+    assert_eq!(app.quality_monitor().quality(), Some(1.0));
+}
+
+#[test]
+fn test_pause_and_step() {
+    let events = ScriptedEvents::new()
+        .frames(3)
+        .resize(Size::new(640, 480))
+        .frames(2);
+    let mut app = application(events);
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    {
+        let frames = Rc::clone(&frames);
+        app.add_overlay("frames", 0, move |_, stats| {
+            frames.borrow_mut().push(stats.frames)
+        });
+    }
+    let pause = app.pause_control();
+    let mut deltas = Vec::new();
+    app.run_until_exit(|event| {
+        if let MyEvent::Update(delta_time) = event {
+            deltas.push(delta_time);
+            match deltas.len() {
+                1 => pause.set_paused(true),
+                2 => pause.step_frame(),
+                _ => (),
+            }
+        }
+    });
+    // Stepped frame and the frame after resizing are rendered while paused.
+    assert_eq!(*frames.borrow(), [0, 1, 1, 2, 3]);
+    let zero: Vec<_> = deltas.iter().map(Duration::is_zero).collect();
+    assert_eq!(zero, [false, true, false, false, true]);
+    assert!(deltas
+        .iter()
+        .all(|&delta_time| delta_time <= MAX_PAUSED_DELTA));
+}
+
+#[test]
+fn test_diagnostics_server() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+
+    let config =
+        Config::new("test".to_owned(), ENGINE_VERSION.clone(), false).with_diagnostics_port(0);
+    let app = Application::with_null_window(config, ScriptedEvents::new().frames(5));
+    let diagnostics = app
+        .diagnostics()
+        .expect("diagnostics server should be started");
+    assert!(diagnostics.address().ip().is_loopback());
+    let mut stream = TcpStream::connect(diagnostics.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream.write_all(b"pause\n").unwrap();
+
+    let pause = app.pause_control();
+    let mut frames = Vec::new();
+    app.run_until_exit(|event| {
+        if let MyEvent::Update(_) = event {
+            frames.push(pause.is_paused());
+            // Command is applied before the next frame once the server received it.
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while diagnostics.stats().commands == 0 && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    });
+    assert_eq!(frames, [false, true, true, true, true]);
+    assert!(pause.is_paused());
+
+    // Connection is closed when the application shuts down.
+    let lines: Vec<_> = BufReader::new(stream)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with(r#"{"type":"stats","frames":1,"#)));
+    assert!(lines
+        .iter()
+        .any(|line| line == r#"{"type":"reply","command":"pause","ok":true}"#));
+}
+
+#[test]
+fn test_paused_delta_clamped() {
+    let config = Config::new("test".to_owned(), ENGINE_VERSION.clone(), false)
+        .with_zero_delta_when_paused(false);
+    let app = Application::with_null_window(config, ScriptedEvents::new().frames(3));
+    let pause = app.pause_control();
+    pause.set_paused(true);
+    let mut deltas = Vec::new();
+    app.run_until_exit(|event| {
+        if let MyEvent::Update(delta_time) = event {
+            deltas.push(delta_time);
+        }
+    });
+    assert_eq!(deltas.len(), 3);
+    assert!(deltas
+        .iter()
+        .all(|&delta_time| delta_time <= MAX_PAUSED_DELTA));
+    assert!(deltas.iter().any(|delta_time| !delta_time.is_zero()));
+}
+
+#[test]
+fn test_overlay_order() {
+    let mut app = application(ScriptedEvents::new().frames(1));
+    let drawn = Rc::new(RefCell::new(Vec::new()));
+    for (name, priority) in [("second", 10), ("first", -10)] {
+        let drawn = Rc::clone(&drawn);
+        app.add_overlay(name, priority, move |_, _| drawn.borrow_mut().push(name));
+    }
+    app.run_until_exit(|_| ());
+    assert_eq!(*drawn.borrow(), ["first", "second"]);
+}
+
+#[test]
+fn test_overlay_level_cycle() {
+    let path = std::env::temp_dir().join(format!("titan_settings_{}.ini", std::process::id()));
+    let config =
+        Config::new("test".to_owned(), ENGINE_VERSION.clone(), false).with_settings_path(&path);
+    let events = ScriptedEvents::new()
+        .frames(1)
+        .key(Key::F3)
+        .resize(Size::new(0, 0))
+        .frames(1)
+        .key(Key::F3)
+        .frames(1);
+    let app = Application::with_null_window(config.clone(), events);
+    assert_eq!(app.overlay_level(), OverlayLevel::Stats);
+    assert_eq!(app.overlay_enabled(FPS_OVERLAY), Some(false));
+    // Levels are cycled while the window is minimized and rendering is paused.
+    app.pause_control().set_paused(true);
+    app.run_until_exit(|_| ());
+
+    // Level is restored by the next run.
+    let settings = EngineSettings::load(&path).unwrap();
+    assert_eq!(settings.overlay_level, OverlayLevel::Off);
+    assert_eq!(settings.window_size, Some(NULL_WINDOW_SIZE));
+    let app = Application::with_null_window(config, ScriptedEvents::new().key(Key::F3));
+    assert_eq!(app.overlay_level(), OverlayLevel::Off);
+    assert_eq!(app.overlay_enabled(STATS_OVERLAY), Some(false));
+    app.run_until_exit(|_| ());
+    let settings = EngineSettings::load(&path).unwrap();
+    assert_eq!(settings.overlay_level, OverlayLevel::Fps);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_frame_time_history() {
+    use super::overlay::{FrameTimes, FRAME_TIME_HISTORY};
+
+    let mut frame_times = FrameTimes::default();
+    let mut stats = FrameStats::default();
+    // Nothing is recorded until the first frame is rendered.
+    frame_times.record(&stats);
+    assert_eq!(frame_times.max(), 0.0);
+
+    for frame in 1..=FRAME_TIME_HISTORY as u64 + 10 {
+        stats.frames = frame;
+        stats.frame_time = Duration::from_millis(frame);
+        frame_times.record(&stats);
+        // The same frame is recorded once even if overlay is drawn several times.
+        frame_times.record(&stats);
+    }
+    assert_eq!(frame_times.times().len(), FRAME_TIME_HISTORY);
+    assert_eq!(frame_times.times().next(), Some(11.0));
+    assert_eq!(frame_times.max(), (FRAME_TIME_HISTORY + 10) as f32);
+}
+
+#[test]
+fn test_fps_overlay_corner() {
+    use egui::{ClippedMesh, RawInput, Rect};
+
+    let mut overlays = Overlays::with_builtins(
+        OverlayLevel::Fps,
+        PauseControl::default(),
+        QualityMonitor::default(),
+        #[cfg(feature = "inspector")]
+        WorldInspector::default(),
+    );
+    let (width, height) = (
+        NULL_WINDOW_SIZE.width as f32,
+        NULL_WINDOW_SIZE.height as f32,
+    );
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(
+            Default::default(),
+            [width, height].into(),
+        )),
+        ..Default::default()
+    };
+    let mut context = CtxRef::default();
+    let mut meshes = Vec::new();
+    // Size of the area is known since the second frame.
+    for _ in 0..2 {
+        context.begin_frame(input.clone());
+        overlays.draw(&context, &FrameStats::default());
+        let (_, shapes) = context.end_frame();
+        meshes = context.tessellate(shapes);
+    }
+
+    // Only FPS is drawn, and it is drawn in the top right corner.
+    let positions: Vec<_> = meshes
+        .iter()
+        .flat_map(|ClippedMesh(_, mesh)| &mesh.vertices)
+        .map(|vertex| vertex.pos)
+        .collect();
+    assert!(!positions.is_empty());
+    assert!(positions
+        .iter()
+        .all(|pos| pos.x > width * 0.75 && pos.x <= width && pos.y >= 0.0 && pos.y < height * 0.1));
+}
+
+#[test]
+#[cfg(feature = "inspector")]
+fn test_world_inspector() {
+    use std::time::Duration;
+
+    use titan_ecs::World;
+
+    let app = application(ScriptedEvents::new().frames(1));
+    let inspector = app.world_inspector();
+    let mut world = World::new();
+    world.spawn_named("tank");
+
+    // Snapshot is not captured until the overlay shows it.
+    assert!(!inspector.update(&world));
+    assert!(inspector.show(|snapshot, _| snapshot.is_none()));
+    assert!(inspector.update(&world));
+    assert!(inspector.show(|snapshot, _| snapshot.map(|snapshot| snapshot.len()) == Some(1)));
+
+    // Snapshot is refreshed no more often than the interval.
+    world.spawn();
+    assert_eq!(inspector.interval(), DEFAULT_INSPECTOR_INTERVAL);
+    assert!(!inspector.update(&world));
+    inspector.set_interval(Duration::ZERO);
+    assert!(inspector.update(&world));
+    assert!(inspector.show(|snapshot, _| snapshot.map(|snapshot| snapshot.len()) == Some(2)));
+}
+
+#[test]
+#[cfg(feature = "inspector")]
+fn test_world_inspector_visibility() {
+    use titan_ecs::World;
+
+    use crate::graphics::Visibility;
+
+    let app = application(ScriptedEvents::new().frames(1));
+    let inspector = app.world_inspector();
+    inspector.set_interval(std::time::Duration::ZERO);
+    let mut world = World::new();
+    let tank = world.spawn_named("tank");
+    let turret = world.spawn_named("turret");
+    world.insert(turret, Visibility::HIDDEN);
+    inspector.show(|_, _| ());
+    assert!(inspector.update(&world));
+
+    // Visibility toggled in the overlay is shown before it is applied.
+    inspector.show(|_, mut visibility| {
+        assert!(visibility.is_visible(tank));
+        assert!(!visibility.is_visible(turret));
+        visibility.set_visible(tank, false);
+        visibility.set_visible(turret, true);
+        assert!(!visibility.is_visible(tank));
+    });
+    assert_eq!(world.get::<Visibility>(tank), None);
+    assert_eq!(inspector.apply_visibility(&mut world), 2);
+    assert_eq!(world.get::<Visibility>(tank), Some(&Visibility::HIDDEN));
+    assert_eq!(world.get::<Visibility>(turret), Some(&Visibility::VISIBLE));
+    assert_eq!(inspector.apply_visibility(&mut world), 0);
+    inspector.show(|_, visibility| assert!(visibility.is_visible(turret)));
+}
+
+#[test]
+fn test_startup_cancellation_stops_items() {
+    use std::sync::{Arc, Mutex};
+
+    const SLOW_ITEM: Duration = Duration::from_millis(20);
+
+    let token = CancellationToken::new();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let reports = Arc::clone(&reports);
+        let token = token.clone();
+        ProgressSink::new(move |phase, done, total| {
+            reports
+                .lock()
+                .unwrap()
+                .push((phase.to_owned(), done, total));
+            // The window was closed after the second item.
+            if done == 2 {
+                token.cancel();
+            }
+        })
+    };
+    let config = Config::default()
+        .with_progress(sink)
+        .with_cancellation(token.clone());
+    let startup = Startup::from_config(&config);
+
+    let mut processed = Vec::new();
+    let result = startup.run_items(PRELOAD_PHASE, 0..5, |item| {
+        std::thread::sleep(SLOW_ITEM);
+        processed.push(item);
+    });
+    assert!(result.is_err());
+    assert!(token.is_cancelled());
+    assert_eq!(processed, [0, 1]);
+    {
+        let reports = reports.lock().unwrap();
+        let done: Vec<_> = reports.iter().map(|&(_, done, _)| done).collect();
+        assert_eq!(done, [0, 1, 2]);
+        assert!(reports
+            .iter()
+            .all(|(phase, _, total)| phase == PRELOAD_PHASE && *total == 5));
+    }
+
+    // Cancelled startup does not start the next phase at all.
+    let mut started = false;
+    let result = startup.run_items(GRAPHICS_PHASE, 0..1, |_| started = true);
+    assert!(result.is_err());
+    assert!(!started);
+}
+
+#[test]
+fn test_startup_without_cancellation() {
+    let startup = Startup::new(None, CancellationToken::default());
+    let mut processed = 0;
+    let result = startup.run_items(PRELOAD_PHASE, 0..3, |_| processed += 1);
+    assert!(result.is_ok());
+    assert_eq!(processed, 3);
+}
+
+#[test]
+fn test_active_camera() {
+    let app = application(ScriptedEvents::new().frames(3));
+    let camera = Camera::look_at(Vec3::new(2.0, 2.0, 2.0), Vec3::zero());
+    app.set_active_camera(camera);
+    let active_camera = app.active_camera();
+    assert_eq!(active_camera.get().position, camera.position);
+
+    // Camera is moved by the callback like it would be moved by controllers.
+    let handle = active_camera.clone();
+    app.run_until_exit(move |event| {
+        if let MyEvent::Update(_) = event {
+            let mut camera = handle.get();
+            camera.position.z += 1.0;
+            handle.set(camera);
+        }
+    });
+    assert_eq!(active_camera.get().position, Vec3::new(2.0, 2.0, 5.0));
+}
+
+#[test]
+fn test_secondary_windows() {
+    let app = application(ScriptedEvents::new().frames(3));
+    let manager = app.window_manager();
+    let first = manager.create(WindowDesc::new("tools"));
+    let events = Rc::new(RefCell::new(Vec::new()));
+
+    // Window is closed by the callback in response to its creation.
+    let recorded = events.clone();
+    app.run_until_exit(move |event| {
+        if let MyEvent::Window { id, event } = event {
+            recorded.borrow_mut().push((id, event));
+            if event == MyWindowEvent::Created {
+                assert_eq!(manager.state(id), Some(WindowState::Open));
+                manager.close(id);
+            }
+        }
+    });
+    let events = events.borrow();
+    assert_eq!(
+        *events,
+        [
+            (first, MyWindowEvent::Created),
+            (first, MyWindowEvent::Closed),
+        ]
+    );
+}
+
+#[test]
+fn test_custom_events() {
+    let app = application(ScriptedEvents::new().frames(2));
+    let proxy = app.event_proxy();
+    std::thread::spawn(move || {
+        proxy.send(7_u32).unwrap();
+        proxy.send("loaded").unwrap();
+    })
+    .join()
+    .unwrap();
+
+    let mut received = Vec::new();
+    app.run_until_exit(|event| {
+        if let MyEvent::Custom(event) = event {
+            match event.downcast::<u32>() {
+                Ok(number) => received.push(number.to_string()),
+                Err(event) => received.push(event.downcast::<&str>().unwrap().to_string()),
+            }
+        }
+    });
+    // Events are delivered once in order of sending.
+    assert_eq!(received, ["7", "loaded"]);
+}
+
+/// Checks that frames of headless application are rendered into offscreen images
+/// and could be read back as pixels.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_headless_read_pixels() {
+    let config = Config::new("test".to_owned(), ENGINE_VERSION.clone(), false)
+        .with_headless(true)
+        .with_window_size(Size::new(64, 32))
+        .with_clear_color(Color::RED);
+    let mut app = super::init(config).unwrap();
+    assert!(app.is_headless());
+    assert!(matches!(app.read_pixels(), Err(ReadPixelsError::NoFrame)));
+
+    let mut created = 0;
+    app.render_frames(2, |event| {
+        if let MyEvent::Created = event {
+            created += 1;
+        }
+    });
+    // The main loop is started only once.
+    app.render_frames(1, |event| assert!(!matches!(event, MyEvent::Created)));
+    assert_eq!(created, 1);
+
+    let pixels = app.read_pixels().unwrap();
+    assert_eq!(pixels.dimensions(), (64, 32));
+    assert_eq!(pixels.get_pixel(32, 16).0, [255, 0, 0, 255]);
+
+    let report = app.run_until_exit(|_| {});
+    assert!(report.leaks.is_empty());
+}
@@ -0,0 +1,90 @@
+//! Fixed-timestep updates which are independent of frame rate.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::DeltaTime;
+
+/// Max count of fixed updates in one frame.
+///
+/// If the game could not keep up with the fixed rate, the rest of the frame time is dropped
+/// instead of running more and more fixed updates on each frame.
+pub const MAX_FIXED_STEPS: u32 = 8;
+
+/// Handle which tells how far the current frame is between two fixed updates
+/// (it could be moved into the callback of the application).
+///
+/// State of the game which is changed by fixed updates could be interpolated
+/// between its previous and current values by [`FixedClock::alpha`]
+/// on [`Event::Update`](crate::window::Event::Update).
+///
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    step: Duration,
+    alpha: Rc<Cell<f32>>,
+}
+
+impl FixedClock {
+    /// Duration of one fixed update.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Fraction of the fixed step (from 0 to 1) which has passed since the last fixed update.
+    pub fn alpha(&self) -> f32 {
+        self.alpha.get()
+    }
+}
+
+/// Accumulator of frame time which is consumed by fixed steps.
+#[derive(Debug)]
+pub(crate) struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    clock: FixedClock,
+}
+
+impl FixedTimestep {
+    /// Creates new accumulator with given fixed step, which must not be zero.
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "fixed step must not be zero");
+        let clock = FixedClock {
+            step,
+            alpha: Rc::new(Cell::new(0.0)),
+        };
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            clock,
+        }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn clock(&self) -> FixedClock {
+        self.clock.clone()
+    }
+
+    /// Accumulates delta time of the frame, returning count of fixed steps to run.
+    ///
+    /// At most [`MAX_FIXED_STEPS`] are returned, the rest of accumulated time is dropped.
+    ///
+    pub fn advance(&mut self, delta_time: DeltaTime) -> u32 {
+        self.accumulator += delta_time;
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < MAX_FIXED_STEPS {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        if self.accumulator >= self.step {
+            // Game could not keep up, so it continues right after the last step.
+            self.accumulator = Duration::ZERO;
+        }
+        let alpha = self.accumulator.as_secs_f64() / self.step.as_secs_f64();
+        self.clock.alpha.set(alpha as f32);
+        steps
+    }
+}
@@ -0,0 +1,430 @@
+//! Registry of assets which are referenced by path or by name.
+//!
+//! Each unique asset reference is resolved into a handle once,
+//! so entities which share a mesh or a texture share its handle too.
+//! Textures are loaded lazily by [`StreamingManager`].
+//!
+//! Meshes from OBJ files and SPIR-V shaders are loaded on blocking threads
+//! of the task pool: handle is returned immediately, and [`AssetEvent`]
+//! is queued when loading is finished (see [`AssetServer::take_events`]).
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::{fmt, fs, io};
+
+use slotmap::{new_key_type, SecondaryMap, SlotMap};
+use thiserror::Error;
+use ultraviolet::Vec3;
+
+use crate::graphics::reflect::{spirv, ReflectionError};
+use crate::graphics::{StreamId, StreamingManager};
+use crate::math::Aabb;
+use crate::task::TaskPool;
+
+pub use obj::ObjError;
+
+mod obj;
+mod tests;
+
+new_key_type! {
+    /// Identifier of the mesh resolved by [`AssetServer`].
+    pub struct MeshHandle;
+
+    /// Identifier of the shader loaded by [`AssetServer`].
+    pub struct ShaderHandle;
+}
+
+/// Error that can happen when asset is loaded in background.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AssetLoadError {
+    #[error("failed to read `{path}`: {kind}")]
+    Io { path: PathBuf, kind: io::ErrorKind },
+
+    #[error("invalid OBJ file `{path}`: {source}")]
+    Mesh {
+        path: PathBuf,
+        #[source]
+        source: ObjError,
+    },
+
+    #[error("invalid SPIR-V module `{path}`: {source}")]
+    Shader {
+        path: PathBuf,
+        #[source]
+        source: ReflectionError,
+    },
+}
+
+/// State of the asset which is loaded in background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    /// Asset is being loaded.
+    Loading,
+    /// Asset was loaded successfully.
+    Loaded,
+    /// Asset could not be loaded.
+    Failed(AssetLoadError),
+}
+
+/// Handle of any asset which is loaded in background.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AssetId {
+    Mesh(MeshHandle),
+    Shader(ShaderHandle),
+}
+
+/// Event which is queued when loading of the asset is finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetEvent {
+    /// Asset was loaded, so its data could be used.
+    Loaded(AssetId),
+    /// Asset could not be loaded.
+    Failed(AssetId, AssetLoadError),
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("\"{0}\" is neither a built-in primitive nor an OBJ file")]
+pub struct ParseMeshSourceError(String);
+
+/// Mesh which is built into game engine.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    Triangle,
+    Quad,
+    Cube,
+}
+
+impl Primitive {
+    /// All built-in primitives.
+    pub const ALL: [Self; 3] = [Self::Triangle, Self::Quad, Self::Cube];
+
+    /// Name of the primitive which is used to reference it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Triangle => "triangle",
+            Self::Quad => "quad",
+            Self::Cube => "cube",
+        }
+    }
+}
+
+/// Reference to the mesh: either built-in primitive or OBJ file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MeshSource {
+    Primitive(Primitive),
+    File(PathBuf),
+}
+
+impl From<Primitive> for MeshSource {
+    fn from(primitive: Primitive) -> Self {
+        Self::Primitive(primitive)
+    }
+}
+
+impl FromStr for MeshSource {
+    type Err = ParseMeshSourceError;
+
+    /// Parses name of the primitive (such as `cube`) or path to the file
+    /// with `.obj` extension.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(&primitive) = Primitive::ALL.iter().find(|p| p.name() == s) {
+            return Ok(Self::Primitive(primitive));
+        }
+        let path = Path::new(s);
+        let is_obj = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|extension| extension.eq_ignore_ascii_case("obj"))
+            .unwrap_or_default();
+        if !is_obj {
+            return Err(ParseMeshSourceError(s.to_string()));
+        }
+        Ok(Self::File(path.to_path_buf()))
+    }
+}
+
+impl fmt::Display for MeshSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primitive(primitive) => f.write_str(primitive.name()),
+            Self::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// SPIR-V module which is loaded from the file.
+struct Shader {
+    path: PathBuf,
+    state: LoadState,
+    code: Option<Arc<[u8]>>,
+}
+
+#[derive(Default)]
+struct Assets {
+    meshes: SlotMap<MeshHandle, MeshSource>,
+    mesh_handles: HashMap<MeshSource, MeshHandle>,
+    mesh_states: SecondaryMap<MeshHandle, LoadState>,
+    mesh_bounds: SecondaryMap<MeshHandle, Aabb>,
+    mesh_triangles: SecondaryMap<MeshHandle, Arc<[[Vec3; 3]]>>,
+    shaders: SlotMap<ShaderHandle, Shader>,
+    shader_handles: HashMap<PathBuf, ShaderHandle>,
+    textures: HashMap<PathBuf, StreamId>,
+    events: Vec<AssetEvent>,
+}
+
+impl Assets {
+    /// Stores result of the loaded mesh, queueing the event.
+    fn finish_mesh(&mut self, handle: MeshHandle, result: Result<Vec<[Vec3; 3]>, AssetLoadError>) {
+        let id = AssetId::Mesh(handle);
+        let (state, event) = match result {
+            Ok(triangles) => {
+                let points = triangles.iter().flatten().copied();
+                if let Some(bounds) = Aabb::from_points(points) {
+                    self.mesh_bounds.insert(handle, bounds);
+                }
+                self.mesh_triangles.insert(handle, triangles.into());
+                (LoadState::Loaded, AssetEvent::Loaded(id))
+            }
+            Err(error) => (
+                LoadState::Failed(error.clone()),
+                AssetEvent::Failed(id, error),
+            ),
+        };
+        self.mesh_states.insert(handle, state);
+        self.events.push(event);
+    }
+
+    /// Stores result of the loaded shader, queueing the event.
+    fn finish_shader(&mut self, handle: ShaderHandle, result: Result<Vec<u8>, AssetLoadError>) {
+        let id = AssetId::Shader(handle);
+        let shader = &mut self.shaders[handle];
+        let event = match result {
+            Ok(code) => {
+                shader.code = Some(code.into());
+                shader.state = LoadState::Loaded;
+                AssetEvent::Loaded(id)
+            }
+            Err(error) => {
+                shader.state = LoadState::Failed(error.clone());
+                AssetEvent::Failed(id, error)
+            }
+        };
+        self.events.push(event);
+    }
+}
+
+/// Reads OBJ file and parses its triangles.
+fn load_mesh(path: &Path) -> Result<Vec<[Vec3; 3]>, AssetLoadError> {
+    let source = fs::read_to_string(path).map_err(|error| AssetLoadError::Io {
+        path: path.to_path_buf(),
+        kind: error.kind(),
+    })?;
+    obj::parse_triangles(&source).map_err(|source| AssetLoadError::Mesh {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Reads SPIR-V module and checks that it could be reflected.
+fn load_shader(path: &Path) -> Result<Vec<u8>, AssetLoadError> {
+    let code = fs::read(path).map_err(|error| AssetLoadError::Io {
+        path: path.to_path_buf(),
+        kind: error.kind(),
+    })?;
+    spirv::parse_bytes(&code).map_err(|source| AssetLoadError::Shader {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(code)
+}
+
+/// Handle which resolves asset references into handles of assets.
+///
+/// Clones share one registry, so the same reference resolves into the same handle
+/// whichever clone resolves it.
+#[derive(Clone)]
+pub struct AssetServer {
+    inner: Arc<Mutex<Assets>>,
+    streaming: StreamingManager,
+}
+
+impl AssetServer {
+    /// Creates new asset server which streams textures with given manager
+    /// (see [`Application::enable_streaming`](crate::app::Application::enable_streaming)).
+    pub fn new(streaming: StreamingManager) -> Self {
+        Self {
+            inner: Arc::default(),
+            streaming,
+        }
+    }
+
+    /// Returns handle of the mesh, registering its source if it is new.
+    ///
+    /// Triangles and bounds of the mesh from the file are loaded in background,
+    /// then [`AssetEvent`] is queued.
+    ///
+    pub fn load_mesh(&self, source: &MeshSource) -> MeshHandle {
+        let mut assets = self.inner.lock().unwrap();
+        if let Some(&handle) = assets.mesh_handles.get(source) {
+            return handle;
+        }
+        let handle = assets.meshes.insert(source.clone());
+        assets.mesh_handles.insert(source.clone(), handle);
+        if let MeshSource::File(path) = source {
+            assets.mesh_states.insert(handle, LoadState::Loading);
+            let path = path.clone();
+            let inner = self.inner.clone();
+            self.task_pool().spawn_blocking(move || {
+                let result = load_mesh(&path);
+                inner.lock().unwrap().finish_mesh(handle, result);
+            });
+        }
+        handle
+    }
+
+    /// Returns state of the mesh, or `None` if handle was not created by this server.
+    ///
+    /// Built-in primitives are always loaded.
+    ///
+    pub fn mesh_state(&self, handle: MeshHandle) -> Option<LoadState> {
+        let assets = self.inner.lock().unwrap();
+        assets.meshes.get(handle)?;
+        let state = assets.mesh_states.get(handle).cloned();
+        Some(state.unwrap_or(LoadState::Loaded))
+    }
+
+    /// Returns source of the mesh, or `None` if handle was not created by this server.
+    pub fn mesh_source(&self, handle: MeshHandle) -> Option<MeshSource> {
+        self.inner.lock().unwrap().meshes.get(handle).cloned()
+    }
+
+    /// Stores bounds of the mesh computed from its vertices when mesh data is uploaded.
+    ///
+    /// Returns `false` if handle was not created by this server.
+    pub fn set_mesh_bounds(&self, handle: MeshHandle, bounds: Aabb) -> bool {
+        let mut assets = self.inner.lock().unwrap();
+        if !assets.meshes.contains_key(handle) {
+            return false;
+        }
+        assets.mesh_bounds.insert(handle, bounds);
+        true
+    }
+
+    /// Returns bounds of the mesh in its local space, or `None` if they are not known yet.
+    pub fn mesh_bounds(&self, handle: MeshHandle) -> Option<Aabb> {
+        self.inner.lock().unwrap().mesh_bounds.get(handle).copied()
+    }
+
+    /// Stores triangles of the mesh (in its local space) for precise picking
+    /// (see [`PrecisePick`](crate::scene::PrecisePick)).
+    ///
+    /// Returns `false` if handle was not created by this server.
+    pub fn set_mesh_triangles(&self, handle: MeshHandle, triangles: Vec<[Vec3; 3]>) -> bool {
+        let mut assets = self.inner.lock().unwrap();
+        if !assets.meshes.contains_key(handle) {
+            return false;
+        }
+        assets.mesh_triangles.insert(handle, triangles.into());
+        true
+    }
+
+    /// Returns triangles of the mesh in its local space, or `None` if they were not stored.
+    pub fn mesh_triangles(&self, handle: MeshHandle) -> Option<Arc<[[Vec3; 3]]>> {
+        self.inner
+            .lock()
+            .unwrap()
+            .mesh_triangles
+            .get(handle)
+            .cloned()
+    }
+
+    /// Returns identifier of the streamable texture, registering the file if it is new.
+    ///
+    /// Texture is loaded when it is needed (see [`StreamingManager::state`]).
+    pub fn load_texture(&self, path: impl Into<PathBuf>) -> StreamId {
+        let path = path.into();
+        let mut assets = self.inner.lock().unwrap();
+        if let Some(&id) = assets.textures.get(&path) {
+            return id;
+        }
+        let id = self.streaming.register(path.clone());
+        assets.textures.insert(path, id);
+        id
+    }
+
+    /// Returns handle of the SPIR-V shader, loading the file in background if it is new.
+    ///
+    /// When the module is loaded and checked, [`AssetEvent`] is queued
+    /// and its code could be given to [`ShaderSet`](crate::graphics::ShaderSet).
+    ///
+    pub fn load_shader(&self, path: impl Into<PathBuf>) -> ShaderHandle {
+        let path = path.into();
+        let mut assets = self.inner.lock().unwrap();
+        if let Some(&handle) = assets.shader_handles.get(&path) {
+            return handle;
+        }
+        let handle = assets.shaders.insert(Shader {
+            path: path.clone(),
+            state: LoadState::Loading,
+            code: None,
+        });
+        assets.shader_handles.insert(path.clone(), handle);
+        let inner = self.inner.clone();
+        self.task_pool().spawn_blocking(move || {
+            let result = load_shader(&path);
+            inner.lock().unwrap().finish_shader(handle, result);
+        });
+        handle
+    }
+
+    /// Returns path of the shader, or `None` if handle was not created by this server.
+    pub fn shader_path(&self, handle: ShaderHandle) -> Option<PathBuf> {
+        let assets = self.inner.lock().unwrap();
+        assets.shaders.get(handle).map(|shader| shader.path.clone())
+    }
+
+    /// Returns state of the shader, or `None` if handle was not created by this server.
+    pub fn shader_state(&self, handle: ShaderHandle) -> Option<LoadState> {
+        let assets = self.inner.lock().unwrap();
+        assets
+            .shaders
+            .get(handle)
+            .map(|shader| shader.state.clone())
+    }
+
+    /// Returns SPIR-V code of the shader, or `None` if it is not loaded yet.
+    pub fn shader_code(&self, handle: ShaderHandle) -> Option<Arc<[u8]>> {
+        let assets = self.inner.lock().unwrap();
+        assets.shaders.get(handle)?.code.clone()
+    }
+
+    /// Returns state of any asset which is loaded in background.
+    pub fn state(&self, id: AssetId) -> Option<LoadState> {
+        match id {
+            AssetId::Mesh(handle) => self.mesh_state(handle),
+            AssetId::Shader(handle) => self.shader_state(handle),
+        }
+    }
+
+    /// Takes events of assets which were loaded since the last call, in order of loading.
+    ///
+    /// Events are delivered as [`Event::Asset`](crate::window::Event::Asset)
+    /// by the server of [`Application::enable_assets`](crate::app::Application::enable_assets).
+    ///
+    pub fn take_events(&self) -> Vec<AssetEvent> {
+        std::mem::take(&mut self.inner.lock().unwrap().events)
+    }
+
+    /// Manager which streams textures of this server.
+    pub fn streaming(&self) -> &StreamingManager {
+        &self.streaming
+    }
+
+    /// Task pool which loads assets of this server in background.
+    pub fn task_pool(&self) -> TaskPool {
+        self.streaming.task_pool()
+    }
+}
@@ -0,0 +1,79 @@
+//! Parser of triangles from Wavefront OBJ files.
+//!
+//! Only positions of vertices and faces are read: normals, texture coordinates,
+//! materials and groups are ignored. Polygons are triangulated as fans.
+
+use thiserror::Error;
+use ultraviolet::Vec3;
+
+/// Error that can happen when OBJ file is parsed.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum ObjError {
+    #[error("vertex at line {0} must have three coordinates")]
+    InvalidVertex(usize),
+
+    #[error("face at line {0} must have at least three vertices")]
+    InvalidFace(usize),
+
+    #[error("face at line {line} refers to vertex {index} which is not declared")]
+    IndexOutOfRange { line: usize, index: i64 },
+}
+
+/// Parses triangles of all faces of OBJ file.
+pub fn parse_triangles(source: &str) -> Result<Vec<[Vec3; 3]>, ObjError> {
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let coords: Option<Vec<f32>> =
+                    tokens.take(3).map(|token| token.parse().ok()).collect();
+                match coords.as_deref() {
+                    Some(&[x, y, z]) => positions.push(Vec3::new(x, y, z)),
+                    _ => return Err(ObjError::InvalidVertex(line_number)),
+                }
+            }
+            Some("f") => {
+                let vertices = tokens
+                    .map(|token| vertex(&positions, token, line_number))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if vertices.len() < 3 {
+                    return Err(ObjError::InvalidFace(line_number));
+                }
+                triangles.extend(
+                    vertices
+                        .windows(2)
+                        .skip(1)
+                        .map(|pair| [vertices[0], pair[0], pair[1]]),
+                );
+            }
+            _ => (),
+        }
+    }
+    Ok(triangles)
+}
+
+/// Resolves position of the face vertex (like `3`, `3/1` or `-1//2`).
+///
+/// Indices start from one, and negative indices are relative to the last declared vertex.
+///
+fn vertex(positions: &[Vec3], token: &str, line: usize) -> Result<Vec3, ObjError> {
+    let index: i64 = token
+        .split('/')
+        .next()
+        .and_then(|index| index.parse().ok())
+        .ok_or(ObjError::InvalidFace(line))?;
+    let resolved = match index {
+        index if index > 0 => index - 1,
+        index => positions.len() as i64 + index,
+    };
+    usize::try_from(resolved)
+        .ok()
+        .filter(|_| index != 0)
+        .and_then(|resolved| positions.get(resolved))
+        .copied()
+        .ok_or(ObjError::IndexOutOfRange { line, index })
+}
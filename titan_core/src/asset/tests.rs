@@ -0,0 +1,159 @@
+#![cfg(test)]
+
+use crate::graphics::StreamingConfig;
+use crate::task::TaskPool;
+
+use super::*;
+
+fn server() -> AssetServer {
+    let streaming = StreamingManager::new(StreamingConfig::default(), TaskPool::new(1, 1));
+    AssetServer::new(streaming)
+}
+
+#[test]
+fn test_parse_mesh_source() {
+    for primitive in Primitive::ALL {
+        let source: MeshSource = primitive.name().parse().unwrap();
+        assert_eq!(source, MeshSource::Primitive(primitive));
+        assert_eq!(source.to_string(), primitive.name());
+    }
+
+    let source: MeshSource = "models/Tank.OBJ".parse().unwrap();
+    assert_eq!(source, MeshSource::File(PathBuf::from("models/Tank.OBJ")));
+
+    for invalid in ["sphere", "models/tank.gltf", "obj", ""] {
+        let error = invalid.parse::<MeshSource>().unwrap_err();
+        assert_eq!(error, ParseMeshSourceError(invalid.to_string()));
+    }
+}
+
+#[test]
+fn test_handles_are_shared() {
+    let server = server();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    let tank = MeshSource::File(PathBuf::from("tank.obj"));
+
+    let handle = server.load_mesh(&cube);
+    assert_eq!(server.load_mesh(&cube), handle);
+    assert_ne!(server.load_mesh(&tank), handle);
+    assert_eq!(server.mesh_source(handle), Some(cube));
+
+    let texture = server.load_texture("grass.png");
+    assert_eq!(server.load_texture("grass.png"), texture);
+    assert_ne!(server.load_texture("sand.png"), texture);
+    assert!(server.streaming().state(texture).is_some());
+}
+
+#[test]
+fn test_parse_obj() {
+    let source = "\
+# quad with a texture
+v 0 0 0
+v 1 0 0
+v 1 1 0 1.0
+v 0 1 0
+vt 0 0
+f 1/1 2/1 3/1 -1/1 # fan of two triangles
+g ignored
+f 1//1 2//1 3//1
+";
+    let a = Vec3::new(0.0, 0.0, 0.0);
+    let b = Vec3::new(1.0, 0.0, 0.0);
+    let c = Vec3::new(1.0, 1.0, 0.0);
+    let d = Vec3::new(0.0, 1.0, 0.0);
+    let triangles = obj::parse_triangles(source).unwrap();
+    assert_eq!(triangles, [[a, b, c], [a, c, d], [a, b, c]]);
+
+    let errors = [
+        ("v 1 2", ObjError::InvalidVertex(1)),
+        ("v 1 2 x", ObjError::InvalidVertex(1)),
+        ("v 0 0 0\nv 1 0 0\nf 1 2", ObjError::InvalidFace(3)),
+        ("v 0 0 0\nf 1 1 x", ObjError::InvalidFace(2)),
+        (
+            "v 0 0 0\nf 1 1 2",
+            ObjError::IndexOutOfRange { line: 2, index: 2 },
+        ),
+        (
+            "v 0 0 0\nf 1 1 0",
+            ObjError::IndexOutOfRange { line: 2, index: 0 },
+        ),
+        (
+            "v 0 0 0\nf 1 1 -2",
+            ObjError::IndexOutOfRange { line: 2, index: -2 },
+        ),
+    ];
+    for (source, error) in errors {
+        assert_eq!(obj::parse_triangles(source), Err(error));
+    }
+}
+
+/// Takes events of the server, waiting until there are given count of them.
+fn wait_events(server: &AssetServer, count: usize) -> Vec<AssetEvent> {
+    let mut events = Vec::new();
+    for _ in 0..500 {
+        events.extend(server.take_events());
+        if events.len() >= count {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    events
+}
+
+#[test]
+fn test_background_loading() {
+    let dir = std::env::temp_dir().join(format!("titan_assets_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mesh_path = dir.join("triangle.obj");
+    std::fs::write(&mesh_path, "v 0 0 0\nv 2 0 0\nv 0 1 -1\nf 1 2 3\n").unwrap();
+    let shader_path = dir.join("invalid.spv");
+    std::fs::write(&shader_path, [0; 20]).unwrap();
+
+    let server = server();
+    let mesh = server.load_mesh(&MeshSource::File(mesh_path));
+    let missing = server.load_mesh(&MeshSource::File(dir.join("missing.obj")));
+    let shader = server.load_shader(&shader_path);
+    assert_eq!(server.load_shader(&shader_path), shader);
+    let cube = server.load_mesh(&MeshSource::Primitive(Primitive::Cube));
+    assert_eq!(server.mesh_state(cube), Some(LoadState::Loaded));
+
+    let mut events = wait_events(&server, 3);
+    events.sort_by_key(|event| match event {
+        AssetEvent::Loaded(id) | AssetEvent::Failed(id, _) => *id != AssetId::Mesh(mesh),
+    });
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], AssetEvent::Loaded(AssetId::Mesh(mesh)));
+    assert_eq!(server.state(AssetId::Mesh(mesh)), Some(LoadState::Loaded));
+    let bounds = Aabb::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(2.0, 1.0, 0.0));
+    assert_eq!(server.mesh_bounds(mesh), Some(bounds));
+    assert_eq!(server.mesh_triangles(mesh).unwrap().len(), 1);
+
+    let missing_error = AssetLoadError::Io {
+        path: dir.join("missing.obj"),
+        kind: io::ErrorKind::NotFound,
+    };
+    assert!(events.contains(&AssetEvent::Failed(
+        AssetId::Mesh(missing),
+        missing_error.clone()
+    )));
+    assert_eq!(
+        server.mesh_state(missing),
+        Some(LoadState::Failed(missing_error))
+    );
+    let shader_error = AssetLoadError::Shader {
+        path: shader_path.clone(),
+        source: ReflectionError::InvalidMagic,
+    };
+    assert!(events.contains(&AssetEvent::Failed(
+        AssetId::Shader(shader),
+        shader_error.clone()
+    )));
+    assert_eq!(
+        server.shader_state(shader),
+        Some(LoadState::Failed(shader_error))
+    );
+    assert_eq!(server.shader_path(shader), Some(shader_path));
+    assert_eq!(server.shader_code(shader), None);
+    assert!(server.take_events().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,135 @@
+//! Audio thread which plays sounds on the default output device with `rodio`.
+
+use std::io::Cursor;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::decoder::DecoderError;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
+
+use super::source::{SoundData, SoundSource, Voice, OUTPUT_CHANNELS};
+
+/// Interval between checks whether default output device was changed.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Command which is sent to the audio thread.
+pub(crate) enum Command {
+    Play(Voice),
+    Shutdown,
+}
+
+/// Decodes WAV or OGG sound from bytes.
+pub(crate) fn decode(bytes: Vec<u8>) -> Result<SoundData, DecoderError> {
+    let decoder = Decoder::new(Cursor::new(bytes))?;
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    let samples = decoder.convert_samples().collect();
+    Ok(SoundData {
+        channels,
+        sample_rate,
+        samples,
+    })
+}
+
+impl Source for SoundSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        OUTPUT_CHANNELS
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SoundSource::sample_rate(self)
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Opened output stream of the device.
+struct Output {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    device: Option<String>,
+}
+
+impl Output {
+    fn open_default() -> Option<Self> {
+        let device = self::default_device_name();
+        match OutputStream::try_default() {
+            Ok((stream, handle)) => {
+                log::info!("audio output opened on device {:?}", device);
+                Some(Self {
+                    _stream: stream,
+                    handle,
+                    device,
+                })
+            }
+            Err(error) => {
+                log::warn!("failed to open audio output: {}", error);
+                None
+            }
+        }
+    }
+
+    fn play(&self, voice: &Voice) {
+        let source = SoundSource::new(voice.clone());
+        if let Err(error) = self.handle.play_raw(source) {
+            log::warn!("failed to play sound: {}", error);
+        }
+    }
+}
+
+fn default_device_name() -> Option<String> {
+    let device = rodio::cpal::default_host().default_output_device()?;
+    device.name().ok()
+}
+
+/// Main loop of the audio thread.
+///
+/// Default output device is polled, and when it is changed or lost
+/// (for example, headphones were unplugged), output is reopened on the new default device.
+/// Sounds which were playing are resumed from their positions.
+///
+pub(crate) fn run(commands: Receiver<Command>) {
+    let mut output = Output::open_default();
+    let mut voices: Vec<Voice> = Vec::new();
+    let mut polled_at = Instant::now();
+    loop {
+        match commands.recv_timeout(DEVICE_POLL_INTERVAL) {
+            Ok(Command::Play(voice)) => {
+                if let Some(output) = &output {
+                    output.play(&voice);
+                }
+                voices.push(voice);
+            }
+            Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => (),
+        }
+        if polled_at.elapsed() < DEVICE_POLL_INTERVAL {
+            continue;
+        }
+        polled_at = Instant::now();
+        voices.retain(|voice| !voice.is_finished());
+
+        let device = self::default_device_name();
+        let opened = output.as_ref().map(|output| &output.device);
+        if opened == Some(&device) || (opened.is_none() && device.is_none()) {
+            continue;
+        }
+        log::info!("default audio device changed to {:?}", device);
+        // Previous stream must be closed before the device is opened again.
+        drop(output.take());
+        output = Output::open_default();
+        if let Some(output) = &output {
+            for voice in &voices {
+                output.play(voice);
+            }
+        }
+    }
+    log::info!("audio thread finished");
+}
@@ -0,0 +1,273 @@
+//! Audio subsystem of game engine with optional spatial playback.
+//!
+//! Sounds are mixed on a separate audio thread which owns the output device,
+//! so the render loop never blocks on it: sounds are sent to the thread through a channel,
+//! while volumes and playback state are shared through atomics.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use rodio::decoder::DecoderError;
+use slotmap::{new_key_type, SlotMap};
+use thiserror::Error;
+
+pub use spatial::{update_spatial_audio, Attenuation, AudioEmitter, AudioListener};
+
+use backend::Command;
+use source::{AtomicF32, Controls, SoundData, Voice};
+
+mod backend;
+mod source;
+mod spatial;
+
+mod tests;
+
+/// Bus which is used by [`PlaybackParams::default`].
+pub const DEFAULT_BUS: &str = "effects";
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("failed to spawn audio thread: {0}")]
+    ThreadSpawn(#[source] std::io::Error),
+
+    #[error("failed to read sound file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to decode sound: {0}")]
+    Decode(#[from] DecoderError),
+
+    #[error("sound was not found")]
+    SoundNotFound,
+
+    #[error("audio thread is not running")]
+    Disconnected,
+}
+
+new_key_type! {
+    /// Identifier of the sound loaded by [`AudioServer`].
+    pub struct SoundHandle;
+}
+
+/// Parameters of sound playback.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlaybackParams {
+    /// Volume of the sound (1 is the original volume).
+    pub volume: f32,
+    /// Whether the sound is repeated after it ends.
+    pub looping: bool,
+    /// Playback speed which also changes the pitch (1 is the original pitch).
+    pub pitch: f32,
+    /// Stereo pan of the sound (-1 is the left channel only, 1 is the right channel only).
+    pub pan: f32,
+    /// Name of the bus which volume is applied to the sound.
+    pub bus: &'static str,
+}
+
+impl Default for PlaybackParams {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            looping: false,
+            pitch: 1.0,
+            pan: 0.0,
+            bus: DEFAULT_BUS,
+        }
+    }
+}
+
+/// Handle to the playing sound.
+///
+/// Dropping the handle does not stop the sound.
+#[derive(Debug, Clone)]
+pub struct SoundInstance {
+    controls: Arc<Controls>,
+}
+
+impl SoundInstance {
+    /// Stops the sound. Stopped sound could not be resumed.
+    pub fn stop(&self) {
+        self.controls.stopped.store(true, Ordering::Relaxed)
+    }
+
+    /// Pauses the sound.
+    pub fn pause(&self) {
+        self.controls.paused.store(true, Ordering::Relaxed)
+    }
+
+    /// Resumes the paused sound.
+    pub fn resume(&self) {
+        self.controls.paused.store(false, Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the sound is paused.
+    pub fn is_paused(&self) -> bool {
+        self.controls.paused.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the sound was stopped or has ended.
+    pub fn is_finished(&self) -> bool {
+        let controls = &self.controls;
+        controls.stopped.load(Ordering::Relaxed) || controls.finished.load(Ordering::Relaxed)
+    }
+
+    /// Sets volume of the sound.
+    pub fn set_volume(&self, volume: f32) {
+        self.controls.volume.set(volume)
+    }
+
+    /// Sets playback speed (and pitch) of the sound.
+    pub fn set_pitch(&self, pitch: f32) {
+        self.controls.pitch.set(pitch)
+    }
+
+    /// Sets stereo pan of the sound (-1 is the left channel only, 1 is the right channel only).
+    ///
+    /// Pan of spatial sounds is combined with the pan towards their [`AudioEmitter`]s.
+    ///
+    pub fn set_pan(&self, pan: f32) {
+        self.controls.pan.set(pan)
+    }
+
+    /// Sets gains of the left and right channels (for spatial audio).
+    pub(crate) fn set_channel_gains(&self, gains: [f32; 2]) {
+        let [left, right] = gains;
+        self.controls.gains[0].set(left);
+        self.controls.gains[1].set(right);
+    }
+}
+
+/// Shared state of the audio server.
+struct Inner {
+    commands: Mutex<Sender<Command>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    sounds: Mutex<SlotMap<SoundHandle, Arc<SoundData>>>,
+    master: Arc<AtomicF32>,
+    buses: Mutex<HashMap<&'static str, Arc<AtomicF32>>>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let _ = self.commands.get_mut().unwrap().send(Command::Shutdown);
+        if let Some(thread) = self.thread.get_mut().unwrap().take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Server which loads sounds and plays them on the default output device.
+///
+/// Clones play sounds on the same audio thread, which is stopped when the last clone is dropped.
+#[derive(Clone)]
+pub struct AudioServer {
+    inner: Arc<Inner>,
+}
+
+impl AudioServer {
+    /// Creates new server and starts its audio thread.
+    ///
+    /// Server is created even if there is no output device:
+    /// sounds will be heard when the device is connected.
+    ///
+    pub fn new() -> Result<Self, AudioError> {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || backend::run(receiver))
+            .map_err(AudioError::ThreadSpawn)?;
+        let inner = Inner {
+            commands: Mutex::new(sender),
+            thread: Mutex::new(Some(thread)),
+            sounds: Mutex::new(SlotMap::with_key()),
+            master: Arc::new(AtomicF32::new(1.0)),
+            buses: Mutex::new(HashMap::new()),
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Loads and decodes WAV or OGG sound from the file.
+    ///
+    /// Sound is decoded on the calling thread.
+    ///
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<SoundHandle, AudioError> {
+        let bytes = std::fs::read(path)?;
+        self.load_bytes(bytes)
+    }
+
+    /// Decodes WAV or OGG sound from the bytes.
+    pub fn load_bytes(&self, bytes: Vec<u8>) -> Result<SoundHandle, AudioError> {
+        let data = backend::decode(bytes)?;
+        let mut sounds = self.inner.sounds.lock().unwrap();
+        Ok(sounds.insert(Arc::new(data)))
+    }
+
+    /// Unloads the sound. Instances of the sound which are playing are not stopped.
+    ///
+    /// Returns `true` if sound was loaded.
+    ///
+    pub fn unload(&self, handle: SoundHandle) -> bool {
+        let mut sounds = self.inner.sounds.lock().unwrap();
+        sounds.remove(handle).is_some()
+    }
+
+    /// Plays the sound with given parameters.
+    pub fn play(
+        &self,
+        handle: SoundHandle,
+        params: PlaybackParams,
+    ) -> Result<SoundInstance, AudioError> {
+        let data = {
+            let sounds = self.inner.sounds.lock().unwrap();
+            sounds
+                .get(handle)
+                .cloned()
+                .ok_or(AudioError::SoundNotFound)?
+        };
+        let controls = Arc::new(Controls::new(params.volume, params.pitch, params.pan));
+        let voice = Voice {
+            data,
+            controls: controls.clone(),
+            master: self.inner.master.clone(),
+            bus: self.bus(params.bus),
+            looping: params.looping,
+        };
+        let commands = self.inner.commands.lock().unwrap();
+        commands
+            .send(Command::Play(voice))
+            .map_err(|_| AudioError::Disconnected)?;
+        Ok(SoundInstance { controls })
+    }
+
+    /// Volume which is applied to all sounds.
+    pub fn master_volume(&self) -> f32 {
+        self.inner.master.get()
+    }
+
+    /// Sets volume which is applied to all sounds.
+    pub fn set_master_volume(&self, volume: f32) {
+        self.inner.master.set(volume)
+    }
+
+    /// Volume which is applied to sounds played on the bus.
+    pub fn bus_volume(&self, bus: &'static str) -> f32 {
+        self.bus(bus).get()
+    }
+
+    /// Sets volume which is applied to sounds played on the bus.
+    pub fn set_bus_volume(&self, bus: &'static str, volume: f32) {
+        self.bus(bus).set(volume)
+    }
+
+    fn bus(&self, bus: &'static str) -> Arc<AtomicF32> {
+        let mut buses = self.inner.buses.lock().unwrap();
+        let volume = buses
+            .entry(bus)
+            .or_insert_with(|| Arc::new(AtomicF32::new(1.0)));
+        volume.clone()
+    }
+}
@@ -0,0 +1,227 @@
+//! Sources of samples which are mixed by the audio thread.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Count of channels of each playing sound (stereo).
+pub(crate) const OUTPUT_CHANNELS: u16 = 2;
+
+/// Floating point number which could be shared between threads without locks.
+#[derive(Debug, Default)]
+pub(crate) struct AtomicF32(AtomicU32);
+
+impl AtomicF32 {
+    pub fn new(value: f32) -> Self {
+        Self(AtomicU32::new(value.to_bits()))
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed)
+    }
+}
+
+/// Decoded sound with interleaved samples.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SoundData {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl SoundData {
+    /// Count of frames (samples of all channels at the same time).
+    pub fn frames(&self) -> usize {
+        match self.channels {
+            0 => 0,
+            channels => self.samples.len() / channels as usize,
+        }
+    }
+
+    /// Stereo frame at given index.
+    ///
+    /// Mono sound is played in both channels, extra channels are ignored.
+    fn frame(&self, index: usize) -> [f32; 2] {
+        let channels = self.channels as usize;
+        let start = index * channels;
+        let left = self.samples[start];
+        let right = if channels > 1 {
+            self.samples[start + 1]
+        } else {
+            left
+        };
+        [left, right]
+    }
+}
+
+/// Gains of the left and right channels for given stereo pan
+/// (-1 is the left channel only, 0 is the center, 1 is the right channel only).
+///
+/// Sound is panned by balance: the channel on the side of the pan stays at full gain,
+/// while the opposite one fades out.
+pub(crate) fn balance(pan: f32) -> [f32; 2] {
+    let pan = if pan.is_nan() {
+        0.0
+    } else {
+        pan.clamp(-1.0, 1.0)
+    };
+    [(1.0 - pan).min(1.0), (1.0 + pan).min(1.0)]
+}
+
+/// Controls of the playing sound which are changed by the game
+/// and read by the audio thread.
+#[derive(Debug)]
+pub(crate) struct Controls {
+    pub volume: AtomicF32,
+    pub pitch: AtomicF32,
+    /// Stereo pan which is set by the game.
+    pub pan: AtomicF32,
+    /// Gains of the left and right channels which are set by spatial audio.
+    pub gains: [AtomicF32; 2],
+    pub paused: AtomicBool,
+    pub stopped: AtomicBool,
+    pub finished: AtomicBool,
+    /// Position of playback in frames (bits of `f64`).
+    position: AtomicU64,
+}
+
+impl Controls {
+    pub fn new(volume: f32, pitch: f32, pan: f32) -> Self {
+        Self {
+            volume: AtomicF32::new(volume),
+            pitch: AtomicF32::new(pitch),
+            pan: AtomicF32::new(pan),
+            gains: [AtomicF32::new(1.0), AtomicF32::new(1.0)],
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            position: AtomicU64::new(0.0f64.to_bits()),
+        }
+    }
+
+    pub fn position(&self) -> f64 {
+        f64::from_bits(self.position.load(Ordering::Relaxed))
+    }
+
+    fn set_position(&self, position: f64) {
+        self.position.store(position.to_bits(), Ordering::Relaxed)
+    }
+}
+
+/// Everything which is needed to play the sound (or resume it on another device).
+#[derive(Debug, Clone)]
+pub(crate) struct Voice {
+    pub data: Arc<SoundData>,
+    pub controls: Arc<Controls>,
+    pub master: Arc<AtomicF32>,
+    pub bus: Arc<AtomicF32>,
+    pub looping: bool,
+}
+
+impl Voice {
+    /// Returns `true` if the voice will not produce samples anymore.
+    pub fn is_finished(&self) -> bool {
+        let controls = &self.controls;
+        controls.finished.load(Ordering::Relaxed) || controls.stopped.load(Ordering::Relaxed)
+    }
+}
+
+/// Iterator over interleaved stereo samples of the voice.
+///
+/// Playback starts from the position stored in the controls of the voice,
+/// so the voice could be resumed by the new source.
+#[derive(Debug)]
+pub(crate) struct SoundSource {
+    voice: Voice,
+    position: f64,
+    channel: usize,
+    frame: [f32; 2],
+}
+
+impl SoundSource {
+    pub fn new(voice: Voice) -> Self {
+        let position = voice.controls.position();
+        Self {
+            voice,
+            position,
+            channel: 0,
+            frame: [0.0; 2],
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.voice.data.sample_rate
+    }
+
+    fn finish(&mut self) -> Option<f32> {
+        self.voice.controls.finished.store(true, Ordering::Relaxed);
+        None
+    }
+
+    /// Computes next frame with all gains applied, advancing the position.
+    fn next_frame(&mut self) -> Option<[f32; 2]> {
+        let controls = &self.voice.controls;
+        if controls.stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+        if controls.paused.load(Ordering::Relaxed) {
+            return Some([0.0; 2]);
+        }
+        let data = &self.voice.data;
+        let frames = data.frames();
+        if frames == 0 {
+            return None;
+        }
+        if self.position >= frames as f64 {
+            if !self.voice.looping {
+                return None;
+            }
+            self.position %= frames as f64;
+        }
+
+        // Linear interpolation between neighbour frames.
+        let index = self.position as usize;
+        let fract = (self.position - index as f64) as f32;
+        let next = match index + 1 {
+            next if next < frames => next,
+            _ if self.voice.looping => 0,
+            _ => index,
+        };
+        let [left, right] = data.frame(index);
+        let [next_left, next_right] = data.frame(next);
+        let frame = [
+            left + (next_left - left) * fract,
+            right + (next_right - right) * fract,
+        ];
+
+        self.position += controls.pitch.get().max(0.0) as f64;
+        controls.set_position(self.position);
+
+        let volume = controls.volume.get() * self.voice.master.get() * self.voice.bus.get();
+        let [left_pan, right_pan] = self::balance(controls.pan.get());
+        let [left_gain, right_gain] = [controls.gains[0].get(), controls.gains[1].get()];
+        Some([
+            frame[0] * volume * left_pan * left_gain,
+            frame[1] * volume * right_pan * right_gain,
+        ])
+    }
+}
+
+impl Iterator for SoundSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 0 {
+            self.frame = match self.next_frame() {
+                Some(frame) => frame,
+                None => return self.finish(),
+            };
+        }
+        let sample = self.frame[self.channel];
+        self.channel = (self.channel + 1) % OUTPUT_CHANNELS as usize;
+        Some(sample)
+    }
+}
@@ -0,0 +1,116 @@
+//! Spatial audio driven by transforms of entities in ECS.
+
+use titan_ecs::{GlobalTransform, World};
+use ultraviolet::{Mat4, Vec3};
+
+use super::source;
+use super::SoundInstance;
+
+/// Component of the entity which hears spatial sounds (usually the camera).
+///
+/// Only the first listener in the world is used.
+/// Its right direction is the local X axis of its [`GlobalTransform`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct AudioListener;
+
+/// Component of the entity which emits spatial sound.
+///
+/// Position of the sound is the translation of [`GlobalTransform`] of the entity.
+pub struct AudioEmitter {
+    pub instance: SoundInstance,
+    pub attenuation: Attenuation,
+}
+
+impl AudioEmitter {
+    /// Creates emitter of the playing sound with default attenuation.
+    pub fn new(instance: SoundInstance) -> Self {
+        Self {
+            instance,
+            attenuation: Attenuation::default(),
+        }
+    }
+}
+
+/// Attenuation of spatial sound by distance between emitter and listener
+/// (inverse distance model, clamped).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Attenuation {
+    /// Distance within which sound is not attenuated.
+    pub min_distance: f32,
+    /// Distance beyond which sound is not attenuated further.
+    pub max_distance: f32,
+    /// How fast sound is attenuated with distance.
+    pub rolloff: f32,
+}
+
+impl Attenuation {
+    /// Gain of the sound at given distance from the listener.
+    pub fn gain(&self, distance: f32) -> f32 {
+        let min_distance = self.min_distance.max(0.0);
+        let distance = distance.clamp(min_distance, self.max_distance.max(min_distance));
+        let attenuated = min_distance + self.rolloff.max(0.0) * (distance - min_distance);
+        if attenuated <= 0.0 {
+            return 1.0;
+        }
+        min_distance / attenuated
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self {
+            min_distance: 1.0,
+            max_distance: 100.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+/// Gains of the left and right channels of spatial sound.
+///
+/// Sound is panned by balance (see [`source::balance`]) towards the side of the emitter.
+/// It is applied on top of the pan of the sound itself.
+pub(crate) fn channel_gains(listener: Mat4, position: Vec3, attenuation: &Attenuation) -> [f32; 2] {
+    let local = listener.inversed().transform_point3(position);
+    let distance = (position - listener.extract_translation()).mag();
+    let gain = attenuation.gain(distance);
+
+    let local_distance = local.mag();
+    let pan = if local_distance > f32::EPSILON {
+        (local.x / local_distance).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    let [left, right] = source::balance(pan);
+    [left * gain, right * gain]
+}
+
+/// Updates panning and distance attenuation of all [`AudioEmitter`]s
+/// relative to the [`AudioListener`].
+///
+/// It should be run each frame after [`propagate_transforms`](titan_ecs::propagate_transforms),
+/// for example as a system of the schedule. If there is no listener,
+/// sounds are heard from the origin of the world.
+///
+/// Returns count of updated emitters.
+///
+pub fn update_spatial_audio(world: &World) -> usize {
+    let listener = world
+        .query::<AudioListener>()
+        .next()
+        .and_then(|(entity, _)| world.get::<GlobalTransform>(entity))
+        .map(GlobalTransform::matrix)
+        .unwrap_or_else(Mat4::identity);
+
+    let mut updated = 0;
+    for (entity, emitter) in world.query::<AudioEmitter>() {
+        let position = world
+            .get::<GlobalTransform>(entity)
+            .map(GlobalTransform::translation)
+            .unwrap_or_else(Vec3::zero);
+        let gains = self::channel_gains(listener, position, &emitter.attenuation);
+        emitter.instance.set_channel_gains(gains);
+        updated += 1;
+    }
+    updated
+}
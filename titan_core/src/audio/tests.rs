@@ -0,0 +1,202 @@
+#![cfg(test)]
+
+use std::f32::consts::PI;
+
+use titan_ecs::{propagate_transforms, Transform, World};
+use ultraviolet::{Mat4, Rotor3, Vec3};
+
+use super::source::{self, SoundSource};
+use super::spatial::channel_gains;
+use super::*;
+
+fn voice(channels: u16, samples: Vec<f32>, looping: bool) -> Voice {
+    let data = SoundData {
+        channels,
+        sample_rate: 44_100,
+        samples,
+    };
+    Voice {
+        data: Arc::new(data),
+        controls: Arc::new(Controls::new(1.0, 1.0, 0.0)),
+        master: Arc::new(AtomicF32::new(1.0)),
+        bus: Arc::new(AtomicF32::new(1.0)),
+        looping,
+    }
+}
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn test_source_volumes() {
+    let voice = voice(2, vec![1.0, -1.0, 0.5, -0.5], false);
+    voice.controls.volume.set(0.5);
+    voice.master.set(0.5);
+    voice.bus.set(2.0);
+    let samples: Vec<_> = SoundSource::new(voice.clone()).collect();
+    assert_close(&samples, &[0.5, -0.5, 0.25, -0.25]);
+    assert!(voice.is_finished());
+}
+
+#[test]
+fn test_source_pan() {
+    let mono = voice(1, vec![1.0, 0.5], false);
+    mono.controls.pan.set(-0.5);
+    let samples: Vec<_> = SoundSource::new(mono).collect();
+    assert_close(&samples, &[1.0, 0.5, 0.5, 0.25]);
+
+    // Pan is combined with gains of spatial audio.
+    let stereo = voice(2, vec![1.0, 1.0], false);
+    stereo.controls.pan.set(1.0);
+    stereo.controls.gains[1].set(0.5);
+    let samples: Vec<_> = SoundSource::new(stereo).collect();
+    assert_close(&samples, &[0.0, 0.5]);
+
+    assert_eq!(source::balance(0.0), [1.0, 1.0]);
+    assert_eq!(source::balance(-2.0), [1.0, 0.0]);
+    assert_eq!(source::balance(f32::NAN), [1.0, 1.0]);
+}
+
+#[test]
+fn test_source_mono_looping() {
+    let voice = voice(1, vec![0.1, 0.2], true);
+    let samples: Vec<_> = SoundSource::new(voice.clone()).take(8).collect();
+    assert_close(&samples, &[0.1, 0.1, 0.2, 0.2, 0.1, 0.1, 0.2, 0.2]);
+    assert!(!voice.is_finished());
+}
+
+#[test]
+fn test_source_pitch() {
+    let voice = voice(1, vec![0.0, 1.0, 0.0, 1.0], false);
+    voice.controls.pitch.set(2.0);
+    let samples: Vec<_> = SoundSource::new(voice.clone()).collect();
+    assert_close(&samples, &[0.0, 0.0, 0.0, 0.0]);
+
+    let voice = self::voice(1, vec![0.0, 1.0], false);
+    voice.controls.pitch.set(0.5);
+    let samples: Vec<_> = SoundSource::new(voice).step_by(2).collect();
+    assert_close(&samples, &[0.0, 0.5, 1.0, 1.0]);
+}
+
+#[test]
+fn test_source_pause_stop_resume() {
+    let voice = voice(1, vec![0.1, 0.2, 0.3], false);
+    let instance = SoundInstance {
+        controls: voice.controls.clone(),
+    };
+    let mut source = SoundSource::new(voice.clone());
+    assert_close(
+        &[source.next().unwrap(), source.next().unwrap()],
+        &[0.1, 0.1],
+    );
+
+    instance.pause();
+    assert_close(
+        &[source.next().unwrap(), source.next().unwrap()],
+        &[0.0, 0.0],
+    );
+    instance.resume();
+
+    // New source (for example, on another device) continues from the same position.
+    let mut resumed = SoundSource::new(voice);
+    assert_close(
+        &[resumed.next().unwrap(), resumed.next().unwrap()],
+        &[0.2, 0.2],
+    );
+
+    instance.stop();
+    assert_eq!(resumed.next(), None);
+    assert!(instance.is_finished());
+}
+
+#[test]
+fn test_attenuation() {
+    let attenuation = Attenuation {
+        min_distance: 2.0,
+        max_distance: 8.0,
+        rolloff: 1.0,
+    };
+    assert_eq!(attenuation.gain(0.0), 1.0);
+    assert_eq!(attenuation.gain(2.0), 1.0);
+    assert_eq!(attenuation.gain(4.0), 0.5);
+    assert_eq!(attenuation.gain(8.0), 0.25);
+    assert_eq!(attenuation.gain(100.0), 0.25);
+
+    let no_rolloff = Attenuation {
+        rolloff: 0.0,
+        ..attenuation
+    };
+    assert_eq!(no_rolloff.gain(100.0), 1.0);
+}
+
+#[test]
+fn test_channel_gains() {
+    let attenuation = Attenuation::default();
+    let listener = Mat4::identity();
+    assert_close(
+        &channel_gains(listener, Vec3::new(1.0, 0.0, 0.0), &attenuation),
+        &[0.0, 1.0],
+    );
+    assert_close(
+        &channel_gains(listener, Vec3::new(-1.0, 0.0, 0.0), &attenuation),
+        &[1.0, 0.0],
+    );
+    assert_close(
+        &channel_gains(listener, Vec3::new(0.0, 0.0, -1.0), &attenuation),
+        &[1.0, 1.0],
+    );
+    assert_close(
+        &channel_gains(listener, Vec3::zero(), &attenuation),
+        &[1.0, 1.0],
+    );
+    assert_close(
+        &channel_gains(listener, Vec3::new(0.0, 4.0, 0.0), &attenuation),
+        &[0.25, 0.25],
+    );
+
+    // Listener which is turned around hears the sound from the other side.
+    let turned = Rotor3::from_rotation_xz(PI)
+        .into_matrix()
+        .into_homogeneous();
+    assert_close(
+        &channel_gains(turned, Vec3::new(1.0, 0.0, 0.0), &attenuation),
+        &[1.0, 0.0],
+    );
+}
+
+#[test]
+fn test_update_spatial_audio() {
+    let mut world = World::new();
+    let listener = world.spawn();
+    world.insert(listener, AudioListener);
+    world.insert(
+        listener,
+        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+    );
+
+    let controls = Arc::new(Controls::new(1.0, 1.0, 0.0));
+    let emitter = world.spawn();
+    let instance = SoundInstance {
+        controls: controls.clone(),
+    };
+    world.insert(emitter, AudioEmitter::new(instance));
+    world.insert(
+        emitter,
+        Transform::from_translation(Vec3::new(2.0, 0.0, 0.0)),
+    );
+
+    propagate_transforms(&mut world);
+    assert_eq!(update_spatial_audio(&world), 1);
+    let gains = [controls.gains[0].get(), controls.gains[1].get()];
+    assert_close(&gains, &[0.0, 0.5]);
+}
@@ -0,0 +1,182 @@
+//! Ready-made camera controllers which are driven by window input.
+
+use ultraviolet::Vec3;
+
+use crate::{
+    app::DeltaTime,
+    window::input::{Input, Key, MouseButton},
+};
+
+use super::{Camera, MAX_PITCH};
+
+/// Position of the camera which orbits around the target.
+pub fn orbit_position(target: Vec3, yaw: f32, pitch: f32, radius: f32) -> Vec3 {
+    target + super::direction(yaw, pitch) * radius
+}
+
+/// Controller which orbits the camera around the target.
+///
+/// Drag with left mouse button to orbit, scroll to zoom
+/// and drag with middle mouse button to pan.
+#[derive(Debug, Copy, Clone)]
+pub struct OrbitController {
+    /// Point around which camera orbits.
+    pub target: Vec3,
+    /// Rotation of the camera around the target (in radians).
+    pub yaw: f32,
+    /// Elevation of the camera above the target (in radians).
+    pub pitch: f32,
+    /// Distance between the camera and the target.
+    pub radius: f32,
+    /// Min distance between the camera and the target.
+    pub min_radius: f32,
+    /// Max distance between the camera and the target.
+    pub max_radius: f32,
+    /// Radians of rotation per pixel of mouse movement.
+    pub sensitivity: f32,
+    /// Part of the radius which is zoomed per one scroll line.
+    pub zoom_speed: f32,
+    /// Part of the radius which is panned per pixel of mouse movement.
+    pub pan_speed: f32,
+    /// If vertical mouse movement should be inverted.
+    pub invert_y: bool,
+}
+
+impl OrbitController {
+    /// Creates new orbit controller around given target.
+    pub fn new(target: Vec3, radius: f32) -> Self {
+        Self {
+            target,
+            radius,
+            ..Default::default()
+        }
+    }
+
+    /// Updates the camera with input state of the current frame.
+    pub fn update(&mut self, camera: &mut Camera, input: &Input, _delta_time: DeltaTime) {
+        let delta = input.mouse_delta();
+        let delta_y = if self.invert_y { -delta.y } else { delta.y };
+
+        if input.mouse_pressed(MouseButton::Left) {
+            self.yaw -= delta.x * self.sensitivity;
+            self.pitch += delta_y * self.sensitivity;
+        }
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let zoom = 1.0 - input.scroll_delta() * self.zoom_speed;
+        self.radius = (self.radius * zoom.max(0.0)).clamp(self.min_radius, self.max_radius);
+
+        let position = self::orbit_position(self.target, self.yaw, self.pitch, self.radius);
+        let (yaw, pitch) = super::yaw_pitch(self.target - position);
+        camera.position = position;
+        camera.yaw = yaw;
+        camera.pitch = pitch;
+
+        if input.mouse_pressed(MouseButton::Middle) {
+            let pan = (camera.right() * -delta.x + camera.up() * delta_y) * self.pan_speed;
+            let pan = pan * self.radius;
+            self.target += pan;
+            camera.position += pan;
+        }
+    }
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            target: Vec3::zero(),
+            yaw: 0.0,
+            pitch: 0.0,
+            radius: 5.0,
+            min_radius: 0.1,
+            max_radius: 1000.0,
+            sensitivity: 0.005,
+            zoom_speed: 0.1,
+            pan_speed: 0.001,
+            invert_y: false,
+        }
+    }
+}
+
+/// Controller which moves the camera freely.
+///
+/// Use WASD to move, Q and E to move down and up and hold shift to move faster.
+/// Look around with mouse while right mouse button is held:
+/// cursor is grabbed while controller is active.
+#[derive(Debug, Copy, Clone)]
+pub struct FlyController {
+    /// Distance per second of movement.
+    pub speed: f32,
+    /// Multiplier of the speed while shift is held.
+    pub fast_multiplier: f32,
+    /// Radians of rotation per pixel of mouse movement.
+    pub sensitivity: f32,
+    /// If vertical mouse movement should be inverted.
+    pub invert_y: bool,
+    /// If mouse-look is active now.
+    active: bool,
+}
+
+impl FlyController {
+    /// Creates new fly controller with given speed.
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if mouse-look is active now.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Updates the camera with input state of the current frame.
+    pub fn update(&mut self, camera: &mut Camera, input: &Input, delta_time: DeltaTime) {
+        let active = input.mouse_pressed(MouseButton::Right);
+        if active != self.active {
+            self.active = active;
+            input.set_cursor_grab(active);
+        }
+
+        if self.active {
+            let delta = input.mouse_delta();
+            let delta_y = if self.invert_y { -delta.y } else { delta.y };
+            camera.yaw -= delta.x * self.sensitivity;
+            camera.pitch -= delta_y * self.sensitivity;
+        }
+        camera.pitch = camera.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let axis = |positive, negative| {
+            let positive = input.key_pressed(positive) as i32 as f32;
+            let negative = input.key_pressed(negative) as i32 as f32;
+            positive - negative
+        };
+        let direction = camera.forward() * axis(Key::W, Key::S)
+            + camera.right() * axis(Key::D, Key::A)
+            + Vec3::unit_z() * axis(Key::E, Key::Q);
+        if direction.mag_sq() == 0.0 {
+            return;
+        }
+
+        let fast = input.key_pressed(Key::LShift) || input.key_pressed(Key::RShift);
+        let speed = if fast {
+            self.speed * self.fast_multiplier
+        } else {
+            self.speed
+        };
+        camera.position += direction.normalized() * speed * delta_time.as_secs_f32();
+    }
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            speed: 5.0,
+            fast_multiplier: 4.0,
+            sensitivity: 0.003,
+            invert_y: false,
+            active: false,
+        }
+    }
+}
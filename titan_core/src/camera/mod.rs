@@ -0,0 +1,254 @@
+//! Camera utilities for game engine and your game.
+
+use std::sync::{Arc, Mutex};
+
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::graphics::RenderLayers;
+use crate::math::Frustum;
+
+pub mod controller;
+
+mod tests;
+
+/// Max absolute value of camera pitch (in radians).
+///
+/// Pitch is clamped to avoid gimbal flip when camera looks straight up or down.
+pub const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+/// Mode of projection of the camera.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProjectionMode {
+    /// Perspective projection with vertical field of view of the camera.
+    Perspective,
+    /// Orthographic projection which shows given height of the world (in world units).
+    Orthographic { height: f32 },
+}
+
+/// Camera which looks from its position in direction given by yaw and pitch.
+///
+/// World uses right-handed coordinate system with Z axis pointing up.
+/// Camera could be attached to the entity as a component; scene is rendered
+/// from the camera which is set by [`ActiveCamera`] handle.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct Camera {
+    /// Position of the camera in the world.
+    pub position: Vec3,
+    /// Rotation around Z axis (in radians).
+    pub yaw: f32,
+    /// Rotation up or down from XY plane (in radians).
+    pub pitch: f32,
+    /// Mode of projection of the camera (perspective by default).
+    pub projection_mode: ProjectionMode,
+    /// Vertical field of view of perspective projection (in radians).
+    pub fov: f32,
+    /// Distance to the near clipping plane.
+    pub near: f32,
+    /// Distance to the far clipping plane.
+    pub far: f32,
+    /// Bitmask of layers which are drawn from the camera (see [`RenderLayers`]).
+    pub layers: u32,
+}
+
+impl Camera {
+    /// Creates new camera with given position, yaw and pitch.
+    pub fn new(position: Vec3, yaw: f32, pitch: f32) -> Self {
+        Self {
+            position,
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            ..Default::default()
+        }
+    }
+
+    /// Creates new camera at given position which looks at the target.
+    pub fn look_at(position: Vec3, target: Vec3) -> Self {
+        let (yaw, pitch) = self::yaw_pitch(target - position);
+        Self::new(position, yaw, pitch)
+    }
+
+    /// Returns the same camera with given mode of projection.
+    pub fn with_projection_mode(self, projection_mode: ProjectionMode) -> Self {
+        Self {
+            projection_mode,
+            ..self
+        }
+    }
+
+    /// Unit vector of direction in which camera looks.
+    pub fn forward(&self) -> Vec3 {
+        self::direction(self.yaw, self.pitch)
+    }
+
+    /// Unit vector which points to the right of the camera (parallel to XY plane).
+    pub fn right(&self) -> Vec3 {
+        Vec3::new(self.yaw.sin(), -self.yaw.cos(), 0.0)
+    }
+
+    /// Unit vector which points up relative to the camera.
+    pub fn up(&self) -> Vec3 {
+        self.right().cross(self.forward())
+    }
+
+    /// View 4x4 matrix of the camera.
+    pub fn view(&self) -> Mat4 {
+        let target = self.position + self.forward();
+        Mat4::look_at(self.position, target, Vec3::unit_z())
+    }
+
+    /// Projection 4x4 matrix of the camera with given aspect ratio (width / height).
+    pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        use ultraviolet::projection::{orthographic_vk, perspective_vk};
+        match self.projection_mode {
+            ProjectionMode::Perspective => {
+                perspective_vk(self.fov, aspect_ratio, self.near, self.far)
+            }
+            ProjectionMode::Orthographic { height } => {
+                let (half_width, half_height) = (height * aspect_ratio / 2.0, height / 2.0);
+                orthographic_vk(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+
+    /// Volume which is visible from the camera with given aspect ratio (width / height).
+    pub fn frustum(&self, aspect_ratio: f32) -> Frustum {
+        Frustum::from_matrix(self.projection(aspect_ratio) * self.view())
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::zero(),
+            yaw: 0.0,
+            pitch: 0.0,
+            projection_mode: ProjectionMode::Perspective,
+            fov: 45f32.to_radians(),
+            near: 0.1,
+            far: 100.0,
+            layers: RenderLayers::DEFAULT.0,
+        }
+    }
+}
+
+/// Handle to the camera from which the scene is rendered into the whole window.
+///
+/// Camera is read by the renderer at the beginning of each frame, so it could be
+/// updated by controllers in the callback of the application through any clone of the handle.
+///
+#[derive(Debug, Default, Clone)]
+pub struct ActiveCamera {
+    camera: Arc<Mutex<Camera>>,
+}
+
+impl ActiveCamera {
+    /// Replaces the active camera with given one.
+    pub fn set(&self, camera: Camera) {
+        *self.camera.lock().unwrap() = camera;
+    }
+
+    /// Returns copy of the active camera.
+    pub fn get(&self) -> Camera {
+        *self.camera.lock().unwrap()
+    }
+}
+
+/// Orthographic camera of 2D world (for example, of sprites) which looks at its position.
+///
+/// World of the camera has Y axis pointing up, so sprites with greater Y are drawn higher.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera2D {
+    /// Position of the center of the view in the world.
+    pub position: Vec2,
+    /// Height of the world which is visible from the camera (in world units).
+    pub height: f32,
+    /// Counterclockwise rotation of the camera (in radians).
+    pub rotation: f32,
+}
+
+impl Camera2D {
+    /// Creates camera which shows given height of the world around its position.
+    pub fn new(position: Vec2, height: f32) -> Self {
+        Self {
+            position,
+            height,
+            rotation: 0.0,
+        }
+    }
+
+    /// Returns camera with given rotation (in radians).
+    pub fn with_rotation(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// View 4x4 matrix of the camera.
+    pub fn view(&self) -> Mat4 {
+        let translation =
+            Mat4::from_translation(Vec3::new(-self.position.x, -self.position.y, 0.0));
+        Mat4::from_rotation_z(-self.rotation) * translation
+    }
+
+    /// Orthographic projection 4x4 matrix of the camera with given aspect ratio (width / height).
+    pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        use ultraviolet::projection::orthographic_vk;
+
+        let (half_width, half_height) = (self.height * aspect_ratio / 2.0, self.height / 2.0);
+        // World is flat, so depth range only has to contain zero.
+        orthographic_vk(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            -1.0,
+            1.0,
+        )
+    }
+
+    /// Matrix which transforms the world into clip space of the view with given aspect ratio.
+    pub fn view_projection(&self, aspect_ratio: f32) -> Mat4 {
+        self.projection(aspect_ratio) * self.view()
+    }
+
+    /// Converts point of the view into the world.
+    ///
+    /// Point of the view is normalized: `[0, 0]` is the top left corner
+    /// and `[1, 1]` is the bottom right one (like cursor position divided by window size).
+    ///
+    pub fn view_to_world(&self, point: Vec2, aspect_ratio: f32) -> Vec2 {
+        let ndc = Vec4::new(2.0 * point.x - 1.0, 2.0 * point.y - 1.0, 0.5, 1.0);
+        let world = self.view_projection(aspect_ratio).inversed() * ndc;
+        world.xy()
+    }
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self::new(Vec2::zero(), 2.0)
+    }
+}
+
+/// Unit vector of direction given by yaw and pitch.
+pub fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (yaw_sin, yaw_cos) = yaw.sin_cos();
+    let (pitch_sin, pitch_cos) = pitch.sin_cos();
+    Vec3::new(pitch_cos * yaw_cos, pitch_cos * yaw_sin, pitch_sin)
+}
+
+/// Yaw and pitch of given direction.
+///
+/// Pitch is clamped to [`MAX_PITCH`].
+pub fn yaw_pitch(direction: Vec3) -> (f32, f32) {
+    let horizontal = (direction.x * direction.x + direction.y * direction.y).sqrt();
+    let yaw = direction.y.atan2(direction.x);
+    let pitch = direction.z.atan2(horizontal).clamp(-MAX_PITCH, MAX_PITCH);
+    (yaw, pitch)
+}
@@ -0,0 +1,94 @@
+#![cfg(test)]
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use ultraviolet::{Vec2, Vec3, Vec4};
+
+use super::{controller::*, *};
+
+fn assert_near(actual: Vec3, expected: Vec3) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-5, "{:?} != {:?}", actual, expected);
+}
+
+#[test]
+fn test_orbit_position() {
+    let target = Vec3::new(1.0, 2.0, 3.0);
+
+    let position = orbit_position(target, 0.0, 0.0, 2.0);
+    assert_near(position, Vec3::new(3.0, 2.0, 3.0));
+
+    let position = orbit_position(target, FRAC_PI_2, 0.0, 2.0);
+    assert_near(position, Vec3::new(1.0, 4.0, 3.0));
+
+    let position = orbit_position(target, PI, 0.0, 2.0);
+    assert_near(position, Vec3::new(-1.0, 2.0, 3.0));
+
+    let position = orbit_position(Vec3::zero(), 0.0, PI / 4.0, 2f32.sqrt());
+    assert_near(position, Vec3::new(1.0, 0.0, 1.0));
+}
+
+#[test]
+fn test_look_at() {
+    let camera = Camera::look_at(Vec3::new(2.0, 0.0, 2.0), Vec3::zero());
+    assert_near(camera.forward(), Vec3::new(-1.0, 0.0, -1.0).normalized());
+    assert_near(camera.right(), Vec3::new(0.0, 1.0, 0.0));
+    assert!(camera.up().z > 0.0);
+}
+
+#[test]
+fn test_pitch_clamp() {
+    let camera = Camera::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero());
+    assert!((camera.pitch + MAX_PITCH).abs() < 1e-6);
+
+    let camera = Camera::new(Vec3::zero(), 0.0, PI);
+    assert!((camera.pitch - MAX_PITCH).abs() < 1e-6);
+}
+
+#[test]
+fn test_orthographic_projection() {
+    // Camera at the origin looks along X axis and shows 4x2 units of the world.
+    let camera = Camera {
+        near: 1.0,
+        far: 10.0,
+        ..Camera::default()
+    }
+    .with_projection_mode(ProjectionMode::Orthographic { height: 2.0 });
+    let frustum = camera.frustum(2.0);
+
+    // Size of the visible area does not depend on the distance.
+    for distance in [1.5, 9.5] {
+        assert!(frustum.contains(Vec3::new(distance, 1.9, 0.9)));
+        assert!(frustum.contains(Vec3::new(distance, -1.9, -0.9)));
+        assert!(!frustum.contains(Vec3::new(distance, 2.1, 0.0)));
+        assert!(!frustum.contains(Vec3::new(distance, 0.0, -1.1)));
+    }
+    assert!(!frustum.contains(Vec3::new(0.5, 0.0, 0.0)));
+    assert!(!frustum.contains(Vec3::new(10.5, 0.0, 0.0)));
+
+    let perspective = camera.with_projection_mode(ProjectionMode::Perspective);
+    assert!(perspective.frustum(2.0).contains(Vec3::new(9.5, 2.1, 0.0)));
+}
+
+#[test]
+fn test_camera_2d() {
+    let camera = Camera2D::new(Vec2::new(10.0, 5.0), 4.0);
+    let view_projection = camera.view_projection(2.0);
+    let clip = |point: Vec2| {
+        let clip = view_projection * Vec4::new(point.x, point.y, 0.0, 1.0);
+        clip.xyz() / clip.w
+    };
+    let flat = |point: Vec2| Vec3::new(point.x, point.y, 0.0);
+    assert_near(clip(Vec2::new(10.0, 5.0)), Vec3::new(0.0, 0.0, 0.5));
+    // Top of the world is at the top of the view (negative Y in Vulkan).
+    assert_near(clip(Vec2::new(10.0, 7.0)), Vec3::new(0.0, -1.0, 0.5));
+    assert_near(clip(Vec2::new(14.0, 5.0)), Vec3::new(1.0, 0.0, 0.5));
+
+    let top_left = camera.view_to_world(Vec2::new(0.0, 0.0), 2.0);
+    assert_near(flat(top_left), Vec3::new(6.0, 7.0, 0.0));
+
+    // Rotated camera sees the world rotated in the opposite direction.
+    let camera = Camera2D::new(Vec2::zero(), 2.0).with_rotation(FRAC_PI_2);
+    let point = camera.view_to_world(Vec2::new(1.0, 0.5), 1.0);
+    assert_near(flat(point), Vec3::new(0.0, 1.0, 0.0));
+}
@@ -0,0 +1,360 @@
+//! Overrides of configuration from command-line arguments.
+//!
+//! Options are described by the declarative table, so parsing and usage text
+//! always stay in sync with each other.
+
+use std::env;
+use std::net::Ipv4Addr;
+
+use thiserror::Error;
+use vulkano::image::SampleCount;
+
+use crate::{
+    graphics::{AaMode, PresentMode, ValidationLevel},
+    window::Size,
+};
+
+use super::{Config, MAX_RENDER_SCALE, MIN_RENDER_SCALE};
+
+/// Size of the window which is used if only one of its dimensions was overridden.
+const DEFAULT_WINDOW_SIZE: Size = Size::new(800, 600);
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ArgsError {
+    #[error("unknown argument `{0}` (see `--help`)")]
+    Unknown(String),
+
+    #[error("option `--{0}` requires a value")]
+    MissingValue(String),
+
+    #[error("invalid value `{value}` of option `--{option}`: {message}")]
+    InvalidValue {
+        option: String,
+        value: String,
+        message: String,
+    },
+
+    #[error("option `--{0}` conflicts with option `--{1}`")]
+    Conflict(String, String),
+
+    /// Usage was requested with `--help`, so it should be printed instead of running.
+    #[error("{0}")]
+    Help(String),
+}
+
+/// Kind of the command-line option.
+#[derive(Copy, Clone)]
+enum Kind {
+    /// Boolean flag which is enabled by its name (e.g. `--vsync`)
+    /// or set explicitly (e.g. `--vsync=off`).
+    Flag(fn(&mut Overrides, bool)),
+    /// Option with required value (e.g. `--width=800` or `--width 800`).
+    Value(&'static str, fn(&mut Overrides, &str) -> Result<(), String>),
+}
+
+/// Description of the command-line option.
+struct Spec {
+    name: &'static str,
+    kind: Kind,
+    help: &'static str,
+}
+
+/// All options which are supported by [`Config::apply_args`].
+const SPECS: &[Spec] = &[
+    Spec {
+        name: "width",
+        kind: Kind::Value("N", |o, value| {
+            o.width = Some(self::parse_positive(value)?);
+            Ok(())
+        }),
+        help: "width of the window in pixels",
+    },
+    Spec {
+        name: "height",
+        kind: Kind::Value("N", |o, value| {
+            o.height = Some(self::parse_positive(value)?);
+            Ok(())
+        }),
+        help: "height of the window in pixels",
+    },
+    Spec {
+        name: "fullscreen",
+        kind: Kind::Flag(|o, enabled| o.config.fullscreen = Some(enabled)),
+        help: "make the window borderless fullscreen (conflicts with size of the window)",
+    },
+    Spec {
+        name: "vsync",
+        kind: Kind::Flag(|o, enabled| o.config.vsync = Some(enabled)),
+        help: "synchronize presentation with the display (`--vsync=off` to disable)",
+    },
+    Spec {
+        name: "present-mode",
+        kind: Kind::Value("MODE", |o, value| {
+            let mode = PresentMode::from_name(value)
+                .ok_or_else(|| "expected `fifo`, `mailbox` or `immediate`".to_string())?;
+            o.config.present_mode = Some(mode);
+            Ok(())
+        }),
+        help: "mode of presentation which overrides vsync",
+    },
+    Spec {
+        name: "max-fps",
+        kind: Kind::Value("N", |o, value| {
+            o.config.max_fps = Some(self::parse_positive(value)?);
+            Ok(())
+        }),
+        help: "limit count of frames rendered per second",
+    },
+    Spec {
+        name: "validation",
+        kind: Kind::Flag(|o, enabled| {
+            o.config.validation = if enabled {
+                ValidationLevel::Full
+            } else {
+                ValidationLevel::Off
+            };
+        }),
+        help: "enable validation layers of Vulkan",
+    },
+    Spec {
+        name: "validation-level",
+        kind: Kind::Value("LEVEL", |o, value| {
+            o.config.validation = match value {
+                "off" => ValidationLevel::Off,
+                "errors" => ValidationLevel::Errors,
+                "full" => ValidationLevel::Full,
+                _ => return Err("expected `off`, `errors` or `full`".to_string()),
+            };
+            Ok(())
+        }),
+        help: "validation layers of Vulkan which report only errors or all messages",
+    },
+    Spec {
+        name: "headless",
+        kind: Kind::Flag(|o, enabled| o.config.headless = enabled),
+        help: "render into offscreen images without any window",
+    },
+    Spec {
+        name: "device-index",
+        kind: Kind::Value("N", |o, value| {
+            let index = value.parse().map_err(|_| "expected an index".to_string())?;
+            o.config.device_index = Some(index);
+            Ok(())
+        }),
+        help: "index of the physical device to render with",
+    },
+    Spec {
+        name: "render-scale",
+        kind: Kind::Value("SCALE", |o, value| {
+            let scale: f32 = value.parse().map_err(|_| "expected a number".to_string())?;
+            if !(MIN_RENDER_SCALE..=MAX_RENDER_SCALE).contains(&scale) {
+                let message = format!(
+                    "expected a number from {} to {}",
+                    MIN_RENDER_SCALE, MAX_RENDER_SCALE,
+                );
+                return Err(message);
+            }
+            o.config.render_scale = Some(scale);
+            Ok(())
+        }),
+        help: "scale of the scene resolution relative to the window",
+    },
+    Spec {
+        name: "msaa",
+        kind: Kind::Value("SAMPLES", |o, value| {
+            let samples = match value {
+                "1" => SampleCount::Sample1,
+                "2" => SampleCount::Sample2,
+                "4" => SampleCount::Sample4,
+                "8" => SampleCount::Sample8,
+                "16" => SampleCount::Sample16,
+                "32" => SampleCount::Sample32,
+                "64" => SampleCount::Sample64,
+                _ => return Err("expected 1, 2, 4, 8, 16, 32 or 64".to_string()),
+            };
+            o.config.antialiasing = AaMode::Msaa(samples).normalized();
+            Ok(())
+        }),
+        help: "multisample antialiasing of the scene with given count of samples",
+    },
+    Spec {
+        name: "diagnostics-port",
+        kind: Kind::Value("PORT", |o, value| {
+            let port = value.parse().map_err(|_| "expected a port".to_string())?;
+            o.config.diagnostics_address = Some((Ipv4Addr::LOCALHOST, port).into());
+            Ok(())
+        }),
+        help: "stream logs and frame statistics to clients of the local port",
+    },
+    Spec {
+        name: "help",
+        kind: Kind::Flag(|o, enabled| o.help |= enabled),
+        help: "print this help",
+    },
+];
+
+/// Configuration with overrides which are applied so far.
+struct Overrides {
+    config: Config,
+    width: Option<u32>,
+    height: Option<u32>,
+    help: bool,
+}
+
+fn parse_positive(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err("expected a positive integer".to_string()),
+        Ok(value) => Ok(value),
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err("expected `on` or `off`".to_string()),
+    }
+}
+
+/// Applies arguments to the configuration.
+///
+/// Returns arguments which are not options of the engine if unknown ones are allowed.
+///
+pub(super) fn apply(
+    config: Config,
+    args: impl IntoIterator<Item = String>,
+    allow_unknown: bool,
+) -> Result<(Config, Vec<String>), ArgsError> {
+    let mut overrides = Overrides {
+        config,
+        width: None,
+        height: None,
+        help: false,
+    };
+    let mut unknown = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let option = arg.strip_prefix("--").unwrap_or("");
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (option, None),
+        };
+        let spec = match SPECS
+            .iter()
+            .find(|spec| !name.is_empty() && spec.name == name)
+        {
+            Some(spec) => spec,
+            None if allow_unknown => {
+                unknown.push(arg);
+                continue;
+            }
+            None => return Err(ArgsError::Unknown(arg)),
+        };
+        let invalid = |value: &str, message| ArgsError::InvalidValue {
+            option: spec.name.to_string(),
+            value: value.to_string(),
+            message,
+        };
+        match spec.kind {
+            Kind::Flag(apply) => {
+                let enabled = match value {
+                    Some(value) => {
+                        self::parse_bool(value).map_err(|error| invalid(value, error))?
+                    }
+                    None => true,
+                };
+                apply(&mut overrides, enabled);
+            }
+            Kind::Value(_, apply) => {
+                let value = match value {
+                    Some(value) => value.to_string(),
+                    None => args
+                        .next()
+                        .ok_or_else(|| ArgsError::MissingValue(spec.name.to_string()))?,
+                };
+                apply(&mut overrides, &value).map_err(|error| invalid(&value, error))?;
+            }
+        }
+        if overrides.help {
+            return Err(ArgsError::Help(self::usage(overrides.config.name())));
+        }
+    }
+
+    let Overrides {
+        mut config,
+        width,
+        height,
+        ..
+    } = overrides;
+    if config.fullscreen() {
+        let explicit = [("width", width), ("height", height)];
+        if let Some((name, _)) = explicit.iter().find(|(_, value)| value.is_some()) {
+            let conflict = ArgsError::Conflict("fullscreen".to_string(), name.to_string());
+            return Err(conflict);
+        }
+    }
+    if width.is_some() || height.is_some() {
+        let size = config.window_size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        let width = width.unwrap_or(size.width);
+        let height = height.unwrap_or(size.height);
+        config.window_size = Some(Size::new(width, height));
+    }
+    Ok((config, unknown))
+}
+
+/// Usage text of the program with given name which lists all options of the engine.
+pub(super) fn usage(program: &str) -> String {
+    let options: Vec<_> = SPECS
+        .iter()
+        .map(|spec| match spec.kind {
+            Kind::Flag(_) => format!("--{}", spec.name),
+            Kind::Value(placeholder, _) => format!("--{}={}", spec.name, placeholder),
+        })
+        .collect();
+    let width = options.iter().map(String::len).max().unwrap_or(0);
+
+    let mut usage = format!("Usage: {} [OPTIONS]\n\nOptions:\n", program);
+    for (option, spec) in options.iter().zip(SPECS) {
+        usage += &format!("  {:width$}  {}\n", option, spec.help, width = width);
+    }
+    usage
+}
+
+impl Config {
+    /// Overrides configuration with command-line arguments
+    /// (without the name of the program).
+    ///
+    /// Options of the value could be passed as `--width=800` or `--width 800`,
+    /// flags could be passed as `--vsync` or `--vsync=off`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any argument is unknown, any value is invalid
+    /// or options conflict with each other. If usage was requested with `--help`,
+    /// [`ArgsError::Help`] with usage text is returned.
+    ///
+    pub fn apply_args(self, args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let (config, _) = self::apply(self, args, false)?;
+        Ok(config)
+    }
+
+    /// Same as [`Config::apply_args`], but unknown arguments are allowed
+    /// and returned in order, so game could parse its own arguments.
+    pub fn apply_args_allow_unknown(
+        self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<(Self, Vec<String>), ArgsError> {
+        self::apply(self, args, true)
+    }
+
+    /// Creates default configuration overridden by arguments of the current process.
+    pub fn from_default_args() -> Result<Self, ArgsError> {
+        Self::default().apply_args(env::args().skip(1))
+    }
+
+    /// Usage text of the command-line options which are supported by [`Config::apply_args`].
+    pub fn args_usage(&self) -> String {
+        self::usage(self.name())
+    }
+}
@@ -1,13 +1,91 @@
 //! Configuration utilities for game engine and your game.
 
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use semver::Version;
 
+use crate::{
+    app::{CancellationToken, OverlayLevel, ProgressSink},
+    graphics::{
+        pipeline_cache, AaMode, CompareOp, PassOps, PresentMode, QualityConfig, TimeoutPolicy,
+        ValidationFilter, ValidationLevel, DEFAULT_FRAMES_IN_FLIGHT, MAX_FRAMES_IN_FLIGHT,
+    },
+    math::Color,
+    settings::{self, EngineSettings},
+    task,
+    window::{input::Key, FullscreenMode, Size, WindowIcon},
+};
+
+pub use args::ArgsError;
+
+mod args;
+mod tests;
+
+/// Minimal scale of the scene resolution relative to the window.
+pub const MIN_RENDER_SCALE: f32 = 0.1;
+
+/// Maximal scale of the scene resolution relative to the window.
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+/// Default deadzone of gamepad axes.
+pub const DEFAULT_GAMEPAD_DEADZONE: f32 = 0.1;
+
+/// Maximal deadzone of gamepad axes.
+pub const MAX_GAMEPAD_DEADZONE: f32 = 0.9;
+
 /// This struct represents general configuration of game engine.
 #[derive(Debug, Clone)]
 pub struct Config {
     name: String,
     version: Version,
-    enable_validation: bool,
+    validation: ValidationLevel,
+    validation_filter: ValidationFilter,
+    pipeline_statistics: bool,
+    gpu_profiling: bool,
+    headless: bool,
+    antialiasing: AaMode,
+    title: Option<String>,
+    icon: Option<WindowIcon>,
+    resizable: bool,
+    window_size: Option<Size>,
+    min_window_size: Option<Size>,
+    max_window_size: Option<Size>,
+    window_position: Option<[i32; 2]>,
+    maximized: Option<bool>,
+    fullscreen: Option<bool>,
+    fullscreen_mode: FullscreenMode,
+    vsync: Option<bool>,
+    present_mode: Option<PresentMode>,
+    max_fps: Option<u32>,
+    device_index: Option<usize>,
+    render_scale: Option<f32>,
+    clear_color: Color,
+    depth_compare: CompareOp,
+    pass_ops: PassOps,
+    texture_mipmaps: bool,
+    frames_in_flight: usize,
+    submit_thread: bool,
+    timeout_policy: TimeoutPolicy,
+    worker_threads: Option<usize>,
+    zero_delta_when_paused: bool,
+    fixed_timestep: Option<Duration>,
+    pause_key: Option<Key>,
+    step_key: Option<Key>,
+    adaptive_quality: Option<QualityConfig>,
+    overlay_key: Option<Key>,
+    overlay_level: OverlayLevel,
+    gamepad_deadzone: f32,
+    settings_path: Option<PathBuf>,
+    persist_settings: bool,
+    pipeline_cache_path: Option<PathBuf>,
+    persist_pipeline_cache: bool,
+    preload: Vec<PathBuf>,
+    scene: Option<PathBuf>,
+    progress: Option<ProgressSink>,
+    cancellation: Option<CancellationToken>,
+    diagnostics_address: Option<SocketAddr>,
 }
 
 pub const ENGINE_NAME: &str = env!("CARGO_CRATE_NAME", "library must be compiled by Cargo");
@@ -19,14 +97,561 @@ pub struct Config {
 
 impl Config {
     /// Creates new configuration with given name, version and validation usage.
+    ///
+    /// Enabled validation reports messages of all severities
+    /// (see [`Config::with_validation_level`]).
+    ///
     pub const fn new(name: String, version: Version, enable_validation: bool) -> Self {
+        let validation = if enable_validation {
+            ValidationLevel::Full
+        } else {
+            ValidationLevel::Off
+        };
         Self {
             name,
             version,
-            enable_validation,
+            validation,
+            validation_filter: ValidationFilter::new(),
+            pipeline_statistics: false,
+            gpu_profiling: false,
+            headless: false,
+            antialiasing: AaMode::Off,
+            title: None,
+            icon: None,
+            resizable: true,
+            window_size: None,
+            min_window_size: None,
+            max_window_size: None,
+            window_position: None,
+            maximized: None,
+            fullscreen: None,
+            fullscreen_mode: FullscreenMode::Borderless,
+            vsync: None,
+            present_mode: None,
+            max_fps: None,
+            device_index: None,
+            render_scale: None,
+            clear_color: Color::BLACK,
+            depth_compare: CompareOp::Less,
+            pass_ops: PassOps::new(),
+            texture_mipmaps: false,
+            frames_in_flight: DEFAULT_FRAMES_IN_FLIGHT,
+            submit_thread: false,
+            timeout_policy: TimeoutPolicy::new(),
+            worker_threads: None,
+            zero_delta_when_paused: true,
+            fixed_timestep: None,
+            pause_key: None,
+            step_key: None,
+            adaptive_quality: None,
+            overlay_key: Some(Key::F3),
+            overlay_level: OverlayLevel::Stats,
+            gamepad_deadzone: DEFAULT_GAMEPAD_DEADZONE,
+            settings_path: None,
+            persist_settings: true,
+            pipeline_cache_path: None,
+            persist_pipeline_cache: true,
+            preload: Vec::new(),
+            scene: None,
+            progress: None,
+            cancellation: None,
+            diagnostics_address: None,
         }
     }
 
+    /// Enables or disables validation (useful for debugging).
+    ///
+    /// Enabled validation reports messages of all severities.
+    ///
+    pub fn with_validation(mut self, enabled: bool) -> Self {
+        self.validation = if enabled {
+            ValidationLevel::Full
+        } else {
+            ValidationLevel::Off
+        };
+        self
+    }
+
+    /// Sets level of validation by Khronos validation layer.
+    pub fn with_validation_level(mut self, level: ValidationLevel) -> Self {
+        self.validation = level;
+        self
+    }
+
+    /// Sets filter of validation messages which are logged,
+    /// so known noisy messages could be suppressed.
+    pub fn with_validation_filter(mut self, filter: ValidationFilter) -> Self {
+        self.validation_filter = filter;
+        self
+    }
+
+    /// Enables or disables pipeline statistics of each frame
+    /// (see [`FrameStats::pipeline_stats`](crate::graphics::FrameStats::pipeline_stats)).
+    ///
+    /// Statistics are disabled by default.
+    ///
+    pub fn with_pipeline_statistics(mut self, enabled: bool) -> Self {
+        self.pipeline_statistics = enabled;
+        self
+    }
+
+    /// Enables or disables measuring of time which the device spends on render passes
+    /// of each frame (see [`FrameStats::gpu_timings`](crate::graphics::FrameStats::gpu_timings)).
+    ///
+    /// Timings are measured by timestamp queries. Profiling is disabled by default.
+    ///
+    pub fn with_gpu_profiling(mut self, enabled: bool) -> Self {
+        self.gpu_profiling = enabled;
+        self
+    }
+
+    /// Enables or disables headless mode, in which no window or surface is created.
+    ///
+    /// Frames are rendered into offscreen images of the window size
+    /// (see [`Config::with_window_size`]), and the last of them could be read
+    /// by [`Application::read_pixels`](crate::app::Application::read_pixels).
+    /// Headless mode is disabled by default.
+    ///
+    pub fn with_headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Sets antialiasing mode of the scene which is used since the first frame.
+    ///
+    /// If sample count of multisampling is not supported by the device,
+    /// it is clamped to the highest supported one (down to disabled antialiasing).
+    /// Antialiasing is disabled by default.
+    ///
+    pub fn with_antialiasing(mut self, mode: AaMode) -> Self {
+        self.antialiasing = mode;
+        self
+    }
+
+    /// Sets title of the window.
+    ///
+    /// Name of the game is used by default.
+    ///
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets icon of the window.
+    ///
+    /// Icon is chosen by the platform by default.
+    ///
+    pub fn with_icon(mut self, icon: WindowIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Allows or forbids the user to resize the window.
+    ///
+    /// Window is resizable by default.
+    ///
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets initial size of the window (in physical pixels).
+    ///
+    /// Size is chosen by the platform by default.
+    ///
+    pub fn with_window_size(mut self, size: Size) -> Self {
+        self.window_size = Some(size);
+        self
+    }
+
+    /// Sets minimal size of the window (in physical pixels).
+    ///
+    /// Window is at least 250x100 logical pixels by default.
+    ///
+    pub fn with_min_window_size(mut self, size: Size) -> Self {
+        self.min_window_size = Some(size);
+        self
+    }
+
+    /// Sets maximal size of the window (in physical pixels).
+    ///
+    /// Size of the window is not limited by default.
+    ///
+    pub fn with_max_window_size(mut self, size: Size) -> Self {
+        self.max_window_size = Some(size);
+        self
+    }
+
+    /// Sets initial position of top left corner of the window
+    /// on the desktop (in physical pixels).
+    ///
+    /// Position is chosen by the platform by default.
+    ///
+    pub fn with_window_position(mut self, position: [i32; 2]) -> Self {
+        self.window_position = Some(position);
+        self
+    }
+
+    /// Makes the window initially maximized.
+    ///
+    /// Window is not maximized by default.
+    ///
+    pub fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = Some(maximized);
+        self
+    }
+
+    /// Makes the window fullscreen on the current monitor.
+    ///
+    /// Window is not fullscreen by default.
+    /// Fullscreen is borderless unless another mode was set
+    /// with [`Config::with_fullscreen_mode`].
+    ///
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = Some(fullscreen);
+        self
+    }
+
+    /// Sets mode which is used if the window is fullscreen.
+    ///
+    /// Exclusive fullscreen falls back to borderless one if its video mode
+    /// is not supported by the primary monitor.
+    ///
+    pub fn with_fullscreen_mode(mut self, mode: FullscreenMode) -> Self {
+        self.fullscreen_mode = mode;
+        self
+    }
+
+    /// Enables or disables synchronization of presentation with refresh rate of the display.
+    ///
+    /// If disabled, frames are presented immediately (if supported by the surface),
+    /// which could lead to tearing. Vertical synchronization is enabled by default.
+    ///
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.vsync = Some(vsync);
+        self
+    }
+
+    /// Sets mode in which frames are presented into the window,
+    /// which overrides [`Config::with_vsync`].
+    ///
+    /// If the mode is not supported by the surface, the next supported mode with more latency
+    /// is used instead (see [`PresentMode::negotiate`]). By default, the mode is
+    /// [`PresentMode::Mailbox`] with vertical synchronization or [`PresentMode::Immediate`] without it.
+    ///
+    pub fn with_present_mode(mut self, mode: PresentMode) -> Self {
+        self.present_mode = Some(mode);
+        self
+    }
+
+    /// Limits count of frames which are rendered per second,
+    /// sleeping on the CPU before rendering of the next frame.
+    ///
+    /// Limiter is useful with [`PresentMode::Immediate`] or [`PresentMode::Mailbox`],
+    /// which do not wait for the display. Frame rate is not limited by default or if it is zero.
+    ///
+    pub fn with_max_fps(mut self, max_fps: u32) -> Self {
+        self.max_fps = (max_fps != 0).then_some(max_fps);
+        self
+    }
+
+    /// Sets index of the physical device (in order of enumeration) which must be used.
+    ///
+    /// Suitable device is chosen automatically by default.
+    ///
+    pub fn with_device_index(mut self, index: usize) -> Self {
+        self.device_index = Some(index);
+        self
+    }
+
+    /// Sets scale of the scene resolution relative to the window.
+    ///
+    /// Scene is rendered offscreen and scaled to the window if scale is not `1.0`,
+    /// while UI is always rendered in the resolution of the window.
+    /// Scale is clamped into range from [`MIN_RENDER_SCALE`] to [`MAX_RENDER_SCALE`].
+    ///
+    pub fn with_render_scale(mut self, scale: f32) -> Self {
+        self.render_scale = Some(scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE));
+        self
+    }
+
+    /// Sets color which the scene is cleared with.
+    ///
+    /// Scene is cleared with opaque black by default.
+    ///
+    pub fn with_clear_color(mut self, color: impl Into<Color>) -> Self {
+        self.clear_color = color.into();
+        self
+    }
+
+    /// Sets operator which compares depth of the fragment with the depth buffer.
+    ///
+    /// Fragments closer to the camera have lesser depth by default (`CompareOp::Less`).
+    /// Use `CompareOp::Greater` for reversed depth, which has better precision
+    /// with floating-point depth buffer: depth buffer is cleared with zero then.
+    ///
+    pub fn with_depth_compare(mut self, compare_op: CompareOp) -> Self {
+        self.depth_compare = compare_op;
+        self
+    }
+
+    /// Sets load and store operations of color and depth of the scene.
+    ///
+    /// By default, both color (with [clear color](Self::with_clear_color)) and depth are cleared
+    /// at the start of each frame. Contents of the previous frame could be kept instead
+    /// (for example, for accumulation effects).
+    ///
+    pub fn with_pass_ops(mut self, ops: PassOps) -> Self {
+        self.pass_ops = ops;
+        self
+    }
+
+    /// Enables or disables generation of mip levels for textures of UI and sprites.
+    ///
+    /// Without mip levels, textures which are drawn much smaller than their size shimmer.
+    /// Mip levels are generated on the GPU when the texture is registered. Disabled by default.
+    ///
+    pub fn with_texture_mipmaps(mut self, enabled: bool) -> Self {
+        self.texture_mipmaps = enabled;
+        self
+    }
+
+    /// Sets count of frames which could be rendered concurrently,
+    /// clamped to `1..=MAX_FRAMES_IN_FLIGHT`.
+    ///
+    /// Two frames are in flight by default. Three frames (triple buffering)
+    /// increase throughput if CPU and GPU times vary, one frame decreases input latency.
+    ///
+    pub fn with_frames_in_flight(mut self, frames_in_flight: usize) -> Self {
+        self.frames_in_flight = frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT);
+        self
+    }
+
+    /// Enables or disables submission of frames on the dedicated submit thread.
+    ///
+    /// Commands of the frame are still recorded on the main thread, but the frame is
+    /// submitted and presented on the submit thread, so the event loop is not blocked
+    /// while the driver waits for the swapchain. Disabled by default.
+    ///
+    pub fn with_submit_thread(mut self, submit_thread: bool) -> Self {
+        self.submit_thread = submit_thread;
+        self
+    }
+
+    /// Sets policy of the renderer for timeouts of swapchain image acquisition
+    /// and waits for frames in flight.
+    ///
+    /// Frames are skipped on timeout instead of blocking forever (see [`TimeoutPolicy`]).
+    ///
+    pub fn with_timeout_policy(mut self, policy: TimeoutPolicy) -> Self {
+        self.timeout_policy = policy;
+        self
+    }
+
+    /// Sets count of worker threads of the [task pool](crate::task::TaskPool) of the application.
+    ///
+    /// One thread for each CPU except the one of the main thread is created by default.
+    ///
+    pub fn with_worker_threads(mut self, count: usize) -> Self {
+        self.worker_threads = Some(count.max(1));
+        self
+    }
+
+    /// Sets whether delta time of updates is zero while rendering is paused
+    /// (see [`PauseControl`](crate::graphics::PauseControl)).
+    ///
+    /// Otherwise delta time is clamped to [`MAX_PAUSED_DELTA`](crate::app::MAX_PAUSED_DELTA).
+    /// Updates of frames which were stepped are always clamped, so the game advances
+    /// one frame at a time. Delta time is zero while paused by default.
+    ///
+    pub fn with_zero_delta_when_paused(mut self, zero: bool) -> Self {
+        self.zero_delta_when_paused = zero;
+        self
+    }
+
+    /// Sets duration of the fixed step of [`Event::FixedUpdate`](crate::window::Event::FixedUpdate)
+    /// (for example, `Duration::from_secs(1) / 60` for 60 Hz).
+    ///
+    /// Fixed timestep is disabled by default or if the step is zero.
+    ///
+    pub fn with_fixed_timestep(mut self, step: Duration) -> Self {
+        self.fixed_timestep = (!step.is_zero()).then_some(step);
+        self
+    }
+
+    /// Binds keyboard key which pauses or resumes rendering of frames.
+    ///
+    /// Key is not bound by default.
+    ///
+    pub fn with_pause_key(mut self, key: Key) -> Self {
+        self.pause_key = Some(key);
+        self
+    }
+
+    /// Binds keyboard key which renders exactly one frame while paused.
+    ///
+    /// Key is not bound by default.
+    ///
+    pub fn with_step_key(mut self, key: Key) -> Self {
+        self.step_key = Some(key);
+        self
+    }
+
+    /// Enables adaptive quality which holds target frame time of the configuration
+    /// (see [`QualityController`](crate::graphics::QualityController)).
+    ///
+    /// Adaptive quality is disabled by default.
+    ///
+    pub fn with_adaptive_quality(mut self, config: QualityConfig) -> Self {
+        self.adaptive_quality = Some(config);
+        self
+    }
+
+    /// Binds keyboard key which cycles levels of built-in overlays
+    /// (see [`OverlayLevel`](crate::app::OverlayLevel)).
+    ///
+    /// Key is `F3` by default.
+    ///
+    pub fn with_overlay_key(mut self, key: Key) -> Self {
+        self.overlay_key = Some(key);
+        self
+    }
+
+    /// Unbinds keyboard key which cycles levels of built-in overlays.
+    pub fn without_overlay_key(mut self) -> Self {
+        self.overlay_key = None;
+        self
+    }
+
+    /// Sets level of built-in overlays which are shown on the first run
+    /// (for example, [`OverlayLevel::Off`] to hide FPS and frame statistics).
+    ///
+    /// Level which was persisted in engine settings by the previous run is used instead,
+    /// if any. Overlays show frame statistics by default.
+    ///
+    pub fn with_overlay_level(mut self, level: OverlayLevel) -> Self {
+        self.overlay_level = level;
+        self
+    }
+
+    /// Sets deadzone of gamepad axes (from `0.0` to [`MAX_GAMEPAD_DEADZONE`]),
+    /// so values of axes near the center are reported as zero
+    /// (only with `gamepad` feature).
+    ///
+    /// Deadzone is [`DEFAULT_GAMEPAD_DEADZONE`] by default.
+    ///
+    pub fn with_gamepad_deadzone(mut self, deadzone: f32) -> Self {
+        self.gamepad_deadzone = deadzone.clamp(0.0, MAX_GAMEPAD_DEADZONE);
+        self
+    }
+
+    /// Sets file in which engine settings (like geometry of the window
+    /// or level of built-in overlays) are persisted across runs.
+    ///
+    /// Settings are persisted into the configuration directory of the platform
+    /// by default (see [`settings::default_path`]).
+    ///
+    pub fn with_settings_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.settings_path = Some(path.into());
+        self.persist_settings = true;
+        self
+    }
+
+    /// Disables persistence of engine settings across runs.
+    pub fn without_settings(mut self) -> Self {
+        self.persist_settings = false;
+        self
+    }
+
+    /// Sets file in which compiled pipelines are persisted across runs,
+    /// so shaders are not compiled again on the next launch.
+    ///
+    /// Pipeline cache is persisted into the cache directory of the platform by default
+    /// (see [`pipeline_cache::default_path`]).
+    ///
+    pub fn with_pipeline_cache_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pipeline_cache_path = Some(path.into());
+        self.persist_pipeline_cache = true;
+        self
+    }
+
+    /// Disables persistence of pipeline cache across runs.
+    pub fn without_pipeline_cache(mut self) -> Self {
+        self.persist_pipeline_cache = false;
+        self
+    }
+
+    /// Sets image files which are loaded as UI textures while the application is created
+    /// (see [`Application::preloaded`](crate::app::Application::preloaded)).
+    ///
+    /// Window is shown with the clear color while they are loaded,
+    /// and progress of loading is displayed in its title.
+    ///
+    pub fn with_preload<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.preload = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets RON file of the scene which is loaded while the application is created
+    /// (see [`Application::scene`](crate::app::Application::scene), only with `scene` feature).
+    ///
+    /// Application is not created if the scene could not be loaded.
+    ///
+    pub fn with_scene(mut self, path: impl Into<PathBuf>) -> Self {
+        self.scene = Some(path.into());
+        self
+    }
+
+    /// Sets callback which receives progress of renderer creation and preloads.
+    pub fn with_progress(mut self, sink: ProgressSink) -> Self {
+        self.progress = Some(sink);
+        self
+    }
+
+    /// Sets token which cancels creation of the application.
+    ///
+    /// Token is also cancelled when the window is closed during preloads.
+    ///
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Enables diagnostics server which listens on the port of the loopback interface
+    /// (see [`diagnostics`](crate::app::diagnostics)).
+    ///
+    /// If the port is 0, it is chosen by the system
+    /// (see [`Application::diagnostics`](crate::app::Application::diagnostics)).
+    /// Server is disabled by default.
+    ///
+    pub fn with_diagnostics_port(self, port: u16) -> Self {
+        self.with_diagnostics_address((Ipv4Addr::LOCALHOST, port).into())
+    }
+
+    /// Enables diagnostics server which listens on given address.
+    ///
+    /// Server has no authentication, so addresses other than loopback ones
+    /// expose the application to everyone in the network.
+    ///
+    pub fn with_diagnostics_address(mut self, address: SocketAddr) -> Self {
+        self.diagnostics_address = Some(address);
+        self
+    }
+
+    /// Fills values which were not set explicitly with the stored settings.
+    pub(crate) fn with_stored(mut self, settings: &EngineSettings) -> Self {
+        self.window_size = self.window_size.or(settings.window_size);
+        self.window_position = self.window_position.or(settings.window_position);
+        self.maximized = self.maximized.or(Some(settings.maximized));
+        self.fullscreen = self.fullscreen.or(Some(settings.fullscreen));
+        self.vsync = self.vsync.or(Some(settings.vsync));
+        let scale = settings
+            .render_scale
+            .clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+        self.render_scale = self.render_scale.or(Some(scale));
+        self
+    }
+
     /// Name of your game.
     pub fn name(&self) -> &str {
         &self.name
@@ -39,7 +664,251 @@ pub fn version(&self) -> &Version {
 
     /// If game will use validation (useful for debugging).
     pub fn enable_validation(&self) -> bool {
-        self.enable_validation
+        self.validation.is_enabled()
+    }
+
+    /// Level of validation by Khronos validation layer.
+    pub fn validation_level(&self) -> ValidationLevel {
+        self.validation
+    }
+
+    /// Filter of validation messages which are logged.
+    pub fn validation_filter(&self) -> &ValidationFilter {
+        &self.validation_filter
+    }
+
+    /// If game will collect pipeline statistics of each frame (if supported by the device).
+    pub fn pipeline_statistics(&self) -> bool {
+        self.pipeline_statistics
+    }
+
+    /// Checks if time spent by the device on render passes is measured.
+    pub fn gpu_profiling(&self) -> bool {
+        self.gpu_profiling
+    }
+
+    /// If frames are rendered without any window or surface.
+    pub fn headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Antialiasing mode of the scene which is used since the first frame.
+    pub fn antialiasing(&self) -> AaMode {
+        self.antialiasing
+    }
+
+    /// Title of the window (name of the game, if it was not set).
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Icon of the window, if set.
+    pub fn icon(&self) -> Option<&WindowIcon> {
+        self.icon.as_ref()
+    }
+
+    /// If the window could be resized by the user.
+    pub fn resizable(&self) -> bool {
+        self.resizable
+    }
+
+    /// Initial size of the window, if set.
+    pub fn window_size(&self) -> Option<Size> {
+        self.window_size
+    }
+
+    /// Minimal size of the window, if set.
+    pub fn min_window_size(&self) -> Option<Size> {
+        self.min_window_size
+    }
+
+    /// Maximal size of the window, if set.
+    pub fn max_window_size(&self) -> Option<Size> {
+        self.max_window_size
+    }
+
+    /// Initial position of the window on the desktop, if set.
+    pub fn window_position(&self) -> Option<[i32; 2]> {
+        self.window_position
+    }
+
+    /// If the window is initially maximized.
+    pub fn maximized(&self) -> bool {
+        self.maximized.unwrap_or(false)
+    }
+
+    /// If the window is fullscreen.
+    pub fn fullscreen(&self) -> bool {
+        self.fullscreen.unwrap_or(false)
+    }
+
+    /// Mode which is used if the window is fullscreen.
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        self.fullscreen_mode
+    }
+
+    /// If presentation is synchronized with refresh rate of the display.
+    pub fn vsync(&self) -> bool {
+        self.vsync.unwrap_or(true)
+    }
+
+    /// Mode in which frames are presented into the window, if it is supported by the surface.
+    pub fn present_mode(&self) -> PresentMode {
+        match self.present_mode {
+            Some(mode) => mode,
+            None if self.vsync() => PresentMode::Mailbox,
+            None => PresentMode::Immediate,
+        }
+    }
+
+    /// Max count of frames which are rendered per second, if frame rate is limited.
+    pub fn max_fps(&self) -> Option<u32> {
+        self.max_fps
+    }
+
+    /// Index of the physical device which must be used, if set.
+    pub fn device_index(&self) -> Option<usize> {
+        self.device_index
+    }
+
+    /// Scale of the scene resolution relative to the window.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale.unwrap_or(1.0)
+    }
+
+    /// Color which the scene is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// Operator which compares depth of the fragment with the depth buffer.
+    pub fn depth_compare(&self) -> CompareOp {
+        self.depth_compare
+    }
+
+    /// Load and store operations of color and depth of the scene.
+    pub fn pass_ops(&self) -> PassOps {
+        self.pass_ops
+    }
+
+    /// Checks if mip levels are generated for textures of UI and sprites.
+    pub fn texture_mipmaps(&self) -> bool {
+        self.texture_mipmaps
+    }
+
+    /// Count of frames which could be rendered concurrently.
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames_in_flight
+    }
+
+    /// Checks if frames are submitted on the dedicated submit thread.
+    pub fn submit_thread(&self) -> bool {
+        self.submit_thread
+    }
+
+    /// Policy of the renderer for timeouts of frames.
+    pub fn timeout_policy(&self) -> TimeoutPolicy {
+        self.timeout_policy
+    }
+
+    /// Count of worker threads of the task pool of the application.
+    pub fn worker_threads(&self) -> usize {
+        self.worker_threads
+            .unwrap_or_else(task::default_worker_threads)
+    }
+
+    /// If delta time of updates is zero while rendering is paused.
+    pub fn zero_delta_when_paused(&self) -> bool {
+        self.zero_delta_when_paused
+    }
+
+    /// Duration of the fixed step, if fixed timestep is enabled.
+    pub fn fixed_timestep(&self) -> Option<Duration> {
+        self.fixed_timestep
+    }
+
+    /// Keyboard key which pauses or resumes rendering of frames, if bound.
+    pub fn pause_key(&self) -> Option<Key> {
+        self.pause_key
+    }
+
+    /// Keyboard key which renders exactly one frame while paused, if bound.
+    pub fn step_key(&self) -> Option<Key> {
+        self.step_key
+    }
+
+    /// Configuration of adaptive quality, if it is enabled.
+    pub fn adaptive_quality(&self) -> Option<QualityConfig> {
+        self.adaptive_quality
+    }
+
+    /// Keyboard key which cycles levels of built-in overlays, if bound.
+    pub fn overlay_key(&self) -> Option<Key> {
+        self.overlay_key
+    }
+
+    /// Level of built-in overlays which are shown on the first run.
+    pub fn overlay_level(&self) -> OverlayLevel {
+        self.overlay_level
+    }
+
+    /// Deadzone of gamepad axes.
+    pub fn gamepad_deadzone(&self) -> f32 {
+        self.gamepad_deadzone
+    }
+
+    /// File in which engine settings are persisted, if they are persisted at all.
+    pub fn settings_path(&self) -> Option<PathBuf> {
+        if !self.persist_settings {
+            return None;
+        }
+        match &self.settings_path {
+            Some(path) => Some(path.clone()),
+            None => settings::default_path(self.name()),
+        }
+    }
+
+    /// File in which pipeline cache is persisted, if it is persisted at all.
+    pub fn pipeline_cache_path(&self) -> Option<PathBuf> {
+        if !self.persist_pipeline_cache {
+            return None;
+        }
+        match &self.pipeline_cache_path {
+            Some(path) => Some(path.clone()),
+            None => pipeline_cache::default_path(self.name()),
+        }
+    }
+
+    /// Image files which are loaded while the application is created.
+    pub fn preload(&self) -> &[PathBuf] {
+        &self.preload
+    }
+
+    /// File of the scene which is loaded while the application is created, if set.
+    pub fn scene(&self) -> Option<&Path> {
+        self.scene.as_deref()
+    }
+
+    /// Callback which receives progress of startup, if set.
+    pub fn progress(&self) -> Option<&ProgressSink> {
+        self.progress.as_ref()
+    }
+
+    /// Token which cancels creation of the application, if set.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Address which diagnostics server listens on, if it is enabled.
+    pub fn diagnostics_address(&self) -> Option<SocketAddr> {
+        self.diagnostics_address
+    }
+
+    /// File which was set by [`Config::with_settings_path`], if any.
+    pub(crate) fn explicit_settings_path(&self) -> Option<&Path> {
+        self.settings_path
+            .as_deref()
+            .filter(|_| self.persist_settings)
     }
 }
 
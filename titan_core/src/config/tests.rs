@@ -0,0 +1,314 @@
+#![cfg(test)]
+
+use vulkano::image::SampleCount;
+
+use crate::graphics::{LoadOp, StoreOp};
+use crate::window::VideoMode;
+
+use super::*;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(ToString::to_string).collect()
+}
+
+fn config() -> Config {
+    Config::new("game".to_string(), Version::new(0, 1, 0), false)
+}
+
+#[test]
+fn test_apply_args() {
+    let config = config()
+        .apply_args(args(&[
+            "--width=1280",
+            "--height",
+            "720",
+            "--vsync=off",
+            "--validation",
+            "--headless",
+            "--device-index=1",
+            "--render-scale=0.5",
+            "--msaa=4",
+            "--diagnostics-port=9000",
+        ]))
+        .unwrap();
+    assert_eq!(config.window_size(), Some(Size::new(1280, 720)));
+    assert!(!config.vsync());
+    assert!(config.enable_validation());
+    assert!(config.headless());
+    assert_eq!(config.device_index(), Some(1));
+    assert_eq!(config.render_scale(), 0.5);
+    assert_eq!(config.antialiasing(), AaMode::Msaa(SampleCount::Sample4));
+    assert!(!config.fullscreen());
+    let address = config.diagnostics_address().unwrap();
+    assert!(address.ip().is_loopback());
+    assert_eq!(address.port(), 9000);
+
+    // Only one dimension keeps the other one of the current size.
+    let config = config
+        .with_vsync(false)
+        .apply_args(args(&["--width=640", "--vsync", "--msaa=1"]))
+        .unwrap();
+    assert_eq!(config.window_size(), Some(Size::new(640, 720)));
+    assert!(config.vsync());
+    assert_eq!(config.antialiasing(), AaMode::Off);
+
+    let config = self::config().apply_args(args(&["--fullscreen"])).unwrap();
+    assert!(config.fullscreen());
+    assert_eq!(config.window_size(), None);
+}
+
+#[test]
+fn test_args_errors() {
+    let cases = [
+        (
+            &["--fullscreen", "--height=600"][..],
+            ArgsError::Conflict("fullscreen".to_string(), "height".to_string()),
+        ),
+        (
+            &["--width=800", "--fullscreen=on"],
+            ArgsError::Conflict("fullscreen".to_string(), "width".to_string()),
+        ),
+        (&["--width"], ArgsError::MissingValue("width".to_string())),
+        (
+            &["--frobnicate"],
+            ArgsError::Unknown("--frobnicate".to_string()),
+        ),
+        (&["level.ron"], ArgsError::Unknown("level.ron".to_string())),
+        (&["--"], ArgsError::Unknown("--".to_string())),
+    ];
+    for (arguments, expected) in cases {
+        assert_eq!(config().apply_args(args(arguments)).unwrap_err(), expected);
+    }
+
+    let invalid = [
+        ("--width=0", "width"),
+        ("--height=tall", "height"),
+        ("--vsync=maybe", "vsync"),
+        ("--device-index=-1", "device-index"),
+        ("--render-scale=3", "render-scale"),
+        ("--msaa=3", "msaa"),
+        ("--diagnostics-port=65536", "diagnostics-port"),
+        ("--validation-level=verbose", "validation-level"),
+        ("--present-mode=vsync", "present-mode"),
+        ("--max-fps=0", "max-fps"),
+    ];
+    for (argument, expected) in invalid {
+        match config().apply_args(args(&[argument])) {
+            Err(ArgsError::InvalidValue { option, .. }) => assert_eq!(option, expected),
+            result => panic!("unexpected result for {}: {:?}", argument, result),
+        }
+    }
+}
+
+#[test]
+fn test_allow_unknown() {
+    let (config, unknown) = config()
+        .apply_args_allow_unknown(args(&["--level", "forest", "--vsync=off", "--god-mode"]))
+        .unwrap();
+    assert!(!config.vsync());
+    assert_eq!(unknown, args(&["--level", "forest", "--god-mode"]));
+}
+
+#[test]
+fn test_help() {
+    let usage = match config().apply_args(args(&["--width=1", "--help"])) {
+        Err(ArgsError::Help(usage)) => usage,
+        result => panic!("unexpected result: {:?}", result),
+    };
+    assert_eq!(usage, config().args_usage());
+    assert!(usage.starts_with("Usage: game [OPTIONS]"));
+    // Each option of the table is described.
+    for option in [
+        "--width=N",
+        "--fullscreen",
+        "--vsync",
+        "--msaa=SAMPLES",
+        "--help",
+    ] {
+        assert!(usage.contains(option), "{} is missing in usage", option);
+    }
+}
+
+#[test]
+fn test_stored_settings() {
+    let stored = EngineSettings {
+        window_position: Some([100, 200]),
+        window_size: Some(Size::new(800, 600)),
+        maximized: true,
+        fullscreen: false,
+        vsync: false,
+        render_scale: 0.5,
+        ..EngineSettings::default()
+    };
+    let config = config().with_stored(&stored);
+    assert_eq!(config.window_position(), Some([100, 200]));
+    assert_eq!(config.window_size(), Some(Size::new(800, 600)));
+    assert!(config.maximized());
+    assert!(!config.vsync());
+    assert_eq!(config.render_scale(), 0.5);
+
+    // Values which were set by the game take precedence over stored ones.
+    let config = self::config()
+        .with_vsync(true)
+        .with_window_size(Size::new(1280, 720))
+        .apply_args(args(&["--render-scale=2"]))
+        .unwrap()
+        .with_stored(&stored);
+    assert!(config.vsync());
+    assert_eq!(config.window_size(), Some(Size::new(1280, 720)));
+    assert_eq!(config.render_scale(), 2.0);
+    assert_eq!(config.window_position(), Some([100, 200]));
+
+    assert_eq!(self::config().without_settings().settings_path(), None);
+    let config = self::config().with_settings_path("settings.ini");
+    assert_eq!(config.settings_path(), Some(PathBuf::from("settings.ini")));
+
+    let config = self::config().without_pipeline_cache();
+    assert_eq!(config.pipeline_cache_path(), None);
+    let config = config.with_pipeline_cache_path("pipelines.bin");
+    assert_eq!(
+        config.pipeline_cache_path(),
+        Some(PathBuf::from("pipelines.bin"))
+    );
+}
+
+#[test]
+fn test_depth_compare() {
+    assert_eq!(config().depth_compare(), CompareOp::Less);
+    let config = config().with_depth_compare(CompareOp::Greater);
+    assert_eq!(config.depth_compare(), CompareOp::Greater);
+}
+
+#[test]
+fn test_gpu_profiling() {
+    assert!(!config().gpu_profiling());
+    assert!(config().with_gpu_profiling(true).gpu_profiling());
+}
+
+#[test]
+fn test_texture_mipmaps() {
+    assert!(!config().texture_mipmaps());
+    assert!(config().with_texture_mipmaps(true).texture_mipmaps());
+}
+
+#[test]
+fn test_pass_ops() {
+    assert_eq!(config().pass_ops(), PassOps::default());
+    let ops = PassOps::new()
+        .with_color_load(LoadOp::Load)
+        .with_depth_ops(LoadOp::Load, StoreOp::Store);
+    assert_eq!(config().with_pass_ops(ops).pass_ops(), ops);
+}
+
+#[test]
+fn test_frames_in_flight() {
+    assert_eq!(config().frames_in_flight(), DEFAULT_FRAMES_IN_FLIGHT);
+    assert_eq!(config().with_frames_in_flight(3).frames_in_flight(), 3);
+    assert_eq!(config().with_frames_in_flight(0).frames_in_flight(), 1);
+    let config = config().with_frames_in_flight(100);
+    assert_eq!(config.frames_in_flight(), MAX_FRAMES_IN_FLIGHT);
+    assert!(!config.submit_thread());
+    assert!(config.with_submit_thread(true).submit_thread());
+}
+
+#[test]
+fn test_fixed_timestep() {
+    assert_eq!(config().fixed_timestep(), None);
+    let step = Duration::from_secs(1) / 60;
+    assert_eq!(
+        config().with_fixed_timestep(step).fixed_timestep(),
+        Some(step)
+    );
+    let config = config().with_fixed_timestep(step);
+    assert_eq!(
+        config.with_fixed_timestep(Duration::ZERO).fixed_timestep(),
+        None
+    );
+}
+
+#[test]
+fn test_present_mode() {
+    assert_eq!(config().present_mode(), PresentMode::Mailbox);
+    assert_eq!(
+        config().with_vsync(false).present_mode(),
+        PresentMode::Immediate
+    );
+    // Explicit mode takes precedence over vsync.
+    let config = self::config()
+        .with_vsync(false)
+        .apply_args(args(&["--present-mode=fifo", "--max-fps", "144"]))
+        .unwrap();
+    assert_eq!(config.present_mode(), PresentMode::Fifo);
+    assert_eq!(config.max_fps(), Some(144));
+
+    assert_eq!(self::config().max_fps(), None);
+    assert_eq!(config.with_max_fps(0).max_fps(), None);
+}
+
+#[test]
+fn test_validation() {
+    assert_eq!(config().validation_level(), ValidationLevel::Off);
+    assert!(!config().enable_validation());
+    let config = config()
+        .apply_args(args(&["--validation-level=errors"]))
+        .unwrap();
+    assert_eq!(config.validation_level(), ValidationLevel::Errors);
+    assert!(config.enable_validation());
+    let config = config.apply_args(args(&["--validation"])).unwrap();
+    assert_eq!(config.validation_level(), ValidationLevel::Full);
+    let config = config.with_validation(false);
+    assert_eq!(config.validation_level(), ValidationLevel::Off);
+}
+
+#[test]
+fn test_validation_filter() {
+    use crate::graphics::ValidationSeverity::*;
+
+    let filter = ValidationFilter::new()
+        .with_min_severity(Warning)
+        .suppress("UNASSIGNED-BestPractices-vkCreateDevice-specialuse-extension");
+    assert!(filter.allows(Error, None));
+    assert!(filter.allows(Warning, Some("VUID-vkCmdDraw-None-02699")));
+    assert!(!filter.allows(Info, None));
+    let suppressed = "UNASSIGNED-BestPractices-vkCreateDevice-specialuse-extension";
+    assert!(!filter.allows(Error, Some(suppressed)));
+
+    let config = config().with_validation_filter(filter.clone());
+    assert_eq!(config.validation_filter(), &filter);
+    assert_eq!(Verbose.log_level(), log::Level::Trace);
+    assert_eq!(Error.log_level(), log::Level::Error);
+}
+
+#[test]
+fn test_window_options() {
+    let config = config();
+    assert_eq!(config.title(), "game");
+    assert!(config.resizable());
+    assert!(config.icon().is_none());
+    assert_eq!(config.min_window_size(), None);
+    assert_eq!(config.max_window_size(), None);
+    assert_eq!(config.fullscreen_mode(), FullscreenMode::Borderless);
+
+    let video_mode = VideoMode {
+        size: Size::new(1920, 1080),
+        refresh_rate: 144,
+        bit_depth: 32,
+    };
+    let config = config
+        .with_title("Game: Remastered")
+        .with_resizable(false)
+        .with_min_window_size(Size::new(640, 480))
+        .with_max_window_size(Size::new(1920, 1080))
+        .with_fullscreen_mode(FullscreenMode::Exclusive(video_mode));
+    assert_eq!(config.title(), "Game: Remastered");
+    assert_eq!(config.name(), "game");
+    assert!(!config.resizable());
+    assert_eq!(config.min_window_size(), Some(Size::new(640, 480)));
+    assert_eq!(config.max_window_size(), Some(Size::new(1920, 1080)));
+    // Mode is used only if the window is fullscreen.
+    assert!(!config.fullscreen());
+    assert_eq!(
+        config.fullscreen_mode(),
+        FullscreenMode::Exclusive(video_mode)
+    );
+}
@@ -0,0 +1,100 @@
+//! First-fit allocation of byte ranges from one block, without any device memory behind it.
+
+use std::ops::Range;
+
+use vulkano::DeviceSize;
+
+use crate::graphics::utils::align_up;
+
+/// Free ranges of the block which are sorted by their offsets.
+///
+/// Freed ranges are merged with their neighbours, so the block which has
+/// no allocations consists of one free range again.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FreeList {
+    capacity: DeviceSize,
+    free: Vec<Range<DeviceSize>>,
+}
+
+impl FreeList {
+    /// Creates list of the block of given capacity which is free entirely.
+    pub fn new(capacity: DeviceSize) -> Self {
+        Self {
+            capacity,
+            free: vec![Range {
+                start: 0,
+                end: capacity,
+            }],
+        }
+    }
+
+    /// Capacity of the block (in bytes).
+    pub fn capacity(&self) -> DeviceSize {
+        self.capacity
+    }
+
+    /// Bytes of the block which are allocated (with alignment padding).
+    pub fn used(&self) -> DeviceSize {
+        let free: DeviceSize = self.free.iter().map(|range| range.end - range.start).sum();
+        self.capacity - free
+    }
+
+    /// Size of the largest free range (in bytes).
+    pub fn largest_free(&self) -> DeviceSize {
+        self.free
+            .iter()
+            .map(|range| range.end - range.start)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if nothing is allocated from the block.
+    pub fn is_empty(&self) -> bool {
+        self.used() == 0
+    }
+
+    /// Allocates range of given size which offset is a multiple of given alignment.
+    ///
+    /// Alignment is rounded up to a power of two. Range is taken from the first
+    /// free range which fits it, and padding before the offset is allocated too,
+    /// so the returned range should be freed as is.
+    ///
+    pub fn allocate(
+        &mut self,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Option<Range<DeviceSize>> {
+        let alignment = alignment.max(1).next_power_of_two();
+        let size = size.max(1);
+        let (index, offset) = self.free.iter().enumerate().find_map(|(index, range)| {
+            let offset = align_up(range.start, alignment);
+            (offset + size <= range.end).then_some((index, offset))
+        })?;
+        let free = &mut self.free[index];
+        let allocated = free.start..offset + size;
+        free.start = allocated.end;
+        if free.is_empty() {
+            self.free.remove(index);
+        }
+        Some(allocated)
+    }
+
+    /// Returns allocated range to the block.
+    pub fn free(&mut self, range: Range<DeviceSize>) {
+        debug_assert!(range.end <= self.capacity, "range is outside of the block");
+        let index = self.free.partition_point(|free| free.start < range.start);
+        debug_assert!(
+            index == self.free.len() || range.end <= self.free[index].start,
+            "range overlaps free range",
+        );
+        self.free.insert(index, range);
+        // Merge with the next range first, so the index stays valid.
+        if index + 1 < self.free.len() && self.free[index].end == self.free[index + 1].start {
+            self.free[index].end = self.free.remove(index + 1).end;
+        }
+        if index > 0 && self.free[index - 1].end == self.free[index].start {
+            self.free[index - 1].end = self.free.remove(index).end;
+        }
+    }
+}
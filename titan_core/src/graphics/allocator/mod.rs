@@ -0,0 +1,402 @@
+//! Sub-allocation of device memory from large blocks.
+//!
+//! Buffers which are created by the renderer itself (uniform buffers, transient blocks,
+//! readback rings, and uploaded buffers like vertex and index buffers of meshes)
+//! do not allocate device memory on their own: their ranges are placed into blocks
+//! of [`DEFAULT_BLOCK_SIZE`] bytes. Blocks of host visible memory are mapped once
+//! for their whole lifetime. Large buffers get a dedicated block. Statistics of allocations
+//! are exposed by [`Renderer::memory_stats`](super::Renderer::memory_stats)
+//! to debug fragmentation and leaks.
+//!
+//! Images (attachments and textures) and buffers which are created by `vulkano` itself
+//! (particles, built-in index buffers) are still allocated from its own memory pool,
+//! because `vulkano` could not bind them to the memory of other allocators.
+
+use std::ops::Range;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::device::physical::MemoryType;
+use vulkano::device::{Device, DeviceOwned};
+use vulkano::memory::{DeviceMemory, DeviceMemoryAllocError, MemoryRequirements};
+use vulkano::{DeviceSize, VulkanObject};
+
+use self::free_list::FreeList;
+
+mod free_list;
+mod tests;
+
+/// Size of each shared block of device memory (in bytes).
+pub const DEFAULT_BLOCK_SIZE: DeviceSize = 16 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum MemoryAllocError {
+    #[error("no suitable memory type")]
+    NoMemoryType,
+
+    #[error("device memory allocation failure: {0}")]
+    Allocation(#[from] DeviceMemoryAllocError),
+
+    #[error("failed to map device memory: {0}")]
+    Map(vk::Result),
+}
+
+/// Statistics of device memory which is allocated by the renderer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Count of live allocations (which grows over time if some of them leak).
+    pub allocations: u32,
+    /// Count of blocks of device memory.
+    pub blocks: u32,
+    /// Bytes which are used by live allocations (with alignment padding).
+    pub used_bytes: u64,
+    /// Bytes of device memory which are allocated for all blocks.
+    pub reserved_bytes: u64,
+    /// Max bytes which were used by allocations at once.
+    pub peak_used_bytes: u64,
+    /// Size of the largest free range of all blocks (in bytes).
+    pub largest_free_range: u64,
+}
+
+impl MemoryStats {
+    /// Bytes of blocks which are not used by any allocation.
+    pub fn free_bytes(&self) -> u64 {
+        self.reserved_bytes - self.used_bytes
+    }
+
+    /// Part of free bytes which are outside of the largest free range,
+    /// from `0.0` (free memory is contiguous) to almost `1.0` (free memory is scattered).
+    pub fn fragmentation(&self) -> f32 {
+        match self.free_bytes() {
+            0 => 0.0,
+            free => 1.0 - self.largest_free_range as f32 / free as f32,
+        }
+    }
+}
+
+/// Block of device memory which is mapped for the whole lifetime of the block
+/// if the memory is host visible.
+struct BlockMemory {
+    memory: DeviceMemory,
+    memory_type: u32,
+    /// Pointer to the start of the mapped memory, valid until the block is dropped
+    /// (or null if the memory is not mapped).
+    pointer: *mut u8,
+    size: DeviceSize,
+    coherent: bool,
+}
+
+// Safety: mapped memory is accessed only through ranges of allocations which do not overlap.
+unsafe impl Send for BlockMemory {}
+unsafe impl Sync for BlockMemory {}
+
+impl BlockMemory {
+    fn new(
+        device: &Arc<Device>,
+        memory_type: MemoryType,
+        size: DeviceSize,
+    ) -> Result<Self, MemoryAllocError> {
+        let memory = DeviceMemory::alloc(device.clone(), memory_type, size)?;
+        if !memory_type.is_host_visible() {
+            return Ok(Self {
+                memory,
+                memory_type: memory_type.id(),
+                pointer: ptr::null_mut(),
+                size,
+                coherent: false,
+            });
+        }
+        let mut pointer = ptr::null_mut();
+        let result = unsafe {
+            device.fns().v1_0.map_memory(
+                device.internal_object(),
+                memory.internal_object(),
+                0,
+                vk::WHOLE_SIZE,
+                vk::MemoryMapFlags::empty(),
+                &mut pointer,
+            )
+        };
+        if result != vk::Result::SUCCESS {
+            return Err(MemoryAllocError::Map(result));
+        }
+        Ok(Self {
+            memory,
+            memory_type: memory_type.id(),
+            pointer: pointer.cast(),
+            size,
+            coherent: memory_type.is_host_coherent(),
+        })
+    }
+}
+
+impl Drop for BlockMemory {
+    fn drop(&mut self) {
+        if self.pointer.is_null() {
+            return;
+        }
+        let device = self.memory.device();
+        unsafe {
+            device
+                .fns()
+                .v1_0
+                .unmap_memory(device.internal_object(), self.memory.internal_object());
+        }
+    }
+}
+
+/// Range of the block which is returned to the allocator on drop.
+pub(crate) struct MemoryAllocation {
+    allocator: Arc<MemoryAllocator>,
+    memory: Arc<BlockMemory>,
+    /// Allocated range of the block, including padding before the offset.
+    range: Range<DeviceSize>,
+    offset: DeviceSize,
+}
+
+impl MemoryAllocation {
+    /// Device memory of the block which contains the allocation.
+    pub fn memory(&self) -> &DeviceMemory {
+        &self.memory.memory
+    }
+
+    /// Offset of the allocation in the block (in bytes).
+    pub fn offset(&self) -> DeviceSize {
+        self.offset
+    }
+
+    /// Offset of the end of the allocation in the block (in bytes).
+    ///
+    /// Allocations of non-coherent memory end at multiples of the `non_coherent_atom_size`
+    /// (or at the end of the block), so flushed ranges never touch other allocations.
+    ///
+    pub fn end(&self) -> DeviceSize {
+        self.range.end
+    }
+
+    /// Pointer to the start of the allocation in the mapped memory.
+    ///
+    /// Allocations of memory which is not host visible must not be accessed by the pointer.
+    ///
+    pub fn pointer(&self) -> *mut u8 {
+        // Safety: offset is inside of the mapped block.
+        unsafe { self.memory.pointer.add(self.offset as usize) }
+    }
+
+    /// Returns `true` if memory of the allocation must not be flushed after writes.
+    pub fn is_coherent(&self) -> bool {
+        self.memory.coherent
+    }
+}
+
+impl Drop for MemoryAllocation {
+    fn drop(&mut self) {
+        self.allocator.free(&self.memory, self.range.clone())
+    }
+}
+
+struct Block {
+    memory: Arc<BlockMemory>,
+    ranges: FreeList,
+    /// Dedicated block is freed as soon as its only allocation is freed.
+    dedicated: bool,
+}
+
+#[derive(Default)]
+struct AllocatorState {
+    blocks: Vec<Block>,
+    allocations: u32,
+    peak_used: DeviceSize,
+}
+
+impl AllocatorState {
+    fn used(&self) -> DeviceSize {
+        self.blocks.iter().map(|block| block.ranges.used()).sum()
+    }
+}
+
+/// Allocator of device memory which places allocations into shared blocks.
+pub(crate) struct MemoryAllocator {
+    device: Arc<Device>,
+    block_size: DeviceSize,
+    state: Mutex<AllocatorState>,
+}
+
+impl MemoryAllocator {
+    /// Creates allocator without blocks: they are created on the first allocations.
+    pub fn new(device: Arc<Device>) -> Arc<Self> {
+        Self::with_block_size(device, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Creates allocator which shared blocks have given size (in bytes).
+    pub fn with_block_size(device: Arc<Device>, block_size: DeviceSize) -> Arc<Self> {
+        Arc::new(Self {
+            device,
+            block_size: block_size.max(1),
+            state: Mutex::default(),
+        })
+    }
+
+    /// Device which memory is allocated.
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Allocates host visible memory which satisfies the requirements,
+    /// preferring memory types which satisfy the predicate.
+    ///
+    /// Allocations which are larger than half of the block get a dedicated block.
+    ///
+    pub fn allocate(
+        self: &Arc<Self>,
+        requirements: &MemoryRequirements,
+        prefer: impl Fn(MemoryType) -> bool,
+    ) -> Result<MemoryAllocation, MemoryAllocError> {
+        self.allocate_from(
+            requirements,
+            |memory_type| memory_type.is_host_visible(),
+            prefer,
+        )
+    }
+
+    /// Allocates device local memory which satisfies the requirements.
+    ///
+    /// Memory types which are not host visible are preferred,
+    /// so host visible device local memory is left for mapped buffers.
+    ///
+    pub fn allocate_device_local(
+        self: &Arc<Self>,
+        requirements: &MemoryRequirements,
+    ) -> Result<MemoryAllocation, MemoryAllocError> {
+        self.allocate_from(
+            requirements,
+            |memory_type| memory_type.is_device_local(),
+            |memory_type| !memory_type.is_host_visible(),
+        )
+    }
+
+    /// Allocates memory of one of memory types which pass the filter,
+    /// preferring memory types which satisfy the predicate.
+    fn allocate_from(
+        self: &Arc<Self>,
+        requirements: &MemoryRequirements,
+        filter: impl Fn(MemoryType) -> bool,
+        prefer: impl Fn(MemoryType) -> bool,
+    ) -> Result<MemoryAllocation, MemoryAllocError> {
+        let physical_device = self.device.physical_device();
+        let memory_types: Vec<_> = physical_device
+            .memory_types()
+            .filter(|memory_type| requirements.memory_type_bits & (1 << memory_type.id()) != 0)
+            .filter(|&memory_type| filter(memory_type))
+            .collect();
+        let memory_type = memory_types
+            .iter()
+            .find(|&&memory_type| prefer(memory_type))
+            .or_else(|| memory_types.first())
+            .copied()
+            .ok_or(MemoryAllocError::NoMemoryType)?;
+
+        // Flushed ranges are expanded to atoms, so they must not overlap other allocations.
+        let (size, alignment) = if !memory_type.is_host_visible() || memory_type.is_host_coherent()
+        {
+            (requirements.size, requirements.alignment)
+        } else {
+            let atom_size = physical_device.properties().non_coherent_atom_size.max(1);
+            let size = (requirements.size + atom_size - 1) / atom_size * atom_size;
+            (size, requirements.alignment.max(atom_size))
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let placed = state
+            .blocks
+            .iter_mut()
+            .filter(|block| !block.dedicated && block.memory.memory_type == memory_type.id())
+            .find_map(|block| {
+                let range = block.ranges.allocate(size, alignment)?;
+                Some((block.memory.clone(), range))
+            });
+        let (memory, range) = match placed {
+            Some(placed) => placed,
+            None => {
+                let dedicated = size > self.block_size / 2;
+                let capacity = if dedicated { size } else { self.block_size };
+                let memory = Arc::new(BlockMemory::new(&self.device, memory_type, capacity)?);
+                let mut ranges = FreeList::new(capacity);
+                let range = ranges
+                    .allocate(size, alignment)
+                    .expect("new block must fit the allocation");
+                state.blocks.push(Block {
+                    memory: memory.clone(),
+                    ranges,
+                    dedicated,
+                });
+                log::debug!(
+                    "memory block of {} bytes was allocated (memory type {})",
+                    capacity,
+                    memory_type.id(),
+                );
+                (memory, range)
+            }
+        };
+        state.allocations += 1;
+        state.peak_used = state.peak_used.max(state.used());
+
+        let offset = range.end - size;
+        Ok(MemoryAllocation {
+            allocator: self.clone(),
+            memory,
+            range,
+            offset,
+        })
+    }
+
+    /// Returns the range to its block.
+    ///
+    /// Empty blocks are freed, except for the last shared block of each memory type
+    /// (so buffers which are recreated often do not allocate device memory each time).
+    ///
+    fn free(&self, memory: &Arc<BlockMemory>, range: Range<DeviceSize>) {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .blocks
+            .iter()
+            .position(|block| Arc::ptr_eq(&block.memory, memory))
+            .expect("allocation must belong to one of blocks");
+        state.blocks[index].ranges.free(range);
+        state.allocations -= 1;
+
+        let block = &state.blocks[index];
+        let shared_blocks = state
+            .blocks
+            .iter()
+            .filter(|other| !other.dedicated && other.memory.memory_type == memory.memory_type)
+            .count();
+        if block.ranges.is_empty() && (block.dedicated || shared_blocks > 1) {
+            let block = state.blocks.swap_remove(index);
+            log::debug!(
+                "memory block of {} bytes was freed",
+                block.ranges.capacity()
+            );
+        }
+    }
+
+    /// Statistics of blocks and allocations.
+    pub fn stats(&self) -> MemoryStats {
+        let state = self.state.lock().unwrap();
+        MemoryStats {
+            allocations: state.allocations,
+            blocks: state.blocks.len() as u32,
+            used_bytes: state.used(),
+            reserved_bytes: state.blocks.iter().map(|block| block.memory.size).sum(),
+            peak_used_bytes: state.peak_used,
+            largest_free_range: state
+                .blocks
+                .iter()
+                .map(|block| block.ranges.largest_free())
+                .max()
+                .unwrap_or_default(),
+        }
+    }
+}
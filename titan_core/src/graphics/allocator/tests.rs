@@ -0,0 +1,126 @@
+#![cfg(test)]
+
+use vulkano::buffer::BufferUsage;
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{DeviceExtensions, Features};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::Version;
+
+use crate::graphics::device_buffer::DeviceBuffer;
+use crate::graphics::mapped::MappedBuffer;
+use crate::graphics::utils;
+
+use super::*;
+
+#[test]
+fn test_free_list() {
+    let mut list = FreeList::new(256);
+    assert!(list.is_empty());
+
+    let first = list.allocate(10, 1).unwrap();
+    assert_eq!(first, 0..10);
+    // Padding before the aligned offset belongs to the allocated range.
+    let second = list.allocate(16, 64).unwrap();
+    assert_eq!(second, 10..80);
+    let third = list.allocate(100, 4).unwrap();
+    assert_eq!(third, 80..180);
+    assert_eq!(list.used(), 180);
+    assert_eq!(list.largest_free(), 76);
+    assert_eq!(list.allocate(100, 1), None);
+
+    // Hole in the middle is reused by the first fit.
+    list.free(second);
+    assert_eq!(list.largest_free(), 76);
+    assert_eq!(list.allocate(32, 16), Some(10..48));
+    list.free(10..48);
+
+    // Freed ranges are merged with both neighbours.
+    list.free(first);
+    assert_eq!(list.largest_free(), 80);
+    list.free(third);
+    assert!(list.is_empty());
+    assert_eq!(list.largest_free(), 256);
+    assert_eq!(list.allocate(256, 256), Some(0..256));
+}
+
+#[test]
+fn test_fragmentation() {
+    let stats = MemoryStats {
+        used_bytes: 512,
+        reserved_bytes: 1024,
+        largest_free_range: 128,
+        ..Default::default()
+    };
+    assert_eq!(stats.free_bytes(), 512);
+    assert!((stats.fragmentation() - 0.75).abs() < 1e-6);
+
+    let full = MemoryStats {
+        used_bytes: 1024,
+        reserved_bytes: 1024,
+        ..Default::default()
+    };
+    assert_eq!(full.fragmentation(), 0.0);
+}
+
+/// Checks that small buffers share one block, large buffers get dedicated ones,
+/// and dropped buffers return their memory.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_allocator_blocks() {
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device.queue_families().next().unwrap();
+    let (device, _queues) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+
+    const BLOCK_SIZE: DeviceSize = 1024 * 1024;
+    let allocator = MemoryAllocator::with_block_size(device, BLOCK_SIZE);
+    let usage = BufferUsage::uniform_buffer();
+    let small: Vec<_> = (0..8)
+        .map(|_| MappedBuffer::<[f32; 16]>::new(&allocator, usage, 1).unwrap())
+        .collect();
+    let stats = allocator.stats();
+    assert_eq!(stats.allocations, 8);
+    assert_eq!(stats.blocks, 1);
+    assert_eq!(stats.reserved_bytes, BLOCK_SIZE);
+
+    let large = MappedBuffer::<u8>::new(&allocator, usage, BLOCK_SIZE as usize).unwrap();
+    assert_eq!(allocator.stats().blocks, 2);
+    drop(large);
+    assert_eq!(allocator.stats().blocks, 1);
+
+    drop(small);
+    let stats = allocator.stats();
+    assert_eq!(stats.allocations, 0);
+    assert_eq!(stats.used_bytes, 0);
+    // The last shared block is kept for the next allocations.
+    assert_eq!(stats.blocks, 1);
+    assert!(stats.peak_used_bytes >= 8 * 64);
+}
+
+/// Checks that memory of device local buffers is counted together with mapped ones.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_device_local_buffers() {
+    let queue = utils::graphics_queue();
+    let allocator = MemoryAllocator::new(queue.device().clone());
+    let usage = BufferUsage::vertex_buffer();
+    let families = [queue.family()];
+    let vertices = DeviceBuffer::<[f32; 3]>::new(&allocator, usage, 64, families).unwrap();
+    let uniforms = MappedBuffer::<[f32; 16]>::new(&allocator, usage, 1).unwrap();
+    let stats = allocator.stats();
+    assert_eq!(stats.allocations, 2);
+    assert!(stats.used_bytes >= 64 * 12 + 64);
+
+    drop((vertices, uniforms));
+    let stats = allocator.stats();
+    assert_eq!(stats.allocations, 0);
+    assert_eq!(stats.used_bytes, 0);
+}
@@ -0,0 +1,206 @@
+//! Minimal JSON values which are written with indentation or on one line.
+
+use std::fmt::{self, Write};
+
+/// Value of JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Object which keys are written in order of insertion.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Creates object from its key-value pairs.
+    pub fn object<'a>(pairs: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        let pairs = pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        Self::Object(pairs)
+    }
+
+    /// Creates array from the values which are converted into JSON.
+    pub fn array<T: Into<Json>>(values: impl IntoIterator<Item = T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Writes the value with two spaces of indentation for each nesting level.
+    pub fn to_pretty_string(&self) -> String {
+        let mut string = String::new();
+        self.write_pretty(&mut string, 0)
+            .expect("writing into string never fails");
+        string.push('\n');
+        string
+    }
+
+    /// Writes the value on one line without whitespace (for example, as a line of NDJSON).
+    pub fn to_compact_string(&self) -> String {
+        let mut string = String::new();
+        self.write_compact(&mut string)
+            .expect("writing into string never fails");
+        string
+    }
+
+    fn write_compact(&self, out: &mut String) -> fmt::Result {
+        match self {
+            Self::Array(values) => {
+                out.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    value.write_compact(out)?;
+                }
+                out.push(']');
+            }
+            Self::Object(pairs) => {
+                out.push('{');
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    self::write_string(out, key)?;
+                    out.push(':');
+                    value.write_compact(out)?;
+                }
+                out.push('}');
+            }
+            // Scalars are written the same way.
+            scalar => scalar.write_pretty(out, 0)?,
+        }
+        Ok(())
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) -> fmt::Result {
+        let indent = |out: &mut String, depth| out.push_str(&"  ".repeat(depth));
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(value) => write!(out, "{}", value)?,
+            // Non-finite numbers are not representable in JSON.
+            Self::Number(value) if !value.is_finite() => out.push_str("null"),
+            Self::Number(value) => write!(out, "{}", value)?,
+            Self::String(value) => self::write_string(out, value)?,
+            Self::Array(values) if values.is_empty() => out.push_str("[]"),
+            Self::Array(values) => {
+                // Arrays of numbers (like matrices) are kept on one line.
+                if values.iter().all(|value| matches!(value, Self::Number(_))) {
+                    out.push('[');
+                    for (index, value) in values.iter().enumerate() {
+                        if index > 0 {
+                            out.push_str(", ");
+                        }
+                        value.write_pretty(out, depth)?;
+                    }
+                    out.push(']');
+                    return Ok(());
+                }
+                out.push_str("[\n");
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(",\n");
+                    }
+                    indent(out, depth + 1);
+                    value.write_pretty(out, depth + 1)?;
+                }
+                out.push('\n');
+                indent(out, depth);
+                out.push(']');
+            }
+            Self::Object(pairs) if pairs.is_empty() => out.push_str("{}"),
+            Self::Object(pairs) => {
+                out.push_str("{\n");
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(",\n");
+                    }
+                    indent(out, depth + 1);
+                    self::write_string(out, key)?;
+                    out.push_str(": ");
+                    value.write_pretty(out, depth + 1)?;
+                }
+                out.push('\n');
+                indent(out, depth);
+                out.push('}');
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_string(out: &mut String, value: &str) -> fmt::Result {
+    out.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            char if char.is_control() => write!(out, "\\u{:04x}", char as u32)?,
+            char => out.push(char),
+        }
+    }
+    out.push('"');
+    Ok(())
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for Json {
+    fn from(value: f32) -> Self {
+        // Shortest representation of `f32` is kept instead of its exact binary value.
+        let value = value.to_string().parse().unwrap_or(f64::NAN);
+        Self::Number(value)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
@@ -0,0 +1,434 @@
+//! Description of everything which was recorded for one frame, dumped for debugging.
+//!
+//! Capture is filled by the renderer on the CPU side while commands of the frame
+//! are recorded (nothing is read back from the GPU), so it could be inspected
+//! even if the rendered image is black. Objects are named by their type
+//! and slot (or role) like `IndirectBufferId(1v1)` or `GraphicsPipeline(object)`,
+//! so dumps of two frames could be compared with a text diff.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bytemuck::Pod;
+use slotmap::Key;
+use ultraviolet::Mat4;
+use vulkano::format::ClearValue;
+use vulkano::render_pass::RenderPass;
+
+use self::json::Json;
+use crate::math::Color;
+
+pub(crate) mod json;
+mod tests;
+
+/// Name of the object with its type and slot, which is stable across frames.
+pub(crate) fn key_name(type_name: &str, key: impl Key) -> String {
+    format!("{}({:?})", type_name, key.data())
+}
+
+/// Name of the object with its type and role in the renderer.
+pub(crate) fn role_name(type_name: &str, role: impl std::fmt::Display) -> String {
+    format!("{}({})", type_name, role)
+}
+
+/// Everything which was recorded for one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCapture {
+    /// Number of the frame (see [`FrameStats::frames`](super::FrameStats::frames)).
+    pub frame: u64,
+    /// Index of the swapchain image which the frame was rendered into.
+    pub image_index: usize,
+    /// Dimensions of the swapchain image.
+    pub extent: [u32; 2],
+    /// Matrices of the camera which were written into uniform buffers.
+    pub camera: CameraCapture,
+    /// Render passes in order of their execution.
+    pub passes: Vec<PassCapture>,
+}
+
+/// Matrices of the camera of the frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraCapture {
+    pub projection: Mat4,
+    pub model: Mat4,
+    pub view: Mat4,
+}
+
+/// Render pass with its attachments and subpasses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassCapture {
+    pub name: String,
+    pub attachments: Vec<AttachmentCapture>,
+    pub subpasses: Vec<SubpassCapture>,
+}
+
+/// Attachment of the render pass with the image of the framebuffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachmentCapture {
+    /// Name of the image which is attached.
+    pub image: String,
+    pub format: String,
+    pub samples: u32,
+    pub load: String,
+    pub store: String,
+    /// Value which the attachment is cleared with, if any.
+    pub clear: Option<ClearCapture>,
+}
+
+/// Value which the attachment is cleared with.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClearCapture {
+    Color([f32; 4]),
+    Depth(f32),
+}
+
+/// Subpass with commands which were recorded into it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubpassCapture {
+    pub name: String,
+    pub commands: Vec<CapturedCommand>,
+}
+
+/// Command which was recorded into the subpass.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapturedCommand {
+    SetViewport {
+        origin: [f32; 2],
+        dimensions: [f32; 2],
+    },
+    BindPipeline {
+        pipeline: String,
+    },
+    /// Descriptor sets were bound, starting from the first set.
+    BindDescriptorSets {
+        first_set: u32,
+        bindings: Vec<String>,
+    },
+    PushConstants {
+        bytes: Vec<u8>,
+    },
+    Draw(DrawCapture),
+    /// Draws which parameters are read by the GPU from the indirect buffer.
+    DrawIndirect {
+        buffer: String,
+        draw_count: u32,
+    },
+    /// Commands which were recorded by the hook and are not described.
+    Hook {
+        stage: String,
+    },
+}
+
+/// Draw with the state which was bound when it was recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawCapture {
+    pub pipeline: Option<String>,
+    /// Color which the mesh is multiplied with and whether it is blended.
+    pub material: Option<MaterialCapture>,
+    pub mesh: String,
+    pub vertex_count: u32,
+    /// Count of indices, if the draw is indexed (then it is equal to vertex count).
+    pub index_count: Option<u32>,
+    pub instance_count: u32,
+    /// Push constants which were set before the draw.
+    pub push_constants: Vec<u8>,
+    /// Descriptor sets which were bound before the draw.
+    pub descriptor_bindings: Vec<String>,
+}
+
+/// Material of the draw.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MaterialCapture {
+    pub color: Color,
+    pub transparent: bool,
+}
+
+impl FrameCapture {
+    /// Subpass with given name in any of render passes, if any.
+    pub fn subpass(&self, name: &str) -> Option<&SubpassCapture> {
+        self.passes
+            .iter()
+            .flat_map(|pass| &pass.subpasses)
+            .find(|subpass| subpass.name == name)
+    }
+
+    /// Mutable subpass with given name in any of render passes, if any.
+    pub(crate) fn subpass_mut(&mut self, name: &str) -> Option<&mut SubpassCapture> {
+        self.passes
+            .iter_mut()
+            .flat_map(|pass| &mut pass.subpasses)
+            .find(|subpass| subpass.name == name)
+    }
+
+    /// Commands of all subpasses in order of their execution.
+    pub fn commands(&self) -> impl Iterator<Item = &CapturedCommand> {
+        self.passes
+            .iter()
+            .flat_map(|pass| &pass.subpasses)
+            .flat_map(|subpass| &subpass.commands)
+    }
+
+    /// Serializes the capture into pretty JSON.
+    pub fn to_json_string(&self) -> String {
+        self.to_json().to_pretty_string()
+    }
+
+    /// Writes the capture as pretty JSON into the file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json_string())
+    }
+
+    fn to_json(&self) -> Json {
+        let camera = &self.camera;
+        let camera = Json::object([
+            ("projection", self::matrix(camera.projection)),
+            ("model", self::matrix(camera.model)),
+            ("view", self::matrix(camera.view)),
+        ]);
+        Json::object([
+            ("frame", self.frame.into()),
+            ("image_index", self.image_index.into()),
+            ("extent", Json::array(self.extent)),
+            ("camera", camera),
+            (
+                "passes",
+                Json::Array(self.passes.iter().map(PassCapture::to_json).collect()),
+            ),
+        ])
+    }
+}
+
+impl PassCapture {
+    /// Describes the render pass with given images attached to its attachments in order.
+    ///
+    /// Attachments which are cleared take their values from `clear_values` in order.
+    ///
+    pub(crate) fn new(
+        name: &str,
+        render_pass: &RenderPass,
+        images: &[String],
+        clear_values: &[ClearValue],
+        subpasses: &[&str],
+    ) -> Self {
+        let attachments = render_pass
+            .desc()
+            .attachments()
+            .iter()
+            .zip(images)
+            .enumerate()
+            .map(|(index, (attachment, image))| {
+                let clear = clear_values.get(index).and_then(|value| match value {
+                    ClearValue::Float(color) => Some(ClearCapture::Color(*color)),
+                    ClearValue::Depth(depth) => Some(ClearCapture::Depth(*depth)),
+                    _ => None,
+                });
+                AttachmentCapture {
+                    image: image.clone(),
+                    format: format!("{:?}", attachment.format),
+                    samples: attachment.samples as u32,
+                    load: format!("{:?}", attachment.load),
+                    store: format!("{:?}", attachment.store),
+                    clear,
+                }
+            })
+            .collect();
+        let subpasses = subpasses
+            .iter()
+            .map(|name| SubpassCapture::new(name))
+            .collect();
+        Self {
+            name: name.to_string(),
+            attachments,
+            subpasses,
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        let attachments = self.attachments.iter().map(|attachment| {
+            let clear = match attachment.clear {
+                Some(ClearCapture::Color(color)) => Json::object([("color", Json::array(color))]),
+                Some(ClearCapture::Depth(depth)) => Json::object([("depth", depth.into())]),
+                None => Json::Null,
+            };
+            Json::object([
+                ("image", attachment.image.as_str().into()),
+                ("format", attachment.format.as_str().into()),
+                ("samples", attachment.samples.into()),
+                ("load", attachment.load.as_str().into()),
+                ("store", attachment.store.as_str().into()),
+                ("clear", clear),
+            ])
+        });
+        let subpasses = self.subpasses.iter().map(|subpass| {
+            let commands = subpass.commands.iter().map(CapturedCommand::to_json);
+            Json::object([
+                ("name", subpass.name.as_str().into()),
+                ("commands", Json::Array(commands.collect())),
+            ])
+        });
+        Json::object([
+            ("name", self.name.as_str().into()),
+            ("attachments", Json::Array(attachments.collect())),
+            ("subpasses", Json::Array(subpasses.collect())),
+        ])
+    }
+}
+
+impl SubpassCapture {
+    /// Creates subpass without commands.
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_viewport(&mut self, origin: [f32; 2], dimensions: [f32; 2]) {
+        self.commands
+            .push(CapturedCommand::SetViewport { origin, dimensions });
+    }
+
+    pub(crate) fn bind_pipeline(&mut self, pipeline: String) {
+        self.commands
+            .push(CapturedCommand::BindPipeline { pipeline });
+    }
+
+    pub(crate) fn bind_descriptor_sets(&mut self, first_set: u32, bindings: Vec<String>) {
+        self.commands.push(CapturedCommand::BindDescriptorSets {
+            first_set,
+            bindings,
+        });
+    }
+
+    /// Records bytes of push constants of type `T`.
+    pub(crate) fn push_constants<T: Pod>(&mut self, push_constants: &T) {
+        let bytes = bytemuck::bytes_of(push_constants).to_vec();
+        self.commands.push(CapturedCommand::PushConstants { bytes });
+    }
+
+    /// Records draw with the pipeline, descriptor sets and push constants
+    /// which were recorded before it.
+    pub(crate) fn draw(
+        &mut self,
+        mesh: String,
+        material: Option<MaterialCapture>,
+        vertex_count: u32,
+        indexed: bool,
+        instance_count: u32,
+    ) {
+        let mut pipeline = None;
+        let mut push_constants = None;
+        let mut descriptor_bindings = None;
+        for command in self.commands.iter().rev() {
+            match command {
+                CapturedCommand::BindPipeline { pipeline: bound } if pipeline.is_none() => {
+                    pipeline = Some(bound.clone())
+                }
+                CapturedCommand::PushConstants { bytes } if push_constants.is_none() => {
+                    push_constants = Some(bytes.clone())
+                }
+                CapturedCommand::BindDescriptorSets { bindings, .. }
+                    if descriptor_bindings.is_none() =>
+                {
+                    descriptor_bindings = Some(bindings.clone())
+                }
+                _ => (),
+            }
+        }
+        self.commands.push(CapturedCommand::Draw(DrawCapture {
+            pipeline,
+            material,
+            mesh,
+            vertex_count,
+            index_count: if indexed { Some(vertex_count) } else { None },
+            instance_count,
+            push_constants: push_constants.unwrap_or_default(),
+            descriptor_bindings: descriptor_bindings.unwrap_or_default(),
+        }));
+    }
+
+    pub(crate) fn draw_indirect(&mut self, buffer: String, draw_count: u32) {
+        self.commands
+            .push(CapturedCommand::DrawIndirect { buffer, draw_count });
+    }
+
+    pub(crate) fn hook(&mut self, stage: String) {
+        self.commands.push(CapturedCommand::Hook { stage });
+    }
+}
+
+impl CapturedCommand {
+    fn to_json(&self) -> Json {
+        match self {
+            Self::SetViewport { origin, dimensions } => Json::object([
+                ("command", "set_viewport".into()),
+                ("origin", Json::array(*origin)),
+                ("dimensions", Json::array(*dimensions)),
+            ]),
+            Self::BindPipeline { pipeline } => Json::object([
+                ("command", "bind_pipeline".into()),
+                ("pipeline", pipeline.as_str().into()),
+            ]),
+            Self::BindDescriptorSets {
+                first_set,
+                bindings,
+            } => Json::object([
+                ("command", "bind_descriptor_sets".into()),
+                ("first_set", (*first_set).into()),
+                ("bindings", Json::array(bindings.iter().map(String::as_str))),
+            ]),
+            Self::PushConstants { bytes } => Json::object([
+                ("command", "push_constants".into()),
+                ("bytes", self::hex(bytes).into()),
+            ]),
+            Self::Draw(draw) => {
+                let material = draw.material.map(|material| {
+                    Json::object([
+                        ("color", Json::array(<[f32; 4]>::from(material.color))),
+                        ("transparent", material.transparent.into()),
+                    ])
+                });
+                Json::object([
+                    ("command", "draw".into()),
+                    ("pipeline", draw.pipeline.as_deref().into()),
+                    ("material", material.into()),
+                    ("mesh", draw.mesh.as_str().into()),
+                    ("vertex_count", draw.vertex_count.into()),
+                    ("index_count", draw.index_count.into()),
+                    ("instance_count", draw.instance_count.into()),
+                    ("push_constants", self::hex(&draw.push_constants).into()),
+                    (
+                        "descriptor_bindings",
+                        Json::array(draw.descriptor_bindings.iter().map(String::as_str)),
+                    ),
+                ])
+            }
+            Self::DrawIndirect { buffer, draw_count } => Json::object([
+                ("command", "draw_indirect".into()),
+                ("buffer", buffer.as_str().into()),
+                ("draw_count", (*draw_count).into()),
+            ]),
+            Self::Hook { stage } => {
+                Json::object([("command", "hook".into()), ("stage", stage.as_str().into())])
+            }
+        }
+    }
+}
+
+/// Matrix as array of its columns.
+fn matrix(matrix: Mat4) -> Json {
+    let cols = matrix.cols.iter();
+    Json::Array(
+        cols.map(|col| Json::array([col.x, col.y, col.z, col.w]))
+            .collect(),
+    )
+}
+
+/// Bytes as lowercase hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
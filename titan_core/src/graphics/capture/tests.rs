@@ -0,0 +1,102 @@
+#![cfg(test)]
+
+use super::*;
+
+/// Capture of the frame which draws one triangle with the object pipeline.
+fn triangle_capture() -> FrameCapture {
+    let mut subpass = SubpassCapture::new("scene");
+    subpass.set_viewport([0.0, 0.0], [640.0, 480.0]);
+    subpass.bind_pipeline(role_name("GraphicsPipeline", "object"));
+    subpass.bind_descriptor_sets(0, vec![role_name("UniformBuffer", "camera")]);
+    subpass.push_constants(&[1.0f32, 0.5]);
+    let material = MaterialCapture {
+        color: Color::WHITE,
+        transparent: false,
+    };
+    subpass.draw(
+        role_name("Primitive", "triangle"),
+        Some(material),
+        3,
+        true,
+        1,
+    );
+
+    let color = AttachmentCapture {
+        image: role_name("SwapchainImage", 0),
+        format: "B8G8R8A8_SRGB".to_string(),
+        samples: 1,
+        load: "Clear".to_string(),
+        store: "Store".to_string(),
+        clear: Some(ClearCapture::Color([0.0, 0.0, 0.0, 1.0])),
+    };
+    let pass = PassCapture {
+        name: "main".to_string(),
+        attachments: vec![color],
+        subpasses: vec![subpass, SubpassCapture::new("ui")],
+    };
+    FrameCapture {
+        frame: 7,
+        image_index: 0,
+        extent: [640, 480],
+        camera: CameraCapture {
+            projection: Mat4::identity(),
+            model: Mat4::identity(),
+            view: Mat4::identity(),
+        },
+        passes: vec![pass],
+    }
+}
+
+#[test]
+fn test_draw_snapshots_bound_state() {
+    let capture = triangle_capture();
+    let draws: Vec<_> = capture
+        .commands()
+        .filter_map(|command| match command {
+            CapturedCommand::Draw(draw) => Some(draw),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(draws.len(), 1);
+    let draw = draws[0];
+    assert_eq!(draw.pipeline.as_deref(), Some("GraphicsPipeline(object)"));
+    assert_eq!(draw.mesh, "Primitive(triangle)");
+    assert_eq!((draw.vertex_count, draw.index_count), (3, Some(3)));
+    assert_eq!(draw.push_constants.len(), 8);
+    assert_eq!(draw.descriptor_bindings, ["UniformBuffer(camera)"]);
+
+    assert!(capture.subpass("ui").unwrap().commands.is_empty());
+    assert!(capture.subpass("post_process").is_none());
+}
+
+#[test]
+fn test_capture_json() {
+    let json = triangle_capture().to_json_string();
+    assert_eq!(json.matches("\"attachments\"").count(), 1);
+    assert_eq!(json.matches("\"command\": \"bind_pipeline\"").count(), 1);
+    assert_eq!(json.matches("\"command\": \"draw\"").count(), 1);
+    assert!(json.contains("\"vertex_count\": 3,"));
+    assert!(json.contains("\"clear\": {\n"));
+    assert!(json.contains("\"color\": [0, 0, 0, 1]"));
+    assert!(json.contains("\"push_constants\": \"0000803f0000003f\""));
+    assert!(json.contains("\"extent\": [640, 480],"));
+}
+
+#[test]
+fn test_json_format() {
+    let json = Json::object([
+        ("name", "quote \" and \\ \n".into()),
+        ("empty", Json::Array(Vec::new())),
+        ("scale", Json::array([0.1f32, f32::NAN])),
+        ("missing", Option::<u32>::None.into()),
+    ]);
+    let expected = concat!(
+        "{\n",
+        "  \"name\": \"quote \\\" and \\\\ \\n\",\n",
+        "  \"empty\": [],\n",
+        "  \"scale\": [0.1, null],\n",
+        "  \"missing\": null\n",
+        "}\n",
+    );
+    assert_eq!(json.to_pretty_string(), expected);
+}
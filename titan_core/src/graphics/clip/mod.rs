@@ -0,0 +1,143 @@
+//! Rectangular clipping of immediate draws and text without UI.
+//!
+//! Clip rectangles are pushed onto [`ClipStack`] which is shared by
+//! [`DrawQueue`](super::DrawQueue) and [`TextBrush`](crate::text::TextBrush).
+//! Draws and text submitted while the rectangle is on the stack are clipped by it:
+//! each of them carries its effective rectangle, which is applied as the scissor
+//! when the draw is recorded into the frame.
+
+use std::sync::{Arc, Mutex};
+
+use vulkano::pipeline::viewport::Scissor;
+
+use crate::graphics::pre_rotation::PreTransform;
+
+mod tests;
+
+/// Rectangle in physical pixels of the window.
+///
+/// Top left corner of the window is (0, 0). Minimal corner is inclusive,
+/// maximal one is exclusive.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ClipRect {
+    pub min: [u32; 2],
+    pub max: [u32; 2],
+}
+
+impl ClipRect {
+    /// Creates rectangle with given top left corner and size.
+    pub fn new(origin: [u32; 2], size: [u32; 2]) -> Self {
+        Self {
+            min: origin,
+            max: [origin[0] + size[0], origin[1] + size[1]],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.max[0].saturating_sub(self.min[0])
+    }
+
+    pub fn height(&self) -> u32 {
+        self.max[1].saturating_sub(self.min[1])
+    }
+
+    /// Returns `true` if rectangle has no area, so nothing is drawn inside of it.
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    /// Intersection of two rectangles, which is empty if they do not overlap.
+    pub fn intersect(self, other: Self) -> Self {
+        let min = [self.min[0].max(other.min[0]), self.min[1].max(other.min[1])];
+        let max = [
+            self.max[0].min(other.max[0]).max(min[0]),
+            self.max[1].min(other.max[1]).max(min[1]),
+        ];
+        Self { min, max }
+    }
+
+    /// Converts rectangle in pixels of the window of given size into pixels of the image
+    /// which the scene of given size is rendered into.
+    ///
+    /// Both sizes are given in orientation of the window.
+    ///
+    pub(crate) fn to_image(
+        self,
+        window_size: [u32; 2],
+        scene_size: [u32; 2],
+        pre_transform: PreTransform,
+    ) -> Self {
+        let scale = [0, 1].map(|axis| scene_size[axis] as f32 / window_size[axis].max(1) as f32);
+        let min = [0, 1].map(|axis| self.min[axis] as f32 * scale[axis]);
+        let max = [0, 1].map(|axis| self.max[axis] as f32 * scale[axis]);
+        let scene_size = scene_size.map(|dimension| dimension as f32);
+        let (min, max) = pre_transform.transform_rect(min, max, scene_size);
+        Self {
+            min: min.map(|coord| coord.round().max(0.0) as u32),
+            max: max.map(|coord| coord.round().max(0.0) as u32),
+        }
+    }
+
+    pub(crate) fn from_scissor(scissor: &Scissor) -> Self {
+        Self::new(scissor.origin, scissor.dimensions)
+    }
+
+    pub(crate) fn scissor(&self) -> Scissor {
+        Scissor {
+            origin: self.min,
+            dimensions: [self.width(), self.height()],
+        }
+    }
+}
+
+/// Handle to the stack of clip rectangles of draws which are submitted now.
+///
+/// Each pushed rectangle is intersected with the parent one, so the top
+/// of the stack is the effective rectangle. Rectangles are additionally
+/// intersected with the viewport when draws are recorded.
+///
+/// Clones share one stack, which is also used by [`DrawQueue`](crate::graphics::DrawQueue)
+/// to clip submitted draws.
+#[derive(Debug, Default, Clone)]
+pub struct ClipStack {
+    rects: Arc<Mutex<Vec<ClipRect>>>,
+}
+
+impl ClipStack {
+    /// Pushes rectangle which clips draws submitted until it is popped.
+    pub fn push_clip_rect(&self, rect: ClipRect) {
+        let mut rects = self.rects.lock().unwrap();
+        let rect = match rects.last() {
+            Some(&parent) => parent.intersect(rect),
+            None => rect,
+        };
+        rects.push(rect)
+    }
+
+    /// Pops the last pushed rectangle, returning its effective rectangle.
+    ///
+    /// Returns `None` if the stack is empty.
+    ///
+    pub fn pop_clip_rect(&self) -> Option<ClipRect> {
+        self.rects.lock().unwrap().pop()
+    }
+
+    /// Effective rectangle of draws which are submitted now, if any.
+    pub fn current(&self) -> Option<ClipRect> {
+        self.rects.lock().unwrap().last().copied()
+    }
+
+    /// Count of rectangles on the stack.
+    pub fn depth(&self) -> usize {
+        self.rects.lock().unwrap().len()
+    }
+
+    /// Intersects the own rectangle of the draw (if any) with the effective one.
+    pub(crate) fn clip(&self, rect: Option<ClipRect>) -> Option<ClipRect> {
+        match (rect, self.current()) {
+            (Some(rect), Some(current)) => Some(current.intersect(rect)),
+            (rect, current) => rect.or(current),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_intersect() {
+    let rect = ClipRect::new([10, 20], [30, 40]);
+    assert_eq!(rect.max, [40, 60]);
+    assert_eq!((rect.width(), rect.height()), (30, 40));
+
+    let other = ClipRect::new([30, 0], [100, 30]);
+    assert_eq!(rect.intersect(other), ClipRect::new([30, 20], [10, 10]));
+    assert_eq!(other.intersect(rect), rect.intersect(other));
+
+    // Rectangles which do not overlap (or only touch) have empty intersection.
+    let far = ClipRect::new([40, 0], [10, 100]);
+    assert!(rect.intersect(far).is_empty());
+    let far = ClipRect::new([100, 100], [10, 10]);
+    let empty = rect.intersect(far);
+    assert!(empty.is_empty());
+    assert_eq!(empty.scissor().dimensions, [0, 0]);
+}
+
+#[test]
+fn test_stack_nesting() {
+    let stack = ClipStack::default();
+    assert_eq!(stack.current(), None);
+    assert_eq!(stack.clip(None), None);
+
+    let list = ClipRect::new([0, 0], [100, 100]);
+    stack.push_clip_rect(list);
+    // Nested rectangle is clipped by the parent one.
+    stack
+        .clone()
+        .push_clip_rect(ClipRect::new([50, 50], [100, 100]));
+    assert_eq!(stack.depth(), 2);
+    assert_eq!(stack.current(), Some(ClipRect::new([50, 50], [50, 50])));
+    let own = ClipRect::new([0, 0], [60, 60]);
+    assert_eq!(
+        stack.clip(Some(own)),
+        Some(ClipRect::new([50, 50], [10, 10]))
+    );
+
+    // Nested rectangle outside of the parent one clips everything.
+    stack.push_clip_rect(ClipRect::new([200, 0], [10, 10]));
+    assert!(stack.current().unwrap().is_empty());
+
+    stack.pop_clip_rect();
+    assert_eq!(
+        stack.pop_clip_rect(),
+        Some(ClipRect::new([50, 50], [50, 50]))
+    );
+    assert_eq!(stack.current(), Some(list));
+    assert_eq!(stack.pop_clip_rect(), Some(list));
+    assert_eq!(stack.pop_clip_rect(), None);
+    assert_eq!(stack.clip(Some(own)), Some(own));
+}
+
+#[test]
+fn test_to_image() {
+    let rect = ClipRect::new([0, 0], [50, 100]);
+    let identity = PreTransform::default();
+    assert_eq!(rect.to_image([100, 100], [100, 100], identity), rect);
+    // Scene which is rendered in half resolution is clipped in its own pixels.
+    assert_eq!(
+        rect.to_image([100, 100], [50, 50], identity),
+        ClipRect::new([0, 0], [25, 50])
+    );
+
+    // Top left corner of the window is placed at top right corner of the rotated image.
+    let rotated = PreTransform::new(1, false);
+    let rect = ClipRect::new([0, 0], [100, 50]);
+    let size = [1920, 1080];
+    assert_eq!(
+        rect.to_image(size, size, rotated),
+        ClipRect {
+            min: [1030, 0],
+            max: [1080, 100]
+        }
+    );
+}
@@ -0,0 +1,185 @@
+//! Safe recording of primary command buffers outside of the renderer.
+//!
+//! [`CommandBuffer`] records commands in the order which Vulkan expects:
+//!
+//! ```text
+//! begin -> [begin_render_pass -> bind_pipeline -> bind_vertex_buffers -> draw -> end_render_pass]* -> end
+//! ```
+//!
+//! Commands which are recorded out of this order are not recorded at all
+//! and return [`OrderError`], so the command buffer stays valid.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, AutoCommandBufferBuilderContextError, BeginRenderPassError,
+    BuildError, CommandBufferUsage, DrawError, PrimaryAutoCommandBuffer, SubpassContents,
+};
+use vulkano::device::Queue;
+use vulkano::format::ClearValue;
+use vulkano::pipeline::vertex::VertexBuffersCollection;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::render_pass::FramebufferAbstract;
+use vulkano::OomError;
+
+pub use self::state::{OrderError, RecordingState};
+
+use self::state::StateTracker;
+
+mod state;
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum CommandBufferError {
+    #[error("command recorded out of order: {0}")]
+    Order(#[from] OrderError),
+
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("begin render pass command failure: {0}")]
+    BeginRenderPass(#[from] BeginRenderPassError),
+
+    #[error("draw command failure: {0}")]
+    Draw(#[from] DrawError),
+
+    #[error("end render pass command failure: {0}")]
+    EndRenderPass(#[from] AutoCommandBufferBuilderContextError),
+
+    #[error("command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+}
+
+/// Primary command buffer which is recorded by the game
+/// and executed on the queue it was created for.
+///
+/// Command buffer could be recorded again after the previous recording was ended.
+///
+pub struct CommandBuffer {
+    queue: Arc<Queue>,
+    tracker: StateTracker,
+    builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+}
+
+impl CommandBuffer {
+    /// Creates command buffer which commands are executed on given queue.
+    pub fn new(queue: Arc<Queue>) -> Self {
+        Self {
+            queue,
+            tracker: StateTracker::new(),
+            builder: None,
+        }
+    }
+
+    /// Queue which recorded commands must be executed on.
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Current state of recording.
+    pub fn state(&self) -> RecordingState {
+        self.tracker.state()
+    }
+
+    /// Begins recording of commands, which are submitted once.
+    pub fn begin(&mut self) -> Result<&mut Self, CommandBufferError> {
+        let mut tracker = self.tracker;
+        tracker.begin()?;
+        let builder = AutoCommandBufferBuilder::primary(
+            self.queue.device().clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        self.builder = Some(builder);
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Begins the render pass of given framebuffer,
+    /// which commands are recorded inline.
+    pub fn begin_render_pass<I>(
+        &mut self,
+        framebuffer: Arc<dyn FramebufferAbstract + Send + Sync>,
+        clear_values: I,
+    ) -> Result<&mut Self, CommandBufferError>
+    where
+        I: IntoIterator<Item = ClearValue>,
+    {
+        let mut tracker = self.tracker;
+        tracker.begin_render_pass()?;
+        self.builder()
+            .begin_render_pass(framebuffer, SubpassContents::Inline, clear_values)?;
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Binds graphics pipeline for the next draw commands.
+    pub fn bind_pipeline(
+        &mut self,
+        pipeline: Arc<GraphicsPipeline>,
+    ) -> Result<&mut Self, CommandBufferError> {
+        let mut tracker = self.tracker;
+        tracker.bind_pipeline()?;
+        self.builder().bind_pipeline_graphics(pipeline);
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Binds vertex buffers starting from given binding for the next draw commands.
+    pub fn bind_vertex_buffers<V>(
+        &mut self,
+        first_binding: u32,
+        vertex_buffers: V,
+    ) -> Result<&mut Self, CommandBufferError>
+    where
+        V: VertexBuffersCollection,
+    {
+        let mut tracker = self.tracker;
+        tracker.bind_vertex_buffers()?;
+        self.builder()
+            .bind_vertex_buffers(first_binding, vertex_buffers);
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Draws vertices with bound pipeline and vertex buffers.
+    pub fn draw(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) -> Result<&mut Self, CommandBufferError> {
+        let mut tracker = self.tracker;
+        tracker.draw()?;
+        self.builder()
+            .draw(vertex_count, instance_count, first_vertex, first_instance)?;
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Ends the current render pass.
+    pub fn end_render_pass(&mut self) -> Result<&mut Self, CommandBufferError> {
+        let mut tracker = self.tracker;
+        tracker.end_render_pass()?;
+        self.builder().end_render_pass()?;
+        self.tracker = tracker;
+        Ok(self)
+    }
+
+    /// Ends recording of commands, returning command buffer
+    /// which could be executed on [the queue](CommandBuffer::queue).
+    ///
+    /// Recorded commands are discarded if the command buffer could not be built.
+    ///
+    pub fn end(&mut self) -> Result<PrimaryAutoCommandBuffer, CommandBufferError> {
+        self.tracker.end()?;
+        let builder = self.builder.take().expect("recording must be begun");
+        Ok(builder.build()?)
+    }
+
+    fn builder(&mut self) -> &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        self.builder.as_mut().expect("recording must be begun")
+    }
+}
@@ -0,0 +1,103 @@
+//! Order of commands which are recorded into the command buffer.
+
+use thiserror::Error;
+
+/// State of the command buffer which is being recorded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RecordingState {
+    /// Recording was not begun yet, or the previous recording was ended.
+    Initial,
+    /// Commands are recorded outside of any render pass.
+    Recording,
+    /// Commands are recorded inside of the render pass.
+    InRenderPass,
+}
+
+/// Command which was recorded out of order.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    #[error("`{command}` could not be recorded in {state:?} state")]
+    WrongState {
+        command: &'static str,
+        state: RecordingState,
+    },
+
+    #[error("`draw` requires graphics pipeline to be bound")]
+    NoPipeline,
+}
+
+/// Tracks state of the command buffer, so commands are recorded only in valid order.
+#[derive(Debug, Copy, Clone)]
+pub(super) struct StateTracker {
+    state: RecordingState,
+    pipeline_bound: bool,
+}
+
+impl StateTracker {
+    pub const fn new() -> Self {
+        Self {
+            state: RecordingState::Initial,
+            pipeline_bound: false,
+        }
+    }
+
+    pub fn state(&self) -> RecordingState {
+        self.state
+    }
+
+    pub fn begin(&mut self) -> Result<(), OrderError> {
+        self.expect("begin", &[RecordingState::Initial])?;
+        self.state = RecordingState::Recording;
+        self.pipeline_bound = false;
+        Ok(())
+    }
+
+    pub fn begin_render_pass(&mut self) -> Result<(), OrderError> {
+        self.expect("begin_render_pass", &[RecordingState::Recording])?;
+        self.state = RecordingState::InRenderPass;
+        Ok(())
+    }
+
+    pub fn bind_pipeline(&mut self) -> Result<(), OrderError> {
+        let allowed = [RecordingState::Recording, RecordingState::InRenderPass];
+        self.expect("bind_pipeline", &allowed)?;
+        self.pipeline_bound = true;
+        Ok(())
+    }
+
+    pub fn bind_vertex_buffers(&mut self) -> Result<(), OrderError> {
+        let allowed = [RecordingState::Recording, RecordingState::InRenderPass];
+        self.expect("bind_vertex_buffers", &allowed)
+    }
+
+    pub fn draw(&mut self) -> Result<(), OrderError> {
+        self.expect("draw", &[RecordingState::InRenderPass])?;
+        if !self.pipeline_bound {
+            return Err(OrderError::NoPipeline);
+        }
+        Ok(())
+    }
+
+    pub fn end_render_pass(&mut self) -> Result<(), OrderError> {
+        self.expect("end_render_pass", &[RecordingState::InRenderPass])?;
+        self.state = RecordingState::Recording;
+        Ok(())
+    }
+
+    pub fn end(&mut self) -> Result<(), OrderError> {
+        self.expect("end", &[RecordingState::Recording])?;
+        self.state = RecordingState::Initial;
+        Ok(())
+    }
+
+    /// Returns an error if the command is recorded in the state which is not allowed.
+    fn expect(&self, command: &'static str, allowed: &[RecordingState]) -> Result<(), OrderError> {
+        if allowed.contains(&self.state) {
+            return Ok(());
+        }
+        Err(OrderError::WrongState {
+            command,
+            state: self.state,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use super::state::StateTracker;
+use super::*;
+
+#[test]
+fn test_recording_order() {
+    let mut tracker = StateTracker::new();
+    assert_eq!(tracker.state(), RecordingState::Initial);
+    tracker.begin().unwrap();
+    tracker.bind_pipeline().unwrap();
+    tracker.begin_render_pass().unwrap();
+    assert_eq!(tracker.state(), RecordingState::InRenderPass);
+    tracker.bind_vertex_buffers().unwrap();
+    tracker.draw().unwrap();
+    tracker.end_render_pass().unwrap();
+    assert_eq!(tracker.state(), RecordingState::Recording);
+    tracker.end().unwrap();
+    assert_eq!(tracker.state(), RecordingState::Initial);
+
+    // Pipeline which was bound by the previous recording is forgotten.
+    tracker.begin().unwrap();
+    tracker.begin_render_pass().unwrap();
+    assert_eq!(tracker.draw(), Err(OrderError::NoPipeline));
+}
+
+#[test]
+fn test_out_of_order() {
+    let wrong_state = |command, state| Err(OrderError::WrongState { command, state });
+
+    let mut tracker = StateTracker::new();
+    assert_eq!(
+        tracker.begin_render_pass(),
+        wrong_state("begin_render_pass", RecordingState::Initial),
+    );
+    assert_eq!(
+        tracker.bind_pipeline(),
+        wrong_state("bind_pipeline", RecordingState::Initial),
+    );
+    assert_eq!(tracker.end(), wrong_state("end", RecordingState::Initial));
+
+    tracker.begin().unwrap();
+    assert_eq!(
+        tracker.begin(),
+        wrong_state("begin", RecordingState::Recording)
+    );
+    tracker.bind_pipeline().unwrap();
+    assert_eq!(
+        tracker.draw(),
+        wrong_state("draw", RecordingState::Recording)
+    );
+    assert_eq!(
+        tracker.end_render_pass(),
+        wrong_state("end_render_pass", RecordingState::Recording),
+    );
+
+    tracker.begin_render_pass().unwrap();
+    assert_eq!(
+        tracker.begin_render_pass(),
+        wrong_state("begin_render_pass", RecordingState::InRenderPass),
+    );
+    assert_eq!(
+        tracker.end(),
+        wrong_state("end", RecordingState::InRenderPass)
+    );
+    // Failed commands do not change the state.
+    assert_eq!(tracker.state(), RecordingState::InRenderPass);
+}
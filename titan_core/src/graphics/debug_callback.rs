@@ -8,42 +8,174 @@
 };
 use vulkano::instance::Instance;
 
-/// Create debug callback for validation via Vulkan SDK.
+/// Level of validation of Vulkan API usage with Khronos validation layer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ValidationLevel {
+    /// Validation layer is not enabled.
+    Off,
+    /// Validation layer is enabled, but only its errors are reported.
+    Errors,
+    /// Validation layer is enabled and messages of all severities are reported.
+    Full,
+}
+
+impl ValidationLevel {
+    /// If validation layer is enabled on this level.
+    pub fn is_enabled(self) -> bool {
+        self != Self::Off
+    }
+
+    /// Least severity of messages which are reported on this level.
+    fn min_severity(self) -> Option<ValidationSeverity> {
+        match self {
+            Self::Off => None,
+            Self::Errors => Some(ValidationSeverity::Error),
+            Self::Full => Some(ValidationSeverity::Verbose),
+        }
+    }
+}
+
+/// Severity of the validation message, from the least to the most severe.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ValidationSeverity {
+    Verbose,
+    Info,
+    Warning,
+    Error,
+}
+
+impl ValidationSeverity {
+    /// Level of the log record for messages of this severity.
+    pub fn log_level(self) -> Level {
+        match self {
+            Self::Verbose => Level::Trace,
+            Self::Info => Level::Debug,
+            Self::Warning => Level::Warn,
+            Self::Error => Level::Error,
+        }
+    }
+
+    /// The most severe of given Vulkan severities.
+    #[rustfmt::skip]
+    fn from_vk(severity: MessageSeverity) -> Self {
+        match severity {
+            MessageSeverity { error: true, .. } => Self::Error,
+            MessageSeverity { warning: true, .. } => Self::Warning,
+            MessageSeverity { information: true, .. } => Self::Info,
+            _ => Self::Verbose,
+        }
+    }
+}
+
+/// Filter of validation messages which are logged.
+///
+/// By default, messages of all severities (allowed by [`ValidationLevel`]) are logged.
+/// Known noisy messages could be suppressed by their ID names,
+/// like `UNASSIGNED-BestPractices-vkCreateInstance-specialuse-extension`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationFilter {
+    min_severity: ValidationSeverity,
+    suppressed: Vec<String>,
+}
+
+impl Default for ValidationFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValidationFilter {
+    /// Creates filter which allows all messages.
+    pub const fn new() -> Self {
+        Self {
+            min_severity: ValidationSeverity::Verbose,
+            suppressed: Vec::new(),
+        }
+    }
+
+    /// Sets the least severity of messages which are logged.
+    pub fn with_min_severity(mut self, severity: ValidationSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Suppresses messages with given ID name.
+    pub fn suppress(mut self, id: impl Into<String>) -> Self {
+        self.suppressed.push(id.into());
+        self
+    }
+
+    /// The least severity of messages which are logged.
+    pub fn min_severity(&self) -> ValidationSeverity {
+        self.min_severity
+    }
+
+    /// ID names of messages which are suppressed.
+    pub fn suppressed(&self) -> &[String] {
+        &self.suppressed
+    }
+
+    /// Returns `true` if the message with given severity and ID name should be logged.
+    pub fn allows(&self, severity: ValidationSeverity, id: Option<&str>) -> bool {
+        let suppressed = id.map_or(false, |id| self.suppressed.iter().any(|s| s == id));
+        severity >= self.min_severity && !suppressed
+    }
+
+    /// Vulkan severities of messages which are passed into the callback on given level,
+    /// so messages filtered out by severity are not even reported by the layer.
+    fn message_severity(&self, level: ValidationLevel) -> MessageSeverity {
+        let min = match level.min_severity() {
+            Some(min) => min.max(self.min_severity),
+            None => return MessageSeverity::none(),
+        };
+        MessageSeverity {
+            error: true,
+            warning: min <= ValidationSeverity::Warning,
+            information: min <= ValidationSeverity::Info,
+            verbose: min <= ValidationSeverity::Verbose,
+        }
+    }
+}
+
+/// Create debug callback for validation via Vulkan SDK
+/// which logs messages allowed by given level and filter.
 ///
 /// Note that Khronos validation layer must be enabled.
 ///
 pub fn create_debug_callback(
     instance: &Arc<Instance>,
-    severity: MessageSeverity,
-    ty: MessageType,
+    level: ValidationLevel,
+    filter: ValidationFilter,
 ) -> Result<DebugCallback, DebugCallbackCreationError> {
-    DebugCallback::new(instance, severity, ty, self::user_callback)
+    let severity = filter.message_severity(level);
+    DebugCallback::new(instance, severity, MessageType::all(), move |message| {
+        self::user_callback(&filter, message)
+    })
 }
 
 /// The actual callback validation function.
 ///
-/// Logs message into global logger.
+/// Logs message into global logger if it is allowed by the filter.
 ///
 #[rustfmt::skip]
-fn user_callback(message: &Message) {
-    let level = match message.severity {
-        MessageSeverity { verbose: true, .. } => Level::Trace,
-        MessageSeverity { information: true, .. } => Level::Info,
-        MessageSeverity { warning: true, .. } => Level::Warn,
-        MessageSeverity { error: true, .. } => Level::Error,
-        _ => Level::Trace,
-    };
+fn user_callback(filter: &ValidationFilter, message: &Message) {
+    let severity = ValidationSeverity::from_vk(message.severity);
+    // ID name of the message is passed by Vulkano as the layer prefix.
+    if !filter.allows(severity, message.layer_prefix) {
+        return;
+    }
     let ty = match message.ty {
-        MessageType { general: true, .. } => "GENERAL",
         MessageType { validation: true, .. } => "VALIDATION",
         MessageType { performance: true, .. } => "PERFORMANCE",
+        MessageType { general: true, .. } => "GENERAL",
         _ => "UNKNOWN",
     };
     let layer_prefix = message.layer_prefix.unwrap_or("Unknown");
     let description = message.description;
 
     log::log!(
-        level,
+        severity.log_level(),
         r#"{} [layer "{}"]: "{}""#,
         ty,
         layer_prefix,
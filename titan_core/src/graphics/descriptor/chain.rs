@@ -0,0 +1,98 @@
+//! Growable list of pools which are allocated from one after another.
+
+use ash::vk;
+
+/// Error of allocation from the chain of pools.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum ChainError {
+    /// New pool could not be created.
+    PoolCreation(vk::Result),
+    /// Allocation failed not because of exhausted pool.
+    Allocation(vk::Result),
+    /// Allocation does not fit even into the new pool.
+    TooLarge,
+}
+
+/// Returns `true` if allocation failed only because the pool has no more space.
+fn is_exhausted(result: vk::Result) -> bool {
+    matches!(
+        result,
+        vk::Result::ERROR_OUT_OF_POOL_MEMORY | vk::Result::ERROR_FRAGMENTED_POOL
+    )
+}
+
+/// List of pools where allocations are made from the active pool
+/// until it is exhausted, then from the next one (which is created on demand).
+///
+/// Pools are never destroyed while the chain is alive, they are only reset,
+/// so the chain grows up to the peak demand and stays there.
+///
+pub(super) struct PoolChain<P> {
+    pools: Vec<P>,
+    /// Index of the pool which allocations are made from.
+    active: usize,
+}
+
+impl<P> PoolChain<P> {
+    pub fn new() -> Self {
+        Self {
+            pools: Vec::new(),
+            active: 0,
+        }
+    }
+
+    /// Count of pools in the chain.
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Allocates from the active pool, moving to the next pool if it is exhausted.
+    ///
+    /// Returns allocated value with count of pools which were created for it.
+    ///
+    pub fn allocate<T>(
+        &mut self,
+        mut create: impl FnMut() -> Result<P, vk::Result>,
+        mut allocate: impl FnMut(&P) -> Result<T, vk::Result>,
+    ) -> Result<(T, u32), ChainError> {
+        let mut created = 0;
+        loop {
+            let fresh = self.active == self.pools.len();
+            if fresh {
+                let pool = create().map_err(ChainError::PoolCreation)?;
+                self.pools.push(pool);
+                created += 1;
+            }
+            match allocate(&self.pools[self.active]) {
+                Ok(value) => return Ok((value, created)),
+                Err(result) if self::is_exhausted(result) => {
+                    if fresh {
+                        return Err(ChainError::TooLarge);
+                    }
+                    self.active += 1;
+                }
+                Err(result) => return Err(ChainError::Allocation(result)),
+            }
+        }
+    }
+
+    /// Resets all pools which were used since the last reset,
+    /// so allocations start from the first pool again.
+    pub fn reset(
+        &mut self,
+        mut reset: impl FnMut(&P) -> Result<(), vk::Result>,
+    ) -> Result<(), vk::Result> {
+        let used = (self.active + 1).min(self.pools.len());
+        for pool in &self.pools[..used] {
+            reset(pool)?;
+        }
+        self.active = 0;
+        Ok(())
+    }
+
+    /// Removes all pools of the chain.
+    pub fn clear(&mut self) {
+        self.pools.clear();
+        self.active = 0;
+    }
+}
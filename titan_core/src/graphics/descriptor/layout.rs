@@ -0,0 +1,214 @@
+//! Descriptor set layouts which are described by their bindings.
+//!
+//! Layouts of built-in pipelines are reflected from their shaders,
+//! but sets which are shared by user pipelines (like sets of materials)
+//! could be described by hand and allocated before any pipeline is created.
+
+use std::ptr;
+use std::sync::Arc;
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::device::Device;
+use vulkano::VulkanObject;
+
+use super::DescriptorPoolSizes;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DescriptorLayoutError {
+    #[error("binding {0} is described more than once")]
+    DuplicateBinding(u32),
+
+    #[error("binding {0} is not used by any shader stage")]
+    NoStages(u32),
+
+    #[error("binding {0} has no descriptors")]
+    Empty(u32),
+
+    #[error("descriptor set layout creation failure: {0}")]
+    Creation(vk::Result),
+}
+
+/// Description of one binding of the descriptor set layout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DescriptorBinding {
+    /// Index of the binding in the set.
+    pub binding: u32,
+    /// Type of descriptors of the binding.
+    pub ty: vk::DescriptorType,
+    /// Count of descriptors of the binding (greater than one for arrays).
+    pub count: u32,
+    /// Shader stages which access descriptors of the binding.
+    pub stages: vk::ShaderStageFlags,
+}
+
+/// Builder of the descriptor set layout from uniform buffers, storage buffers
+/// and combined image samplers.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DescriptorLayoutBuilder {
+    bindings: Vec<DescriptorBinding>,
+}
+
+impl DescriptorLayoutBuilder {
+    /// Creates builder of the layout without bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds binding of one uniform buffer.
+    pub fn uniform_buffer(self, binding: u32, stages: vk::ShaderStageFlags) -> Self {
+        self.binding(DescriptorBinding {
+            binding,
+            ty: vk::DescriptorType::UNIFORM_BUFFER,
+            count: 1,
+            stages,
+        })
+    }
+
+    /// Adds binding of one storage buffer.
+    pub fn storage_buffer(self, binding: u32, stages: vk::ShaderStageFlags) -> Self {
+        self.binding(DescriptorBinding {
+            binding,
+            ty: vk::DescriptorType::STORAGE_BUFFER,
+            count: 1,
+            stages,
+        })
+    }
+
+    /// Adds binding of one image with its sampler.
+    pub fn combined_image_sampler(self, binding: u32, stages: vk::ShaderStageFlags) -> Self {
+        self.binding(DescriptorBinding {
+            binding,
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            count: 1,
+            stages,
+        })
+    }
+
+    /// Adds binding with arbitrary type and count of descriptors.
+    pub fn binding(mut self, binding: DescriptorBinding) -> Self {
+        self.bindings.push(binding);
+        self
+    }
+
+    /// Bindings which were added to the builder.
+    pub fn bindings(&self) -> &[DescriptorBinding] {
+        &self.bindings
+    }
+
+    /// Checks that each binding is described once and is used by some descriptors and stages.
+    pub fn validate(&self) -> Result<(), DescriptorLayoutError> {
+        for (index, binding) in self.bindings.iter().enumerate() {
+            if self.bindings[..index]
+                .iter()
+                .any(|other| other.binding == binding.binding)
+            {
+                return Err(DescriptorLayoutError::DuplicateBinding(binding.binding));
+            }
+            if binding.count == 0 {
+                return Err(DescriptorLayoutError::Empty(binding.binding));
+            }
+            if binding.stages.is_empty() {
+                return Err(DescriptorLayoutError::NoStages(binding.binding));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates descriptor set layout with described bindings.
+    pub fn build(
+        self,
+        device: Arc<Device>,
+    ) -> Result<Arc<DescriptorLayout>, DescriptorLayoutError> {
+        self.validate()?;
+        let bindings: Vec<_> = self
+            .bindings
+            .iter()
+            .map(|binding| {
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(binding.binding)
+                    .descriptor_type(binding.ty)
+                    .descriptor_count(binding.count)
+                    .stage_flags(binding.stages)
+                    .build()
+            })
+            .collect();
+        let create_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings);
+        let mut handle = vk::DescriptorSetLayout::null();
+        let result = unsafe {
+            device.fns().v1_0.create_descriptor_set_layout(
+                device.internal_object(),
+                &*create_info,
+                ptr::null(),
+                &mut handle,
+            )
+        };
+        match result {
+            vk::Result::SUCCESS => Ok(Arc::new(DescriptorLayout {
+                device,
+                handle,
+                bindings: self.bindings,
+            })),
+            result => Err(DescriptorLayoutError::Creation(result)),
+        }
+    }
+}
+
+/// Descriptor set layout which was created by [`DescriptorLayoutBuilder`].
+///
+/// Sets are allocated with this layout by [`DescriptorAllocator`](super::DescriptorAllocator).
+/// Layout is destroyed on drop, so it must outlive all of its sets.
+///
+pub struct DescriptorLayout {
+    device: Arc<Device>,
+    handle: vk::DescriptorSetLayout,
+    bindings: Vec<DescriptorBinding>,
+}
+
+impl DescriptorLayout {
+    /// Bindings of the layout.
+    pub fn bindings(&self) -> &[DescriptorBinding] {
+        &self.bindings
+    }
+
+    /// Capacity of the descriptor pool which fits given count of sets with this layout.
+    pub fn pool_sizes(&self, max_sets: u32) -> DescriptorPoolSizes {
+        pool_sizes(&self.bindings, max_sets)
+    }
+}
+
+unsafe impl VulkanObject for DescriptorLayout {
+    type Object = vk::DescriptorSetLayout;
+
+    fn internal_object(&self) -> vk::DescriptorSetLayout {
+        self.handle
+    }
+}
+
+impl Drop for DescriptorLayout {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.fns().v1_0.destroy_descriptor_set_layout(
+                self.device.internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+/// Capacity of the descriptor pool which fits given count of sets with given bindings.
+pub(super) fn pool_sizes(bindings: &[DescriptorBinding], max_sets: u32) -> DescriptorPoolSizes {
+    let mut descriptors: Vec<(vk::DescriptorType, u32)> = Vec::new();
+    for binding in bindings {
+        let count = binding.count * max_sets;
+        match descriptors.iter_mut().find(|(ty, _)| *ty == binding.ty) {
+            Some((_, total)) => *total += count,
+            None => descriptors.push((binding.ty, count)),
+        }
+    }
+    DescriptorPoolSizes {
+        max_sets,
+        descriptors,
+    }
+}
@@ -0,0 +1,477 @@
+//! Descriptor sets which are allocated from pools owned by frames in flight.
+//!
+//! Sets of the frame are never freed one by one: all pools of the frame are reset
+//! wholesale when the frame begins again. If the pool is exhausted
+//! (`ERROR_OUT_OF_POOL_MEMORY` or `ERROR_FRAGMENTED_POOL`), allocation transparently
+//! moves to the next pool, creating it on demand. Long-lived sets (like sets of materials)
+//! are allocated from separate persistent pools which are never reset.
+//!
+//! Writes into descriptor sets are batched, so all of them are issued
+//! with one `vkUpdateDescriptorSets` call.
+//!
+//! Sets are allocated with layouts of pipelines or with layouts
+//! which are described by [`DescriptorLayoutBuilder`].
+
+use std::any::Any;
+use std::ptr;
+use std::sync::Arc;
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::buffer::BufferAccess;
+use vulkano::device::Device;
+use vulkano::image::view::ImageViewAbstract;
+use vulkano::sampler::Sampler;
+use vulkano::VulkanObject;
+
+use self::chain::{ChainError, PoolChain};
+
+pub use self::layout::{
+    DescriptorBinding, DescriptorLayout, DescriptorLayoutBuilder, DescriptorLayoutError,
+};
+
+mod chain;
+mod layout;
+mod tests;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DescriptorAllocError {
+    #[error("descriptor pool creation failure: {0}")]
+    PoolCreation(vk::Result),
+
+    #[error("descriptor set allocation failure: {0}")]
+    Allocation(vk::Result),
+
+    #[error("descriptor set layout does not fit into an empty descriptor pool")]
+    TooLarge,
+
+    #[error("failed to reset descriptor pool: {0}")]
+    Reset(vk::Result),
+}
+
+impl From<ChainError> for DescriptorAllocError {
+    fn from(error: ChainError) -> Self {
+        match error {
+            ChainError::PoolCreation(result) => Self::PoolCreation(result),
+            ChainError::Allocation(result) => Self::Allocation(result),
+            ChainError::TooLarge => Self::TooLarge,
+        }
+    }
+}
+
+/// Capacity of each descriptor pool created by the allocator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorPoolSizes {
+    /// Max count of descriptor sets allocated from one pool.
+    pub max_sets: u32,
+    /// Count of descriptors of each type in one pool.
+    pub descriptors: Vec<(vk::DescriptorType, u32)>,
+}
+
+impl Default for DescriptorPoolSizes {
+    fn default() -> Self {
+        Self {
+            max_sets: 1024,
+            descriptors: vec![
+                (vk::DescriptorType::UNIFORM_BUFFER, 1024),
+                (vk::DescriptorType::STORAGE_BUFFER, 1024),
+                (vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 1024),
+            ],
+        }
+    }
+}
+
+/// Statistics of descriptor allocations of the frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DescriptorStats {
+    /// Count of descriptor sets allocated in the frame (both per-frame and persistent).
+    pub sets_allocated: u32,
+    /// Count of descriptor pools created in the frame.
+    pub pools_created: u32,
+    /// Count of descriptor pools owned by the allocator.
+    pub pools: u32,
+    /// Count of descriptor writes issued in the frame.
+    pub writes: u32,
+}
+
+/// Descriptor pool which is destroyed on drop.
+struct RawPool {
+    device: Arc<Device>,
+    handle: vk::DescriptorPool,
+}
+
+impl RawPool {
+    fn new(device: Arc<Device>, sizes: &DescriptorPoolSizes) -> Result<Self, vk::Result> {
+        let pool_sizes: Vec<_> = sizes
+            .descriptors
+            .iter()
+            .map(|&(ty, descriptor_count)| vk::DescriptorPoolSize {
+                ty,
+                descriptor_count,
+            })
+            .collect();
+        let create_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(sizes.max_sets)
+            .pool_sizes(&pool_sizes);
+        let mut handle = vk::DescriptorPool::null();
+        let result = unsafe {
+            device.fns().v1_0.create_descriptor_pool(
+                device.internal_object(),
+                &*create_info,
+                ptr::null(),
+                &mut handle,
+            )
+        };
+        match result {
+            vk::Result::SUCCESS => Ok(Self { device, handle }),
+            result => Err(result),
+        }
+    }
+
+    fn allocate(&self, layout: vk::DescriptorSetLayout) -> Result<vk::DescriptorSet, vk::Result> {
+        let layouts = [layout];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(self.handle)
+            .set_layouts(&layouts);
+        let mut set = vk::DescriptorSet::null();
+        let result = unsafe {
+            self.device.fns().v1_0.allocate_descriptor_sets(
+                self.device.internal_object(),
+                &*allocate_info,
+                &mut set,
+            )
+        };
+        match result {
+            vk::Result::SUCCESS => Ok(set),
+            result => Err(result),
+        }
+    }
+
+    /// Returns all sets allocated from the pool back to it.
+    ///
+    /// # Safety
+    ///
+    /// Sets of the pool must not be used by any pending commands.
+    ///
+    unsafe fn reset(&self) -> Result<(), vk::Result> {
+        let result = self.device.fns().v1_0.reset_descriptor_pool(
+            self.device.internal_object(),
+            self.handle,
+            vk::DescriptorPoolResetFlags::empty(),
+        );
+        match result {
+            vk::Result::SUCCESS => Ok(()),
+            result => Err(result),
+        }
+    }
+}
+
+impl Drop for RawPool {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.fns().v1_0.destroy_descriptor_pool(
+                self.device.internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+/// Resource which must be alive while descriptors referring to it are used.
+type Resource = Arc<dyn Any + Send + Sync>;
+
+/// Descriptor info of the pending write.
+enum WriteInfo {
+    Buffer(vk::DescriptorBufferInfo),
+    Image(vk::DescriptorImageInfo),
+}
+
+struct PendingWrite {
+    set: vk::DescriptorSet,
+    binding: u32,
+    array_element: u32,
+    ty: vk::DescriptorType,
+    info: WriteInfo,
+}
+
+/// Batch of writes into descriptor sets which are issued all at once.
+#[derive(Default)]
+pub struct DescriptorWrites {
+    writes: Vec<PendingWrite>,
+    /// Resources referred by the writes.
+    resources: Vec<Resource>,
+}
+
+impl DescriptorWrites {
+    /// Count of pending writes.
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Checks if there are no pending writes.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Writes the whole buffer into the binding of the set.
+    pub fn buffer<B>(
+        &mut self,
+        set: vk::DescriptorSet,
+        binding: u32,
+        array_element: u32,
+        ty: vk::DescriptorType,
+        buffer: Arc<B>,
+    ) where
+        B: BufferAccess + Send + Sync + 'static,
+    {
+        let inner = buffer.inner();
+        let info = vk::DescriptorBufferInfo {
+            buffer: inner.buffer.internal_object(),
+            offset: inner.offset,
+            range: buffer.size(),
+        };
+        self.writes.push(PendingWrite {
+            set,
+            binding,
+            array_element,
+            ty,
+            info: WriteInfo::Buffer(info),
+        });
+        self.resources.push(buffer);
+    }
+
+    /// Writes the image view with the sampler into the combined image sampler binding of the set.
+    ///
+    /// Image must be in the `SHADER_READ_ONLY_OPTIMAL` layout when it is sampled.
+    ///
+    pub fn sampled_image<I>(
+        &mut self,
+        set: vk::DescriptorSet,
+        binding: u32,
+        array_element: u32,
+        image_view: Arc<I>,
+        sampler: Arc<Sampler>,
+    ) where
+        I: ImageViewAbstract + Send + Sync + 'static,
+    {
+        let info = vk::DescriptorImageInfo {
+            sampler: sampler.internal_object(),
+            image_view: image_view.inner().internal_object(),
+            image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        };
+        self.writes.push(PendingWrite {
+            set,
+            binding,
+            array_element,
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            info: WriteInfo::Image(info),
+        });
+        self.resources.push(image_view);
+        self.resources.push(sampler);
+    }
+
+    /// Issues all pending writes with one call, returning resources referred by them.
+    fn flush(&mut self, device: &Device) -> Vec<Resource> {
+        if self.writes.is_empty() {
+            return Vec::new();
+        }
+        // Infos are collected first, so their addresses are stable while writes refer to them.
+        let (buffer_infos, image_infos): (Vec<_>, Vec<_>) = self
+            .writes
+            .iter()
+            .map(|write| match write.info {
+                WriteInfo::Buffer(info) => (info, vk::DescriptorImageInfo::default()),
+                WriteInfo::Image(info) => (vk::DescriptorBufferInfo::default(), info),
+            })
+            .unzip();
+        let writes: Vec<_> = self
+            .writes
+            .iter()
+            .enumerate()
+            .map(|(index, write)| {
+                let builder = vk::WriteDescriptorSet::builder()
+                    .dst_set(write.set)
+                    .dst_binding(write.binding)
+                    .dst_array_element(write.array_element)
+                    .descriptor_type(write.ty);
+                match write.info {
+                    WriteInfo::Buffer(_) => builder.buffer_info(&buffer_infos[index..=index]),
+                    WriteInfo::Image(_) => builder.image_info(&image_infos[index..=index]),
+                }
+                .build()
+            })
+            .collect();
+        unsafe {
+            device.fns().v1_0.update_descriptor_sets(
+                device.internal_object(),
+                writes.len() as u32,
+                writes.as_ptr(),
+                0,
+                ptr::null(),
+            );
+        }
+        self.writes.clear();
+        std::mem::take(&mut self.resources)
+    }
+}
+
+/// Pools of one frame in flight.
+struct FramePools {
+    chain: PoolChain<RawPool>,
+    /// Resources referred by sets of the frame which are released when the frame begins again.
+    resources: Vec<Resource>,
+}
+
+impl FramePools {
+    fn new() -> Self {
+        Self {
+            chain: PoolChain::new(),
+            resources: Vec::new(),
+        }
+    }
+}
+
+/// Allocator of descriptor sets which live for one frame or for the whole lifetime of the allocator.
+///
+/// Sets allocated for the frame must be used only by commands of the frame
+/// which was begun the last, because they become invalid when the same frame begins again.
+/// Resources written into the sets are kept alive until the frame which flushed
+/// the writes begins again, so resources of persistent sets must be kept alive by the user.
+///
+pub struct DescriptorAllocator {
+    device: Arc<Device>,
+    sizes: DescriptorPoolSizes,
+    frames: Vec<FramePools>,
+    /// Index of the frame which was begun the last.
+    current: usize,
+    persistent: PoolChain<RawPool>,
+    writes: DescriptorWrites,
+    stats: DescriptorStats,
+}
+
+impl DescriptorAllocator {
+    /// Creates new allocator for given count of frames in flight.
+    ///
+    /// No pools are created until the first allocation.
+    ///
+    pub(crate) fn new(device: Arc<Device>, frames: usize, sizes: DescriptorPoolSizes) -> Self {
+        Self {
+            device,
+            sizes,
+            frames: (0..frames.max(1)).map(|_| FramePools::new()).collect(),
+            current: 0,
+            persistent: PoolChain::new(),
+            writes: DescriptorWrites::default(),
+            stats: DescriptorStats::default(),
+        }
+    }
+
+    /// Begins the frame with given index, resetting all of its pools.
+    ///
+    /// Statistics of the previous frame are cleared.
+    ///
+    /// # Safety
+    ///
+    /// Commands of the previous frame with the same index must be finished.
+    ///
+    pub(crate) unsafe fn begin_frame(&mut self, index: usize) -> Result<(), DescriptorAllocError> {
+        if index >= self.frames.len() {
+            self.frames.resize_with(index + 1, FramePools::new);
+        }
+        let frame = &mut self.frames[index];
+        frame
+            .chain
+            .reset(|pool| pool.reset())
+            .map_err(DescriptorAllocError::Reset)?;
+        frame.resources.clear();
+        self.current = index;
+        self.stats = DescriptorStats {
+            pools: self.pool_count(),
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    /// Allocates descriptor set with given layout which is valid until the current frame begins again.
+    pub fn allocate<L>(&mut self, layout: &L) -> Result<vk::DescriptorSet, DescriptorAllocError>
+    where
+        L: VulkanObject<Object = vk::DescriptorSetLayout> + ?Sized,
+    {
+        let chain = &mut self.frames[self.current].chain;
+        let (set, created) =
+            Self::allocate_from(&self.device, &self.sizes, chain, layout.internal_object())?;
+        self.count_allocation(created);
+        Ok(set)
+    }
+
+    /// Allocates descriptor set with given layout which is valid for the whole lifetime of the allocator.
+    pub fn allocate_persistent<L>(
+        &mut self,
+        layout: &L,
+    ) -> Result<vk::DescriptorSet, DescriptorAllocError>
+    where
+        L: VulkanObject<Object = vk::DescriptorSetLayout> + ?Sized,
+    {
+        let chain = &mut self.persistent;
+        let (set, created) =
+            Self::allocate_from(&self.device, &self.sizes, chain, layout.internal_object())?;
+        self.count_allocation(created);
+        Ok(set)
+    }
+
+    fn allocate_from(
+        device: &Arc<Device>,
+        sizes: &DescriptorPoolSizes,
+        chain: &mut PoolChain<RawPool>,
+        layout: vk::DescriptorSetLayout,
+    ) -> Result<(vk::DescriptorSet, u32), DescriptorAllocError> {
+        let result = chain.allocate(
+            || RawPool::new(device.clone(), sizes),
+            |pool| pool.allocate(layout),
+        )?;
+        Ok(result)
+    }
+
+    fn count_allocation(&mut self, created: u32) {
+        self.stats.sets_allocated += 1;
+        self.stats.pools_created += created;
+        self.stats.pools = self.pool_count();
+    }
+
+    fn pool_count(&self) -> u32 {
+        let frame_pools: usize = self.frames.iter().map(|frame| frame.chain.len()).sum();
+        (frame_pools + self.persistent.len()) as u32
+    }
+
+    /// Pending writes into descriptor sets which are issued by [`Self::flush_writes`].
+    pub fn writes(&mut self) -> &mut DescriptorWrites {
+        &mut self.writes
+    }
+
+    /// Issues all pending writes with one `vkUpdateDescriptorSets` call.
+    ///
+    /// Must be called before commands which use written sets are submitted.
+    ///
+    pub fn flush_writes(&mut self) {
+        self.stats.writes += self.writes.len() as u32;
+        let resources = self.writes.flush(&self.device);
+        self.frames[self.current].resources.extend(resources);
+    }
+
+    /// Statistics of the current frame.
+    pub fn stats(&self) -> DescriptorStats {
+        self.stats
+    }
+
+    /// Destroys all pools, so all allocated sets become invalid.
+    pub(crate) fn clear(&mut self) {
+        self.writes = DescriptorWrites::default();
+        for frame in &mut self.frames {
+            frame.chain.clear();
+            frame.resources.clear();
+        }
+        self.persistent.clear();
+        self.stats = DescriptorStats::default();
+    }
+}
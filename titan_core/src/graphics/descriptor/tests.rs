@@ -0,0 +1,237 @@
+#![cfg(test)]
+
+use std::cell::Cell;
+
+use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{DeviceExtensions, Features};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::pipeline::ComputePipeline;
+use vulkano::Version;
+
+use super::*;
+
+/// Pool which can hold given count of sets.
+struct FakePool {
+    capacity: u32,
+    allocated: Cell<u32>,
+}
+
+impl FakePool {
+    fn new(capacity: u32) -> Self {
+        Self {
+            capacity,
+            allocated: Cell::new(0),
+        }
+    }
+
+    fn allocate(&self, size: u32) -> Result<u32, vk::Result> {
+        let allocated = self.allocated.get() + size;
+        if allocated > self.capacity {
+            return Err(vk::Result::ERROR_OUT_OF_POOL_MEMORY);
+        }
+        self.allocated.set(allocated);
+        Ok(allocated)
+    }
+
+    fn reset(&self) -> Result<(), vk::Result> {
+        self.allocated.set(0);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pool_chain_growth() {
+    let mut chain = PoolChain::new();
+    let mut created = 0;
+    for _ in 0..10 {
+        let (_, new_pools) = chain
+            .allocate(|| Ok(FakePool::new(4)), |pool| pool.allocate(1))
+            .unwrap();
+        created += new_pools;
+    }
+    assert_eq!(created, 3);
+    assert_eq!(chain.len(), 3);
+
+    // Pools are reused after reset, so the chain does not grow for the same demand.
+    for _ in 0..100 {
+        chain.reset(FakePool::reset).unwrap();
+        for _ in 0..10 {
+            let (_, new_pools) = chain
+                .allocate(|| Ok(FakePool::new(4)), |pool| pool.allocate(1))
+                .unwrap();
+            assert_eq!(new_pools, 0);
+        }
+    }
+    assert_eq!(chain.len(), 3);
+}
+
+#[test]
+fn test_pool_chain_errors() {
+    let mut chain = PoolChain::new();
+    let result = chain.allocate(|| Ok(FakePool::new(4)), |pool| pool.allocate(5));
+    assert_eq!(result.unwrap_err(), ChainError::TooLarge);
+
+    let mut chain = PoolChain::new();
+    let result = chain.allocate(
+        || Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY),
+        |pool: &FakePool| pool.allocate(1),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        ChainError::PoolCreation(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY),
+    );
+
+    // Fragmentation moves to the next pool just like exhaustion does.
+    let mut chain = PoolChain::new();
+    chain
+        .allocate(|| Ok(FakePool::new(4)), |pool| pool.allocate(1))
+        .unwrap();
+    let mut fragmented = true;
+    let (_, created) = chain
+        .allocate(
+            || Ok(FakePool::new(4)),
+            |pool| match std::mem::take(&mut fragmented) {
+                true => Err(vk::Result::ERROR_FRAGMENTED_POOL),
+                false => pool.allocate(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(created, 1);
+
+    let result = chain.allocate(
+        || Ok(FakePool::new(4)),
+        |_| Err::<u32, _>(vk::Result::ERROR_OUT_OF_HOST_MEMORY),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        ChainError::Allocation(vk::Result::ERROR_OUT_OF_HOST_MEMORY),
+    );
+}
+
+#[test]
+fn test_layout_builder() {
+    let stages = vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT;
+    let builder = DescriptorLayoutBuilder::new()
+        .uniform_buffer(0, stages)
+        .combined_image_sampler(1, vk::ShaderStageFlags::FRAGMENT)
+        .storage_buffer(2, vk::ShaderStageFlags::COMPUTE)
+        .binding(DescriptorBinding {
+            binding: 3,
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            count: 4,
+            stages: vk::ShaderStageFlags::FRAGMENT,
+        });
+    assert_eq!(builder.validate(), Ok(()));
+    assert_eq!(
+        layout::pool_sizes(builder.bindings(), 8),
+        DescriptorPoolSizes {
+            max_sets: 8,
+            descriptors: vec![
+                (vk::DescriptorType::UNIFORM_BUFFER, 8),
+                (vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 40),
+                (vk::DescriptorType::STORAGE_BUFFER, 8),
+            ],
+        },
+    );
+
+    let duplicate = builder.clone().uniform_buffer(1, stages);
+    assert_eq!(
+        duplicate.validate(),
+        Err(DescriptorLayoutError::DuplicateBinding(1)),
+    );
+    let unused = builder
+        .clone()
+        .storage_buffer(4, vk::ShaderStageFlags::empty());
+    assert_eq!(unused.validate(), Err(DescriptorLayoutError::NoStages(4)));
+    let empty = builder.binding(DescriptorBinding {
+        binding: 5,
+        ty: vk::DescriptorType::UNIFORM_BUFFER,
+        count: 0,
+        stages,
+    });
+    assert_eq!(empty.validate(), Err(DescriptorLayoutError::Empty(5)));
+}
+
+/// Allocates and writes many sets in each frame, checking that pools stop growing.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_allocator_stress() {
+    const FRAMES_IN_FLIGHT: usize = 3;
+    const FRAMES: usize = 100;
+    const SETS_PER_FRAME: u32 = 10_000;
+
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device
+        .queue_families()
+        .find(|family| family.supports_compute())
+        .unwrap();
+    let (device, _) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+
+    // Layout of particle simulation has three storage buffers.
+    let pipeline = {
+        use crate::graphics::shader::particles::compute;
+
+        let shader_module = compute::Shader::load(device.clone()).unwrap();
+        ComputePipeline::new(
+            device.clone(),
+            &shader_module.main_entry_point(),
+            &(),
+            None,
+            |_| {},
+        )
+        .unwrap()
+    };
+    let layout = pipeline.layout().descriptor_set_layouts()[0].clone();
+    let usage = BufferUsage {
+        storage_buffer: true,
+        ..BufferUsage::none()
+    };
+    let buffer =
+        DeviceLocalBuffer::<[[f32; 8]]>::array(device.clone(), 16, usage, [queue_family]).unwrap();
+
+    let mut allocator =
+        DescriptorAllocator::new(device, FRAMES_IN_FLIGHT, DescriptorPoolSizes::default());
+    allocator.allocate_persistent(&*layout).unwrap();
+    let mut pools = 0;
+    for frame in 0..FRAMES {
+        // Safety: no commands use the sets, so frames are finished immediately.
+        unsafe { allocator.begin_frame(frame % FRAMES_IN_FLIGHT).unwrap() };
+        for _ in 0..SETS_PER_FRAME {
+            let set = allocator.allocate(&*layout).unwrap();
+            for binding in 0..3 {
+                let ty = vk::DescriptorType::STORAGE_BUFFER;
+                allocator
+                    .writes()
+                    .buffer(set, binding, 0, ty, buffer.clone());
+            }
+        }
+        allocator.flush_writes();
+
+        let stats = allocator.stats();
+        assert_eq!(stats.sets_allocated, SETS_PER_FRAME);
+        assert_eq!(stats.writes, SETS_PER_FRAME * 3);
+        // Each frame in flight creates its pools once, then only reuses them.
+        if frame < FRAMES_IN_FLIGHT {
+            assert!(stats.pools_created > 0);
+        } else {
+            assert_eq!(stats.pools_created, 0);
+            assert_eq!(stats.pools, pools);
+        }
+        pools = stats.pools;
+    }
+    // Written buffers are kept alive until their frame in flight begins again.
+    let writes = FRAMES_IN_FLIGHT * 3 * SETS_PER_FRAME as usize;
+    assert_eq!(Arc::strong_count(&buffer), 1 + writes);
+
+    allocator.clear();
+    assert_eq!(Arc::strong_count(&buffer), 1);
+}
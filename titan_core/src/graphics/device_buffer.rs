@@ -0,0 +1,125 @@
+//! Buffers in device local memory which is placed into blocks of [`MemoryAllocator`].
+
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::sys::{BufferCreationError, UnsafeBuffer};
+use vulkano::buffer::{BufferAccess, BufferInner, BufferUsage, TypedBufferAccess};
+use vulkano::device::physical::QueueFamily;
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::sync::{AccessError, Sharing};
+use vulkano::{DeviceSize, OomError};
+
+use super::allocator::{MemoryAllocError, MemoryAllocation, MemoryAllocator};
+
+#[derive(Debug, Error)]
+pub enum DeviceBufferCreationError {
+    #[error("device buffer must contain at least one element")]
+    Empty,
+
+    #[error("buffer creation failure: {0}")]
+    Creation(#[from] BufferCreationError),
+
+    #[error("buffer memory allocation failure: {0}")]
+    Allocation(#[from] MemoryAllocError),
+
+    #[error("failed to bind memory to the buffer: {0}")]
+    Bind(#[from] OomError),
+}
+
+/// Buffer of `T` elements in device local memory of the renderer.
+///
+/// Memory of the buffer is counted by [`Renderer::memory_stats`](super::Renderer::memory_stats).
+///
+/// Access to the buffer is not tracked: commands which write the buffer must be waited for
+/// by commands which read it (for example, uploads are waited for by the next rendered frame).
+///
+pub struct DeviceBuffer<T> {
+    // Buffer is destroyed before its memory is returned to the allocator.
+    inner: UnsafeBuffer,
+    _allocation: MemoryAllocation,
+    marker: PhantomData<T>,
+}
+
+impl<T> DeviceBuffer<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Creates buffer of `len` elements which is shared between given queue families.
+    pub(crate) fn new<'a>(
+        allocator: &Arc<MemoryAllocator>,
+        usage: BufferUsage,
+        len: usize,
+        queue_families: impl IntoIterator<Item = QueueFamily<'a>>,
+    ) -> Result<Arc<Self>, DeviceBufferCreationError> {
+        if len == 0 {
+            return Err(DeviceBufferCreationError::Empty);
+        }
+        let size = (len * size_of::<T>()) as DeviceSize;
+        let queue_families: Vec<_> = queue_families
+            .into_iter()
+            .map(|family| family.id())
+            .collect();
+        let (inner, requirements) = unsafe {
+            let sharing = if queue_families.len() > 1 {
+                Sharing::Concurrent(queue_families.iter().copied())
+            } else {
+                Sharing::Exclusive
+            };
+            UnsafeBuffer::new(allocator.device().clone(), size, usage, sharing, None)?
+        };
+
+        let allocation = allocator.allocate_device_local(&requirements)?;
+        unsafe { inner.bind_memory(allocation.memory(), allocation.offset())? };
+
+        Ok(Arc::new(Self {
+            inner,
+            _allocation: allocation,
+            marker: PhantomData,
+        }))
+    }
+}
+
+unsafe impl<T> BufferAccess for DeviceBuffer<T>
+where
+    T: Send + Sync,
+{
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+
+    fn conflict_key(&self) -> (u64, u64) {
+        (self.inner.key(), 0)
+    }
+
+    fn try_gpu_lock(&self, _: bool, _: &Queue) -> Result<(), AccessError> {
+        // Access is synchronized by the owner of the buffer (see docs of the buffer).
+        Ok(())
+    }
+
+    unsafe fn increase_gpu_lock(&self) {}
+
+    unsafe fn unlock(&self) {}
+}
+
+unsafe impl<T> TypedBufferAccess for DeviceBuffer<T>
+where
+    T: Send + Sync,
+{
+    type Content = [T];
+}
+
+unsafe impl<T> DeviceOwned for DeviceBuffer<T> {
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
@@ -0,0 +1,369 @@
+//! Immediate-mode submission of draws which does not require ECS.
+//!
+//! Draws are submitted each frame (for example, while handling update event)
+//! and drawn after draws of the scene, then the list is cleared.
+//! Opaque draws are sorted front to back to reduce overdraw,
+//! transparent ones are sorted back to front to be blended correctly.
+//!
+//! Many instances of the same mesh could be submitted at once as the instanced draw,
+//! which is drawn with one command after other draws. Instances are not sorted.
+
+use std::mem;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+use ultraviolet::Mat4;
+
+use crate::asset::{MeshSource, Primitive};
+use crate::graphics::clip::{ClipRect, ClipStack};
+use crate::graphics::specialization::SpecializationInfo;
+use crate::math::Color;
+
+mod tests;
+
+/// Draw of the mesh which is submitted for one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawCommand {
+    /// Mesh to be drawn. Only built-in primitives could be drawn for now.
+    pub mesh: MeshSource,
+    /// Color which multiplies colors of the mesh.
+    ///
+    /// Draw is transparent if alpha of the color is less than 1.
+    pub color: Color,
+    /// Homogeneous matrix which transforms the mesh into the world.
+    pub transform: Mat4,
+    /// Value added to the distance from the camera when draws are sorted.
+    pub sort_bias: f32,
+    /// Rectangle which the draw is clipped by, if any.
+    ///
+    /// When the draw is submitted, it is intersected with the rectangle
+    /// of [`ClipStack`] of the queue.
+    ///
+    pub clip: Option<ClipRect>,
+    /// Specialization constants of the default shaders which the draw is drawn with.
+    ///
+    /// Draws with different constants are drawn with different pipelines,
+    /// which are created when they are drawn for the first time.
+    ///
+    pub specialization: SpecializationInfo,
+}
+
+impl DrawCommand {
+    /// Creates white draw of the mesh with given transform.
+    pub fn new(mesh: impl Into<MeshSource>, transform: Mat4) -> Self {
+        Self {
+            mesh: mesh.into(),
+            color: Color::WHITE,
+            transform,
+            sort_bias: 0.0,
+            clip: None,
+            specialization: SpecializationInfo::new(),
+        }
+    }
+
+    /// Returns draw with given color.
+    pub fn with_color(self, color: impl Into<Color>) -> Self {
+        let color = color.into();
+        Self { color, ..self }
+    }
+
+    /// Returns draw with given sort bias.
+    pub fn with_sort_bias(self, sort_bias: f32) -> Self {
+        Self { sort_bias, ..self }
+    }
+
+    /// Returns draw which is clipped by given rectangle.
+    pub fn with_clip(self, clip: ClipRect) -> Self {
+        let clip = Some(clip);
+        Self { clip, ..self }
+    }
+
+    /// Returns draw with given specialization constants
+    /// (for example, [`SWAP_RED_BLUE`](crate::graphics::SWAP_RED_BLUE)).
+    pub fn with_specialization(self, specialization: SpecializationInfo) -> Self {
+        Self {
+            specialization,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the draw should be blended with colors behind it.
+    pub fn is_transparent(&self) -> bool {
+        self.color.alpha < 1.0
+    }
+
+    /// Primitive which the draw was validated to have.
+    pub(crate) fn primitive(&self) -> Primitive {
+        match self.mesh {
+            MeshSource::Primitive(primitive) => primitive,
+            MeshSource::File(_) => unreachable!("draws with meshes from files are rejected"),
+        }
+    }
+
+    /// Distance from the camera with given view matrix along its direction.
+    fn sort_key(&self, view: Mat4) -> f32 {
+        let position = view.transform_point3(self.transform.extract_translation());
+        // Camera looks along negative Z axis of the view space.
+        -position.z + self.sort_bias
+    }
+
+    fn validate(&self) -> Result<(), DrawSubmitError> {
+        if let MeshSource::File(_) = &self.mesh {
+            return Err(DrawSubmitError::UnsupportedMesh(self.mesh.clone()));
+        }
+        let finite = self
+            .transform
+            .as_array()
+            .iter()
+            .all(|value| value.is_finite());
+        if !finite || !self.sort_bias.is_finite() {
+            return Err(DrawSubmitError::NonFinite);
+        }
+        Ok(())
+    }
+}
+
+/// Data of one instance of the instanced draw.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InstanceData {
+    /// Homogeneous matrix which transforms the instance of the mesh into the world.
+    pub transform: Mat4,
+    /// Color which multiplies colors of the mesh for this instance.
+    ///
+    /// Instanced draw is transparent if alpha of any of its instances is less than 1.
+    pub color: Color,
+}
+
+impl InstanceData {
+    /// Creates white instance with given transform.
+    pub fn new(transform: Mat4) -> Self {
+        Self {
+            transform,
+            color: Color::WHITE,
+        }
+    }
+
+    /// Returns instance with given color.
+    pub fn with_color(self, color: impl Into<Color>) -> Self {
+        let color = color.into();
+        Self { color, ..self }
+    }
+}
+
+/// Draw of many instances of the mesh which is submitted for one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct InstancedDraw {
+    pub mesh: MeshSource,
+    pub instances: Vec<InstanceData>,
+    /// Rectangle of [`ClipStack`] which the draw is clipped by, if any.
+    pub clip: Option<ClipRect>,
+}
+
+impl InstancedDraw {
+    /// Returns `true` if the draw should be blended with colors behind it.
+    pub fn is_transparent(&self) -> bool {
+        self.instances
+            .iter()
+            .any(|instance| instance.color.alpha < 1.0)
+    }
+
+    /// Primitive which the draw was validated to have.
+    pub fn primitive(&self) -> Primitive {
+        match self.mesh {
+            MeshSource::Primitive(primitive) => primitive,
+            MeshSource::File(_) => unreachable!("draws with meshes from files are rejected"),
+        }
+    }
+
+    fn validate(&self) -> Result<(), DrawSubmitError> {
+        if let MeshSource::File(_) = &self.mesh {
+            return Err(DrawSubmitError::UnsupportedMesh(self.mesh.clone()));
+        }
+        let finite = self
+            .instances
+            .iter()
+            .flat_map(|instance| instance.transform.as_array())
+            .all(|value| value.is_finite());
+        if !finite {
+            return Err(DrawSubmitError::NonFinite);
+        }
+        Ok(())
+    }
+}
+
+/// Error that can happen when draw is submitted.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DrawSubmitError {
+    #[error("mesh \"{0}\" could not be drawn (only built-in primitives are uploaded)")]
+    UnsupportedMesh(MeshSource),
+
+    #[error("transform or sort bias of the draw is not finite")]
+    NonFinite,
+}
+
+#[derive(Debug, Default)]
+struct DrawState {
+    commands: Vec<DrawCommand>,
+    instanced: Vec<InstancedDraw>,
+    recording: bool,
+}
+
+/// Handle which submits draws to be drawn in the next frame.
+///
+/// Clones share one queue and its [`ClipStack`], so draws submitted through any of them
+/// are drawn in order of submission.
+#[derive(Debug, Default, Clone)]
+pub struct DrawQueue {
+    state: Arc<Mutex<DrawState>>,
+    clip: ClipStack,
+}
+
+impl DrawQueue {
+    /// Creates queue which draws are clipped by rectangles of given stack.
+    pub(crate) fn with_clip_stack(clip: ClipStack) -> Self {
+        Self {
+            state: Default::default(),
+            clip,
+        }
+    }
+
+    /// Stack of rectangles which clip draws submitted to the queue.
+    pub fn clip_stack(&self) -> &ClipStack {
+        &self.clip
+    }
+
+    /// Submits draw to be drawn in the next frame.
+    ///
+    /// If recording of the frame has already started, draw is drawn in the frame after it.
+    /// Draw is clipped by the current rectangle of [`ClipStack`]; if it clips everything,
+    /// draw is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned if the mesh could not be drawn or the draw has non-finite values.
+    ///
+    pub fn submit(&self, mut command: DrawCommand) -> Result<(), DrawSubmitError> {
+        command.validate()?;
+        command.clip = self.clip.clip(command.clip);
+        if matches!(command.clip, Some(clip) if clip.is_empty()) {
+            log::trace!("draw was skipped because its clip rectangle is empty");
+            return Ok(());
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.recording {
+            log::debug!(
+                "draw was submitted while the frame is recorded, it is queued for the next frame"
+            );
+        }
+        state.commands.push(command);
+        Ok(())
+    }
+
+    /// Submits draw of many instances of the mesh to be drawn in the next frame.
+    ///
+    /// All instances are drawn with one command after other draws of the frame,
+    /// so they are neither sorted nor clipped one by one: the whole draw is clipped
+    /// by the current rectangle of [`ClipStack`]. Empty list of instances is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned if the mesh could not be drawn
+    /// or any instance has non-finite transform.
+    ///
+    pub fn submit_instanced(
+        &self,
+        mesh: impl Into<MeshSource>,
+        instances: &[InstanceData],
+    ) -> Result<(), DrawSubmitError> {
+        let draw = InstancedDraw {
+            mesh: mesh.into(),
+            instances: instances.to_vec(),
+            clip: self.clip.clip(None),
+        };
+        draw.validate()?;
+        if draw.instances.is_empty() || matches!(draw.clip, Some(clip) if clip.is_empty()) {
+            log::trace!("instanced draw was skipped because it has nothing to draw");
+            return Ok(());
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.recording {
+            log::debug!(
+                "instanced draw was submitted while the frame is recorded, it is queued for the next frame"
+            );
+        }
+        state.instanced.push(draw);
+        Ok(())
+    }
+
+    /// Count of draws which are queued for the next frame
+    /// (each instanced draw is counted once).
+    pub fn len(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.commands.len() + state.instanced.len()
+    }
+
+    /// Returns `true` if there are no draws queued for the next frame.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes all queued draws to be recorded into the current frame.
+    ///
+    /// Draws submitted until returned list is dropped are queued for the next frame.
+    ///
+    pub(crate) fn begin_frame(&self) -> FrameDraws {
+        let mut state = self.state.lock().unwrap();
+        state.recording = true;
+        FrameDraws {
+            commands: mem::take(&mut state.commands),
+            instanced: mem::take(&mut state.instanced),
+            queue: self.clone(),
+        }
+    }
+}
+
+/// Draws which are recorded into the current frame.
+pub(crate) struct FrameDraws {
+    commands: Vec<DrawCommand>,
+    instanced: Vec<InstancedDraw>,
+    queue: DrawQueue,
+}
+
+impl FrameDraws {
+    /// Instanced draws in order of their submission.
+    pub fn instanced(&self) -> &[InstancedDraw] {
+        &self.instanced
+    }
+
+    /// Draws in order in which they should be recorded for the camera with given view matrix:
+    /// opaque ones front to back, then transparent ones back to front.
+    pub fn sorted(&self, view: Mat4) -> Vec<&DrawCommand> {
+        let (mut opaque, mut transparent): (Vec<_>, Vec<_>) = self
+            .commands
+            .iter()
+            .map(|command| (command.sort_key(view), command))
+            .partition(|(_, command)| !command.is_transparent());
+        opaque.sort_by(|a, b| a.0.total_cmp(&b.0));
+        transparent.sort_by(|a, b| b.0.total_cmp(&a.0));
+        opaque
+            .into_iter()
+            .chain(transparent)
+            .map(|(_, command)| command)
+            .collect()
+    }
+}
+
+impl Deref for FrameDraws {
+    type Target = [DrawCommand];
+
+    fn deref(&self) -> &Self::Target {
+        &self.commands
+    }
+}
+
+impl Drop for FrameDraws {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().recording = false;
+    }
+}
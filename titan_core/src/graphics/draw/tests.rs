@@ -0,0 +1,144 @@
+#![cfg(test)]
+
+use ultraviolet::Vec3;
+
+use crate::camera::Camera;
+
+use super::*;
+
+fn cube_at(x: f32) -> DrawCommand {
+    DrawCommand::new(
+        Primitive::Cube,
+        Mat4::from_translation(Vec3::new(x, 0.0, 0.0)),
+    )
+}
+
+#[test]
+fn test_submit_validation() {
+    let queue = DrawQueue::default();
+    assert!(queue.submit(cube_at(1.0)).is_ok());
+
+    let tank = MeshSource::File("tank.obj".into());
+    let error = queue.submit(DrawCommand::new(tank.clone(), Mat4::identity()));
+    assert_eq!(error, Err(DrawSubmitError::UnsupportedMesh(tank)));
+    let error = queue.submit(cube_at(f32::NAN));
+    assert_eq!(error, Err(DrawSubmitError::NonFinite));
+    let error = queue.submit(cube_at(1.0).with_sort_bias(f32::INFINITY));
+    assert_eq!(error, Err(DrawSubmitError::NonFinite));
+    assert_eq!(queue.len(), 1);
+}
+
+#[test]
+fn test_frame_clears_queue() {
+    let queue = DrawQueue::default();
+    queue.submit(cube_at(1.0)).unwrap();
+    queue.submit(cube_at(2.0)).unwrap();
+
+    let draws = queue.begin_frame();
+    assert_eq!(draws.len(), 2);
+    assert!(queue.is_empty());
+    // Draws submitted while the frame is recorded belong to the next frame.
+    queue.submit(cube_at(3.0)).unwrap();
+    assert_eq!(draws.len(), 2);
+    drop(draws);
+
+    let draws = queue.begin_frame();
+    assert_eq!(*draws, [cube_at(3.0)]);
+    drop(draws);
+    assert!(queue.begin_frame().is_empty());
+}
+
+#[test]
+fn test_sorted() {
+    let queue = DrawQueue::default();
+    let glass = |x: f32| cube_at(x).with_color(Color::WHITE.with_alpha(0.5));
+    let draws = [
+        cube_at(5.0),
+        glass(2.0),
+        cube_at(2.0),
+        glass(8.0),
+        // Bias moves the draw behind the farthest opaque one.
+        cube_at(1.0).with_sort_bias(10.0),
+    ];
+    for draw in draws.iter().cloned() {
+        queue.submit(draw).unwrap();
+    }
+
+    // Camera at the origin looks along X axis.
+    let view = Camera::default().view();
+    let frame = queue.begin_frame();
+    let sorted = frame.sorted(view);
+    let expected = [&draws[2], &draws[0], &draws[4], &draws[3], &draws[1]];
+    assert_eq!(sorted, expected);
+
+    // Order is reversed for the camera which looks in the opposite direction.
+    let view = Camera::new(Vec3::new(10.0, 0.0, 0.0), std::f32::consts::PI, 0.0).view();
+    let sorted = frame.sorted(view);
+    let expected = [&draws[0], &draws[2], &draws[4], &draws[1], &draws[3]];
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn test_submit_clipped() {
+    let queue = DrawQueue::default();
+    let stack = queue.clip_stack();
+    stack.push_clip_rect(ClipRect::new([0, 0], [100, 100]));
+    queue.submit(cube_at(1.0)).unwrap();
+    let own = ClipRect::new([50, 0], [100, 10]);
+    queue.submit(cube_at(2.0).with_clip(own)).unwrap();
+    // Draw outside of the current rectangle is skipped entirely.
+    let outside = ClipRect::new([200, 200], [10, 10]);
+    queue.submit(cube_at(3.0).with_clip(outside)).unwrap();
+    stack.pop_clip_rect();
+    queue.submit(cube_at(4.0)).unwrap();
+
+    let draws = queue.begin_frame();
+    let clips: Vec<_> = draws.iter().map(|draw| draw.clip).collect();
+    let expected = [
+        Some(ClipRect::new([0, 0], [100, 100])),
+        Some(ClipRect::new([50, 0], [50, 10])),
+        None,
+    ];
+    assert_eq!(clips, expected);
+}
+
+#[test]
+fn test_submit_instanced() {
+    let queue = DrawQueue::default();
+    let instances: Vec<_> = (0..3)
+        .map(|x| InstanceData::new(Mat4::from_translation(Vec3::new(x as f32, 0.0, 0.0))))
+        .collect();
+    queue.submit_instanced(Primitive::Cube, &instances).unwrap();
+    queue.submit(cube_at(1.0)).unwrap();
+    // Instanced draw is counted once.
+    assert_eq!(queue.len(), 2);
+
+    let tank = MeshSource::File("tank.obj".into());
+    let error = queue.submit_instanced(tank.clone(), &instances);
+    assert_eq!(error, Err(DrawSubmitError::UnsupportedMesh(tank)));
+    let broken = [InstanceData::new(Mat4::from_scale(f32::NAN))];
+    let error = queue.submit_instanced(Primitive::Quad, &broken);
+    assert_eq!(error, Err(DrawSubmitError::NonFinite));
+    // Draw without instances is skipped.
+    queue.submit_instanced(Primitive::Quad, &[]).unwrap();
+    assert_eq!(queue.len(), 2);
+
+    let stack = queue.clip_stack();
+    stack.push_clip_rect(ClipRect::new([0, 0], [100, 100]));
+    let glass = [InstanceData::new(Mat4::identity()).with_color(Color::WHITE.with_alpha(0.5))];
+    queue.submit_instanced(Primitive::Triangle, &glass).unwrap();
+    stack.pop_clip_rect();
+
+    let draws = queue.begin_frame();
+    assert_eq!(draws.len(), 1);
+    let instanced = draws.instanced();
+    assert_eq!(instanced.len(), 2);
+    assert_eq!(instanced[0].instances, instances);
+    assert_eq!(instanced[0].clip, None);
+    assert!(!instanced[0].is_transparent());
+    assert_eq!(instanced[1].primitive(), Primitive::Triangle);
+    assert_eq!(instanced[1].clip, Some(ClipRect::new([0, 0], [100, 100])));
+    assert!(instanced[1].is_transparent());
+    drop(draws);
+    assert!(queue.begin_frame().instanced().is_empty());
+}
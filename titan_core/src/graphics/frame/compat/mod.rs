@@ -0,0 +1,147 @@
+//! Compatibility checks between render pass, its framebuffers and pipelines.
+//!
+//! Mismatches are reported with typed errors before any command is recorded,
+//! instead of validation layer messages or undefined behavior at draw time.
+
+use std::fmt;
+
+use thiserror::Error;
+use vulkano::format::Format;
+use vulkano::image::{ImageAccess, SampleCount};
+use vulkano::render_pass::RenderPass;
+
+mod tests;
+
+/// Format and sample count of the attachment of render pass or framebuffer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AttachmentInfo {
+    pub format: Format,
+    pub samples: SampleCount,
+}
+
+impl AttachmentInfo {
+    /// Info of the image which is attached to the framebuffer.
+    pub fn of_image<I>(image: &I) -> Self
+    where
+        I: ImageAccess + ?Sized,
+    {
+        Self {
+            format: image.format(),
+            samples: image.samples(),
+        }
+    }
+}
+
+impl fmt::Display for AttachmentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} with {:?}", self.format, self.samples)
+    }
+}
+
+#[derive(Debug, Error, Copy, Clone, PartialEq)]
+pub enum CompatibilityError {
+    #[error("render pass has {expected} attachment(s), but framebuffer has {actual}")]
+    AttachmentCount { expected: usize, actual: usize },
+
+    #[error("attachment {index} of framebuffer is {actual}, but render pass expects {expected}")]
+    IncompatibleAttachment {
+        index: usize,
+        expected: AttachmentInfo,
+        actual: AttachmentInfo,
+    },
+
+    #[error("render pass has no subpass {index} (there are {count} subpasses)")]
+    SubpassNotFound { index: u32, count: usize },
+
+    #[error("subpass {subpass} has {expected} color attachment(s), but pipeline blends {actual}")]
+    BlendAttachmentCount {
+        subpass: u32,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+/// Description of the render pass which is retained after its creation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderPassInfo {
+    /// Attachments of the render pass in order of their creation.
+    pub attachments: Vec<AttachmentInfo>,
+    /// Count of color attachments of each subpass.
+    pub color_attachments: Vec<usize>,
+}
+
+impl RenderPassInfo {
+    /// Retrieves description of the render pass.
+    pub fn new(render_pass: &RenderPass) -> Self {
+        let desc = render_pass.desc();
+        let attachments = desc
+            .attachments()
+            .iter()
+            .map(|attachment| AttachmentInfo {
+                format: attachment.format,
+                samples: attachment.samples,
+            })
+            .collect();
+        let color_attachments = desc
+            .subpasses()
+            .iter()
+            .map(|subpass| subpass.color_attachments.len())
+            .collect();
+        Self {
+            attachments,
+            color_attachments,
+        }
+    }
+
+    /// Checks that images attached to the framebuffer match attachments of the render pass.
+    pub fn check_framebuffer(&self, images: &[AttachmentInfo]) -> Result<(), CompatibilityError> {
+        if self.attachments.len() != images.len() {
+            return Err(CompatibilityError::AttachmentCount {
+                expected: self.attachments.len(),
+                actual: images.len(),
+            });
+        }
+        let mismatch = self
+            .attachments
+            .iter()
+            .zip(images)
+            .enumerate()
+            .find(|(_, (expected, actual))| expected != actual);
+        match mismatch {
+            Some((index, (&expected, &actual))) => {
+                Err(CompatibilityError::IncompatibleAttachment {
+                    index,
+                    expected,
+                    actual,
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the pipeline could be created for the subpass with given index.
+    ///
+    /// Count of blend attachments should be provided only if blending is set
+    /// for each color attachment separately.
+    ///
+    pub fn check_pipeline(
+        &self,
+        subpass: u32,
+        blend_attachments: Option<usize>,
+    ) -> Result<(), CompatibilityError> {
+        let expected = *self.color_attachments.get(subpass as usize).ok_or(
+            CompatibilityError::SubpassNotFound {
+                index: subpass,
+                count: self.color_attachments.len(),
+            },
+        )?;
+        match blend_attachments {
+            Some(actual) if actual != expected => Err(CompatibilityError::BlendAttachmentCount {
+                subpass,
+                expected,
+                actual,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(test)]
+
+use super::*;
+
+fn attachment(format: Format) -> AttachmentInfo {
+    AttachmentInfo {
+        format,
+        samples: SampleCount::Sample1,
+    }
+}
+
+/// Description of render pass which is used by the frame system.
+fn render_pass() -> RenderPassInfo {
+    RenderPassInfo {
+        attachments: vec![
+            attachment(Format::B8G8R8A8_SRGB),
+            attachment(Format::D32_SFLOAT),
+        ],
+        color_attachments: vec![1, 1],
+    }
+}
+
+#[test]
+fn test_compatible_framebuffer() {
+    let images = [
+        attachment(Format::B8G8R8A8_SRGB),
+        attachment(Format::D32_SFLOAT),
+    ];
+    assert_eq!(render_pass().check_framebuffer(&images), Ok(()));
+}
+
+#[test]
+fn test_wrong_format() {
+    let images = [
+        attachment(Format::R8G8B8A8_UNORM),
+        attachment(Format::D32_SFLOAT),
+    ];
+    assert_eq!(
+        render_pass().check_framebuffer(&images),
+        Err(CompatibilityError::IncompatibleAttachment {
+            index: 0,
+            expected: attachment(Format::B8G8R8A8_SRGB),
+            actual: attachment(Format::R8G8B8A8_UNORM),
+        }),
+    );
+}
+
+#[test]
+fn test_wrong_samples() {
+    let multisampled = AttachmentInfo {
+        samples: SampleCount::Sample4,
+        ..attachment(Format::D32_SFLOAT)
+    };
+    let images = [attachment(Format::B8G8R8A8_SRGB), multisampled];
+    assert_eq!(
+        render_pass().check_framebuffer(&images),
+        Err(CompatibilityError::IncompatibleAttachment {
+            index: 1,
+            expected: attachment(Format::D32_SFLOAT),
+            actual: multisampled,
+        }),
+    );
+}
+
+#[test]
+fn test_wrong_attachment_count() {
+    let images = [attachment(Format::B8G8R8A8_SRGB)];
+    assert_eq!(
+        render_pass().check_framebuffer(&images),
+        Err(CompatibilityError::AttachmentCount {
+            expected: 2,
+            actual: 1,
+        }),
+    );
+}
+
+#[test]
+fn test_pipeline() {
+    let render_pass = render_pass();
+    assert_eq!(render_pass.check_pipeline(1, None), Ok(()));
+    assert_eq!(render_pass.check_pipeline(0, Some(1)), Ok(()));
+    assert_eq!(
+        render_pass.check_pipeline(2, None),
+        Err(CompatibilityError::SubpassNotFound { index: 2, count: 2 }),
+    );
+    assert_eq!(
+        render_pass.check_pipeline(0, Some(2)),
+        Err(CompatibilityError::BlendAttachmentCount {
+            subpass: 0,
+            expected: 1,
+            actual: 2,
+        }),
+    );
+}
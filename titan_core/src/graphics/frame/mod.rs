@@ -1,3 +1,5 @@
+pub mod compat;
 pub mod object_draw;
+pub mod post_process;
 pub mod system;
 pub mod ui_draw;
@@ -1,11 +1,15 @@
 use thiserror::Error;
-use vulkano::command_buffer::{BuildError, DrawIndexedError};
+use vulkano::command_buffer::{BuildError, DrawIndexedError, DrawIndexedIndirectError};
 use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::pipeline::GraphicsPipelineCreationError;
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
-use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::{
+    index::IndexBufferError, indirect::IndirectDrawError,
+    renderer::error::DescriptorSetCreationError, specialization::SpecializationError,
+    TransientWriteError,
+};
 
 #[derive(Debug, Error)]
 pub enum ObjectDrawSystemCreationError {
@@ -18,11 +22,17 @@ pub enum ObjectDrawSystemCreationError {
     #[error("graphics pipeline creation failure: {0}")]
     GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
 
+    #[error("invalid specialization constants: {0}")]
+    Specialization(#[from] SpecializationError),
+
     #[error("vertex/index buffer creation failure: {0}")]
     BufferCreation(#[from] FlushError),
 
     #[error("vertex/index buffer allocation failure: {0}")]
     BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("index buffer creation failure: {0}")]
+    IndexBuffer(#[from] IndexBufferError),
 }
 
 #[derive(Debug, Error)]
@@ -33,9 +43,21 @@ pub enum ObjectDrawError {
     #[error("draw indexed command failure: {0}")]
     DrawIndexed(#[from] DrawIndexedError),
 
+    #[error("draw indexed indirect command failure: {0}")]
+    DrawIndexedIndirect(#[from] DrawIndexedIndirectError),
+
+    #[error("invalid indirect draw: {0}")]
+    IndirectDraw(#[from] IndirectDrawError),
+
+    #[error("instance buffer allocation failure: {0}")]
+    InstanceBufferAllocation(#[from] TransientWriteError),
+
     #[error("uniform buffer descriptor set creation failure: {0}")]
     DescriptorSetCreation(#[from] DescriptorSetCreationError),
 
+    #[error("pipeline creation failure for submitted draws: {0}")]
+    PipelineCreation(#[from] ObjectDrawSystemCreationError),
+
     #[error("draw command buffer build failure: {0}")]
     CommandBufferBuild(#[from] BuildError),
 }
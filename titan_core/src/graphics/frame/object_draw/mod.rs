@@ -1,102 +1,169 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use palette::Srgba;
-use ultraviolet::Vec3;
-use vulkano::buffer::{BufferUsage, ImmutableBuffer, TypedBufferAccess};
-use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
-};
+use ultraviolet::{Mat4, Vec3};
+use vulkano::buffer::{BufferAccess, BufferSlice, BufferUsage, ImmutableBuffer};
+use vulkano::command_buffer::SecondaryAutoCommandBuffer;
 use vulkano::descriptor_set::SingleLayoutDescSetPool;
 use vulkano::device::Queue;
-use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::blend::AttachmentBlend;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::vertex::BuffersDefinition;
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
 use vulkano::sync::GpuFuture;
 
-use crate::{
-    graphics::{
-        camera::CameraUBO,
-        frame::object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
-        renderer::error::DescriptorSetCreationError,
-        vertex::Vertex,
-    },
-    window::Size,
+use crate::asset::Primitive;
+use crate::graphics::{
+    capture::{self, MaterialCapture, SubpassCapture},
+    clip::ClipRect,
+    draw::{DrawCommand, InstancedDraw},
+    frame::object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
+    index::IndexBuffer,
+    indirect::{IndirectBuffer, IndirectDraw},
+    mapped::MappedBuffer,
+    pipeline_stats,
+    renderer::error::DescriptorSetCreationError,
+    sampler::CompareOp,
+    shader::default::vertex::ty::PushConstants,
+    specialization::SpecializationInfo,
+    transient::{TransientBufferPool, TransientUsage},
+    utils,
+    vertex::{InstanceVertex, Vertex},
+    viewport::Region,
 };
+use crate::math::Color;
 
 pub mod error;
 
-const fn indices() -> [u32; 12] {
-    [0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]
+/// Count of indices of the scene, which are followed by indices of built-in primitives.
+const SCENE_INDEX_COUNT: u32 = 12;
+
+const fn indices() -> [u32; 57] {
+    [
+        // Scene
+        0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4, //
+        // Triangle
+        8, 9, 10, //
+        // Quad
+        11, 12, 13, 13, 14, 11, //
+        // Cube
+        19, 20, 21, 21, 22, 19, 15, 18, 17, 17, 16, 15, 15, 16, 20, 20, 19, 15, //
+        17, 18, 22, 22, 21, 17, 16, 17, 21, 21, 20, 16, 18, 15, 19, 19, 22, 18,
+    ]
 }
 
-fn vertices() -> [Vertex; 8] {
+fn vertices() -> [Vertex; 23] {
+    let white = |x, y, z| Vertex::new(Vec3::new(x, y, z), Color::WHITE);
     [
-        Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Srgba::new(1.0, 0.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, -0.5, 0.0), Srgba::new(0.0, 1.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, 0.5, 0.0), Srgba::new(0.0, 0.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, 0.5, 0.0), Srgba::new(1.0, 1.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, -0.5, -0.5), Srgba::new(1.0, 0.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, -0.5, -0.5), Srgba::new(0.0, 1.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, 0.5, -0.5), Srgba::new(0.0, 0.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, 0.5, -0.5), Srgba::new(1.0, 1.0, 1.0, 1.0)),
+        // Scene
+        Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Color::RED),
+        Vertex::new(Vec3::new(0.5, -0.5, 0.0), Color::GREEN),
+        Vertex::new(Vec3::new(0.5, 0.5, 0.0), Color::BLUE),
+        Vertex::new(Vec3::new(-0.5, 0.5, 0.0), Color::WHITE),
+        Vertex::new(Vec3::new(-0.5, -0.5, -0.5), Color::RED),
+        Vertex::new(Vec3::new(0.5, -0.5, -0.5), Color::GREEN),
+        Vertex::new(Vec3::new(0.5, 0.5, -0.5), Color::BLUE),
+        Vertex::new(Vec3::new(-0.5, 0.5, -0.5), Color::WHITE),
+        // Triangle
+        white(-0.5, -0.5, 0.0),
+        white(0.5, -0.5, 0.0),
+        white(0.0, 0.5, 0.0),
+        // Quad
+        white(-0.5, -0.5, 0.0),
+        white(0.5, -0.5, 0.0),
+        white(0.5, 0.5, 0.0),
+        white(-0.5, 0.5, 0.0),
+        // Cube
+        white(-0.5, -0.5, -0.5),
+        white(0.5, -0.5, -0.5),
+        white(0.5, 0.5, -0.5),
+        white(-0.5, 0.5, -0.5),
+        white(-0.5, -0.5, 0.5),
+        white(0.5, -0.5, 0.5),
+        white(0.5, 0.5, 0.5),
+        white(-0.5, 0.5, 0.5),
     ]
 }
 
+/// First index and count of indices of the built-in primitive.
+const fn primitive_indices(primitive: Primitive) -> (u32, u32) {
+    match primitive {
+        Primitive::Triangle => (SCENE_INDEX_COUNT, 3),
+        Primitive::Quad => (SCENE_INDEX_COUNT + 3, 6),
+        Primitive::Cube => (SCENE_INDEX_COUNT + 9, 36),
+    }
+}
+
+/// Blending and specialization constants which the pipeline of submitted draws is created with.
+type PipelineKey = (bool, SpecializationInfo);
+
+/// Instances of the instanced draw which were written into transient buffer of the frame.
+pub(crate) struct InstanceBatch {
+    primitive: Primitive,
+    transparent: bool,
+    count: u32,
+    /// Rectangle which the draw is clipped by, if any.
+    pub clip: Option<ClipRect>,
+    instances: Arc<BufferSlice<[InstanceVertex], Arc<MappedBuffer<u8>>>>,
+}
+
 /// System that contains the necessary facilities for rendering game objects.
 pub struct ObjectDrawSystem {
     /// Queue to render.
     graphics_queue: Arc<Queue>,
 
+    /// Cache which all pipelines are created with.
+    pipeline_cache: Arc<PipelineCache>,
+
+    /// Operator which depth of fragments is compared with by all pipelines.
+    depth_compare: CompareOp,
+
     /// Buffer for all vertices of game objects.
     vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
 
     /// Buffer for all indices of vertices in game object.
-    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    index_buffer: IndexBuffer,
 
     /// Graphics pipeline used for rendering of game objects.
     pipeline: Arc<GraphicsPipeline>,
 
+    /// Graphics pipelines used for rendering of submitted draws
+    /// (transparent or with specialization constants), keyed by their blending and constants.
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipeline>>,
+
+    /// Graphics pipelines used for rendering of instanced draws, keyed by their blending.
+    ///
+    /// They are created when instanced draws are drawn for the first time.
+    ///
+    instanced_pipelines: HashMap<bool, Arc<GraphicsPipeline>>,
+
     /// Pool of descriptor sets of uniform buffers with data for vertex shader.
     descriptor_set_pool: SingleLayoutDescSetPool,
 }
 
 impl ObjectDrawSystem {
-    /// Creates new object draw system.
+    /// Creates new object draw system which creates its pipelines with given cache
+    /// and depth compare operator.
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        pipeline_cache: Arc<PipelineCache>,
+        depth_compare: CompareOp,
     ) -> Result<Self, ObjectDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(ObjectDrawSystemCreationError::QueueFamilyNotSupported);
         }
 
-        let pipeline = {
-            use crate::graphics::shader::default::{fragment, vertex};
-
-            let device = graphics_queue.device().clone();
-
-            let vert_shader_module = vertex::Shader::load(device.clone())?;
-            let frag_shader_module = fragment::Shader::load(device.clone())?;
-
-            Arc::new(
-                GraphicsPipeline::start()
-                    .vertex_input_single_buffer::<Vertex>()
-                    .vertex_shader(vert_shader_module.main_entry_point(), ())
-                    .fragment_shader(frag_shader_module.main_entry_point(), ())
-                    .triangle_list()
-                    .primitive_restart(false)
-                    .viewports_dynamic_scissors_irrelevant(1)
-                    .depth_stencil_simple_depth()
-                    .cull_mode_back()
-                    .render_pass(subpass)
-                    .build(device)?,
-            )
-        };
+        let (pipeline, pipelines) =
+            Self::pipelines(&graphics_queue, subpass, &pipeline_cache, depth_compare)?;
 
+        let vertices = self::vertices();
+        let vertex_count = vertices.len();
         let vertex_buffer = {
             let (vertex_buffer, future) = ImmutableBuffer::from_iter(
-                self::vertices(),
+                vertices,
                 BufferUsage::vertex_buffer(),
                 graphics_queue.clone(),
             )?;
@@ -104,43 +171,286 @@ pub fn new(
             vertex_buffer
         };
 
-        let index_buffer = {
-            let (index_buffer, future) = ImmutableBuffer::from_iter(
-                self::indices(),
-                BufferUsage::index_buffer(),
-                graphics_queue.clone(),
-            )?;
-            future.flush()?;
-            index_buffer
-        };
+        let index_buffer =
+            IndexBuffer::new(graphics_queue.clone(), &self::indices(), vertex_count)?;
 
-        let descriptor_set_pool = {
-            let layout = &pipeline.layout().descriptor_set_layouts()[0];
-            SingleLayoutDescSetPool::new(layout.clone())
-        };
+        let descriptor_set_pool = Self::descriptor_set_pool(&pipeline);
 
         Ok(Self {
             graphics_queue,
+            pipeline_cache,
+            depth_compare,
             vertex_buffer,
             index_buffer,
             pipeline,
+            pipelines,
+            instanced_pipelines: HashMap::new(),
             descriptor_set_pool,
         })
     }
 
-    /// Builds a secondary command buffer that draws game objects on the current subpass.
-    pub fn draw<B>(
+    /// Creates opaque and transparent pipelines without specialization constants.
+    #[allow(clippy::type_complexity)]
+    fn pipelines(
+        graphics_queue: &Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: &Arc<PipelineCache>,
+        depth_compare: CompareOp,
+    ) -> Result<
+        (
+            Arc<GraphicsPipeline>,
+            HashMap<PipelineKey, Arc<GraphicsPipeline>>,
+        ),
+        ObjectDrawSystemCreationError,
+    > {
+        let specialization = SpecializationInfo::new();
+        let pipeline = Self::pipeline(
+            graphics_queue,
+            subpass.clone(),
+            pipeline_cache,
+            depth_compare,
+            false,
+            &specialization,
+        )?;
+        let transparent_pipeline = Self::pipeline(
+            graphics_queue,
+            subpass,
+            pipeline_cache,
+            depth_compare,
+            true,
+            &specialization,
+        )?;
+        let mut pipelines = HashMap::new();
+        pipelines.insert((false, specialization.clone()), pipeline.clone());
+        pipelines.insert((true, specialization), transparent_pipeline);
+        Ok((pipeline, pipelines))
+    }
+
+    fn pipeline(
+        graphics_queue: &Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: &Arc<PipelineCache>,
+        depth_compare: CompareOp,
+        transparent: bool,
+        specialization: &SpecializationInfo,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+        use crate::graphics::shader::default::{fragment, vertex};
+
+        let device = graphics_queue.device().clone();
+
+        let vert_shader_module = vertex::Shader::load(device.clone())?;
+        let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+        let blend = if transparent {
+            AttachmentBlend::alpha_blending()
+        } else {
+            AttachmentBlend::pass_through()
+        };
+
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<Vertex>()
+            .vertex_shader(vert_shader_module.main_entry_point(), ())
+            .fragment_shader(
+                frag_shader_module.main_entry_point(),
+                specialization.apply::<fragment::SpecializationConstants>()?,
+            )
+            .triangle_list()
+            .primitive_restart(false)
+            .viewports_scissors_dynamic(1)
+            .depth_stencil(utils::depth_test(depth_compare))
+            .cull_mode_back()
+            .blend_collective(blend)
+            .render_pass(subpass)
+            .build_with_cache(pipeline_cache.clone())
+            .build(device)?;
+        Ok(Arc::new(pipeline))
+    }
+
+    /// Creates pipeline of instanced draws, which vertices are transformed
+    /// by per-instance attributes of the second vertex buffer.
+    fn instanced_pipeline(
+        graphics_queue: &Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: &Arc<PipelineCache>,
+        depth_compare: CompareOp,
+        transparent: bool,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+        use crate::graphics::shader::default::{fragment, instanced};
+
+        let device = graphics_queue.device().clone();
+
+        let vert_shader_module = instanced::Shader::load(device.clone())?;
+        let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+        let blend = if transparent {
+            AttachmentBlend::alpha_blending()
+        } else {
+            AttachmentBlend::pass_through()
+        };
+        let vertex_input = BuffersDefinition::new()
+            .vertex::<Vertex>()
+            .instance::<InstanceVertex>();
+
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input(vertex_input)
+            .vertex_shader(vert_shader_module.main_entry_point(), ())
+            .fragment_shader(
+                frag_shader_module.main_entry_point(),
+                fragment::SpecializationConstants::default(),
+            )
+            .triangle_list()
+            .primitive_restart(false)
+            .viewports_scissors_dynamic(1)
+            .depth_stencil(utils::depth_test(depth_compare))
+            .cull_mode_back()
+            .blend_collective(blend)
+            .render_pass(subpass)
+            .build_with_cache(pipeline_cache.clone())
+            .build(device)?;
+        Ok(Arc::new(pipeline))
+    }
+
+    fn descriptor_set_pool(pipeline: &GraphicsPipeline) -> SingleLayoutDescSetPool {
+        let layout = &pipeline.layout().descriptor_set_layouts()[0];
+        SingleLayoutDescSetPool::new(layout.clone())
+    }
+
+    /// Recreates graphics pipelines for another subpass
+    /// (e.g. when render pass was recreated with another sample count).
+    ///
+    /// Vertex and index buffers are kept as is. Pipelines with specialization constants
+    /// and pipelines of instanced draws are created again when they are drawn with.
+    ///
+    pub fn set_subpass(&mut self, subpass: Subpass) -> Result<(), ObjectDrawSystemCreationError> {
+        let (pipeline, pipelines) = Self::pipelines(
+            &self.graphics_queue,
+            subpass,
+            &self.pipeline_cache,
+            self.depth_compare,
+        )?;
+        self.descriptor_set_pool = Self::descriptor_set_pool(&pipeline);
+        self.pipeline = pipeline;
+        self.pipelines = pipelines;
+        self.instanced_pipelines.clear();
+        Ok(())
+    }
+
+    /// Operator which depth of fragments is compared with.
+    pub fn depth_compare(&self) -> CompareOp {
+        self.depth_compare
+    }
+
+    /// Recreates graphics pipelines with another depth compare operator.
+    ///
+    /// Depth buffer must be cleared with the farthest depth for the operator
+    /// (see [`FrameSystem::set_depth_compare`](super::system::FrameSystem::set_depth_compare)).
+    ///
+    pub fn set_depth_compare(
+        &mut self,
+        depth_compare: CompareOp,
+    ) -> Result<(), ObjectDrawSystemCreationError> {
+        if self.depth_compare == depth_compare {
+            return Ok(());
+        }
+        self.depth_compare = depth_compare;
+        let subpass = self.pipeline.subpass().clone();
+        self.set_subpass(subpass)
+    }
+
+    /// Returns pipeline of submitted draws with given key, creating it if needed.
+    fn keyed_pipeline(
+        &mut self,
+        key: PipelineKey,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+        if let Some(pipeline) = self.pipelines.get(&key) {
+            return Ok(pipeline.clone());
+        }
+        let subpass = self.pipeline.subpass().clone();
+        let pipeline = Self::pipeline(
+            &self.graphics_queue,
+            subpass,
+            &self.pipeline_cache,
+            self.depth_compare,
+            key.0,
+            &key.1,
+        )?;
+        log::debug!("created object pipeline for specialization {}", key.1);
+        self.pipelines.insert(key, pipeline.clone());
+        Ok(pipeline)
+    }
+
+    /// Returns pipeline of instanced draws with given blending, creating it if needed.
+    fn instanced_pipeline_for(
+        &mut self,
+        transparent: bool,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+        if let Some(pipeline) = self.instanced_pipelines.get(&transparent) {
+            return Ok(pipeline.clone());
+        }
+        let pipeline = Self::instanced_pipeline(
+            &self.graphics_queue,
+            self.pipeline.subpass().clone(),
+            &self.pipeline_cache,
+            self.depth_compare,
+            transparent,
+        )?;
+        self.instanced_pipelines
+            .insert(transparent, pipeline.clone());
+        Ok(pipeline)
+    }
+
+    /// Writes instances of instanced draws into transient buffers of the current frame,
+    /// so they are written once and drawn in each viewport.
+    pub(crate) fn write_instances(
+        transient: &mut TransientBufferPool,
+        draws: &[InstancedDraw],
+    ) -> Result<Vec<InstanceBatch>, ObjectDrawError> {
+        draws
+            .iter()
+            .map(|draw| {
+                let instances: Vec<_> = draw
+                    .instances
+                    .iter()
+                    .copied()
+                    .map(InstanceVertex::from)
+                    .collect();
+                let allocation = transient.write(TransientUsage::Vertex, &instances)?;
+                Ok(InstanceBatch {
+                    primitive: draw.primitive(),
+                    transparent: draw.is_transparent(),
+                    count: instances.len() as u32,
+                    clip: draw.clip,
+                    instances: allocation.slice::<InstanceVertex>(),
+                })
+            })
+            .collect()
+    }
+
+    /// Builds a secondary command buffer that draws game objects
+    /// in the region of the current subpass.
+    ///
+    /// If indirect draw is provided, objects are drawn with commands of its buffer
+    /// (one by one if `multi_draw` is `false`).
+    /// Submitted draws are recorded after objects in given order with their scissors,
+    /// switching to the blending pipeline for transparent ones and to specialized
+    /// pipelines for ones with specialization constants.
+    /// Recorded commands are also described in the capture, if any.
+    ///
+    pub(crate) fn draw<B>(
         &mut self,
-        viewport_size: Size,
+        region: &Region,
         uniform_buffer: Arc<B>,
+        indirect: Option<(&IndirectBuffer, &IndirectDraw)>,
+        multi_draw: bool,
+        draws: &[(&DrawCommand, ClipRect)],
+        mut capture: Option<&mut SubpassCapture>,
     ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError>
     where
-        B: TypedBufferAccess<Content = CameraUBO> + Send + Sync + 'static,
+        B: BufferAccess + Send + Sync + 'static,
     {
-        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+        let mut builder = pipeline_stats::secondary_graphics(
             self.graphics_queue.device().clone(),
             self.graphics_queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
             self.pipeline.subpass().clone(),
         )?;
 
@@ -153,23 +463,185 @@ pub fn draw<B>(
             Arc::new(descriptor_set)
         };
 
-        let viewport = Viewport {
-            origin: [0.0, 0.0],
-            dimensions: [viewport_size.width as f32, viewport_size.height as f32],
-            depth_range: 0.0..1.0,
-        };
+        let push_constants = Self::push_constants(Mat4::identity(), Color::WHITE);
+        let mut current_scissor = ClipRect::from_scissor(&region.scissor());
         builder
-            .set_viewport(0, std::iter::once(viewport))
+            .set_viewport(0, std::iter::once(region.viewport()))
+            .set_scissor(0, std::iter::once(region.scissor()))
             .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_vertex_buffers(0, self.vertex_buffer.clone())
-            .bind_index_buffer(self.index_buffer.clone())
+            .bind_vertex_buffers(0, self.vertex_buffer.clone());
+        self.index_buffer
+            .bind(&mut builder)
             .bind_descriptor_sets(
                 PipelineBindPoint::Graphics,
                 self.pipeline.layout().clone(),
                 0,
-                descriptor_sets,
+                descriptor_sets.clone(),
             )
-            .draw_indexed(self.index_buffer.len() as u32, 1, 0, 0, 0)?;
+            .push_constants(self.pipeline.layout().clone(), 0, push_constants);
+        let camera = vec![capture::role_name("UniformBuffer", "camera")];
+        if let Some(capture) = capture.as_deref_mut() {
+            capture.set_viewport(region.origin, region.dimensions);
+            capture.bind_pipeline(capture::role_name("GraphicsPipeline", "object"));
+            capture.bind_descriptor_sets(0, camera.clone());
+            capture.push_constants(&push_constants);
+        }
+        match indirect {
+            Some((indirect_buffer, draw)) => {
+                for slice in indirect_buffer.slices(draw, multi_draw)? {
+                    builder.draw_indexed_indirect(slice)?;
+                }
+                if let Some(capture) = capture.as_deref_mut() {
+                    let buffer = capture::key_name("IndirectBufferId", draw.buffer);
+                    capture.draw_indirect(buffer, draw.draw_count);
+                }
+            }
+            None => {
+                builder.draw_indexed(SCENE_INDEX_COUNT, 1, 0, 0, 0)?;
+                if let Some(capture) = capture.as_deref_mut() {
+                    let material = MaterialCapture {
+                        color: Color::WHITE,
+                        transparent: false,
+                    };
+                    let mesh = capture::role_name("Mesh", "scene");
+                    capture.draw(mesh, Some(material), SCENE_INDEX_COUNT, true, 1);
+                }
+            }
+        }
+
+        let mut bound: PipelineKey = (false, SpecializationInfo::new());
+        for &(draw, scissor) in draws {
+            if scissor != current_scissor {
+                current_scissor = scissor;
+                builder.set_scissor(0, std::iter::once(scissor.scissor()));
+            }
+            let transparent = draw.is_transparent();
+            if transparent != bound.0 || draw.specialization != bound.1 {
+                bound = (transparent, draw.specialization.clone());
+                let pipeline = self.keyed_pipeline(bound.clone())?;
+                builder
+                    .bind_pipeline_graphics(pipeline.clone())
+                    .bind_descriptor_sets(
+                        PipelineBindPoint::Graphics,
+                        pipeline.layout().clone(),
+                        0,
+                        descriptor_sets.clone(),
+                    );
+                if let Some(capture) = capture.as_deref_mut() {
+                    let name = if transparent {
+                        "object_transparent"
+                    } else {
+                        "object"
+                    };
+                    let pipeline = if bound.1.is_empty() {
+                        capture::role_name("GraphicsPipeline", name)
+                    } else {
+                        let name = format!("{} {}", name, bound.1);
+                        capture::role_name("GraphicsPipeline", &name)
+                    };
+                    capture.bind_pipeline(pipeline);
+                    capture.bind_descriptor_sets(0, camera.clone());
+                }
+            }
+            let (first_index, index_count) = self::primitive_indices(draw.primitive());
+            let push_constants = Self::push_constants(draw.transform, draw.color);
+            builder
+                .push_constants(self.pipeline.layout().clone(), 0, push_constants)
+                .draw_indexed(index_count, 1, first_index, 0, 0)?;
+            if let Some(capture) = capture.as_deref_mut() {
+                let material = MaterialCapture {
+                    color: draw.color,
+                    transparent: draw.is_transparent(),
+                };
+                let mesh = capture::role_name("Primitive", draw.primitive().name());
+                capture.push_constants(&push_constants);
+                capture.draw(mesh, Some(material), index_count, true, 1);
+            }
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Builds a secondary command buffer that draws instanced draws
+    /// in the region of the current subpass with their scissors.
+    ///
+    /// Each draw is drawn with one command in given order, switching to the blending
+    /// pipeline for transparent ones. Recorded commands are also described in the capture, if any.
+    ///
+    pub(crate) fn draw_instanced<B>(
+        &mut self,
+        region: &Region,
+        uniform_buffer: Arc<B>,
+        batches: &[(&InstanceBatch, ClipRect)],
+        mut capture: Option<&mut SubpassCapture>,
+    ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError>
+    where
+        B: BufferAccess + Send + Sync + 'static,
+    {
+        let mut builder = pipeline_stats::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            self.pipeline.subpass().clone(),
+        )?;
+
+        let descriptor_sets = {
+            let mut builder = self.descriptor_set_pool.next();
+            builder
+                .add_buffer(uniform_buffer)
+                .map_err(DescriptorSetCreationError::from)?;
+            let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            Arc::new(descriptor_set)
+        };
+
+        builder.set_viewport(0, std::iter::once(region.viewport()));
+        self.index_buffer.bind(&mut builder);
+        if let Some(capture) = capture.as_deref_mut() {
+            capture.set_viewport(region.origin, region.dimensions);
+        }
+        let camera = vec![capture::role_name("UniformBuffer", "camera")];
+        let mut bound = None;
+        for &(batch, scissor) in batches {
+            if bound != Some(batch.transparent) {
+                bound = Some(batch.transparent);
+                let pipeline = self.instanced_pipeline_for(batch.transparent)?;
+                builder
+                    .bind_pipeline_graphics(pipeline.clone())
+                    .bind_descriptor_sets(
+                        PipelineBindPoint::Graphics,
+                        pipeline.layout().clone(),
+                        0,
+                        descriptor_sets.clone(),
+                    );
+                if let Some(capture) = capture.as_deref_mut() {
+                    let name = if batch.transparent {
+                        "object_instanced_transparent"
+                    } else {
+                        "object_instanced"
+                    };
+                    capture.bind_pipeline(capture::role_name("GraphicsPipeline", name));
+                    capture.bind_descriptor_sets(0, camera.clone());
+                }
+            }
+            let (first_index, index_count) = self::primitive_indices(batch.primitive);
+            builder
+                .set_scissor(0, std::iter::once(scissor.scissor()))
+                .bind_vertex_buffers(0, (self.vertex_buffer.clone(), batch.instances.clone()))
+                .draw_indexed(index_count, batch.count, first_index, 0, 0)?;
+            if let Some(capture) = capture.as_deref_mut() {
+                let material = MaterialCapture {
+                    color: Color::WHITE,
+                    transparent: batch.transparent,
+                };
+                let mesh = capture::role_name("Primitive", batch.primitive.name());
+                capture.draw(mesh, Some(material), index_count, true, batch.count);
+            }
+        }
         Ok(builder.build()?)
     }
+
+    fn push_constants(model: Mat4, color: Color) -> PushConstants {
+        PushConstants {
+            model: model.into(),
+            color: color.into(),
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use thiserror::Error;
+use vulkano::command_buffer::{BuildError, DrawError};
+use vulkano::image::view::ImageViewCreationError;
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::OomError;
+
+use crate::graphics::renderer::error::DescriptorSetCreationError;
+
+#[derive(Debug, Error)]
+pub enum PostProcessSystemCreationError {
+    #[error("shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("queue family must support graphics operations")]
+    QueueFamilyNotSupported,
+
+    #[error("graphics pipeline creation failure: {0}")]
+    GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
+}
+
+#[derive(Debug, Error)]
+pub enum PostProcessError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("scene texture view creation failure: {0}")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("scene texture descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("scene texture was not set before drawing")]
+    NoInput,
+
+    #[error("draw command failure: {0}")]
+    Draw(#[from] DrawError),
+
+    #[error("post-process command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+}
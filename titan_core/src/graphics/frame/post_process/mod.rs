@@ -0,0 +1,268 @@
+//! Post-processing of the scene which was rendered into offscreen target.
+
+use std::sync::Arc;
+
+use vulkano::command_buffer::SecondaryAutoCommandBuffer;
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::Queue;
+use vulkano::image::view::ImageView;
+use vulkano::image::{AttachmentImage, SampleCount, SampleCounts};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
+use vulkano::render_pass::Subpass;
+use vulkano::sampler::Sampler;
+
+use crate::graphics::{
+    capture::{self, SubpassCapture},
+    frame::post_process::error::{PostProcessError, PostProcessSystemCreationError},
+    pipeline_stats,
+    renderer::error::DescriptorSetCreationError,
+};
+
+pub mod error;
+
+mod tests;
+
+/// All sample counts in ascending order.
+const SAMPLE_COUNTS: [SampleCount; 7] = [
+    SampleCount::Sample1,
+    SampleCount::Sample2,
+    SampleCount::Sample4,
+    SampleCount::Sample8,
+    SampleCount::Sample16,
+    SampleCount::Sample32,
+    SampleCount::Sample64,
+];
+
+/// Antialiasing mode of the scene.
+///
+/// UI is never antialiased, because it is drawn over the final image.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum AaMode {
+    /// Scene is rendered directly into the final image.
+    #[default]
+    Off,
+    /// Scene is rendered into multisampled target
+    /// which is resolved and copied into the final image.
+    Msaa(SampleCount),
+    /// Scene is rendered into offscreen target which is smoothed
+    /// by fast approximate antialiasing (FXAA) into the final image.
+    Fxaa,
+}
+
+impl AaMode {
+    /// Multisampling with one sample is the same as no antialiasing.
+    pub fn normalized(self) -> Self {
+        match self {
+            Self::Msaa(SampleCount::Sample1) => Self::Off,
+            mode => mode,
+        }
+    }
+
+    /// Sample count of attachments which the scene is rendered into.
+    pub fn samples(self) -> SampleCount {
+        match self {
+            Self::Msaa(samples) => samples,
+            Self::Off | Self::Fxaa => SampleCount::Sample1,
+        }
+    }
+
+    /// Filter which post-processes the scene into the final image,
+    /// or `None` if the scene is rendered into the final image directly.
+    pub(crate) fn filter(self) -> Option<PostFilter> {
+        match self.normalized() {
+            Self::Off => None,
+            Self::Msaa(_) => Some(PostFilter::Copy),
+            Self::Fxaa => Some(PostFilter::Fxaa),
+        }
+    }
+
+    /// Checks if the mode is supported by the physical device.
+    ///
+    /// Sample count of multisampling must be supported both for color and depth attachments.
+    ///
+    pub fn is_supported(self, physical_device: PhysicalDevice) -> bool {
+        let properties = physical_device.properties();
+        let samples = self.samples();
+        self::contains(&properties.framebuffer_color_sample_counts, samples)
+            && self::contains(&properties.framebuffer_depth_sample_counts, samples)
+    }
+
+    /// Returns the mode which is supported by the physical device.
+    ///
+    /// Sample count of multisampling is clamped to the highest count which is
+    /// not greater than requested and is supported both for color and depth attachments.
+    ///
+    pub fn clamped(self, physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        self.clamped_to(
+            &properties.framebuffer_color_sample_counts,
+            &properties.framebuffer_depth_sample_counts,
+        )
+    }
+
+    fn clamped_to(self, color: &SampleCounts, depth: &SampleCounts) -> Self {
+        let samples = match self.normalized() {
+            Self::Msaa(samples) => samples,
+            mode => return mode,
+        };
+        let requested = SAMPLE_COUNTS.iter().position(|&count| count == samples);
+        let samples = SAMPLE_COUNTS[..=requested.unwrap_or_default()]
+            .iter()
+            .rev()
+            .copied()
+            .find(|&count| self::contains(color, count) && self::contains(depth, count))
+            .unwrap_or(SampleCount::Sample1);
+        Self::Msaa(samples).normalized()
+    }
+}
+
+/// Checks if the set of sample counts contains given sample count.
+fn contains(counts: &SampleCounts, samples: SampleCount) -> bool {
+    match samples {
+        SampleCount::Sample1 => counts.sample1,
+        SampleCount::Sample2 => counts.sample2,
+        SampleCount::Sample4 => counts.sample4,
+        SampleCount::Sample8 => counts.sample8,
+        SampleCount::Sample16 => counts.sample16,
+        SampleCount::Sample32 => counts.sample32,
+        SampleCount::Sample64 => counts.sample64,
+    }
+}
+
+/// Filter which is applied to the scene by post-process pass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum PostFilter {
+    /// Scene is copied as is (it was already resolved if multisampled).
+    Copy,
+    /// Scene is smoothed by FXAA.
+    Fxaa,
+}
+
+/// System that draws the scene texture into the final image with full-screen triangle.
+pub struct PostProcessSystem {
+    /// Queue to render.
+    graphics_queue: Arc<Queue>,
+
+    /// Graphics pipeline which applies the filter.
+    pipeline: Arc<GraphicsPipeline>,
+
+    /// Sampler of the scene texture.
+    sampler: Arc<Sampler>,
+
+    /// Descriptor set of the scene texture, if it was set.
+    descriptor_set: Option<Arc<PersistentDescriptorSet>>,
+}
+
+impl PostProcessSystem {
+    /// Creates new post-process system which applies given filter,
+    /// creating its pipeline with given cache.
+    pub(crate) fn new(
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        filter: PostFilter,
+        sampler: Arc<Sampler>,
+        pipeline_cache: Arc<PipelineCache>,
+    ) -> Result<Self, PostProcessSystemCreationError> {
+        // Check queue for graphics support.
+        if !graphics_queue.family().supports_graphics() {
+            return Err(PostProcessSystemCreationError::QueueFamilyNotSupported);
+        }
+
+        let pipeline = {
+            use crate::graphics::shader::post_process::{copy, fxaa, vertex};
+
+            let device = graphics_queue.device().clone();
+            let vert_shader_module = vertex::Shader::load(device.clone())?;
+            // Full-screen triangle is generated by vertex shader, so there is no vertex input.
+            let builder = GraphicsPipeline::start()
+                .vertex_shader(vert_shader_module.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .render_pass(subpass)
+                .build_with_cache(pipeline_cache);
+            let pipeline = match filter {
+                PostFilter::Copy => {
+                    let frag_shader_module = copy::Shader::load(device.clone())?;
+                    builder
+                        .fragment_shader(frag_shader_module.main_entry_point(), ())
+                        .build(device)?
+                }
+                PostFilter::Fxaa => {
+                    let frag_shader_module = fxaa::Shader::load(device.clone())?;
+                    builder
+                        .fragment_shader(frag_shader_module.main_entry_point(), ())
+                        .build(device)?
+                }
+            };
+            Arc::new(pipeline)
+        };
+
+        Ok(Self {
+            graphics_queue,
+            pipeline,
+            sampler,
+            descriptor_set: None,
+        })
+    }
+
+    /// Sets the texture which the scene was rendered into.
+    ///
+    /// Must be called each time the offscreen target is recreated.
+    ///
+    pub fn set_input(&mut self, texture: Arc<AttachmentImage>) -> Result<(), PostProcessError> {
+        let image_view = ImageView::new(texture)?;
+        let layout = self.pipeline.layout().descriptor_set_layouts()[0].clone();
+        let mut builder = PersistentDescriptorSet::start(layout);
+        builder
+            .add_sampled_image(image_view, self.sampler.clone())
+            .map_err(DescriptorSetCreationError::from)?;
+        let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+        self.descriptor_set = Some(Arc::new(set));
+        Ok(())
+    }
+
+    /// Builds a secondary command buffer that draws the scene texture
+    /// over the whole image of given size.
+    pub fn draw(&self, size: [u32; 2]) -> Result<SecondaryAutoCommandBuffer, PostProcessError> {
+        let descriptor_set = self
+            .descriptor_set
+            .clone()
+            .ok_or(PostProcessError::NoInput)?;
+        let mut builder = pipeline_stats::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            self.pipeline.subpass().clone(),
+        )?;
+
+        let viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [size[0] as f32, size[1] as f32],
+            depth_range: 0.0..1.0,
+        };
+        builder
+            .set_viewport(0, std::iter::once(viewport))
+            .bind_pipeline_graphics(self.pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .draw(3, 1, 0, 0)?;
+        Ok(builder.build()?)
+    }
+
+    /// Describes commands which are recorded by [`draw`](Self::draw) for the image of given size.
+    pub(crate) fn capture(&self, subpass: &mut SubpassCapture, size: [u32; 2]) {
+        subpass.set_viewport([0.0, 0.0], [size[0] as f32, size[1] as f32]);
+        subpass.bind_pipeline(capture::role_name("GraphicsPipeline", "post_process"));
+        let texture = capture::role_name("DescriptorSet", "scene_texture");
+        subpass.bind_descriptor_sets(0, vec![texture]);
+        let mesh = capture::role_name("Mesh", "fullscreen_triangle");
+        subpass.draw(mesh, None, 3, false, 1);
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_aa_mode() {
+    assert_eq!(AaMode::default(), AaMode::Off);
+    assert_eq!(AaMode::Msaa(SampleCount::Sample1).normalized(), AaMode::Off);
+    assert_eq!(
+        AaMode::Msaa(SampleCount::Sample4).normalized(),
+        AaMode::Msaa(SampleCount::Sample4),
+    );
+
+    assert_eq!(AaMode::Off.filter(), None);
+    assert_eq!(AaMode::Msaa(SampleCount::Sample1).filter(), None);
+    assert_eq!(
+        AaMode::Msaa(SampleCount::Sample8).filter(),
+        Some(PostFilter::Copy),
+    );
+    assert_eq!(AaMode::Fxaa.filter(), Some(PostFilter::Fxaa));
+
+    assert_eq!(AaMode::Fxaa.samples(), SampleCount::Sample1);
+    assert_eq!(
+        AaMode::Msaa(SampleCount::Sample2).samples(),
+        SampleCount::Sample2,
+    );
+}
+
+#[test]
+fn test_contains_samples() {
+    let counts = SampleCounts {
+        sample1: true,
+        sample2: false,
+        sample4: true,
+        sample8: false,
+        sample16: false,
+        sample32: false,
+        sample64: false,
+    };
+    assert!(contains(&counts, SampleCount::Sample1));
+    assert!(contains(&counts, SampleCount::Sample4));
+    assert!(!contains(&counts, SampleCount::Sample2));
+    assert!(!contains(&counts, SampleCount::Sample64));
+}
+
+#[test]
+fn test_clamped_samples() {
+    let counts = |max: SampleCount| {
+        let supported = |count: SampleCount| SAMPLE_COUNTS.iter().position(|&c| c == count);
+        let max = supported(max).unwrap();
+        let has = |count| supported(count).unwrap() <= max;
+        SampleCounts {
+            sample1: has(SampleCount::Sample1),
+            sample2: has(SampleCount::Sample2),
+            sample4: has(SampleCount::Sample4),
+            sample8: has(SampleCount::Sample8),
+            sample16: has(SampleCount::Sample16),
+            sample32: has(SampleCount::Sample32),
+            sample64: has(SampleCount::Sample64),
+        }
+    };
+    let color = counts(SampleCount::Sample8);
+    let depth = counts(SampleCount::Sample4);
+
+    // Sample count must be supported both for color and depth attachments.
+    let mode = AaMode::Msaa(SampleCount::Sample8);
+    assert_eq!(
+        mode.clamped_to(&color, &depth),
+        AaMode::Msaa(SampleCount::Sample4),
+    );
+    let mode = AaMode::Msaa(SampleCount::Sample2);
+    assert_eq!(mode.clamped_to(&color, &depth), mode);
+    // Multisampling is disabled if only one sample is supported.
+    let single = counts(SampleCount::Sample1);
+    let mode = AaMode::Msaa(SampleCount::Sample4);
+    assert_eq!(mode.clamped_to(&single, &single), AaMode::Off);
+    assert_eq!(AaMode::Fxaa.clamped_to(&single, &single), AaMode::Fxaa);
+}
@@ -1,13 +1,19 @@
 use thiserror::Error;
 use vulkano::command_buffer::{
-    AutoCommandBufferBuilderContextError, BeginRenderPassError, BuildError, CommandBufferExecError,
-    ExecuteCommandsError,
+    AutoCommandBufferBuilderContextError, BeginQueryError, BeginRenderPassError, BuildError,
+    CommandBufferExecError, EndQueryError, ExecuteCommandsError, ResetQueryPoolError,
+    WriteTimestampError,
 };
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
 use vulkano::render_pass::{FramebufferCreationError, RenderPassCreationError};
 use vulkano::OomError;
 
+use crate::graphics::frame::{
+    compat::CompatibilityError,
+    post_process::error::{PostProcessError, PostProcessSystemCreationError},
+};
+
 #[derive(Debug, Error)]
 pub enum FrameSystemCreationError {
     #[error("queue family must support graphics operations")]
@@ -15,6 +21,9 @@ pub enum FrameSystemCreationError {
 
     #[error("render pass creation failure: {0}")]
     RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("post-process system creation failure: {0}")]
+    PostProcessSystemCreation(#[from] PostProcessSystemCreationError),
 }
 
 #[derive(Debug, Error)]
@@ -33,6 +42,21 @@ pub enum FrameCreationError {
 
     #[error("failed to create framebuffer for the frame: {0}")]
     FramebufferCreation(#[from] FramebufferCreationError),
+
+    #[error("framebuffer is incompatible with render pass: {0}")]
+    Incompatible(#[from] CompatibilityError),
+
+    #[error("reset query pool command failure: {0}")]
+    ResetQueryPool(#[from] ResetQueryPoolError),
+
+    #[error("begin query command failure: {0}")]
+    BeginQuery(#[from] BeginQueryError),
+
+    #[error("write timestamp command failure: {0}")]
+    WriteTimestamp(#[from] WriteTimestampError),
+
+    #[error("failed to pass offscreen target to post-process: {0}")]
+    PostProcess(#[from] PostProcessError),
 }
 
 #[derive(Debug, Error)]
@@ -45,6 +69,21 @@ pub enum NextPassError {
 
     #[error("next pass command buffer execution failure: {0}")]
     Execution(#[from] CommandBufferExecError),
+
+    #[error("end query command failure: {0}")]
+    EndQuery(#[from] EndQueryError),
+
+    #[error("write timestamp command failure: {0}")]
+    WriteTimestamp(#[from] WriteTimestampError),
+
+    #[error("begin post-process pass command failure: {0}")]
+    BeginRenderPass(#[from] BeginRenderPassError),
+
+    #[error("post-process command buffer execution failure: {0}")]
+    ExecuteCommands(#[from] ExecuteCommandsError),
+
+    #[error("post-process failure: {0}")]
+    PostProcess(#[from] PostProcessError),
 }
 
 #[derive(Debug, Error)]
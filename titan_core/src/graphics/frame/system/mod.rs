@@ -7,46 +7,201 @@
 use vulkano::device::Queue;
 use vulkano::format::{ClearValue, Format};
 use vulkano::image::view::ImageView;
-use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage};
-use vulkano::render_pass::{Framebuffer, FramebufferAbstract, RenderPass, Subpass};
+use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage, SampleCount};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::query::QueryControlFlags;
+use vulkano::render_pass::{
+    Framebuffer, FramebufferAbstract, LoadOp as VkLoadOp, RenderPass, RenderPassDesc,
+    StoreOp as VkStoreOp, Subpass,
+};
+use vulkano::sampler::Sampler;
 use vulkano::sync::GpuFuture;
 
 use error::{DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError};
 
-use crate::{graphics::utils, window::Size};
+pub use ops::{LoadOp, PassOps, StoreOp};
+
+use crate::{
+    graphics::{
+        capture::{self, PassCapture},
+        frame::{
+            compat::{AttachmentInfo, CompatibilityError, RenderPassInfo},
+            post_process::{AaMode, PostFilter, PostProcessSystem},
+        },
+        gpu_profiler::{Timestamp, TimestampQuery},
+        pipeline_stats::StatisticsQuery,
+        sampler::CompareOp,
+        target::RenderTarget,
+        utils,
+    },
+    math::Color,
+    window::Size,
+};
 
 pub mod error;
 
+mod ops;
+mod tests;
+
+type DynFramebuffer = Arc<dyn FramebufferAbstract + Send + Sync>;
+
+/// Max count of final images which framebuffers are cached for
+/// (more than any swapchain has images).
+const MAX_CACHED_FRAMEBUFFERS: usize = 8;
+
+/// Framebuffers which were created for the final image,
+/// so they are reused by the next frames which are drawn into the same image.
+struct CachedFramebuffers {
+    /// Address of the final image. Image is kept alive by the framebuffers,
+    /// so the address could not be reused by another image while it is cached.
+    final_image: usize,
+    framebuffer: DynFramebuffer,
+    post_framebuffer: Option<DynFramebuffer>,
+}
+
+/// Render pass which post-processes the offscreen scene into the final image
+/// and draws UI over it.
+struct PostPass {
+    render_pass: Arc<RenderPass>,
+
+    /// Description of the render pass which framebuffers are checked against.
+    render_pass_info: RenderPassInfo,
+
+    /// System which draws the offscreen scene into the final image.
+    system: PostProcessSystem,
+
+    /// Offscreen target which the scene is rendered into.
+    /// It is (re)created lazily when dimensions of the final image change.
+    target: Option<RenderTarget>,
+}
+
 /// System that contains the necessary facilities for rendering a single frame.
 pub struct FrameSystem {
     /// Queue to render everything.
     graphics_queue: Arc<Queue>,
 
-    /// Render pass used for the drawing.
+    /// Antialiasing mode which render passes were created for.
+    aa_mode: AaMode,
+
+    /// Format of the final image.
+    final_output_format: Format,
+
+    /// Scale of the scene resolution relative to the final image.
+    render_scale: f32,
+
+    /// Color which the scene is cleared with.
+    clear_color: Color,
+
+    /// Operator which depth of the scene is compared with,
+    /// so the depth buffer is cleared with the farthest depth for it.
+    depth_compare: CompareOp,
+
+    /// Load and store operations which render pass of the scene was created with.
+    ops: PassOps,
+
+    /// Render pass used for the drawing of the scene
+    /// (and UI if the scene is not post-processed).
     render_pass: Arc<RenderPass>,
 
+    /// Description of the render pass which framebuffers are checked against.
+    render_pass_info: RenderPassInfo,
+
     /// Intermediate render target that will contain the depth of each pixel of the scene.
-    /// This is a traditional depth buffer. `0.0` means "near", and `1.0` means "far".
+    /// This is a traditional depth buffer. `0.0` means "near", and `1.0` means "far",
+    /// unless depth is reversed by greater compare operator.
     depth_buffer: Option<Arc<AttachmentImage>>,
+
+    /// Post-process pass, if the scene is rendered offscreen.
+    post_pass: Option<PostPass>,
+
+    /// Framebuffers of final images which frames were drawn into.
+    /// They are dropped when attachments of the scene or the swapchain are recreated.
+    framebuffers: Vec<CachedFramebuffers>,
 }
 
 impl FrameSystem {
-    /// Creates the frame system.
+    /// Creates the frame system for given antialiasing mode.
+    ///
+    /// If antialiasing is enabled or render scale is not `1.0`, the scene is rendered
+    /// into offscreen target which is post-processed into the final image with given sampler.
+    /// Pipeline of the post-process pass is created with given cache.
+    /// Attachments of the scene are loaded and stored with given operations.
+    ///
     pub fn new(
         graphics_queue: Arc<Queue>,
         final_output_format: Format,
+        aa_mode: AaMode,
+        render_scale: f32,
+        sampler: Arc<Sampler>,
+        pipeline_cache: Arc<PipelineCache>,
+        ops: PassOps,
     ) -> Result<Self, FrameSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(FrameSystemCreationError::QueueFamilyNotSupported);
         }
+        let aa_mode = aa_mode.normalized();
+
+        // Scaled scene is only copied (with filtering) into the final image.
+        let filter = aa_mode
+            .filter()
+            .or_else(|| (render_scale != 1.0).then(|| PostFilter::Copy));
+        let (render_pass, post_pass) = match filter {
+            None => (
+                Self::direct_pass(&graphics_queue, final_output_format)?,
+                None,
+            ),
+            Some(filter) => {
+                let scene_pass =
+                    Self::scene_pass(&graphics_queue, final_output_format, aa_mode.samples())?;
+                let render_pass = Self::post_pass(&graphics_queue, final_output_format)?;
+                let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+                let system = PostProcessSystem::new(
+                    graphics_queue.clone(),
+                    subpass,
+                    filter,
+                    sampler,
+                    pipeline_cache,
+                )?;
+                let post_pass = PostPass {
+                    render_pass_info: RenderPassInfo::new(&render_pass),
+                    render_pass,
+                    system,
+                    target: None,
+                };
+                (scene_pass, Some(post_pass))
+            }
+        };
+        let render_pass = Self::apply_ops(&graphics_queue, render_pass, ops)?;
+        let render_pass_info = RenderPassInfo::new(&render_pass);
+
+        Ok(Self {
+            graphics_queue,
+            aa_mode,
+            final_output_format,
+            render_scale,
+            clear_color: Color::BLACK,
+            depth_compare: CompareOp::Less,
+            ops,
+            render_pass,
+            render_pass_info,
+            depth_buffer: None,
+            post_pass,
+            framebuffers: Vec::new(),
+        })
+    }
 
+    /// Creates render pass which draws both the scene and UI into the final image.
+    fn direct_pass(
+        graphics_queue: &Arc<Queue>,
+        final_output_format: Format,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
         let device = graphics_queue.device().clone();
         let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
 
         // TODO: vulkano error: https://github.com/vulkano-rs/vulkano/issues/1665
         let render_pass = Arc::new(vulkano::ordered_passes_renderpass! {
-            graphics_queue.device().clone(),
+            device,
             attachments: {
                 color: {
                     load: Clear,
@@ -70,29 +225,331 @@ pub fn new(
                 { color: [color], depth_stencil: {}, input: [] }
             ]
         }?);
+        Ok(render_pass)
+    }
 
-        Ok(Self {
-            graphics_queue,
-            render_pass,
-            depth_buffer: None,
+    /// Creates render pass which draws the scene into offscreen target.
+    ///
+    /// Multisampled color is resolved at the end of the pass,
+    /// so the target could be sampled by post-process pass.
+    ///
+    fn scene_pass(
+        graphics_queue: &Arc<Queue>,
+        format: Format,
+        samples: SampleCount,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
+        let device = graphics_queue.device().clone();
+        let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
+
+        if samples == SampleCount::Sample1 {
+            let render_pass = vulkano::single_pass_renderpass! {
+                device,
+                attachments: {
+                    color: {
+                        load: Clear,
+                        store: Store,
+                        format: format,
+                        samples: 1,
+                        initial_layout: ImageLayout::Undefined,
+                        final_layout: ImageLayout::ShaderReadOnlyOptimal,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: depth_format,
+                        samples: 1,
+                        initial_layout: ImageLayout::Undefined,
+                        final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                    }
+                },
+                pass: { color: [color], depth_stencil: {depth} }
+            }?;
+            return Ok(Arc::new(render_pass));
+        }
+
+        let samples = samples as u32;
+        let render_pass = vulkano::single_pass_renderpass! {
+            device,
+            attachments: {
+                // Multisampled color is not needed after it was resolved.
+                color: {
+                    load: Clear,
+                    store: DontCare,
+                    format: format,
+                    samples: samples,
+                },
+                resolve: {
+                    load: DontCare,
+                    store: Store,
+                    format: format,
+                    samples: 1,
+                    initial_layout: ImageLayout::Undefined,
+                    final_layout: ImageLayout::ShaderReadOnlyOptimal,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: depth_format,
+                    samples: samples,
+                    initial_layout: ImageLayout::Undefined,
+                    final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                }
+            },
+            pass: { color: [color], depth_stencil: {depth}, resolve: [resolve] }
+        }?;
+        Ok(Arc::new(render_pass))
+    }
+
+    /// Creates render pass which post-processes the scene into the final image
+    /// and draws UI over it.
+    fn post_pass(
+        graphics_queue: &Arc<Queue>,
+        final_output_format: Format,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
+        let device = graphics_queue.device().clone();
+        let render_pass = vulkano::ordered_passes_renderpass! {
+            device,
+            attachments: {
+                // Each pixel is overwritten by post-process, so there is no need to clear.
+                color: {
+                    load: DontCare,
+                    store: Store,
+                    format: final_output_format,
+                    samples: 1,
+                }
+            },
+            passes: [
+                // Subpass for post-processing.
+                { color: [color], depth_stencil: {}, input: [] },
+                // Subpass for UI rendering.
+                { color: [color], depth_stencil: {}, input: [] }
+            ]
+        }?;
+        Ok(Arc::new(render_pass))
+    }
+
+    /// Recreates render pass of the scene with operations of its color (the first)
+    /// and depth (the last) attachments replaced by given ones.
+    ///
+    /// Resolve attachment (if any) is left as is, because it is always overwritten.
+    ///
+    fn apply_ops(
+        graphics_queue: &Arc<Queue>,
+        render_pass: Arc<RenderPass>,
+        ops: PassOps,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
+        if ops == PassOps::default() {
+            return Ok(render_pass);
+        }
+        let desc = render_pass.desc();
+        let mut attachments = desc.attachments().to_vec();
+        let depth = attachments.len() - 1;
+
+        let color = &mut attachments[0];
+        color.load = ops.color_load.into();
+        // Multisampled color must be stored too, so the next frame could load it.
+        if ops.color_load == LoadOp::Load {
+            color.store = VkStoreOp::Store;
+        }
+        let depth_attachment = &mut attachments[depth];
+        depth_attachment.load = ops.depth_load.into();
+        depth_attachment.store = ops.depth_store.into();
+
+        // Contents are preserved only if the attachment is not transitioned from undefined layout.
+        for index in [0, depth] {
+            let attachment = &mut attachments[index];
+            if attachment.load == VkLoadOp::Load {
+                attachment.initial_layout = attachment.final_layout;
+            }
+        }
+
+        let desc = RenderPassDesc::new(
+            attachments,
+            desc.subpasses().to_vec(),
+            desc.dependencies().to_vec(),
+        );
+        let device = graphics_queue.device().clone();
+        Ok(Arc::new(RenderPass::new(device, desc)?))
+    }
+
+    /// Antialiasing mode of the scene.
+    pub fn aa_mode(&self) -> AaMode {
+        self.aa_mode
+    }
+
+    /// Format of the final image which frames are rendered into.
+    pub fn final_output_format(&self) -> Format {
+        self.final_output_format
+    }
+
+    /// Scale of the scene resolution relative to the final image.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Color which the scene is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// Sets color which the scene is cleared with since the next frame.
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = color;
+    }
+
+    /// Load and store operations of attachments of the scene.
+    pub fn ops(&self) -> PassOps {
+        self.ops
+    }
+
+    /// Operator which depth of the scene is compared with.
+    pub fn depth_compare(&self) -> CompareOp {
+        self.depth_compare
+    }
+
+    /// Sets operator which depth of the scene is compared with since the next frame
+    /// (see [`ObjectDrawSystem::set_depth_compare`](super::object_draw::ObjectDrawSystem::set_depth_compare)).
+    ///
+    /// Depth buffer is cleared with `0.0` for greater operators (reversed depth)
+    /// and with `1.0` otherwise.
+    ///
+    pub fn set_depth_compare(&mut self, compare_op: CompareOp) {
+        self.depth_compare = compare_op;
+    }
+
+    /// Dimensions of the scene which is rendered for the final image of given dimensions.
+    pub fn scene_dimensions(&self, dimensions: [u32; 2]) -> [u32; 2] {
+        if self.post_pass.is_none() {
+            return dimensions;
+        }
+        dimensions.map(|dimension| {
+            let scaled = (dimension as f32 * self.render_scale).round() as u32;
+            scaled.max(1)
         })
     }
 
+    /// Drops framebuffers which were created for final images of previous frames.
+    ///
+    /// It should be called when the swapchain is recreated, so its old images are released.
+    /// Depth buffer and offscreen target are kept if the scene dimensions stay the same.
+    ///
+    pub fn clear_framebuffers(&mut self) {
+        self.framebuffers.clear();
+    }
+
+    /// Count of final images which framebuffers are cached for.
+    pub fn cached_framebuffers(&self) -> usize {
+        self.framebuffers.len()
+    }
+
+    /// Retrieve subpass with given index for pipeline creation.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if render pass has no such subpass.
+    ///
+    pub fn subpass(&self, index: u32) -> Result<Subpass, CompatibilityError> {
+        self.render_pass_info.check_pipeline(index, None)?;
+        Ok(Subpass::from(self.render_pass.clone(), index).unwrap())
+    }
+
     /// Retrieve subpass for object rendering.
     pub fn object_subpass(&self) -> Subpass {
-        Subpass::from(self.render_pass.clone(), 0).unwrap()
+        self.subpass(0).unwrap()
     }
 
     /// Retrieve subpass for UI rendering.
+    ///
+    /// If the scene is post-processed, UI is drawn in the post-process pass.
+    ///
     pub fn ui_subpass(&self) -> Subpass {
-        Subpass::from(self.render_pass.clone(), 1).unwrap()
+        match &self.post_pass {
+            Some(post_pass) => Subpass::from(post_pass.render_pass.clone(), 1).unwrap(),
+            None => self.subpass(1).unwrap(),
+        }
+    }
+
+    /// Clear values of color and depth of the scene,
+    /// which are [`ClearValue::None`] if the attachment is not cleared.
+    fn clear_values(&self) -> (ClearValue, ClearValue) {
+        let color = match self.ops.color_load {
+            LoadOp::Clear => ClearValue::Float(self.clear_color.into()),
+            _ => ClearValue::None,
+        };
+        let depth = match self.ops.depth_load {
+            LoadOp::Clear => ClearValue::Depth(utils::clear_depth(self.depth_compare)),
+            _ => ClearValue::None,
+        };
+        (color, depth)
+    }
+
+    /// Describes render passes of the frame which is drawn into the final image
+    /// with given name and dimensions, without commands of the scene and UI.
+    ///
+    /// Scene commands are captured into subpass `scene`, UI ones into subpass `ui`.
+    ///
+    pub(crate) fn capture_passes(
+        &self,
+        final_image: String,
+        dimensions: [u32; 2],
+    ) -> Vec<PassCapture> {
+        let depth = capture::role_name("AttachmentImage", "depth");
+        let (color, depth_value) = self.clear_values();
+        let post_pass = match &self.post_pass {
+            None => {
+                let images = [final_image, depth];
+                let clear_values = [color, depth_value];
+                let pass = PassCapture::new(
+                    "main",
+                    &self.render_pass,
+                    &images,
+                    &clear_values,
+                    &["scene", "ui"],
+                );
+                return vec![pass];
+            }
+            Some(post_pass) => post_pass,
+        };
+
+        let mut images = vec![capture::role_name("RenderTarget", "color")];
+        let mut clear_values = vec![color];
+        if self.aa_mode.samples() != SampleCount::Sample1 {
+            images.push(capture::role_name("RenderTarget", "resolve"));
+            clear_values.push(ClearValue::None);
+        }
+        images.push(depth);
+        clear_values.push(depth_value);
+        let scene = PassCapture::new(
+            "scene",
+            &self.render_pass,
+            &images,
+            &clear_values,
+            &["scene"],
+        );
+
+        let mut post = PassCapture::new(
+            "post_process",
+            &post_pass.render_pass,
+            &[final_image],
+            &[ClearValue::None],
+            &["post_process", "ui"],
+        );
+        post_pass.system.capture(&mut post.subpasses[0], dimensions);
+        vec![scene, post]
     }
 
     /// Starts drawing a new frame.
+    ///
+    /// If pipeline statistics query is provided, it is reset and recorded
+    /// around the render pass of the frame. Timestamps of the frame are written
+    /// into timestamp query (if any) before and after each render pass.
+    ///
     pub fn frame<F, I>(
         &mut self,
         before_future: F,
         final_image: Arc<I>,
+        statistics_query: Option<StatisticsQuery>,
+        timestamp_query: Option<TimestampQuery>,
     ) -> Result<Frame, FrameCreationError>
     where
         F: GpuFuture + Send + Sync + 'static,
@@ -100,7 +557,8 @@ pub fn frame<F, I>(
     {
         let device = self.graphics_queue.device().clone();
 
-        let dimensions = final_image.dimensions().width_height();
+        let dimensions = self.scene_dimensions(final_image.dimensions().width_height());
+        let samples = self.aa_mode.samples();
         let old_dimensions = self
             .depth_buffer
             .as_ref()
@@ -112,35 +570,75 @@ pub fn frame<F, I>(
             // (Re)create depth buffer.
             let depth_buffer = {
                 let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
-                AttachmentImage::with_usage(
-                    device.clone(),
-                    dimensions,
-                    depth_format,
-                    ImageUsage::depth_stencil_attachment(),
-                )?
+                let usage = ImageUsage::depth_stencil_attachment();
+                if samples == SampleCount::Sample1 {
+                    AttachmentImage::with_usage(device.clone(), dimensions, depth_format, usage)?
+                } else {
+                    AttachmentImage::multisampled_with_usage(
+                        device.clone(),
+                        dimensions,
+                        samples,
+                        depth_format,
+                        usage,
+                    )?
+                }
             };
             self.depth_buffer = Some(depth_buffer.clone());
+            self.framebuffers.clear();
         }
 
-        // Create framebuffer.
-        let framebuffer = {
-            let image_view = ImageView::new(final_image.clone())?;
-            let depth_buffer_view = {
-                let depth_buffer = self.depth_buffer.as_ref().unwrap().clone();
-                ImageView::new(depth_buffer)?
-            };
-            Arc::new(
-                Framebuffer::start(self.render_pass.clone())
-                    .add(image_view)?
-                    .add(depth_buffer_view)?
-                    .build()?,
-            )
+        // (Re)create offscreen target of the scene and pass it to post-process.
+        if let Some(post_pass) = &mut self.post_pass {
+            let format = self.final_output_format;
+            let outdated = post_pass
+                .target
+                .as_ref()
+                .map(|target| !target.matches(dimensions, format, samples))
+                .unwrap_or(true);
+            if outdated {
+                let keep_contents = self.ops.color_load == LoadOp::Load;
+                let target = RenderTarget::new_multisampled(
+                    device.clone(),
+                    dimensions,
+                    format,
+                    samples,
+                    keep_contents,
+                )?;
+                post_pass.system.set_input(target.texture().clone())?;
+                post_pass.target = Some(target);
+                self.framebuffers.clear();
+            }
+        }
+
+        // Reuse framebuffers of the final image, if they were created by previous frames.
+        let key = Arc::as_ptr(&final_image) as *const () as usize;
+        let cached = self
+            .framebuffers
+            .iter()
+            .find(|cached| cached.final_image == key);
+        let (framebuffer, post_framebuffer) = match cached {
+            Some(cached) => (cached.framebuffer.clone(), cached.post_framebuffer.clone()),
+            None => {
+                let (framebuffer, post_framebuffer) = self.create_framebuffers(final_image)?;
+                if self.framebuffers.len() >= MAX_CACHED_FRAMEBUFFERS {
+                    self.framebuffers.clear();
+                }
+                self.framebuffers.push(CachedFramebuffers {
+                    final_image: key,
+                    framebuffer: framebuffer.clone(),
+                    post_framebuffer: post_framebuffer.clone(),
+                });
+                (framebuffer, post_framebuffer)
+            }
         };
 
-        let clear_values = [
-            ClearValue::Float([0.0, 0.0, 0.0, 1.0]),
-            ClearValue::Depth(1.0),
-        ];
+        // Resolve attachment (if any) is not cleared.
+        let (color, depth) = self.clear_values();
+        let mut clear_values = vec![color];
+        if samples != SampleCount::Sample1 {
+            clear_values.push(ClearValue::None);
+        }
+        clear_values.push(depth);
 
         // Build primary command buffer that will execute secondary command buffers
         // in rendering process.
@@ -149,6 +647,22 @@ pub fn frame<F, I>(
             self.graphics_queue.family(),
             CommandBufferUsage::OneTimeSubmit,
         )?;
+        if let Some(query) = &statistics_query {
+            let StatisticsQuery { pool, index } = query.clone();
+            // Safety: query is not used by other command buffers
+            // until results of the previous frame which used it were read.
+            unsafe {
+                builder.reset_query_pool(pool.clone(), index..index + 1)?;
+                builder.begin_query(pool, index, QueryControlFlags { precise: false })?;
+            }
+        }
+        if let Some(query) = &timestamp_query {
+            // Safety: same as for the statistics query.
+            unsafe {
+                builder.reset_query_pool(query.pool.clone(), query.range())?;
+                query.write(&mut builder, Timestamp::FrameStart)?;
+            }
+        }
         builder.begin_render_pass(
             framebuffer.clone(),
             SubpassContents::SecondaryCommandBuffers,
@@ -160,9 +674,74 @@ pub fn frame<F, I>(
             subpass_number: 0,
             before_future: Some(Box::new(before_future)),
             framebuffer,
+            post_framebuffer,
             command_buffer_builder: Some(builder),
+            statistics_query,
+            timestamp_query,
         })
     }
+
+    /// Creates framebuffers of the scene (and post-process pass, if any)
+    /// which draw into the final image.
+    fn create_framebuffers<I>(
+        &self,
+        final_image: Arc<I>,
+    ) -> Result<(DynFramebuffer, Option<DynFramebuffer>), FrameCreationError>
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        let depth_buffer = self.depth_buffer.as_ref().unwrap().clone();
+        let framebuffers: (DynFramebuffer, Option<DynFramebuffer>) = match &self.post_pass {
+            None => {
+                let images = [
+                    AttachmentInfo::of_image(&*final_image),
+                    AttachmentInfo::of_image(&*depth_buffer),
+                ];
+                self.render_pass_info.check_framebuffer(&images)?;
+
+                let image_view = ImageView::new(final_image.clone())?;
+                let depth_buffer_view = ImageView::new(depth_buffer)?;
+                let framebuffer = Framebuffer::start(self.render_pass.clone())
+                    .add(image_view)?
+                    .add(depth_buffer_view)?
+                    .build()?;
+                (Arc::new(framebuffer), None)
+            }
+            Some(post_pass) => {
+                let target = post_pass.target.as_ref().unwrap();
+                let color = target.color().clone();
+                let mut images = vec![AttachmentInfo::of_image(&*color)];
+                images.extend(
+                    target
+                        .resolve()
+                        .map(|image| AttachmentInfo::of_image(&**image)),
+                );
+                images.push(AttachmentInfo::of_image(&*depth_buffer));
+                self.render_pass_info.check_framebuffer(&images)?;
+
+                let builder =
+                    Framebuffer::start(self.render_pass.clone()).add(ImageView::new(color)?)?;
+                let depth_buffer_view = ImageView::new(depth_buffer)?;
+                let framebuffer: DynFramebuffer = match target.resolve() {
+                    Some(resolve) => Arc::new(
+                        builder
+                            .add(ImageView::new(resolve.clone())?)?
+                            .add(depth_buffer_view)?
+                            .build()?,
+                    ),
+                    None => Arc::new(builder.add(depth_buffer_view)?.build()?),
+                };
+
+                let images = [AttachmentInfo::of_image(&*final_image)];
+                post_pass.render_pass_info.check_framebuffer(&images)?;
+                let post_framebuffer = Framebuffer::start(post_pass.render_pass.clone())
+                    .add(ImageView::new(final_image.clone())?)?
+                    .build()?;
+                (framebuffer, Some(Arc::new(post_framebuffer)))
+            }
+        };
+        Ok(framebuffers)
+    }
 }
 
 /// Represents the active process of rendering a frame.
@@ -177,10 +756,19 @@ pub struct Frame<'a> {
     before_future: Option<Box<dyn GpuFuture + Send + Sync>>,
 
     /// Framebuffer that was used when starting the render pass.
-    framebuffer: Arc<dyn FramebufferAbstract + Send + Sync>,
+    framebuffer: DynFramebuffer,
+
+    /// Framebuffer of the post-process pass, if the scene is post-processed.
+    post_framebuffer: Option<DynFramebuffer>,
 
     /// The command buffer builder that will be built during the lifetime of this object.
     command_buffer_builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+
+    /// Pipeline statistics query which is active during the render pass.
+    statistics_query: Option<StatisticsQuery>,
+
+    /// Timestamp queries which are written outside of render passes.
+    timestamp_query: Option<TimestampQuery>,
 }
 
 impl<'a> Frame<'a> {
@@ -197,10 +785,29 @@ pub fn next_pass<'f>(&'f mut self) -> Result<Option<Pass<'f, 'a>>, NextPassError
 
             // If we are in the pass 1 then we have finished drawing the objects on the scene.
             1 => {
-                self.command_buffer_builder
-                    .as_mut()
-                    .unwrap()
-                    .next_subpass(SubpassContents::SecondaryCommandBuffers)?;
+                let builder = self.command_buffer_builder.as_mut().unwrap();
+                match (&self.system.post_pass, &self.post_framebuffer) {
+                    (Some(post_pass), Some(post_framebuffer)) => {
+                        // Scene is post-processed into the final image before UI.
+                        builder.end_render_pass()?;
+                        if let Some(query) = &self.timestamp_query {
+                            // Safety: queries were reset at the start of the frame.
+                            unsafe { query.write(builder, Timestamp::MainPassEnd)? };
+                        }
+                        builder.begin_render_pass(
+                            post_framebuffer.clone(),
+                            SubpassContents::SecondaryCommandBuffers,
+                            [ClearValue::None],
+                        )?;
+                        let size = post_framebuffer.dimensions();
+                        let command_buffer = post_pass.system.draw([size[0], size[1]])?;
+                        builder.execute_commands(command_buffer)?;
+                        builder.next_subpass(SubpassContents::SecondaryCommandBuffers)?;
+                    }
+                    _ => {
+                        builder.next_subpass(SubpassContents::SecondaryCommandBuffers)?;
+                    }
+                }
 
                 // Returning an object that will allow the user to render UI.
                 Ok(Some(Pass::UI(DrawPass { frame: self })))
@@ -208,10 +815,21 @@ pub fn next_pass<'f>(&'f mut self) -> Result<Option<Pass<'f, 'a>>, NextPassError
 
             // If we are in pass 2 then we have finished rendering UI.
             2 => {
-                self.command_buffer_builder
-                    .as_mut()
-                    .unwrap()
-                    .end_render_pass()?;
+                let builder = self.command_buffer_builder.as_mut().unwrap();
+                builder.end_render_pass()?;
+                if let Some(StatisticsQuery { pool, index }) = self.statistics_query.take() {
+                    builder.end_query(pool, index)?;
+                }
+                if let Some(query) = self.timestamp_query.take() {
+                    // Without post-process, the main pass ends together with the frame.
+                    // Safety: queries were reset at the start of the frame.
+                    unsafe {
+                        if self.system.post_pass.is_none() || self.post_framebuffer.is_none() {
+                            query.write(builder, Timestamp::MainPassEnd)?;
+                        }
+                        query.write(builder, Timestamp::PostPassEnd)?;
+                    }
+                }
                 let command_buffer = self.command_buffer_builder.take().unwrap().build()?;
 
                 // Extract `before_future` and append the command buffer execution to it.
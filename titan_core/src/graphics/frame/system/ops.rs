@@ -0,0 +1,94 @@
+//! Load and store operations of attachments of the render pass of the scene.
+
+use vulkano::render_pass::{LoadOp as VkLoadOp, StoreOp as VkStoreOp};
+
+/// Operation which is done with contents of the attachment at the start of the frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LoadOp {
+    /// Attachment is cleared (color is cleared with
+    /// [clear color](crate::graphics::Renderer::set_clear_color)).
+    Clear,
+    /// Contents of the attachment which were stored by the previous frame are kept
+    /// (for example, for accumulation effects).
+    Load,
+    /// Contents of the attachment are undefined, so the frame must overwrite each pixel.
+    DontCare,
+}
+
+/// Operation which is done with contents of the attachment at the end of the frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StoreOp {
+    /// Contents of the attachment are stored, so the next frame could load them.
+    Store,
+    /// Contents of the attachment are not needed after the frame.
+    DontCare,
+}
+
+/// Load and store operations of attachments of the render pass where the scene is drawn.
+///
+/// Color of the scene is always stored, because it is presented (or post-processed).
+/// If the scene is drawn directly into swapchain images, loaded color is the color
+/// of the last frame which was drawn into the same image, not of the previous frame.
+/// Scene is drawn offscreen if antialiasing is enabled or render scale is not `1.0`.
+///
+/// By default, both color and depth are cleared, and depth is not stored.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PassOps {
+    /// Load operation of the color of the scene.
+    pub color_load: LoadOp,
+    /// Load operation of the depth buffer.
+    pub depth_load: LoadOp,
+    /// Store operation of the depth buffer: depth must be stored to be loaded by the next frame.
+    pub depth_store: StoreOp,
+}
+
+impl PassOps {
+    /// Operations which clear both color and depth.
+    pub const fn new() -> Self {
+        Self {
+            color_load: LoadOp::Clear,
+            depth_load: LoadOp::Clear,
+            depth_store: StoreOp::DontCare,
+        }
+    }
+
+    /// Same operations with given load operation of the color.
+    pub const fn with_color_load(self, color_load: LoadOp) -> Self {
+        Self { color_load, ..self }
+    }
+
+    /// Same operations with given load and store operations of the depth buffer.
+    pub const fn with_depth_ops(self, depth_load: LoadOp, depth_store: StoreOp) -> Self {
+        Self {
+            depth_load,
+            depth_store,
+            ..self
+        }
+    }
+}
+
+impl Default for PassOps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<LoadOp> for VkLoadOp {
+    fn from(load: LoadOp) -> Self {
+        match load {
+            LoadOp::Clear => VkLoadOp::Clear,
+            LoadOp::Load => VkLoadOp::Load,
+            LoadOp::DontCare => VkLoadOp::DontCare,
+        }
+    }
+}
+
+impl From<StoreOp> for VkStoreOp {
+    fn from(store: StoreOp) -> Self {
+        match store {
+            StoreOp::Store => VkStoreOp::Store,
+            StoreOp::DontCare => VkStoreOp::DontCare,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+#![cfg(test)]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use image::RgbaImage;
+use ultraviolet::{Mat4, Vec3};
+use vulkano::buffer::BufferUsage;
+use vulkano::sync;
+
+use crate::asset::Primitive;
+use crate::graphics::{
+    allocator::MemoryAllocator,
+    camera::CameraUBO,
+    clip::ClipRect,
+    draw::DrawCommand,
+    frame::object_draw::ObjectDrawSystem,
+    mapped::MappedBuffer,
+    pre_rotation::PreTransform,
+    readback::{ImageSubresource, Readback},
+    sampler::{SamplerCache, SamplerDesc},
+    specialization::{SpecializationInfo, SWAP_RED_BLUE},
+    utils::graphics_queue,
+    viewport::{Rect, Region},
+};
+
+use super::*;
+
+const SIZE: [u32; 2] = [64, 64];
+
+/// Renders the scene of object draw system and given draws with given antialiasing mode
+/// and camera, then reads the final image back.
+fn render(
+    queue: Arc<Queue>,
+    aa_mode: AaMode,
+    ubo: CameraUBO,
+    draws: &[(&DrawCommand, ClipRect)],
+) -> RgbaImage {
+    let device = queue.device().clone();
+    let format = Format::R8G8B8A8_UNORM;
+    let mut sampler_cache = SamplerCache::new(device.clone());
+    let sampler = sampler_cache.get(SamplerDesc::linear()).unwrap();
+    let pipeline_cache = PipelineCache::empty(device.clone()).unwrap();
+    let mut frame_system = FrameSystem::new(
+        queue.clone(),
+        format,
+        aa_mode,
+        1.0,
+        sampler,
+        pipeline_cache.clone(),
+        PassOps::default(),
+    )
+    .unwrap();
+    let mut object_draw_system = ObjectDrawSystem::new(
+        queue.clone(),
+        frame_system.object_subpass(),
+        pipeline_cache,
+        CompareOp::Less,
+    )
+    .unwrap();
+
+    let usage = ImageUsage {
+        color_attachment: true,
+        transfer_source: true,
+        ..ImageUsage::none()
+    };
+    let final_image = AttachmentImage::with_usage(device.clone(), SIZE, format, usage).unwrap();
+    let allocator = MemoryAllocator::new(device.clone());
+    let uniform_buffer = MappedBuffer::new(&allocator, BufferUsage::uniform_buffer(), 1).unwrap();
+    uniform_buffer.write(0, ubo).unwrap();
+    let window_size = SIZE.map(|dimension| dimension as f32);
+    let region = Region::new(Rect::FULL, 0, 1, window_size, PreTransform::default());
+
+    let mut frame = frame_system
+        .frame(sync::now(device.clone()), final_image.clone(), None, None)
+        .unwrap();
+    let mut future = None;
+    while let Some(pass) = frame.next_pass().unwrap() {
+        match pass {
+            Pass::Deferred(mut draw_pass) => {
+                let command_buffer = object_draw_system
+                    .draw(&region, uniform_buffer.clone(), None, false, draws, None)
+                    .unwrap();
+                draw_pass.execute(command_buffer).unwrap();
+            }
+            Pass::UI(_) => (),
+            Pass::Finished(after_future) => future = Some(after_future),
+        }
+    }
+
+    // Final image is read back the same way as frames of the renderer.
+    let mut readback = Readback::new(allocator);
+    let ticket = readback.frame(ImageSubresource::default());
+    let (command_buffer, batch) = readback.record(&queue, Some(final_image)).unwrap().unwrap();
+    let fence = future
+        .unwrap()
+        .then_execute(queue, command_buffer)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap();
+    batch.submitted(Arc::new(fence));
+
+    let pixels = ticket.wait(Duration::from_secs(5)).unwrap();
+    let info = ticket.image_info().unwrap();
+    assert_eq!((info.extent, info.format), ([SIZE[0], SIZE[1], 1], format));
+    RgbaImage::from_raw(SIZE[0], SIZE[1], pixels).unwrap()
+}
+
+/// Compares the image with the golden one, returning mean and max difference of channels.
+///
+/// Golden image is (re)written instead if `TITAN_BLESS_GOLDEN` environment variable is set.
+///
+fn compare_golden(name: &str, image: &RgbaImage) -> (f64, u8) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    if std::env::var_os("TITAN_BLESS_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        image.save(&path).unwrap();
+        return (0.0, 0);
+    }
+    let golden = match image::open(&path) {
+        Ok(golden) => golden.into_rgba8(),
+        Err(error) => panic!(
+            "failed to open golden image {}: {} (set TITAN_BLESS_GOLDEN to write it)",
+            path.display(),
+            error,
+        ),
+    };
+    assert_eq!(golden.dimensions(), image.dimensions());
+
+    let differences: Vec<_> = golden
+        .as_raw()
+        .iter()
+        .zip(image.as_raw())
+        .map(|(&golden, &actual)| golden.abs_diff(actual))
+        .collect();
+    let sum: f64 = differences
+        .iter()
+        .map(|&difference| difference as f64)
+        .sum();
+    let max = differences.iter().copied().max().unwrap_or(0);
+    (sum / differences.len() as f64, max)
+}
+
+/// Renders the same scene with each antialiasing mode and compares with golden images.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_antialiasing_golden() {
+    let queue = graphics_queue();
+    let physical_device = queue.device().physical_device();
+
+    // FXAA differs between vendors much more than rasterization does.
+    let modes = [
+        ("antialiasing_off", AaMode::Off, (0.5, 8)),
+        (
+            "antialiasing_msaa",
+            AaMode::Msaa(SampleCount::Sample4),
+            (1.0, 32),
+        ),
+        ("antialiasing_fxaa", AaMode::Fxaa, (4.0, 128)),
+    ];
+    for (name, mode, (mean_tolerance, max_tolerance)) in modes {
+        // Vulkan requires 4 samples to be supported for color and depth attachments.
+        assert!(
+            mode.is_supported(physical_device),
+            "{:?} is not supported by the device",
+            mode,
+        );
+        // Rotated quad has diagonal edges which are aliased without antialiasing.
+        let ubo = CameraUBO::new(
+            Mat4::from_rotation_z(0.5),
+            Mat4::identity(),
+            Mat4::identity(),
+        );
+        let image = render(queue.clone(), mode, ubo, &[]);
+        let (mean, max) = compare_golden(name, &image);
+        assert!(
+            mean <= mean_tolerance && max <= max_tolerance,
+            "{:?} differs from golden image: mean {:.2}, max {}",
+            mode,
+            mean,
+            max,
+        );
+    }
+}
+
+/// Renders two overlapping quads clipped by the left half of the image
+/// and checks that pixels outside of the clip rectangle keep the clear color.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_clipped_draws() {
+    // With identity camera depth is equal to Z, so quads are behind the scene
+    // and the green one is in front of the red one.
+    let quad = |x: f32, z: f32, color: Color| {
+        let transform = Mat4::from_translation(Vec3::new(x, 0.0, z)) * Mat4::from_scale(2.0);
+        DrawCommand::new(Primitive::Quad, transform).with_color(color)
+    };
+    let red = quad(0.0, 0.75, Color::RED);
+    let green = quad(0.5, 0.5, Color::GREEN);
+    let clip = ClipRect::new([0, 0], [SIZE[0] / 2, SIZE[1]]);
+    let ubo = CameraUBO::new(Mat4::identity(), Mat4::identity(), Mat4::identity());
+    let image = render(
+        graphics_queue(),
+        AaMode::Off,
+        ubo,
+        &[(&red, clip), (&green, clip)],
+    );
+
+    let pixel = |x, y| image.get_pixel(x, y).0;
+    let [clear, red, green]: [[u8; 4]; 3] =
+        [Color::BLACK, Color::RED, Color::GREEN].map(Into::into);
+    assert_eq!(pixel(4, 4), red);
+    assert_eq!(pixel(24, 4), green);
+    // Right half is covered by both quads, but they are clipped there.
+    for (x, y) in [(40, 4), (56, 8), (60, 60), (34, 56)] {
+        assert_eq!(pixel(x, y), clear, "pixel ({}, {}) is not clear", x, y);
+    }
+}
+
+/// Draws red quads of the default shader with and without swapped channels.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_specialized_draws() {
+    let quad = |x: f32| {
+        let transform = Mat4::from_translation(Vec3::new(x, 0.0, 0.5)) * Mat4::from_scale(0.5);
+        DrawCommand::new(Primitive::Quad, transform).with_color(Color::RED)
+    };
+    // Quads are placed at the sides of the scene, which is in front of them.
+    let plain = quad(-0.75);
+    let swapped =
+        quad(0.75).with_specialization(SpecializationInfo::new().with(SWAP_RED_BLUE, true));
+    let full = ClipRect::new([0, 0], SIZE);
+    let ubo = CameraUBO::new(Mat4::identity(), Mat4::identity(), Mat4::identity());
+    let image = render(
+        graphics_queue(),
+        AaMode::Off,
+        ubo,
+        &[(&plain, full), (&swapped, full)],
+    );
+
+    // Both quads are drawn by the same shader, but with different pipelines.
+    let [red, blue]: [[u8; 4]; 2] = [Color::RED, Color::BLUE].map(Into::into);
+    assert_eq!(image.get_pixel(8, SIZE[1] / 2).0, red);
+    assert_eq!(image.get_pixel(SIZE[0] - 8, SIZE[1] / 2).0, blue);
+}
+
+/// Framebuffers are reused for the same final image until they are cleared
+/// (like when the swapchain is recreated).
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_cached_framebuffers() {
+    let queue = graphics_queue();
+    let device = queue.device().clone();
+    let format = Format::R8G8B8A8_UNORM;
+    let mut sampler_cache = SamplerCache::new(device.clone());
+    let sampler = sampler_cache.get(SamplerDesc::linear()).unwrap();
+    let pipeline_cache = PipelineCache::empty(device.clone()).unwrap();
+    let ops = PassOps::default();
+    let mut frame_system = FrameSystem::new(
+        queue,
+        format,
+        AaMode::Off,
+        0.5,
+        sampler,
+        pipeline_cache,
+        ops,
+    )
+    .unwrap();
+    let usage = ImageUsage::color_attachment();
+    let images: Vec<_> = (0..2)
+        .map(|_| AttachmentImage::with_usage(device.clone(), SIZE, format, usage).unwrap())
+        .collect();
+
+    // Frames are not submitted: only their framebuffers are checked.
+    let mut framebuffers = Vec::new();
+    for image in [&images[0], &images[0], &images[1]] {
+        let frame = frame_system
+            .frame(sync::now(device.clone()), image.clone(), None, None)
+            .unwrap();
+        framebuffers.push(frame.framebuffer.clone());
+    }
+    assert!(Arc::ptr_eq(&framebuffers[0], &framebuffers[1]));
+    assert!(!Arc::ptr_eq(&framebuffers[0], &framebuffers[2]));
+    assert_eq!(frame_system.cached_framebuffers(), 2);
+
+    frame_system.clear_framebuffers();
+    assert_eq!(frame_system.cached_framebuffers(), 0);
+    let frame = frame_system
+        .frame(sync::now(device.clone()), images[0].clone(), None, None)
+        .unwrap();
+    assert!(!Arc::ptr_eq(&framebuffers[0], &frame.framebuffer));
+    drop(frame);
+    assert_eq!(frame_system.cached_framebuffers(), 1);
+}
+
+/// Attachments of the scene are loaded and stored with operations of the frame system,
+/// and loaded ones are not transitioned from undefined layout.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_pass_ops() {
+    let queue = graphics_queue();
+    let device = queue.device().clone();
+    let format = Format::R8G8B8A8_UNORM;
+    let mut sampler_cache = SamplerCache::new(device.clone());
+    let sampler = sampler_cache.get(SamplerDesc::linear()).unwrap();
+    let pipeline_cache = PipelineCache::empty(device).unwrap();
+    let ops = PassOps::new()
+        .with_color_load(LoadOp::Load)
+        .with_depth_ops(LoadOp::DontCare, StoreOp::Store);
+
+    for aa_mode in [AaMode::Off, AaMode::Msaa(SampleCount::Sample4)] {
+        let frame_system = FrameSystem::new(
+            queue.clone(),
+            format,
+            aa_mode,
+            1.0,
+            sampler.clone(),
+            pipeline_cache.clone(),
+            ops,
+        )
+        .unwrap();
+        assert_eq!(frame_system.ops(), ops);
+
+        let attachments = frame_system.render_pass.desc().attachments();
+        let (color, depth) = (&attachments[0], attachments.last().unwrap());
+        assert_eq!(
+            (color.load, color.store),
+            (VkLoadOp::Load, VkStoreOp::Store)
+        );
+        assert_eq!(color.initial_layout, color.final_layout);
+        assert_eq!(
+            (depth.load, depth.store),
+            (VkLoadOp::DontCare, VkStoreOp::Store)
+        );
+        let clear_values = frame_system.clear_values();
+        assert!(matches!(clear_values, (ClearValue::None, ClearValue::None)));
+    }
+}
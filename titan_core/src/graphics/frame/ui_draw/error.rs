@@ -2,13 +2,11 @@
 use vulkano::command_buffer::{BuildError, DrawIndexedError};
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
-use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::pipeline::GraphicsPipelineCreationError;
-use vulkano::sampler::SamplerCreationError;
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
-use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::{renderer::error::DescriptorSetCreationError, TransientWriteError};
 
 #[derive(Debug, Error)]
 pub enum UiDrawSystemCreationError {
@@ -20,9 +18,6 @@ pub enum UiDrawSystemCreationError {
 
     #[error("graphics pipeline creation failure: {0}")]
     GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
-
-    #[error("texture sampler creation failure: {0}")]
-    SamplerCreation(#[from] SamplerCreationError),
 }
 
 #[derive(Debug, Error)]
@@ -43,7 +38,7 @@ pub enum UiDrawError {
     DescriptorSetCreation(#[from] DescriptorSetCreationError),
 
     #[error("vertex/index buffer allocation failure: {0}")]
-    BufferAllocation(#[from] DeviceMemoryAllocError),
+    BufferAllocation(#[from] TransientWriteError),
 
     #[error("draw indexed command failure: {0}")]
     DrawIndexed(#[from] DrawIndexedError),
@@ -2,26 +2,29 @@
 
 use egui::{ClippedMesh, Pos2, Texture, TextureId};
 use slotmap::{DefaultKey, Key, KeyData, SlotMap};
-use vulkano::buffer::{BufferUsage, CpuBufferPool, TypedBufferAccess};
-use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
-};
+use vulkano::command_buffer::SecondaryAutoCommandBuffer;
 use vulkano::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
 use vulkano::device::Queue;
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
 use vulkano::image::{ImageDimensions, ImageViewAbstract, ImmutableImage, MipmapsCount};
 use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::cache::PipelineCache;
 use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
-use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::sampler::Sampler;
 use vulkano::sync::GpuFuture;
 
 use crate::{
     graphics::{
+        capture::{self, SubpassCapture},
         frame::ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
+        pipeline_stats,
+        pre_rotation::PreTransform,
         renderer::error::DescriptorSetCreationError,
+        transient::{TransientBufferPool, TransientUsage},
+        utils,
         vertex::UiVertex,
     },
     window::Size,
@@ -29,19 +32,22 @@
 
 pub mod error;
 
+mod tests;
+
 pub struct UiDrawSystem {
     /// Queue to render.
     graphics_queue: Arc<Queue>,
 
-    /// Buffer for all vertices of UI.
-    vertex_buffer: Arc<CpuBufferPool<UiVertex>>,
-
-    /// Buffer for all indices of vertices in UI element.
-    index_buffer: Arc<CpuBufferPool<u32>>,
-
     /// Graphics pipeline used for rendering of UI.
     pipeline: Arc<GraphicsPipeline>,
 
+    /// Cache which the pipeline is created with.
+    pipeline_cache: Arc<PipelineCache>,
+
+    /// Whether UI is drawn into the image of sRGB format,
+    /// so colors of `egui` are converted into linear space by the shader.
+    srgb_target: bool,
+
     /// Version of `egui` base texture.
     texture_version: u64,
 
@@ -51,72 +57,33 @@ pub struct UiDrawSystem {
     /// Collection of descriptor sets for user textures to be drawn in UI.
     user_texture_descriptor_sets: SlotMap<DefaultKey, Arc<dyn DescriptorSet + Send + Sync>>,
 
-    /// A sampler for textures used in UI rendering.
+    /// A sampler for `egui` base texture.
     sampler: Arc<Sampler>,
 }
 
 impl UiDrawSystem {
-    /// Creates new UI draw system.
+    /// Creates new UI draw system which draws into the image of given format
+    /// (format of the swapchain), creating its pipeline with given cache.
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        output_format: Format,
+        sampler: Arc<Sampler>,
+        pipeline_cache: Arc<PipelineCache>,
     ) -> Result<Self, UiDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(UiDrawSystemCreationError::QueueFamilyNotSupported);
         }
 
-        let device = graphics_queue.device().clone();
-        let pipeline = {
-            use crate::graphics::shader::ui::{fragment, vertex};
-
-            let vert_shader_module = vertex::Shader::load(device.clone())?;
-            let frag_shader_module = fragment::Shader::load(device.clone())?;
-
-            let blend = AttachmentBlend {
-                color_source: BlendFactor::One,
-                ..AttachmentBlend::alpha_blending()
-            };
-
-            Arc::new(
-                GraphicsPipeline::start()
-                    .vertex_input_single_buffer::<UiVertex>()
-                    .vertex_shader(vert_shader_module.main_entry_point(), ())
-                    .fragment_shader(frag_shader_module.main_entry_point(), ())
-                    .triangle_list()
-                    .viewports_scissors_dynamic(1)
-                    .cull_mode_disabled()
-                    .blend_collective(blend)
-                    .render_pass(subpass)
-                    .build(device.clone())?,
-            )
-        };
-
-        let vertex_buffer = Arc::new(CpuBufferPool::vertex_buffer(device.clone()));
-        let index_buffer = Arc::new(CpuBufferPool::new(
-            device.clone(),
-            BufferUsage::index_buffer(),
-        ));
-
-        let sampler = Sampler::new(
-            device.clone(),
-            Filter::Linear,
-            Filter::Linear,
-            MipmapMode::Linear,
-            SamplerAddressMode::ClampToEdge,
-            SamplerAddressMode::ClampToEdge,
-            SamplerAddressMode::ClampToEdge,
-            0.0,
-            1.0,
-            0.0,
-            0.0,
-        )?;
+        let srgb_target = utils::is_srgb(output_format);
+        let pipeline = Self::pipeline(&graphics_queue, subpass, &pipeline_cache, srgb_target)?;
 
         Ok(Self {
             graphics_queue,
-            vertex_buffer,
-            index_buffer,
             pipeline,
+            pipeline_cache,
+            srgb_target,
             sampler,
             texture_version: 0,
             texture_descriptor_set: None,
@@ -124,25 +91,77 @@ pub fn new(
         })
     }
 
+    fn pipeline(
+        graphics_queue: &Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: &Arc<PipelineCache>,
+        srgb_target: bool,
+    ) -> Result<Arc<GraphicsPipeline>, UiDrawSystemCreationError> {
+        use crate::graphics::shader::ui::{fragment, vertex};
+
+        let device = graphics_queue.device().clone();
+        let vert_shader_module = vertex::Shader::load(device.clone())?;
+        let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+        let blend = AttachmentBlend {
+            color_source: BlendFactor::One,
+            ..AttachmentBlend::alpha_blending()
+        };
+
+        // Colors are converted by the same shader, which is specialized for the target.
+        let constants = vertex::SpecializationConstants {
+            srgb_target: srgb_target as u32,
+        };
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<UiVertex>()
+            .vertex_shader(vert_shader_module.main_entry_point(), constants)
+            .fragment_shader(frag_shader_module.main_entry_point(), ())
+            .triangle_list()
+            .viewports_scissors_dynamic(1)
+            .cull_mode_disabled()
+            .blend_collective(blend)
+            .render_pass(subpass)
+            .build_with_cache(pipeline_cache.clone())
+            .build(device)?;
+        Ok(Arc::new(pipeline))
+    }
+
+    /// Recreates graphics pipeline for another subpass
+    /// (e.g. when UI is moved into post-process pass).
+    ///
+    /// Registered textures are kept, because layout of their descriptor sets is the same.
+    ///
+    pub fn set_subpass(&mut self, subpass: Subpass) -> Result<(), UiDrawSystemCreationError> {
+        self.pipeline = Self::pipeline(
+            &self.graphics_queue,
+            subpass,
+            &self.pipeline_cache,
+            self.srgb_target,
+        )?;
+        Ok(())
+    }
+
     fn image_descriptor_set(
         &self,
         image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
     ) -> Result<Arc<PersistentDescriptorSet>, DescriptorSetCreationError> {
         let layout = self.pipeline.layout().descriptor_set_layouts()[0].clone();
         let mut builder = PersistentDescriptorSet::start(layout);
         builder
-            .add_sampled_image(image_view, self.sampler.clone())
+            .add_sampled_image(image_view, sampler)
             .map_err(DescriptorSetCreationError::from)?;
         let set = builder.build().map_err(DescriptorSetCreationError::from)?;
         Ok(Arc::new(set))
     }
 
-    /// Registers new user texture to be drawn in UI.
+    /// Registers new user texture to be drawn in UI with given sampler.
     pub fn register_texture(
         &mut self,
         image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
     ) -> Result<TextureId, DescriptorSetCreationError> {
-        let descriptor_set = self.image_descriptor_set(image_view)?;
+        let descriptor_set = self.image_descriptor_set(image_view, sampler)?;
         let key = self.user_texture_descriptor_sets.insert(descriptor_set);
         let id = key.data().as_ffi();
         Ok(TextureId::User(id))
@@ -157,20 +176,39 @@ pub fn unregister_texture(&mut self, texture_id: TextureId) {
         }
     }
 
+    /// Unregisters all user textures to be drawn in UI.
+    pub fn clear_textures(&mut self) {
+        self.user_texture_descriptor_sets.clear();
+    }
+
+    /// Returns iterator over keys of registered user textures.
+    pub fn texture_keys(&self) -> impl Iterator<Item = DefaultKey> + '_ {
+        self.user_texture_descriptor_sets.keys()
+    }
+
     /// Builds a secondary command buffer that draws UI on the current subpass.
+    ///
+    /// UI is rotated by given pre-transform, so viewport size is expected
+    /// to be in native orientation of the surface.
+    ///
+    /// Vertices and indices are allocated from transient buffers of the current frame.
+    /// Recorded commands are also described in the capture, if any.
+    ///
     pub fn draw(
         &mut self,
+        transient: &mut TransientBufferPool,
         viewport_size: Size,
+        pre_transform: PreTransform,
         scale_factor: f32,
         meshes: Vec<ClippedMesh>,
         texture: Arc<Texture>,
+        mut capture: Option<&mut SubpassCapture>,
     ) -> Result<SecondaryAutoCommandBuffer, UiDrawError> {
         use crate::graphics::shader::ui::vertex;
 
-        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+        let mut builder = pipeline_stats::secondary_graphics(
             self.graphics_queue.device().clone(),
             self.graphics_queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
             self.pipeline.subpass().clone(),
         )?;
 
@@ -196,21 +234,33 @@ pub fn draw(
             };
 
             let image = ImageView::new(image)?;
-            let set = self.image_descriptor_set(image)?;
+            let set = self.image_descriptor_set(image, self.sampler.clone())?;
             self.texture_descriptor_set = Some(set);
         }
 
-        let width = viewport_size.width as f32;
-        let height = viewport_size.height as f32;
+        // UI is laid out in orientation of the window.
+        let [width, height] = pre_transform.swap_dimensions(viewport_size.into());
+        let (width, height) = (width as f32, height as f32);
+        let pre_rotation = pre_transform.matrix2();
         let push_constants = vertex::ty::PushConstants {
+            pre_rotation: [
+                pre_rotation.cols[0].x,
+                pre_rotation.cols[0].y,
+                pre_rotation.cols[1].x,
+                pre_rotation.cols[1].y,
+            ],
             screen_size: [width / scale_factor, height / scale_factor],
         };
 
-        for ClippedMesh(rect, mesh) in meshes {
-            // Nothing to draw if we don't have vertices & indices
-            if mesh.vertices.is_empty() || mesh.indices.is_empty() {
-                continue;
-            }
+        // Nothing to draw if we don't have vertices & indices
+        let meshes: Vec<_> = meshes
+            .into_iter()
+            .filter(|ClippedMesh(_, mesh)| !mesh.vertices.is_empty() && !mesh.indices.is_empty())
+            .collect();
+        if meshes.is_empty() {
+            return Ok(builder.build()?);
+        }
+        for (index, ClippedMesh(rect, mesh)) in meshes.into_iter().enumerate() {
             let scissor = {
                 let min = rect.min;
                 let min = Pos2 {
@@ -230,43 +280,62 @@ pub fn draw(
                     x: max.x.clamp(min.x, width),
                     y: max.y.clamp(min.y, height),
                 };
+                // Scissor is given in pixels of the image, not of the window.
+                let (min, max) =
+                    pre_transform.transform_rect([min.x, min.y], [max.x, max.y], [width, height]);
                 Scissor {
-                    origin: [min.x.round() as u32, min.y.round() as u32],
+                    origin: [min[0].round() as u32, min[1].round() as u32],
                     dimensions: [
-                        (max.x.round() - min.x) as u32,
-                        (max.y.round() - min.y) as u32,
+                        (max[0].round() - min[0]) as u32,
+                        (max[1].round() - min[1]) as u32,
                     ],
                 }
             };
+            // Some drivers dislike zero-area scissors, and nothing is visible anyway.
+            if scissor.dimensions.contains(&0) {
+                continue;
+            }
 
-            let chunk = mesh.vertices.into_iter().map(UiVertex::from);
-            let vertex_buffer = self.vertex_buffer.chunk(chunk)?;
-
-            let chunk = mesh.indices.into_iter();
-            let index_buffer = self.index_buffer.chunk(chunk)?;
+            let vertices: Vec<_> = mesh.vertices.into_iter().map(UiVertex::from).collect();
+            let vertices = transient.write(TransientUsage::Vertex, &vertices)?;
+            let indices = transient.write(TransientUsage::Index, &mesh.indices)?;
 
             let viewport = Viewport {
                 origin: [0.0, 0.0],
                 dimensions: [viewport_size.width as f32, viewport_size.height as f32],
                 depth_range: 0.0..1.0,
             };
-            let descriptor_sets = match mesh.texture_id {
-                TextureId::Egui => self.texture_descriptor_set.as_ref().unwrap().clone(),
+            let (descriptor_sets, texture_name) = match mesh.texture_id {
+                TextureId::Egui => {
+                    let set = self.texture_descriptor_set.as_ref().unwrap().clone();
+                    (set, capture::role_name("UiTexture", "egui"))
+                }
                 TextureId::User(id) => {
                     let key_data = KeyData::from_ffi(id);
                     let key = DefaultKey::from(key_data);
-                    self.user_texture_descriptor_sets
+                    let set = self
+                        .user_texture_descriptor_sets
                         .get(key)
                         .expect("User texture was unregistered, but still in use!")
-                        .clone()
+                        .clone();
+                    (set, capture::key_name("UiTexture", key))
                 }
             };
+            let index_count = mesh.indices.len() as u32;
+            if let Some(capture) = capture.as_deref_mut() {
+                capture.set_viewport(viewport.origin, viewport.dimensions);
+                capture.bind_pipeline(capture::role_name("GraphicsPipeline", "ui"));
+                capture.bind_descriptor_sets(0, vec![texture_name]);
+                capture.push_constants(&push_constants);
+                let mesh = capture::role_name("UiMesh", index);
+                capture.draw(mesh, None, index_count, true, 1);
+            }
             builder
                 .set_viewport(0, std::iter::once(viewport))
                 .set_scissor(0, std::iter::once(scissor))
                 .bind_pipeline_graphics(self.pipeline.clone())
-                .bind_vertex_buffers(0, vertex_buffer)
-                .bind_index_buffer(index_buffer.clone())
+                .bind_vertex_buffers(0, vertices.slice::<UiVertex>())
+                .bind_index_buffer(indices.slice::<u32>())
                 .bind_descriptor_sets(
                     PipelineBindPoint::Graphics,
                     self.pipeline.layout().clone(),
@@ -274,7 +343,7 @@ pub fn draw(
                     descriptor_sets,
                 )
                 .push_constants(self.pipeline.layout().clone(), 0, push_constants)
-                .draw_indexed(index_buffer.len() as u32, 1, 0, 0, 0)?;
+                .draw_indexed(index_count, 1, 0, 0, 0)?;
         }
 
         Ok(builder.build()?)
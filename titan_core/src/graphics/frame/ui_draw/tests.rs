@@ -0,0 +1,52 @@
+#![cfg(test)]
+
+use epaint::{Color32, Pos2, Vertex};
+use vulkano::format::Format;
+
+use crate::graphics::{utils, vertex::UiVertex};
+use crate::math::Color;
+
+/// Color which is written into the target by the UI shader for the vertex color of `egui`.
+fn target_bytes(color: Color32, format: Format) -> [u8; 4] {
+    let vertex = Vertex {
+        pos: Pos2::new(0.0, 0.0),
+        uv: Pos2::new(0.0, 0.0),
+        color,
+    };
+    let [red, green, blue, alpha] = UiVertex::from(vertex).color.into();
+    if utils::is_srgb(format) {
+        // Shader converts colors into linear space, then they are encoded by the hardware.
+        Color::from_srgb(red, green, blue, alpha).into()
+    } else {
+        [red, green, blue, alpha].map(|component| (component * 255.0).round() as u8)
+    }
+}
+
+#[test]
+fn test_srgb_target() {
+    assert!(utils::is_srgb(Format::B8G8R8A8_SRGB));
+    assert!(utils::is_srgb(Format::R8G8B8A8_SRGB));
+    assert!(!utils::is_srgb(Format::B8G8R8A8_UNORM));
+    assert!(!utils::is_srgb(Format::R16G16B16A16_SFLOAT));
+}
+
+#[test]
+fn test_ui_colors_match_on_srgb_and_unorm_targets() {
+    let gray = Color32::from_gray(128);
+    assert_eq!(
+        target_bytes(gray, Format::B8G8R8A8_SRGB),
+        [128, 128, 128, 255]
+    );
+    assert_eq!(
+        target_bytes(gray, Format::B8G8R8A8_UNORM),
+        [128, 128, 128, 255]
+    );
+    for value in 0..=255 {
+        let color = Color32::from_rgba_premultiplied(value, value / 2, 255 - value, 255);
+        let srgb = target_bytes(color, Format::B8G8R8A8_SRGB);
+        let unorm = target_bytes(color, Format::B8G8R8A8_UNORM);
+        for (srgb, unorm) in srgb.into_iter().zip(unorm) {
+            assert!((srgb as i16 - unorm as i16).abs() <= 1, "{:?}", color);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Synchronization of frames which are rendered concurrently.
+//!
+//! CPU records the next frame while GPU still renders previous ones,
+//! but no more than *frames in flight* of them: more frames increase throughput
+//! when CPU and GPU take turns unevenly, fewer frames decrease input latency.
+//! Semaphores between acquisition, rendering and presentation are owned
+//! by GPU futures of the frame, and fences of submitted frames are kept here.
+
+use std::collections::VecDeque;
+
+mod tests;
+
+/// Count of frames in flight which is used by default (double buffering).
+pub const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+
+/// Max count of frames in flight.
+pub const MAX_FRAMES_IN_FLIGHT: usize = 4;
+
+/// Fences of submitted frames which limit count of frames in flight
+/// and guard resources of each swapchain image.
+pub(crate) struct FrameSync<F> {
+    frames_in_flight: usize,
+    /// Fences of frames which could be still rendered, from the oldest one.
+    in_flight: VecDeque<F>,
+    /// Fences of the last frames rendered into each swapchain image.
+    image_fences: Vec<Option<F>>,
+}
+
+impl<F> FrameSync<F>
+where
+    F: Clone,
+{
+    /// Creates synchronization of given count of frames in flight
+    /// (clamped to `1..=MAX_FRAMES_IN_FLIGHT`) for given count of swapchain images.
+    pub fn new(frames_in_flight: usize, image_count: usize) -> Self {
+        Self {
+            frames_in_flight: frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT),
+            in_flight: VecDeque::new(),
+            image_fences: vec![None; image_count],
+        }
+    }
+
+    /// Count of frames which could be rendered concurrently.
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames_in_flight
+    }
+
+    /// Sets count of frames in flight (clamped to `1..=MAX_FRAMES_IN_FLIGHT`)
+    /// since the next frame.
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) {
+        self.frames_in_flight = frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT);
+        self.forget_finished();
+    }
+
+    /// Fence of the frame which must be finished before the next frame is begun,
+    /// or `None` if there are less frames in flight than allowed.
+    pub fn frame_fence(&self) -> Option<&F> {
+        (self.in_flight.len() >= self.frames_in_flight)
+            .then(|| self.in_flight.front())
+            .flatten()
+    }
+
+    /// Fence of the last frame which was rendered into the swapchain image, if any.
+    ///
+    /// Resources of the image (like its uniform buffers) could be written
+    /// only after the frame is finished.
+    ///
+    pub fn image_fence(&self, image_index: usize) -> Option<&F> {
+        self.image_fences.get(image_index)?.as_ref()
+    }
+
+    /// Remembers fence of the frame which was submitted for the swapchain image.
+    pub fn submitted(&mut self, image_index: usize, fence: F) {
+        if image_index >= self.image_fences.len() {
+            self.image_fences.resize(image_index + 1, None);
+        }
+        self.image_fences[image_index] = Some(fence.clone());
+        self.in_flight.push_back(fence);
+        self.forget_finished();
+    }
+
+    /// Forgets frames which are waited by later frames only.
+    fn forget_finished(&mut self) {
+        while self.in_flight.len() > self.frames_in_flight {
+            self.in_flight.pop_front();
+        }
+    }
+}
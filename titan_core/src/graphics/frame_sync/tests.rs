@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_frames_in_flight() {
+    let mut sync = FrameSync::new(2, 3);
+    assert_eq!(sync.frames_in_flight(), 2);
+    assert_eq!(sync.frame_fence(), None);
+
+    // Frames are rendered into images in order of acquisition.
+    sync.submitted(0, 10);
+    assert_eq!(sync.frame_fence(), None);
+    sync.submitted(1, 11);
+    assert_eq!(sync.frame_fence(), Some(&10));
+    sync.submitted(2, 12);
+    assert_eq!(sync.frame_fence(), Some(&11));
+    assert_eq!(sync.image_fence(0), Some(&10));
+    sync.submitted(0, 13);
+    assert_eq!(sync.frame_fence(), Some(&12));
+    assert_eq!(sync.image_fence(0), Some(&13));
+    assert_eq!(sync.image_fence(3), None);
+
+    // One frame in flight waits for the previous frame before each frame.
+    sync.set_frames_in_flight(1);
+    assert_eq!(sync.frame_fence(), Some(&13));
+    sync.set_frames_in_flight(3);
+    assert_eq!(sync.frame_fence(), None);
+
+    // Swapchain could be recreated with more images.
+    sync.submitted(4, 14);
+    assert_eq!(sync.image_fence(4), Some(&14));
+}
+
+#[test]
+fn test_frames_in_flight_clamped() {
+    let sync = FrameSync::<u32>::new(0, 2);
+    assert_eq!(sync.frames_in_flight(), 1);
+    let sync = FrameSync::<u32>::new(100, 2);
+    assert_eq!(sync.frames_in_flight(), MAX_FRAMES_IN_FLIGHT);
+}
@@ -0,0 +1,207 @@
+//! Timestamp queries which measure time spent by the device on render passes of each frame.
+//!
+//! Like pipeline statistics, timestamps of the frame are read one or more frames later
+//! without waiting for the device, so the query does not stall rendering.
+
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, PrimaryAutoCommandBuffer, WriteTimestampError,
+};
+use vulkano::device::Queue;
+use vulkano::query::{QueryPool, QueryPoolCreationError, QueryResultFlags, QueryType};
+use vulkano::sync::PipelineStage;
+
+use super::pipeline_stats::QuerySlots;
+
+mod tests;
+
+/// Count of timestamps written by one frame (one for each [`Timestamp`]).
+const TIMESTAMPS_PER_FRAME: usize = 3;
+
+/// Time spent by the device on render passes of the frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct GpuTimings {
+    /// Time of the main render pass, where the scene is drawn.
+    ///
+    /// If the scene is not post-processed, UI is drawn in this pass too.
+    ///
+    pub main_pass: Duration,
+    /// Time of the post-process render pass, where UI is drawn over the processed scene.
+    ///
+    /// It is zero if the scene is not post-processed.
+    ///
+    pub post_pass: Duration,
+}
+
+impl GpuTimings {
+    /// Time of all render passes of the frame.
+    pub fn total(&self) -> Duration {
+        self.main_pass + self.post_pass
+    }
+
+    /// Creates timings from timestamps of the query (in order of [`Timestamp`])
+    /// with given count of valid bits and period (in nanoseconds per tick).
+    fn from_results(results: [u64; TIMESTAMPS_PER_FRAME], valid_bits: u32, period: f32) -> Self {
+        let mask = u64::MAX >> (u64::BITS - valid_bits.clamp(1, u64::BITS));
+        // Timestamps could wrap around between writes.
+        let elapsed = |start: u64, end: u64| {
+            let ticks = end.wrapping_sub(start) & mask;
+            Duration::from_nanos((ticks as f64 * period as f64) as u64)
+        };
+        let [start, main_end, post_end] = results;
+        Self {
+            main_pass: elapsed(start, main_end),
+            post_pass: elapsed(main_end, post_end),
+        }
+    }
+}
+
+/// Point of the frame where the timestamp is written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Timestamp {
+    /// Before the main render pass.
+    FrameStart,
+    /// After the main render pass.
+    MainPassEnd,
+    /// After the post-process render pass, or right after the main one
+    /// if the scene is not post-processed.
+    PostPassEnd,
+}
+
+/// Returns `true` if timestamps could be written on the queue.
+pub(crate) fn supported(queue: &Queue) -> bool {
+    queue.family().timestamp_valid_bits().is_some()
+}
+
+/// Queries of the pool which are written by one frame.
+#[derive(Debug, Clone)]
+pub(crate) struct TimestampQuery {
+    pub pool: Arc<QueryPool>,
+    pub index: u32,
+}
+
+impl TimestampQuery {
+    /// Range of queries of the frame, which must be reset before the frame writes them.
+    pub fn range(&self) -> Range<u32> {
+        let first = self.query(Timestamp::FrameStart);
+        first..first + TIMESTAMPS_PER_FRAME as u32
+    }
+
+    /// Writes the timestamp after all previous commands of the frame are finished
+    /// (or before any of them for [`Timestamp::FrameStart`]).
+    ///
+    /// Must be called outside of any render pass, because render passes of the frame
+    /// only execute secondary command buffers.
+    ///
+    /// # Safety
+    ///
+    /// Queries must be reset in the same command buffer and not be used by other
+    /// command buffers until results of the previous frame which used them were read.
+    ///
+    pub unsafe fn write(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        timestamp: Timestamp,
+    ) -> Result<(), WriteTimestampError> {
+        let stage = match timestamp {
+            Timestamp::FrameStart => PipelineStage::TopOfPipe,
+            _ => PipelineStage::BottomOfPipe,
+        };
+        builder.write_timestamp(self.pool.clone(), self.query(timestamp), stage)?;
+        Ok(())
+    }
+
+    /// Index of the query of the timestamp in the pool.
+    fn query(&self, timestamp: Timestamp) -> u32 {
+        self.index * TIMESTAMPS_PER_FRAME as u32 + timestamp as u32
+    }
+}
+
+/// Pool of timestamp queries, with queries of each frame in flight.
+pub(crate) struct GpuProfiler {
+    pool: Arc<QueryPool>,
+    slots: QuerySlots,
+    valid_bits: u32,
+    /// Count of nanoseconds of one tick of timestamps.
+    period: f32,
+    latest: Option<GpuTimings>,
+}
+
+impl GpuProfiler {
+    /// Creates pool with queries for each of `frames` frames in flight,
+    /// which are written on the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if timestamps are not [supported](self::supported) by the queue.
+    ///
+    pub fn new(queue: &Queue, frames: u32) -> Result<Self, QueryPoolCreationError> {
+        let valid_bits = queue
+            .family()
+            .timestamp_valid_bits()
+            .expect("timestamps must be supported by the queue");
+        let device = queue.device();
+        let period = device.physical_device().properties().timestamp_period;
+        let count = frames * TIMESTAMPS_PER_FRAME as u32;
+        let pool = QueryPool::new(device.clone(), QueryType::Timestamp, count)?;
+        Ok(Self {
+            pool,
+            slots: QuerySlots::new(frames),
+            valid_bits,
+            period,
+            latest: None,
+        })
+    }
+
+    /// Reads results of finished frames without waiting for the device
+    /// and returns timings of the latest one.
+    pub fn poll(&mut self) -> Option<GpuTimings> {
+        let (pool, valid_bits, period) = (&self.pool, self.valid_bits, self.period);
+        let finished = self.slots.poll(|index| {
+            let mut results = [0u64; TIMESTAMPS_PER_FRAME];
+            let flags = QueryResultFlags {
+                wait: false,
+                with_availability: false,
+                partial: false,
+            };
+            let first = index * TIMESTAMPS_PER_FRAME as u32;
+            let range = pool.queries_range(first..first + TIMESTAMPS_PER_FRAME as u32)?;
+            match range.get_results(&mut results, flags) {
+                Ok(true) => Some(GpuTimings::from_results(results, valid_bits, period)),
+                Ok(false) => None,
+                Err(error) => {
+                    log::warn!("failed to read timestamps of the frame: {}", error);
+                    None
+                }
+            }
+        });
+        if let Some(timings) = finished {
+            self.latest = Some(timings);
+        }
+        self.latest
+    }
+
+    /// Queries which should be written in the next frame.
+    ///
+    /// Returns `None` if results of all queries were not read yet,
+    /// so the frame is not measured instead of waiting for the device.
+    ///
+    pub fn next_query(&self) -> Option<TimestampQuery> {
+        let index = self.slots.next()?;
+        Some(TimestampQuery {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Marks queries as submitted, so their results will be read later.
+    ///
+    /// Queries of the frame which was not submitted are reused by the next frame.
+    ///
+    pub fn submit(&mut self, query: &TimestampQuery) {
+        self.slots.submit(query.index)
+    }
+}
@@ -0,0 +1,31 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_from_results() {
+    let timings = GpuTimings::from_results([100, 1100, 1600], 64, 2.0);
+    assert_eq!(
+        timings,
+        GpuTimings {
+            main_pass: Duration::from_nanos(2000),
+            post_pass: Duration::from_nanos(1000),
+        }
+    );
+    assert_eq!(timings.total(), Duration::from_nanos(3000));
+
+    // Post-process pass ends right after the main one if there is none.
+    let timings = GpuTimings::from_results([10, 20, 20], 64, 1.0);
+    assert_eq!(timings.post_pass, Duration::ZERO);
+}
+
+#[test]
+fn test_from_results_wraps() {
+    // Only lower 8 bits of timestamps are valid, so they wrap around after 255.
+    let timings = GpuTimings::from_results([250, 4, 10], 8, 1.0);
+    assert_eq!(timings.main_pass, Duration::from_nanos(10));
+    assert_eq!(timings.post_pass, Duration::from_nanos(6));
+
+    let timings = GpuTimings::from_results([u64::MAX, 1, 2], 64, 1.0);
+    assert_eq!(timings.main_pass, Duration::from_nanos(2));
+}
@@ -0,0 +1,146 @@
+//! Declarations of images and passes of the render graph.
+
+use vulkano::format::Format;
+
+use crate::math::Color;
+
+/// Identifier of the image which was added to the [render graph](super::RenderGraph).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceId(pub(crate) usize);
+
+/// Identifier of the pass which was added to the [render graph](super::RenderGraph).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PassId(pub(crate) usize);
+
+/// Size of the graph image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageSize {
+    /// Fixed size (in pixels), for example, of the shadow map.
+    Absolute([u32; 2]),
+    /// Size which is scaled from the extent the graph is prepared for.
+    Relative(f32),
+}
+
+impl ImageSize {
+    /// Size of the image (in pixels) for given extent, at least one pixel.
+    pub fn resolve(self, extent: [u32; 2]) -> [u32; 2] {
+        match self {
+            Self::Absolute(size) => size.map(|dimension| dimension.max(1)),
+            Self::Relative(scale) => extent.map(|dimension| {
+                let scaled = (dimension as f32 * scale).round() as u32;
+                scaled.max(1)
+            }),
+        }
+    }
+}
+
+impl Default for ImageSize {
+    fn default() -> Self {
+        Self::Relative(1.0)
+    }
+}
+
+/// Description of the image which is created and owned by the render graph.
+///
+/// Whether it is a color or depth image is derived from passes which write it.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphImage {
+    /// Name of the image which is used in errors and logs.
+    pub name: String,
+    pub format: Format,
+    pub size: ImageSize,
+}
+
+impl GraphImage {
+    pub fn new(name: impl Into<String>, format: Format, size: ImageSize) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            size,
+        }
+    }
+}
+
+/// How the pass accesses the image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Access {
+    /// Written as color attachment, cleared first if the color is set.
+    Color(Option<Color>),
+    /// Written as depth attachment, cleared first if the depth is set.
+    Depth(Option<f32>),
+    /// Sampled by shaders of the pass.
+    Sampled,
+}
+
+impl Access {
+    /// Checks if the image is written by the pass.
+    pub fn is_write(self) -> bool {
+        !matches!(self, Self::Sampled)
+    }
+
+    /// Checks if previous contents of the image are discarded by the pass.
+    pub fn is_clear(self) -> bool {
+        matches!(self, Self::Color(Some(_)) | Self::Depth(Some(_)))
+    }
+}
+
+/// Declaration of the pass: which images it reads and writes.
+///
+/// Attachments are bound to the framebuffer of the pass in declaration order.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphPass {
+    name: String,
+    accesses: Vec<(ResourceId, Access)>,
+}
+
+impl GraphPass {
+    pub(crate) fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            accesses: Vec::new(),
+        }
+    }
+
+    /// Name of the pass which is used in errors and logs.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Images which are accessed by the pass in declaration order.
+    pub fn accesses(&self) -> &[(ResourceId, Access)] {
+        &self.accesses
+    }
+
+    /// Writes the image as color attachment, keeping its previous contents.
+    pub fn write_color(&mut self, image: ResourceId) -> &mut Self {
+        self.access(image, Access::Color(None))
+    }
+
+    /// Clears the image with the color and writes it as color attachment.
+    pub fn clear_color(&mut self, image: ResourceId, color: Color) -> &mut Self {
+        self.access(image, Access::Color(Some(color)))
+    }
+
+    /// Writes the image as depth attachment, keeping its previous contents.
+    pub fn write_depth(&mut self, image: ResourceId) -> &mut Self {
+        self.access(image, Access::Depth(None))
+    }
+
+    /// Clears the image with the depth and writes it as depth attachment.
+    pub fn clear_depth(&mut self, image: ResourceId, depth: f32) -> &mut Self {
+        self.access(image, Access::Depth(Some(depth)))
+    }
+
+    /// Samples the image which was written by one of previous passes.
+    pub fn read_texture(&mut self, image: ResourceId) -> &mut Self {
+        self.access(image, Access::Sampled)
+    }
+
+    /// Accesses the image in given way.
+    pub fn access(&mut self, image: ResourceId, access: Access) -> &mut Self {
+        self.accesses.push((image, access));
+        self
+    }
+}
@@ -0,0 +1,411 @@
+//! Render graph of offscreen passes: shadow maps, post-processing chains and so on.
+//!
+//! Passes declare which images they write as attachments and which ones they sample,
+//! and the graph derives everything else: which passes contribute to exported images,
+//! load and store operations of attachments, layout transitions between passes
+//! and render passes with framebuffers themselves.
+//!
+//! Passes are executed in declaration order, so each pass sees contents of images
+//! written by passes declared before it. Layouts are transitioned by render passes
+//! at their end, and barriers of the [plan](GraphPlan) are inserted by vulkano,
+//! which synchronizes commands that access the same images.
+//!
+//! Graph is intended to be owned by the [render hook](super::RenderHook)
+//! of [`HookStage::BeforeMainPass`](super::HookStage::BeforeMainPass):
+//! the hook prepares the graph for the extent of the frame and records it,
+//! then exported images could be sampled by the main pass.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, AutoCommandBufferBuilderContextError, BeginRenderPassError,
+    PrimaryAutoCommandBuffer, SubpassContents,
+};
+use vulkano::device::Device;
+use vulkano::format::ClearValue;
+use vulkano::image::view::{ImageView, ImageViewCreationError};
+use vulkano::image::{AttachmentImage, ImageCreationError, ImageLayout, ImageUsage, SampleCount};
+use vulkano::render_pass::{
+    AttachmentDesc, Framebuffer, FramebufferAbstract, FramebufferCreationError, LoadOp, RenderPass,
+    RenderPassCreationError, RenderPassDesc, StoreOp, Subpass, SubpassDesc,
+};
+
+use super::hook::HookError;
+
+pub use self::desc::{Access, GraphImage, GraphPass, ImageSize, PassId, ResourceId};
+pub use self::plan::{AttachmentKind, AttachmentPlan, Barrier, GraphPlan, ImagePlan, PassPlan};
+
+mod desc;
+mod plan;
+mod tests;
+
+type DynFramebuffer = Arc<dyn FramebufferAbstract + Send + Sync>;
+
+/// Function which records commands of the pass inside of its render pass.
+pub type PassRecord = dyn FnMut(
+    &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    &PassContext,
+) -> Result<(), HookError>;
+
+/// Error that can happen when declarations of the graph are compiled.
+#[derive(Debug, Error, PartialEq)]
+pub enum RenderGraphError {
+    #[error("render graph exports no images")]
+    NoOutputs,
+
+    #[error("image #{0} does not belong to the render graph")]
+    UnknownImage(usize),
+
+    #[error("pass `{0}` writes no attachments")]
+    NoAttachments(String),
+
+    #[error("pass `{0}` writes more than one depth attachment")]
+    MultipleDepth(String),
+
+    #[error("pass `{0}` writes attachments of different sizes")]
+    SizeMismatch(String),
+
+    #[error("image `{image}` is accessed more than once by pass `{pass}`")]
+    DuplicateAccess { pass: String, image: String },
+
+    #[error("pass `{pass}` samples image `{image}` before any pass writes it")]
+    ReadBeforeWrite { pass: String, image: String },
+
+    #[error("image `{0}` is written both as color and depth attachment")]
+    AttachmentKindMismatch(String),
+
+    #[error("exported image `{0}` is not written by any pass")]
+    NotWritten(String),
+}
+
+/// Error that can happen when the graph is prepared for the frame.
+#[derive(Debug, Error)]
+pub enum GraphPrepareError {
+    #[error("render graph compilation failure: {0}")]
+    Graph(#[from] RenderGraphError),
+
+    #[error("render pass creation failure: {0}")]
+    RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("image creation failure: {0}")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("image view creation failure: {0}")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("framebuffer creation failure: {0}")]
+    FramebufferCreation(#[from] FramebufferCreationError),
+}
+
+/// Error that can happen when commands of the graph are recorded.
+#[derive(Debug, Error)]
+pub enum GraphRecordError {
+    #[error("render graph was not prepared")]
+    NotPrepared,
+
+    #[error("begin render pass command failure: {0}")]
+    BeginRenderPass(#[from] BeginRenderPassError),
+
+    #[error("command buffer builder misuse: {0}")]
+    WrongUsage(#[from] AutoCommandBufferBuilderContextError),
+
+    #[error("pass `{pass}` failed to record: {error}")]
+    Pass { pass: String, error: HookError },
+}
+
+/// State of the pass which is given to its record function.
+pub struct PassContext<'a> {
+    /// Name of the pass.
+    pub name: &'a str,
+    /// Subpass which pipelines of the pass must be created for.
+    pub subpass: Subpass,
+    /// Size of attachments of the pass (in pixels).
+    pub extent: [u32; 2],
+    images: &'a [Option<Arc<AttachmentImage>>],
+}
+
+impl PassContext<'_> {
+    /// Image of the graph, for example, to sample it.
+    ///
+    /// Images of culled passes are not created.
+    ///
+    pub fn image(&self, image: ResourceId) -> Option<&Arc<AttachmentImage>> {
+        self.images.get(image.0)?.as_ref()
+    }
+}
+
+/// Images and framebuffers of the graph for one extent.
+struct Targets {
+    extent: [u32; 2],
+    images: Vec<Option<Arc<AttachmentImage>>>,
+    framebuffers: Vec<DynFramebuffer>,
+}
+
+/// Plan of the graph with render passes of its passes.
+struct Compiled {
+    plan: GraphPlan,
+    render_passes: Vec<Arc<RenderPass>>,
+    targets: Option<Targets>,
+}
+
+/// Graph of render passes which is compiled into render passes and images.
+///
+/// Changes of declarations take effect when the graph is prepared next time.
+///
+#[derive(Default)]
+pub struct RenderGraph {
+    images: Vec<GraphImage>,
+    passes: Vec<GraphPass>,
+    records: Vec<Box<PassRecord>>,
+    outputs: Vec<ResourceId>,
+    compiled: Option<Compiled>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds image which is created and owned by the graph.
+    pub fn add_image(&mut self, image: GraphImage) -> ResourceId {
+        self.compiled = None;
+        self.images.push(image);
+        ResourceId(self.images.len() - 1)
+    }
+
+    /// Adds pass with the function which records its commands.
+    ///
+    /// Images which are accessed by the pass are declared with returned pass.
+    ///
+    pub fn add_pass<F>(&mut self, name: impl Into<String>, record: F) -> &mut GraphPass
+    where
+        F: FnMut(
+                &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+                &PassContext,
+            ) -> Result<(), HookError>
+            + 'static,
+    {
+        self.compiled = None;
+        self.records.push(Box::new(record));
+        self.passes.push(GraphPass::new(name));
+        self.passes.last_mut().unwrap()
+    }
+
+    /// Exports the image from the graph, so it is kept after the graph was executed.
+    ///
+    /// Passes which do not contribute to exported images are culled.
+    /// Exported images are left in the `SHADER_READ_ONLY_OPTIMAL` layout.
+    ///
+    pub fn export(&mut self, image: ResourceId) {
+        self.compiled = None;
+        if !self.outputs.contains(&image) {
+            self.outputs.push(image);
+        }
+    }
+
+    /// Identifier of the pass with given name.
+    pub fn pass_id(&self, name: &str) -> Option<PassId> {
+        let index = self.passes.iter().position(|pass| pass.name() == name)?;
+        Some(PassId(index))
+    }
+
+    /// Validates declarations of the graph and compiles them into the plan.
+    pub fn compile(&self) -> Result<GraphPlan, RenderGraphError> {
+        plan::compile(&self.images, &self.passes, &self.outputs)
+    }
+
+    /// Plan of the graph which was compiled when the graph was prepared.
+    pub fn plan(&self) -> Option<&GraphPlan> {
+        self.compiled.as_ref().map(|compiled| &compiled.plan)
+    }
+
+    /// Subpass of the pass which its pipelines must be created for,
+    /// if the graph was prepared and the pass was not culled.
+    pub fn subpass(&self, pass: PassId) -> Option<Subpass> {
+        let compiled = self.compiled.as_ref()?;
+        let index = compiled
+            .plan
+            .passes
+            .iter()
+            .position(|plan| plan.pass == pass)?;
+        Subpass::from(compiled.render_passes[index].clone(), 0)
+    }
+
+    /// Image of the graph, if the graph was prepared and the image is used by some pass.
+    ///
+    /// Images are recreated when the extent changes, so they should be retrieved
+    /// again after each preparation.
+    ///
+    pub fn image(&self, image: ResourceId) -> Option<&Arc<AttachmentImage>> {
+        let targets = self.compiled.as_ref()?.targets.as_ref()?;
+        targets.images.get(image.0)?.as_ref()
+    }
+
+    /// Prepares the graph for the frame with given extent.
+    ///
+    /// Graph is compiled and render passes are created after declarations changed;
+    /// images and framebuffers are (re)created when the extent changes.
+    ///
+    pub fn prepare(
+        &mut self,
+        device: &Arc<Device>,
+        extent: [u32; 2],
+    ) -> Result<(), GraphPrepareError> {
+        if self.compiled.is_none() {
+            let plan = self.compile()?;
+            log::debug!(
+                "render graph compiled: {} passes, {} culled, {} barriers",
+                plan.passes.len(),
+                plan.culled.len(),
+                plan.barriers.len(),
+            );
+            let render_passes = plan
+                .passes
+                .iter()
+                .map(|pass| self.create_render_pass(device, pass))
+                .collect::<Result<_, _>>()?;
+            self.compiled = Some(Compiled {
+                plan,
+                render_passes,
+                targets: None,
+            });
+        }
+
+        let compiled = self.compiled.as_mut().unwrap();
+        if let Some(targets) = &compiled.targets {
+            if targets.extent == extent {
+                return Ok(());
+            }
+        }
+        compiled.targets = None;
+
+        let mut images = vec![None; self.images.len()];
+        for image in &compiled.plan.images {
+            let desc = &self.images[image.image.0];
+            let usage = match image.kind {
+                AttachmentKind::Color => ImageUsage::color_attachment(),
+                AttachmentKind::Depth => ImageUsage::depth_stencil_attachment(),
+            };
+            let usage = ImageUsage {
+                sampled: image.sampled,
+                ..usage
+            };
+            let size = desc.size.resolve(extent);
+            let created = AttachmentImage::with_usage(device.clone(), size, desc.format, usage)?;
+            images[image.image.0] = Some(created);
+        }
+
+        let mut framebuffers = Vec::with_capacity(compiled.plan.passes.len());
+        for (pass, render_pass) in compiled.plan.passes.iter().zip(&compiled.render_passes) {
+            let mut builder = Framebuffer::start(render_pass.clone()).boxed();
+            for attachment in &pass.attachments {
+                let image = images[attachment.image.0].clone().unwrap();
+                builder = builder.add(ImageView::new(image)?)?.boxed();
+            }
+            let framebuffer: DynFramebuffer = Arc::new(builder.build()?);
+            framebuffers.push(framebuffer);
+        }
+
+        compiled.targets = Some(Targets {
+            extent,
+            images,
+            framebuffers,
+        });
+        Ok(())
+    }
+
+    /// Records render passes of the graph in execution order.
+    ///
+    /// Commands must be recorded outside of any render pass.
+    ///
+    pub fn record(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    ) -> Result<(), GraphRecordError> {
+        let compiled = self
+            .compiled
+            .as_ref()
+            .ok_or(GraphRecordError::NotPrepared)?;
+        let targets = compiled
+            .targets
+            .as_ref()
+            .ok_or(GraphRecordError::NotPrepared)?;
+
+        let passes = compiled.plan.passes.iter().zip(&compiled.render_passes);
+        for ((pass, render_pass), framebuffer) in passes.zip(&targets.framebuffers) {
+            let accesses = self.passes[pass.pass.0].accesses();
+            let clear_values = accesses.iter().filter_map(|&(_, access)| match access {
+                Access::Color(Some(color)) => Some(ClearValue::Float(color.into())),
+                Access::Depth(Some(depth)) => Some(ClearValue::Depth(depth)),
+                Access::Color(None) | Access::Depth(None) => Some(ClearValue::None),
+                Access::Sampled => None,
+            });
+            builder.begin_render_pass(
+                framebuffer.clone(),
+                SubpassContents::Inline,
+                clear_values,
+            )?;
+
+            let size = framebuffer.dimensions();
+            let context = PassContext {
+                name: &pass.name,
+                subpass: Subpass::from(render_pass.clone(), 0).unwrap(),
+                extent: [size[0], size[1]],
+                images: &targets.images,
+            };
+            let record = &mut self.records[pass.pass.0];
+            record(builder, &context).map_err(|error| GraphRecordError::Pass {
+                pass: pass.name.clone(),
+                error,
+            })?;
+            builder.end_render_pass()?;
+        }
+        Ok(())
+    }
+
+    /// Creates render pass with one subpass which writes attachments of the pass.
+    fn create_render_pass(
+        &self,
+        device: &Arc<Device>,
+        pass: &PassPlan,
+    ) -> Result<Arc<RenderPass>, RenderPassCreationError> {
+        let attachments = pass
+            .attachments
+            .iter()
+            .map(|attachment| AttachmentDesc {
+                format: self.images[attachment.image.0].format,
+                samples: SampleCount::Sample1,
+                load: attachment.load,
+                store: attachment.store,
+                stencil_load: LoadOp::DontCare,
+                stencil_store: StoreOp::DontCare,
+                initial_layout: attachment.initial_layout,
+                final_layout: attachment.final_layout,
+            })
+            .collect();
+
+        let kinds = pass.attachments.iter().map(|attachment| attachment.kind);
+        let color_attachments = kinds
+            .clone()
+            .enumerate()
+            .filter(|(_, kind)| *kind == AttachmentKind::Color)
+            .map(|(index, _)| (index, ImageLayout::ColorAttachmentOptimal))
+            .collect();
+        let depth_stencil = kinds
+            .enumerate()
+            .find(|(_, kind)| *kind == AttachmentKind::Depth)
+            .map(|(index, _)| (index, ImageLayout::DepthStencilAttachmentOptimal));
+        let subpass = SubpassDesc {
+            color_attachments,
+            depth_stencil,
+            input_attachments: Vec::new(),
+            resolve_attachments: Vec::new(),
+            preserve_attachments: Vec::new(),
+        };
+
+        let desc = RenderPassDesc::new(attachments, vec![subpass], Vec::new());
+        let render_pass = RenderPass::new(device.clone(), desc)?;
+        Ok(Arc::new(render_pass))
+    }
+}
@@ -0,0 +1,286 @@
+//! Compilation of the render graph: culling of passes, load and store operations,
+//! layouts of attachments and barriers between passes.
+
+use std::collections::HashSet;
+
+use vulkano::image::ImageLayout;
+use vulkano::render_pass::{LoadOp, StoreOp};
+
+use super::{
+    desc::{Access, GraphImage, GraphPass, PassId, ResourceId},
+    RenderGraphError,
+};
+
+/// Kind of attachment which the image is used as.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AttachmentKind {
+    Color,
+    Depth,
+}
+
+/// Image which must be created for the compiled graph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImagePlan {
+    pub image: ResourceId,
+    pub kind: AttachmentKind,
+    /// Whether the image is sampled by some pass or exported from the graph.
+    pub sampled: bool,
+}
+
+/// Attachment of the render pass which is created for the graph pass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AttachmentPlan {
+    pub image: ResourceId,
+    pub kind: AttachmentKind,
+    pub load: LoadOp,
+    pub store: StoreOp,
+    /// Layout of the image when the pass begins.
+    pub initial_layout: ImageLayout,
+    /// Layout which the image is transitioned into when the pass ends,
+    /// so it is ready for the next pass which accesses it.
+    pub final_layout: ImageLayout,
+}
+
+/// Pass of the compiled graph with attachments of its render pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassPlan {
+    pub pass: PassId,
+    pub name: String,
+    /// Attachments in declaration order.
+    pub attachments: Vec<AttachmentPlan>,
+    /// Images which are sampled by the pass.
+    pub sampled: Vec<ResourceId>,
+}
+
+/// Dependency between two passes which access the same image,
+/// where the later one must wait for the earlier one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Barrier {
+    pub image: ResourceId,
+    pub src_pass: PassId,
+    pub dst_pass: PassId,
+    /// Layout of the image in the source pass.
+    pub old_layout: ImageLayout,
+    /// Layout of the image in the destination pass.
+    pub new_layout: ImageLayout,
+}
+
+/// Result of the compilation of the render graph.
+///
+/// Passes are executed in declaration order, so each read sees contents written
+/// by passes declared before it. Passes which do not contribute to exported images
+/// are culled.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GraphPlan {
+    /// Passes which are executed, in execution order.
+    pub passes: Vec<PassPlan>,
+    /// Passes which were culled.
+    pub culled: Vec<PassId>,
+    /// Images which are used by executed passes, in ascending order.
+    pub images: Vec<ImagePlan>,
+    /// Barriers in execution order of their destination passes.
+    pub barriers: Vec<Barrier>,
+}
+
+impl GraphPlan {
+    /// Plan of the pass, if the pass was not culled.
+    pub fn pass(&self, pass: PassId) -> Option<&PassPlan> {
+        self.passes.iter().find(|plan| plan.pass == pass)
+    }
+}
+
+/// Layout of the image which the access requires.
+pub fn access_layout(access: Access) -> ImageLayout {
+    match access {
+        Access::Color(_) => ImageLayout::ColorAttachmentOptimal,
+        Access::Depth(_) => ImageLayout::DepthStencilAttachmentOptimal,
+        Access::Sampled => ImageLayout::ShaderReadOnlyOptimal,
+    }
+}
+
+/// Validates declarations of the graph and compiles them into the plan.
+pub fn compile(
+    images: &[GraphImage],
+    passes: &[GraphPass],
+    outputs: &[ResourceId],
+) -> Result<GraphPlan, RenderGraphError> {
+    let kinds = self::validate(images, passes, outputs)?;
+
+    // Walk passes backwards from outputs, keeping only passes
+    // which write contents that are needed later.
+    let mut needed: HashSet<_> = outputs.iter().copied().collect();
+    let mut kept = vec![false; passes.len()];
+    for (index, pass) in passes.iter().enumerate().rev() {
+        let writes = pass
+            .accesses()
+            .iter()
+            .filter(|(_, access)| access.is_write());
+        if !writes.clone().any(|(image, _)| needed.contains(image)) {
+            continue;
+        }
+        kept[index] = true;
+        for &(image, access) in writes {
+            if access.is_clear() {
+                needed.remove(&image);
+            }
+        }
+        let reads = pass
+            .accesses()
+            .iter()
+            .filter(|(_, access)| !access.is_write());
+        needed.extend(reads.map(|&(image, _)| image));
+    }
+
+    // Accesses of each image by kept passes in execution order.
+    let mut history = vec![Vec::new(); images.len()];
+    for (index, pass) in passes.iter().enumerate().filter(|(index, _)| kept[*index]) {
+        for &(image, access) in pass.accesses() {
+            history[image.0].push((PassId(index), access));
+        }
+    }
+
+    let mut plan = GraphPlan::default();
+    for (index, pass) in passes.iter().enumerate() {
+        let id = PassId(index);
+        if !kept[index] {
+            plan.culled.push(id);
+            continue;
+        }
+        let mut attachments = Vec::new();
+        let mut sampled = Vec::new();
+        for &(image, access) in pass.accesses() {
+            let accesses = &history[image.0];
+            let position = accesses.iter().position(|&(pass, _)| pass == id).unwrap();
+            let previous = position.checked_sub(1).map(|position| accesses[position]);
+            let next = accesses.get(position + 1).copied();
+
+            if let Some((src_pass, previous)) = previous {
+                // Images which are only sampled one after another need no barrier.
+                if previous.is_write() || access.is_write() {
+                    plan.barriers.push(Barrier {
+                        image,
+                        src_pass,
+                        dst_pass: id,
+                        old_layout: self::access_layout(previous),
+                        new_layout: self::access_layout(access),
+                    });
+                }
+            }
+            if !access.is_write() {
+                sampled.push(image);
+                continue;
+            }
+
+            let load = match (access.is_clear(), previous) {
+                (true, _) => LoadOp::Clear,
+                (false, Some(_)) => LoadOp::Load,
+                (false, None) => LoadOp::DontCare,
+            };
+            let initial_layout = match (load, previous) {
+                (LoadOp::Load, Some((_, previous))) => self::access_layout(previous),
+                _ => ImageLayout::Undefined,
+            };
+            let exported = outputs.contains(&image);
+            let store = match next {
+                Some((_, next)) if !next.is_clear() => StoreOp::Store,
+                None if exported => StoreOp::Store,
+                _ => StoreOp::DontCare,
+            };
+            let final_layout = match next {
+                Some((_, next)) => self::access_layout(next),
+                // Exported images are sampled by the user after the graph.
+                None if exported => ImageLayout::ShaderReadOnlyOptimal,
+                None => self::access_layout(access),
+            };
+            attachments.push(AttachmentPlan {
+                image,
+                kind: kinds[image.0].unwrap(),
+                load,
+                store,
+                initial_layout,
+                final_layout,
+            });
+        }
+        plan.passes.push(PassPlan {
+            pass: id,
+            name: pass.name().to_string(),
+            attachments,
+            sampled,
+        });
+    }
+
+    for (index, accesses) in history.iter().enumerate() {
+        if accesses.is_empty() {
+            continue;
+        }
+        let image = ResourceId(index);
+        plan.images.push(ImagePlan {
+            image,
+            kind: kinds[index].unwrap(),
+            sampled: outputs.contains(&image) || accesses.iter().any(|(_, a)| !a.is_write()),
+        });
+    }
+    Ok(plan)
+}
+
+/// Validates declarations of the graph, returning kinds of attachments of images.
+fn validate(
+    images: &[GraphImage],
+    passes: &[GraphPass],
+    outputs: &[ResourceId],
+) -> Result<Vec<Option<AttachmentKind>>, RenderGraphError> {
+    if outputs.is_empty() {
+        return Err(RenderGraphError::NoOutputs);
+    }
+    let image = |id: ResourceId| images.get(id.0).ok_or(RenderGraphError::UnknownImage(id.0));
+    for &output in outputs {
+        image(output)?;
+    }
+
+    let mut kinds = vec![None; images.len()];
+    for pass in passes {
+        let name = || pass.name().to_string();
+        let mut size = None;
+        let mut depth = false;
+        let mut seen = HashSet::new();
+        for &(id, access) in pass.accesses() {
+            let desc = image(id)?;
+            if !seen.insert(id) {
+                return Err(RenderGraphError::DuplicateAccess {
+                    pass: name(),
+                    image: desc.name.clone(),
+                });
+            }
+            let kind = match access {
+                Access::Color(_) => AttachmentKind::Color,
+                Access::Depth(_) => AttachmentKind::Depth,
+                Access::Sampled => {
+                    if kinds[id.0].is_none() {
+                        return Err(RenderGraphError::ReadBeforeWrite {
+                            pass: name(),
+                            image: desc.name.clone(),
+                        });
+                    }
+                    continue;
+                }
+            };
+            if kind == AttachmentKind::Depth && std::mem::replace(&mut depth, true) {
+                return Err(RenderGraphError::MultipleDepth(name()));
+            }
+            if *kinds[id.0].get_or_insert(kind) != kind {
+                return Err(RenderGraphError::AttachmentKindMismatch(desc.name.clone()));
+            }
+            if *size.get_or_insert(desc.size) != desc.size {
+                return Err(RenderGraphError::SizeMismatch(name()));
+            }
+        }
+        if size.is_none() {
+            return Err(RenderGraphError::NoAttachments(name()));
+        }
+    }
+    if let Some(&output) = outputs.iter().find(|output| kinds[output.0].is_none()) {
+        return Err(RenderGraphError::NotWritten(images[output.0].name.clone()));
+    }
+    Ok(kinds)
+}
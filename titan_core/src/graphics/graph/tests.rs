@@ -0,0 +1,274 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+use vulkano::image::ImageLayout;
+use vulkano::render_pass::{LoadOp, StoreOp};
+
+use crate::math::Color;
+
+use super::*;
+
+const SHADOW_MAP: ResourceId = ResourceId(0);
+const SCENE_COLOR: ResourceId = ResourceId(1);
+const SCENE_DEPTH: ResourceId = ResourceId(2);
+const BLOOM: ResourceId = ResourceId(3);
+
+fn images() -> Vec<GraphImage> {
+    vec![
+        GraphImage::new(
+            "shadow_map",
+            Format::D32_SFLOAT,
+            ImageSize::Absolute([1024; 2]),
+        ),
+        GraphImage::new(
+            "scene_color",
+            Format::R16G16B16A16_SFLOAT,
+            ImageSize::default(),
+        ),
+        GraphImage::new("scene_depth", Format::D32_SFLOAT, ImageSize::default()),
+        GraphImage::new(
+            "bloom",
+            Format::R16G16B16A16_SFLOAT,
+            ImageSize::Relative(0.5),
+        ),
+    ]
+}
+
+/// Shadow map, scene, overlay over the scene, bloom and a debug pass.
+fn passes() -> Vec<GraphPass> {
+    let mut shadows = GraphPass::new("shadows");
+    shadows.clear_depth(SHADOW_MAP, 1.0);
+    let mut scene = GraphPass::new("scene");
+    scene
+        .read_texture(SHADOW_MAP)
+        .clear_color(SCENE_COLOR, Color::BLACK)
+        .clear_depth(SCENE_DEPTH, 1.0);
+    let mut overlay = GraphPass::new("overlay");
+    overlay.write_color(SCENE_COLOR).write_depth(SCENE_DEPTH);
+    let mut bloom = GraphPass::new("bloom");
+    bloom.read_texture(SCENE_COLOR).write_color(BLOOM);
+    let mut debug = GraphPass::new("debug");
+    debug
+        .read_texture(SCENE_DEPTH)
+        .clear_color(BLOOM, Color::BLACK);
+    vec![shadows, scene, overlay, bloom, debug]
+}
+
+fn attachment(plan: &GraphPlan, pass: usize, image: ResourceId) -> AttachmentPlan {
+    let pass = plan.pass(PassId(pass)).unwrap();
+    *pass.attachments.iter().find(|a| a.image == image).unwrap()
+}
+
+#[test]
+fn test_image_size() {
+    assert_eq!(ImageSize::Absolute([512, 0]).resolve([800, 600]), [512, 1]);
+    assert_eq!(ImageSize::Relative(0.5).resolve([801, 600]), [401, 300]);
+    assert_eq!(ImageSize::Relative(0.0).resolve([800, 600]), [1, 1]);
+    assert_eq!(ImageSize::default().resolve([800, 600]), [800, 600]);
+}
+
+#[test]
+fn test_culling() {
+    let images = self::images();
+    let passes = self::passes();
+
+    // Debug pass clears bloom last, so bloom pass does not contribute to it.
+    let plan = plan::compile(&images, &passes, &[BLOOM]).unwrap();
+    let executed: Vec<_> = plan.passes.iter().map(|pass| pass.name.as_str()).collect();
+    assert_eq!(executed, ["shadows", "scene", "overlay", "debug"]);
+    assert_eq!(plan.culled, [PassId(3)]);
+
+    let plan = plan::compile(&images, &passes[..4], &[BLOOM]).unwrap();
+    assert_eq!(plan.passes.len(), 4);
+    assert!(plan.culled.is_empty());
+    let images: Vec<_> = plan.images.iter().map(|image| image.image).collect();
+    assert_eq!(images, [SHADOW_MAP, SCENE_COLOR, SCENE_DEPTH, BLOOM]);
+
+    let plan = plan::compile(&self::images(), &passes, &[SHADOW_MAP]).unwrap();
+    assert_eq!(plan.passes.len(), 1);
+    assert_eq!(plan.culled, [PassId(1), PassId(2), PassId(3), PassId(4)]);
+}
+
+#[test]
+fn test_attachments() {
+    let plan = plan::compile(&self::images(), &self::passes()[..4], &[BLOOM]).unwrap();
+
+    let shadow_map = self::attachment(&plan, 0, SHADOW_MAP);
+    assert_eq!(shadow_map.kind, AttachmentKind::Depth);
+    assert_eq!(
+        (shadow_map.load, shadow_map.store),
+        (LoadOp::Clear, StoreOp::Store)
+    );
+    assert_eq!(shadow_map.initial_layout, ImageLayout::Undefined);
+    assert_eq!(shadow_map.final_layout, ImageLayout::ShaderReadOnlyOptimal);
+    assert_eq!(plan.pass(PassId(1)).unwrap().sampled, [SHADOW_MAP]);
+
+    // Scene is cleared, and overlay loads it.
+    let color = self::attachment(&plan, 1, SCENE_COLOR);
+    assert_eq!((color.load, color.store), (LoadOp::Clear, StoreOp::Store));
+    assert_eq!(color.final_layout, ImageLayout::ColorAttachmentOptimal);
+    let color = self::attachment(&plan, 2, SCENE_COLOR);
+    assert_eq!((color.load, color.store), (LoadOp::Load, StoreOp::Store));
+    assert_eq!(color.initial_layout, ImageLayout::ColorAttachmentOptimal);
+    assert_eq!(color.final_layout, ImageLayout::ShaderReadOnlyOptimal);
+
+    // Depth is discarded after overlay.
+    let depth = self::attachment(&plan, 2, SCENE_DEPTH);
+    assert_eq!((depth.load, depth.store), (LoadOp::Load, StoreOp::DontCare));
+    assert_eq!(
+        depth.final_layout,
+        ImageLayout::DepthStencilAttachmentOptimal
+    );
+
+    // Bloom is written from scratch and exported.
+    let bloom = self::attachment(&plan, 3, BLOOM);
+    assert_eq!(
+        (bloom.load, bloom.store),
+        (LoadOp::DontCare, StoreOp::Store)
+    );
+    assert_eq!(bloom.initial_layout, ImageLayout::Undefined);
+    assert_eq!(bloom.final_layout, ImageLayout::ShaderReadOnlyOptimal);
+
+    let sampled: Vec<_> = plan.images.iter().map(|image| image.sampled).collect();
+    assert_eq!(sampled, [true, true, false, true]);
+}
+
+#[test]
+fn test_barriers() {
+    let plan = plan::compile(&self::images(), &self::passes()[..4], &[BLOOM]).unwrap();
+    let barriers: Vec<_> = plan
+        .barriers
+        .iter()
+        .map(|b| {
+            (
+                b.image,
+                b.src_pass.0,
+                b.dst_pass.0,
+                b.old_layout,
+                b.new_layout,
+            )
+        })
+        .collect();
+    assert_eq!(
+        barriers,
+        [
+            (
+                SHADOW_MAP,
+                0,
+                1,
+                ImageLayout::DepthStencilAttachmentOptimal,
+                ImageLayout::ShaderReadOnlyOptimal,
+            ),
+            (
+                SCENE_COLOR,
+                1,
+                2,
+                ImageLayout::ColorAttachmentOptimal,
+                ImageLayout::ColorAttachmentOptimal,
+            ),
+            (
+                SCENE_DEPTH,
+                1,
+                2,
+                ImageLayout::DepthStencilAttachmentOptimal,
+                ImageLayout::DepthStencilAttachmentOptimal,
+            ),
+            (
+                SCENE_COLOR,
+                2,
+                3,
+                ImageLayout::ColorAttachmentOptimal,
+                ImageLayout::ShaderReadOnlyOptimal,
+            ),
+        ]
+    );
+
+    // Images which are only sampled one after another need no barrier.
+    let mut passes = self::passes();
+    passes[4] = GraphPass::new("blur");
+    passes[4].read_texture(SCENE_COLOR).write_color(BLOOM);
+    let plan = plan::compile(&self::images(), &passes, &[BLOOM]).unwrap();
+    let scene_reads: Vec<_> = plan
+        .barriers
+        .iter()
+        .filter(|b| b.image == SCENE_COLOR && b.new_layout == ImageLayout::ShaderReadOnlyOptimal)
+        .map(|b| (b.src_pass.0, b.dst_pass.0))
+        .collect();
+    assert_eq!(scene_reads, [(2, 3)]);
+    let bloom = plan.barriers.iter().filter(|b| b.image == BLOOM);
+    assert_eq!(
+        bloom
+            .map(|b| (b.src_pass.0, b.dst_pass.0))
+            .collect::<Vec<_>>(),
+        [(3, 4)]
+    );
+}
+
+#[test]
+fn test_errors() {
+    let images = self::images();
+    let passes = self::passes();
+    assert_eq!(
+        plan::compile(&images, &passes, &[]),
+        Err(RenderGraphError::NoOutputs)
+    );
+    assert_eq!(
+        plan::compile(&images, &passes, &[ResourceId(10)]),
+        Err(RenderGraphError::UnknownImage(10))
+    );
+    assert_eq!(
+        plan::compile(&images, &passes[..3], &[BLOOM]),
+        Err(RenderGraphError::NotWritten("bloom".to_string()))
+    );
+
+    let mut pass = GraphPass::new("pass");
+    pass.read_texture(SCENE_COLOR);
+    assert_eq!(
+        plan::compile(&images, &[pass], &[SCENE_COLOR]),
+        Err(RenderGraphError::ReadBeforeWrite {
+            pass: "pass".to_string(),
+            image: "scene_color".to_string(),
+        })
+    );
+    let errors = [
+        (vec![], RenderGraphError::NoAttachments("pass".to_string())),
+        (
+            vec![
+                (SHADOW_MAP, Access::Depth(None)),
+                (SCENE_DEPTH, Access::Depth(None)),
+            ],
+            RenderGraphError::MultipleDepth("pass".to_string()),
+        ),
+        (
+            vec![
+                (SCENE_COLOR, Access::Color(None)),
+                (BLOOM, Access::Color(None)),
+            ],
+            RenderGraphError::SizeMismatch("pass".to_string()),
+        ),
+        (
+            vec![
+                (SCENE_COLOR, Access::Color(None)),
+                (SCENE_COLOR, Access::Sampled),
+            ],
+            RenderGraphError::DuplicateAccess {
+                pass: "pass".to_string(),
+                image: "scene_color".to_string(),
+            },
+        ),
+        (
+            vec![(SCENE_DEPTH, Access::Color(None))],
+            RenderGraphError::AttachmentKindMismatch("scene_depth".to_string()),
+        ),
+    ];
+    for (accesses, error) in errors {
+        let mut first = GraphPass::new("first");
+        first.clear_depth(SCENE_DEPTH, 1.0);
+        let mut pass = GraphPass::new("pass");
+        for (image, access) in accesses {
+            pass.access(image, access);
+        }
+        let result = plan::compile(&images, &[first, pass], &[SCENE_DEPTH]);
+        assert_eq!(result, Err(error));
+    }
+}
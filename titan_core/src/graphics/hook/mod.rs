@@ -0,0 +1,174 @@
+//! User-defined render passes which are injected into the frame.
+//!
+//! Hooks are registered with [`Renderer::add_hook`](super::Renderer::add_hook)
+//! for one of the [stages](HookStage) of the frame. Hooks of each stage run in
+//! registration order; if a hook fails, the error is logged and only this hook
+//! is skipped in the current frame.
+
+use std::error::Error;
+use std::sync::Arc;
+
+use ultraviolet::Mat4;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, PrimaryAutoCommandBuffer, SecondaryAutoCommandBuffer,
+};
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::render_pass::Subpass;
+
+mod tests;
+
+/// Error which is returned by the hook.
+pub type HookError = Box<dyn Error + Send + Sync>;
+
+/// Stage of the frame where the hook records its commands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HookStage {
+    /// Before the main pass, outside of any render pass
+    /// (for example, to render shadow maps or other offscreen targets).
+    BeforeMainPass,
+    /// Inside the subpass of the main pass where the scene is drawn,
+    /// before objects of the engine are drawn.
+    InsideMainPass,
+    /// After the main pass (and UI), outside of any render pass.
+    AfterMainPass,
+}
+
+/// State of the frame which is given to hooks.
+#[derive(Clone)]
+pub struct FrameContext {
+    /// Number of the frame since the start of the renderer.
+    pub frame: u64,
+    /// Index of the swapchain image (and its framebuffer) which the frame is rendered into.
+    ///
+    /// Each image is rendered again only after its previous frame is finished,
+    /// so per-image resources of hooks could be keyed by this index.
+    ///
+    pub image_index: usize,
+    /// Extent of the swapchain image (in pixels).
+    pub extent: [u32; 2],
+    /// Format of the swapchain image.
+    pub format: Format,
+    /// Projection matrix of the camera.
+    pub projection: Mat4,
+    /// View matrix of the camera.
+    pub view: Mat4,
+    /// Subpass of the main pass where the scene is drawn,
+    /// which pipelines of [`HookStage::InsideMainPass`] must be created for.
+    pub subpass: Subpass,
+    /// Queue which commands of hooks are executed on.
+    pub queue: Arc<Queue>,
+}
+
+/// Command buffer which the hook records its commands into.
+pub enum HookCommands<'a> {
+    /// Primary command buffer outside of any render pass
+    /// (for [`HookStage::BeforeMainPass`] and [`HookStage::AfterMainPass`]).
+    Primary(&'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>),
+    /// Secondary command buffer inside the main pass (for [`HookStage::InsideMainPass`]).
+    Secondary(&'a mut AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>),
+}
+
+/// User-defined render pass which is injected into the frame.
+pub trait RenderHook {
+    /// Prepares resources of the hook for the frame before any commands are recorded.
+    ///
+    /// If preparation fails, the hook is skipped in this frame.
+    ///
+    fn prepare(&mut self, device: &Arc<Device>, frame: &FrameContext) -> Result<(), HookError>;
+
+    /// Records commands of the hook for the stage which it was registered for.
+    fn record(
+        &mut self,
+        commands: &mut HookCommands,
+        frame: &FrameContext,
+    ) -> Result<(), HookError>;
+
+    /// Called after swapchain was recreated with new extent,
+    /// so resources which depend on swapchain images could be released.
+    fn on_resize(&mut self, _extent: [u32; 2]) {}
+}
+
+struct HookEntry<H: ?Sized> {
+    stage: HookStage,
+    hook: Box<H>,
+    /// Whether the hook failed in the current frame, so it is skipped until the next one.
+    failed: bool,
+}
+
+/// Registered hooks in registration order.
+pub(crate) struct HookList<H: ?Sized = dyn RenderHook> {
+    entries: Vec<HookEntry<H>>,
+}
+
+impl<H: ?Sized> Default for HookList<H> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<H: ?Sized> HookList<H> {
+    pub fn push(&mut self, stage: HookStage, hook: Box<H>) {
+        self.entries.push(HookEntry {
+            stage,
+            hook,
+            failed: false,
+        })
+    }
+
+    /// Checks if there are hooks of the stage which did not fail in the current frame.
+    pub fn has_active(&self, stage: HookStage) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.stage == stage && !entry.failed)
+    }
+
+    /// Begins new frame, preparing all hooks.
+    pub fn prepare(&mut self, mut prepare: impl FnMut(&mut H) -> Result<(), HookError>) {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.failed = false;
+            if let Err(error) = prepare(&mut entry.hook) {
+                log::error!(
+                    "render hook #{} ({:?}) failed to prepare, skipped: {}",
+                    index,
+                    entry.stage,
+                    error,
+                );
+                entry.failed = true;
+            }
+        }
+    }
+
+    /// Records commands of active hooks of the stage.
+    pub fn record(
+        &mut self,
+        stage: HookStage,
+        mut record: impl FnMut(&mut H) -> Result<(), HookError>,
+    ) {
+        let entries = self.entries.iter_mut().enumerate();
+        for (index, entry) in entries.filter(|(_, entry)| entry.stage == stage && !entry.failed) {
+            if let Err(error) = record(&mut entry.hook) {
+                log::error!(
+                    "render hook #{} ({:?}) failed to record, skipped: {}",
+                    index,
+                    entry.stage,
+                    error,
+                );
+                entry.failed = true;
+            }
+        }
+    }
+
+    /// Calls the closure for each hook in registration order.
+    pub fn for_each(&mut self, mut f: impl FnMut(&mut H)) {
+        for entry in &mut self.entries {
+            f(&mut entry.hook)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(test)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::*;
+
+/// Hook which logs its calls and fails on demand.
+struct FakeHook {
+    name: &'static str,
+    log: Rc<RefCell<Vec<String>>>,
+    fail_prepare: bool,
+    fail_record: bool,
+}
+
+impl FakeHook {
+    fn new(name: &'static str, log: &Rc<RefCell<Vec<String>>>) -> Box<Self> {
+        Box::new(Self {
+            name,
+            log: log.clone(),
+            fail_prepare: false,
+            fail_record: false,
+        })
+    }
+
+    fn prepare(&mut self) -> Result<(), HookError> {
+        self.log.borrow_mut().push(format!("prepare {}", self.name));
+        match self.fail_prepare {
+            true => Err("prepare failed".into()),
+            false => Ok(()),
+        }
+    }
+
+    fn record(&mut self) -> Result<(), HookError> {
+        self.log.borrow_mut().push(format!("record {}", self.name));
+        match self.fail_record {
+            true => Err("record failed".into()),
+            false => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_hooks_order() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut hooks = HookList::<FakeHook>::default();
+    hooks.push(HookStage::AfterMainPass, FakeHook::new("a", &log));
+    hooks.push(HookStage::BeforeMainPass, FakeHook::new("b", &log));
+    hooks.push(HookStage::BeforeMainPass, FakeHook::new("c", &log));
+    assert!(!hooks.has_active(HookStage::InsideMainPass));
+
+    hooks.prepare(FakeHook::prepare);
+    for stage in [
+        HookStage::BeforeMainPass,
+        HookStage::InsideMainPass,
+        HookStage::AfterMainPass,
+    ] {
+        hooks.record(stage, FakeHook::record);
+    }
+    let expected = [
+        "prepare a",
+        "prepare b",
+        "prepare c",
+        "record b",
+        "record c",
+        "record a",
+    ];
+    assert_eq!(*log.borrow(), expected);
+}
+
+#[test]
+fn test_failed_hooks_skipped() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut hooks = HookList::<FakeHook>::default();
+    let mut failing = FakeHook::new("a", &log);
+    failing.fail_prepare = true;
+    hooks.push(HookStage::BeforeMainPass, failing);
+    let mut failing = FakeHook::new("b", &log);
+    failing.fail_record = true;
+    hooks.push(HookStage::BeforeMainPass, failing);
+    hooks.push(HookStage::BeforeMainPass, FakeHook::new("c", &log));
+
+    hooks.prepare(FakeHook::prepare);
+    hooks.record(HookStage::BeforeMainPass, FakeHook::record);
+    // Failed hook is skipped again within the same frame only.
+    hooks.record(HookStage::BeforeMainPass, FakeHook::record);
+    let expected = [
+        "prepare a",
+        "prepare b",
+        "prepare c",
+        "record b",
+        "record c",
+        "record c",
+    ];
+    assert_eq!(*log.borrow(), expected);
+    assert!(hooks.has_active(HookStage::BeforeMainPass));
+
+    // Next frame gives failed hooks another chance.
+    log.borrow_mut().clear();
+    hooks.for_each(|hook| hook.fail_prepare = false);
+    hooks.prepare(FakeHook::prepare);
+    hooks.record(HookStage::BeforeMainPass, FakeHook::record);
+    let expected = [
+        "prepare a",
+        "prepare b",
+        "prepare c",
+        "record a",
+        "record b",
+        "record c",
+    ];
+    assert_eq!(*log.borrow(), expected);
+}
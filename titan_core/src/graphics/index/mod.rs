@@ -0,0 +1,154 @@
+//! Index buffers which store indices of vertices with the smallest suitable type.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferUsage, ImmutableBuffer};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::Queue;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::sync::{FlushError, GpuFuture};
+
+mod tests;
+
+/// Type of indices of the index buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IndexType {
+    /// 16-bit indices, for meshes with no more than [`IndexType::MAX_U16_VERTICES`] vertices.
+    U16,
+    /// 32-bit indices.
+    U32,
+}
+
+impl IndexType {
+    /// Max count of vertices which could be indexed by 16-bit indices.
+    ///
+    /// The largest 16-bit value is never used as index,
+    /// because it restarts primitives if primitive restart is enabled.
+    ///
+    pub const MAX_U16_VERTICES: usize = u16::MAX as usize;
+
+    /// The smallest type of indices which could index given count of vertices.
+    pub fn for_vertex_count(vertex_count: usize) -> Self {
+        if vertex_count <= Self::MAX_U16_VERTICES {
+            Self::U16
+        } else {
+            Self::U32
+        }
+    }
+
+    /// Size of one index (in bytes).
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U16 => std::mem::size_of::<u16>(),
+            Self::U32 => std::mem::size_of::<u32>(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum IndexBufferError {
+    #[error("index buffer must contain at least one index")]
+    Empty,
+
+    #[error("index {index} is out of range of {vertex_count} vertices")]
+    IndexOutOfRange { index: u32, vertex_count: usize },
+
+    #[error("index buffer allocation failure: {0}")]
+    Allocation(#[from] DeviceMemoryAllocError),
+
+    #[error("index buffer upload failure: {0}")]
+    Upload(#[from] FlushError),
+}
+
+/// Indices of the buffer with their type.
+enum Indices {
+    U16(Arc<ImmutableBuffer<[u16]>>),
+    U32(Arc<ImmutableBuffer<[u32]>>),
+}
+
+/// Immutable buffer of indices of vertices in device local memory.
+///
+/// Indices are stored as 16-bit ones if all vertices could be indexed by them
+/// (which halves memory of the buffer), and as 32-bit ones otherwise.
+///
+pub struct IndexBuffer {
+    indices: Indices,
+    count: u32,
+}
+
+impl IndexBuffer {
+    /// Uploads indices of mesh with given count of vertices into the new buffer on the queue.
+    ///
+    /// Upload is flushed, but not waited for: draws which use the buffer wait for it.
+    ///
+    pub fn new(
+        queue: Arc<Queue>,
+        indices: &[u32],
+        vertex_count: usize,
+    ) -> Result<Self, IndexBufferError> {
+        let index_type = self::check(indices, vertex_count)?;
+        let usage = BufferUsage::index_buffer();
+        let buffer = match index_type {
+            IndexType::U16 => {
+                // All indices are less than count of vertices, so they fit into 16 bits.
+                let iter = indices.iter().map(|&index| index as u16);
+                let (buffer, future) = ImmutableBuffer::from_iter(iter, usage, queue)?;
+                future.flush()?;
+                Indices::U16(buffer)
+            }
+            IndexType::U32 => {
+                let iter = indices.iter().copied();
+                let (buffer, future) = ImmutableBuffer::from_iter(iter, usage, queue)?;
+                future.flush()?;
+                Indices::U32(buffer)
+            }
+        };
+        Ok(Self {
+            indices: buffer,
+            count: indices.len() as u32,
+        })
+    }
+
+    /// Type of indices of the buffer.
+    pub fn index_type(&self) -> IndexType {
+        match self.indices {
+            Indices::U16(_) => IndexType::U16,
+            Indices::U32(_) => IndexType::U32,
+        }
+    }
+
+    /// Count of indices of the buffer.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Binds the buffer as index buffer of next indexed draws of the command buffer.
+    pub fn bind<'b, L, P>(
+        &self,
+        builder: &'b mut AutoCommandBufferBuilder<L, P>,
+    ) -> &'b mut AutoCommandBufferBuilder<L, P> {
+        match &self.indices {
+            Indices::U16(buffer) => builder.bind_index_buffer(buffer.clone()),
+            Indices::U32(buffer) => builder.bind_index_buffer(buffer.clone()),
+        }
+    }
+}
+
+/// Checks that indices are not empty and all of them are in range of vertices,
+/// then returns type of indices which they are stored with.
+pub(super) fn check(indices: &[u32], vertex_count: usize) -> Result<IndexType, IndexBufferError> {
+    if indices.is_empty() {
+        return Err(IndexBufferError::Empty);
+    }
+    let out_of_range = indices
+        .iter()
+        .find(|&&index| index as usize >= vertex_count);
+    if let Some(&index) = out_of_range {
+        return Err(IndexBufferError::IndexOutOfRange {
+            index,
+            vertex_count,
+        });
+    }
+    Ok(IndexType::for_vertex_count(vertex_count))
+}
@@ -0,0 +1,32 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_index_type() {
+    assert_eq!(IndexType::for_vertex_count(0), IndexType::U16);
+    assert_eq!(IndexType::for_vertex_count(23), IndexType::U16);
+    assert_eq!(IndexType::for_vertex_count(65535), IndexType::U16);
+    // Index 0xFFFF is reserved for primitive restart.
+    assert_eq!(IndexType::for_vertex_count(65536), IndexType::U32);
+    assert_eq!(IndexType::for_vertex_count(1 << 20), IndexType::U32);
+
+    assert_eq!(IndexType::U16.size(), 2);
+    assert_eq!(IndexType::U32.size(), 4);
+}
+
+#[test]
+fn test_check_indices() {
+    assert_eq!(self::check(&[0, 1, 2, 2, 3, 0], 4).unwrap(), IndexType::U16);
+    assert_eq!(self::check(&[0, 65536, 1], 70000).unwrap(), IndexType::U32);
+
+    assert!(matches!(self::check(&[], 4), Err(IndexBufferError::Empty)));
+    let result = self::check(&[0, 1, 4], 4);
+    assert!(matches!(
+        result,
+        Err(IndexBufferError::IndexOutOfRange {
+            index: 4,
+            vertex_count: 4
+        })
+    ));
+}
@@ -0,0 +1,245 @@
+//! Indirect drawing of game objects with commands built on the CPU.
+//!
+//! Draw commands are written into a buffer once, then the whole list is drawn
+//! with a single indirect draw call on each frame. If the device does not support
+//! `multi_draw_indirect` feature, commands are drawn one by one instead.
+
+use std::mem::size_of;
+use std::ops::Range;
+use std::sync::Arc;
+
+use slotmap::new_key_type;
+use thiserror::Error;
+use vulkano::buffer::{BufferSlice, BufferUsage, CpuAccessibleBuffer, TypedBufferAccess};
+use vulkano::command_buffer::DrawIndexedIndirectCommand;
+use vulkano::device::Device;
+use vulkano::memory::DeviceMemoryAllocError;
+
+mod tests;
+
+/// Size of one draw command in the indirect buffer (in bytes).
+///
+/// Commands are tightly packed, so offsets and strides of indirect draws
+/// must be multiples of this size.
+pub const INDIRECT_STRIDE: u32 = size_of::<DrawIndexedIndirectCommand>() as u32;
+
+new_key_type! {
+    /// Identifier of the indirect buffer created by the renderer.
+    pub struct IndirectBufferId;
+}
+
+#[derive(Debug, Error)]
+pub enum IndirectBufferCreationError {
+    #[error("indirect buffer must contain at least one draw command")]
+    Empty,
+
+    #[error("indirect buffer allocation failure: {0}")]
+    Allocation(#[from] DeviceMemoryAllocError),
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum IndirectDrawError {
+    #[error("indirect buffer was not found")]
+    BufferNotFound,
+
+    #[error("offset {0} is not a multiple of draw command size")]
+    UnalignedOffset(u64),
+
+    #[error("stride {0} is not a multiple of draw command size")]
+    InvalidStride(u32),
+
+    #[error("{draw_count} draw command(s) at offset {offset} exceed buffer of {size} bytes")]
+    OutOfBounds {
+        offset: u64,
+        draw_count: u32,
+        size: u64,
+    },
+}
+
+/// List of indexed draw commands which is written into [`IndirectBuffer`].
+#[derive(Debug, Default, Clone)]
+pub struct IndirectDrawList {
+    commands: Vec<DrawIndexedIndirectCommand>,
+}
+
+impl IndirectDrawList {
+    /// Creates an empty list of draw commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends indexed draw command to the end of the list.
+    pub fn push(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) -> &mut Self {
+        self.commands.push(DrawIndexedIndirectCommand {
+            index_count,
+            instance_count,
+            first_index,
+            // Vulkano stores signed offset as unsigned, bits are the same.
+            vertex_offset: vertex_offset as u32,
+            first_instance,
+        });
+        self
+    }
+
+    /// Count of draw commands in the list.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if there are no draw commands in the list.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Removes all draw commands from the list.
+    pub fn clear(&mut self) {
+        self.commands.clear()
+    }
+
+    /// Draw commands in order of their submission.
+    pub(crate) fn commands(&self) -> &[DrawIndexedIndirectCommand] {
+        &self.commands
+    }
+}
+
+/// Range of draw commands in the indirect buffer which is drawn on each frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IndirectDraw {
+    /// Buffer with draw commands.
+    pub buffer: IndirectBufferId,
+    /// Offset of the first draw command (in bytes).
+    pub offset: u64,
+    /// Count of draw commands.
+    pub draw_count: u32,
+    /// Distance between consecutive draw commands (in bytes).
+    pub stride: u32,
+}
+
+impl IndirectDraw {
+    /// Draws given count of commands from the beginning of the buffer.
+    pub fn new(buffer: IndirectBufferId, draw_count: u32) -> Self {
+        Self {
+            buffer,
+            offset: 0,
+            draw_count,
+            stride: INDIRECT_STRIDE,
+        }
+    }
+
+    /// Returns indices of draw commands in the buffer of given size (in bytes),
+    /// with distance between them in commands.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if offset or stride is not a multiple of [`INDIRECT_STRIDE`]
+    /// or draw commands are out of buffer bounds.
+    ///
+    pub(crate) fn commands(&self, size: u64) -> Result<(Range<u64>, u64), IndirectDrawError> {
+        let command_size = INDIRECT_STRIDE as u64;
+        if self.offset % command_size != 0 {
+            return Err(IndirectDrawError::UnalignedOffset(self.offset));
+        }
+        if self.stride == 0 || self.stride % INDIRECT_STRIDE != 0 {
+            return Err(IndirectDrawError::InvalidStride(self.stride));
+        }
+        let first = self.offset / command_size;
+        let step = (self.stride / INDIRECT_STRIDE) as u64;
+        if self.draw_count == 0 {
+            return Ok((first..first, step));
+        }
+
+        let out_of_bounds = IndirectDrawError::OutOfBounds {
+            offset: self.offset,
+            draw_count: self.draw_count,
+            size,
+        };
+        let end = (self.draw_count as u64 - 1)
+            .checked_mul(step)
+            .and_then(|last| last.checked_add(first + 1))
+            .ok_or_else(|| out_of_bounds.clone())?;
+        if end * command_size > size {
+            return Err(out_of_bounds);
+        }
+        Ok((first..end, step))
+    }
+}
+
+/// Buffer with indexed draw commands which is used for indirect drawing.
+pub struct IndirectBuffer {
+    buffer: Arc<CpuAccessibleBuffer<[DrawIndexedIndirectCommand]>>,
+}
+
+impl IndirectBuffer {
+    /// Creates new buffer with draw commands of the list.
+    pub(crate) fn new(
+        device: Arc<Device>,
+        list: &IndirectDrawList,
+    ) -> Result<Self, IndirectBufferCreationError> {
+        if list.is_empty() {
+            return Err(IndirectBufferCreationError::Empty);
+        }
+        let usage = BufferUsage {
+            indirect_buffer: true,
+            ..BufferUsage::none()
+        };
+        let commands = list.commands().iter().copied();
+        let buffer = CpuAccessibleBuffer::from_iter(device, usage, false, commands)?;
+        Ok(Self { buffer })
+    }
+
+    /// Size of the buffer (in bytes).
+    pub(crate) fn size(&self) -> u64 {
+        self.buffer.len() * INDIRECT_STRIDE as u64
+    }
+
+    /// Underlying buffer with draw commands.
+    pub(crate) fn buffer(&self) -> &Arc<CpuAccessibleBuffer<[DrawIndexedIndirectCommand]>> {
+        &self.buffer
+    }
+
+    /// Returns slices of the buffer which should be drawn with separate indirect draw calls.
+    ///
+    /// All commands are drawn with one call only if they are tightly packed
+    /// and `multi_draw_indirect` feature is enabled.
+    ///
+    pub(crate) fn slices(
+        &self,
+        draw: &IndirectDraw,
+        multi_draw: bool,
+    ) -> Result<Vec<IndirectSlice>, IndirectDrawError> {
+        let (commands, step) = draw.commands(self.size())?;
+        if commands.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ranges: Vec<_> = if multi_draw && step == 1 {
+            vec![commands]
+        } else {
+            commands
+                .step_by(step as usize)
+                .map(|index| index..index + 1)
+                .collect()
+        };
+        let slices = ranges
+            .into_iter()
+            .map(|range| {
+                BufferSlice::from_typed_buffer_access(self.buffer.clone())
+                    .slice(range)
+                    .expect("range was checked against buffer size")
+            })
+            .collect();
+        Ok(slices)
+    }
+}
+
+/// Slice of the indirect buffer which is drawn with one indirect draw call.
+pub(crate) type IndirectSlice = BufferSlice<
+    [DrawIndexedIndirectCommand],
+    Arc<CpuAccessibleBuffer<[DrawIndexedIndirectCommand]>>,
+>;
@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+use slotmap::KeyData;
+
+use super::*;
+
+const SIZE: u64 = 4 * INDIRECT_STRIDE as u64;
+
+fn draw(offset: u64, draw_count: u32, stride: u32) -> IndirectDraw {
+    IndirectDraw {
+        buffer: IndirectBufferId::from(KeyData::from_ffi(1)),
+        offset,
+        draw_count,
+        stride,
+    }
+}
+
+#[test]
+fn test_layout() {
+    // Layout of `VkDrawIndexedIndirectCommand`: five 32-bit fields.
+    assert_eq!(INDIRECT_STRIDE, 20);
+
+    let mut list = IndirectDrawList::new();
+    list.push(6, 1, 0, 0, 0).push(6, 2, 6, -4, 1);
+    assert_eq!(list.len(), 2);
+    let command = list.commands()[1];
+    assert_eq!(command.index_count, 6);
+    assert_eq!(command.instance_count, 2);
+    assert_eq!(command.first_index, 6);
+    assert_eq!(command.vertex_offset as i32, -4);
+    assert_eq!(command.first_instance, 1);
+
+    list.clear();
+    assert!(list.is_empty());
+}
+
+#[test]
+fn test_same_as_direct_draw() {
+    // Two quads drawn by separate commands cover the same indices
+    // as one direct draw of both of them.
+    let mut list = IndirectDrawList::new();
+    list.push(6, 1, 0, 0, 0).push(6, 1, 6, 0, 0);
+    let mut indices: Vec<_> = list
+        .commands()
+        .iter()
+        .flat_map(|command| command.first_index..command.first_index + command.index_count)
+        .collect();
+    indices.sort_unstable();
+    assert_eq!(indices, (0..12).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_commands() {
+    let stride = INDIRECT_STRIDE;
+    assert_eq!(draw(0, 4, stride).commands(SIZE), Ok((0..4, 1)));
+    assert_eq!(draw(stride as u64, 2, stride).commands(SIZE), Ok((1..3, 1)));
+    assert_eq!(draw(0, 2, 2 * stride).commands(SIZE), Ok((0..3, 2)));
+    assert_eq!(draw(0, 0, stride).commands(SIZE), Ok((0..0, 1)));
+}
+
+#[test]
+fn test_commands_invalid() {
+    let stride = INDIRECT_STRIDE;
+    assert_eq!(
+        draw(4, 1, stride).commands(SIZE),
+        Err(IndirectDrawError::UnalignedOffset(4)),
+    );
+    assert_eq!(
+        draw(0, 1, 16).commands(SIZE),
+        Err(IndirectDrawError::InvalidStride(16)),
+    );
+    assert_eq!(
+        draw(0, 1, 0).commands(SIZE),
+        Err(IndirectDrawError::InvalidStride(0)),
+    );
+    let out_of_bounds = |offset, draw_count| IndirectDrawError::OutOfBounds {
+        offset,
+        draw_count,
+        size: SIZE,
+    };
+    assert_eq!(draw(0, 5, stride).commands(SIZE), Err(out_of_bounds(0, 5)));
+    assert_eq!(
+        draw(2 * stride as u64, 2, 2 * stride).commands(SIZE),
+        Err(out_of_bounds(2 * stride as u64, 2)),
+    );
+    assert_eq!(
+        draw(0, u32::MAX, u32::MAX / stride * stride).commands(SIZE),
+        Err(out_of_bounds(0, u32::MAX)),
+    );
+}
@@ -0,0 +1,352 @@
+//! Buffers which are persistently mapped into host memory.
+//!
+//! Memory of the buffer is allocated from the block of [`MemoryAllocator`] which is mapped
+//! for its whole lifetime, so data written on each frame (like camera uniforms or UI vertices)
+//! is copied directly into the buffer. If memory of the buffer is not `HOST_COHERENT`, written ranges
+//! are flushed explicitly, expanded to the `non_coherent_atom_size` of the device.
+
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::Range;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::buffer::sys::{BufferCreationError, UnsafeBuffer};
+use vulkano::buffer::{BufferAccess, BufferInner, BufferUsage, TypedBufferAccess};
+use vulkano::device::physical::MemoryType;
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::sync::{AccessError, Sharing};
+use vulkano::{DeviceSize, OomError, VulkanObject};
+
+use super::allocator::{MemoryAllocError, MemoryAllocation, MemoryAllocator};
+
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum MappedBufferCreationError {
+    #[error("mapped buffer must contain at least one element")]
+    Empty,
+
+    #[error("buffer creation failure: {0}")]
+    Creation(#[from] BufferCreationError),
+
+    #[error("buffer memory allocation failure: {0}")]
+    Allocation(#[from] MemoryAllocError),
+
+    #[error("failed to bind memory to the buffer: {0}")]
+    Bind(#[from] OomError),
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum MappedBufferWriteError {
+    #[error("{count} element(s) at offset {offset} exceed buffer of {len} elements")]
+    OutOfBounds {
+        offset: usize,
+        count: usize,
+        len: usize,
+    },
+
+    #[error("failed to flush written memory: {0}")]
+    Flush(vk::Result),
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum MappedBufferReadError {
+    #[error("range {start}..{end} exceeds buffer of {len} bytes")]
+    OutOfBounds {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+
+    #[error("failed to invalidate mapped memory: {0}")]
+    Invalidate(vk::Result),
+}
+
+/// Buffer of `T` elements which memory is mapped for the whole lifetime of the buffer.
+///
+/// Buffer is not synchronized with the device: data must not be written
+/// while commands which read it are executed (for example, buffer of the frame
+/// is written only after the previous frame which used it is finished).
+///
+pub(crate) struct MappedBuffer<T> {
+    // Buffer is destroyed before its memory is returned to the allocator.
+    inner: UnsafeBuffer,
+    allocation: MemoryAllocation,
+    /// Pointer to the mapped memory, valid until the buffer is dropped.
+    pointer: *mut T,
+    len: usize,
+    /// Offset of the end of the allocation in its block (in bytes).
+    memory_size: DeviceSize,
+    non_coherent_atom_size: DeviceSize,
+    /// Serializes writes from different threads.
+    write_lock: Mutex<()>,
+    marker: PhantomData<T>,
+}
+
+// Safety: mapped memory is owned by the buffer, and writes are serialized by the lock.
+unsafe impl<T> Send for MappedBuffer<T> where T: Send {}
+unsafe impl<T> Sync for MappedBuffer<T> where T: Sync {}
+
+impl<T> MappedBuffer<T>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates buffer of `len` elements in the host visible memory.
+    ///
+    /// Memory which is also device local is preferred.
+    ///
+    pub fn new(
+        allocator: &Arc<MemoryAllocator>,
+        usage: BufferUsage,
+        len: usize,
+    ) -> Result<Arc<Self>, MappedBufferCreationError> {
+        Self::with_memory_type(allocator, usage, len, |memory_type| {
+            memory_type.is_device_local()
+        })
+    }
+
+    /// Creates buffer of `len` elements in the host visible memory,
+    /// preferring memory types which satisfy the predicate.
+    pub fn with_memory_type(
+        allocator: &Arc<MemoryAllocator>,
+        usage: BufferUsage,
+        len: usize,
+        prefer: impl Fn(MemoryType) -> bool,
+    ) -> Result<Arc<Self>, MappedBufferCreationError> {
+        if len == 0 {
+            return Err(MappedBufferCreationError::Empty);
+        }
+        let device = allocator.device();
+        let size = (len * size_of::<T>()) as DeviceSize;
+        let (inner, requirements) = unsafe {
+            let sharing = Sharing::Exclusive::<std::iter::Empty<u32>>;
+            UnsafeBuffer::new(device.clone(), size, usage, sharing, None)?
+        };
+
+        let allocation = allocator.allocate(&requirements, prefer)?;
+        unsafe { inner.bind_memory(allocation.memory(), allocation.offset())? };
+
+        let physical_device = device.physical_device();
+        Ok(Arc::new(Self {
+            inner,
+            pointer: allocation.pointer().cast(),
+            len,
+            memory_size: allocation.end(),
+            allocation,
+            non_coherent_atom_size: physical_device.properties().non_coherent_atom_size,
+            write_lock: Mutex::new(()),
+            marker: PhantomData,
+        }))
+    }
+
+    /// Count of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if memory of the buffer must be flushed after writes.
+    pub fn is_coherent(&self) -> bool {
+        self.allocation.is_coherent()
+    }
+
+    /// Writes the element at given index.
+    pub fn write(&self, index: usize, value: T) -> Result<(), MappedBufferWriteError> {
+        self.write_slice(index, &[value])
+    }
+
+    /// Writes elements starting from given offset (in elements).
+    ///
+    /// Written range is flushed if memory of the buffer is not coherent.
+    ///
+    pub fn write_slice(&self, offset: usize, values: &[T]) -> Result<(), MappedBufferWriteError> {
+        let count = values.len();
+        let end = offset.checked_add(count).filter(|&end| end <= self.len);
+        if end.is_none() {
+            return Err(MappedBufferWriteError::OutOfBounds {
+                offset,
+                count,
+                len: self.len,
+            });
+        }
+        if count == 0 {
+            return Ok(());
+        }
+
+        let start = offset * size_of::<T>();
+        // Safety: range was checked to be inside of the mapped memory.
+        unsafe { self.copy_bytes(start, values.as_ptr().cast(), count * size_of::<T>()) }
+    }
+
+    /// Copies bytes into the mapped memory starting from given offset (in bytes)
+    /// and flushes them if memory of the buffer is not coherent.
+    ///
+    /// # Safety
+    ///
+    /// Range of bytes must be inside of the buffer.
+    ///
+    unsafe fn copy_bytes(
+        &self,
+        start: usize,
+        source: *const u8,
+        count: usize,
+    ) -> Result<(), MappedBufferWriteError> {
+        let _lock = self.write_lock.lock().unwrap();
+        ptr::copy_nonoverlapping(source, self.pointer.cast::<u8>().add(start), count);
+        if self.is_coherent() {
+            return Ok(());
+        }
+
+        // Ranges are flushed in offsets of the whole block.
+        let offset = self.allocation.offset();
+        let end = offset + (start + count) as DeviceSize;
+        let start = offset + start as DeviceSize;
+        let range = self::flush_range(start..end, self.non_coherent_atom_size, self.memory_size);
+        let range = vk::MappedMemoryRange {
+            memory: self.allocation.memory().internal_object(),
+            offset: range.start,
+            size: range.end - range.start,
+            ..Default::default()
+        };
+        let device = self.inner.device();
+        let result =
+            device
+                .fns()
+                .v1_0
+                .flush_mapped_memory_ranges(device.internal_object(), 1, &range);
+        match result {
+            vk::Result::SUCCESS => Ok(()),
+            error => Err(MappedBufferWriteError::Flush(error)),
+        }
+    }
+}
+
+impl MappedBuffer<u8> {
+    /// Writes values of any type starting from given offset (in bytes).
+    ///
+    /// Offset should be aligned for `U`, so the device reads values
+    /// with the same layout as the host.
+    ///
+    pub fn write_values<U>(&self, offset: usize, values: &[U]) -> Result<(), MappedBufferWriteError>
+    where
+        U: Copy,
+    {
+        let count = std::mem::size_of_val(values);
+        let end = offset.checked_add(count).filter(|&end| end <= self.len);
+        if end.is_none() {
+            return Err(MappedBufferWriteError::OutOfBounds {
+                offset,
+                count,
+                len: self.len,
+            });
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        // Safety: range was checked to be inside of the mapped memory.
+        unsafe { self.copy_bytes(offset, values.as_ptr().cast(), count) }
+    }
+
+    /// Reads bytes of the range, which were written by the device.
+    ///
+    /// Range is invalidated first if memory of the buffer is not coherent.
+    /// Device must have finished writing the range (for example, its fence is signaled).
+    ///
+    pub fn read_bytes(&self, range: Range<usize>) -> Result<Vec<u8>, MappedBufferReadError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(MappedBufferReadError::OutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+        if !self.is_coherent() && !range.is_empty() {
+            let offset = self.allocation.offset();
+            let read = offset + range.start as DeviceSize..offset + range.end as DeviceSize;
+            // Invalidated range has the same requirements as flushed one.
+            let range = self::flush_range(read, self.non_coherent_atom_size, self.memory_size);
+            let range = vk::MappedMemoryRange {
+                memory: self.allocation.memory().internal_object(),
+                offset: range.start,
+                size: range.end - range.start,
+                ..Default::default()
+            };
+            let device = self.inner.device();
+            let result = unsafe {
+                device.fns().v1_0.invalidate_mapped_memory_ranges(
+                    device.internal_object(),
+                    1,
+                    &range,
+                )
+            };
+            if result != vk::Result::SUCCESS {
+                return Err(MappedBufferReadError::Invalidate(result));
+            }
+        }
+        // Safety: range was checked to be inside of the mapped memory.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(self.pointer.add(range.start), range.end - range.start)
+        };
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Range of memory (in bytes) which must be flushed after writing to the range of bytes.
+///
+/// Flushed range must start and end at multiples of `atom_size`
+/// or at the end of the allocation which ends at `memory_size` bytes.
+///
+pub(crate) fn flush_range(
+    written: Range<DeviceSize>,
+    atom_size: DeviceSize,
+    memory_size: DeviceSize,
+) -> Range<DeviceSize> {
+    let atom_size = atom_size.max(1);
+    let start = written.start / atom_size * atom_size;
+    let end = (written.end + atom_size - 1) / atom_size * atom_size;
+    start..end.min(memory_size)
+}
+
+unsafe impl<T> BufferAccess for MappedBuffer<T>
+where
+    T: Send + Sync,
+{
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+
+    fn conflict_key(&self) -> (u64, u64) {
+        (self.inner.key(), 0)
+    }
+
+    fn try_gpu_lock(&self, _: bool, _: &Queue) -> Result<(), AccessError> {
+        // Buffer is written by the host without locks (see docs of the buffer).
+        Ok(())
+    }
+
+    unsafe fn increase_gpu_lock(&self) {}
+
+    unsafe fn unlock(&self) {}
+}
+
+unsafe impl<T> TypedBufferAccess for MappedBuffer<T>
+where
+    T: Send + Sync,
+{
+    type Content = [T];
+}
+
+unsafe impl<T> DeviceOwned for MappedBuffer<T> {
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
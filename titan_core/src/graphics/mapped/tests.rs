@@ -0,0 +1,96 @@
+#![cfg(test)]
+
+use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{DeviceExtensions, Features};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::sync::GpuFuture;
+use vulkano::Version;
+
+use super::*;
+
+#[test]
+fn test_flush_range() {
+    // Aligned range is flushed as is.
+    assert_eq!(flush_range(64..128, 64, 256), 64..128);
+    // Unaligned range is expanded to atoms.
+    assert_eq!(flush_range(4..8, 64, 256), 0..64);
+    assert_eq!(flush_range(60..68, 64, 256), 0..128);
+    // Range at the end of memory is clamped to its size.
+    assert_eq!(flush_range(200..210, 64, 210), 192..210);
+    // Zero atom size is treated as one byte.
+    assert_eq!(flush_range(3..5, 0, 8), 3..5);
+}
+
+/// Checks that the device sees data written into the buffer from the non-coherent memory
+/// (if the device has one).
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_device_sees_written_data() {
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device
+        .queue_families()
+        .find(|family| family.explicitly_supports_transfers() || family.supports_graphics())
+        .unwrap();
+    let (device, mut queues) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    const LEN: usize = 256;
+    let allocator = MemoryAllocator::new(device.clone());
+    let buffer = MappedBuffer::<u32>::with_memory_type(
+        &allocator,
+        BufferUsage::transfer_source(),
+        LEN,
+        |memory_type| !memory_type.is_host_coherent(),
+    )
+    .unwrap();
+
+    // Writes which are not aligned to the atom size.
+    let values: Vec<u32> = (0..LEN as u32).collect();
+    buffer.write_slice(0, &values[..3]).unwrap();
+    buffer.write_slice(3, &values[3..LEN - 1]).unwrap();
+    buffer.write(LEN - 1, values[LEN - 1]).unwrap();
+    assert_eq!(
+        buffer.write(LEN, 0),
+        Err(MappedBufferWriteError::OutOfBounds {
+            offset: LEN,
+            count: 1,
+            len: LEN,
+        })
+    );
+
+    let destination = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        (0..LEN).map(|_| 0u32),
+    )
+    .unwrap();
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device,
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    builder.copy_buffer(buffer, destination.clone()).unwrap();
+    let command_buffer = builder.build().unwrap();
+    command_buffer
+        .execute(queue)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    let copied = destination.read().unwrap();
+    assert_eq!(&copied[..], &values[..]);
+}
@@ -0,0 +1,192 @@
+//! Layout of the parameter block of the material which is derived from reflection of shaders.
+
+use ash::vk;
+
+use crate::graphics::{
+    descriptor::{DescriptorBinding as LayoutBinding, DescriptorLayoutBuilder},
+    reflect::{DescriptorKind, PipelineReflection, ShaderStage},
+    shader_set::ShaderSet,
+};
+
+use super::{MaterialError, MaterialParam, MaterialParams};
+
+/// Parameter of the material which is bound to one binding of the material set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialSlot {
+    /// Name of the variable in shaders which the parameter is looked up by.
+    pub name: String,
+    /// Index of the binding in the material set.
+    pub binding: u32,
+    /// Type of the descriptor: uniform buffer or combined image sampler.
+    pub kind: DescriptorKind,
+    /// Size of the uniform block (in bytes), zero for textures.
+    pub size: u32,
+    /// Stages which use the binding, in ascending order.
+    pub stages: Vec<ShaderStage>,
+}
+
+/// Parameters of the material in ascending order of their bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialLayout {
+    set: u32,
+    slots: Vec<MaterialSlot>,
+}
+
+impl MaterialLayout {
+    /// Derives layout from bindings of the descriptor set with given index
+    /// which are used by the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the pipeline uses no bindings of the set,
+    /// or some of them has no name, is an array or is neither
+    /// a uniform buffer nor a combined image sampler.
+    ///
+    pub fn from_reflection(
+        reflection: &PipelineReflection,
+        set: u32,
+    ) -> Result<Self, MaterialError> {
+        let mut slots = reflection
+            .bindings
+            .iter()
+            .filter(|binding| binding.set == set)
+            .map(|binding| {
+                if binding.name.is_empty() {
+                    return Err(MaterialError::UnnamedBinding(binding.binding));
+                }
+                if !matches!(
+                    binding.kind,
+                    DescriptorKind::UniformBuffer | DescriptorKind::CombinedImageSampler
+                ) {
+                    return Err(MaterialError::UnsupportedBinding {
+                        name: binding.name.clone(),
+                        kind: binding.kind,
+                    });
+                }
+                if binding.count != 1 {
+                    return Err(MaterialError::ArrayBinding(binding.name.clone()));
+                }
+                Ok(MaterialSlot {
+                    name: binding.name.clone(),
+                    binding: binding.binding,
+                    kind: binding.kind,
+                    size: binding.size,
+                    stages: binding.stages.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if slots.is_empty() {
+            return Err(MaterialError::NoBindings(set));
+        }
+        slots.sort_by_key(|slot| slot.binding);
+        Ok(Self { set, slots })
+    }
+
+    /// Validates shaders of the pipeline and derives layout
+    /// from bindings of the descriptor set with given index.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if shaders could not form one pipeline
+    /// (see [`ShaderSet::validate`]) or the layout could not be derived
+    /// (see [`MaterialLayout::from_reflection`]).
+    ///
+    pub fn from_shaders(shaders: &ShaderSet, set: u32) -> Result<Self, MaterialError> {
+        let reflection = shaders.validate()?;
+        Self::from_reflection(&reflection, set)
+    }
+
+    /// Index of the descriptor set of the material.
+    pub fn set(&self) -> u32 {
+        self.set
+    }
+
+    /// Parameters in ascending order of their bindings.
+    pub fn slots(&self) -> &[MaterialSlot] {
+        &self.slots
+    }
+
+    /// Parameter with given name, if any.
+    pub fn slot(&self, name: &str) -> Option<&MaterialSlot> {
+        self.slots.iter().find(|slot| slot.name == name)
+    }
+
+    /// Describes descriptor set layout of the material,
+    /// so it could be created before any pipeline which uses it.
+    pub fn descriptor_layout(&self) -> DescriptorLayoutBuilder {
+        self.slots
+            .iter()
+            .fold(DescriptorLayoutBuilder::new(), |builder, slot| {
+                let ty = match slot.kind {
+                    DescriptorKind::UniformBuffer => vk::DescriptorType::UNIFORM_BUFFER,
+                    _ => vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                };
+                builder.binding(LayoutBinding {
+                    binding: slot.binding,
+                    ty,
+                    count: 1,
+                    stages: self::stage_flags(&slot.stages),
+                })
+            })
+    }
+
+    /// Matches parameters with slots of the layout by their names,
+    /// returning them in ascending order of their bindings.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if some parameter is not used by shaders,
+    /// is not set, or its type or size does not match its binding.
+    ///
+    pub fn resolve<'a>(
+        &'a self,
+        params: &'a MaterialParams,
+    ) -> Result<Vec<(&'a MaterialSlot, &'a MaterialParam)>, MaterialError> {
+        if let Some((name, _)) = params.iter().find(|(name, _)| self.slot(name).is_none()) {
+            return Err(MaterialError::UnknownParam(name.to_string()));
+        }
+        self.slots
+            .iter()
+            .map(|slot| {
+                let param = params
+                    .get(&slot.name)
+                    .ok_or_else(|| MaterialError::MissingParam(slot.name.clone()))?;
+                if param.kind() != slot.kind {
+                    return Err(MaterialError::ParamMismatch {
+                        name: slot.name.clone(),
+                        expected: slot.kind,
+                        actual: param.kind(),
+                    });
+                }
+                if let MaterialParam::Uniform(bytes) = param {
+                    if bytes.len() < slot.size as usize {
+                        return Err(MaterialError::UniformTooSmall {
+                            name: slot.name.clone(),
+                            size: bytes.len(),
+                            expected: slot.size,
+                        });
+                    }
+                }
+                Ok((slot, param))
+            })
+            .collect()
+    }
+}
+
+fn stage_flags(stages: &[ShaderStage]) -> vk::ShaderStageFlags {
+    stages
+        .iter()
+        .fold(vk::ShaderStageFlags::empty(), |flags, stage| {
+            flags
+                | match stage {
+                    ShaderStage::Vertex => vk::ShaderStageFlags::VERTEX,
+                    ShaderStage::TessellationControl => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+                    ShaderStage::TessellationEvaluation => {
+                        vk::ShaderStageFlags::TESSELLATION_EVALUATION
+                    }
+                    ShaderStage::Geometry => vk::ShaderStageFlags::GEOMETRY,
+                    ShaderStage::Fragment => vk::ShaderStageFlags::FRAGMENT,
+                    ShaderStage::Compute => vk::ShaderStageFlags::COMPUTE,
+                }
+        })
+}
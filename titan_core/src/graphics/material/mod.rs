@@ -0,0 +1,194 @@
+//! Materials of user pipelines: a pipeline with a block of textures and uniform values.
+//!
+//! Layout of the parameter block is not described by hand: it is derived from
+//! bindings of one descriptor set of the pipeline which are reflected from its shaders
+//! (see [`ShaderSet`]). Parameters are matched with bindings by names of their variables,
+//! so a material is created from a pipeline, its shaders and named parameters.
+//!
+//! Materials could be created by [render hooks](super::RenderHook) as soon as their
+//! pipelines are, because only the device is needed. Parameters are written into
+//! a persistent descriptor set once when the material is created. Values which change
+//! each frame should be given with push constants or with
+//! [`UniformBuffer`](super::UniformBuffer) in another descriptor set.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::device::Device;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
+
+use super::{
+    reflect::DescriptorKind,
+    renderer::error::DescriptorSetCreationError,
+    shader_set::{ShaderSet, ShaderSetError},
+};
+
+pub use self::layout::{MaterialLayout, MaterialSlot};
+pub use self::params::{MaterialParam, MaterialParams, MaterialTexture};
+
+mod layout;
+mod params;
+mod tests;
+
+/// Error that can happen when layout of the material is derived or its parameters are matched.
+#[derive(Debug, Error)]
+pub enum MaterialError {
+    #[error("shaders are invalid: {0}")]
+    Shaders(#[from] ShaderSetError),
+
+    #[error("shaders use no bindings of descriptor set {0}")]
+    NoBindings(u32),
+
+    #[error("binding {0} of the material set has no name (debug names were stripped)")]
+    UnnamedBinding(u32),
+
+    #[error("binding `{name}` is {kind}, but only uniform buffers and combined image samplers are supported")]
+    UnsupportedBinding { name: String, kind: DescriptorKind },
+
+    #[error("binding `{0}` is an array, which is not supported")]
+    ArrayBinding(String),
+
+    #[error("parameter `{0}` is not used by shaders")]
+    UnknownParam(String),
+
+    #[error("parameter `{0}` is used by shaders, but was not set")]
+    MissingParam(String),
+
+    #[error("parameter `{name}` is {actual}, but {expected} is used by shaders")]
+    ParamMismatch {
+        name: String,
+        expected: DescriptorKind,
+        actual: DescriptorKind,
+    },
+
+    #[error("uniform parameter `{name}` has {size} bytes, but its block has {expected} bytes")]
+    UniformTooSmall {
+        name: String,
+        size: usize,
+        expected: u32,
+    },
+}
+
+/// Error that can happen when the material is created.
+#[derive(Debug, Error)]
+pub enum MaterialCreationError {
+    #[error("invalid material: {0}")]
+    Material(#[from] MaterialError),
+
+    #[error("pipeline has no descriptor set {0}")]
+    NoPipelineSet(u32),
+
+    #[error("uniform buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+}
+
+/// Graphics pipeline with the descriptor set of its parameters.
+///
+/// Materials are intended for pipelines of [render hooks](super::RenderHook):
+/// [`Material::bind`] binds both the pipeline and the material set,
+/// so only vertex buffers and other sets (if any) are left to the hook.
+/// Resources of parameters are kept alive by the material.
+///
+pub struct Material {
+    pipeline: Arc<GraphicsPipeline>,
+    layout: MaterialLayout,
+    descriptor_set: Arc<dyn DescriptorSet + Send + Sync>,
+}
+
+impl Material {
+    /// Creates material of the pipeline with parameters in the descriptor set
+    /// with given index, matching them with bindings of shaders of the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if parameters do not match bindings of shaders
+    /// (see [`MaterialLayout::resolve`]), the pipeline has no such set,
+    /// or uniform buffers could not be allocated.
+    ///
+    pub fn new(
+        device: &Arc<Device>,
+        pipeline: Arc<GraphicsPipeline>,
+        shaders: &ShaderSet,
+        set: u32,
+        params: &MaterialParams,
+    ) -> Result<Self, MaterialCreationError> {
+        let layout = MaterialLayout::from_shaders(shaders, set)?;
+        let set_layout = pipeline
+            .layout()
+            .descriptor_set_layouts()
+            .get(set as usize)
+            .ok_or(MaterialCreationError::NoPipelineSet(set))?
+            .clone();
+
+        let mut builder = PersistentDescriptorSet::start(set_layout);
+        let mut next_binding = 0;
+        for (slot, param) in layout.resolve(params)? {
+            // Bindings which are declared, but not used by shaders, stay empty.
+            while next_binding < slot.binding {
+                builder
+                    .add_empty()
+                    .map_err(DescriptorSetCreationError::from)?;
+                next_binding += 1;
+            }
+            match param {
+                MaterialParam::Uniform(bytes) => {
+                    let usage = BufferUsage::uniform_buffer();
+                    let bytes = bytes.iter().copied();
+                    let buffer =
+                        CpuAccessibleBuffer::from_iter(device.clone(), usage, false, bytes)?;
+                    builder
+                        .add_buffer(buffer)
+                        .map_err(DescriptorSetCreationError::from)?;
+                }
+                MaterialParam::Texture(texture) => {
+                    let sampler = texture.sampler.clone();
+                    builder
+                        .add_sampled_image(texture.image_view.clone(), sampler)
+                        .map_err(DescriptorSetCreationError::from)?;
+                }
+            }
+            next_binding += 1;
+        }
+        let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+
+        Ok(Self {
+            pipeline,
+            layout,
+            descriptor_set: Arc::new(descriptor_set),
+        })
+    }
+
+    /// Pipeline which the material is drawn with.
+    pub fn pipeline(&self) -> &Arc<GraphicsPipeline> {
+        &self.pipeline
+    }
+
+    /// Layout of parameters which was derived from shaders of the pipeline.
+    pub fn layout(&self) -> &MaterialLayout {
+        &self.layout
+    }
+
+    /// Descriptor set with parameters of the material.
+    pub fn descriptor_set(&self) -> &Arc<dyn DescriptorSet + Send + Sync> {
+        &self.descriptor_set
+    }
+
+    /// Binds the pipeline and the descriptor set of the material.
+    pub fn bind<L>(&self, builder: &mut AutoCommandBufferBuilder<L>) {
+        builder
+            .bind_pipeline_graphics(self.pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                self.layout.set(),
+                self.descriptor_set.clone(),
+            );
+    }
+}
@@ -0,0 +1,123 @@
+//! Values of parameters of the material.
+
+use std::fmt;
+use std::sync::Arc;
+
+use bytemuck::Pod;
+use vulkano::image::view::ImageViewAbstract;
+use vulkano::sampler::Sampler;
+
+use crate::graphics::reflect::DescriptorKind;
+
+/// Texture parameter of the material: an image view which is sampled with the sampler.
+#[derive(Clone)]
+pub struct MaterialTexture {
+    /// View of the image which must be in the `SHADER_READ_ONLY_OPTIMAL` layout.
+    pub image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+    /// Sampler which the image is sampled with.
+    pub sampler: Arc<Sampler>,
+}
+
+impl fmt::Debug for MaterialTexture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaterialTexture").finish_non_exhaustive()
+    }
+}
+
+/// Value of one parameter of the material.
+#[derive(Debug, Clone)]
+pub enum MaterialParam {
+    /// Texture which is bound to the combined image sampler.
+    Texture(MaterialTexture),
+    /// Bytes of the uniform block which are copied into the uniform buffer.
+    Uniform(Vec<u8>),
+}
+
+impl MaterialParam {
+    /// Type of the descriptor which the parameter is bound to.
+    pub fn kind(&self) -> DescriptorKind {
+        match self {
+            Self::Texture(_) => DescriptorKind::CombinedImageSampler,
+            Self::Uniform(_) => DescriptorKind::UniformBuffer,
+        }
+    }
+}
+
+/// Parameter block of the material: named textures and uniform values.
+///
+/// Parameters are matched with bindings of shaders by names of their variables
+/// when the material is created.
+///
+#[derive(Debug, Clone, Default)]
+pub struct MaterialParams {
+    params: Vec<(String, MaterialParam)>,
+}
+
+impl MaterialParams {
+    /// Creates an empty parameter block.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets texture with given name, replacing existing parameter with the same name.
+    pub fn with_texture(
+        self,
+        name: impl Into<String>,
+        image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
+    ) -> Self {
+        let texture = MaterialTexture {
+            image_view,
+            sampler,
+        };
+        self.with_param(name, MaterialParam::Texture(texture))
+    }
+
+    /// Sets uniform block with given name, replacing existing parameter with the same name.
+    ///
+    /// Value must have the layout of the block in shaders (`std140`).
+    ///
+    pub fn with_uniform<T: Pod>(self, name: impl Into<String>, value: &T) -> Self {
+        let bytes = bytemuck::bytes_of(value).to_vec();
+        self.with_param(name, MaterialParam::Uniform(bytes))
+    }
+
+    /// Sets parameter with given name, replacing existing one with the same name.
+    pub fn with_param(mut self, name: impl Into<String>, param: MaterialParam) -> Self {
+        let name = name.into();
+        match self
+            .params
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing = param,
+            None => self.params.push((name, param)),
+        }
+        self
+    }
+
+    /// Parameter with given name, if it was set.
+    pub fn get(&self, name: &str) -> Option<&MaterialParam> {
+        self.params
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, param)| param)
+    }
+
+    /// Names and values of parameters in order of their setting.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MaterialParam)> {
+        self.params
+            .iter()
+            .map(|(name, param)| (name.as_str(), param))
+    }
+
+    /// Count of parameters.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Checks if no parameters were set.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+}
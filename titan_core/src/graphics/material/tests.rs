@@ -0,0 +1,162 @@
+#![cfg(test)]
+
+use ash::vk;
+
+use crate::graphics::reflect::{DescriptorBinding, PipelineReflection, ShaderStage};
+
+use super::*;
+
+fn binding(
+    set: u32,
+    binding: u32,
+    kind: DescriptorKind,
+    size: u32,
+    name: &str,
+) -> DescriptorBinding {
+    DescriptorBinding {
+        set,
+        binding,
+        kind,
+        count: 1,
+        size,
+        name: name.to_string(),
+        stages: vec![ShaderStage::Fragment],
+    }
+}
+
+fn reflection(bindings: Vec<DescriptorBinding>) -> PipelineReflection {
+    PipelineReflection {
+        bindings,
+        push_constants: None,
+    }
+}
+
+/// Camera in set 0 and material with a texture and a uniform block in set 1.
+fn material_reflection() -> PipelineReflection {
+    let mut camera = self::binding(0, 0, DescriptorKind::UniformBuffer, 128, "camera");
+    camera.stages = vec![ShaderStage::Vertex];
+    self::reflection(vec![
+        camera,
+        self::binding(1, 0, DescriptorKind::CombinedImageSampler, 0, "albedo"),
+        self::binding(1, 2, DescriptorKind::UniformBuffer, 16, "params"),
+    ])
+}
+
+#[test]
+fn test_material_layout() {
+    let layout = MaterialLayout::from_reflection(&self::material_reflection(), 1).unwrap();
+    assert_eq!(layout.set(), 1);
+    let slots: Vec<_> = layout
+        .slots()
+        .iter()
+        .map(|slot| (slot.name.as_str(), slot.binding, slot.kind, slot.size))
+        .collect();
+    assert_eq!(
+        slots,
+        [
+            ("albedo", 0, DescriptorKind::CombinedImageSampler, 0),
+            ("params", 2, DescriptorKind::UniformBuffer, 16),
+        ]
+    );
+    assert_eq!(layout.slot("params").map(|slot| slot.binding), Some(2));
+    assert!(layout.slot("camera").is_none());
+
+    let builder = layout.descriptor_layout();
+    builder.validate().unwrap();
+    let bindings: Vec<_> = builder
+        .bindings()
+        .iter()
+        .map(|binding| (binding.binding, binding.ty, binding.stages))
+        .collect();
+    let fragment = vk::ShaderStageFlags::FRAGMENT;
+    assert_eq!(
+        bindings,
+        [
+            (0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, fragment),
+            (2, vk::DescriptorType::UNIFORM_BUFFER, fragment),
+        ]
+    );
+}
+
+#[test]
+fn test_material_layout_errors() {
+    let result = MaterialLayout::from_reflection(&self::material_reflection(), 2);
+    assert!(matches!(result, Err(MaterialError::NoBindings(2))));
+
+    let unnamed = self::binding(1, 3, DescriptorKind::UniformBuffer, 16, "");
+    let result = MaterialLayout::from_reflection(&self::reflection(vec![unnamed]), 1);
+    assert!(matches!(result, Err(MaterialError::UnnamedBinding(3))));
+
+    let storage = self::binding(1, 0, DescriptorKind::StorageBuffer, 16, "particles");
+    let result = MaterialLayout::from_reflection(&self::reflection(vec![storage]), 1);
+    assert!(matches!(
+        result,
+        Err(MaterialError::UnsupportedBinding {
+            kind: DescriptorKind::StorageBuffer,
+            ..
+        })
+    ));
+
+    let mut array = self::binding(1, 0, DescriptorKind::CombinedImageSampler, 0, "layers");
+    array.count = 4;
+    let result = MaterialLayout::from_reflection(&self::reflection(vec![array]), 1);
+    assert!(matches!(result, Err(MaterialError::ArrayBinding(name)) if name == "layers"));
+}
+
+#[test]
+fn test_resolve_params() {
+    let reflection = self::reflection(vec![
+        self::binding(0, 1, DescriptorKind::UniformBuffer, 8, "tint"),
+        self::binding(0, 0, DescriptorKind::UniformBuffer, 4, "time"),
+    ]);
+    let layout = MaterialLayout::from_reflection(&reflection, 0).unwrap();
+
+    let params = MaterialParams::new()
+        .with_uniform("tint", &[1.0_f32, 0.5])
+        .with_uniform("time", &0.0_f32)
+        .with_uniform("time", &2.0_f32);
+    assert_eq!(params.len(), 2);
+    let resolved = layout.resolve(&params).unwrap();
+    let names: Vec<_> = resolved
+        .iter()
+        .map(|(slot, _)| slot.name.as_str())
+        .collect();
+    assert_eq!(names, ["time", "tint"]);
+    match resolved[0].1 {
+        MaterialParam::Uniform(bytes) => assert_eq!(bytes, &2.0_f32.to_ne_bytes()),
+        param => panic!("unexpected parameter: {:?}", param),
+    }
+
+    let unknown = params.clone().with_uniform("speed", &1.0_f32);
+    assert!(matches!(
+        layout.resolve(&unknown),
+        Err(MaterialError::UnknownParam(name)) if name == "speed"
+    ));
+    let missing = MaterialParams::new().with_uniform("time", &0.0_f32);
+    assert!(matches!(
+        layout.resolve(&missing),
+        Err(MaterialError::MissingParam(name)) if name == "tint"
+    ));
+    let small = params.with_uniform("tint", &1.0_f32);
+    assert!(matches!(
+        layout.resolve(&small),
+        Err(MaterialError::UniformTooSmall {
+            size: 4,
+            expected: 8,
+            ..
+        })
+    ));
+
+    let layout = MaterialLayout::from_reflection(&self::material_reflection(), 1).unwrap();
+    let params = MaterialParams::new()
+        .with_uniform("albedo", &[0_u32; 4])
+        .with_uniform("params", &[0_u32; 4]);
+    assert!(matches!(
+        layout.resolve(&params),
+        Err(MaterialError::ParamMismatch {
+            expected: DescriptorKind::CombinedImageSampler,
+            actual: DescriptorKind::UniformBuffer,
+            ..
+        })
+    ));
+}
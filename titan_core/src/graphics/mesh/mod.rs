@@ -0,0 +1,117 @@
+//! Meshes with user geometry which are uploaded into device local memory.
+
+use std::sync::Arc;
+
+use slotmap::new_key_type;
+use thiserror::Error;
+use vulkano::buffer::BufferUsage;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+
+use super::device_buffer::DeviceBuffer;
+use super::index::{self, IndexBufferError, IndexType};
+use super::upload::{StagingRing, UploadError};
+use super::vertex::Vertex;
+
+mod tests;
+
+new_key_type! {
+    /// Identifier of the mesh created by the renderer.
+    pub struct MeshId;
+}
+
+#[derive(Debug, Error)]
+pub enum MeshCreationError {
+    #[error("mesh must contain at least one vertex")]
+    NoVertices,
+
+    #[error("invalid indices of mesh: {0}")]
+    Indices(#[from] IndexBufferError),
+
+    #[error("mesh upload failure: {0}")]
+    Upload(#[from] UploadError),
+}
+
+/// Indices of the mesh with their type.
+#[derive(Clone)]
+enum Indices {
+    U16(Arc<DeviceBuffer<u16>>),
+    U32(Arc<DeviceBuffer<u32>>),
+}
+
+/// Vertex and index buffers of the mesh in device local memory.
+///
+/// Like [`IndexBuffer`](super::IndexBuffer), indices are stored as 16-bit ones
+/// if all vertices could be indexed by them.
+///
+#[derive(Clone)]
+pub struct Mesh {
+    vertices: Arc<DeviceBuffer<Vertex>>,
+    indices: Indices,
+    vertex_count: u32,
+    index_count: u32,
+}
+
+impl Mesh {
+    /// Uploads vertices and indices of the mesh through the staging ring.
+    ///
+    /// Upload is not waited for by the host: the next rendered frame waits for it.
+    ///
+    pub(crate) fn new(
+        staging: &mut StagingRing,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Result<Self, MeshCreationError> {
+        if vertices.is_empty() {
+            return Err(MeshCreationError::NoVertices);
+        }
+        let usage = BufferUsage::index_buffer();
+        let buffer = match index::check(indices, vertices.len())? {
+            IndexType::U16 => {
+                // All indices are less than count of vertices, so they fit into 16 bits.
+                let indices: Vec<_> = indices.iter().map(|&index| index as u16).collect();
+                Indices::U16(staging.upload_buffer(&indices, usage)?)
+            }
+            IndexType::U32 => Indices::U32(staging.upload_buffer(indices, usage)?),
+        };
+        Ok(Self {
+            vertices: staging.upload_buffer(vertices, BufferUsage::vertex_buffer())?,
+            indices: buffer,
+            vertex_count: vertices.len() as u32,
+            index_count: indices.len() as u32,
+        })
+    }
+
+    /// Count of vertices of the mesh.
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    /// Count of indices of the mesh.
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    /// Type of indices of the mesh.
+    pub fn index_type(&self) -> IndexType {
+        match self.indices {
+            Indices::U16(_) => IndexType::U16,
+            Indices::U32(_) => IndexType::U32,
+        }
+    }
+
+    /// Binds vertex and index buffers of the mesh for next indexed draws of the command buffer.
+    ///
+    /// Vertices are bound to the first binding, so the mesh could be drawn
+    /// by pipelines with vertex input of [`Vertex`].
+    ///
+    pub fn bind<'b, L, P>(
+        &self,
+        builder: &'b mut AutoCommandBufferBuilder<L, P>,
+    ) -> &'b mut AutoCommandBufferBuilder<L, P> {
+        builder.bind_vertex_buffers(0, self.vertices.clone());
+        match &self.indices {
+            Indices::U16(buffer) => builder.bind_index_buffer(buffer.clone()),
+            Indices::U32(buffer) => builder.bind_index_buffer(buffer.clone()),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(test)]
+
+use ultraviolet::Vec3;
+
+use crate::graphics::allocator::MemoryAllocator;
+use crate::graphics::utils::graphics_queue;
+use crate::math::Color;
+
+use super::*;
+
+/// Checks that geometry is validated and indices are stored with the smallest type.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_mesh_upload() {
+    let queue = graphics_queue();
+    let allocator = MemoryAllocator::new(queue.device().clone());
+    let mut staging = StagingRing::new(allocator, queue.clone(), queue).unwrap();
+    let vertices = [
+        Vertex::new(Vec3::new(0.0, 0.0, 0.0), Color::WHITE),
+        Vertex::new(Vec3::new(1.0, 0.0, 0.0), Color::WHITE),
+        Vertex::new(Vec3::new(0.0, 1.0, 0.0), Color::WHITE),
+        Vertex::new(Vec3::new(1.0, 1.0, 0.0), Color::WHITE),
+    ];
+
+    let mesh = Mesh::new(&mut staging, &vertices, &[0, 1, 2, 2, 1, 3]).unwrap();
+    assert_eq!((mesh.vertex_count(), mesh.index_count()), (4, 6));
+    assert_eq!(mesh.index_type(), IndexType::U16);
+
+    let result = Mesh::new(&mut staging, &[], &[0]);
+    assert!(matches!(result, Err(MeshCreationError::NoVertices)));
+    let result = Mesh::new(&mut staging, &vertices, &[0, 1, 4]);
+    assert!(matches!(
+        result,
+        Err(MeshCreationError::Indices(
+            IndexBufferError::IndexOutOfRange { index: 4, .. }
+        ))
+    ));
+    staging.abandoned();
+}
@@ -1,12 +1,132 @@
 //! Graphics utilities and backend based on Vulkan API for game engine.
 
+pub use vulkano::image::SampleCount;
+
+pub use self::allocator::{MemoryAllocError, MemoryStats, DEFAULT_BLOCK_SIZE};
+pub use self::capture::{
+    AttachmentCapture, CameraCapture, CapturedCommand, ClearCapture, DrawCapture, FrameCapture,
+    MaterialCapture, PassCapture, SubpassCapture,
+};
+pub use self::clip::{ClipRect, ClipStack};
+pub use self::command::{CommandBuffer, CommandBufferError, OrderError, RecordingState};
+pub use self::debug_callback::{ValidationFilter, ValidationLevel, ValidationSeverity};
+pub use self::descriptor::{
+    DescriptorAllocError, DescriptorAllocator, DescriptorBinding, DescriptorLayout,
+    DescriptorLayoutBuilder, DescriptorLayoutError, DescriptorPoolSizes, DescriptorStats,
+    DescriptorWrites,
+};
+pub use self::device_buffer::{DeviceBuffer, DeviceBufferCreationError};
+pub use self::draw::{DrawCommand, DrawQueue, DrawSubmitError, InstanceData};
+pub use self::frame::post_process::AaMode;
+pub use self::frame::system::{LoadOp, PassOps, StoreOp};
+pub use self::frame_sync::{DEFAULT_FRAMES_IN_FLIGHT, MAX_FRAMES_IN_FLIGHT};
+pub use self::gpu_profiler::GpuTimings;
+pub use self::graph::{
+    GraphImage, GraphPrepareError, GraphRecordError, ImageSize, PassContext, RenderGraph,
+    RenderGraphError,
+};
+pub use self::hook::{FrameContext, HookCommands, HookError, HookStage, RenderHook};
+pub use self::index::{IndexBuffer, IndexBufferError, IndexType};
+pub use self::indirect::{
+    IndirectBufferCreationError, IndirectBufferId, IndirectDraw, IndirectDrawError,
+    IndirectDrawList, INDIRECT_STRIDE,
+};
+pub use self::mapped::{MappedBufferCreationError, MappedBufferWriteError};
+pub use self::material::{
+    Material, MaterialCreationError, MaterialError, MaterialLayout, MaterialParam, MaterialParams,
+    MaterialSlot, MaterialTexture,
+};
+pub use self::mesh::{Mesh, MeshCreationError, MeshId};
+pub use self::particles::{
+    EmitterShape, Particle, ParticleEmitter, ParticleParams, ParticleSystem,
+};
+pub use self::pause::PauseControl;
+pub use self::pipeline_cache::{PersistentPipelineCache, PipelineCacheError};
+pub use self::pipeline_stats::PipelineStatistics;
+pub use self::pre_rotation::PreTransform;
+pub use self::present::PresentMode;
+pub use self::quality::{QualityConfig, QualityController, QualityMonitor};
+pub(crate) use self::readback::rgba_image;
+pub use self::readback::{
+    ImageReadbackInfo, ImageSubresource, ReadbackError, ReadbackImage, ReadbackRecordError,
+    ReadbackTicket, ReadbackWaitError, Screenshot, ScreenshotError,
+};
+pub use self::reflect::{PipelineReflection, ShaderReflection, VertexFormat, VertexFormats};
 pub use self::renderer::*;
+pub use self::sampler::{
+    AddressMode, BorderColor, CompareOp, Filter, MipmapMode, SamplerCache, SamplerDesc,
+};
+pub use self::secondary::{SecondaryWindowCreationError, SecondaryWindowRenderError};
+#[cfg(feature = "glsl")]
+pub use self::shader::glsl::{GlslCompiler, GlslDiagnostic, GlslError};
+pub use self::shader_set::{
+    ShaderFile, ShaderSet, ShaderSetError, ShaderWatcher, StageShader, WatchedShaders,
+};
+pub use self::specialization::{
+    SpecializationError, SpecializationInfo, SpecializationValue, SWAP_RED_BLUE,
+};
+pub use self::sprite::{Sprite, SpriteBatch, SpriteTextureId};
+pub use self::stats::{CullingReport, CullingStats, FrameStats, ResourceList};
+pub use self::streaming::{
+    StreamId, StreamState, StreamingConfig, StreamingManager, StreamingStats,
+};
+pub use self::texture::{BlockFormat, CompressedImage, TextureData, TextureLoadError};
+#[cfg(feature = "fault-injection")]
+pub use self::timeout::FaultInjector;
+pub use self::timeout::TimeoutPolicy;
+pub use self::transient::{TransientAllocError, TransientStats, TransientWriteError};
+pub use self::uniform::UniformBuffer;
+pub use self::upload::{UploadError, STAGING_BLOCK_COUNT, STAGING_BLOCK_SIZE};
+pub use self::vertex::Vertex;
+pub use self::viewport::{Rect, Viewport, ViewportError, ViewportList};
+pub use self::visibility::{RenderLayers, Visibility};
 
 pub(crate) mod camera;
+pub(crate) mod capture;
+pub mod graph;
+pub mod particles;
+pub mod pipeline_cache;
+pub mod quality;
+pub mod reflect;
+pub mod sprite;
+pub mod streaming;
+pub mod texture;
 
+mod allocator;
+mod clip;
+mod command;
 mod debug_callback;
+mod descriptor;
+mod device_buffer;
+mod draw;
 mod frame;
+mod frame_sync;
+mod gpu_profiler;
+mod hook;
+mod index;
+mod indirect;
+mod mapped;
+mod material;
+mod mesh;
+mod pause;
+mod pipeline_stats;
+mod pre_rotation;
+mod present;
+mod readback;
 mod renderer;
+mod sampler;
+mod secondary;
 mod shader;
+mod shader_set;
+mod specialization;
+mod stats;
+mod submit;
+mod target;
+mod timeout;
+mod transient;
+mod uniform;
+mod upload;
 mod utils;
 mod vertex;
+mod viewport;
+mod visibility;
@@ -0,0 +1,67 @@
+use thiserror::Error;
+use vulkano::command_buffer::{BuildError, DispatchError, DrawError, FillBufferError};
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::pipeline::{ComputePipelineCreationError, GraphicsPipelineCreationError};
+use vulkano::OomError;
+
+use crate::graphics::renderer::error::DescriptorSetCreationError;
+
+#[derive(Debug, Error)]
+pub enum ParticleSystemCreationError {
+    #[error("shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("max count of particles must be greater than zero")]
+    NoParticles,
+
+    #[error("compute pipeline creation failure: {0}")]
+    ComputePipelineCreation(#[from] ComputePipelineCreationError),
+
+    #[error("particle buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+}
+
+#[derive(Debug, Error)]
+pub enum ParticleUpdateError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("queue family must support compute operations")]
+    QueueFamilyNotSupported,
+
+    #[error("spawned particle buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("particle buffer clearing failure: {0}")]
+    FillBuffer(#[from] FillBufferError),
+
+    #[error("particle buffer descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("dispatch command failure: {0}")]
+    Dispatch(#[from] DispatchError),
+
+    #[error("compute command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+}
+
+#[derive(Debug, Error)]
+pub enum ParticleDrawError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("queue family must support graphics operations")]
+    QueueFamilyNotSupported,
+
+    #[error("graphics pipeline creation failure: {0}")]
+    GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
+
+    #[error("uniform buffer descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("draw command failure: {0}")]
+    Draw(#[from] DrawError),
+
+    #[error("draw command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+}
@@ -0,0 +1,606 @@
+//! GPU-driven particle system of game engine.
+//!
+//! Particles are integrated by compute shader in two storage buffers
+//! which are swapped on each update (ping-pong), and then rendered as point sprites.
+
+use std::sync::{Arc, Mutex};
+
+use ultraviolet::Vec3;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuBufferPool, DeviceLocalBuffer};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer,
+    SecondaryAutoCommandBuffer,
+};
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline, PipelineBindPoint};
+use vulkano::render_pass::Subpass;
+use vulkano::DeviceSize;
+
+use crate::graphics::{
+    capture::{self, SubpassCapture},
+    particles::error::{ParticleDrawError, ParticleSystemCreationError, ParticleUpdateError},
+    pipeline_stats,
+    renderer::error::DescriptorSetCreationError,
+    sampler::CompareOp,
+    utils,
+    viewport::Region,
+};
+use crate::math::Color;
+
+pub mod error;
+
+mod tests;
+
+/// Count of particles which are processed by one work group of compute shader.
+const WORK_GROUP_SIZE: u32 = 256;
+
+/// Particle which is stored in the storage buffer.
+///
+/// Layout of this type matches layout of particle in the compute shader.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct Particle {
+    /// Position of the particle in the world.
+    pub position: [f32; 3],
+    /// Remaining time of life (in seconds). Particle is dead if it is not positive.
+    pub lifetime: f32,
+    /// Velocity of the particle (in units per second).
+    pub velocity: [f32; 3],
+    /// Time since the particle was spawned (in seconds).
+    pub age: f32,
+}
+
+vulkano::impl_vertex!(Particle, position, lifetime);
+
+impl Particle {
+    /// Creates new particle with given position, velocity and lifetime.
+    pub fn new(position: Vec3, velocity: Vec3, lifetime: f32) -> Self {
+        Self {
+            position: position.into(),
+            lifetime,
+            velocity: velocity.into(),
+            age: 0.0,
+        }
+    }
+
+    /// Returns `true` if the particle is alive.
+    pub fn is_alive(&self) -> bool {
+        self.lifetime > 0.0
+    }
+
+    /// Integrates the particle on the CPU exactly as compute shader does on the GPU.
+    ///
+    /// Semi-implicit Euler method is used: velocity is updated before position.
+    /// Dead particles are not changed.
+    ///
+    pub fn step(self, params: &ParticleParams, delta_time: f32) -> Self {
+        if !self.is_alive() {
+            return self;
+        }
+        let velocity = Vec3::from(self.velocity);
+        let acceleration = params.gravity - params.drag * velocity;
+        let velocity = velocity + acceleration * delta_time;
+        let position = Vec3::from(self.position) + velocity * delta_time;
+        Self {
+            position: position.into(),
+            lifetime: self.lifetime - delta_time,
+            velocity: velocity.into(),
+            age: self.age + delta_time,
+        }
+    }
+}
+
+/// Shape of the area where particles are spawned.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EmitterShape {
+    /// All particles are spawned at one point.
+    Point(Vec3),
+    /// Particles are spawned uniformly inside of the sphere.
+    Sphere { center: Vec3, radius: f32 },
+    /// Particles are spawned uniformly inside of axis-aligned box.
+    Cuboid { min: Vec3, max: Vec3 },
+}
+
+impl Default for EmitterShape {
+    fn default() -> Self {
+        Self::Point(Vec3::zero())
+    }
+}
+
+/// Parameters of particle simulation and rendering.
+#[derive(Debug, Copy, Clone)]
+pub struct ParticleParams {
+    /// Acceleration which is applied to all particles.
+    pub gravity: Vec3,
+    /// Coefficient of linear drag (velocity is decreased proportionally to itself).
+    pub drag: f32,
+    /// Lifetime of spawned particles (in seconds).
+    pub lifetime: f32,
+    /// Velocity of spawned particles.
+    pub velocity: Vec3,
+    /// Max length of random velocity which is added to velocity of spawned particles.
+    pub velocity_spread: f32,
+    /// Count of particles which are spawned by [`ParticleParams::emitter`] each second.
+    pub spawn_rate: f32,
+    /// Shape in which particles are spawned continuously.
+    pub emitter: EmitterShape,
+    /// Color of particles.
+    pub color: Color,
+    /// Size of point sprites (in pixels).
+    ///
+    /// Sizes greater than 1 require `large_points` feature of the device.
+    pub point_size: f32,
+}
+
+impl Default for ParticleParams {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, 0.0, -9.81),
+            drag: 0.0,
+            lifetime: 5.0,
+            velocity: Vec3::zero(),
+            velocity_spread: 1.0,
+            spawn_rate: 0.0,
+            emitter: EmitterShape::default(),
+            color: Color::WHITE,
+            point_size: 1.0,
+        }
+    }
+}
+
+/// Handle which queues particle spawns for the next update of [`ParticleSystem`].
+///
+/// Clones share queued spawns, which are taken once by the next update of the system.
+#[derive(Debug, Default, Clone)]
+pub struct ParticleEmitter {
+    emissions: Arc<Mutex<Vec<(u32, EmitterShape)>>>,
+}
+
+impl ParticleEmitter {
+    /// Queues spawn of `count` particles in given shape for the next update.
+    pub fn emit(&self, count: u32, shape: EmitterShape) {
+        self.emissions.lock().unwrap().push((count, shape));
+    }
+
+    fn take(&self) -> Vec<(u32, EmitterShape)> {
+        std::mem::take(&mut *self.emissions.lock().unwrap())
+    }
+}
+
+/// Simple pseudorandom number generator (xorshift) for particle spawning.
+#[derive(Debug, Copy, Clone)]
+struct Random(u32);
+
+impl Random {
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Returns random number in `0.0..1.0` range.
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Returns random vector inside of unit sphere.
+    fn next_in_sphere(&mut self) -> Vec3 {
+        loop {
+            let vec =
+                Vec3::new(self.next_f32(), self.next_f32(), self.next_f32()) * 2.0 - Vec3::one();
+            if vec.mag_sq() <= 1.0 {
+                return vec;
+            }
+        }
+    }
+}
+
+/// CPU side of particle spawning.
+///
+/// Spawned particles replace the oldest ones in ring order of the particle buffer.
+#[derive(Debug)]
+struct Spawner {
+    params: ParticleParams,
+    emitter: ParticleEmitter,
+    random: Random,
+    /// Fractional count of particles which were not spawned continuously yet.
+    accumulated: f32,
+    /// Index of particle which will be replaced by the next spawned one.
+    cursor: u32,
+    max_particles: u32,
+}
+
+impl Spawner {
+    fn new(max_particles: u32, params: ParticleParams) -> Self {
+        Self {
+            params,
+            emitter: ParticleEmitter::default(),
+            random: Random(0x9E37_79B9),
+            accumulated: 0.0,
+            cursor: 0,
+            max_particles,
+        }
+    }
+
+    fn particle(&mut self, shape: EmitterShape) -> Particle {
+        let position = match shape {
+            EmitterShape::Point(point) => point,
+            EmitterShape::Sphere { center, radius } => {
+                center + self.random.next_in_sphere() * radius
+            }
+            EmitterShape::Cuboid { min, max } => {
+                let t = Vec3::new(
+                    self.random.next_f32(),
+                    self.random.next_f32(),
+                    self.random.next_f32(),
+                );
+                min + (max - min) * t
+            }
+        };
+        let spread = self.random.next_in_sphere() * self.params.velocity_spread;
+        let velocity = self.params.velocity + spread;
+        Particle::new(position, velocity, self.params.lifetime)
+    }
+
+    /// Returns index of the first spawned particle in the buffer and spawned particles.
+    ///
+    /// Count of spawned particles is limited by max count of particles.
+    ///
+    fn spawn(&mut self, delta_time: f32) -> (u32, Vec<Particle>) {
+        self.accumulated += self.params.spawn_rate * delta_time;
+        let continuous = self.accumulated.floor();
+        self.accumulated -= continuous;
+
+        let mut emissions = self.emitter.take();
+        if continuous >= 1.0 {
+            emissions.push((continuous as u32, self.params.emitter));
+        }
+
+        let mut spawned = Vec::new();
+        for (count, shape) in emissions {
+            let available = self.max_particles as usize - spawned.len();
+            let count = (count as usize).min(available);
+            spawned.extend((0..count).map(|_| self.particle(shape)));
+        }
+
+        let start = self.cursor;
+        self.cursor = (self.cursor + spawned.len() as u32) % self.max_particles;
+        (start, spawned)
+    }
+}
+
+/// System which simulates particles on the GPU with compute shader
+/// and renders them as point sprites.
+pub struct ParticleSystem {
+    device: Arc<Device>,
+
+    /// Storage buffers of particles which are swapped on each update.
+    buffers: [Arc<DeviceLocalBuffer<[Particle]>>; 2],
+
+    /// Index of the buffer which contains particles of the last update.
+    current: usize,
+
+    /// Whether particle buffers were cleared after creation.
+    cleared: bool,
+
+    /// Buffer for particles which are spawned on update.
+    spawned_buffer: CpuBufferPool<Particle>,
+
+    /// Cache which all pipelines are created with.
+    pipeline_cache: Arc<PipelineCache>,
+
+    /// Compute pipeline used for particle integration.
+    compute_pipeline: Arc<ComputePipeline>,
+
+    /// Graphics pipeline used for rendering of particles (created on first draw).
+    draw_pipeline: Option<Arc<GraphicsPipeline>>,
+
+    /// Operator which depth of particles is compared with.
+    depth_compare: CompareOp,
+
+    spawner: Spawner,
+}
+
+impl ParticleSystem {
+    /// Creates new particle system with given max count of particles,
+    /// creating its pipelines with given cache.
+    pub(crate) fn new(
+        device: Arc<Device>,
+        max_particles: u32,
+        params: ParticleParams,
+        pipeline_cache: Arc<PipelineCache>,
+    ) -> Result<Self, ParticleSystemCreationError> {
+        if max_particles == 0 {
+            return Err(ParticleSystemCreationError::NoParticles);
+        }
+
+        let compute_pipeline = {
+            use crate::graphics::shader::particles::compute;
+
+            let shader_module = compute::Shader::load(device.clone())?;
+            Arc::new(ComputePipeline::new(
+                device.clone(),
+                &shader_module.main_entry_point(),
+                &(),
+                Some(pipeline_cache.clone()),
+                |_| {},
+            )?)
+        };
+
+        // Buffers are shared between all compute and graphics queue families,
+        // so no ownership transfer is needed if compute queue is separate.
+        let families: Vec<_> = device
+            .active_queue_families()
+            .filter(|family| family.supports_graphics() || family.supports_compute())
+            .collect();
+        let usage = BufferUsage {
+            storage_buffer: true,
+            vertex_buffer: true,
+            transfer_destination: true,
+            ..BufferUsage::none()
+        };
+        let buffer = || {
+            DeviceLocalBuffer::array(
+                device.clone(),
+                max_particles as DeviceSize,
+                usage,
+                families.iter().copied(),
+            )
+        };
+        let buffers = [buffer()?, buffer()?];
+
+        let usage = BufferUsage {
+            storage_buffer: true,
+            ..BufferUsage::none()
+        };
+        let spawned_buffer = CpuBufferPool::new(device.clone(), usage);
+
+        Ok(Self {
+            device,
+            buffers,
+            current: 0,
+            cleared: false,
+            spawned_buffer,
+            pipeline_cache,
+            compute_pipeline,
+            draw_pipeline: None,
+            depth_compare: CompareOp::Less,
+            spawner: Spawner::new(max_particles, params),
+        })
+    }
+
+    /// Max count of particles which could be alive at once.
+    pub fn max_particles(&self) -> u32 {
+        self.spawner.max_particles
+    }
+
+    /// Parameters of particle simulation and rendering.
+    pub fn params(&self) -> &ParticleParams {
+        &self.spawner.params
+    }
+
+    /// Replaces parameters of particle simulation and rendering.
+    pub fn set_params(&mut self, params: ParticleParams) {
+        self.spawner.params = params;
+    }
+
+    /// Queues spawn of `count` particles in given shape for the next update.
+    ///
+    /// If more particles are spawned than the system could hold,
+    /// the oldest particles are replaced.
+    ///
+    pub fn emit(&self, count: u32, shape: EmitterShape) {
+        self.spawner.emitter.emit(count, shape)
+    }
+
+    /// Returns handle which could queue particle spawns for this system.
+    pub fn emitter(&self) -> ParticleEmitter {
+        self.spawner.emitter.clone()
+    }
+
+    /// Builds a command buffer which spawns queued particles and integrates all of them.
+    ///
+    /// Command buffer must be executed before the next [`draw`](ParticleSystem::draw).
+    ///
+    pub(crate) fn update(
+        &mut self,
+        compute_queue: Arc<Queue>,
+        delta_time: f32,
+    ) -> Result<PrimaryAutoCommandBuffer, ParticleUpdateError> {
+        use crate::graphics::shader::particles::compute;
+
+        // Check queue for compute support.
+        if !compute_queue.family().supports_compute() {
+            return Err(ParticleUpdateError::QueueFamilyNotSupported);
+        }
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            compute_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        // Memory of new buffers is uninitialized, so all particles are made dead.
+        if !self.cleared {
+            for buffer in &self.buffers {
+                builder.fill_buffer(buffer.clone(), 0)?;
+            }
+            self.cleared = true;
+        }
+
+        let (spawn_start, spawned) = self.spawner.spawn(delta_time);
+        let spawn_count = spawned.len() as u32;
+        // Buffer of spawned particles must not be empty to be bound.
+        let spawned = if spawned.is_empty() {
+            vec![Particle::default()]
+        } else {
+            spawned
+        };
+        let spawned = self.spawned_buffer.chunk(spawned)?;
+
+        let source = self.buffers[self.current].clone();
+        let destination = self.buffers[1 - self.current].clone();
+        let descriptor_set = {
+            let layout = self.compute_pipeline.layout().descriptor_set_layouts()[0].clone();
+            let mut builder = PersistentDescriptorSet::start(layout);
+            builder
+                .add_buffer(source)
+                .map_err(DescriptorSetCreationError::from)?;
+            builder
+                .add_buffer(destination)
+                .map_err(DescriptorSetCreationError::from)?;
+            builder
+                .add_buffer(Arc::new(spawned))
+                .map_err(DescriptorSetCreationError::from)?;
+            let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            Arc::new(set)
+        };
+
+        let max_particles = self.max_particles();
+        let params = &self.spawner.params;
+        let push_constants = compute::ty::PushConstants {
+            gravity: params.gravity.into(),
+            drag: params.drag,
+            delta_time,
+            spawn_start,
+            spawn_count,
+            max_particles,
+        };
+        let group_count = (max_particles + WORK_GROUP_SIZE - 1) / WORK_GROUP_SIZE;
+        builder
+            .bind_pipeline_compute(self.compute_pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                self.compute_pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .push_constants(self.compute_pipeline.layout().clone(), 0, push_constants)
+            .dispatch([group_count, 1, 1])?;
+        self.current = 1 - self.current;
+        Ok(builder.build()?)
+    }
+
+    /// Sets operator which depth of particles is compared with
+    /// (the same as of the scene they are drawn in).
+    ///
+    /// Graphics pipeline is recreated on the next draw.
+    ///
+    pub(crate) fn set_depth_compare(&mut self, depth_compare: CompareOp) {
+        if self.depth_compare != depth_compare {
+            self.depth_compare = depth_compare;
+            self.draw_pipeline = None;
+        }
+    }
+
+    fn draw_pipeline(
+        &mut self,
+        subpass: Subpass,
+    ) -> Result<Arc<GraphicsPipeline>, ParticleDrawError> {
+        // Pipeline is recreated if render pass was recreated (e.g. with another sample count).
+        if let Some(pipeline) = &self.draw_pipeline {
+            let current = pipeline.subpass();
+            if Arc::ptr_eq(current.render_pass(), subpass.render_pass())
+                && current.index() == subpass.index()
+            {
+                return Ok(pipeline.clone());
+            }
+        }
+
+        use crate::graphics::shader::particles::{fragment, vertex};
+
+        let vert_shader_module = vertex::Shader::load(self.device.clone())?;
+        let frag_shader_module = fragment::Shader::load(self.device.clone())?;
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<Particle>()
+                .vertex_shader(vert_shader_module.main_entry_point(), ())
+                .fragment_shader(frag_shader_module.main_entry_point(), ())
+                .point_list()
+                .viewports_scissors_dynamic(1)
+                .depth_stencil(utils::depth_test(self.depth_compare))
+                .render_pass(subpass)
+                .build_with_cache(self.pipeline_cache.clone())
+                .build(self.device.clone())?,
+        );
+        self.draw_pipeline = Some(pipeline.clone());
+        Ok(pipeline)
+    }
+
+    /// Builds a secondary command buffer that draws live particles
+    /// in the region of given subpass.
+    ///
+    /// Graphics pipeline is created for the subpass of the first draw
+    /// and recreated only when another subpass is used.
+    /// Recorded commands are also described in the capture, if any.
+    ///
+    pub(crate) fn draw<B>(
+        &mut self,
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        region: &Region,
+        uniform_buffer: Arc<B>,
+        capture: Option<&mut SubpassCapture>,
+    ) -> Result<SecondaryAutoCommandBuffer, ParticleDrawError>
+    where
+        B: BufferAccess + Send + Sync + 'static,
+    {
+        use crate::graphics::shader::particles::vertex;
+
+        // Check queue for graphics support.
+        if !graphics_queue.family().supports_graphics() {
+            return Err(ParticleDrawError::QueueFamilyNotSupported);
+        }
+
+        let pipeline = self.draw_pipeline(subpass)?;
+        let mut builder = pipeline_stats::secondary_graphics(
+            self.device.clone(),
+            graphics_queue.family(),
+            pipeline.subpass().clone(),
+        )?;
+
+        let descriptor_set = {
+            let layout = pipeline.layout().descriptor_set_layouts()[0].clone();
+            let mut builder = PersistentDescriptorSet::start(layout);
+            builder
+                .add_buffer(uniform_buffer)
+                .map_err(DescriptorSetCreationError::from)?;
+            let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            Arc::new(set)
+        };
+
+        let params = &self.spawner.params;
+        let push_constants = vertex::ty::PushConstants {
+            color: params.color.into(),
+            point_size: params.point_size,
+        };
+        let particles = self.buffers[self.current].clone();
+        builder
+            .set_viewport(0, std::iter::once(region.viewport()))
+            .set_scissor(0, std::iter::once(region.scissor()))
+            .bind_pipeline_graphics(pipeline.clone())
+            .bind_vertex_buffers(0, particles)
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .push_constants(pipeline.layout().clone(), 0, push_constants)
+            .draw(self.max_particles(), 1, 0, 0)?;
+        if let Some(capture) = capture {
+            capture.set_viewport(region.origin, region.dimensions);
+            capture.bind_pipeline(capture::role_name("GraphicsPipeline", "particles"));
+            capture.bind_descriptor_sets(0, vec![capture::role_name("UniformBuffer", "camera")]);
+            capture.push_constants(&push_constants);
+            let mesh = capture::role_name("Buffer", "particles");
+            capture.draw(mesh, None, self.max_particles(), false, 1);
+        }
+        Ok(builder.build()?)
+    }
+}
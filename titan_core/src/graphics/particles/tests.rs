@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+use ultraviolet::Vec3;
+
+use super::*;
+
+/// Simulates one update of the particle buffer on the CPU like compute shader does.
+fn simulate(
+    particles: &[Particle],
+    spawn_start: u32,
+    spawned: &[Particle],
+    params: &ParticleParams,
+    delta_time: f32,
+) -> Vec<Particle> {
+    let max_particles = particles.len() as u32;
+    (0..max_particles)
+        .map(|index| {
+            let offset = (index + max_particles - spawn_start) % max_particles;
+            match spawned.get(offset as usize) {
+                Some(&particle) => particle,
+                None => particles[index as usize].step(params, delta_time),
+            }
+        })
+        .collect()
+}
+
+fn assert_near(actual: Vec3, expected: Vec3) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-4, "{:?} != {:?}", actual, expected);
+}
+
+#[test]
+fn test_gravity_steps() {
+    let params = ParticleParams {
+        gravity: Vec3::new(0.0, 0.0, -10.0),
+        ..Default::default()
+    };
+    let delta_time = 0.1;
+    let position = Vec3::new(1.0, 2.0, 3.0);
+    let velocity = Vec3::new(1.0, 0.0, 5.0);
+
+    let mut spawner = Spawner::new(4, params);
+    spawner.emitter.emit(1, EmitterShape::Point(position));
+    let (spawn_start, spawned) = spawner.spawn(delta_time);
+    assert_eq!(spawn_start, 0);
+    assert_eq!(spawned.len(), 1);
+    let spawned = [Particle::new(position, velocity, params.lifetime)];
+
+    let mut particles = simulate(&[Particle::default(); 4], 0, &spawned, &params, delta_time);
+    for _ in 0..3 {
+        particles = simulate(&particles, 0, &[], &params, delta_time);
+    }
+
+    // Semi-implicit Euler: v(n) = v0 + g n dt, x(n) = x0 + v0 n dt + g dt^2 n (n + 1) / 2.
+    let n = 3.0;
+    let expected_velocity = velocity + params.gravity * n * delta_time;
+    let expected_position = position
+        + velocity * n * delta_time
+        + params.gravity * delta_time * delta_time * n * (n + 1.0) / 2.0;
+    let particle = particles[0];
+    assert_near(particle.velocity.into(), expected_velocity);
+    assert_near(particle.position.into(), expected_position);
+    assert!((particle.age - n * delta_time).abs() < 1e-5);
+    assert!(particles[1..].iter().all(|particle| !particle.is_alive()));
+}
+
+#[test]
+fn test_dead_particles_unchanged() {
+    let params = ParticleParams::default();
+    let particle = Particle::new(Vec3::one(), Vec3::unit_x(), 0.05);
+    let particle = particle.step(&params, 0.1);
+    assert!(!particle.is_alive());
+    assert_eq!(particle.step(&params, 0.1), particle);
+}
+
+#[test]
+fn test_spawn_ring() {
+    let params = ParticleParams {
+        spawn_rate: 25.0,
+        emitter: EmitterShape::Sphere {
+            center: Vec3::zero(),
+            radius: 2.0,
+        },
+        ..Default::default()
+    };
+    let mut spawner = Spawner::new(8, params);
+
+    // 2.5 particles per update: fractional part is accumulated.
+    let (start, spawned) = spawner.spawn(0.1);
+    assert_eq!((start, spawned.len()), (0, 2));
+    let (start, spawned) = spawner.spawn(0.1);
+    assert_eq!((start, spawned.len()), (2, 3));
+    assert!(spawned
+        .iter()
+        .all(|particle| Vec3::from(particle.position).mag() <= 2.0));
+
+    // Emission is truncated by max count of particles.
+    spawner
+        .emitter
+        .emit(100, EmitterShape::Point(Vec3::unit_z()));
+    let (start, spawned) = spawner.spawn(0.0);
+    assert_eq!((start, spawned.len()), (5, 8));
+    assert_eq!(spawner.cursor, 5);
+}
@@ -0,0 +1,79 @@
+//! Pause and single-step mode of the renderer for debugging of frames.
+//!
+//! While paused, no swapchain image is acquired, submitted or presented,
+//! so the last presented image stays on screen. Events, updates and UI are still processed:
+//! UI is built on each iteration of the main loop and reacts to input
+//! (including pause and step buttons of the stats overlay), but it is presented
+//! only with the next rendered frame.
+//!
+//! UI-only frames were not chosen on purpose: they would need to acquire and present
+//! swapchain images, which changes the image under investigation (and its timings)
+//! just to redraw the UI. The tradeoff is that UI does not visually respond while paused:
+//! hovering is not highlighted, and windows are moved on screen only after the next step.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+mod tests;
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: AtomicBool,
+    step_requested: AtomicBool,
+}
+
+/// Handle which pauses rendering of frames or steps them one at a time.
+///
+/// Clones share one state, so rendering paused through one clone is resumed through any other.
+#[derive(Debug, Default, Clone)]
+pub struct PauseControl {
+    state: Arc<PauseState>,
+}
+
+impl PauseControl {
+    /// Pauses (or resumes) rendering of frames.
+    ///
+    /// Step which was requested but not rendered yet is dropped on resume.
+    ///
+    pub fn set_paused(&self, paused: bool) {
+        self.state.paused.store(paused, Ordering::Relaxed);
+        if !paused {
+            self.state.step_requested.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns `true` if rendering of frames is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses rendering if it is running, resumes otherwise.
+    pub fn toggle(&self) {
+        self.set_paused(!self.is_paused())
+    }
+
+    /// Requests exactly one frame to be rendered while paused.
+    ///
+    /// Steps are not accumulated: several requests before the next frame render one frame.
+    /// Does nothing if rendering is not paused.
+    ///
+    pub fn step_frame(&self) {
+        if self.is_paused() {
+            self.state.step_requested.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns `true` if a frame should be rendered now.
+    ///
+    /// Frame is always rendered if it is `forced` (for example, after swapchain recreation,
+    /// so the window is not left with garbage).
+    ///
+    pub(crate) fn should_render(&self, forced: bool) -> bool {
+        forced || !self.is_paused() || self.state.step_requested.load(Ordering::Relaxed)
+    }
+
+    /// Notifies that a frame was submitted for presentation, so the requested step is done.
+    pub(crate) fn frame_rendered(&self) {
+        self.state.step_requested.store(false, Ordering::Relaxed);
+    }
+}
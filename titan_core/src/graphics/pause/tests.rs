@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_pause_and_step() {
+    let pause = PauseControl::default();
+    assert!(!pause.is_paused());
+    assert!(pause.should_render(false));
+
+    // Steps are ignored while running.
+    pause.step_frame();
+    pause.set_paused(true);
+    assert!(!pause.should_render(false));
+
+    pause.step_frame();
+    pause.step_frame();
+    assert!(pause.should_render(false));
+    // Step is kept until the frame is rendered (for example, if it was skipped).
+    assert!(pause.should_render(false));
+    pause.frame_rendered();
+    assert!(!pause.should_render(false));
+    assert!(pause.should_render(true));
+
+    pause.step_frame();
+    pause.toggle();
+    assert!(!pause.is_paused());
+    pause.toggle();
+    assert!(pause.is_paused());
+    assert!(!pause.should_render(false));
+}
+
+#[test]
+fn test_pause_shared() {
+    let pause = PauseControl::default();
+    let handle = pause.clone();
+    handle.set_paused(true);
+    assert!(pause.is_paused());
+    handle.step_frame();
+    assert!(pause.should_render(false));
+}
@@ -0,0 +1,185 @@
+//! Pipeline cache which is persisted across runs of the application.
+//!
+//! All pipelines of the renderer are created with the same Vulkan pipeline cache.
+//! Its data is stored in the cache directory of the platform on shutdown
+//! and given to the driver on the next launch, so shaders which were already
+//! compiled for the device are not compiled again.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::Device;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::OomError;
+
+use crate::settings;
+
+mod tests;
+
+/// Name of the file with pipeline cache data inside of the cache directory of the game.
+pub const PIPELINE_CACHE_FILE_NAME: &str = "pipeline_cache.bin";
+
+/// Size of the header which precedes pipeline cache data of any driver.
+const HEADER_SIZE: usize = 32;
+
+/// Version of the header which is defined by Vulkan 1.0.
+const HEADER_VERSION_ONE: u32 = 1;
+
+/// Error that can happen when pipeline cache data is saved into the file.
+#[derive(Debug, Error)]
+pub enum PipelineCacheError {
+    #[error("failed to retrieve pipeline cache data: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("failed to write pipeline cache into `{path}`: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Identity of the device and its driver which pipeline cache data was created by.
+///
+/// Drivers must reject data of other devices, but some of them crash on it instead,
+/// so the header of the data is checked before it is given to the driver.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct CacheHeader {
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub uuid: [u8; 16],
+}
+
+impl CacheHeader {
+    /// Header of pipeline cache data which is created by given device.
+    pub fn of(physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        Self {
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            uuid: properties.pipeline_cache_uuid,
+        }
+    }
+
+    /// Checks that pipeline cache data was created by the same device and driver.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        if data.len() < HEADER_SIZE {
+            return false;
+        }
+        // Unlike other Vulkan structures, fields of the header are always little-endian.
+        let field = |index: usize| {
+            let bytes = data[index * 4..index * 4 + 4].try_into().unwrap();
+            u32::from_le_bytes(bytes)
+        };
+        field(0) as usize >= HEADER_SIZE
+            && field(1) == HEADER_VERSION_ONE
+            && field(2) == self.vendor_id
+            && field(3) == self.device_id
+            && data[16..HEADER_SIZE] == self.uuid
+    }
+}
+
+/// Pipeline cache of the device which is loaded from and saved into the file.
+pub struct PersistentPipelineCache {
+    cache: Arc<PipelineCache>,
+    path: Option<PathBuf>,
+}
+
+impl PersistentPipelineCache {
+    /// Creates pipeline cache with data of given file, if any.
+    ///
+    /// Cache is created empty if the file does not exist, could not be read
+    /// or was created by another device or driver.
+    /// If path is `None`, the cache is used only while the application runs.
+    ///
+    pub fn new(device: Arc<Device>, path: Option<PathBuf>) -> Result<Self, OomError> {
+        let data = path.as_deref().and_then(|path| match fs::read(path) {
+            Ok(data) => Some(data),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => {
+                log::warn!("failed to read pipeline cache {:?}: {}", path, error);
+                None
+            }
+        });
+        let header = CacheHeader::of(device.physical_device());
+        let cache = match data {
+            Some(data) if header.matches(&data) => {
+                log::info!("pipeline cache of {} bytes was loaded", data.len());
+                // Header of the data was checked to be created by the same device and driver.
+                unsafe { PipelineCache::with_data(device, &data)? }
+            }
+            Some(_) => {
+                log::info!("pipeline cache was created by another device or driver, ignoring it");
+                PipelineCache::empty(device)?
+            }
+            None => PipelineCache::empty(device)?,
+        };
+        Ok(Self { cache, path })
+    }
+
+    /// Pipeline cache which pipelines are created with.
+    pub fn cache(&self) -> &Arc<PipelineCache> {
+        &self.cache
+    }
+
+    /// File which the cache is persisted into, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes data of the cache into its file (if any), creating parent directories as needed.
+    ///
+    /// Data is written into temporary file which then replaces the old one,
+    /// so the file is never left partially written.
+    ///
+    pub fn save(&self) -> Result<(), PipelineCacheError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let data = self.cache.get_data()?;
+        let io_error = |source| PipelineCacheError::Io {
+            path: path.clone(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, &data).map_err(io_error)?;
+        fs::rename(&temp_path, path).map_err(io_error)?;
+        log::info!("pipeline cache of {} bytes was saved", data.len());
+        Ok(())
+    }
+}
+
+/// Default pipeline cache file of the game with given name
+/// inside of the cache directory of the platform.
+///
+/// Returns `None` if the cache directory is not known.
+///
+pub fn default_path(name: &str) -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let cache_dir = if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Caches"))
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
+    }?;
+    Some(
+        cache_dir
+            .join(settings::dir_name(name))
+            .join(PIPELINE_CACHE_FILE_NAME),
+    )
+}
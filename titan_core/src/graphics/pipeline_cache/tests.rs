@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use vulkano::device::{DeviceExtensions, Features};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::Version;
+
+use super::*;
+
+const HEADER: CacheHeader = CacheHeader {
+    vendor_id: 0x10de,
+    device_id: 0x1234,
+    uuid: [7; 16],
+};
+
+/// Pipeline cache data with given header fields followed by some driver data.
+fn data(length: u32, version: u32, header: &CacheHeader) -> Vec<u8> {
+    let mut data = Vec::new();
+    for field in [length, version, header.vendor_id, header.device_id] {
+        data.extend(field.to_le_bytes());
+    }
+    data.extend(header.uuid);
+    data.extend([0; 64]);
+    data
+}
+
+#[test]
+fn test_header_matches() {
+    assert!(HEADER.matches(&data(32, 1, &HEADER)));
+
+    let other_device = CacheHeader {
+        device_id: 0x4321,
+        ..HEADER
+    };
+    let other_driver = CacheHeader {
+        uuid: [8; 16],
+        ..HEADER
+    };
+    let mismatches = [
+        data(32, 1, &other_device),
+        data(32, 1, &other_driver),
+        data(32, 2, &HEADER),
+        data(16, 1, &HEADER),
+        data(32, 1, &HEADER)[..HEADER_SIZE - 1].to_vec(),
+        Vec::new(),
+    ];
+    for data in mismatches {
+        assert!(!HEADER.matches(&data));
+    }
+}
+
+/// Saves pipeline cache of the device into the file and loads it back.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_persistence() {
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device.queue_families().next().unwrap();
+    let (device, _) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+
+    let path = std::env::temp_dir()
+        .join(format!("titan_pipeline_cache_{}", std::process::id()))
+        .join(PIPELINE_CACHE_FILE_NAME);
+    let cache = PersistentPipelineCache::new(device.clone(), Some(path.clone())).unwrap();
+    assert_eq!(cache.path(), Some(path.as_path()));
+    cache.save().unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    assert!(CacheHeader::of(physical_device).matches(&data));
+    let cache = PersistentPipelineCache::new(device, Some(path.clone())).unwrap();
+    assert!(cache.cache().get_data().unwrap().len() >= HEADER_SIZE);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,238 @@
+//! Pipeline statistics queries which count geometry work of each frame.
+//!
+//! Statistics of the frame are read one or more frames later without waiting for the device,
+//! so the query does not stall rendering.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BeginError, CommandBufferUsage, SecondaryAutoCommandBuffer,
+};
+use vulkano::device::physical::{PhysicalDevice, QueueFamily};
+use vulkano::device::{Device, Features};
+use vulkano::query::{
+    QueryPipelineStatisticFlags, QueryPool, QueryPoolCreationError, QueryResultFlags, QueryType,
+};
+use vulkano::render_pass::Subpass;
+use vulkano::OomError;
+
+mod tests;
+
+/// Count of values written by one query (one for each enabled statistic).
+const VALUES_PER_QUERY: usize = 5;
+
+/// Counters of the work done by the graphics pipeline during the frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PipelineStatistics {
+    /// Count of vertices processed by the input assembly stage.
+    pub input_assembly_vertices: u64,
+    /// Count of primitives processed by the input assembly stage.
+    pub input_assembly_primitives: u64,
+    /// Count of vertex shader invocations.
+    pub vertex_shader_invocations: u64,
+    /// Count of primitives output by the clipping stage.
+    pub clipping_primitives: u64,
+    /// Count of fragment shader invocations.
+    pub fragment_shader_invocations: u64,
+}
+
+impl PipelineStatistics {
+    /// Creates statistics from results of the query
+    /// (in order of bits of [`QueryPipelineStatisticFlags`]).
+    fn from_results(results: [u64; VALUES_PER_QUERY]) -> Self {
+        Self {
+            input_assembly_vertices: results[0],
+            input_assembly_primitives: results[1],
+            vertex_shader_invocations: results[2],
+            clipping_primitives: results[3],
+            fragment_shader_invocations: results[4],
+        }
+    }
+}
+
+/// Statistics which are counted by the query.
+fn flags() -> QueryPipelineStatisticFlags {
+    QueryPipelineStatisticFlags {
+        input_assembly_vertices: true,
+        input_assembly_primitives: true,
+        vertex_shader_invocations: true,
+        clipping_primitives: true,
+        fragment_shader_invocations: true,
+        ..QueryPipelineStatisticFlags::none()
+    }
+}
+
+/// Features which are needed for pipeline statistics, if the physical device supports them.
+///
+/// Secondary command buffers are executed while the query is active,
+/// so queries must be inherited by them.
+///
+pub(crate) fn features(physical_device: PhysicalDevice) -> Option<Features> {
+    let supported = physical_device.supported_features();
+    let supported = supported.pipeline_statistics_query && supported.inherited_queries;
+    supported.then(|| Features {
+        pipeline_statistics_query: true,
+        inherited_queries: true,
+        ..Features::none()
+    })
+}
+
+/// Returns `true` if the device was created with features for pipeline statistics.
+pub(crate) fn enabled(device: &Device) -> bool {
+    let features = device.enabled_features();
+    features.pipeline_statistics_query && features.inherited_queries
+}
+
+/// Starts building a secondary command buffer for the subpass.
+///
+/// Command buffer inherits pipeline statistics query if it is enabled on the device,
+/// so it could be executed while the query is active.
+///
+pub(crate) fn secondary_graphics(
+    device: Arc<Device>,
+    queue_family: QueueFamily,
+    subpass: Subpass,
+) -> Result<AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>, OomError> {
+    let statistics = if self::enabled(&device) {
+        self::flags()
+    } else {
+        QueryPipelineStatisticFlags::none()
+    };
+    let builder = AutoCommandBufferBuilder::secondary_graphics_inherit_queries(
+        device,
+        queue_family,
+        CommandBufferUsage::OneTimeSubmit,
+        subpass,
+        None,
+        statistics,
+    );
+    match builder {
+        Ok(builder) => Ok(builder),
+        Err(BeginError::OomError(error)) => Err(error),
+        // Statistics are inherited only if the feature is enabled, occlusion query is not.
+        Err(error) => unreachable!("{}", error),
+    }
+}
+
+/// Query of the pool which is recorded around the render pass of one frame.
+#[derive(Debug, Clone)]
+pub(crate) struct StatisticsQuery {
+    pub pool: Arc<QueryPool>,
+    pub index: u32,
+}
+
+/// Pool of pipeline statistics queries, one for each frame in flight.
+pub(crate) struct PipelineStatisticsQueries {
+    pool: Arc<QueryPool>,
+    slots: QuerySlots,
+    latest: Option<PipelineStatistics>,
+}
+
+impl PipelineStatisticsQueries {
+    /// Creates pool with a query for each of `frames` frames in flight.
+    pub fn new(device: Arc<Device>, frames: u32) -> Result<Self, QueryPoolCreationError> {
+        let ty = QueryType::PipelineStatistics(self::flags());
+        let pool = QueryPool::new(device, ty, frames)?;
+        Ok(Self {
+            pool,
+            slots: QuerySlots::new(frames),
+            latest: None,
+        })
+    }
+
+    /// Reads results of finished frames without waiting for the device
+    /// and returns statistics of the latest one.
+    pub fn poll(&mut self) -> Option<PipelineStatistics> {
+        let pool = &self.pool;
+        let finished = self.slots.poll(|index| {
+            let mut results = [0u64; VALUES_PER_QUERY];
+            let flags = QueryResultFlags {
+                wait: false,
+                with_availability: false,
+                partial: false,
+            };
+            let range = pool.queries_range(index..index + 1)?;
+            match range.get_results(&mut results, flags) {
+                Ok(true) => Some(PipelineStatistics::from_results(results)),
+                Ok(false) => None,
+                Err(error) => {
+                    log::warn!("failed to read pipeline statistics: {}", error);
+                    None
+                }
+            }
+        });
+        if let Some(statistics) = finished {
+            self.latest = Some(statistics);
+        }
+        self.latest
+    }
+
+    /// Query which should be recorded in the next frame.
+    ///
+    /// Returns `None` if results of all queries were not read yet,
+    /// so the frame is not measured instead of waiting for the device.
+    ///
+    pub fn next_query(&self) -> Option<StatisticsQuery> {
+        let index = self.slots.next()?;
+        Some(StatisticsQuery {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Marks the query as submitted, so its results will be read later.
+    ///
+    /// Query of the frame which was not submitted is reused by the next frame.
+    ///
+    pub fn submit(&mut self, query: &StatisticsQuery) {
+        self.slots.submit(query.index)
+    }
+}
+
+/// Queue of queries which were recorded and wait for their results.
+#[derive(Debug)]
+pub(super) struct QuerySlots {
+    free: Vec<u32>,
+    pending: VecDeque<u32>,
+}
+
+impl QuerySlots {
+    pub fn new(count: u32) -> Self {
+        Self {
+            free: (0..count).rev().collect(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Free query which will be used by the next frame.
+    pub fn next(&self) -> Option<u32> {
+        self.free.last().copied()
+    }
+
+    /// Marks the free query as pending.
+    pub fn submit(&mut self, index: u32) {
+        if let Some(position) = self.free.iter().position(|&free| free == index) {
+            self.free.remove(position);
+            self.pending.push_back(index);
+        }
+    }
+
+    /// Reads results of pending queries in order of their submission
+    /// until the first query without results.
+    ///
+    /// Returns results of the latest query which were read.
+    ///
+    pub fn poll<T>(&mut self, mut read: impl FnMut(u32) -> Option<T>) -> Option<T> {
+        let mut latest = None;
+        while let Some(&index) = self.pending.front() {
+            match read(index) {
+                Some(results) => latest = Some(results),
+                None => break,
+            }
+            self.pending.pop_front();
+            self.free.push(index);
+        }
+        latest
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_from_results() {
+    let statistics = PipelineStatistics::from_results([1, 2, 3, 4, 5]);
+    assert_eq!(
+        statistics,
+        PipelineStatistics {
+            input_assembly_vertices: 1,
+            input_assembly_primitives: 2,
+            vertex_shader_invocations: 3,
+            clipping_primitives: 4,
+            fragment_shader_invocations: 5,
+        }
+    );
+}
+
+#[test]
+fn test_slots_read_later() {
+    let mut slots = QuerySlots::new(2);
+    let first = slots.next().unwrap();
+    slots.submit(first);
+    let second = slots.next().unwrap();
+    assert_ne!(first, second);
+    slots.submit(second);
+    // All queries wait for their results, so the next frame is not measured.
+    assert_eq!(slots.next(), None);
+
+    // Results of the first frame are not ready yet.
+    assert_eq!(slots.poll(|_| None::<u32>), None);
+    assert_eq!(slots.next(), None);
+
+    // Results are read in order of submission, the latest ones are returned.
+    assert_eq!(slots.poll(Some), Some(second));
+    assert!(slots.next().is_some());
+}
+
+#[test]
+fn test_slots_poll_stops_at_pending() {
+    let mut slots = QuerySlots::new(3);
+    let mut submitted = Vec::new();
+    for _ in 0..3 {
+        let index = slots.next().unwrap();
+        slots.submit(index);
+        submitted.push(index);
+    }
+    let ready = submitted[0];
+    let mut read = Vec::new();
+    let latest = slots.poll(|index| {
+        read.push(index);
+        (index == ready).then_some(index)
+    });
+    assert_eq!(latest, Some(ready));
+    assert_eq!(read, [submitted[0], submitted[1]]);
+    assert_eq!(slots.next(), Some(ready));
+}
+
+#[test]
+fn test_slots_not_submitted() {
+    let mut slots = QuerySlots::new(1);
+    // Frame failed before submission, so its query is reused.
+    let index = slots.next().unwrap();
+    assert_eq!(slots.next(), Some(index));
+    slots.submit(index);
+    assert_eq!(slots.next(), None);
+    // Query is submitted only once.
+    slots.submit(index);
+    assert_eq!(slots.poll(Some), Some(index));
+    assert_eq!(slots.poll(Some), None);
+}
@@ -0,0 +1,120 @@
+//! Pre-rotation of rendered content for surfaces with non-identity transform.
+//!
+//! On some platforms (for example, Android) display can be rotated relative to
+//! native orientation of the surface. Instead of letting the compositor rotate each image,
+//! swapchain is created in native orientation and content is rotated while rendering.
+
+use ultraviolet::{Mat2, Mat4, Vec2, Vec4};
+use vulkano::swapchain::SurfaceTransform;
+
+mod tests;
+
+/// Transform which is applied to rendered content before presentation.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PreTransform {
+    /// Count of counterclockwise quarter turns (from 0 to 3).
+    quarter_turns: u8,
+    /// Whether content is mirrored horizontally before rotation.
+    mirror: bool,
+}
+
+impl PreTransform {
+    /// Transform which does not change anything.
+    pub const IDENTITY: Self = Self {
+        quarter_turns: 0,
+        mirror: false,
+    };
+
+    /// Creates transform with given count of quarter turns and horizontal mirroring.
+    pub const fn new(quarter_turns: u8, mirror: bool) -> Self {
+        Self {
+            quarter_turns: quarter_turns % 4,
+            mirror,
+        }
+    }
+
+    /// Returns `true` if this transform does not change anything.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Returns `true` if width and height are swapped by this transform (90 or 270 degrees).
+    pub fn swaps_dimensions(&self) -> bool {
+        self.quarter_turns % 2 == 1
+    }
+
+    /// Converts dimensions of the window into dimensions of swapchain images
+    /// (or vice versa), swapping width and height if needed.
+    pub fn swap_dimensions<T>(&self, dimensions: [T; 2]) -> [T; 2] {
+        let [width, height] = dimensions;
+        if self.swaps_dimensions() {
+            [height, width]
+        } else {
+            [width, height]
+        }
+    }
+
+    /// Matrix which rotates content in normalized device coordinates.
+    ///
+    /// Projection matrix of the camera should be multiplied by it.
+    pub fn matrix(&self) -> Mat4 {
+        // Exact values of cosine and sine for each quarter turn.
+        const COS_SIN: [(f32, f32); 4] = [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)];
+
+        let (cos, sin) = COS_SIN[self.quarter_turns as usize];
+        let mirror = if self.mirror { -1.0 } else { 1.0 };
+        Mat4::new(
+            Vec4::new(cos * mirror, sin * mirror, 0.0, 0.0),
+            Vec4::new(-sin, cos, 0.0, 0.0),
+            Vec4::unit_z(),
+            Vec4::unit_w(),
+        )
+    }
+
+    /// Two-dimensional matrix which rotates content in normalized device coordinates.
+    pub fn matrix2(&self) -> Mat2 {
+        let matrix = self.matrix();
+        Mat2::new(matrix.cols[0].xy(), matrix.cols[1].xy())
+    }
+
+    /// Converts rectangle (min and max corners) in pixels of the window
+    /// into rectangle in pixels of swapchain image.
+    pub fn transform_rect(
+        &self,
+        min: [f32; 2],
+        max: [f32; 2],
+        window_dimensions: [f32; 2],
+    ) -> ([f32; 2], [f32; 2]) {
+        if self.is_identity() {
+            return (min, max);
+        }
+        let window = Vec2::from(window_dimensions);
+        let image = Vec2::from(self.swap_dimensions(window_dimensions));
+        let matrix = self.matrix2();
+        let transform = |point: [f32; 2]| {
+            let ndc = Vec2::from(point) / window * 2.0 - Vec2::one();
+            let ndc = matrix * ndc;
+            (ndc + Vec2::one()) / 2.0 * image
+        };
+
+        let (first, second) = (transform(min), transform(max));
+        let min = first.min_by_component(second);
+        let max = first.max_by_component(second);
+        (min.into(), max.into())
+    }
+}
+
+impl From<SurfaceTransform> for PreTransform {
+    fn from(transform: SurfaceTransform) -> Self {
+        match transform {
+            SurfaceTransform::Identity | SurfaceTransform::Inherit => Self::new(0, false),
+            SurfaceTransform::Rotate90 => Self::new(1, false),
+            SurfaceTransform::Rotate180 => Self::new(2, false),
+            SurfaceTransform::Rotate270 => Self::new(3, false),
+            SurfaceTransform::HorizontalMirror => Self::new(0, true),
+            SurfaceTransform::HorizontalMirrorRotate90 => Self::new(1, true),
+            SurfaceTransform::HorizontalMirrorRotate180 => Self::new(2, true),
+            SurfaceTransform::HorizontalMirrorRotate270 => Self::new(3, true),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use ultraviolet::{Vec2, Vec4};
+use vulkano::swapchain::SurfaceTransform;
+
+use super::*;
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-3, "{:?} != {:?}", actual, expected);
+}
+
+fn rotate(transform: SurfaceTransform, point: Vec2) -> Vec2 {
+    let matrix = PreTransform::from(transform).matrix();
+    let point = matrix * Vec4::new(point.x, point.y, 0.5, 1.0);
+    point.xy()
+}
+
+#[test]
+fn test_rotation_matrix() {
+    use SurfaceTransform::*;
+
+    let x = Vec2::unit_x();
+    let y = Vec2::unit_y();
+    let expected = [
+        (Identity, x, y),
+        (Inherit, x, y),
+        (Rotate90, y, -x),
+        (Rotate180, -x, -y),
+        (Rotate270, -y, x),
+        (HorizontalMirror, -x, y),
+        (HorizontalMirrorRotate90, -y, -x),
+        (HorizontalMirrorRotate180, x, -y),
+        (HorizontalMirrorRotate270, y, x),
+    ];
+    for (transform, rotated_x, rotated_y) in expected {
+        assert_near(rotate(transform, x), rotated_x);
+        assert_near(rotate(transform, y), rotated_y);
+    }
+}
+
+#[test]
+fn test_identity_unaffected() {
+    let transform = PreTransform::from(SurfaceTransform::Identity);
+    assert!(transform.is_identity());
+    assert_eq!(transform.matrix(), Mat4::identity());
+    assert_eq!(transform.swap_dimensions([1920, 1080]), [1920, 1080]);
+
+    let rect = transform.transform_rect([10.0, 20.0], [30.0, 40.0], [1920.0, 1080.0]);
+    assert_eq!(rect, ([10.0, 20.0], [30.0, 40.0]));
+}
+
+#[test]
+fn test_swapped_dimensions() {
+    let transform = PreTransform::from(SurfaceTransform::Rotate90);
+    assert!(transform.swaps_dimensions());
+    assert_eq!(transform.swap_dimensions([1920, 1080]), [1080, 1920]);
+
+    // Whole window covers the whole image.
+    let (min, max) = transform.transform_rect([0.0; 2], [1920.0, 1080.0], [1920.0, 1080.0]);
+    assert_near(min.into(), Vec2::zero());
+    assert_near(max.into(), Vec2::new(1080.0, 1920.0));
+
+    // Top left corner of the window is placed at top right corner of the image.
+    let (min, max) = transform.transform_rect([0.0; 2], [100.0, 50.0], [1920.0, 1080.0]);
+    assert_near(min.into(), Vec2::new(1030.0, 0.0));
+    assert_near(max.into(), Vec2::new(1080.0, 100.0));
+}
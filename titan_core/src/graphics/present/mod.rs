@@ -0,0 +1,62 @@
+//! Modes of presentation of frames into the window.
+
+use vulkano::swapchain::PresentMode as VkPresentMode;
+
+mod tests;
+
+/// Mode in which rendered frames are presented into the window.
+///
+/// If the mode is not supported by the surface, the next one which is supported
+/// is negotiated in order `Immediate -> Mailbox -> Fifo`. [`PresentMode::Fifo`]
+/// is supported by all surfaces.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// Frames are presented in order of their rendering at the refresh rate of the display
+    /// (vertical synchronization). Rendering waits for the display.
+    Fifo,
+    /// Frames are presented at the refresh rate of the display, but rendering does not wait:
+    /// the latest frame replaces the one waiting to be presented.
+    Mailbox,
+    /// Frames are presented right after they were rendered, which could cause tearing.
+    Immediate,
+}
+
+impl PresentMode {
+    /// All modes, from the one with the least latency.
+    const ALL: [Self; 3] = [Self::Immediate, Self::Mailbox, Self::Fifo];
+
+    /// Name of the mode which is used in command-line arguments.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::Mailbox => "mailbox",
+            Self::Immediate => "immediate",
+        }
+    }
+
+    /// Mode with given name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Returns this mode if it is supported,
+    /// otherwise the first supported mode with more latency.
+    pub fn negotiate(self, supports: impl Fn(Self) -> bool) -> Self {
+        Self::ALL
+            .into_iter()
+            .skip_while(|&mode| mode != self)
+            .find(|&mode| supports(mode))
+            .unwrap_or(Self::Fifo)
+    }
+}
+
+impl From<PresentMode> for VkPresentMode {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::Fifo => Self::Fifo,
+            PresentMode::Mailbox => Self::Mailbox,
+            PresentMode::Immediate => Self::Immediate,
+        }
+    }
+}
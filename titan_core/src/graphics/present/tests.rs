@@ -0,0 +1,44 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_negotiate() {
+    let all = |_| true;
+    assert_eq!(
+        PresentMode::Immediate.negotiate(all),
+        PresentMode::Immediate
+    );
+    assert_eq!(PresentMode::Fifo.negotiate(all), PresentMode::Fifo);
+
+    let no_mailbox = |mode| mode != PresentMode::Mailbox;
+    assert_eq!(
+        PresentMode::Mailbox.negotiate(no_mailbox),
+        PresentMode::Fifo
+    );
+    assert_eq!(
+        PresentMode::Immediate.negotiate(no_mailbox),
+        PresentMode::Immediate
+    );
+
+    let mailbox_only = |mode| mode == PresentMode::Mailbox;
+    assert_eq!(
+        PresentMode::Immediate.negotiate(mailbox_only),
+        PresentMode::Mailbox
+    );
+    // Mode with less latency is never chosen.
+    assert_eq!(PresentMode::Fifo.negotiate(mailbox_only), PresentMode::Fifo);
+    // Fifo is supported by all surfaces.
+    assert_eq!(
+        PresentMode::Immediate.negotiate(|_| false),
+        PresentMode::Fifo
+    );
+}
+
+#[test]
+fn test_names() {
+    for mode in PresentMode::ALL {
+        assert_eq!(PresentMode::from_name(mode.name()), Some(mode));
+    }
+    assert_eq!(PresentMode::from_name("vsync"), None);
+}
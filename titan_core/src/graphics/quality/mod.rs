@@ -0,0 +1,259 @@
+//! Adaptive scaling of GPU workload which holds the target frame time.
+//!
+//! [`QualityController`] measures frame times and computes quality in range from 0 to 1:
+//! it is lowered while frames take longer than the target and raised while there is
+//! enough headroom. Consumers of quality (render scale, antialiasing and registered callbacks)
+//! are notified at most once per [`NOTIFY_INTERVAL`], so expensive changes are rare.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::graphics::AaMode;
+
+mod tests;
+
+/// Min interval between notifications about changed quality.
+pub const NOTIFY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Count of latest frames which quality is kept in the history.
+pub const HISTORY_LEN: usize = 120;
+
+/// Weight of the latest frame time in the smoothed one.
+const SMOOTHING: f32 = 0.1;
+
+/// Change of quality per frame for each relative error of the smoothed frame time.
+const GAIN: f32 = 0.05;
+
+/// Max change of quality per frame.
+const MAX_STEP: f32 = 0.02;
+
+/// Frames which are this much slower than the target (relative to it) lower quality.
+const LOWER_THRESHOLD: f32 = 0.05;
+
+/// Frames which are this much faster than the target (relative to it) raise quality.
+///
+/// It is larger than [`LOWER_THRESHOLD`], so quality does not oscillate
+/// when frame time is close to the target.
+const RAISE_THRESHOLD: f32 = 0.15;
+
+/// Min change of quality which consumers are notified about.
+const MIN_CHANGE: f32 = 0.01;
+
+/// Step of render scale, so it is not changed for each small change of quality.
+const RENDER_SCALE_STEP: f32 = 0.05;
+
+/// Quality above antialiasing threshold at which disabled antialiasing is enabled again.
+const ANTIALIASING_HYSTERESIS: f32 = 0.1;
+
+/// Settings of adaptive quality with built-in consumers which are opt-in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QualityConfig {
+    target_frame_time: Duration,
+    render_scale: Option<[f32; 2]>,
+    antialiasing_threshold: Option<f32>,
+}
+
+impl QualityConfig {
+    /// Creates settings which hold given frame time without built-in consumers.
+    pub fn new(target_frame_time: Duration) -> Self {
+        Self {
+            target_frame_time,
+            render_scale: None,
+            antialiasing_threshold: None,
+        }
+    }
+
+    /// Enables scaling of the scene resolution between given scales
+    /// (lowest quality uses min scale, highest one uses max scale).
+    pub fn with_render_scale(mut self, min: f32, max: f32) -> Self {
+        self.render_scale = Some([min.min(max), max.max(min)]);
+        self
+    }
+
+    /// Enables disabling of antialiasing while quality is below given threshold.
+    pub fn with_antialiasing_threshold(mut self, threshold: f32) -> Self {
+        self.antialiasing_threshold = Some(threshold.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Frame time which quality is adapted to hold.
+    pub fn target_frame_time(&self) -> Duration {
+        self.target_frame_time
+    }
+
+    /// Range of render scale, or `None` if render scale is not adapted.
+    pub fn render_scale_range(&self) -> Option<[f32; 2]> {
+        self.render_scale
+    }
+
+    /// Quality below which antialiasing is disabled, or `None` if antialiasing is not adapted.
+    pub fn antialiasing_threshold(&self) -> Option<f32> {
+        self.antialiasing_threshold
+    }
+
+    /// Render scale for given quality, rounded to multiple of the scale step.
+    pub fn render_scale(&self, quality: f32) -> Option<f32> {
+        let [min, max] = self.render_scale?;
+        let scale = min + (max - min) * quality;
+        let scale = (scale / RENDER_SCALE_STEP).round() * RENDER_SCALE_STEP;
+        Some(scale.clamp(min, max))
+    }
+
+    /// Antialiasing mode for given quality if current mode is `current`
+    /// and mode of the configuration is `configured`.
+    ///
+    /// Disabled antialiasing is enabled again only when quality is noticeably
+    /// above the threshold, so it is not toggled back and forth.
+    ///
+    pub fn antialiasing(&self, quality: f32, current: AaMode, configured: AaMode) -> AaMode {
+        let threshold = match self.antialiasing_threshold {
+            Some(threshold) => threshold,
+            None => return current,
+        };
+        if quality < threshold {
+            AaMode::Off
+        } else if quality >= threshold + ANTIALIASING_HYSTERESIS {
+            configured
+        } else {
+            current
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QualityState {
+    quality: Option<f32>,
+    history: VecDeque<f32>,
+}
+
+/// Handle which reads current quality and its recent history.
+///
+/// Clones observe the same state, which is updated by [`QualityController`] after each frame.
+#[derive(Debug, Default, Clone)]
+pub struct QualityMonitor {
+    state: Arc<Mutex<QualityState>>,
+}
+
+impl QualityMonitor {
+    /// Current quality, or `None` if adaptive quality is disabled.
+    pub fn quality(&self) -> Option<f32> {
+        self.state.lock().unwrap().quality
+    }
+
+    /// Quality of at most [`HISTORY_LEN`] latest frames, from the oldest to the newest one.
+    pub fn history(&self) -> Vec<f32> {
+        self.state.lock().unwrap().history.iter().copied().collect()
+    }
+
+    fn push(&self, quality: f32) {
+        let mut state = self.state.lock().unwrap();
+        state.quality = Some(quality);
+        if state.history.len() == HISTORY_LEN {
+            state.history.pop_front();
+        }
+        state.history.push_back(quality);
+    }
+}
+
+/// Damped controller of quality which is driven by measured frame times.
+pub struct QualityController {
+    target: f32,
+    smoothed: Option<f32>,
+    quality: f32,
+    notified: f32,
+    since_notified: Duration,
+    monitor: QualityMonitor,
+    callbacks: Vec<Box<dyn FnMut(f32)>>,
+}
+
+impl QualityController {
+    /// Creates controller of the highest quality which holds given frame time.
+    pub fn new(target_frame_time: Duration) -> Self {
+        let monitor = QualityMonitor::default();
+        monitor.state.lock().unwrap().quality = Some(1.0);
+        Self {
+            target: target_frame_time.as_secs_f32(),
+            smoothed: None,
+            quality: 1.0,
+            notified: 1.0,
+            since_notified: Duration::ZERO,
+            monitor,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Current quality in range from 0 to 1.
+    pub fn quality(&self) -> f32 {
+        self.quality
+    }
+
+    /// Returns handle which reads quality of this controller.
+    pub fn monitor(&self) -> QualityMonitor {
+        self.monitor.clone()
+    }
+
+    /// Registers callback which is invoked with new quality when it is changed.
+    ///
+    /// Callbacks are invoked at most once per [`NOTIFY_INTERVAL`].
+    ///
+    pub fn on_quality_changed(&mut self, callback: impl FnMut(f32) + 'static) {
+        self.callbacks.push(Box::new(callback))
+    }
+
+    /// Updates quality with time of the latest frame.
+    ///
+    /// Frames which were disturbed (for example, by swapchain recreation or pause)
+    /// do not change quality, so a single hitch does not lower it.
+    /// Returns new quality if callbacks were notified about it.
+    ///
+    pub fn update(&mut self, frame_time: Duration, disturbed: bool) -> Option<f32> {
+        self.since_notified += frame_time;
+        if !disturbed {
+            self.adjust(frame_time.as_secs_f32());
+            self.monitor.push(self.quality);
+        }
+
+        // Small changes are still reported when quality reaches its bounds.
+        let at_bound = self.quality == 0.0 || self.quality == 1.0;
+        let changed = (self.quality - self.notified).abs() >= MIN_CHANGE
+            || (at_bound && self.quality != self.notified);
+        if !changed || self.since_notified < NOTIFY_INTERVAL {
+            return None;
+        }
+        self.notified = self.quality;
+        self.since_notified = Duration::ZERO;
+        for callback in &mut self.callbacks {
+            callback(self.quality);
+        }
+        Some(self.quality)
+    }
+
+    fn adjust(&mut self, frame_time: f32) {
+        let smoothed = match self.smoothed {
+            Some(smoothed) => smoothed + (frame_time - smoothed) * SMOOTHING,
+            None => frame_time,
+        };
+        self.smoothed = Some(smoothed);
+
+        // Positive error means that there is headroom.
+        let error = (self.target - smoothed) / self.target;
+        if (-LOWER_THRESHOLD..=RAISE_THRESHOLD).contains(&error) {
+            return;
+        }
+        let step = (error * GAIN).clamp(-MAX_STEP, MAX_STEP);
+        self.quality = (self.quality + step).clamp(0.0, 1.0);
+    }
+}
+
+impl fmt::Debug for QualityController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QualityController")
+            .field("target", &self.target)
+            .field("smoothed", &self.smoothed)
+            .field("quality", &self.quality)
+            .field("callbacks", &self.callbacks.len())
+            .finish()
+    }
+}
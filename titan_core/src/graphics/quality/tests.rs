@@ -0,0 +1,135 @@
+#![cfg(test)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use vulkano::image::SampleCount;
+
+use super::*;
+
+const TARGET: Duration = Duration::from_millis(16);
+
+/// Feeds frames of given time for given total duration, returning notified qualities.
+fn feed(controller: &mut QualityController, frame_time: Duration, total: Duration) -> Vec<f32> {
+    let count = (total.as_secs_f32() / frame_time.as_secs_f32()).ceil() as u32;
+    (0..count)
+        .filter_map(|_| controller.update(frame_time, false))
+        .collect()
+}
+
+#[test]
+fn test_steady_frames() {
+    let mut controller = QualityController::new(TARGET);
+    let monitor = controller.monitor();
+    assert_eq!(monitor.quality(), Some(1.0));
+
+    let notified = feed(&mut controller, TARGET, Duration::from_secs(5));
+    assert!(notified.is_empty());
+    assert_eq!(controller.quality(), 1.0);
+    let history = monitor.history();
+    assert_eq!(history.len(), HISTORY_LEN);
+    assert!(history.iter().all(|&quality| quality == 1.0));
+}
+
+#[test]
+fn test_overload_and_recovery() {
+    let mut controller = QualityController::new(TARGET);
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    {
+        let calls = calls.clone();
+        controller.on_quality_changed(move |quality| calls.borrow_mut().push(quality));
+    }
+
+    // Twice slower frames lower quality once per second down to zero.
+    let slow = Duration::from_millis(32);
+    let lowered = feed(&mut controller, slow, Duration::from_secs(4));
+    assert!(lowered.len() >= 2 && lowered.len() <= 4, "{:?}", lowered);
+    assert!(lowered.windows(2).all(|pair| pair[1] < pair[0]));
+    assert_eq!(controller.quality(), 0.0);
+    assert_eq!(lowered.last(), Some(&0.0));
+    assert_eq!(*calls.borrow(), lowered);
+
+    // Twice faster frames raise quality back to the highest.
+    let fast = Duration::from_millis(8);
+    let raised = feed(&mut controller, fast, Duration::from_secs(4));
+    assert!(raised.len() >= 2 && raised.len() <= 4, "{:?}", raised);
+    assert!(raised.windows(2).all(|pair| pair[1] > pair[0]));
+    assert_eq!(controller.quality(), 1.0);
+    assert_eq!(calls.borrow().len(), lowered.len() + raised.len());
+}
+
+#[test]
+fn test_hysteresis() {
+    let mut controller = QualityController::new(TARGET);
+
+    // Frames close to the target do not change quality.
+    for index in 0..300 {
+        let micros = if index % 2 == 0 { 15_000 } else { 16_800 };
+        assert_eq!(
+            controller.update(Duration::from_micros(micros), false),
+            None
+        );
+    }
+    assert_eq!(controller.quality(), 1.0);
+
+    feed(
+        &mut controller,
+        Duration::from_millis(24),
+        Duration::from_millis(500),
+    );
+    let lowered = controller.quality();
+    assert!(lowered < 1.0);
+    // Smoothed frame time settles slightly under the target,
+    // which is not enough headroom to raise quality back.
+    feed(
+        &mut controller,
+        Duration::from_millis(15),
+        Duration::from_secs(3),
+    );
+    assert!(controller.quality() <= lowered);
+    let settled = controller.quality();
+    feed(
+        &mut controller,
+        Duration::from_millis(15),
+        Duration::from_secs(3),
+    );
+    assert_eq!(controller.quality(), settled);
+}
+
+#[test]
+fn test_disturbed_frames_ignored() {
+    let mut controller = QualityController::new(TARGET);
+    for _ in 0..10 {
+        controller.update(TARGET, false);
+    }
+    // Swapchain recreation takes half a second.
+    assert_eq!(controller.update(Duration::from_millis(500), true), None);
+    for _ in 0..10 {
+        assert_eq!(controller.update(TARGET, false), None);
+    }
+    assert_eq!(controller.quality(), 1.0);
+    assert_eq!(controller.monitor().history().len(), 20);
+}
+
+#[test]
+fn test_consumers() {
+    let config = QualityConfig::new(TARGET);
+    assert_eq!(config.render_scale(0.5), None);
+    let msaa = AaMode::Msaa(SampleCount::Sample4);
+    assert_eq!(config.antialiasing(0.0, msaa, msaa), msaa);
+
+    let config = config
+        .with_render_scale(1.0, 0.5)
+        .with_antialiasing_threshold(0.4);
+    assert_eq!(config.render_scale_range(), Some([0.5, 1.0]));
+    assert_eq!(config.render_scale(0.0), Some(0.5));
+    assert_eq!(config.render_scale(1.0), Some(1.0));
+    // Scale is rounded to the step.
+    assert_eq!(config.render_scale(0.51), Some(0.75));
+
+    assert_eq!(config.antialiasing(0.3, msaa, msaa), AaMode::Off);
+    // Antialiasing is not enabled right above the threshold.
+    assert_eq!(config.antialiasing(0.45, AaMode::Off, msaa), AaMode::Off);
+    assert_eq!(config.antialiasing(0.45, msaa, msaa), msaa);
+    assert_eq!(config.antialiasing(0.6, AaMode::Off, msaa), msaa);
+}
@@ -0,0 +1,643 @@
+//! Readback of buffers and images from the device into host memory.
+//!
+//! Copies which were requested since the previous frame are recorded into one command buffer
+//! at the end of the next rendered frame, which is executed right before presentation,
+//! so all of them are finished when the fence of the frame is signaled. Data is copied
+//! into host visible ring buffer and delivered through [`ReadbackTicket`],
+//! which could be polled or waited on. If the ring is full, bigger ring is created instead,
+//! and the previous one is freed when all of its readbacks are resolved.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferAccess, BufferSlice, BufferUsage, TypedBufferAccess};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BuildError, CommandBufferUsage, CopyBufferError,
+    CopyBufferImageError, PrimaryAutoCommandBuffer,
+};
+use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::image::ImageAccess;
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture};
+use vulkano::{DeviceSize, OomError};
+
+pub use self::screenshot::{Screenshot, ScreenshotError};
+
+pub(crate) use self::screenshot::rgba_image;
+
+use self::ring::Ring;
+use super::allocator::MemoryAllocator;
+use super::mapped::{MappedBuffer, MappedBufferCreationError};
+
+mod ring;
+mod screenshot;
+mod tests;
+
+/// Min capacity of the ring buffer (in bytes).
+pub const MIN_RING_SIZE: DeviceSize = 1024 * 1024;
+
+/// Image which could be read back by the renderer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReadbackImage {
+    /// Swapchain image which the next rendered frame is drawn into
+    /// (read after the frame is drawn, right before it is presented).
+    Frame,
+}
+
+/// Mip level and array layer of the image which is read back.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ImageSubresource {
+    pub mip_level: u32,
+    pub array_layer: u32,
+}
+
+/// Description of the image data which was read back.
+///
+/// Rows of texels (or blocks of compressed formats) are tightly packed,
+/// and slices of 3D image follow each other.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageReadbackInfo {
+    /// Extent of the subresource (in texels).
+    pub extent: [u32; 3],
+    pub format: Format,
+}
+
+impl ImageReadbackInfo {
+    /// Size of one row of texel blocks (in bytes).
+    pub fn row_size(&self) -> DeviceSize {
+        let [block_width, _] = self.format.block_dimensions();
+        let block_size = self.format.size().unwrap_or(0);
+        let blocks = (self.extent[0] + block_width - 1) / block_width;
+        blocks as DeviceSize * block_size
+    }
+
+    /// Count of rows of texel blocks (in all slices).
+    pub fn rows(&self) -> u32 {
+        let [_, block_height] = self.format.block_dimensions();
+        let rows = (self.extent[1] + block_height - 1) / block_height;
+        rows * self.extent[2]
+    }
+
+    /// Size of the whole image data (in bytes).
+    pub fn size(&self) -> DeviceSize {
+        self.row_size() * self.rows() as DeviceSize
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ReadbackError {
+    #[error("buffer was not found")]
+    BufferNotFound,
+
+    #[error("range {start}..{end} exceeds buffer of {size} bytes")]
+    OutOfBounds {
+        start: DeviceSize,
+        end: DeviceSize,
+        size: DeviceSize,
+    },
+
+    #[error("image could not be used as a transfer source")]
+    UnsupportedImage,
+
+    #[error("image format {0:?} could not be read back")]
+    UnsupportedFormat(Format),
+
+    #[error("image has no subresource {0:?}")]
+    InvalidSubresource(ImageSubresource),
+}
+
+#[derive(Debug, Error)]
+pub enum ReadbackRecordError {
+    #[error("ring buffer creation failure: {0}")]
+    RingCreation(#[from] MappedBufferCreationError),
+
+    #[error("command buffer creation failure: {0}")]
+    CommandBufferCreation(#[from] OomError),
+
+    #[error("buffer copy failure: {0}")]
+    CopyBuffer(#[from] CopyBufferError),
+
+    #[error("image copy failure: {0}")]
+    CopyImage(#[from] CopyBufferImageError),
+
+    #[error("command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+}
+
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum ReadbackWaitError {
+    #[error("copy was not submitted yet: it is recorded at the end of the next rendered frame")]
+    NotSubmitted,
+
+    #[error("copy was not finished before timeout")]
+    Timeout,
+
+    #[error("data was already taken")]
+    Taken,
+
+    #[error("copy was not executed by the device")]
+    Failed,
+}
+
+/// Fence which is signaled when copies of the readback are finished.
+pub(crate) trait ReadbackFence: Send + Sync {
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), FlushError>;
+}
+
+impl<F> ReadbackFence for FenceSignalFuture<F>
+where
+    F: GpuFuture,
+    Self: Send + Sync,
+{
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), FlushError> {
+        FenceSignalFuture::wait(self, timeout)
+    }
+}
+
+/// Range of the ring buffer which data of the readback is copied into.
+struct Placement {
+    buffer: Arc<MappedBuffer<u8>>,
+    /// Ring which the range was allocated from.
+    generation: u64,
+    range: Range<DeviceSize>,
+}
+
+enum Stage {
+    /// Copy was requested, but not recorded yet.
+    Queued,
+    /// Copy was recorded into the command buffer of the frame, which was not submitted yet.
+    Recorded,
+    Submitted(Arc<dyn ReadbackFence>),
+    Delivered,
+    Failed,
+}
+
+struct SlotState {
+    stage: Stage,
+    placement: Option<Placement>,
+    info: Option<ImageReadbackInfo>,
+}
+
+/// State of one readback which is shared between its ticket and the renderer.
+struct Slot {
+    state: Mutex<SlotState>,
+    /// Set when the ticket is dropped, so data is never delivered.
+    cancelled: AtomicBool,
+}
+
+impl Slot {
+    fn new() -> Arc<Self> {
+        let state = SlotState {
+            stage: Stage::Queued,
+            placement: None,
+            info: None,
+        };
+        Arc::new(Self {
+            state: Mutex::new(state),
+            cancelled: AtomicBool::new(false),
+        })
+    }
+
+    fn fail(&self) {
+        self.state.lock().unwrap().stage = Stage::Failed;
+    }
+
+    /// Returns `true` if range of the slot in the ring is not used anymore.
+    fn is_resolved(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        match &state.stage {
+            Stage::Queued | Stage::Recorded => false,
+            Stage::Delivered | Stage::Failed => true,
+            // Dropped ticket does not take the data, so the copy is waited for here.
+            Stage::Submitted(fence) => {
+                let cancelled = self.cancelled.load(Ordering::SeqCst);
+                cancelled && !matches!(fence.wait(Some(Duration::ZERO)), Err(FlushError::Timeout))
+            }
+        }
+    }
+}
+
+/// Handle of the readback which receives its data when the copy is finished.
+///
+/// Dropping the ticket cancels delivery of the data, but the copy which was already
+/// recorded is still executed.
+///
+pub struct ReadbackTicket {
+    slot: Arc<Slot>,
+}
+
+impl ReadbackTicket {
+    /// Description of the image data, if the image is read back.
+    ///
+    /// Description is known only after the copy was recorded at the end of the frame.
+    ///
+    pub fn image_info(&self) -> Option<ImageReadbackInfo> {
+        self.slot.state.lock().unwrap().info
+    }
+
+    /// Returns the data if the copy is finished.
+    ///
+    /// Data is returned only once: the next calls return `None`.
+    ///
+    pub fn try_get(&self) -> Option<Vec<u8>> {
+        self.take(Some(Duration::ZERO)).ok()
+    }
+
+    /// Blocks until the copy is finished, then returns the data.
+    ///
+    /// Copy must be submitted (that is, the next frame must be rendered) before the call,
+    /// so the error is returned immediately if it was not.
+    ///
+    pub fn wait(&self, timeout: Duration) -> Result<Vec<u8>, ReadbackWaitError> {
+        self.take(Some(timeout))
+    }
+
+    fn take(&self, timeout: Option<Duration>) -> Result<Vec<u8>, ReadbackWaitError> {
+        let fence = match &self.slot.state.lock().unwrap().stage {
+            Stage::Queued | Stage::Recorded => return Err(ReadbackWaitError::NotSubmitted),
+            Stage::Submitted(fence) => fence.clone(),
+            Stage::Delivered => return Err(ReadbackWaitError::Taken),
+            Stage::Failed => return Err(ReadbackWaitError::Failed),
+        };
+        // Fence is waited for without the lock, so the renderer is not blocked.
+        match fence.wait(timeout) {
+            Ok(()) => (),
+            Err(FlushError::Timeout) => return Err(ReadbackWaitError::Timeout),
+            Err(error) => {
+                log::warn!("failed to wait for readback: {}", error);
+                self.slot.fail();
+                return Err(ReadbackWaitError::Failed);
+            }
+        }
+
+        let mut state = self.slot.state.lock().unwrap();
+        if !matches!(state.stage, Stage::Submitted(_)) {
+            return Err(ReadbackWaitError::Taken);
+        }
+        let placement = state
+            .placement
+            .as_ref()
+            .expect("submitted readback must be placed into the ring");
+        let range = placement.range.start as usize..placement.range.end as usize;
+        match placement.buffer.read_bytes(range) {
+            Ok(data) => {
+                state.stage = Stage::Delivered;
+                Ok(data)
+            }
+            Err(error) => {
+                log::warn!("failed to read back data: {}", error);
+                state.stage = Stage::Failed;
+                Err(ReadbackWaitError::Failed)
+            }
+        }
+    }
+}
+
+impl Drop for ReadbackTicket {
+    fn drop(&mut self) {
+        self.slot.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+type Builder = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+
+/// Slice of the ring buffer which data of one readback is copied into.
+type RingSlice = BufferSlice<[u8], Arc<MappedBuffer<u8>>>;
+
+/// Image which the frame was rendered into.
+pub(crate) type FrameImage = Arc<dyn ImageAccess + Send + Sync>;
+
+type CopyBufferFn = Box<dyn FnOnce(&mut Builder, RingSlice) -> Result<(), CopyBufferError> + Send>;
+
+enum CopySource {
+    Buffer {
+        size: DeviceSize,
+        copy: CopyBufferFn,
+    },
+    Frame(ImageSubresource),
+}
+
+struct QueuedCopy {
+    source: CopySource,
+    slot: Arc<Slot>,
+}
+
+/// Readbacks which were recorded into the command buffer of the frame.
+///
+/// If the batch is dropped before it was submitted, its readbacks fail.
+///
+pub(crate) struct ReadbackBatch {
+    slots: Vec<Arc<Slot>>,
+}
+
+impl ReadbackBatch {
+    /// Marks readbacks as submitted with the fence of the frame.
+    pub fn submitted(mut self, fence: Arc<dyn ReadbackFence>) {
+        for slot in self.slots.drain(..) {
+            slot.state.lock().unwrap().stage = Stage::Submitted(fence.clone());
+        }
+    }
+}
+
+impl Drop for ReadbackBatch {
+    fn drop(&mut self) {
+        for slot in &self.slots {
+            slot.fail();
+        }
+    }
+}
+
+/// Queue of readbacks which are recorded at the end of each frame.
+pub(crate) struct Readback {
+    allocator: Arc<MemoryAllocator>,
+    ring: Ring,
+    ring_buffer: Option<Arc<MappedBuffer<u8>>>,
+    generation: u64,
+    queued: Vec<QueuedCopy>,
+    /// Readbacks which ranges of the ring could still be used.
+    in_flight: Vec<Arc<Slot>>,
+}
+
+impl Readback {
+    /// Creates an empty queue: the ring buffer is created for the first readback.
+    pub fn new(allocator: Arc<MemoryAllocator>) -> Self {
+        Self {
+            allocator,
+            ring: Ring::new(0),
+            ring_buffer: None,
+            generation: 0,
+            queued: Vec::new(),
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Requests readback of the range of the buffer (in bytes).
+    pub fn buffer<B, T>(
+        &mut self,
+        buffer: Arc<B>,
+        range: Range<DeviceSize>,
+    ) -> Result<ReadbackTicket, ReadbackError>
+    where
+        B: TypedBufferAccess<Content = [T]> + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        let size = buffer.size();
+        if range.start > range.end || range.end > size {
+            return Err(ReadbackError::OutOfBounds {
+                start: range.start,
+                end: range.end,
+                size,
+            });
+        }
+        let copied = range.end - range.start;
+        let copy = move |builder: &mut Builder, destination: RingSlice| {
+            if range.is_empty() {
+                return Ok(());
+            }
+            // Safety: bytes of any buffer could be copied.
+            let source =
+                unsafe { BufferSlice::from_typed_buffer_access(buffer).reinterpret::<[u8]>() };
+            let source = source
+                .slice(range)
+                .expect("range was checked against buffer size");
+            builder.copy_buffer(source, destination)?;
+            Ok(())
+        };
+        let source = CopySource::Buffer {
+            size: copied,
+            copy: Box::new(copy),
+        };
+        Ok(self.enqueue(source))
+    }
+
+    /// Requests readback of the image which the next frame is rendered into.
+    ///
+    /// Image must have been created with `transfer_source` usage.
+    ///
+    pub fn frame(&mut self, subresource: ImageSubresource) -> ReadbackTicket {
+        self.enqueue(CopySource::Frame(subresource))
+    }
+
+    fn enqueue(&mut self, source: CopySource) -> ReadbackTicket {
+        let slot = Slot::new();
+        self.queued.push(QueuedCopy {
+            source,
+            slot: slot.clone(),
+        });
+        ReadbackTicket { slot }
+    }
+
+    /// Records all queued copies into one command buffer.
+    ///
+    /// `frame_image` is the image which the frame was rendered into: if there is none,
+    /// readbacks of the frame image fail. Layout of the image is transitioned for the copy
+    /// and back by the command buffer builder. Returns `None` if there is nothing to copy.
+    ///
+    pub fn record(
+        &mut self,
+        queue: &Arc<Queue>,
+        frame_image: Option<FrameImage>,
+    ) -> Result<Option<(PrimaryAutoCommandBuffer, ReadbackBatch)>, ReadbackRecordError> {
+        self.reclaim();
+        let queued: Vec<_> = std::mem::take(&mut self.queued)
+            .into_iter()
+            // Copy of the dropped ticket which was not recorded yet is not needed at all.
+            .filter(|copy| !copy.slot.cancelled.load(Ordering::SeqCst))
+            .collect();
+        if queued.is_empty() {
+            return Ok(None);
+        }
+        let slots: Vec<_> = queued.iter().map(|copy| copy.slot.clone()).collect();
+        let recorded = AutoCommandBufferBuilder::primary(
+            self.allocator.device().clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .map_err(ReadbackRecordError::from)
+        .and_then(|mut builder| {
+            let batch = self.record_copies(&mut builder, queued, frame_image)?;
+            Ok((builder.build()?, batch))
+        });
+        if recorded.is_err() {
+            slots.iter().for_each(|slot| slot.fail());
+        }
+        recorded.map(Some)
+    }
+
+    fn record_copies(
+        &mut self,
+        builder: &mut Builder,
+        queued: Vec<QueuedCopy>,
+        frame_image: Option<FrameImage>,
+    ) -> Result<ReadbackBatch, ReadbackRecordError> {
+        let mut batch = ReadbackBatch {
+            slots: Vec::with_capacity(queued.len()),
+        };
+        for QueuedCopy { source, slot } in queued {
+            let layout = match &source {
+                CopySource::Buffer { size, .. } => Ok((*size, 4, None)),
+                CopySource::Frame(subresource) => match &frame_image {
+                    Some(image) => self::image_layout(&**image, *subresource),
+                    None => Err(ReadbackError::UnsupportedImage),
+                },
+            };
+            let (size, alignment, info) = match layout {
+                Ok(layout) => layout,
+                Err(error) => {
+                    log::warn!("image could not be read back: {}", error);
+                    slot.fail();
+                    continue;
+                }
+            };
+            let mut placement = self.allocate(size, alignment)?;
+            // Empty range is still allocated as one byte, which is not read back.
+            placement.range.end = placement.range.start + size;
+            let destination = BufferSlice::from_typed_buffer_access(placement.buffer.clone())
+                .slice(placement.range.clone())
+                .expect("placement must be inside of the ring");
+            match source {
+                CopySource::Buffer { copy, .. } => copy(builder, destination)?,
+                CopySource::Frame(subresource) => {
+                    let image = frame_image.clone().expect("frame image was checked");
+                    self::copy_image(builder, image, subresource, destination)?
+                }
+            }
+
+            let mut state = slot.state.lock().unwrap();
+            state.stage = Stage::Recorded;
+            state.placement = Some(placement);
+            state.info = info;
+            drop(state);
+            self.in_flight.push(slot.clone());
+            batch.slots.push(slot);
+        }
+        Ok(batch)
+    }
+
+    /// Allocates range of the ring, creating bigger ring if the current one is full.
+    fn allocate(
+        &mut self,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Result<Placement, MappedBufferCreationError> {
+        if let Some(buffer) = &self.ring_buffer {
+            if let Some(range) = self.ring.allocate(size, alignment) {
+                return Ok(Placement {
+                    buffer: buffer.clone(),
+                    generation: self.generation,
+                    range,
+                });
+            }
+        }
+        // Previous ring is kept alive by readbacks which still use it.
+        let capacity = (self.ring.capacity() * 2)
+            .max(size)
+            .max(MIN_RING_SIZE)
+            .next_power_of_two();
+        let buffer = MappedBuffer::with_memory_type(
+            &self.allocator,
+            BufferUsage::transfer_destination(),
+            capacity as usize,
+            |memory_type| memory_type.is_host_cached(),
+        )?;
+        log::debug!("readback ring of {} bytes was created", capacity);
+        self.ring = Ring::new(capacity);
+        self.ring_buffer = Some(buffer.clone());
+        self.generation += 1;
+        let range = self
+            .ring
+            .allocate(size, alignment)
+            .expect("empty ring must fit the range");
+        Ok(Placement {
+            buffer,
+            generation: self.generation,
+            range,
+        })
+    }
+
+    /// Releases ranges of the ring which are not used by readbacks anymore.
+    fn reclaim(&mut self) {
+        let ring = &mut self.ring;
+        let generation = self.generation;
+        self.in_flight.retain(|slot| {
+            if !slot.is_resolved() {
+                return true;
+            }
+            let state = slot.state.lock().unwrap();
+            if let Some(placement) = &state.placement {
+                if placement.generation == generation {
+                    ring.release(placement.range.start);
+                }
+            }
+            false
+        });
+    }
+}
+
+/// Description of the subresource of the image which is read back.
+fn image_info(
+    image: &dyn ImageAccess,
+    subresource: ImageSubresource,
+) -> Result<ImageReadbackInfo, ReadbackError> {
+    let dimensions = image.dimensions();
+    let mip_dimensions = (subresource.array_layer < dimensions.array_layers())
+        .then(|| dimensions.mipmap_dimensions(subresource.mip_level))
+        .flatten()
+        .filter(|_| subresource.mip_level < image.mipmap_levels())
+        .ok_or(ReadbackError::InvalidSubresource(subresource))?;
+    let format = image.format();
+    if format.size().is_none() {
+        return Err(ReadbackError::UnsupportedFormat(format));
+    }
+    Ok(ImageReadbackInfo {
+        extent: mip_dimensions.width_height_depth(),
+        format,
+    })
+}
+
+/// Size, alignment in the ring and description of the image data.
+fn image_layout(
+    image: &dyn ImageAccess,
+    subresource: ImageSubresource,
+) -> Result<(DeviceSize, DeviceSize, Option<ImageReadbackInfo>), ReadbackError> {
+    let info = self::image_info(image, subresource)?;
+    // Offset of the copy must be a multiple of both 4 and size of the texel block.
+    let block_size = info.format.size().unwrap_or(1);
+    let alignment = self::lcm(4, block_size);
+    Ok((info.size(), alignment, Some(info)))
+}
+
+fn copy_image(
+    builder: &mut Builder,
+    image: FrameImage,
+    subresource: ImageSubresource,
+    destination: RingSlice,
+) -> Result<(), CopyBufferImageError> {
+    let info = self::image_info(&*image, subresource).expect("subresource was checked");
+    // Buffer rows are tightly packed, so the data needs no de-padding.
+    builder.copy_image_to_buffer_dimensions(
+        image,
+        destination,
+        [0, 0, 0],
+        info.extent,
+        subresource.array_layer,
+        1,
+        subresource.mip_level,
+    )?;
+    Ok(())
+}
+
+fn lcm(a: DeviceSize, b: DeviceSize) -> DeviceSize {
+    fn gcd(a: DeviceSize, b: DeviceSize) -> DeviceSize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    a / gcd(a, b).max(1) * b
+}
@@ -0,0 +1,82 @@
+//! Allocation of byte ranges of the ring buffer which could be released in any order.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use vulkano::DeviceSize;
+
+use crate::graphics::utils::align_up;
+
+/// Ring of bytes which ranges are allocated one after another, wrapping at the end.
+///
+/// Space of the released range is reused only after all ranges allocated
+/// before it were released too.
+///
+#[derive(Debug)]
+pub(super) struct Ring {
+    capacity: DeviceSize,
+    /// Live ranges in order of allocation, with flags whether they were released.
+    ranges: VecDeque<(Range<DeviceSize>, bool)>,
+}
+
+impl Ring {
+    /// Creates an empty ring of given capacity (in bytes).
+    pub fn new(capacity: DeviceSize) -> Self {
+        Self {
+            capacity,
+            ranges: VecDeque::new(),
+        }
+    }
+
+    /// Capacity of the ring (in bytes).
+    pub fn capacity(&self) -> DeviceSize {
+        self.capacity
+    }
+
+    /// Allocates range of given size which offset is a multiple of given alignment,
+    /// or returns `None` if there is no free space for it.
+    pub fn allocate(
+        &mut self,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Option<Range<DeviceSize>> {
+        let size = size.max(1);
+        let range = match (self.ranges.front(), self.ranges.back()) {
+            (Some((first, _)), Some((last, _))) => {
+                let start = align_up(last.end, alignment);
+                let wrapped = last.start < first.start;
+                if wrapped {
+                    // Free space is between the last and the first ranges.
+                    (start + size <= first.start).then(|| start..start + size)
+                } else if start + size <= self.capacity {
+                    Some(start..start + size)
+                } else {
+                    // Free space at the end is too small, so the range starts at the beginning.
+                    (size <= first.start).then(|| 0..size)
+                }
+            }
+            _ => (size <= self.capacity).then(|| 0..size),
+        }?;
+        self.ranges.push_back((range.clone(), false));
+        Some(range)
+    }
+
+    /// Releases the range which starts at given offset.
+    ///
+    /// Returns `false` if there is no such range.
+    ///
+    pub fn release(&mut self, offset: DeviceSize) -> bool {
+        let range = self
+            .ranges
+            .iter_mut()
+            .find(|(range, released)| range.start == offset && !released);
+        match range {
+            Some((_, released)) => *released = true,
+            None => return false,
+        }
+        while let Some((_, true)) = self.ranges.front() {
+            self.ranges.pop_front();
+        }
+        true
+    }
+}
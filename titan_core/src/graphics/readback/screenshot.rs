@@ -0,0 +1,106 @@
+//! Screenshots: capture of rendered frames as RGBA images.
+
+use std::path::Path;
+use std::time::Duration;
+
+use image::{ImageError, ImageFormat, RgbaImage};
+use thiserror::Error;
+use vulkano::format::Format;
+
+use super::{ImageReadbackInfo, ReadbackTicket, ReadbackWaitError};
+
+/// Error that can happen when the captured frame is converted into the image.
+#[derive(Debug, Error)]
+pub enum ScreenshotError {
+    #[error("frame readback failure: {0}")]
+    Readback(#[from] ReadbackWaitError),
+
+    #[error("frame format {0:?} could not be converted into RGBA image")]
+    UnsupportedFormat(Format),
+
+    #[error("frame data is smaller than its extent")]
+    InvalidData,
+
+    #[error("failed to save image: {0}")]
+    Save(#[from] ImageError),
+}
+
+/// Handle of the frame which is captured by [`Renderer::capture_frame`](crate::graphics::Renderer::capture_frame).
+///
+/// Frame is copied after it is drawn, right before it is presented,
+/// so the image is ready only after the next frame is rendered.
+///
+pub struct Screenshot {
+    ticket: ReadbackTicket,
+}
+
+impl Screenshot {
+    pub(crate) fn new(ticket: ReadbackTicket) -> Self {
+        Self { ticket }
+    }
+
+    /// Returns the image if the frame was rendered and copied,
+    /// or `None` if the frame is not ready yet.
+    ///
+    /// Image is returned only once: the next calls return an error.
+    ///
+    pub fn try_image(&self) -> Option<Result<RgbaImage, ScreenshotError>> {
+        match self.ticket.wait(Duration::ZERO) {
+            Err(ReadbackWaitError::NotSubmitted | ReadbackWaitError::Timeout) => None,
+            result => Some(self.image(result)),
+        }
+    }
+
+    /// Blocks until the frame is copied, then returns the image.
+    ///
+    /// Frame must be rendered before the call (for example, by
+    /// [`Application::render_frames`](crate::app::Application::render_frames)),
+    /// so the error is returned immediately if it was not.
+    ///
+    pub fn wait(&self, timeout: Duration) -> Result<RgbaImage, ScreenshotError> {
+        self.image(self.ticket.wait(timeout))
+    }
+
+    /// Blocks until the frame is copied (see [`Screenshot::wait`]),
+    /// then saves it into the file as PNG image.
+    pub fn save_png(
+        &self,
+        path: impl AsRef<Path>,
+        timeout: Duration,
+    ) -> Result<(), ScreenshotError> {
+        let image = self.wait(timeout)?;
+        image.save_with_format(path, ImageFormat::Png)?;
+        Ok(())
+    }
+
+    fn image(
+        &self,
+        data: Result<Vec<u8>, ReadbackWaitError>,
+    ) -> Result<RgbaImage, ScreenshotError> {
+        let data = data?;
+        let info = self
+            .ticket
+            .image_info()
+            .expect("frame must be read back as an image");
+        self::rgba_image(info, data)
+    }
+}
+
+/// Converts data of the frame image into RGBA image, swapping channels of BGRA formats.
+///
+/// Pixels are left as is, so they are in sRGB color space for sRGB formats.
+///
+pub(crate) fn rgba_image(
+    info: ImageReadbackInfo,
+    mut data: Vec<u8>,
+) -> Result<RgbaImage, ScreenshotError> {
+    match info.format {
+        Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => (),
+        Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => {
+            data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2))
+        }
+        format => return Err(ScreenshotError::UnsupportedFormat(format)),
+    }
+    let [width, height, _] = info.extent;
+    RgbaImage::from_raw(width, height, data).ok_or(ScreenshotError::InvalidData)
+}
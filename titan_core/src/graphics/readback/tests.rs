@@ -0,0 +1,174 @@
+#![cfg(test)]
+
+use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::command_buffer::PrimaryCommandBuffer;
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{Device, DeviceExtensions, Features};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::Version;
+
+use super::ring::Ring;
+use super::*;
+
+#[test]
+fn test_lcm() {
+    assert_eq!(lcm(4, 3), 12);
+    assert_eq!(lcm(4, 16), 16);
+}
+
+#[test]
+fn test_ring_wraps_after_release() {
+    let mut ring = Ring::new(100);
+    assert_eq!(ring.allocate(40, 4), Some(0..40));
+    assert_eq!(ring.allocate(30, 4), Some(40..70));
+    // There are only 30 bytes left at the end.
+    assert_eq!(ring.allocate(40, 4), None);
+
+    assert!(ring.release(0));
+    assert_eq!(ring.allocate(40, 4), Some(0..40));
+    // After wrapping, only space before the first range could be used.
+    assert_eq!(ring.allocate(1, 4), None);
+    assert!(ring.release(40));
+    assert_eq!(ring.allocate(50, 4), Some(40..90));
+    assert!(!ring.release(41));
+}
+
+#[test]
+fn test_ring_out_of_order_release() {
+    let mut ring = Ring::new(64);
+    let first = ring.allocate(16, 1).unwrap();
+    let second = ring.allocate(16, 1).unwrap();
+    let third = ring.allocate(32, 1).unwrap();
+    assert_eq!(third, 32..64);
+
+    // Released range is reused only after all older ranges were released too.
+    assert!(ring.release(second.start));
+    assert_eq!(ring.allocate(16, 1), None);
+    assert!(ring.release(first.start));
+    assert_eq!(ring.allocate(32, 1), Some(0..32));
+
+    assert!(ring.release(third.start));
+    assert!(ring.release(0));
+    // Empty ring starts from the beginning again.
+    assert_eq!(ring.allocate(64, 1), Some(0..64));
+    assert_eq!(Ring::new(8).allocate(9, 1), None);
+}
+
+#[test]
+fn test_image_info_size() {
+    let info = ImageReadbackInfo {
+        extent: [5, 3, 1],
+        format: Format::R8G8B8A8_UNORM,
+    };
+    assert_eq!((info.row_size(), info.rows(), info.size()), (20, 3, 60));
+    // Compressed rows are rows of 4x4 blocks.
+    let info = ImageReadbackInfo {
+        extent: [6, 5, 2],
+        format: Format::BC1_RGBA_UNORM_BLOCK,
+    };
+    assert_eq!((info.row_size(), info.rows(), info.size()), (16, 4, 64));
+}
+
+#[test]
+fn test_rgba_image() {
+    let mut info = ImageReadbackInfo {
+        extent: [2, 1, 1],
+        format: Format::B8G8R8A8_SRGB,
+    };
+    let data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let image = rgba_image(info, data.clone()).unwrap();
+    assert_eq!(image.dimensions(), (2, 1));
+    assert_eq!(image.into_raw(), [3, 2, 1, 4, 7, 6, 5, 8]);
+
+    info.format = Format::R8G8B8A8_UNORM;
+    assert_eq!(rgba_image(info, data.clone()).unwrap().into_raw(), data);
+    assert!(matches!(
+        rgba_image(info, data[..4].to_vec()),
+        Err(ScreenshotError::InvalidData)
+    ));
+    info.format = Format::R16G16B16A16_SFLOAT;
+    assert!(matches!(
+        rgba_image(info, data),
+        Err(ScreenshotError::UnsupportedFormat(
+            Format::R16G16B16A16_SFLOAT
+        ))
+    ));
+}
+
+/// Round-trips buffer which was written by the CPU through the device.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_buffer_readback() {
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device
+        .queue_families()
+        .find(|family| family.supports_graphics())
+        .unwrap();
+    let (device, mut queues) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    let values: Vec<u32> = (0..64).map(|value| value * 3).collect();
+    let source = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_source(),
+        false,
+        values.iter().copied(),
+    )
+    .unwrap();
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect();
+
+    let mut readback = Readback::new(MemoryAllocator::new(device));
+    let whole = readback.buffer(source.clone(), 0..256).unwrap();
+    let part = readback.buffer(source.clone(), 10..30).unwrap();
+    let dropped = readback.buffer(source.clone(), 0..4).unwrap();
+    let frame = readback.frame(ImageSubresource::default());
+    assert!(matches!(
+        readback.buffer(source.clone(), 250..260),
+        Err(ReadbackError::OutOfBounds {
+            start: 250,
+            end: 260,
+            size: 256
+        })
+    ));
+    drop(dropped);
+    assert_eq!(
+        whole.wait(Duration::from_secs(1)),
+        Err(ReadbackWaitError::NotSubmitted)
+    );
+
+    // All readbacks of the frame share one command buffer and one fence.
+    let (command_buffer, batch) = readback.record(&queue, None).unwrap().unwrap();
+    assert_eq!(batch.slots.len(), 2);
+    let fence = command_buffer
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap();
+    batch.submitted(Arc::new(fence));
+
+    assert_eq!(whole.wait(Duration::from_secs(1)).unwrap(), bytes);
+    assert_eq!(part.wait(Duration::from_secs(1)).unwrap(), &bytes[10..30]);
+    assert_eq!(whole.try_get(), None);
+    assert_eq!(whole.wait(Duration::ZERO), Err(ReadbackWaitError::Taken));
+    // There is no frame image, so its readback fails.
+    assert_eq!(frame.wait(Duration::ZERO), Err(ReadbackWaitError::Failed));
+    assert!(frame.image_info().is_none());
+
+    // Resolved ranges are reused by the next frame.
+    let again = readback.buffer(source, 0..8).unwrap();
+    let (_, batch) = readback.record(&queue, None).unwrap().unwrap();
+    assert_eq!(readback.in_flight.len(), 1);
+    drop(batch);
+    assert_eq!(again.try_get(), None);
+}
@@ -0,0 +1,543 @@
+//! Reflection of SPIR-V shaders at runtime.
+//!
+//! Built-in pipelines are created from shaders compiled by `vulkano_shaders`,
+//! which checks their interface at compile time. Shaders loaded at runtime
+//! (for example, by [render hooks](super::RenderHook)) are reflected with this module instead:
+//! descriptor bindings, push constants, vertex inputs and specialization constants
+//! are read from SPIR-V,
+//! merged across stages of the pipeline and matched against vertex types.
+
+use std::fmt;
+
+use thiserror::Error;
+use vulkano::format::Format;
+use vulkano::pipeline::vertex::{Vertex, VertexMemberInfo, VertexMemberTy};
+
+use super::{
+    particles::Particle,
+    vertex::{UiVertex, Vertex as SceneVertex},
+};
+
+pub mod spirv;
+
+mod tests;
+
+/// Error that can happen when parsing SPIR-V module.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum ReflectionError {
+    #[error("module is not a SPIR-V module")]
+    InvalidMagic,
+
+    #[error("module is truncated")]
+    Truncated,
+
+    #[error("instruction at word {0} is invalid")]
+    InvalidInstruction(usize),
+
+    #[error("type with id {0} is not declared or not supported")]
+    UnknownType(u32),
+
+    #[error("module has no entry point")]
+    NoEntryPoint,
+
+    #[error("execution model {0} is not supported")]
+    UnsupportedStage(u32),
+
+    #[error("vertex input at location {0} is not a scalar or vector of 32-bit numbers")]
+    UnsupportedInput(u32),
+}
+
+/// Error that can happen when interfaces of shaders do not match each other or vertex type.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InterfaceError {
+    #[error("binding {binding} of set {set} is {first} in one stage, but {second} in another")]
+    BindingMismatch {
+        set: u32,
+        binding: u32,
+        first: DescriptorKind,
+        second: DescriptorKind,
+    },
+
+    #[error("binding {binding} of set {set} has {first} descriptors in one stage, but {second} in another")]
+    BindingCountMismatch {
+        set: u32,
+        binding: u32,
+        first: u32,
+        second: u32,
+    },
+
+    #[error("shader of {0:?} stage has no vertex inputs")]
+    NotVertexShader(ShaderStage),
+
+    #[error(
+        "vertex type `{vertex}` has no attribute `{name}` which is required at location {location}"
+    )]
+    MissingAttribute {
+        vertex: &'static str,
+        name: String,
+        location: u32,
+    },
+
+    #[error("attribute `{name}` of vertex type `{vertex}` is not {expected} as required at location {location}")]
+    AttributeFormatMismatch {
+        vertex: &'static str,
+        name: String,
+        location: u32,
+        expected: AttributeFormat,
+    },
+}
+
+/// Stage of the pipeline which the shader is executed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ShaderStage {
+    Vertex,
+    TessellationControl,
+    TessellationEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    /// Stage of the entry point with given SPIR-V execution model.
+    fn from_execution_model(model: u32) -> Option<Self> {
+        let stage = match model {
+            0 => Self::Vertex,
+            1 => Self::TessellationControl,
+            2 => Self::TessellationEvaluation,
+            3 => Self::Geometry,
+            4 => Self::Fragment,
+            5 => Self::Compute,
+            _ => return None,
+        };
+        Some(stage)
+    }
+}
+
+/// Type of the descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DescriptorKind {
+    Sampler,
+    CombinedImageSampler,
+    SampledImage,
+    StorageImage,
+    UniformTexelBuffer,
+    StorageTexelBuffer,
+    UniformBuffer,
+    StorageBuffer,
+    InputAttachment,
+}
+
+impl fmt::Display for DescriptorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Descriptor binding which is used by shaders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorBinding {
+    /// Index of the descriptor set.
+    pub set: u32,
+    /// Index of the binding in the set.
+    pub binding: u32,
+    /// Type of descriptors of the binding.
+    pub kind: DescriptorKind,
+    /// Count of descriptors, zero for runtime arrays.
+    pub count: u32,
+    /// Size of one block of uniform and storage buffers (in bytes),
+    /// without trailing runtime array. Zero for other descriptors.
+    pub size: u32,
+    /// Name of the variable in the shader (empty if debug names were stripped).
+    pub name: String,
+    /// Stages which use the binding, in ascending order.
+    pub stages: Vec<ShaderStage>,
+}
+
+/// Range of push constants which is used by shaders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushConstantRange {
+    /// Offset of the range (in bytes).
+    pub offset: u32,
+    /// Size of the range (in bytes).
+    pub size: u32,
+    /// Stages which use push constants, in ascending order.
+    pub stages: Vec<ShaderStage>,
+}
+
+/// Type of components of the vertex attribute.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NumericType {
+    Float,
+    Int,
+    Uint,
+}
+
+/// Format of the vertex attribute: scalar or vector of 32-bit numbers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AttributeFormat {
+    /// Type of components.
+    pub numeric: NumericType,
+    /// Count of components (from 1 to 4).
+    pub components: u32,
+}
+
+impl AttributeFormat {
+    /// Vulkan format of the attribute in the vertex buffer.
+    pub fn format(self) -> Option<Format> {
+        let format = match (self.numeric, self.components) {
+            (NumericType::Float, 1) => Format::R32_SFLOAT,
+            (NumericType::Float, 2) => Format::R32G32_SFLOAT,
+            (NumericType::Float, 3) => Format::R32G32B32_SFLOAT,
+            (NumericType::Float, 4) => Format::R32G32B32A32_SFLOAT,
+            (NumericType::Int, 1) => Format::R32_SINT,
+            (NumericType::Int, 2) => Format::R32G32_SINT,
+            (NumericType::Int, 3) => Format::R32G32B32_SINT,
+            (NumericType::Int, 4) => Format::R32G32B32A32_SINT,
+            (NumericType::Uint, 1) => Format::R32_UINT,
+            (NumericType::Uint, 2) => Format::R32G32_UINT,
+            (NumericType::Uint, 3) => Format::R32G32B32_UINT,
+            (NumericType::Uint, 4) => Format::R32G32B32A32_UINT,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Returns `true` if member of the vertex type has this format.
+    fn matches(self, member: &VertexMemberInfo) -> bool {
+        let numeric = match member.ty {
+            VertexMemberTy::F32 => NumericType::Float,
+            VertexMemberTy::I32 => NumericType::Int,
+            VertexMemberTy::U32 => NumericType::Uint,
+            _ => return false,
+        };
+        numeric == self.numeric && member.array_size as u32 == self.components
+    }
+}
+
+impl fmt::Display for AttributeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scalar = match self.numeric {
+            NumericType::Float => "float",
+            NumericType::Int => "int",
+            NumericType::Uint => "uint",
+        };
+        match self.components {
+            1 => write!(f, "{}", scalar),
+            components => write!(f, "{}{}", scalar, components),
+        }
+    }
+}
+
+/// Vertex input of the vertex shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexInput {
+    /// Location of the input.
+    pub location: u32,
+    /// Name of the variable in the shader (empty if debug names were stripped).
+    pub name: String,
+    /// Format of the input.
+    pub format: AttributeFormat,
+}
+
+/// Type of the specialization constant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConstantKind {
+    Bool,
+    Int,
+    Uint,
+    Float,
+}
+
+impl fmt::Display for ConstantKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Bool => "bool",
+            Self::Int => "int",
+            Self::Uint => "uint",
+            Self::Float => "float",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Specialization constant which is declared by the shader
+/// (with `layout(constant_id = ...)` in GLSL).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecializationConstant {
+    /// Identifier of the constant.
+    pub id: u32,
+    /// Name of the constant in the shader (empty if debug names were stripped).
+    pub name: String,
+    /// Type of the constant.
+    pub kind: ConstantKind,
+}
+
+/// Interface of the entry point of the shader, read from its SPIR-V code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderReflection {
+    stage: ShaderStage,
+    entry_point: String,
+    bindings: Vec<DescriptorBinding>,
+    push_constants: Option<PushConstantRange>,
+    inputs: Vec<VertexInput>,
+    constants: Vec<SpecializationConstant>,
+}
+
+impl ShaderReflection {
+    /// Reflects SPIR-V module from words.
+    ///
+    /// Only the first entry point of the module is reflected.
+    ///
+    pub fn from_words(words: &[u32]) -> Result<Self, ReflectionError> {
+        spirv::parse(words)
+    }
+
+    /// Reflects SPIR-V module from bytes (for example, contents of `.spv` file).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReflectionError> {
+        spirv::parse_bytes(bytes)
+    }
+
+    /// Stage of the entry point.
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    /// Name of the entry point.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Descriptor bindings in ascending order of their sets and bindings.
+    pub fn bindings(&self) -> &[DescriptorBinding] {
+        &self.bindings
+    }
+
+    /// Range of push constants, if any.
+    pub fn push_constants(&self) -> Option<&PushConstantRange> {
+        self.push_constants.as_ref()
+    }
+
+    /// Vertex inputs in ascending order of their locations (empty if it is not a vertex shader).
+    pub fn inputs(&self) -> &[VertexInput] {
+        &self.inputs
+    }
+
+    /// Specialization constants of 32-bit types in ascending order of their identifiers.
+    pub fn specialization_constants(&self) -> &[SpecializationConstant] {
+        &self.constants
+    }
+}
+
+/// Layout of the pipeline which is merged from reflections of all its shaders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineReflection {
+    /// Descriptor bindings of all stages in ascending order of their sets and bindings.
+    pub bindings: Vec<DescriptorBinding>,
+    /// Range of push constants which covers ranges of all stages, if any.
+    pub push_constants: Option<PushConstantRange>,
+}
+
+impl PipelineReflection {
+    /// Merges reflections of shaders of all stages of the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if binding which is used by several stages
+    /// has different type or count of descriptors in them.
+    ///
+    pub fn from_shaders(shaders: &[&ShaderReflection]) -> Result<Self, InterfaceError> {
+        let mut layout = Self::default();
+        for shader in shaders {
+            for binding in &shader.bindings {
+                let existing = layout.bindings.iter_mut().find(|existing| {
+                    existing.set == binding.set && existing.binding == binding.binding
+                });
+                let existing = match existing {
+                    Some(existing) => existing,
+                    None => {
+                        layout.bindings.push(binding.clone());
+                        continue;
+                    }
+                };
+                if existing.kind != binding.kind {
+                    return Err(InterfaceError::BindingMismatch {
+                        set: binding.set,
+                        binding: binding.binding,
+                        first: existing.kind,
+                        second: binding.kind,
+                    });
+                }
+                if existing.count != binding.count {
+                    return Err(InterfaceError::BindingCountMismatch {
+                        set: binding.set,
+                        binding: binding.binding,
+                        first: existing.count,
+                        second: binding.count,
+                    });
+                }
+                // Stages could declare only a prefix of the block.
+                existing.size = existing.size.max(binding.size);
+                self::merge_stages(&mut existing.stages, &binding.stages);
+            }
+            if let Some(range) = &shader.push_constants {
+                match &mut layout.push_constants {
+                    Some(existing) => {
+                        existing.size = existing.size.max(range.offset + range.size);
+                        self::merge_stages(&mut existing.stages, &range.stages);
+                    }
+                    None => layout.push_constants = Some(range.clone()),
+                }
+            }
+        }
+        layout
+            .bindings
+            .sort_by_key(|binding| (binding.set, binding.binding));
+        Ok(layout)
+    }
+}
+
+fn merge_stages(stages: &mut Vec<ShaderStage>, other: &[ShaderStage]) {
+    stages.extend_from_slice(other);
+    stages.sort_unstable();
+    stages.dedup();
+}
+
+/// Attribute of the vertex input state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VertexInputAttribute {
+    /// Location of the input in the vertex shader.
+    pub location: u32,
+    /// Offset of the attribute in the vertex (in bytes).
+    pub offset: u32,
+    /// Format of the attribute.
+    pub format: Format,
+}
+
+/// Vertex input state of the pipeline with single vertex buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexInputState {
+    /// Size of one vertex (in bytes).
+    pub stride: u32,
+    /// Attributes in ascending order of their locations.
+    pub attributes: Vec<VertexInputAttribute>,
+}
+
+/// Vertex type which could be fed into vertex shaders.
+#[derive(Debug, Copy, Clone)]
+pub struct VertexFormat {
+    name: &'static str,
+    stride: u32,
+    member: fn(&str) -> Option<VertexMemberInfo>,
+}
+
+impl VertexFormat {
+    /// Format of the vertex type which implements [`Vertex`]
+    /// (for example, with [`vulkano::impl_vertex`] macro).
+    pub fn of<T: Vertex>() -> Self {
+        let name = std::any::type_name::<T>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+        Self {
+            name,
+            stride: std::mem::size_of::<T>() as u32,
+            member: T::member,
+        }
+    }
+
+    /// Name of the vertex type (without its path).
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Size of one vertex (in bytes).
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Derives vertex input state from inputs of the vertex shader,
+    /// matching them with members of this type by their names.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the vertex type has no member which is required by the shader
+    /// or format of the member differs from format of the input.
+    ///
+    pub fn vertex_input(
+        &self,
+        shader: &ShaderReflection,
+    ) -> Result<VertexInputState, InterfaceError> {
+        if shader.stage != ShaderStage::Vertex {
+            return Err(InterfaceError::NotVertexShader(shader.stage));
+        }
+        let attributes = shader
+            .inputs
+            .iter()
+            .map(|input| {
+                let member =
+                    (self.member)(&input.name).ok_or_else(|| InterfaceError::MissingAttribute {
+                        vertex: self.name,
+                        name: input.name.clone(),
+                        location: input.location,
+                    })?;
+                let format = input
+                    .format
+                    .format()
+                    .filter(|_| input.format.matches(&member));
+                let format = format.ok_or_else(|| InterfaceError::AttributeFormatMismatch {
+                    vertex: self.name,
+                    name: input.name.clone(),
+                    location: input.location,
+                    expected: input.format,
+                })?;
+                Ok(VertexInputAttribute {
+                    location: input.location,
+                    offset: member.offset as u32,
+                    format,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(VertexInputState {
+            stride: self.stride,
+            attributes,
+        })
+    }
+}
+
+/// Registry of known vertex types which are looked up by their names.
+///
+/// Vertex types of the engine are registered by default.
+///
+#[derive(Debug, Clone)]
+pub struct VertexFormats {
+    formats: Vec<VertexFormat>,
+}
+
+impl VertexFormats {
+    /// Registers vertex type, replacing existing one with the same name.
+    pub fn register<T: Vertex>(&mut self) {
+        let format = VertexFormat::of::<T>();
+        self.formats.retain(|existing| existing.name != format.name);
+        self.formats.push(format);
+    }
+
+    /// Returns format of the vertex type with given name.
+    pub fn get(&self, name: &str) -> Option<&VertexFormat> {
+        self.formats.iter().find(|format| format.name == name)
+    }
+
+    /// Formats of all registered vertex types in order of their registration.
+    pub fn iter(&self) -> impl Iterator<Item = &VertexFormat> {
+        self.formats.iter()
+    }
+}
+
+impl Default for VertexFormats {
+    fn default() -> Self {
+        let formats = vec![
+            VertexFormat::of::<SceneVertex>(),
+            VertexFormat::of::<UiVertex>(),
+            VertexFormat::of::<Particle>(),
+        ];
+        Self { formats }
+    }
+}
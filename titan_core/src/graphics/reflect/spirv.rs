@@ -0,0 +1,563 @@
+//! Minimal parser of SPIR-V modules which extracts interface of the entry point.
+//!
+//! Only instructions which describe types, names, decorations and global variables are read,
+//! function bodies are skipped.
+
+use std::collections::HashMap;
+
+use super::{
+    AttributeFormat, ConstantKind, DescriptorBinding, DescriptorKind, NumericType,
+    PushConstantRange, ReflectionError, ShaderReflection, ShaderStage, SpecializationConstant,
+    VertexInput,
+};
+
+/// Magic number which every SPIR-V module starts with.
+const MAGIC: u32 = 0x0723_0203;
+
+/// Count of words in the header of the module.
+const HEADER_LEN: usize = 5;
+
+mod op {
+    pub const NAME: u16 = 5;
+    pub const ENTRY_POINT: u16 = 15;
+    pub const TYPE_BOOL: u16 = 20;
+    pub const TYPE_INT: u16 = 21;
+    pub const TYPE_FLOAT: u16 = 22;
+    pub const TYPE_VECTOR: u16 = 23;
+    pub const TYPE_MATRIX: u16 = 24;
+    pub const TYPE_IMAGE: u16 = 25;
+    pub const TYPE_SAMPLER: u16 = 26;
+    pub const TYPE_SAMPLED_IMAGE: u16 = 27;
+    pub const TYPE_ARRAY: u16 = 28;
+    pub const TYPE_RUNTIME_ARRAY: u16 = 29;
+    pub const TYPE_STRUCT: u16 = 30;
+    pub const TYPE_POINTER: u16 = 32;
+    pub const CONSTANT: u16 = 43;
+    pub const SPEC_CONSTANT_TRUE: u16 = 48;
+    pub const SPEC_CONSTANT_FALSE: u16 = 49;
+    pub const SPEC_CONSTANT: u16 = 50;
+    pub const VARIABLE: u16 = 59;
+    pub const DECORATE: u16 = 71;
+    pub const MEMBER_DECORATE: u16 = 72;
+}
+
+mod decoration {
+    pub const SPEC_ID: u32 = 1;
+    pub const BUFFER_BLOCK: u32 = 3;
+    pub const ARRAY_STRIDE: u32 = 6;
+    pub const MATRIX_STRIDE: u32 = 7;
+    pub const BUILT_IN: u32 = 11;
+    pub const LOCATION: u32 = 30;
+    pub const BINDING: u32 = 33;
+    pub const DESCRIPTOR_SET: u32 = 34;
+    pub const OFFSET: u32 = 35;
+}
+
+mod storage_class {
+    pub const UNIFORM_CONSTANT: u32 = 0;
+    pub const INPUT: u32 = 1;
+    pub const UNIFORM: u32 = 2;
+    pub const PUSH_CONSTANT: u32 = 9;
+    pub const STORAGE_BUFFER: u32 = 12;
+}
+
+/// Dimensionality of the image which is read through texel buffer.
+const DIM_BUFFER: u32 = 5;
+
+/// Dimensionality of the image which is read as input attachment.
+const DIM_SUBPASS_DATA: u32 = 6;
+
+/// Type declared by the module.
+#[derive(Debug, Clone)]
+enum Type {
+    Bool,
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, count: u32 },
+    Image { dim: u32, sampled: u32 },
+    Sampler,
+    SampledImage,
+    Array { element: u32, length: u32 },
+    RuntimeArray { element: u32 },
+    Struct { members: Vec<u32> },
+    Pointer { pointee: u32 },
+}
+
+/// Global variable declared by the module.
+struct Variable {
+    id: u32,
+    ty: u32,
+    storage_class: u32,
+}
+
+/// Decorations of the object (or of the member of the structure).
+#[derive(Default)]
+struct Decorations {
+    spec_id: Option<u32>,
+    buffer_block: bool,
+    built_in: bool,
+    array_stride: Option<u32>,
+    matrix_stride: Option<u32>,
+    location: Option<u32>,
+    binding: Option<u32>,
+    set: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl Decorations {
+    fn apply(&mut self, decoration: u32, value: Option<u32>) {
+        match decoration {
+            decoration::SPEC_ID => self.spec_id = value,
+            decoration::BUFFER_BLOCK => self.buffer_block = true,
+            decoration::BUILT_IN => self.built_in = true,
+            decoration::ARRAY_STRIDE => self.array_stride = value,
+            decoration::MATRIX_STRIDE => self.matrix_stride = value,
+            decoration::LOCATION => self.location = value,
+            decoration::BINDING => self.binding = value,
+            decoration::DESCRIPTOR_SET => self.set = value,
+            decoration::OFFSET => self.offset = value,
+            _ => (),
+        }
+    }
+}
+
+/// Objects of the module which are needed for reflection.
+#[derive(Default)]
+struct Module {
+    entry_point: Option<(u32, String)>,
+    names: HashMap<u32, String>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    /// Result ids and types of specialization constants.
+    spec_constants: Vec<(u32, u32)>,
+    variables: Vec<Variable>,
+    decorations: HashMap<u32, Decorations>,
+    member_decorations: HashMap<(u32, u32), Decorations>,
+}
+
+/// Decodes literal string which is stored in words.
+fn string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    let len = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Parses SPIR-V module from words (in the host byte order).
+pub fn parse(words: &[u32]) -> Result<ShaderReflection, ReflectionError> {
+    if words.len() < HEADER_LEN {
+        return Err(ReflectionError::Truncated);
+    }
+    if words[0] != MAGIC {
+        return Err(ReflectionError::InvalidMagic);
+    }
+
+    let mut module = Module::default();
+    let mut offset = HEADER_LEN;
+    while offset < words.len() {
+        let word_count = (words[offset] >> 16) as usize;
+        let opcode = words[offset] as u16;
+        if word_count == 0 {
+            return Err(ReflectionError::InvalidInstruction(offset));
+        }
+        let operands = words
+            .get(offset + 1..offset + word_count)
+            .ok_or(ReflectionError::Truncated)?;
+        module.instruction(opcode, operands, offset)?;
+        offset += word_count;
+    }
+    module.reflection()
+}
+
+/// Parses SPIR-V module from bytes (in any byte order).
+pub fn parse_bytes(bytes: &[u8]) -> Result<ShaderReflection, ReflectionError> {
+    self::parse(&self::words(bytes)?)
+}
+
+/// Converts bytes of SPIR-V module (in any byte order) into words in the host byte order.
+pub fn words(bytes: &[u8]) -> Result<Vec<u32>, ReflectionError> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(ReflectionError::Truncated);
+    }
+    let words = bytes.chunks_exact(4).map(|chunk| chunk.try_into().unwrap());
+    let swapped = bytes.len() >= 4 && u32::from_be_bytes(bytes[..4].try_into().unwrap()) == MAGIC;
+    let words = if swapped {
+        words.map(u32::from_be_bytes).collect()
+    } else {
+        words.map(u32::from_le_bytes).collect()
+    };
+    Ok(words)
+}
+
+impl Module {
+    fn instruction(
+        &mut self,
+        opcode: u16,
+        operands: &[u32],
+        offset: usize,
+    ) -> Result<(), ReflectionError> {
+        let operand = |index: usize| {
+            operands
+                .get(index)
+                .copied()
+                .ok_or(ReflectionError::InvalidInstruction(offset))
+        };
+        match opcode {
+            op::NAME => {
+                let name = self::string(&operands[1.min(operands.len())..]);
+                self.names.insert(operand(0)?, name);
+            }
+            // Only the first entry point is reflected.
+            op::ENTRY_POINT if self.entry_point.is_none() => {
+                let name = self::string(&operands[2.min(operands.len())..]);
+                self.entry_point = Some((operand(0)?, name));
+            }
+            op::TYPE_BOOL => {
+                self.types.insert(operand(0)?, Type::Bool);
+            }
+            op::TYPE_INT => {
+                let ty = Type::Int {
+                    width: operand(1)?,
+                    signed: operand(2)? != 0,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_FLOAT => {
+                let ty = Type::Float { width: operand(1)? };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_VECTOR => {
+                let ty = Type::Vector {
+                    component: operand(1)?,
+                    count: operand(2)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_MATRIX => {
+                let ty = Type::Matrix {
+                    column: operand(1)?,
+                    count: operand(2)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_IMAGE => {
+                let ty = Type::Image {
+                    dim: operand(2)?,
+                    sampled: operand(6)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_SAMPLER => {
+                self.types.insert(operand(0)?, Type::Sampler);
+            }
+            op::TYPE_SAMPLED_IMAGE => {
+                self.types.insert(operand(0)?, Type::SampledImage);
+            }
+            op::TYPE_ARRAY => {
+                let ty = Type::Array {
+                    element: operand(1)?,
+                    length: operand(2)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_RUNTIME_ARRAY => {
+                let ty = Type::RuntimeArray {
+                    element: operand(1)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_STRUCT => {
+                let ty = Type::Struct {
+                    members: operands[1.min(operands.len())..].to_vec(),
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::TYPE_POINTER => {
+                let ty = Type::Pointer {
+                    pointee: operand(2)?,
+                };
+                self.types.insert(operand(0)?, ty);
+            }
+            op::CONSTANT => {
+                // Only 32-bit constants are needed (lengths of arrays).
+                self.constants.insert(operand(1)?, operand(2)?);
+            }
+            op::SPEC_CONSTANT_TRUE | op::SPEC_CONSTANT_FALSE | op::SPEC_CONSTANT => {
+                self.spec_constants.push((operand(1)?, operand(0)?));
+            }
+            op::VARIABLE => {
+                let variable = Variable {
+                    ty: operand(0)?,
+                    id: operand(1)?,
+                    storage_class: operand(2)?,
+                };
+                self.variables.push(variable);
+            }
+            op::DECORATE => {
+                let decorations = self.decorations.entry(operand(0)?).or_default();
+                decorations.apply(operand(1)?, operands.get(2).copied());
+            }
+            op::MEMBER_DECORATE => {
+                let key = (operand(0)?, operand(1)?);
+                let decorations = self.member_decorations.entry(key).or_default();
+                decorations.apply(operand(2)?, operands.get(3).copied());
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn ty(&self, id: u32) -> Result<&Type, ReflectionError> {
+        self.types.get(&id).ok_or(ReflectionError::UnknownType(id))
+    }
+
+    fn decorations(&self, id: u32) -> Option<&Decorations> {
+        self.decorations.get(&id)
+    }
+
+    /// Builds reflection of the entry point.
+    fn reflection(&self) -> Result<ShaderReflection, ReflectionError> {
+        let (model, entry_point) = self
+            .entry_point
+            .clone()
+            .ok_or(ReflectionError::NoEntryPoint)?;
+        let stage = ShaderStage::from_execution_model(model)
+            .ok_or(ReflectionError::UnsupportedStage(model))?;
+
+        let mut bindings = Vec::new();
+        let mut push_constants = None;
+        let mut inputs = Vec::new();
+        for variable in &self.variables {
+            let pointee = match self.ty(variable.ty)? {
+                Type::Pointer { pointee } => *pointee,
+                _ => return Err(ReflectionError::UnknownType(variable.ty)),
+            };
+            let decorations = self.decorations(variable.id);
+            let name = self.names.get(&variable.id).cloned().unwrap_or_default();
+            match variable.storage_class {
+                storage_class::UNIFORM_CONSTANT
+                | storage_class::UNIFORM
+                | storage_class::STORAGE_BUFFER => {
+                    let (set, binding) = match decorations {
+                        Some(Decorations {
+                            set,
+                            binding: Some(binding),
+                            ..
+                        }) => (set.unwrap_or(0), *binding),
+                        _ => continue,
+                    };
+                    let (kind, count) = self.descriptor(pointee, variable.storage_class)?;
+                    let size = match kind {
+                        DescriptorKind::UniformBuffer | DescriptorKind::StorageBuffer => {
+                            self.block_size(pointee)?
+                        }
+                        _ => 0,
+                    };
+                    bindings.push(DescriptorBinding {
+                        set,
+                        binding,
+                        kind,
+                        count,
+                        size,
+                        name,
+                        stages: vec![stage],
+                    });
+                }
+                storage_class::PUSH_CONSTANT => {
+                    push_constants = Some(PushConstantRange {
+                        offset: 0,
+                        size: self.size(pointee)?,
+                        stages: vec![stage],
+                    });
+                }
+                storage_class::INPUT if stage == ShaderStage::Vertex => {
+                    let location = match decorations {
+                        Some(Decorations {
+                            location: Some(location),
+                            built_in: false,
+                            ..
+                        }) => *location,
+                        _ => continue,
+                    };
+                    let format = self
+                        .attribute_format(pointee)
+                        .ok_or(ReflectionError::UnsupportedInput(location))?;
+                    inputs.push(VertexInput {
+                        location,
+                        name,
+                        format,
+                    });
+                }
+                _ => (),
+            }
+        }
+        bindings.sort_by_key(|binding| (binding.set, binding.binding));
+        inputs.sort_by_key(|input| input.location);
+        Ok(ShaderReflection {
+            stage,
+            entry_point,
+            bindings,
+            push_constants,
+            inputs,
+            constants: self.specialization_constants(),
+        })
+    }
+
+    /// Specialization constants which have identifiers, in ascending order of them.
+    ///
+    /// Constants of types other than `bool` and 32-bit numbers could not be specialized
+    /// by the engine, so they are skipped.
+    ///
+    fn specialization_constants(&self) -> Vec<SpecializationConstant> {
+        let mut constants: Vec<_> = self
+            .spec_constants
+            .iter()
+            .filter_map(|&(id, ty)| {
+                let spec_id = self.decorations(id)?.spec_id?;
+                let kind = match self.types.get(&ty)? {
+                    Type::Bool => ConstantKind::Bool,
+                    Type::Int {
+                        width: 32,
+                        signed: true,
+                    } => ConstantKind::Int,
+                    Type::Int {
+                        width: 32,
+                        signed: false,
+                    } => ConstantKind::Uint,
+                    Type::Float { width: 32 } => ConstantKind::Float,
+                    _ => return None,
+                };
+                Some(SpecializationConstant {
+                    id: spec_id,
+                    name: self.names.get(&id).cloned().unwrap_or_default(),
+                    kind,
+                })
+            })
+            .collect();
+        constants.sort_by_key(|constant| constant.id);
+        constants
+    }
+
+    /// Kind and count of descriptors of the variable with given type and storage class.
+    ///
+    /// Count of runtime arrays is zero.
+    ///
+    fn descriptor(
+        &self,
+        ty: u32,
+        storage_class: u32,
+    ) -> Result<(DescriptorKind, u32), ReflectionError> {
+        let (ty, count) = match self.ty(ty)? {
+            Type::Array { element, length } => {
+                let length = self
+                    .constants
+                    .get(length)
+                    .copied()
+                    .ok_or(ReflectionError::UnknownType(*length))?;
+                (*element, length)
+            }
+            Type::RuntimeArray { element } => (*element, 0),
+            _ => (ty, 1),
+        };
+        let kind = match (self.ty(ty)?, storage_class) {
+            (Type::Struct { .. }, storage_class::STORAGE_BUFFER) => DescriptorKind::StorageBuffer,
+            (Type::Struct { .. }, storage_class::UNIFORM) => {
+                let buffer_block = self
+                    .decorations(ty)
+                    .map(|decorations| decorations.buffer_block)
+                    .unwrap_or(false);
+                if buffer_block {
+                    DescriptorKind::StorageBuffer
+                } else {
+                    DescriptorKind::UniformBuffer
+                }
+            }
+            (Type::SampledImage, _) => DescriptorKind::CombinedImageSampler,
+            (Type::Sampler, _) => DescriptorKind::Sampler,
+            (&Type::Image { dim, sampled }, _) => match (dim, sampled) {
+                (DIM_SUBPASS_DATA, _) => DescriptorKind::InputAttachment,
+                (DIM_BUFFER, 2) => DescriptorKind::StorageTexelBuffer,
+                (DIM_BUFFER, _) => DescriptorKind::UniformTexelBuffer,
+                (_, 2) => DescriptorKind::StorageImage,
+                _ => DescriptorKind::SampledImage,
+            },
+            _ => return Err(ReflectionError::UnknownType(ty)),
+        };
+        Ok((kind, count))
+    }
+
+    /// Size (in bytes) of one block of the buffer variable with given type
+    /// (element of the array, if the variable is an array of blocks).
+    fn block_size(&self, ty: u32) -> Result<u32, ReflectionError> {
+        match self.ty(ty)? {
+            Type::Array { element, .. } | Type::RuntimeArray { element } => self.size(*element),
+            _ => self.size(ty),
+        }
+    }
+
+    /// Size (in bytes) of the value of given type in the block.
+    fn size(&self, ty: u32) -> Result<u32, ReflectionError> {
+        let size = match self.ty(ty)? {
+            Type::Int { width, .. } | Type::Float { width } => width / 8,
+            Type::Vector { component, count } => self.size(*component)? * count,
+            Type::Matrix { column, count } => self.size(*column)? * count,
+            Type::Array { element, length } => {
+                let length = self.constants.get(length).copied().unwrap_or(0);
+                let stride = self.decorations(ty).and_then(|d| d.array_stride);
+                let stride = match stride {
+                    Some(stride) => stride,
+                    None => self.size(*element)?,
+                };
+                stride * length
+            }
+            Type::Struct { members } => {
+                let mut size = 0;
+                for (index, &member) in members.iter().enumerate() {
+                    let decorations = self.member_decorations.get(&(ty, index as u32));
+                    let offset = decorations.and_then(|d| d.offset).unwrap_or(size);
+                    let member_size = match (self.ty(member)?, decorations) {
+                        (
+                            Type::Matrix { count, .. },
+                            Some(Decorations {
+                                matrix_stride: Some(stride),
+                                ..
+                            }),
+                        ) => stride * count,
+                        _ => self.size(member)?,
+                    };
+                    size = size.max(offset + member_size);
+                }
+                size
+            }
+            _ => 0,
+        };
+        Ok(size)
+    }
+
+    /// Format of the vertex attribute of given type, if it is scalar or vector of 32-bit numbers.
+    fn attribute_format(&self, ty: u32) -> Option<AttributeFormat> {
+        let (component, components) = match self.types.get(&ty)? {
+            Type::Vector { component, count } => (*component, *count),
+            _ => (ty, 1),
+        };
+        let numeric = match self.types.get(&component)? {
+            Type::Float { width: 32 } => NumericType::Float,
+            Type::Int {
+                width: 32,
+                signed: true,
+            } => NumericType::Int,
+            Type::Int {
+                width: 32,
+                signed: false,
+            } => NumericType::Uint,
+            _ => return None,
+        };
+        Some(AttributeFormat {
+            numeric,
+            components,
+        })
+    }
+}
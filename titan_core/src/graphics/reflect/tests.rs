@@ -0,0 +1,517 @@
+#![cfg(test)]
+
+use super::*;
+
+/// Assembler of SPIR-V modules which declare only the interface of their entry point.
+///
+/// Modules of tests declare the same interface as built-in shaders,
+/// because their SPIR-V code is not available at runtime.
+///
+struct Assembler {
+    words: Vec<u32>,
+    bound: u32,
+}
+
+const INPUT: u32 = 1;
+const UNIFORM: u32 = 2;
+const UNIFORM_CONSTANT: u32 = 0;
+const OUTPUT: u32 = 3;
+const PUSH_CONSTANT: u32 = 9;
+const STORAGE_BUFFER: u32 = 12;
+
+const BUILT_IN: u32 = 11;
+const LOCATION: u32 = 30;
+const BINDING: u32 = 33;
+const DESCRIPTOR_SET: u32 = 34;
+const OFFSET: u32 = 35;
+const MATRIX_STRIDE: u32 = 7;
+const ARRAY_STRIDE: u32 = 6;
+const SPEC_ID: u32 = 1;
+
+impl Assembler {
+    /// Starts module with the entry point `main` of given execution model.
+    fn new(model: u32) -> Self {
+        let mut assembler = Self {
+            words: vec![0x0723_0203, 0x0001_0000, 0, 0, 0],
+            bound: 1,
+        };
+        let function = assembler.id();
+        let mut operands = vec![model, function];
+        operands.extend(self::string("main"));
+        assembler.op(15, &operands);
+        assembler
+    }
+
+    fn id(&mut self) -> u32 {
+        self.bound += 1;
+        self.bound - 1
+    }
+
+    fn op(&mut self, opcode: u16, operands: &[u32]) {
+        let word_count = operands.len() as u32 + 1;
+        self.words.push(word_count << 16 | opcode as u32);
+        self.words.extend_from_slice(operands);
+    }
+
+    /// Declares type with given opcode and operands after the result id.
+    fn ty(&mut self, opcode: u16, operands: &[u32]) -> u32 {
+        let id = self.id();
+        let mut words = vec![id];
+        words.extend_from_slice(operands);
+        self.op(opcode, &words);
+        id
+    }
+
+    fn float(&mut self) -> u32 {
+        self.ty(22, &[32])
+    }
+
+    fn uint(&mut self) -> u32 {
+        self.ty(21, &[32, 0])
+    }
+
+    fn vector(&mut self, count: u32) -> u32 {
+        let float = self.float();
+        self.ty(23, &[float, count])
+    }
+
+    fn mat4(&mut self) -> u32 {
+        let column = self.vector(4);
+        self.ty(24, &[column, 4])
+    }
+
+    /// Declares structure with members of given types at given offsets.
+    fn block(&mut self, members: &[(u32, u32)]) -> u32 {
+        let types: Vec<_> = members.iter().map(|&(ty, _)| ty).collect();
+        let block = self.ty(30, &types);
+        for (index, &(_, offset)) in members.iter().enumerate() {
+            self.op(72, &[block, index as u32, OFFSET, offset]);
+        }
+        block
+    }
+
+    fn variable(&mut self, ty: u32, storage_class: u32, name: &str) -> u32 {
+        let pointer = self.ty(32, &[storage_class, ty]);
+        let variable = self.id();
+        self.op(59, &[pointer, variable, storage_class]);
+        let mut operands = vec![variable];
+        operands.extend(self::string(name));
+        self.op(5, &operands);
+        variable
+    }
+
+    fn decorate(&mut self, id: u32, decoration: u32, value: &[u32]) {
+        let mut operands = vec![id, decoration];
+        operands.extend_from_slice(value);
+        self.op(71, &operands);
+    }
+
+    fn input(&mut self, ty: u32, location: u32, name: &str) {
+        let variable = self.variable(ty, INPUT, name);
+        self.decorate(variable, LOCATION, &[location]);
+    }
+
+    fn output(&mut self, ty: u32, location: u32, name: &str) {
+        let variable = self.variable(ty, OUTPUT, name);
+        self.decorate(variable, LOCATION, &[location]);
+    }
+
+    fn descriptor(&mut self, ty: u32, storage_class: u32, set: u32, binding: u32, name: &str) {
+        let variable = self.variable(ty, storage_class, name);
+        self.decorate(variable, DESCRIPTOR_SET, &[set]);
+        self.decorate(variable, BINDING, &[binding]);
+    }
+
+    /// Declares `CameraUBO` uniform block at binding 0.
+    fn camera_ubo(&mut self) {
+        let mat4 = self.mat4();
+        let camera = self.block(&[(mat4, 0), (mat4, 64), (mat4, 128)]);
+        for member in 0..3 {
+            self.op(72, &[camera, member, MATRIX_STRIDE, 16]);
+        }
+        self.descriptor(camera, UNIFORM, 0, 0, "ubo");
+    }
+
+    /// Declares specialization constant with given opcode, type and default value.
+    fn spec_constant(&mut self, opcode: u16, ty: u32, value: &[u32], name: &str) -> u32 {
+        let id = self.id();
+        let mut operands = vec![ty, id];
+        operands.extend_from_slice(value);
+        self.op(opcode, &operands);
+        let mut operands = vec![id];
+        operands.extend(self::string(name));
+        self.op(5, &operands);
+        id
+    }
+
+    fn finish(mut self) -> Vec<u32> {
+        self.words[3] = self.bound;
+        self.words
+    }
+}
+
+/// Encodes nul-terminated string into words.
+fn string(string: &str) -> Vec<u32> {
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.resize(bytes.len() / 4 * 4 + 4, 0);
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+/// Module with the interface of `default.vert`.
+fn default_vert() -> ShaderReflection {
+    let mut module = Assembler::new(0);
+    module.camera_ubo();
+    let vec3 = module.vector(3);
+    let vec4 = module.vector(4);
+    module.input(vec3, 0, "position");
+    module.input(vec4, 1, "color");
+    module.output(vec4, 0, "outColor");
+    // Built-in inputs are not vertex attributes.
+    let index = module.uint();
+    let vertex_index = module.variable(index, INPUT, "gl_VertexIndex");
+    module.decorate(vertex_index, BUILT_IN, &[42]);
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+/// Module with the interface of `default.frag`.
+fn default_frag() -> ShaderReflection {
+    let mut module = Assembler::new(4);
+    let vec4 = module.vector(4);
+    module.input(vec4, 0, "color");
+    module.output(vec4, 0, "outColor");
+    let bool = module.ty(20, &[]);
+    let swap = module.spec_constant(49, bool, &[], "SWAP_RED_BLUE");
+    module.decorate(swap, SPEC_ID, &[0]);
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+/// Module with the interface of `ui.vert`.
+fn ui_vert() -> ShaderReflection {
+    let mut module = Assembler::new(0);
+    let vec2 = module.vector(2);
+    let vec4 = module.vector(4);
+    module.input(vec2, 0, "position");
+    module.input(vec2, 1, "uv");
+    module.input(vec4, 2, "color");
+    let push_constants = module.block(&[(vec4, 0), (vec2, 16)]);
+    module.variable(push_constants, PUSH_CONSTANT, "pushConstants");
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+/// Module with the interface of `ui.frag`.
+fn ui_frag() -> ShaderReflection {
+    let mut module = Assembler::new(4);
+    let vec4 = module.vector(4);
+    let float = module.float();
+    module.input(vec4, 0, "color");
+    module.output(vec4, 0, "outColor");
+    // 2D sampled image of floats.
+    let image = module.ty(25, &[float, 1, 0, 0, 0, 1, 0]);
+    let sampled_image = module.ty(27, &[image]);
+    module.descriptor(sampled_image, UNIFORM_CONSTANT, 0, 0, "fontTexture");
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+/// Module with the interface of `particles.vert`.
+fn particles_vert() -> ShaderReflection {
+    let mut module = Assembler::new(0);
+    module.camera_ubo();
+    let vec3 = module.vector(3);
+    let float = module.float();
+    let vec4 = module.vector(4);
+    module.input(vec3, 0, "position");
+    module.input(float, 1, "lifetime");
+    let push_constants = module.block(&[(vec4, 0), (float, 16)]);
+    module.variable(push_constants, PUSH_CONSTANT, "pushConstants");
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+/// Module with the interface of `particles.comp`.
+fn particles_comp() -> ShaderReflection {
+    let mut module = Assembler::new(5);
+    let vec3 = module.vector(3);
+    let float = module.float();
+    let uint = module.uint();
+    let particle = module.block(&[(vec3, 0), (float, 12), (vec3, 16), (float, 28)]);
+    let particles = module.ty(29, &[particle]);
+    module.decorate(particles, ARRAY_STRIDE, &[32]);
+    let buffer = module.block(&[(particles, 0)]);
+    for (binding, name) in ["source", "destination", "spawned"].iter().enumerate() {
+        module.descriptor(buffer, STORAGE_BUFFER, 0, binding as u32, name);
+    }
+    let push_constants = module.block(&[
+        (vec3, 0),
+        (float, 12),
+        (float, 16),
+        (uint, 20),
+        (uint, 24),
+        (uint, 28),
+    ]);
+    module.variable(push_constants, PUSH_CONSTANT, "pushConstants");
+    ShaderReflection::from_words(&module.finish()).unwrap()
+}
+
+fn float(components: u32) -> AttributeFormat {
+    AttributeFormat {
+        numeric: NumericType::Float,
+        components,
+    }
+}
+
+#[test]
+fn test_reflect_builtin_shaders() {
+    let shader = self::default_vert();
+    assert_eq!(shader.stage(), ShaderStage::Vertex);
+    assert_eq!(shader.entry_point(), "main");
+    assert_eq!(
+        shader.bindings(),
+        [DescriptorBinding {
+            set: 0,
+            binding: 0,
+            kind: DescriptorKind::UniformBuffer,
+            count: 1,
+            size: 192,
+            name: "ubo".to_string(),
+            stages: vec![ShaderStage::Vertex],
+        }]
+    );
+    assert_eq!(shader.push_constants(), None);
+    let inputs: Vec<_> = shader
+        .inputs()
+        .iter()
+        .map(|input| (input.location, input.name.as_str(), input.format))
+        .collect();
+    assert_eq!(inputs, [(0, "position", float(3)), (1, "color", float(4))]);
+
+    assert!(shader.specialization_constants().is_empty());
+
+    let shader = self::default_frag();
+    assert_eq!(
+        shader.specialization_constants(),
+        [SpecializationConstant {
+            id: 0,
+            name: "SWAP_RED_BLUE".to_string(),
+            kind: ConstantKind::Bool,
+        }]
+    );
+
+    let shader = self::ui_vert();
+    assert!(shader.bindings().is_empty());
+    assert_eq!(shader.push_constants().map(|range| range.size), Some(24));
+    assert_eq!(shader.inputs().len(), 3);
+
+    let shader = self::ui_frag();
+    assert_eq!(shader.stage(), ShaderStage::Fragment);
+    let binding = &shader.bindings()[0];
+    assert_eq!(binding.kind, DescriptorKind::CombinedImageSampler);
+    assert_eq!(binding.name, "fontTexture");
+    // Inputs of other stages are not vertex attributes.
+    assert!(shader.inputs().is_empty());
+
+    let shader = self::particles_vert();
+    assert_eq!(shader.push_constants().map(|range| range.size), Some(20));
+
+    let shader = self::particles_comp();
+    assert_eq!(shader.stage(), ShaderStage::Compute);
+    let bindings: Vec<_> = shader
+        .bindings()
+        .iter()
+        .map(|binding| (binding.binding, binding.kind, binding.name.as_str()))
+        .collect();
+    assert_eq!(
+        bindings,
+        [
+            (0, DescriptorKind::StorageBuffer, "source"),
+            (1, DescriptorKind::StorageBuffer, "destination"),
+            (2, DescriptorKind::StorageBuffer, "spawned"),
+        ]
+    );
+    assert_eq!(shader.push_constants().map(|range| range.size), Some(32));
+}
+
+#[test]
+fn test_reflection_errors() {
+    assert_eq!(
+        ShaderReflection::from_words(&[1, 2, 3, 4, 5]),
+        Err(ReflectionError::InvalidMagic),
+    );
+    assert_eq!(
+        ShaderReflection::from_words(&[0x0723_0203]),
+        Err(ReflectionError::Truncated),
+    );
+    let mut words = Assembler::new(0).finish();
+    words.pop();
+    assert_eq!(
+        ShaderReflection::from_words(&words),
+        Err(ReflectionError::Truncated),
+    );
+
+    // Execution model of kernels.
+    let words = Assembler::new(6).finish();
+    assert_eq!(
+        ShaderReflection::from_words(&words),
+        Err(ReflectionError::UnsupportedStage(6)),
+    );
+}
+
+#[test]
+fn test_from_bytes() {
+    let mut module = Assembler::new(4);
+    let vec4 = module.vector(4);
+    module.output(vec4, 0, "outColor");
+    let words = module.finish();
+    let expected = ShaderReflection::from_words(&words).unwrap();
+
+    let little: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    assert_eq!(ShaderReflection::from_bytes(&little).unwrap(), expected);
+    let big: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+    assert_eq!(ShaderReflection::from_bytes(&big).unwrap(), expected);
+    assert_eq!(
+        ShaderReflection::from_bytes(&little[..little.len() - 1]),
+        Err(ReflectionError::Truncated),
+    );
+}
+
+#[test]
+fn test_pipeline_reflection() {
+    let (vertex, fragment) = (self::ui_vert(), self::ui_frag());
+    let layout = PipelineReflection::from_shaders(&[&vertex, &fragment]).unwrap();
+    assert_eq!(layout.bindings.len(), 1);
+    assert_eq!(layout.bindings[0].stages, [ShaderStage::Fragment]);
+    let push_constants = layout.push_constants.unwrap();
+    assert_eq!(push_constants.size, 24);
+    assert_eq!(push_constants.stages, [ShaderStage::Vertex]);
+
+    // Camera UBO is shared by both stages.
+    let (vertex, other) = (self::particles_vert(), self::default_vert());
+    let layout = PipelineReflection::from_shaders(&[&vertex, &other]).unwrap();
+    assert_eq!(layout.bindings.len(), 1);
+    assert_eq!(layout.push_constants.unwrap().size, 20);
+
+    let (vertex, fragment) = (self::default_vert(), self::ui_frag());
+    assert_eq!(
+        PipelineReflection::from_shaders(&[&vertex, &fragment]),
+        Err(InterfaceError::BindingMismatch {
+            set: 0,
+            binding: 0,
+            first: DescriptorKind::UniformBuffer,
+            second: DescriptorKind::CombinedImageSampler,
+        }),
+    );
+}
+
+#[test]
+fn test_vertex_input() {
+    let formats = VertexFormats::default();
+    let ui_vertex = formats.get("UiVertex").unwrap();
+    let state = ui_vertex.vertex_input(&self::ui_vert()).unwrap();
+    assert_eq!(state.stride, ui_vertex.stride());
+    let attributes: Vec<_> = state
+        .attributes
+        .iter()
+        .map(|attribute| (attribute.location, attribute.offset, attribute.format))
+        .collect();
+    assert_eq!(
+        attributes,
+        [
+            (0, 0, Format::R32G32_SFLOAT),
+            (1, 8, Format::R32G32_SFLOAT),
+            (2, 16, Format::R32G32B32A32_SFLOAT),
+        ]
+    );
+
+    let vertex = formats.get("Vertex").unwrap();
+    assert!(vertex.vertex_input(&self::default_vert()).is_ok());
+    assert_eq!(
+        vertex.vertex_input(&self::particles_vert()),
+        Err(InterfaceError::MissingAttribute {
+            vertex: "Vertex",
+            name: "lifetime".to_string(),
+            location: 1,
+        }),
+    );
+    assert_eq!(
+        ui_vertex.vertex_input(&self::default_vert()),
+        Err(InterfaceError::AttributeFormatMismatch {
+            vertex: "UiVertex",
+            name: "position".to_string(),
+            location: 0,
+            expected: float(3),
+        }),
+    );
+    assert_eq!(
+        vertex.vertex_input(&self::ui_frag()),
+        Err(InterfaceError::NotVertexShader(ShaderStage::Fragment)),
+    );
+
+    let particle = formats.get("Particle").unwrap();
+    let state = particle.vertex_input(&self::particles_vert()).unwrap();
+    assert_eq!(state.stride, 32);
+    assert_eq!(state.attributes[1].offset, 12);
+}
+
+#[test]
+fn test_specialization_constants() {
+    let mut module = Assembler::new(5);
+    let float = module.float();
+    let uint = module.uint();
+    let int = module.ty(21, &[32, 1]);
+    let double = module.ty(22, &[64]);
+    let constants = [
+        (float, &[0x3f80_0000][..], "scale", 7),
+        (uint, &[16], "count", 2),
+        (int, &[u32::MAX], "offset", 3),
+        (double, &[0, 0], "precise", 4),
+    ];
+    for (ty, value, name, spec_id) in constants {
+        let constant = module.spec_constant(50, ty, value, name);
+        module.decorate(constant, SPEC_ID, &[spec_id]);
+    }
+    // Constants without identifiers could not be specialized.
+    module.spec_constant(50, uint, &[1], "local");
+    let shader = ShaderReflection::from_words(&module.finish()).unwrap();
+
+    let constants: Vec<_> = shader
+        .specialization_constants()
+        .iter()
+        .map(|constant| (constant.id, constant.name.as_str(), constant.kind))
+        .collect();
+    // 64-bit constants are skipped.
+    assert_eq!(
+        constants,
+        [
+            (2, "count", ConstantKind::Uint),
+            (3, "offset", ConstantKind::Int),
+            (7, "scale", ConstantKind::Float),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_specialization() {
+    use crate::graphics::specialization::{SpecializationError, SpecializationInfo};
+
+    let (vertex, fragment) = (self::default_vert(), self::default_frag());
+    let shaders = [&vertex, &fragment];
+    let info = SpecializationInfo::new().with(0, true);
+    assert_eq!(info.validate(&shaders), Ok(()));
+    assert_eq!(SpecializationInfo::new().validate(&shaders), Ok(()));
+
+    let info = SpecializationInfo::new().with(0, 1u32);
+    assert_eq!(
+        info.validate(&shaders),
+        Err(SpecializationError::KindMismatch {
+            id: 0,
+            expected: ConstantKind::Bool,
+            actual: ConstantKind::Uint,
+        }),
+    );
+    let info = SpecializationInfo::new().with(0, false).with(5, 1.0);
+    assert_eq!(
+        info.validate(&shaders),
+        Err(SpecializationError::UnknownConstant(5)),
+    );
+}
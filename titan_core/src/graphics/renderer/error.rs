@@ -1,24 +1,36 @@
 //! Error types and utilities for graphics backend for game engine.
 
 use thiserror::Error;
-use vulkano::command_buffer::{BuildError, CommandBufferExecError, UpdateBufferError};
+use vulkano::command_buffer::{BuildError, CommandBufferExecError, CopyBufferImageError};
 use vulkano::descriptor_set::DescriptorSetError;
 use vulkano::device::DeviceCreationError;
+use vulkano::format::Format;
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
 use vulkano::instance::debug::DebugCallbackCreationError;
 use vulkano::instance::InstanceCreationError;
 use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::sampler::SamplerCreationError;
 use vulkano::swapchain::{AcquireError, CapabilitiesError, SwapchainCreationError};
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
-use crate::graphics::frame::{
-    object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
-    system::error::{
-        DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError,
+use crate::app::Cancelled;
+use crate::graphics::{
+    descriptor::DescriptorAllocError,
+    frame::{
+        object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
+        post_process::AaMode,
+        system::error::{
+            DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError,
+        },
+        ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
     },
-    ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
+    mapped::{MappedBufferCreationError, MappedBufferWriteError},
+    particles::error::{ParticleDrawError, ParticleUpdateError},
+    readback::{ReadbackRecordError, ReadbackWaitError},
+    sprite::error::{SpriteDrawError, SpriteDrawSystemCreationError},
+    upload::UploadError,
 };
 
 /// Error that can happen when creating the [`Renderer`](super::Renderer) system.
@@ -45,8 +57,17 @@ pub enum RendererCreationError {
     #[error("swapchain creation failure: {0}")]
     SwapchainCreation(#[from] SwapchainCreationError),
 
+    #[error("offscreen image creation failure: {0}")]
+    OffscreenImageCreation(#[from] ImageCreationError),
+
+    #[error("pipeline cache creation failure: {0}")]
+    PipelineCacheCreation(#[from] OomError),
+
     #[error("failed to allocate device memory: {0}")]
-    MemoryAllocation(#[from] DeviceMemoryAllocError),
+    MemoryAllocation(#[from] MappedBufferCreationError),
+
+    #[error("texture sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
 
     #[error("frame system creation failure: {0}")]
     FrameSystemCreation(#[from] FrameSystemCreationError),
@@ -56,6 +77,15 @@ pub enum RendererCreationError {
 
     #[error("UI draw system creation failure: {0}")]
     UiDrawSystemCreation(#[from] UiDrawSystemCreationError),
+
+    #[error("sprite draw system creation failure: {0}")]
+    SpriteDrawSystemCreation(#[from] SpriteDrawSystemCreationError),
+
+    #[error("failed to spawn submit thread: {0}")]
+    ThreadSpawn(#[source] std::io::Error),
+
+    #[error("renderer creation was cancelled")]
+    Cancelled(#[from] Cancelled),
 }
 
 /// Error that can happen on descriptor set creation.
@@ -68,30 +98,21 @@ pub enum DescriptorSetCreationError {
 /// Error that can happen on resizing of [`Renderer`](super::Renderer) system.
 #[derive(Debug, Error)]
 pub enum ResizeError {
+    #[error("surface capabilities retrieve failure: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
     #[error("swapchain recreation failure: {0}")]
     SwapchainRecreation(#[from] SwapchainCreationError),
 }
 
-/// Error that can happen on transfer command buffer creation
-/// for [`Renderer`](super::Renderer) system.
-///
-#[derive(Debug, Error)]
-pub enum TransferCommandBufferCreationError {
-    #[error("failed to allocate transfer command buffer: {0}")]
-    OutOfMemory(#[from] OomError),
-
-    #[error("update buffer command failure: {0}")]
-    UpdateBuffer(#[from] UpdateBufferError),
-
-    #[error("transfer command buffer build failure: {0}")]
-    Build(#[from] BuildError),
-}
-
 /// Error that can happen on rendering operation of [`Renderer`](super::Renderer) system.
 #[derive(Debug, Error)]
 pub enum RenderError {
-    #[error("transfer command buffer creation error while rendering: {0}")]
-    TransferCommandBufferCreation(#[from] TransferCommandBufferCreationError),
+    #[error("failed to write camera UBO while rendering: {0}")]
+    UniformWrite(#[from] MappedBufferWriteError),
+
+    #[error("failed to wait for the previous frame while rendering: {0}")]
+    FrameWait(#[source] FlushError),
 
     #[error("acquiring next image failure while rendering: {0}")]
     AcquireNextImage(#[from] AcquireError),
@@ -114,11 +135,67 @@ pub enum RenderError {
     #[error("failed to draw UI: {0}")]
     UiDraw(#[from] UiDrawError),
 
+    #[error("failed to upload text atlas: {0}")]
+    TextAtlasUpload(#[from] ImageRegisterError),
+
+    #[error("failed to update particles: {0}")]
+    ParticleUpdate(#[from] ParticleUpdateError),
+
+    #[error("failed to draw particles: {0}")]
+    ParticleDraw(#[from] ParticleDrawError),
+
+    #[error("failed to draw sprites: {0}")]
+    SpriteDraw(#[from] SpriteDrawError),
+
     #[error("failed to execute draw command buffer: {0}")]
     DrawPassExecution(#[from] DrawPassExecuteError),
 
     #[error("failed to resize while rendering: {0}")]
     Resize(#[from] ResizeError),
+
+    #[error("failed to allocate uniform buffer of viewport: {0}")]
+    ViewportBufferAllocation(#[from] MappedBufferCreationError),
+
+    #[error("failed to begin descriptor allocations of the frame: {0}")]
+    DescriptorAlloc(#[from] DescriptorAllocError),
+
+    #[error("swapchain is stalled: frames keep timing out after {0} swapchain recreation(s)")]
+    SwapchainStalled(u32),
+
+    #[error("submit thread has stopped")]
+    SubmitThreadStopped,
+
+    #[error("failed to create command buffer of render hooks: {0}")]
+    HookCommandsCreation(#[from] OomError),
+
+    #[error("failed to build command buffer of render hooks: {0}")]
+    HookCommandsBuild(#[from] BuildError),
+
+    #[error("failed to record readback copies: {0}")]
+    Readback(#[from] ReadbackRecordError),
+}
+
+/// Error that can happen on reading pixels of the frame
+/// which was rendered by [`Renderer`](super::Renderer) system.
+#[derive(Debug, Error)]
+pub enum ReadPixelsError {
+    #[error("frames are presented into the window, so they could not be read as pixels")]
+    NotHeadless,
+
+    #[error("no frame was rendered yet")]
+    NoFrame,
+
+    #[error("failed to record copy of the frame: {0}")]
+    Record(#[from] ReadbackRecordError),
+
+    #[error("copy command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit copy of the frame: {0}")]
+    SubmitQueue(#[from] FlushError),
+
+    #[error("failed to wait for copy of the frame: {0}")]
+    Wait(#[from] ReadbackWaitError),
 }
 
 /// Error of registering an image for UI.
@@ -133,6 +210,63 @@ pub enum ImageRegisterError {
     #[error("image creation failure: {0}")]
     ImageViewCreation(#[from] ImageViewCreationError),
 
+    #[error("texture sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+
     #[error("flush error: {0}")]
     Flush(#[from] FlushError),
+
+    #[error("format {0:?} is not supported by the device")]
+    UnsupportedFormat(Format),
+
+    #[error("staging buffer allocation failure: {0}")]
+    StagingBufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("upload command buffer creation failure: {0}")]
+    CommandBufferCreation(#[from] OomError),
+
+    #[error("failed to copy mip level: {0}")]
+    LevelCopy(#[from] CopyBufferImageError),
+
+    #[error("upload command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("upload command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("image upload failure: {0}")]
+    Upload(#[from] UploadError),
+}
+
+/// Error that can happen on shutdown of [`Renderer`](super::Renderer) system.
+#[derive(Debug, Error)]
+pub enum ShutdownError {
+    #[error("failed to wait until device becomes idle: {0}")]
+    WaitIdle(#[from] OomError),
+}
+
+/// Error that can happen on change of antialiasing mode
+/// of [`Renderer`](super::Renderer) system.
+#[derive(Debug, Error)]
+pub enum AntialiasingError {
+    #[error("antialiasing mode {0:?} is not supported by the device")]
+    NotSupported(AaMode),
+
+    #[error("failed to wait until device becomes idle: {0}")]
+    WaitIdle(#[from] OomError),
+
+    #[error("texture sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+
+    #[error("frame system creation failure: {0}")]
+    FrameSystemCreation(#[from] FrameSystemCreationError),
+
+    #[error("object draw system recreation failure: {0}")]
+    ObjectDrawSystemCreation(#[from] ObjectDrawSystemCreationError),
+
+    #[error("UI draw system recreation failure: {0}")]
+    UiDrawSystemCreation(#[from] UiDrawSystemCreationError),
+
+    #[error("sprite draw system recreation failure: {0}")]
+    SpriteDrawSystemCreation(#[from] SpriteDrawSystemCreationError),
 }
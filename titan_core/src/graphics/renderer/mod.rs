@@ -1,76 +1,247 @@
 //! Render utilities for graphics backend for game engine.
 
 use std::collections::HashSet;
-use std::iter;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use egui::{ClippedMesh, Texture, TextureId};
+use bytemuck::Pod;
+use egui::{pos2, ClippedMesh, Rect, Texture, TextureId};
 use image::RgbaImage;
-use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+use slotmap::{Key, SecondaryMap, SlotMap};
+use vulkano::buffer::BufferUsage;
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer,
 };
-use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::physical::{PhysicalDevice, QueueFamily};
 use vulkano::device::{Device, DeviceExtensions, Features, Queue};
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
-use vulkano::image::{ImageDimensions, ImageUsage, ImmutableImage, MipmapsCount, SwapchainImage};
-use vulkano::instance::debug::{DebugCallback, MessageSeverity, MessageType};
+use vulkano::image::{ImageUsage, ImmutableImage};
+use vulkano::instance::debug::DebugCallback;
 use vulkano::instance::Instance;
-use vulkano::swapchain::{AcquireError, PresentMode, Surface, Swapchain};
-use vulkano::sync::{FlushError, GpuFuture, SharingMode};
-use vulkano::{swapchain, sync};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::swapchain::{AcquireError, Capabilities, Swapchain};
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture, SharingMode};
+use vulkano::{swapchain, sync, DeviceSize};
 use vulkano_win::VkSurfaceBuild;
-use winit::dpi::LogicalSize;
-use winit::event_loop::EventLoop;
-use winit::window::{Window, WindowBuilder};
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event_loop::{EventLoop, EventLoopWindowTarget};
+use winit::window::{Window, WindowBuilder, WindowId as WinitWindowId};
 
 pub use error::RendererCreationError;
-use error::{ImageRegisterError, RenderError, ResizeError, TransferCommandBufferCreationError};
+use error::{
+    AntialiasingError, ImageRegisterError, ReadPixelsError, RenderError, ResizeError, ShutdownError,
+};
+use output::{FrameOutput, OffscreenImages};
+pub use output::{DEFAULT_OFFSCREEN_SIZE, OFFSCREEN_FORMAT};
 
-use crate::config::Config;
+use crate::{
+    app::startup::{Startup, GRAPHICS_PHASE},
+    asset::MeshSource,
+    camera::{ActiveCamera, Camera},
+    config::{Config, MAX_RENDER_SCALE, MIN_RENDER_SCALE},
+    math::Color,
+    task::TaskPool,
+    text::TextBrush,
+    window::{monitor, Size, WindowDesc, WindowIcon, WindowId},
+};
 
 use super::{
+    allocator::{MemoryAllocator, MemoryStats},
     camera::CameraUBO,
+    capture::{self, CameraCapture, FrameCapture},
+    clip::{ClipRect, ClipStack},
+    command::CommandBuffer,
+    descriptor::{DescriptorAllocator, DescriptorPoolSizes},
+    device_buffer::DeviceBuffer,
+    draw::{DrawCommand, DrawQueue, DrawSubmitError, InstanceData},
     frame::{
-        object_draw::ObjectDrawSystem,
-        system::{FrameSystem, Pass},
+        object_draw::{error::ObjectDrawSystemCreationError, ObjectDrawSystem},
+        post_process::AaMode,
+        system::{FrameSystem, Pass, PassOps},
         ui_draw::UiDrawSystem,
     },
+    frame_sync::FrameSync,
+    gpu_profiler::{self, GpuProfiler, GpuTimings, TimestampQuery},
+    hook::{FrameContext, HookCommands, HookList, HookStage, RenderHook},
+    index::{IndexBuffer, IndexBufferError},
+    indirect::{
+        IndirectBuffer, IndirectBufferCreationError, IndirectBufferId, IndirectDraw,
+        IndirectDrawError, IndirectDrawList,
+    },
+    mapped::{MappedBufferCreationError, MappedBufferWriteError},
+    mesh::{Mesh, MeshCreationError, MeshId},
+    particles::{
+        error::ParticleSystemCreationError, ParticleEmitter, ParticleParams, ParticleSystem,
+    },
+    pause::PauseControl,
+    pipeline_cache::{PersistentPipelineCache, PipelineCacheError},
+    pipeline_stats::{self, PipelineStatisticsQueries, StatisticsQuery},
+    pre_rotation::PreTransform,
+    present::PresentMode,
+    readback::{
+        FrameImage, ImageSubresource, Readback, ReadbackBatch, ReadbackError, ReadbackImage,
+        ReadbackTicket,
+    },
+    sampler::{CompareOp, SamplerCache, SamplerDesc},
+    secondary::{SecondaryWindow, SecondaryWindowCreationError, SecondaryWindowRenderError},
+    sprite::{SpriteBatch, SpriteDrawSystem, SpriteTextureId},
+    stats::{CullingReport, FrameStats, ResourceList},
+    streaming::{StreamingConfig, StreamingManager},
+    submit::SubmitThread,
+    texture::{self, TextureData},
+    timeout::{TimeoutAction, TimeoutTracker},
+    transient::TransientBufferPool,
+    uniform::{UniformBuffer, UniformRegistry, UniformRing},
+    upload::{StagingRing, UploadError},
     utils,
+    vertex::Vertex,
+    viewport::{self, Region, Viewport, ViewportError, ViewportList},
 };
 
 pub mod error;
+mod output;
+
+/// Time for which window size must stay unchanged before swapchain will be recreated.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Future which is signaled when the frame is finished (and presented into the window).
+type FrameFence = FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>;
+
+/// Thread which submits futures of frames and signals their fences.
+type FrameSubmitThread =
+    SubmitThread<Box<dyn GpuFuture + Send + Sync>, Result<FrameFence, FlushError>>;
+
+/// Frame which was submitted, with resources which wait for its fence.
+struct SubmittedFrame {
+    image_index: usize,
+    readback: Option<ReadbackBatch>,
+    statistics_query: Option<StatisticsQuery>,
+    timestamp_query: Option<TimestampQuery>,
+}
 
 /// System that renders all game objects and UI.
 #[allow(dead_code)]
 pub struct Renderer {
     previous_frame_end: Option<Box<dyn GpuFuture + Send + Sync>>,
+    /// Fences of frames in flight and of the last frames rendered into each swapchain image.
+    frame_sync: FrameSync<Arc<FrameFence>>,
+    /// Thread which submits frames, if they are not submitted on the main thread.
+    submit_thread: Option<FrameSubmitThread>,
+    /// Frame which was sent to the submit thread, but its result was not taken yet.
+    pending_frame: Option<SubmittedFrame>,
     recreate_swapchain: bool,
+    /// Whether acquisition reported that the swapchain is suboptimal,
+    /// so it is recreated after debounce even if its size is unchanged.
+    swapchain_suboptimal: bool,
+    /// Consecutive timeouts of image acquisition and frame waits.
+    timeouts: TimeoutTracker,
+    #[cfg(feature = "fault-injection")]
+    faults: super::timeout::FaultInjector,
+    resize_requested_at: Option<Instant>,
+    pause: PauseControl,
+    pre_transform: PreTransform,
     camera_ubo: CameraUBO,
+    active_camera: ActiveCamera,
+    stats: FrameStats,
+    /// File which the capture of the next rendered frame is dumped into.
+    capture_path: Option<PathBuf>,
+    readback: Readback,
+    /// If swapchain images could be read back.
+    frame_readback: bool,
 
     ui_draw_system: UiDrawSystem,
     object_draw_system: ObjectDrawSystem,
+    sprite_draw_system: SpriteDrawSystem,
+    sprites: SpriteBatch,
+    indirect_buffers: SlotMap<IndirectBufferId, IndirectBuffer>,
+    indirect_draw: Option<IndirectDraw>,
+    meshes: SlotMap<MeshId, Mesh>,
+    particle_system: Option<ParticleSystem>,
+    particles_updated_at: Instant,
+    pipeline_stats: Option<PipelineStatisticsQueries>,
+    gpu_profiler: Option<GpuProfiler>,
+    text_brush: TextBrush,
+    text_texture: Option<TextureId>,
+    streaming: Option<StreamingManager>,
     frame_system: FrameSystem,
-    uniform_buffers: Vec<Arc<DeviceLocalBuffer<CameraUBO>>>,
+    viewports: ViewportList,
+    culling: CullingReport,
+    draws: DrawQueue,
+    clip_stack: ClipStack,
+    /// Allocator of memory of host visible buffers of the renderer.
+    allocator: Arc<MemoryAllocator>,
+    /// Camera uniform buffers of each viewport, with buffer for each swapchain image.
+    uniform_buffers: Vec<UniformRing<CameraUBO>>,
+    /// Uniform buffers of the game which are written on each frame.
+    uniforms: UniformRegistry,
+    sampler_cache: SamplerCache,
+    pipeline_cache: PersistentPipelineCache,
+    descriptor_allocator: DescriptorAllocator,
+    /// Transient buffers of each swapchain image.
+    transient_pools: Vec<TransientBufferPool>,
+    /// Staging blocks of uploads and copies which the next frame waits for.
+    staging: StagingRing,
+    /// If mip levels are generated for textures of UI and sprites.
+    texture_mipmaps: bool,
+    hooks: HookList,
+    /// Secondary windows which share the device with the main window.
+    secondary_windows: SecondaryMap<WindowId, SecondaryWindow>,
 
-    swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
-    swapchain: Arc<Swapchain<Window>>,
+    output: FrameOutput,
     graphics_queue: Arc<Queue>,
     present_queue: Arc<Queue>,
     transfer_queue: Arc<Queue>,
     device: Arc<Device>,
-    surface: Arc<Surface<Window>>,
     debug_callback: Option<DebugCallback>,
     instance: Arc<Instance>,
 }
 
 impl Renderer {
-    /// Creates render system.
-    pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, RendererCreationError>
+    /// Creates render system which presents frames into the new window.
+    ///
+    /// Progress is reported after each step of creation, and creation is stopped
+    /// between steps if startup was cancelled.
+    ///
+    pub(crate) fn new<T>(
+        config: &Config,
+        event_loop: &EventLoop<T>,
+        startup: &Startup,
+    ) -> Result<Self, RendererCreationError>
+    where
+        T: 'static,
+    {
+        Self::create(config, Some(event_loop), startup)
+    }
+
+    /// Creates render system which renders frames into offscreen images
+    /// without any window or surface (headless mode).
+    ///
+    /// Images have window size of the config (or [`DEFAULT_OFFSCREEN_SIZE`]),
+    /// and the last rendered frame could be read by [`Renderer::read_pixels`].
+    ///
+    pub(crate) fn headless(
+        config: &Config,
+        startup: &Startup,
+    ) -> Result<Self, RendererCreationError> {
+        Self::create::<()>(config, None, startup)
+    }
+
+    fn create<T>(
+        config: &Config,
+        event_loop: Option<&EventLoop<T>>,
+        startup: &Startup,
+    ) -> Result<Self, RendererCreationError>
     where
         T: 'static,
     {
+        // Instance & window, device, swapchain, frame passes, object and UI pipelines.
+        const STEPS: usize = 6;
+        let step = |done| startup.step(GRAPHICS_PHASE, done, STEPS);
+        step(0)?;
+
         let instance = utils::create_instance(config)?;
         log::info!(
             "max version of Vulkan instance is {}",
@@ -81,24 +252,67 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
             .enable_validation()
             .then(|| {
                 use super::debug_callback::create_debug_callback as new;
-                let debug_callback = new(&instance, MessageSeverity::all(), MessageType::all())?;
+                let level = config.validation_level();
+                let filter = config.validation_filter().clone();
+                let debug_callback = new(&instance, level, filter)?;
                 log::info!("debug callback was attached to the instance");
                 Result::<_, RendererCreationError>::Ok(debug_callback)
             })
             .transpose()?;
 
-        let surface = WindowBuilder::new()
-            .with_title(config.name())
-            .with_min_inner_size(LogicalSize::new(250, 100))
-            .with_visible(false)
-            .build_vk_surface(event_loop, instance.clone())?;
-        log::info!("window & surface initialized successfully");
+        let surface = event_loop
+            .map(|event_loop| {
+                let mut window_builder = WindowBuilder::new()
+                    .with_title(config.title())
+                    .with_resizable(config.resizable())
+                    .with_window_icon(config.icon().map(WindowIcon::icon))
+                    .with_visible(false);
+                window_builder = match config.min_window_size() {
+                    Some(size) => window_builder
+                        .with_min_inner_size(PhysicalSize::new(size.width, size.height)),
+                    None => window_builder.with_min_inner_size(LogicalSize::new(250, 100)),
+                };
+                if let Some(size) = config.max_window_size() {
+                    window_builder = window_builder
+                        .with_max_inner_size(PhysicalSize::new(size.width, size.height));
+                }
+                if let Some(size) = config.window_size() {
+                    window_builder =
+                        window_builder.with_inner_size(PhysicalSize::new(size.width, size.height));
+                }
+                if let Some([x, y]) = config.window_position() {
+                    window_builder = window_builder.with_position(PhysicalPosition::new(x, y));
+                }
+                if config.maximized() {
+                    window_builder = window_builder.with_maximized(true);
+                }
+                if config.fullscreen() {
+                    let mode = config.fullscreen_mode();
+                    let fullscreen = monitor::primary_fullscreen(event_loop, mode);
+                    window_builder = window_builder.with_fullscreen(Some(fullscreen));
+                }
+                window_builder.build_vk_surface(event_loop, instance.clone())
+            })
+            .transpose()?;
+        match &surface {
+            Some(_) => log::info!("window & surface initialized successfully"),
+            None => log::info!("headless mode, frames are rendered into offscreen images"),
+        }
+        step(1)?;
 
         let physical_devices = PhysicalDevice::enumerate(&instance);
         log::info!("enumerated {} physical devices", physical_devices.len());
+        // Device which was chosen by the user is used only if it is suitable.
+        let physical_devices: Vec<_> = match config.device_index() {
+            Some(index) => PhysicalDevice::from_index(&instance, index)
+                .into_iter()
+                .collect(),
+            None => physical_devices.collect(),
+        };
 
+        // Swapchain is not needed if frames are not presented.
         let required_extensions = DeviceExtensions {
-            khr_swapchain: true,
+            khr_swapchain: surface.is_some(),
             ..DeviceExtensions::none()
         };
         let required_features = Features::none();
@@ -108,8 +322,8 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
             present_family,
             transfer_family,
         } = utils::suitable_physical_device(
-            physical_devices,
-            &surface,
+            physical_devices.into_iter(),
+            surface.as_ref(),
             &required_extensions,
             &required_features,
         )
@@ -121,7 +335,23 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
             physical_device.api_version(),
         );
 
-        let (device, mut queues) = {
+        // Optional features are enabled only if supported by physical device.
+        let statistics_features = if config.pipeline_statistics() {
+            pipeline_stats::features(physical_device).unwrap_or_else(|| {
+                log::info!("pipeline statistics are not supported by the device");
+                Features::none()
+            })
+        } else {
+            Features::none()
+        };
+        let enabled_features = Features {
+            sampler_anisotropy: physical_device.supported_features().sampler_anisotropy,
+            multi_draw_indirect: physical_device.supported_features().multi_draw_indirect,
+            texture_compression_bc: physical_device.supported_features().texture_compression_bc,
+            ..required_features
+        }
+        .union(&statistics_features);
+        let (device, queues) = {
             let priorities = 1.0;
             let unique_queue_families = {
                 let unique_queue_families: HashSet<_> = [
@@ -144,163 +374,1544 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
                 .union(&required_extensions);
             Device::new(
                 physical_device,
-                &required_features,
+                &enabled_features,
                 &required_extensions,
                 unique_queue_families,
             )?
         };
-        let graphics_queue = queues.next().unwrap();
-        let present_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-        let transfer_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-
-        let (swapchain, swapchain_images) = {
-            let capabilities = surface.capabilities(physical_device)?;
-            let (format, color_space) = utils::suitable_image_format(&capabilities);
-            let present_mode = capabilities
-                .present_modes
+        // Queues are created in order of unique families, not in order of their roles.
+        let queues: Vec<_> = queues.collect();
+        let queue_of = |family: QueueFamily| {
+            queues
                 .iter()
-                .find(|&mode| mode == PresentMode::Mailbox)
-                .unwrap_or(PresentMode::Fifo);
-            let dimensions = if let Some(current_extent) = capabilities.current_extent {
-                current_extent
-            } else {
-                let window_size = surface.window().inner_size();
-                let min_width = capabilities.min_image_extent[0];
-                let max_width = capabilities.max_image_extent[0];
-                let min_height = capabilities.min_image_extent[1];
-                let max_height = capabilities.max_image_extent[1];
-                [
-                    window_size.width.clamp(min_width, max_width),
-                    window_size.height.clamp(min_height, max_height),
-                ]
-            };
-            let image_count = {
-                let image_count = capabilities.min_image_count + 1;
-                if let Some(max_image_count) = capabilities.max_image_count {
-                    image_count.max(max_image_count)
-                } else {
-                    image_count
+                .find(|queue| queue.family().id() == family.id())
+                .cloned()
+        };
+        let graphics_queue = queue_of(graphics_family).unwrap();
+        let present_queue = present_family
+            .and_then(queue_of)
+            .unwrap_or_else(|| graphics_queue.clone());
+        let transfer_queue = transfer_family
+            .and_then(queue_of)
+            .unwrap_or_else(|| graphics_queue.clone());
+        if transfer_queue.family().id() != graphics_queue.family().id() {
+            log::info!(
+                "uploads use separate transfer queue family {}",
+                transfer_queue.family().id(),
+            );
+        }
+        step(2)?;
+
+        let (output, pre_transform, frame_readback) = match surface {
+            Some(surface) => {
+                let capabilities = surface.capabilities(physical_device)?;
+                // Content is pre-rotated by renderer instead of the compositor.
+                let pre_transform = PreTransform::from(capabilities.current_transform);
+                let (format, color_space) = utils::suitable_image_format(&capabilities);
+                let preferred = config.present_mode();
+                let present_mode =
+                    preferred.negotiate(|mode| capabilities.present_modes.supports(mode.into()));
+                if present_mode != preferred {
+                    log::info!(
+                        "present mode {:?} is not supported, {:?} is used instead",
+                        preferred,
+                        present_mode,
+                    );
                 }
-            };
-            let sharing_mode = present_family
-                .as_ref()
-                .map(|present_family| {
-                    (present_family.id() != graphics_family.id()).then(|| {
-                        let queues = [&graphics_queue, &present_queue];
-                        SharingMode::from(&queues[..])
+                let window_size = surface.window().inner_size().into();
+                let dimensions =
+                    self::swapchain_dimensions(&capabilities, window_size, pre_transform);
+                // One more image than frames in flight is needed, so acquisition does not wait
+                // for the image which is presented.
+                let image_count = {
+                    let frames_in_flight = config.frames_in_flight() as u32;
+                    let image_count = (capabilities.min_image_count + 1).max(frames_in_flight + 1);
+                    if let Some(max_image_count) = capabilities.max_image_count {
+                        image_count.min(max_image_count)
+                    } else {
+                        image_count
+                    }
+                };
+                let sharing_mode = present_family
+                    .as_ref()
+                    .map(|present_family| {
+                        (present_family.id() != graphics_family.id()).then(|| {
+                            let queues = [&graphics_queue, &present_queue];
+                            SharingMode::from(&queues[..])
+                        })
                     })
-                })
-                .flatten()
-                .unwrap_or_else(|| SharingMode::from(&graphics_queue));
-            Swapchain::start(device.clone(), surface.clone())
-                .format(format)
-                .color_space(color_space)
-                .present_mode(present_mode)
-                .dimensions(dimensions)
-                .num_images(image_count)
-                .transform(capabilities.current_transform)
-                .sharing_mode(sharing_mode)
-                .usage(ImageUsage::color_attachment())
-                .build()?
+                    .flatten()
+                    .unwrap_or_else(|| SharingMode::from(&graphics_queue));
+                // Frames are read back only if the surface allows to copy from its images.
+                let frame_readback = capabilities.supported_usage_flags.transfer_source;
+                let usage = ImageUsage {
+                    transfer_source: frame_readback,
+                    ..ImageUsage::color_attachment()
+                };
+                let (swapchain, swapchain_images) =
+                    Swapchain::start(device.clone(), surface.clone())
+                        .format(format)
+                        .color_space(color_space)
+                        .present_mode(present_mode.into())
+                        .dimensions(dimensions)
+                        .num_images(image_count)
+                        .transform(capabilities.current_transform)
+                        .sharing_mode(sharing_mode)
+                        .usage(usage)
+                        .build()?;
+                let output = FrameOutput::Window {
+                    swapchain_images,
+                    swapchain,
+                    surface,
+                };
+                (output, pre_transform, frame_readback)
+            }
+            None => {
+                let size = config.window_size().unwrap_or(DEFAULT_OFFSCREEN_SIZE);
+                // Images are not held by presentation engine, so one image per frame is enough.
+                let offscreen =
+                    OffscreenImages::new(device.clone(), size.into(), config.frames_in_flight())?;
+                (
+                    FrameOutput::Offscreen(offscreen),
+                    PreTransform::IDENTITY,
+                    true,
+                )
+            }
         };
+        step(3)?;
 
-        let uniform_buffers = swapchain_images
-            .iter()
-            .map(|_| {
-                DeviceLocalBuffer::new(
-                    device.clone(),
-                    BufferUsage::uniform_buffer_transfer_destination(),
-                    iter::once(transfer_queue.family()),
-                )
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        // Host visible buffers of the renderer are placed into shared blocks of memory.
+        let allocator = MemoryAllocator::new(device.clone());
+        let uniform_buffers = vec![UniformRing::new(&allocator, output.image_count())?];
 
-        let frame_system = FrameSystem::new(graphics_queue.clone(), swapchain.format())?;
+        let mut sampler_cache = SamplerCache::new(device.clone());
+        // All pipelines are created with the same cache which is saved on shutdown.
+        let pipeline_cache =
+            PersistentPipelineCache::new(device.clone(), config.pipeline_cache_path())?;
+        let requested_aa_mode = config.antialiasing().normalized();
+        let aa_mode = requested_aa_mode.clamped(physical_device);
+        if aa_mode != requested_aa_mode {
+            log::warn!(
+                "{:?} is not supported by the device, {:?} is used instead",
+                requested_aa_mode,
+                aa_mode,
+            );
+        }
+        let mut frame_system = FrameSystem::new(
+            graphics_queue.clone(),
+            output.format(),
+            aa_mode,
+            config.render_scale(),
+            sampler_cache.get(SamplerDesc::linear())?,
+            pipeline_cache.cache().clone(),
+            config.pass_ops(),
+        )?;
+        frame_system.set_clear_color(config.clear_color());
+        frame_system.set_depth_compare(config.depth_compare());
+        step(4)?;
+
+        let object_draw_system = ObjectDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.object_subpass(),
+            pipeline_cache.cache().clone(),
+            config.depth_compare(),
+        )?;
+        step(5)?;
 
-        let object_draw_system =
-            ObjectDrawSystem::new(graphics_queue.clone(), frame_system.object_subpass())?;
+        let ui_draw_system = UiDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.ui_subpass(),
+            output.format(),
+            sampler_cache.get(SamplerDesc::linear())?,
+            pipeline_cache.cache().clone(),
+        )?;
+        let sprite_draw_system = SpriteDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.object_subpass(),
+            pipeline_cache.cache().clone(),
+        )?;
+        step(6)?;
 
-        let ui_draw_system = UiDrawSystem::new(graphics_queue.clone(), frame_system.ui_subpass())?;
+        // Statistics of the frame are read while next frames are in flight.
+        let pipeline_stats = pipeline_stats::enabled(&device)
+            .then(|| {
+                let frames = output.image_count() as u32 + 1;
+                PipelineStatisticsQueries::new(device.clone(), frames)
+                    .map_err(|error| {
+                        log::warn!("failed to create pipeline statistics queries: {}", error)
+                    })
+                    .ok()
+            })
+            .flatten();
+        let gpu_profiler = (config.gpu_profiling() && gpu_profiler::supported(&graphics_queue))
+            .then(|| {
+                let frames = output.image_count() as u32 + 1;
+                GpuProfiler::new(&graphics_queue, frames)
+                    .map_err(|error| log::warn!("failed to create timestamp queries: {}", error))
+                    .ok()
+            })
+            .flatten();
 
+        // Descriptor pools of the frame are reset when its swapchain image is acquired again.
+        let descriptor_allocator = DescriptorAllocator::new(
+            device.clone(),
+            output.image_count(),
+            DescriptorPoolSizes::default(),
+        );
+
+        // Transient buffers of the frame are reset when its swapchain image is acquired again.
+        let transient_pools = (0..output.image_count())
+            .map(|_| TransientBufferPool::new(allocator.clone()))
+            .collect();
+
+        let readback = Readback::new(allocator.clone());
+        let staging = StagingRing::new(
+            allocator.clone(),
+            transfer_queue.clone(),
+            graphics_queue.clone(),
+        )?;
+        // Immediate draws and text are clipped by the same stack of rectangles.
+        let clip_stack = ClipStack::default();
         let previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
-        Ok(Self {
+        let frame_sync = FrameSync::new(config.frames_in_flight(), output.image_count());
+        // Only one frame is submitted at a time, because the next one waits for its fence.
+        let submit_thread = config
+            .submit_thread()
+            .then(|| {
+                SubmitThread::spawn("submit", 1, |future: Box<dyn GpuFuture + Send + Sync>| {
+                    future.then_signal_fence_and_flush()
+                })
+            })
+            .transpose()
+            .map_err(RendererCreationError::ThreadSpawn)?;
+        let mut renderer = Self {
             instance,
             debug_callback,
-            surface,
             device,
             graphics_queue,
             present_queue,
             transfer_queue,
-            swapchain,
-            swapchain_images,
+            output,
+            viewports: ViewportList::default(),
+            culling: CullingReport::default(),
+            draws: DrawQueue::with_clip_stack(clip_stack.clone()),
+            allocator,
             uniform_buffers,
+            uniforms: UniformRegistry::default(),
+            sampler_cache,
+            pipeline_cache,
+            descriptor_allocator,
+            transient_pools,
+            staging,
+            texture_mipmaps: config.texture_mipmaps(),
+            hooks: HookList::default(),
             frame_system,
             object_draw_system,
+            indirect_buffers: SlotMap::with_key(),
+            indirect_draw: None,
+            meshes: SlotMap::with_key(),
             ui_draw_system,
+            sprite_draw_system,
+            sprites: SpriteBatch::default(),
+            particle_system: None,
+            particles_updated_at: Instant::now(),
+            pipeline_stats,
+            gpu_profiler,
+            text_brush: TextBrush::with_clip_stack(clip_stack.clone()),
+            clip_stack,
+            text_texture: None,
+            streaming: None,
             camera_ubo: CameraUBO::default(),
+            active_camera: ActiveCamera::default(),
+            secondary_windows: SecondaryMap::new(),
+            stats: FrameStats::default(),
+            capture_path: None,
+            readback,
+            frame_readback,
             previous_frame_end,
+            frame_sync,
+            submit_thread,
+            pending_frame: None,
             recreate_swapchain: false,
-        })
+            swapchain_suboptimal: false,
+            timeouts: TimeoutTracker::new(config.timeout_policy()),
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
+            resize_requested_at: None,
+            pause: PauseControl::default(),
+            pre_transform,
+        };
+        renderer.update_resources();
+        Ok(renderer)
+    }
+
+    /// Underlying window of render system, if it is not headless.
+    pub(crate) fn window(&self) -> Option<&Window> {
+        self.output.window()
+    }
+
+    /// Returns `true` if frames are rendered into offscreen images instead of the window.
+    pub fn is_headless(&self) -> bool {
+        matches!(self.output, FrameOutput::Offscreen(_))
+    }
+
+    /// Size of images which frames are rendered into (in pixels).
+    pub(crate) fn frame_size(&self) -> Size {
+        self.output.dimensions().into()
+    }
+
+    /// Statistics of frames rendered by this system.
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// Time spent by the device on render passes of the latest frame which timestamps were read.
+    ///
+    /// Timestamps are read a few frames later without waiting for the device.
+    /// Returns `None` if GPU profiling was not enabled in the configuration
+    /// (see [`Config::with_gpu_profiling`](crate::config::Config::with_gpu_profiling))
+    /// or is not supported by the graphics queue.
+    ///
+    pub fn gpu_timings(&self) -> Option<GpuTimings> {
+        self.stats.gpu_timings
+    }
+
+    /// Statistics of device memory which is allocated by the renderer for its host visible
+    /// buffers (uniform buffers, transient blocks and readback rings).
+    ///
+    /// Live allocations which keep growing point to leaks,
+    /// while high fragmentation means that freed ranges are too small to be reused.
+    ///
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.allocator.stats()
+    }
+
+    /// Creates device local buffer with given usage and uploads data into it
+    /// through the transfer queue.
+    ///
+    /// Upload is not waited for by the host: the next rendered frame waits for it
+    /// on the device, so the buffer could be used by commands of that frame
+    /// (for example, by [render hooks](RenderHook)).
+    ///
+    pub fn upload_buffer<T>(
+        &mut self,
+        data: &[T],
+        usage: BufferUsage,
+    ) -> Result<Arc<DeviceBuffer<T>>, UploadError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        self.staging().upload_buffer(data, usage)
+    }
+
+    /// Creates sampled image with given texels through the transfer queue.
+    ///
+    /// Texels are tightly packed rows of the image of given format.
+    /// Like [`Renderer::upload_buffer`], upload is waited for by the next rendered frame.
+    ///
+    pub fn upload_image(
+        &mut self,
+        texels: &[u8],
+        dimensions: [u32; 2],
+        format: Format,
+    ) -> Result<Arc<ImmutableImage>, UploadError> {
+        self.staging()
+            .upload_image(texels, dimensions, format, false)
+    }
+
+    /// Creates sampled image with given texels and the full chain of its mip levels.
+    ///
+    /// Mip levels are generated on the graphics queue by successive blits with linear filtering,
+    /// unless the device could not blit images of given format: then only texels are uploaded.
+    /// Like [`Renderer::upload_image`], upload is waited for by the next rendered frame.
+    ///
+    pub fn upload_image_with_mipmaps(
+        &mut self,
+        texels: &[u8],
+        dimensions: [u32; 2],
+        format: Format,
+    ) -> Result<Arc<ImmutableImage>, UploadError> {
+        self.staging()
+            .upload_image(texels, dimensions, format, true)
+    }
+
+    /// Creates index buffer with indices of mesh with given count of vertices,
+    /// which are stored as 16-bit ones if all vertices could be indexed by them.
+    ///
+    /// Buffer is uploaded through the graphics queue, so it could be bound
+    /// by commands of [render hooks](RenderHook) for indexed draws.
+    ///
+    pub fn create_index_buffer(
+        &self,
+        indices: &[u32],
+        vertex_count: usize,
+    ) -> Result<IndexBuffer, IndexBufferError> {
+        IndexBuffer::new(self.graphics_queue.clone(), indices, vertex_count)
+    }
+
+    /// Creates mesh with given vertices and indices of triangles,
+    /// which are uploaded into device local memory through the transfer queue.
+    ///
+    /// Like [`Renderer::upload_buffer`], upload is waited for by the next rendered frame,
+    /// so the mesh could be drawn by commands of [render hooks](RenderHook) of that frame.
+    ///
+    pub fn create_mesh(
+        &mut self,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Result<MeshId, MeshCreationError> {
+        let mesh = Mesh::new(self.staging(), vertices, indices)?;
+        let id = self.meshes.insert(mesh);
+        self.update_resources();
+        Ok(id)
+    }
+
+    /// Mesh which was created by [`Renderer::create_mesh`], if it was not removed.
+    pub fn mesh(&self, id: MeshId) -> Option<&Mesh> {
+        self.meshes.get(id)
+    }
+
+    /// Removes the mesh.
+    ///
+    /// Its buffers are destroyed after frames which use them are finished.
+    ///
+    pub fn remove_mesh(&mut self, id: MeshId) -> bool {
+        let removed = self.meshes.remove(id).is_some();
+        self.update_resources();
+        removed
+    }
+
+    /// Creates command buffer which is recorded by the game and executed on the graphics queue.
+    ///
+    /// Commands are not synchronized with frames of the renderer,
+    /// so the game must wait for its own submissions.
+    ///
+    pub fn command_buffer(&self) -> CommandBuffer {
+        CommandBuffer::new(self.graphics_queue.clone())
+    }
+
+    /// Dumps everything which is recorded for the next rendered frame
+    /// (render passes with their attachments, pipeline binds and draws) into the file as JSON.
+    ///
+    /// Frames which are skipped (e.g. while paused) are not captured.
+    /// Failure to write the file is only logged.
+    ///
+    pub fn dump_next_frame(&mut self, path: &Path) {
+        self.capture_path = Some(path.to_path_buf());
+    }
+
+    /// Handle which injects faults into this system.
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injector(&self) -> super::timeout::FaultInjector {
+        self.faults.clone()
+    }
+
+    /// Allocator of descriptor sets for the frame which is being rendered.
+    pub fn descriptor_allocator(&mut self) -> &mut DescriptorAllocator {
+        &mut self.descriptor_allocator
+    }
+
+    /// Pipeline cache which all pipelines of the renderer are created with.
+    ///
+    /// Hooks could create their pipelines with it too, so they are also persisted across runs.
+    ///
+    pub fn pipeline_cache(&self) -> &Arc<PipelineCache> {
+        self.pipeline_cache.cache()
+    }
+
+    /// Saves pipeline cache into the file which was set by [`Config::with_pipeline_cache_path`]
+    /// (or the default one), if the cache is persisted at all.
+    pub fn save_pipeline_cache(&self) -> Result<(), PipelineCacheError> {
+        self.pipeline_cache.save()
+    }
+
+    /// Adds user-defined render pass which will be run on each frame at given stage.
+    ///
+    /// Hooks of the same stage are run in registration order.
+    ///
+    pub fn add_hook(&mut self, stage: HookStage, hook: Box<dyn RenderHook>) {
+        self.hooks.push(stage, hook)
     }
 
-    /// Underlying window of render system.
-    pub fn window(&self) -> &Window {
-        self.surface.window()
+    /// Notifies render system that the underlying window was resized.
+    ///
+    /// Swapchain will be recreated only when window size stays unchanged
+    /// for some time, so frames in between are rendered with the existing swapchain.
+    pub fn request_resize(&mut self) {
+        self.resize_requested_at = Some(Instant::now());
     }
 
     /// Resize the underlying window and update Vulkan objects.
+    ///
+    /// Surface capabilities are queried again because orientation of the display
+    /// could be changed (which changes transform of the surface).
+    ///
+    /// Only the swapchain is recreated, and framebuffers of its old images are dropped.
+    /// Device, command pools, descriptor sets and pipelines stay alive:
+    /// viewports and scissors of pipelines are dynamic state which is set on each draw.
+    /// Depth buffer and offscreen targets are recreated by the next frame
+    /// only if dimensions of the scene were changed.
+    ///
     pub fn resize(&mut self) -> Result<(), ResizeError> {
-        let dimensions = self.window().inner_size().into();
+        self.resize_requested_at = None;
+        let (swapchain_images, swapchain, surface) = match &mut self.output {
+            FrameOutput::Window {
+                swapchain_images,
+                swapchain,
+                surface,
+            } => (swapchain_images, swapchain, surface),
+            // Offscreen images keep the size of the config.
+            FrameOutput::Offscreen(_) => return Ok(()),
+        };
+        let capabilities = surface.capabilities(self.device.physical_device())?;
+        let pre_transform = PreTransform::from(capabilities.current_transform);
+        let window_size = surface.window().inner_size().into();
+        let dimensions = self::swapchain_dimensions(&capabilities, window_size, pre_transform);
+
+        // Nothing to do if window size was restored while resizing,
+        // unless the swapchain does not match the surface anymore.
+        let unchanged = swapchain.dimensions() == dimensions && self.pre_transform == pre_transform;
+        if !self.recreate_swapchain && !self.swapchain_suboptimal && unchanged {
+            return Ok(());
+        }
 
-        let (swapchain, swapchain_images) =
-            self.swapchain.recreate().dimensions(dimensions).build()?;
-        self.swapchain = swapchain;
-        self.swapchain_images = swapchain_images;
+        let (new_swapchain, new_images) = swapchain
+            .recreate()
+            .dimensions(dimensions)
+            .transform(capabilities.current_transform)
+            .build()?;
+        *swapchain = new_swapchain;
+        *swapchain_images = new_images;
+        self.frame_system.clear_framebuffers();
+        self.pre_transform = pre_transform;
+        self.stats.swapchain_recreations += 1;
+        self.hooks.for_each(|hook| hook.on_resize(dimensions));
 
         self.recreate_swapchain = false;
+        self.swapchain_suboptimal = false;
+        Ok(())
+    }
+
+    /// Reads pixels of the last rendered frame in headless mode.
+    ///
+    /// Copy of the frame image is submitted after the frame and waited on,
+    /// so this call blocks until the frame is finished. Pixels are in sRGB color space.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the renderer is not headless or no frame was rendered yet.
+    ///
+    pub fn read_pixels(&mut self) -> Result<RgbaImage, ReadPixelsError> {
+        let image: FrameImage = match &self.output {
+            FrameOutput::Offscreen(offscreen) => offscreen
+                .last_rendered()
+                .ok_or(ReadPixelsError::NoFrame)?
+                .clone(),
+            FrameOutput::Window { .. } => return Err(ReadPixelsError::NotHeadless),
+        };
+        let [width, height] = self.output.dimensions();
+        let ticket = self.readback.frame(ImageSubresource::default());
+        let (command_buffer, batch) = self
+            .readback
+            .record(&self.graphics_queue, Some(image))?
+            .expect("readback of the frame was queued");
+        self.finish_submission_logged();
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let future = previous_frame_end
+            .then_execute(self.graphics_queue.clone(), command_buffer)
+            .map_err(ReadPixelsError::from)
+            .and_then(|future| Ok(future.then_signal_fence_and_flush()?));
+        let future = match future {
+            Ok(future) => Arc::new(future),
+            Err(error) => {
+                self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+                return Err(error);
+            }
+        };
+        batch.submitted(future.clone());
+        self.previous_frame_end = Some(Box::new(future));
+
+        let pixels = ticket.wait(self.timeouts.policy().fence_timeout)?;
+        let image = RgbaImage::from_raw(width, height, pixels)
+            .expect("offscreen image must consist of RGBA pixels");
+        Ok(image)
+    }
+
+    /// Captures the next rendered frame as RGBA image (for example, for bug reports).
+    ///
+    /// Presented image could not be read, so the frame is copied after it is drawn,
+    /// right before it is presented (see [`ReadbackImage::Frame`]).
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the frame image could not be used as a transfer source.
+    ///
+    pub fn capture_frame(&mut self) -> Result<Screenshot, ReadbackError> {
+        let ticket = self.readback_image(ReadbackImage::Frame, ImageSubresource::default())?;
+        Ok(Screenshot::new(ticket))
+    }
+
+    /// Returns `true` if swapchain should be recreated before rendering of the next frame.
+    ///
+    /// Resize is not debounced while paused, because no frames are rendered in between.
+    ///
+    fn resize_needed(&self) -> bool {
+        let debounced = self
+            .resize_requested_at
+            .map(|requested_at| self.pause.is_paused() || requested_at.elapsed() >= RESIZE_DEBOUNCE)
+            .unwrap_or(false);
+        self.recreate_swapchain || debounced
+    }
+
+    /// Pauses (or resumes) rendering of frames.
+    ///
+    /// While paused, no swapchain image is acquired, submitted or presented,
+    /// so the last presented image stays on screen (see [`PauseControl`]).
+    ///
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pause.set_paused(paused)
+    }
+
+    /// Returns `true` if rendering of frames is paused.
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_paused()
+    }
+
+    /// Queues exactly one frame to be rendered while paused.
+    pub fn step_frame(&mut self) {
+        self.pause.step_frame()
+    }
+
+    /// Returns handle which pauses rendering or steps frames one at a time.
+    pub fn pause_control(&self) -> PauseControl {
+        self.pause.clone()
+    }
+
+    /// Changes antialiasing mode of the scene.
+    ///
+    /// Device is waited to become idle, then only render passes, offscreen targets
+    /// and graphics pipelines which depend on the mode are recreated.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the mode is not supported by the device
+    /// (the current mode is kept then) or recreation failed.
+    ///
+    pub fn set_antialiasing(&mut self, mode: AaMode) -> Result<(), AntialiasingError> {
+        let mode = mode.normalized();
+        if mode == self.frame_system.aa_mode() {
+            return Ok(());
+        }
+        if !mode.is_supported(self.device.physical_device()) {
+            return Err(AntialiasingError::NotSupported(mode));
+        }
+
+        self.recreate_frame_system(
+            mode,
+            self.frame_system.render_scale(),
+            self.frame_system.ops(),
+        )?;
+        log::info!("antialiasing mode was changed to {:?}", mode);
+        Ok(())
+    }
+
+    /// Changes scale of the scene resolution relative to the window.
+    ///
+    /// Scale is clamped into range from [`MIN_RENDER_SCALE`] to [`MAX_RENDER_SCALE`].
+    /// Frame system is recreated in the same way as when antialiasing mode is changed
+    /// (see [`Renderer::set_antialiasing`]), so it should not be called on each frame.
+    ///
+    pub fn set_render_scale(&mut self, scale: f32) -> Result<(), AntialiasingError> {
+        let scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+        if scale == self.frame_system.render_scale() {
+            return Ok(());
+        }
+
+        self.recreate_frame_system(self.frame_system.aa_mode(), scale, self.frame_system.ops())?;
+        log::info!("render scale was changed to {}", scale);
+        Ok(())
+    }
+
+    /// Scale of the scene resolution relative to the window.
+    pub fn render_scale(&self) -> f32 {
+        self.frame_system.render_scale()
+    }
+
+    /// Recreates frame system with given antialiasing mode, render scale
+    /// and operations of the scene attachments after the device becomes idle.
+    fn recreate_frame_system(
+        &mut self,
+        mode: AaMode,
+        render_scale: f32,
+        ops: PassOps,
+    ) -> Result<(), AntialiasingError> {
+        self.finish_submission_logged();
+        if let Some(future) = self.previous_frame_end.as_mut() {
+            future.cleanup_finished();
+        }
+        // Safety: no work is submitted to the device until this call returns.
+        unsafe { self.device.wait()? };
+
+        let mut frame_system = FrameSystem::new(
+            self.graphics_queue.clone(),
+            self.frame_system.final_output_format(),
+            mode,
+            render_scale,
+            self.sampler_cache.get(SamplerDesc::linear())?,
+            self.pipeline_cache.cache().clone(),
+            ops,
+        )?;
+        frame_system.set_clear_color(self.frame_system.clear_color());
+        frame_system.set_depth_compare(self.frame_system.depth_compare());
+        self.object_draw_system
+            .set_subpass(frame_system.object_subpass())?;
+        self.ui_draw_system.set_subpass(frame_system.ui_subpass())?;
+        self.sprite_draw_system
+            .set_subpass(frame_system.object_subpass())?;
+        // Particles recreate their pipeline on the next draw.
+        self.frame_system = frame_system;
         Ok(())
     }
 
-    pub fn set_camera_ubo(&mut self, ubo: CameraUBO) {
-        self.camera_ubo = ubo;
+    /// Antialiasing mode of the scene.
+    pub fn antialiasing(&self) -> AaMode {
+        self.frame_system.aa_mode()
+    }
+
+    /// Sets color which the scene is cleared with since the next frame.
+    pub fn set_clear_color(&mut self, color: impl Into<Color>) {
+        self.frame_system.set_clear_color(color.into())
+    }
+
+    /// Color which the scene is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.frame_system.clear_color()
+    }
+
+    /// Changes load and store operations of color and depth of the scene,
+    /// for example, to keep contents of the previous frame instead of clearing them.
+    ///
+    /// Frame system is recreated in the same way as when antialiasing mode is changed
+    /// (see [`Renderer::set_antialiasing`]), so it should not be called on each frame.
+    ///
+    pub fn set_pass_ops(&mut self, ops: PassOps) -> Result<(), AntialiasingError> {
+        if ops == self.frame_system.ops() {
+            return Ok(());
+        }
+
+        let mode = self.frame_system.aa_mode();
+        self.recreate_frame_system(mode, self.frame_system.render_scale(), ops)?;
+        log::info!(
+            "operations of the scene attachments were changed to {:?}",
+            ops
+        );
+        Ok(())
     }
 
-    /// Create command buffer for transfer operations which will be executed
-    /// before actual rendering.
-    fn transfer_cb(
+    /// Load and store operations of color and depth of the scene.
+    pub fn pass_ops(&self) -> PassOps {
+        self.frame_system.ops()
+    }
+
+    /// Sets operator which compares depth of the fragment with the depth buffer
+    /// since the next frame.
+    ///
+    /// Pipelines of objects and particles are recreated if the operator was changed.
+    ///
+    pub fn set_depth_compare(
+        &mut self,
+        compare_op: CompareOp,
+    ) -> Result<(), ObjectDrawSystemCreationError> {
+        self.object_draw_system.set_depth_compare(compare_op)?;
+        self.frame_system.set_depth_compare(compare_op);
+        if let Some(particle_system) = self.particle_system.as_mut() {
+            particle_system.set_depth_compare(compare_op);
+        }
+        Ok(())
+    }
+
+    /// Operator which compares depth of the fragment with the depth buffer.
+    pub fn depth_compare(&self) -> CompareOp {
+        self.frame_system.depth_compare()
+    }
+
+    /// Enables or disables generation of mip levels for textures of UI and sprites
+    /// which are registered since now.
+    pub fn set_texture_mipmaps(&mut self, enabled: bool) {
+        self.texture_mipmaps = enabled;
+    }
+
+    /// Checks if mip levels are generated for registered textures of UI and sprites.
+    pub fn texture_mipmaps(&self) -> bool {
+        self.texture_mipmaps
+    }
+
+    /// Sets count of frames which could be rendered concurrently since the next frame.
+    ///
+    /// Count is clamped to `1..=MAX_FRAMES_IN_FLIGHT`. More frames in flight
+    /// increase throughput, fewer frames decrease input latency.
+    /// Swapchain is not recreated, so count of its images could limit frames in flight too.
+    ///
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) {
+        self.frame_sync.set_frames_in_flight(frames_in_flight)
+    }
+
+    /// Count of frames which could be rendered concurrently.
+    pub fn frames_in_flight(&self) -> usize {
+        self.frame_sync.frames_in_flight()
+    }
+
+    /// Returns handle to the camera from which the scene is rendered into the whole window.
+    ///
+    /// Viewports are rendered from their own cameras, so the active camera
+    /// is used only if there are no viewports.
+    ///
+    pub fn active_camera(&self) -> ActiveCamera {
+        self.active_camera.clone()
+    }
+
+    /// Sets camera from which the scene is rendered into the whole window since the next frame.
+    pub fn set_active_camera(&mut self, camera: Camera) {
+        self.active_camera.set(camera)
+    }
+
+    /// Replaces viewports which are rendered on each frame.
+    ///
+    /// Scene is rendered once per viewport, while UI is rendered once over the whole window.
+    /// If there are no viewports, the scene is rendered into the whole window.
+    ///
+    pub fn set_viewports(&mut self, viewports: &[Viewport]) -> Result<(), ViewportError> {
+        self.viewports.set(viewports)
+    }
+
+    /// Returns handle to the list of viewports which are rendered on each frame.
+    pub fn viewports(&self) -> ViewportList {
+        self.viewports.clone()
+    }
+
+    /// Creates secondary window which shares the device with the main window.
+    pub(crate) fn create_window<T>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        id: WindowId,
+        desc: &WindowDesc,
+    ) -> Result<(), SecondaryWindowCreationError> {
+        let window = SecondaryWindow::new(
+            target,
+            self.instance.clone(),
+            &self.graphics_queue,
+            &self.present_queue,
+            desc,
+        )?;
+        self.secondary_windows.insert(id, window);
+        Ok(())
+    }
+
+    /// Destroys secondary window after its last frame is finished.
+    pub(crate) fn close_window(&mut self, id: WindowId) -> bool {
+        self.secondary_windows.remove(id).is_some()
+    }
+
+    /// Finds secondary window by identifier of its underlying window.
+    pub(crate) fn find_window(&self, window_id: WinitWindowId) -> Option<WindowId> {
+        self.secondary_windows
+            .iter()
+            .find(|(_, window)| window.window().id() == window_id)
+            .map(|(id, _)| id)
+    }
+
+    /// Requests to recreate swapchain of the secondary window before its next frame.
+    pub(crate) fn request_window_resize(&mut self, id: WindowId) {
+        if let Some(window) = self.secondary_windows.get_mut(id) {
+            window.request_resize()
+        }
+    }
+
+    /// Presents next frame of each secondary window.
+    ///
+    /// Error of one window does not prevent other windows from being rendered:
+    /// the first error is returned after all windows were rendered.
+    ///
+    pub(crate) fn render_windows(&mut self) -> Result<(), SecondaryWindowRenderError> {
+        let (graphics_queue, present_queue) = (&self.graphics_queue, &self.present_queue);
+        self.secondary_windows
+            .values_mut()
+            .map(|window| window.render(graphics_queue, present_queue))
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Returns handle through which results of frustum culling are reported
+    /// into statistics of rendered frames.
+    pub fn culling_report(&self) -> CullingReport {
+        self.culling.clone()
+    }
+
+    /// Submits draw to be drawn in the next frame after draws of the scene.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned if the mesh could not be drawn or the draw has non-finite values.
+    ///
+    pub fn submit(&self, command: DrawCommand) -> Result<(), DrawSubmitError> {
+        self.draws.submit(command)
+    }
+
+    /// Submits draw of many instances of the mesh to be drawn in the next frame
+    /// with one command after other draws.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned if the mesh could not be drawn or any instance has non-finite transform.
+    ///
+    pub fn draw_instanced(
         &self,
-        image_index: usize,
-    ) -> Result<PrimaryAutoCommandBuffer, TransferCommandBufferCreationError> {
-        let uniform_buffer = self.uniform_buffers[image_index].clone();
+        mesh: impl Into<MeshSource>,
+        instances: &[InstanceData],
+    ) -> Result<(), DrawSubmitError> {
+        self.draws.submit_instanced(mesh, instances)
+    }
 
-        let mut builder = AutoCommandBufferBuilder::primary(
+    /// Returns handle which submits draws to be drawn in the next frame.
+    pub fn draw_queue(&self) -> DrawQueue {
+        self.draws.clone()
+    }
+
+    /// Returns handle to the stack of rectangles which clip submitted draws and queued text.
+    pub fn clip_stack(&self) -> ClipStack {
+        self.clip_stack.clone()
+    }
+
+    /// Scissor of the draw with given clip rectangle in the region of swapchain image.
+    ///
+    /// Returns `None` if the draw is clipped entirely.
+    ///
+    fn draw_scissor(&self, region: &Region, clip: Option<ClipRect>) -> Option<ClipRect> {
+        let region_rect = ClipRect::from_scissor(&region.scissor());
+        let clip = match clip {
+            Some(clip) => clip,
+            None => return Some(region_rect),
+        };
+        let dimensions = self.output.dimensions();
+        let window_size = self.pre_transform.swap_dimensions(dimensions);
+        let scene_size = self
+            .pre_transform
+            .swap_dimensions(self.frame_system.scene_dimensions(dimensions));
+        let clip = clip
+            .to_image(window_size, scene_size, self.pre_transform)
+            .intersect(region_rect);
+        (!clip.is_empty()).then_some(clip)
+    }
+
+    /// Size of the window in which the scene is rendered (in pixels of the scene).
+    fn window_size(&self) -> [f32; 2] {
+        // Scene could be rendered in another resolution than the window.
+        let dimensions = self
+            .frame_system
+            .scene_dimensions(self.output.dimensions())
+            .map(|dimension| dimension as f32);
+        self.pre_transform.swap_dimensions(dimensions)
+    }
+
+    /// Updates camera UBO of the whole window from the active camera.
+    fn update_camera_ubo(&mut self) {
+        let [width, height] = self.window_size();
+        let camera = self.active_camera.get();
+        let projection = camera.projection(width / height);
+        self.camera_ubo = CameraUBO::new(projection, self.camera_ubo.model, camera.view());
+    }
+
+    /// Regions of swapchain image and camera UBOs of current viewports.
+    fn viewport_regions(&self) -> Vec<(Region, CameraUBO)> {
+        let window_size = self.window_size();
+        let viewports = self.viewports.get();
+        if viewports.is_empty() {
+            let region = Region::new(viewport::Rect::FULL, 0, 1, window_size, self.pre_transform);
+            return vec![(region, self.camera_ubo)];
+        }
+
+        let count = viewports.len();
+        viewports
+            .into_iter()
+            .enumerate()
+            .map(|(index, viewport)| {
+                let region =
+                    Region::new(viewport.rect, index, count, window_size, self.pre_transform);
+                let camera = viewport.camera;
+                let projection = camera.projection(region.aspect_ratio);
+                let ubo = CameraUBO::new(projection, self.camera_ubo.model, camera.view());
+                (region, ubo)
+            })
+            .collect()
+    }
+
+    /// Allocates uniform buffers for each swapchain image until there are enough for viewports.
+    fn reserve_uniform_buffers(&mut self, count: usize) -> Result<(), RenderError> {
+        while self.uniform_buffers.len() < count {
+            let buffers = UniformRing::new(&self.allocator, self.output.image_count())?;
+            self.uniform_buffers.push(buffers);
+        }
+        Ok(())
+    }
+
+    /// Creates uniform buffer with given initial value for shaders of render hooks
+    /// (see [`UniformBuffer`]).
+    pub fn create_uniform_buffer<T>(
+        &mut self,
+        value: T,
+    ) -> Result<UniformBuffer<T>, MappedBufferCreationError>
+    where
+        T: Pod + Send + Sync,
+    {
+        let uniform = UniformBuffer::new(&self.allocator, self.output.image_count(), value)?;
+        self.uniforms.register(&uniform);
+        Ok(uniform)
+    }
+
+    /// Count of uniform buffers of the game which are still alive.
+    pub fn uniform_buffer_count(&self) -> usize {
+        self.uniforms.len()
+    }
+
+    /// Returns `true` if all commands of indirect draw are drawn with one draw call.
+    ///
+    /// Otherwise (if `multi_draw_indirect` feature is not supported by the device)
+    /// commands are drawn one by one.
+    ///
+    pub fn supports_multi_draw_indirect(&self) -> bool {
+        self.device.enabled_features().multi_draw_indirect
+    }
+
+    /// Creates new buffer with draw commands of the list for indirect drawing.
+    pub fn create_indirect_buffer(
+        &mut self,
+        list: &IndirectDrawList,
+    ) -> Result<IndirectBufferId, IndirectBufferCreationError> {
+        let buffer = IndirectBuffer::new(self.device.clone(), list)?;
+        let id = self.indirect_buffers.insert(buffer);
+        self.update_resources();
+        Ok(id)
+    }
+
+    /// Removes buffer for indirect drawing.
+    ///
+    /// Indirect draw which uses this buffer is reset, so game objects are drawn directly.
+    /// Buffer is destroyed after frames which use it are finished.
+    ///
+    pub fn remove_indirect_buffer(&mut self, id: IndirectBufferId) -> bool {
+        if self.indirect_draw.map(|draw| draw.buffer) == Some(id) {
+            self.indirect_draw = None;
+        }
+        let removed = self.indirect_buffers.remove(id).is_some();
+        self.update_resources();
+        removed
+    }
+
+    /// Requests readback of the range of the indirect buffer (in bytes).
+    ///
+    /// Buffer is copied at the end of the next rendered frame.
+    ///
+    pub fn readback_buffer(
+        &mut self,
+        id: IndirectBufferId,
+        range: Range<DeviceSize>,
+    ) -> Result<ReadbackTicket, ReadbackError> {
+        let buffer = self
+            .indirect_buffers
+            .get(id)
+            .ok_or(ReadbackError::BufferNotFound)?
+            .buffer()
+            .clone();
+        self.readback.buffer(buffer, range)
+    }
+
+    /// Requests readback of the subresource of the image.
+    ///
+    /// Image is copied at the end of the next rendered frame.
+    ///
+    pub fn readback_image(
+        &mut self,
+        image: ReadbackImage,
+        subresource: ImageSubresource,
+    ) -> Result<ReadbackTicket, ReadbackError> {
+        match image {
+            ReadbackImage::Frame => {
+                if !self.frame_readback {
+                    return Err(ReadbackError::UnsupportedImage);
+                }
+                // Swapchain images have one mip level and one array layer.
+                if subresource != ImageSubresource::default() {
+                    return Err(ReadbackError::InvalidSubresource(subresource));
+                }
+                Ok(self.readback.frame(subresource))
+            }
+        }
+    }
+
+    /// Sets indirect draw which is used to draw game objects on each frame.
+    ///
+    /// If indirect draw is `None`, game objects are drawn directly.
+    ///
+    pub fn set_indirect_draw(
+        &mut self,
+        draw: Option<IndirectDraw>,
+    ) -> Result<(), IndirectDrawError> {
+        if let Some(draw) = &draw {
+            let buffer = self
+                .indirect_buffers
+                .get(draw.buffer)
+                .ok_or(IndirectDrawError::BufferNotFound)?;
+            draw.commands(buffer.size())?;
+        }
+        self.indirect_draw = draw;
+        Ok(())
+    }
+
+    /// Enables GPU particle system which will be simulated and drawn on each frame.
+    ///
+    /// Previous particle system (if any) is replaced by the new one.
+    /// Returns handle which could queue particle spawns.
+    ///
+    pub fn enable_particles(
+        &mut self,
+        max_particles: u32,
+        params: ParticleParams,
+    ) -> Result<ParticleEmitter, ParticleSystemCreationError> {
+        let mut particle_system = ParticleSystem::new(
             self.device.clone(),
-            self.transfer_queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
+            max_particles,
+            params,
+            self.pipeline_cache.cache().clone(),
         )?;
-        builder.update_buffer(uniform_buffer, Box::new(self.camera_ubo))?;
-        Ok(builder.build()?)
+        particle_system.set_depth_compare(self.frame_system.depth_compare());
+        let emitter = particle_system.emitter();
+        self.particle_system = Some(particle_system);
+        self.particles_updated_at = Instant::now();
+        Ok(emitter)
+    }
+
+    /// Disables GPU particle system, if any.
+    pub fn disable_particles(&mut self) {
+        self.particle_system = None;
+    }
+
+    /// Returns GPU particle system, if it was enabled.
+    pub fn particle_system(&mut self) -> Option<&mut ParticleSystem> {
+        self.particle_system.as_mut()
+    }
+
+    /// Enables streaming of textures which will be uploaded at the beginning of each frame.
+    ///
+    /// Previous streaming manager (if any) is replaced by the new one,
+    /// and its resident textures are evicted.
+    ///
+    pub fn enable_streaming(
+        &mut self,
+        config: StreamingConfig,
+        tasks: TaskPool,
+    ) -> StreamingManager {
+        self.disable_streaming();
+        let streaming = StreamingManager::new(config, tasks);
+        self.streaming = Some(streaming.clone());
+        streaming
     }
 
+    /// Disables streaming of textures (if any), evicting all resident textures.
+    pub fn disable_streaming(&mut self) {
+        if let Some(streaming) = self.streaming.take() {
+            for texture_id in streaming.release_all() {
+                self.ui_draw_system.unregister_texture(texture_id);
+            }
+            self.update_resources();
+        }
+    }
+
+    /// Evicts and uploads streamed textures planned for the current frame.
+    ///
+    /// Textures are uploaded through the transfer queue.
+    fn stream_textures(&mut self) {
+        let streaming = match &self.streaming {
+            Some(streaming) => streaming.clone(),
+            None => return,
+        };
+        let plan = streaming.plan();
+        // Evicted textures are destroyed after frames which use them are finished.
+        for texture_id in plan.evictions {
+            self.ui_draw_system.unregister_texture(texture_id);
+        }
+        for upload in plan.uploads {
+            let sampler = SamplerDesc::linear();
+            match self.register_ui_texture(upload.texture, sampler) {
+                Ok(texture_id) => {
+                    if let Some(texture_id) = streaming.finish_upload(upload.id, texture_id) {
+                        self.ui_draw_system.unregister_texture(texture_id);
+                    }
+                }
+                Err(error) => {
+                    log::warn!("failed to upload streamed texture: {}", error);
+                    streaming.fail_upload(upload.id);
+                }
+            }
+        }
+        self.update_resources();
+        self.stats.streaming = streaming.stats();
+    }
+
+    /// Writes camera UBOs of viewports into uniform buffers of the swapchain image.
+    ///
+    /// Previous frame rendered into this image must be finished.
+    ///
+    fn write_ubos(
+        &self,
+        image_index: usize,
+        ubos: impl IntoIterator<Item = CameraUBO>,
+    ) -> Result<(), MappedBufferWriteError> {
+        for (uniform_buffers, ubo) in self.uniform_buffers.iter().zip(ubos) {
+            // Rotate content if the surface is not in its native orientation.
+            let ubo = CameraUBO {
+                projection: self.pre_transform.matrix() * ubo.projection,
+                ..ubo
+            };
+            uniform_buffers.write(image_index, ubo)?;
+        }
+        Ok(())
+    }
+
+    /// Returns handle which queues text to be drawn in the next frame.
+    pub fn text_brush(&self) -> TextBrush {
+        self.text_brush.clone()
+    }
+
+    /// Builds meshes of queued text, uploading glyph atlas if it was changed.
+    fn text_meshes(&mut self, scale_factor: f32) -> Result<Vec<ClippedMesh>, ImageRegisterError> {
+        let flush = match self.text_brush.flush(scale_factor) {
+            Some(flush) => flush,
+            None => return Ok(Vec::new()),
+        };
+        if let Some((size, coverage)) = flush.atlas {
+            // Coverage is stored as premultiplied white color, as in `egui` font texture.
+            let pixels = coverage.into_iter().flat_map(|c| [c, c, c, c]).collect();
+            let texture_id = self.register_ui_pixels(
+                pixels,
+                [size, size],
+                Format::R8G8B8A8_UNORM,
+                SamplerDesc::linear(),
+                false,
+            )?;
+            // Previous atlas is released after frames which use it are finished.
+            if let Some(texture_id) = self.text_texture.replace(texture_id) {
+                self.ui_draw_system.unregister_texture(texture_id);
+                self.update_resources();
+            }
+        }
+        let texture_id = match self.text_texture {
+            Some(texture_id) => texture_id,
+            None => return Ok(Vec::new()),
+        };
+        let meshes = flush.meshes.into_iter().map(|(clip, mut mesh)| {
+            mesh.texture_id = texture_id;
+            // Clip rectangles of UI meshes are in logical pixels.
+            let rect = clip.map_or(Rect::EVERYTHING, |clip| {
+                let [min, max] = [clip.min, clip.max]
+                    .map(|[x, y]| pos2(x as f32 / scale_factor, y as f32 / scale_factor));
+                Rect::from_min_max(min, max)
+            });
+            ClippedMesh(rect, mesh)
+        });
+        Ok(meshes.collect())
+    }
+
+    /// Registers an image to be drawn in UI with given sampler.
     pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
+        sampler: SamplerDesc,
     ) -> Result<TextureId, ImageRegisterError> {
+        let pixels = image.pixels().flat_map(|p| p.0).collect();
+        let dimensions = [image.width(), image.height()];
+        // todo: remove hardcoded format
+        let format = Format::R8G8B8A8_SRGB;
+        let mipmaps = self.texture_mipmaps;
+        self.register_ui_pixels(pixels, dimensions, format, sampler, mipmaps)
+    }
+
+    /// Registers decoded texture to be drawn in UI with given sampler.
+    ///
+    /// Compressed texture is uploaded as is if the device could sample its format,
+    /// otherwise its decompressed fallback (if any) is uploaded instead.
+    /// Mip levels of RGBA textures are generated if [enabled](Renderer::set_texture_mipmaps),
+    /// while compressed textures only have mip levels stored in them.
+    ///
+    /// # Errors
+    ///
+    /// [`ImageRegisterError::UnsupportedFormat`] is returned if the device could not sample
+    /// compressed texture and there is no fallback.
+    ///
+    pub fn register_ui_texture(
+        &mut self,
+        texture: TextureData,
+        sampler: SamplerDesc,
+    ) -> Result<TextureId, ImageRegisterError> {
+        let image = match texture {
+            TextureData::Rgba(image) => {
+                let dimensions = [image.width(), image.height()];
+                let (format, mipmaps) = (Format::R8G8B8A8_SRGB, self.texture_mipmaps);
+                let pixels = image.into_raw();
+                return self.register_ui_pixels(pixels, dimensions, format, sampler, mipmaps);
+            }
+            TextureData::Compressed(image) => image,
+        };
+        if !image.is_supported(&self.device) {
+            if let Some(fallback) = image.fallback() {
+                log::debug!(
+                    "{:?} is not supported by the device, fallback is used",
+                    image.format(),
+                );
+                return self.register_ui_image(fallback, sampler);
+            }
+        }
+        let texture = texture::upload_compressed(&self.transfer_queue, &image)?;
+        let image_view = ImageView::new(texture)?;
+        let sampler = self.sampler_cache.get(sampler)?;
+        let texture_id = self.ui_draw_system.register_texture(image_view, sampler)?;
+        self.update_resources();
+        Ok(texture_id)
+    }
+
+    /// Unregisters an image which was registered to be drawn in UI.
+    ///
+    /// Image is destroyed after frames which use it are finished.
+    pub fn unregister_ui_image(&mut self, texture_id: TextureId) {
+        self.ui_draw_system.unregister_texture(texture_id);
+        self.update_resources();
+    }
+
+    /// Registers RGBA pixels of given format to be drawn in UI with given sampler,
+    /// generating their mip levels if `mipmaps` is `true`.
+    fn register_ui_pixels(
+        &mut self,
+        pixels: Vec<u8>,
+        dimensions: [u32; 2],
+        format: Format,
+        sampler: SamplerDesc,
+        mipmaps: bool,
+    ) -> Result<TextureId, ImageRegisterError> {
+        let image = self
+            .staging()
+            .upload_image(&pixels, dimensions, format, mipmaps)?;
+        let image_view = ImageView::new(image)?;
+        let sampler = self.sampler_cache.get(sampler)?;
+        let texture_id = self.ui_draw_system.register_texture(image_view, sampler)?;
+        self.update_resources();
+        Ok(texture_id)
+    }
+
+    /// Handle which queues sprites to be drawn over the scene in the next frame.
+    pub fn sprite_batch(&self) -> SpriteBatch {
+        self.sprites.clone()
+    }
+
+    /// Registers an image to be drawn by sprites with given sampler.
+    pub fn register_sprite_image(
+        &mut self,
+        image: &RgbaImage,
+        sampler: SamplerDesc,
+    ) -> Result<SpriteTextureId, ImageRegisterError> {
         let pixels: Vec<_> = image.pixels().flat_map(|p| p.0).collect();
-        let (image, future) = ImmutableImage::from_iter(
-            pixels,
-            ImageDimensions::Dim2d {
-                width: image.width(),
-                height: image.height(),
-                array_layers: 1,
+        let dimensions = [image.width(), image.height()];
+        let (format, mipmaps) = (Format::R8G8B8A8_SRGB, self.texture_mipmaps);
+        let image = self
+            .staging()
+            .upload_image(&pixels, dimensions, format, mipmaps)?;
+        let image_view = ImageView::new(image)?;
+        let sampler = self.sampler_cache.get(sampler)?;
+        let texture_id = self
+            .sprite_draw_system
+            .register_texture(image_view, sampler)?;
+        self.update_resources();
+        Ok(texture_id)
+    }
+
+    /// Unregisters an image which was registered to be drawn by sprites.
+    ///
+    /// Image is destroyed after frames which use it are finished.
+    /// Sprites of unregistered image are skipped.
+    ///
+    pub fn unregister_sprite_image(&mut self, texture_id: SpriteTextureId) {
+        self.sprite_draw_system.unregister_texture(texture_id);
+        self.update_resources();
+    }
+
+    /// Updates list of live graphics objects in statistics.
+    fn update_resources(&mut self) {
+        let textures = self
+            .ui_draw_system
+            .texture_keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect();
+        let sprite_textures = self
+            .sprite_draw_system
+            .texture_keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect();
+        let samplers = self
+            .sampler_cache
+            .descs()
+            .map(|desc| format!("{:?}", desc))
+            .collect();
+        self.stats.resources = vec![
+            ResourceList {
+                name: "UI texture",
+                keys: textures,
+            },
+            ResourceList {
+                name: "sprite texture",
+                keys: sprite_textures,
             },
-            MipmapsCount::One,
-            Format::R8G8B8A8_SRGB, // todo: remove hardcoded format
-            self.transfer_queue.clone(),
+            ResourceList {
+                name: "sampler",
+                keys: samplers,
+            },
+            ResourceList {
+                name: "indirect buffer",
+                keys: self.indirect_buffer_keys(),
+            },
+            ResourceList {
+                name: "mesh",
+                keys: self.mesh_keys(),
+            },
+        ];
+    }
+
+    /// Keys of live buffers for indirect drawing.
+    fn indirect_buffer_keys(&self) -> Vec<String> {
+        self.indirect_buffers
+            .keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect()
+    }
+
+    /// Keys of live meshes.
+    fn mesh_keys(&self) -> Vec<String> {
+        self.meshes
+            .keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect()
+    }
+
+    /// Waits until the device finishes all work and destroys graphics objects of this system.
+    ///
+    /// Returns graphics objects of each type which were still alive
+    /// (were registered by the user, but were not unregistered).
+    ///
+    pub(crate) fn shutdown(&mut self) -> Result<Vec<ResourceList>, ShutdownError> {
+        self.finish_submission_logged();
+        if let Some(mut future) = self.previous_frame_end.take() {
+            future.cleanup_finished();
+        }
+        // Safety: no work is submitted to the device until this call returns.
+        unsafe { self.device.wait()? };
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+
+        // Text atlas and streamed textures are owned by the renderer, so they are not leaks.
+        if let Some(texture_id) = self.text_texture.take() {
+            self.ui_draw_system.unregister_texture(texture_id);
+        }
+        self.disable_streaming();
+        let textures = self
+            .ui_draw_system
+            .texture_keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect();
+        let sprite_textures = self
+            .sprite_draw_system
+            .texture_keys()
+            .map(|key| format!("{:?}", key.data()))
+            .collect();
+        let leaks = vec![
+            ResourceList {
+                name: "UI texture",
+                keys: textures,
+            },
+            ResourceList {
+                name: "sprite texture",
+                keys: sprite_textures,
+            },
+            ResourceList {
+                name: "indirect buffer",
+                keys: self.indirect_buffer_keys(),
+            },
+            ResourceList {
+                name: "mesh",
+                keys: self.mesh_keys(),
+            },
+        ];
+
+        // Descriptor sets of textures refer to samplers, so they are destroyed first.
+        self.ui_draw_system.clear_textures();
+        self.sprite_draw_system.clear_textures();
+        self.particle_system = None;
+        self.hooks.clear();
+        self.secondary_windows.clear();
+        self.pipeline_stats = None;
+        self.gpu_profiler = None;
+        self.indirect_draw = None;
+        self.indirect_buffers.clear();
+        self.meshes.clear();
+        self.descriptor_allocator.clear();
+        self.sampler_cache.clear();
+        self.update_resources();
+        Ok(leaks)
+    }
+
+    /// Records commands of hooks of the stage outside of any render pass.
+    ///
+    /// Returns `None` if there are no hooks to run at this stage.
+    ///
+    fn record_hooks(
+        &mut self,
+        stage: HookStage,
+        frame: &FrameContext,
+    ) -> Result<Option<PrimaryAutoCommandBuffer>, RenderError> {
+        if !self.hooks.has_active(stage) {
+            return Ok(None);
+        }
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
         )?;
-        future.flush()?;
-        let image_view = ImageView::new(image)?;
-        Ok(self.ui_draw_system.register_texture(image_view)?)
+        self.hooks.record(stage, |hook| {
+            hook.record(&mut HookCommands::Primary(&mut builder), frame)
+        });
+        Ok(Some(builder.build()?))
+    }
+
+    /// Skips the frame after the timeout, recreating swapchain if timeouts repeat.
+    fn skip_frame(&mut self, waited_for: &str) -> Result<(), RenderError> {
+        self.stats.frames_skipped += 1;
+        match self.timeouts.timeout() {
+            TimeoutAction::Skip => {
+                log::warn!("timed out {}, frame is skipped", waited_for);
+                Ok(())
+            }
+            TimeoutAction::Recreate => {
+                log::warn!("timed out {} repeatedly, recreating swapchain", waited_for);
+                self.recreate_swapchain = true;
+                Ok(())
+            }
+            TimeoutAction::Fail => Err(RenderError::SwapchainStalled(self.timeouts.recreations())),
+        }
+    }
+
+    /// Returns `true` if acquisition of swapchain image must time out because of injected fault.
+    #[cfg(feature = "fault-injection")]
+    fn acquire_fault(&self) -> bool {
+        self.faults.take_acquire_timeout()
+    }
+
+    #[cfg(not(feature = "fault-injection"))]
+    fn acquire_fault(&self) -> bool {
+        false
     }
 
     /// Render new frame into the underlying window.
@@ -308,51 +1919,334 @@ pub fn render(
         &mut self,
         mut ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
     ) -> Result<(), RenderError> {
+        let frame_start = Instant::now();
+        self.finish_submission()?;
+        // Submitted draws are consumed even if the frame is not rendered.
+        let draws = self.draws.begin_frame();
+        let (sprites, sprite_camera) = self.sprites.take();
         self.previous_frame_end.as_mut().unwrap().cleanup_finished();
-        if self.recreate_swapchain {
+        // Swapchain recreated while paused is rendered into immediately.
+        let resized = self.resize_needed();
+        if resized {
             self.resize()?;
         }
 
-        let (image_index, suboptimal, acquire_future) =
-            match swapchain::acquire_next_image(self.swapchain.clone(), None) {
-                Ok(r) => r,
-                Err(AcquireError::OutOfDate) => {
-                    self.recreate_swapchain = true;
-                    return Ok(());
+        self.stream_textures();
+        if let Some(pipeline_stats) = &mut self.pipeline_stats {
+            self.stats.pipeline_stats = pipeline_stats.poll();
+        }
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            self.stats.gpu_timings = gpu_profiler.poll();
+        }
+        let scale_factor = self
+            .window()
+            .map_or(1.0, |window| window.scale_factor() as f32);
+        if let Some((meshes, _)) = ui.as_mut() {
+            // Text is drawn under the UI.
+            let text_meshes = self.text_meshes(scale_factor)?;
+            meshes.splice(0..0, text_meshes);
+        }
+        // Streamed textures and queued text are still consumed while paused.
+        if !self.pause.should_render(resized) {
+            // Particles are not simulated for the time of the pause.
+            self.particles_updated_at = Instant::now();
+            return Ok(());
+        }
+
+        // Count of frames which are rendered concurrently is limited.
+        if let Some(fence) = self.frame_sync.frame_fence() {
+            match fence.wait(Some(self.timeouts.policy().fence_timeout)) {
+                Ok(()) => (),
+                Err(FlushError::Timeout) => return self.skip_frame("waiting for frames in flight"),
+                Err(err) => return Err(RenderError::FrameWait(err)),
+            }
+        }
+
+        let fault = self.acquire_fault();
+        let timeout = self.timeouts.policy().acquire_timeout;
+        let acquired = match &mut self.output {
+            FrameOutput::Window { .. } if fault => Err(AcquireError::Timeout),
+            FrameOutput::Window { swapchain, .. } => {
+                swapchain::acquire_next_image(swapchain.clone(), Some(timeout)).map(
+                    |(image_index, suboptimal, future)| {
+                        let future: Box<dyn GpuFuture + Send + Sync> = Box::new(future);
+                        (image_index, suboptimal, future)
+                    },
+                )
+            }
+            // Offscreen images are always available, so frames are limited by fences only.
+            FrameOutput::Offscreen(offscreen) => {
+                let future: Box<dyn GpuFuture + Send + Sync> =
+                    Box::new(sync::now(self.device.clone()));
+                Ok((offscreen.acquire(), false, future))
+            }
+        };
+        let (image_index, suboptimal, acquire_future) = match acquired {
+            Ok(r) => r,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                return Ok(());
+            }
+            Err(AcquireError::Timeout) => return self.skip_frame("acquiring swapchain image"),
+            Err(err) => return Err(RenderError::AcquireNextImage(err)),
+        };
+        // Suboptimal swapchain is still usable, so recreate it after debounce.
+        if suboptimal {
+            self.swapchain_suboptimal = true;
+            if self.resize_requested_at.is_none() {
+                self.request_resize();
+            }
+        }
+
+        // Buffers of the image are written by the host, so the previous frame
+        // which used them must be finished.
+        if let Some(fence) = self.frame_sync.image_fence(image_index) {
+            match fence.wait(Some(self.timeouts.policy().fence_timeout)) {
+                Ok(()) => (),
+                Err(FlushError::Timeout) => {
+                    // Image is already acquired, so the next submission waits for it instead.
+                    let previous_frame_end = self.previous_frame_end.take().unwrap();
+                    let future = previous_frame_end.join(acquire_future);
+                    self.previous_frame_end = Some(Box::new(future));
+                    return self.skip_frame("waiting for the previous frame");
                 }
-                Err(err) => return Err(RenderError::AcquireNextImage(err)),
-            };
-        self.recreate_swapchain = suboptimal;
+                Err(err) => return Err(RenderError::FrameWait(err)),
+            }
+        }
+        // Safety: the previous frame which used this image is finished.
+        unsafe { self.descriptor_allocator.begin_frame(image_index)? };
+        unsafe { self.transient_pools[image_index].reset() };
+        let instance_batches = ObjectDrawSystem::write_instances(
+            &mut self.transient_pools[image_index],
+            draws.instanced(),
+        )?;
+        let sprite_vertices =
+            SpriteDrawSystem::write_vertices(&mut self.transient_pools[image_index], &sprites)?;
+        let sprite_region = Region::new(
+            viewport::Rect::FULL,
+            0,
+            1,
+            self.window_size(),
+            self.pre_transform,
+        );
 
-        let transfer_command_buffer = self.transfer_cb(image_index)?;
+        self.update_camera_ubo();
+        let (regions, ubos): (Vec<_>, Vec<_>) = self.viewport_regions().into_iter().unzip();
+        let sorted_draws: Vec<_> = ubos.iter().map(|ubo| draws.sorted(ubo.view)).collect();
+        self.reserve_uniform_buffers(regions.len())?;
+        self.write_ubos(image_index, ubos)?;
+        self.uniforms.flush(image_index)?;
+        let mut capture = self.capture_path.is_some().then(|| {
+            let extent = self.output.dimensions();
+            let role = match self.output {
+                FrameOutput::Window { .. } => "SwapchainImage",
+                FrameOutput::Offscreen(_) => "OffscreenImage",
+            };
+            let final_image = capture::role_name(role, image_index);
+            let CameraUBO {
+                projection,
+                model,
+                view,
+            } = self.camera_ubo;
+            FrameCapture {
+                frame: self.stats.frames,
+                image_index,
+                extent,
+                camera: CameraCapture {
+                    projection,
+                    model,
+                    view,
+                },
+                passes: self.frame_system.capture_passes(final_image, extent),
+            }
+        });
+        let frame_context = FrameContext {
+            frame: self.stats.frames,
+            image_index,
+            extent: self.output.dimensions(),
+            format: self.output.format(),
+            projection: self.camera_ubo.projection,
+            view: self.camera_ubo.view,
+            subpass: self.frame_system.object_subpass(),
+            queue: self.graphics_queue.clone(),
+        };
+        let device = self.device.clone();
+        self.hooks
+            .prepare(|hook| hook.prepare(&device, &frame_context));
         let previous_frame_end = self.previous_frame_end.take().unwrap();
-        let before_future = previous_frame_end
-            .join(acquire_future)
-            .then_execute(self.transfer_queue.clone(), transfer_command_buffer)?
-            .then_signal_semaphore();
+        let mut before_future: Box<dyn GpuFuture + Send + Sync> =
+            Box::new(previous_frame_end.join(acquire_future));
+        // Commands of the frame may use data which is still uploaded by the transfer queue.
+        if let Some(uploads) = self.staging.take_pending() {
+            before_future = Box::new(before_future.join(uploads));
+        }
+        // Particles are simulated before they are drawn in the same frame.
+        if let Some(particle_system) = &mut self.particle_system {
+            let delta_time = self.particles_updated_at.elapsed().as_secs_f32();
+            self.particles_updated_at = Instant::now();
+            let queue = self.graphics_queue.clone();
+            let command_buffer = particle_system.update(queue.clone(), delta_time)?;
+            before_future = Box::new(
+                before_future
+                    .then_execute(queue, command_buffer)?
+                    .then_signal_semaphore(),
+            );
+        }
+        let stage = HookStage::BeforeMainPass;
+        if let Some(command_buffer) = self.record_hooks(stage, &frame_context)? {
+            before_future = Box::new(
+                before_future
+                    .then_execute(self.graphics_queue.clone(), command_buffer)?
+                    .then_signal_semaphore(),
+            );
+        }
+        let object_subpass = self.frame_system.object_subpass();
+        let indirect = self.indirect_draw.and_then(|draw| {
+            let buffer = self.indirect_buffers.get(draw.buffer)?;
+            Some((buffer, draw))
+        });
+        let multi_draw = self.supports_multi_draw_indirect();
+        let statistics_query = self
+            .pipeline_stats
+            .as_ref()
+            .and_then(PipelineStatisticsQueries::next_query);
+        let timestamp_query = self.gpu_profiler.as_ref().and_then(GpuProfiler::next_query);
+
+        // Draws which are clipped entirely in a viewport are skipped.
+        // Scissors are computed before the frame borrows the frame system.
+        let clipped_draws: Vec<(Vec<_>, Vec<_>)> = regions
+            .iter()
+            .zip(&sorted_draws)
+            .map(|(region, sorted_draws)| {
+                let draws = sorted_draws
+                    .iter()
+                    .filter_map(|&draw| {
+                        let scissor = self.draw_scissor(region, draw.clip)?;
+                        Some((draw, scissor))
+                    })
+                    .collect();
+                let batches = instance_batches
+                    .iter()
+                    .filter_map(|batch| {
+                        let scissor = self.draw_scissor(region, batch.clip)?;
+                        Some((batch, scissor))
+                    })
+                    .collect();
+                (draws, batches)
+            })
+            .collect();
 
-        let scale_factor = self.window().scale_factor() as f32;
+        // Sets must be written before they are bound by commands of the frame.
+        self.descriptor_allocator.flush_writes();
         let graphics_future = {
-            let mut frame = self
-                .frame_system
-                .frame(before_future, self.swapchain_images[image_index].clone())?;
+            let statistics_query = statistics_query.clone();
+            let timestamp_query = timestamp_query.clone();
+            let mut frame = match &self.output {
+                FrameOutput::Window {
+                    swapchain_images, ..
+                } => self.frame_system.frame(
+                    before_future,
+                    swapchain_images[image_index].clone(),
+                    statistics_query,
+                    timestamp_query,
+                )?,
+                FrameOutput::Offscreen(offscreen) => self.frame_system.frame(
+                    before_future,
+                    offscreen.images()[image_index].clone(),
+                    statistics_query,
+                    timestamp_query,
+                )?,
+            };
             let mut graphics_future = Box::new(sync::now(self.device.clone())) as Box<_>;
             while let Some(next_pass) = frame.next_pass()? {
                 match next_pass {
                     Pass::Deferred(mut draw_pass) => {
-                        let uniform_buffer = self.uniform_buffers[image_index].clone();
-                        let command_buffer = self
-                            .object_draw_system
-                            .draw(draw_pass.viewport_size(), uniform_buffer)?;
-                        draw_pass.execute(command_buffer)?;
+                        // Hooks are drawn once under the scene of all viewports.
+                        if self.hooks.has_active(HookStage::InsideMainPass) {
+                            let mut builder = pipeline_stats::secondary_graphics(
+                                self.device.clone(),
+                                self.graphics_queue.family(),
+                                object_subpass.clone(),
+                            )?;
+                            self.hooks.record(HookStage::InsideMainPass, |hook| {
+                                let mut commands = HookCommands::Secondary(&mut builder);
+                                hook.record(&mut commands, &frame_context)
+                            });
+                            draw_pass.execute(builder.build()?)?;
+                            if let Some(scene) =
+                                capture.as_mut().and_then(|c| c.subpass_mut("scene"))
+                            {
+                                scene.hook(format!("{:?}", HookStage::InsideMainPass));
+                            }
+                        }
+                        // Scene is drawn once for each viewport.
+                        let uniform_buffers = self
+                            .uniform_buffers
+                            .iter()
+                            .map(|buffers| buffers.buffer(image_index));
+                        let mut viewport_draws = Vec::with_capacity(regions.len());
+                        let viewports = regions.iter().zip(uniform_buffers).zip(&clipped_draws);
+                        for ((region, uniform_buffer), (clipped_draws, clipped_batches)) in
+                            viewports
+                        {
+                            let indirect = indirect.as_ref().map(|(buffer, draw)| (*buffer, draw));
+                            let command_buffer = self.object_draw_system.draw(
+                                region,
+                                uniform_buffer.clone(),
+                                indirect,
+                                multi_draw,
+                                clipped_draws,
+                                capture.as_mut().and_then(|c| c.subpass_mut("scene")),
+                            )?;
+                            draw_pass.execute(command_buffer)?;
+                            let mut draws = 1;
+                            if !clipped_batches.is_empty() {
+                                let command_buffer = self.object_draw_system.draw_instanced(
+                                    region,
+                                    uniform_buffer.clone(),
+                                    clipped_batches,
+                                    capture.as_mut().and_then(|c| c.subpass_mut("scene")),
+                                )?;
+                                draw_pass.execute(command_buffer)?;
+                                draws += 1;
+                            }
+                            if let Some(particle_system) = &mut self.particle_system {
+                                let command_buffer = particle_system.draw(
+                                    self.graphics_queue.clone(),
+                                    object_subpass.clone(),
+                                    region,
+                                    uniform_buffer.clone(),
+                                    capture.as_mut().and_then(|c| c.subpass_mut("scene")),
+                                )?;
+                                draw_pass.execute(command_buffer)?;
+                                draws += 1;
+                            }
+                            viewport_draws.push(draws);
+                        }
+                        self.stats.viewport_draws = viewport_draws;
+                        // Sprites are drawn once over the scene of all viewports.
+                        if let Some((vertices, batches)) = &sprite_vertices {
+                            let command_buffer = self.sprite_draw_system.draw(
+                                &sprite_region,
+                                self.pre_transform,
+                                sprite_camera,
+                                vertices,
+                                batches,
+                                capture.as_mut().and_then(|c| c.subpass_mut("scene")),
+                            )?;
+                            draw_pass.execute(command_buffer)?;
+                        }
                     }
                     Pass::UI(mut ui_pass) => {
                         if let Some((meshes, texture)) = ui.take() {
                             let command_buffer = self.ui_draw_system.draw(
+                                &mut self.transient_pools[image_index],
                                 ui_pass.viewport_size(),
+                                self.pre_transform,
                                 scale_factor,
                                 meshes,
                                 texture,
+                                capture.as_mut().and_then(|c| c.subpass_mut("ui")),
                             )?;
                             ui_pass.execute(command_buffer)?;
                         }
@@ -364,28 +2258,184 @@ pub fn render(
             }
             graphics_future
         };
+        self.stats.descriptors = self.descriptor_allocator.stats();
+        self.stats.transient = self.transient_pools[image_index].stats();
+        self.stats.memory = self.allocator.stats();
+        let stage = HookStage::AfterMainPass;
+        let graphics_future: Box<dyn GpuFuture + Send + Sync> =
+            match self.record_hooks(stage, &frame_context)? {
+                Some(command_buffer) => Box::new(
+                    graphics_future
+                        .then_execute(self.graphics_queue.clone(), command_buffer)?
+                        .then_signal_semaphore(),
+                ),
+                None => graphics_future,
+            };
 
-        let future = graphics_future
-            .then_swapchain_present(
-                self.present_queue.clone(),
-                self.swapchain.clone(),
-                image_index,
-            )
-            .then_signal_fence_and_flush();
+        // Readbacks are copied after the frame is drawn, right before it is presented.
+        let frame_image = self.output.frame_image(image_index);
+        let (graphics_future, readback) = match self
+            .readback
+            .record(&self.graphics_queue, Some(frame_image))?
+        {
+            Some((command_buffer, batch)) => {
+                let future: Box<dyn GpuFuture + Send + Sync> = Box::new(
+                    graphics_future
+                        .then_execute(self.graphics_queue.clone(), command_buffer)?
+                        .then_signal_semaphore(),
+                );
+                (future, Some(batch))
+            }
+            None => (graphics_future, None),
+        };
+
+        let graphics_future: Box<dyn GpuFuture + Send + Sync> = match &self.output {
+            FrameOutput::Window { swapchain, .. } => {
+                Box::new(graphics_future.then_swapchain_present(
+                    self.present_queue.clone(),
+                    swapchain.clone(),
+                    image_index,
+                ))
+            }
+            FrameOutput::Offscreen(_) => graphics_future,
+        };
+        if let (Some(capture), Some(path)) = (capture, self.capture_path.take()) {
+            match capture.save(&path) {
+                Ok(()) => log::info!("frame {} was captured into {:?}", capture.frame, path),
+                Err(err) => log::warn!("failed to write frame capture into {:?}: {}", path, err),
+            }
+        }
+        self.pause.frame_rendered();
+        self.stats.culling = self.culling.get();
+        self.stats.frames += 1;
+        self.stats.frame_time = frame_start.elapsed();
+        let frame = SubmittedFrame {
+            image_index,
+            readback,
+            statistics_query,
+            timestamp_query,
+        };
+        match &mut self.submit_thread {
+            Some(submit_thread) => {
+                if submit_thread.submit(graphics_future).is_err() {
+                    return self.submit_thread_stopped();
+                }
+                self.pending_frame = Some(frame);
+                Ok(())
+            }
+            None => {
+                let future = graphics_future.then_signal_fence_and_flush();
+                self.frame_submitted(frame, future)
+            }
+        }
+    }
+
+    /// Takes the result of the frame which was sent to the submit thread, waiting for it.
+    fn finish_submission(&mut self) -> Result<(), RenderError> {
+        let frame = match self.pending_frame.take() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        match self.submit_thread.as_mut().and_then(SubmitThread::wait) {
+            Some(future) => self.frame_submitted(frame, future),
+            None => self.submit_thread_stopped(),
+        }
+    }
+
+    /// Takes the result of the frame which was sent to the submit thread outside of rendering,
+    /// so the frame could be waited for by the host.
+    fn finish_submission_logged(&mut self) {
+        if let Err(error) = self.finish_submission() {
+            log::error!("failed to submit the previous frame: {}", error);
+        }
+    }
+
+    /// Staging ring which uploads are written into.
+    ///
+    /// Blocks are marked as waited for by the frame when it is submitted,
+    /// so the frame which is submitted by the submit thread must be finished first.
+    ///
+    fn staging(&mut self) -> &mut StagingRing {
+        self.finish_submission_logged();
+        &mut self.staging
+    }
+
+    fn submit_thread_stopped(&mut self) -> Result<(), RenderError> {
+        self.staging.abandoned();
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+        Err(RenderError::SubmitThreadStopped)
+    }
+
+    /// Tracks resources of the submitted frame until its fence is signaled.
+    fn frame_submitted(
+        &mut self,
+        frame: SubmittedFrame,
+        future: Result<FrameFence, FlushError>,
+    ) -> Result<(), RenderError> {
+        let SubmittedFrame {
+            image_index,
+            readback,
+            statistics_query,
+            timestamp_query,
+        } = frame;
         match future {
             Ok(future) => {
+                let future = Arc::new(future);
+                self.frame_sync.submitted(image_index, future.clone());
+                if let FrameOutput::Offscreen(offscreen) = &mut self.output {
+                    offscreen.rendered(image_index);
+                }
+                if let Some(readback) = readback {
+                    readback.submitted(future.clone());
+                }
+                self.staging.submitted(future.clone());
                 self.previous_frame_end = Some(Box::new(future));
+                self.timeouts.rendered();
+                if let (Some(pipeline_stats), Some(query)) =
+                    (&mut self.pipeline_stats, &statistics_query)
+                {
+                    pipeline_stats.submit(query);
+                }
+                if let (Some(gpu_profiler), Some(query)) =
+                    (&mut self.gpu_profiler, &timestamp_query)
+                {
+                    gpu_profiler.submit(query);
+                }
                 Ok(())
             }
             Err(FlushError::OutOfDate) => {
                 self.recreate_swapchain = true;
+                self.staging.abandoned();
                 self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
                 Ok(())
             }
             Err(err) => {
+                self.staging.abandoned();
                 self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
                 Err(RenderError::SubmitQueue(err))
             }
         }
     }
 }
+
+/// Dimensions of swapchain images for the surface with given capabilities.
+///
+/// Window size is swapped if the surface is rotated by 90 or 270 degrees,
+/// because swapchain images are in native orientation of the surface.
+///
+pub(super) fn swapchain_dimensions(
+    capabilities: &Capabilities,
+    window_size: [u32; 2],
+    pre_transform: PreTransform,
+) -> [u32; 2] {
+    if let Some(current_extent) = capabilities.current_extent {
+        return current_extent;
+    }
+    let [width, height] = pre_transform.swap_dimensions(window_size);
+    let [min_width, min_height] = capabilities.min_image_extent;
+    let [max_width, max_height] = capabilities.max_image_extent;
+    [
+        width.clamp(min_width, max_width),
+        height.clamp(min_height, max_height),
+    ]
+}
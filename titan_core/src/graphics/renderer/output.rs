@@ -0,0 +1,139 @@
+//! Images which frames of the renderer are rendered into.
+
+use std::sync::Arc;
+
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::image::{
+    AttachmentImage, ImageAccess, ImageCreationError, ImageUsage, SwapchainImage,
+};
+use vulkano::swapchain::{Surface, Swapchain};
+use winit::window::Window;
+
+use crate::graphics::readback::FrameImage;
+use crate::window::Size;
+
+/// Size of offscreen images if window size was not set by the config.
+pub const DEFAULT_OFFSCREEN_SIZE: Size = Size::new(800, 600);
+
+/// Format of offscreen images, so rendered frames could be read as RGBA pixels as is.
+pub const OFFSCREEN_FORMAT: Format = Format::R8G8B8A8_SRGB;
+
+/// Destination of rendered frames.
+pub(super) enum FrameOutput {
+    /// Frames are presented into the window through its swapchain.
+    Window {
+        swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
+        swapchain: Arc<Swapchain<Window>>,
+        surface: Arc<Surface<Window>>,
+    },
+    /// Frames are rendered into offscreen images (headless mode).
+    Offscreen(OffscreenImages),
+}
+
+impl FrameOutput {
+    /// Underlying window, if frames are presented into it.
+    pub fn window(&self) -> Option<&Window> {
+        match self {
+            FrameOutput::Window { surface, .. } => Some(surface.window()),
+            FrameOutput::Offscreen(_) => None,
+        }
+    }
+
+    /// Dimensions of images which frames are rendered into.
+    pub fn dimensions(&self) -> [u32; 2] {
+        match self {
+            FrameOutput::Window { swapchain, .. } => swapchain.dimensions(),
+            FrameOutput::Offscreen(offscreen) => offscreen.dimensions(),
+        }
+    }
+
+    /// Format of images which frames are rendered into.
+    pub fn format(&self) -> Format {
+        match self {
+            FrameOutput::Window { swapchain, .. } => swapchain.format(),
+            FrameOutput::Offscreen(_) => OFFSCREEN_FORMAT,
+        }
+    }
+
+    /// Count of images which frames are rendered into.
+    pub fn image_count(&self) -> usize {
+        match self {
+            FrameOutput::Window {
+                swapchain_images, ..
+            } => swapchain_images.len(),
+            FrameOutput::Offscreen(offscreen) => offscreen.images.len(),
+        }
+    }
+
+    /// Image with given index which the frame is rendered into.
+    pub fn frame_image(&self, image_index: usize) -> FrameImage {
+        match self {
+            FrameOutput::Window {
+                swapchain_images, ..
+            } => swapchain_images[image_index].clone(),
+            FrameOutput::Offscreen(offscreen) => offscreen.images[image_index].clone(),
+        }
+    }
+}
+
+/// Images which frames are rendered into one after another in headless mode.
+pub(super) struct OffscreenImages {
+    images: Vec<Arc<AttachmentImage>>,
+    next: usize,
+    /// Index of the image which the last frame was rendered into.
+    last_rendered: Option<usize>,
+}
+
+impl OffscreenImages {
+    /// Creates images of given dimensions which could be copied from.
+    pub fn new(
+        device: Arc<Device>,
+        dimensions: [u32; 2],
+        count: usize,
+    ) -> Result<Self, ImageCreationError> {
+        let usage = ImageUsage {
+            transfer_source: true,
+            ..ImageUsage::color_attachment()
+        };
+        let images = (0..count.max(1))
+            .map(|_| {
+                AttachmentImage::with_usage(device.clone(), dimensions, OFFSCREEN_FORMAT, usage)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            images,
+            next: 0,
+            last_rendered: None,
+        })
+    }
+
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.images[0].dimensions().width_height()
+    }
+
+    pub fn images(&self) -> &[Arc<AttachmentImage>] {
+        &self.images
+    }
+
+    /// Index of the image which the next frame is rendered into.
+    ///
+    /// Images are used in order, so the image is reused
+    /// only after frames were rendered into all other images.
+    ///
+    pub fn acquire(&mut self) -> usize {
+        let image_index = self.next;
+        self.next = (self.next + 1) % self.images.len();
+        image_index
+    }
+
+    /// Marks the image as the one which the last frame was rendered into.
+    pub fn rendered(&mut self, image_index: usize) {
+        self.last_rendered = Some(image_index);
+    }
+
+    /// Image which the last frame was rendered into, if any.
+    pub fn last_rendered(&self) -> Option<&Arc<AttachmentImage>> {
+        self.last_rendered.map(|index| &self.images[index])
+    }
+}
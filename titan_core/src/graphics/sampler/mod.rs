@@ -0,0 +1,383 @@
+//! Sampler utilities for graphics backend of game engine.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use vulkano::device::Device;
+use vulkano::pipeline::depth_stencil::CompareOp as Compare;
+use vulkano::sampler::{
+    BorderColor as VkBorderColor, Filter as VkFilter, MipmapMode as VkMipmapMode, Sampler,
+    SamplerAddressMode, SamplerCreationError,
+};
+
+mod tests;
+
+/// Filter which is used when texture is magnified or minified.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// Filter which is used between mipmap levels of texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MipmapMode {
+    Nearest,
+    Linear,
+}
+
+/// Behaviour of sampler for texture coordinates outside of `0.0..=1.0` range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AddressMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Coordinates outside of the texture will return [`SamplerDesc::border_color`].
+    ClampToBorder,
+}
+
+/// Color which is returned for coordinates outside of the texture
+/// with [`AddressMode::ClampToBorder`] address mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BorderColor {
+    TransparentBlack,
+    OpaqueBlack,
+    OpaqueWhite,
+}
+
+/// Comparison operator which is used for depth comparison sampling.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+/// Description of the texture sampler.
+///
+/// Identical descriptions share the same sampler (see [`SamplerCache`]).
+#[derive(Debug, Copy, Clone)]
+pub struct SamplerDesc {
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    pub mipmap_mode: MipmapMode,
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub address_mode_w: AddressMode,
+    /// Max anisotropy level, or `None` if anisotropic filtering is disabled.
+    pub anisotropy: Option<f32>,
+    /// Comparison operator for depth comparison sampling, if any.
+    pub compare_op: Option<CompareOp>,
+    pub border_color: BorderColor,
+    /// Bias which is added to the computed mip level (LOD) of the texture.
+    ///
+    /// Positive bias selects smaller mip levels, which are blurrier, but shimmer less.
+    /// Bias is clamped to `max_sampler_lod_bias` limit of the device.
+    ///
+    pub lod_bias: f32,
+    /// Minimal mip level which could be sampled.
+    pub min_lod: f32,
+    /// Maximal mip level which could be sampled, not less than [`SamplerDesc::min_lod`].
+    pub max_lod: f32,
+}
+
+impl SamplerDesc {
+    /// Sampler with linear filtering which clamps texture coordinates to edge.
+    pub const fn linear() -> Self {
+        Self {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_mode: MipmapMode::Linear,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            anisotropy: None,
+            compare_op: None,
+            border_color: BorderColor::TransparentBlack,
+            lod_bias: 0.0,
+            min_lod: 0.0,
+            max_lod: 1000.0,
+        }
+    }
+
+    /// Sampler with linear filtering which repeats texture.
+    pub const fn linear_repeat() -> Self {
+        Self {
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::Repeat,
+            address_mode_w: AddressMode::Repeat,
+            ..Self::linear()
+        }
+    }
+
+    /// Sampler with nearest-neighbor filtering (useful for pixel art).
+    pub const fn pixel_art() -> Self {
+        Self {
+            min_filter: Filter::Nearest,
+            mag_filter: Filter::Nearest,
+            mipmap_mode: MipmapMode::Nearest,
+            ..Self::linear()
+        }
+    }
+
+    /// Same description with address mode applied for all coordinates.
+    pub const fn with_address_mode(self, address_mode: AddressMode) -> Self {
+        Self {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            ..self
+        }
+    }
+
+    /// Same description with anisotropic filtering of given level.
+    pub const fn with_anisotropy(self, anisotropy: f32) -> Self {
+        Self {
+            anisotropy: Some(anisotropy),
+            ..self
+        }
+    }
+
+    /// Same description with given bias of mip level (LOD).
+    pub const fn with_lod_bias(self, lod_bias: f32) -> Self {
+        Self { lod_bias, ..self }
+    }
+
+    /// Same description which samples only mip levels in `min_lod..=max_lod` range.
+    ///
+    /// For example, `with_lod_range(0.0, 0.0)` samples only the top mip level.
+    ///
+    pub const fn with_lod_range(self, min_lod: f32, max_lod: f32) -> Self {
+        Self {
+            min_lod,
+            max_lod,
+            ..self
+        }
+    }
+
+    /// Bit representation of anisotropy used for hashing and comparison.
+    fn anisotropy_bits(&self) -> Option<u32> {
+        self.anisotropy.map(f32::to_bits)
+    }
+
+    /// Bit representation of bias and range of mip levels used for hashing and comparison.
+    fn lod_bits(&self) -> [u32; 3] {
+        [self.lod_bias, self.min_lod, self.max_lod].map(f32::to_bits)
+    }
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::linear()
+    }
+}
+
+impl PartialEq for SamplerDesc {
+    fn eq(&self, other: &Self) -> bool {
+        self.min_filter == other.min_filter
+            && self.mag_filter == other.mag_filter
+            && self.mipmap_mode == other.mipmap_mode
+            && self.address_mode_u == other.address_mode_u
+            && self.address_mode_v == other.address_mode_v
+            && self.address_mode_w == other.address_mode_w
+            && self.anisotropy_bits() == other.anisotropy_bits()
+            && self.compare_op == other.compare_op
+            && self.border_color == other.border_color
+            && self.lod_bits() == other.lod_bits()
+    }
+}
+
+impl Eq for SamplerDesc {}
+
+impl Hash for SamplerDesc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.min_filter.hash(state);
+        self.mag_filter.hash(state);
+        self.mipmap_mode.hash(state);
+        self.address_mode_u.hash(state);
+        self.address_mode_v.hash(state);
+        self.address_mode_w.hash(state);
+        self.anisotropy_bits().hash(state);
+        self.compare_op.hash(state);
+        self.border_color.hash(state);
+        self.lod_bits().hash(state);
+    }
+}
+
+impl From<Filter> for VkFilter {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Nearest => VkFilter::Nearest,
+            Filter::Linear => VkFilter::Linear,
+        }
+    }
+}
+
+impl From<MipmapMode> for VkMipmapMode {
+    fn from(mipmap_mode: MipmapMode) -> Self {
+        match mipmap_mode {
+            MipmapMode::Nearest => VkMipmapMode::Nearest,
+            MipmapMode::Linear => VkMipmapMode::Linear,
+        }
+    }
+}
+
+impl From<BorderColor> for VkBorderColor {
+    fn from(border_color: BorderColor) -> Self {
+        match border_color {
+            BorderColor::TransparentBlack => VkBorderColor::FloatTransparentBlack,
+            BorderColor::OpaqueBlack => VkBorderColor::FloatOpaqueBlack,
+            BorderColor::OpaqueWhite => VkBorderColor::FloatOpaqueWhite,
+        }
+    }
+}
+
+impl From<CompareOp> for Compare {
+    fn from(compare_op: CompareOp) -> Self {
+        match compare_op {
+            CompareOp::Never => Compare::Never,
+            CompareOp::Less => Compare::Less,
+            CompareOp::Equal => Compare::Equal,
+            CompareOp::LessOrEqual => Compare::LessOrEqual,
+            CompareOp::Greater => Compare::Greater,
+            CompareOp::NotEqual => Compare::NotEqual,
+            CompareOp::GreaterOrEqual => Compare::GreaterOrEqual,
+            CompareOp::Always => Compare::Always,
+        }
+    }
+}
+
+/// Converts address mode with border color into Vulkan address mode.
+fn address_mode(address_mode: AddressMode, border_color: BorderColor) -> SamplerAddressMode {
+    match address_mode {
+        AddressMode::Repeat => SamplerAddressMode::Repeat,
+        AddressMode::MirroredRepeat => SamplerAddressMode::MirroredRepeat,
+        AddressMode::ClampToEdge => SamplerAddressMode::ClampToEdge,
+        AddressMode::ClampToBorder => SamplerAddressMode::ClampToBorder(border_color.into()),
+    }
+}
+
+/// Clamps anisotropy to `1.0..=max_anisotropy` range,
+/// or disables it if anisotropic filtering is not enabled on the device.
+fn clamp_anisotropy(anisotropy: Option<f32>, enabled: bool, max_anisotropy: f32) -> Option<f32> {
+    if !enabled {
+        return None;
+    }
+    anisotropy.map(|anisotropy| anisotropy.clamp(1.0, max_anisotropy))
+}
+
+/// Cache of samplers which were created from [`SamplerDesc`].
+///
+/// Identical descriptions return the same sampler instead of creating duplicates
+/// (count of samplers is limited by `max_sampler_allocation_count` of the device).
+pub struct SamplerCache {
+    device: Arc<Device>,
+    samplers: HashMap<SamplerDesc, Arc<Sampler>>,
+}
+
+impl SamplerCache {
+    /// Creates an empty sampler cache for the device.
+    pub(crate) fn new(device: Arc<Device>) -> Self {
+        Self {
+            device,
+            samplers: HashMap::new(),
+        }
+    }
+
+    /// Max anisotropy which will actually be used for given description.
+    ///
+    /// Anisotropy is clamped to the limits of physical device
+    /// and disabled if `sampler_anisotropy` feature is not enabled.
+    pub fn effective_anisotropy(&self, desc: &SamplerDesc) -> Option<f32> {
+        let enabled = self.device.enabled_features().sampler_anisotropy;
+        let max_anisotropy = self
+            .device
+            .physical_device()
+            .properties()
+            .max_sampler_anisotropy;
+        self::clamp_anisotropy(desc.anisotropy, enabled, max_anisotropy)
+    }
+
+    /// Returns sampler for given description, creating it if needed.
+    pub(crate) fn get(&mut self, desc: SamplerDesc) -> Result<Arc<Sampler>, SamplerCreationError> {
+        if let Some(sampler) = self.samplers.get(&desc) {
+            return Ok(sampler.clone());
+        }
+
+        let device = self.device.clone();
+        let mag_filter = desc.mag_filter.into();
+        let min_filter = desc.min_filter.into();
+        let mipmap_mode = desc.mipmap_mode.into();
+        let address_u = self::address_mode(desc.address_mode_u, desc.border_color);
+        let address_v = self::address_mode(desc.address_mode_v, desc.border_color);
+        let address_w = self::address_mode(desc.address_mode_w, desc.border_color);
+        let max_anisotropy = self.effective_anisotropy(&desc).unwrap_or(1.0);
+        let max_lod_bias = self
+            .device
+            .physical_device()
+            .properties()
+            .max_sampler_lod_bias;
+        let mip_lod_bias = desc.lod_bias.clamp(-max_lod_bias, max_lod_bias);
+        let (min_lod, max_lod) = (desc.min_lod, desc.max_lod.max(desc.min_lod));
+
+        let sampler = match desc.compare_op {
+            Some(compare_op) => Sampler::compare(
+                device,
+                mag_filter,
+                min_filter,
+                mipmap_mode,
+                address_u,
+                address_v,
+                address_w,
+                mip_lod_bias,
+                max_anisotropy,
+                min_lod,
+                max_lod,
+                compare_op.into(),
+            )?,
+            None => Sampler::new(
+                device,
+                mag_filter,
+                min_filter,
+                mipmap_mode,
+                address_u,
+                address_v,
+                address_w,
+                mip_lod_bias,
+                max_anisotropy,
+                min_lod,
+                max_lod,
+            )?,
+        };
+        self.samplers.insert(desc, sampler.clone());
+        Ok(sampler)
+    }
+
+    /// Returns iterator over descriptions of samplers which were created by this cache.
+    pub fn descs(&self) -> impl Iterator<Item = &SamplerDesc> {
+        self.samplers.keys()
+    }
+
+    /// Count of samplers which were created by this cache.
+    pub fn len(&self) -> usize {
+        self.samplers.len()
+    }
+
+    /// Returns `true` if no samplers were created by this cache.
+    pub fn is_empty(&self) -> bool {
+        self.samplers.is_empty()
+    }
+
+    /// Removes all samplers from this cache.
+    ///
+    /// Samplers are destroyed when they are no longer in use.
+    pub(crate) fn clear(&mut self) {
+        self.samplers.clear()
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+use std::collections::HashSet;
+
+use crate::graphics::utils::graphics_queue;
+
+use super::*;
+
+#[test]
+fn test_presets() {
+    let linear = SamplerDesc::linear();
+    assert_eq!(
+        (linear.min_filter, linear.mag_filter),
+        (Filter::Linear, Filter::Linear)
+    );
+    assert_eq!(linear.mipmap_mode, MipmapMode::Linear);
+    assert_eq!(linear.address_mode_u, AddressMode::ClampToEdge);
+    assert_eq!(linear.anisotropy, None);
+    assert_eq!(SamplerDesc::default(), linear);
+
+    let repeat = SamplerDesc::linear_repeat();
+    assert_eq!(
+        [
+            repeat.address_mode_u,
+            repeat.address_mode_v,
+            repeat.address_mode_w
+        ],
+        [AddressMode::Repeat; 3],
+    );
+    assert_eq!(linear.with_address_mode(AddressMode::Repeat), repeat);
+
+    let pixel_art = SamplerDesc::pixel_art();
+    assert_eq!(pixel_art.min_filter, Filter::Nearest);
+    assert_eq!(pixel_art.mag_filter, Filter::Nearest);
+    assert_eq!(pixel_art.mipmap_mode, MipmapMode::Nearest);
+    assert_eq!(pixel_art.address_mode_u, AddressMode::ClampToEdge);
+}
+
+#[test]
+fn test_cache_key() {
+    let mut keys = HashSet::new();
+    assert!(keys.insert(SamplerDesc::linear()));
+    // Identical descriptions built differently share one key.
+    assert!(!keys.insert(SamplerDesc::default()));
+    assert!(!keys.insert(SamplerDesc::linear_repeat().with_address_mode(AddressMode::ClampToEdge)));
+    assert!(keys.insert(SamplerDesc::linear().with_anisotropy(4.0)));
+    assert!(!keys.insert(SamplerDesc::linear().with_anisotropy(4.0)));
+    assert!(keys.insert(SamplerDesc::linear().with_anisotropy(8.0)));
+    assert!(keys.insert(SamplerDesc::linear().with_lod_bias(0.5)));
+    assert_eq!(keys.len(), 4);
+
+    // Floats are compared by their bits, so NaN is equal to itself.
+    let nan = SamplerDesc::linear().with_anisotropy(f32::NAN);
+    assert_eq!(nan, nan);
+    assert_ne!(
+        SamplerDesc::linear().with_lod_bias(0.0),
+        SamplerDesc::linear().with_lod_bias(-0.0),
+    );
+}
+
+#[test]
+fn test_clamp_anisotropy() {
+    assert_eq!(self::clamp_anisotropy(None, true, 16.0), None);
+    assert_eq!(self::clamp_anisotropy(Some(4.0), true, 16.0), Some(4.0));
+    assert_eq!(self::clamp_anisotropy(Some(64.0), true, 16.0), Some(16.0));
+    assert_eq!(self::clamp_anisotropy(Some(0.5), true, 16.0), Some(1.0));
+    // Anisotropy is disabled without `sampler_anisotropy` feature.
+    assert_eq!(self::clamp_anisotropy(Some(4.0), false, 16.0), None);
+}
+
+/// Checks that identical descriptions share the same sampler.
+///
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_cache_dedup() {
+    let queue = graphics_queue();
+    let mut cache = SamplerCache::new(queue.device().clone());
+    assert!(cache.is_empty());
+    let linear = cache.get(SamplerDesc::linear()).unwrap();
+    let default = cache.get(SamplerDesc::default()).unwrap();
+    assert!(Arc::ptr_eq(&linear, &default));
+    cache.get(SamplerDesc::pixel_art()).unwrap();
+    assert_eq!(cache.len(), 2);
+
+    // Device is created without `sampler_anisotropy` feature.
+    let anisotropic = SamplerDesc::linear().with_anisotropy(4.0);
+    assert_eq!(cache.effective_anisotropy(&anisotropic), None);
+    cache.clear();
+    assert!(cache.is_empty());
+}
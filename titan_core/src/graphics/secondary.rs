@@ -0,0 +1,253 @@
+//! Secondary windows which share the device with the main window of the renderer.
+//!
+//! Each secondary window owns its surface and swapchain, while the device and queues
+//! are shared with the main window. Images of secondary windows are only cleared
+//! with their clear color, and one frame of each window is in flight at most.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BuildError, ClearColorImageError, CommandBufferExecError,
+    CommandBufferUsage,
+};
+use vulkano::device::Queue;
+use vulkano::format::ClearValue;
+use vulkano::image::{ImageUsage, SwapchainImage};
+use vulkano::instance::Instance;
+use vulkano::swapchain::{
+    self, AcquireError, CapabilitiesError, PresentFuture, PresentMode, Surface, Swapchain,
+    SwapchainCreationError,
+};
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture, SharingMode};
+use vulkano::OomError;
+use vulkano_win::VkSurfaceBuild;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder};
+
+use crate::math::Color;
+use crate::window::WindowDesc;
+
+use super::pre_rotation::PreTransform;
+use super::renderer::swapchain_dimensions;
+use super::utils;
+
+/// Max time to wait for the previous frame of the window before its frame is skipped.
+const FRAME_TIMEOUT: Duration = Duration::from_millis(100);
+
+type WindowFence = FenceSignalFuture<PresentFuture<Box<dyn GpuFuture + Send + Sync>, Window>>;
+
+#[derive(Debug, Error)]
+pub enum SecondaryWindowCreationError {
+    #[error("surface creation failure: {0}")]
+    SurfaceCreation(#[from] vulkano_win::CreationError),
+
+    #[error("surface could not be presented by the queue of the device")]
+    UnsupportedSurface,
+
+    #[error("images of the surface could not be cleared")]
+    UnsupportedUsage,
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("swapchain creation failure: {0}")]
+    SwapchainCreation(#[from] SwapchainCreationError),
+}
+
+#[derive(Debug, Error)]
+pub enum SecondaryWindowRenderError {
+    #[error("failed to wait for the previous frame: {0}")]
+    FrameWait(#[source] FlushError),
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("swapchain recreation failure: {0}")]
+    SwapchainRecreation(#[from] SwapchainCreationError),
+
+    #[error("acquiring next image failure: {0}")]
+    AcquireNextImage(#[from] AcquireError),
+
+    #[error("command buffer allocation failure: {0}")]
+    CommandBufferAllocation(#[from] OomError),
+
+    #[error("failed to clear image of the window: {0}")]
+    Clear(#[from] ClearColorImageError),
+
+    #[error("command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit commands: {0}")]
+    SubmitQueue(#[from] FlushError),
+}
+
+/// Window with its own surface and swapchain which is presented by the renderer.
+pub(crate) struct SecondaryWindow {
+    surface: Arc<Surface<Window>>,
+    swapchain: Arc<Swapchain<Window>>,
+    images: Vec<Arc<SwapchainImage<Window>>>,
+    clear_color: Color,
+    recreate_swapchain: bool,
+    /// Fence of the last submitted frame of the window.
+    last_frame: Option<WindowFence>,
+}
+
+impl SecondaryWindow {
+    /// Creates the window with its surface and swapchain.
+    pub fn new<T>(
+        target: &EventLoopWindowTarget<T>,
+        instance: Arc<Instance>,
+        graphics_queue: &Arc<Queue>,
+        present_queue: &Arc<Queue>,
+        desc: &WindowDesc,
+    ) -> Result<Self, SecondaryWindowCreationError> {
+        let surface = WindowBuilder::new()
+            .with_title(&desc.title)
+            .with_inner_size(PhysicalSize::new(desc.size.width, desc.size.height))
+            .build_vk_surface(target, instance)?;
+        if !surface.is_supported(present_queue.family())? {
+            return Err(SecondaryWindowCreationError::UnsupportedSurface);
+        }
+
+        let physical_device = graphics_queue.device().physical_device();
+        let capabilities = surface.capabilities(physical_device)?;
+        if !capabilities.supported_usage_flags.transfer_destination {
+            return Err(SecondaryWindowCreationError::UnsupportedUsage);
+        }
+        let (format, color_space) = utils::suitable_image_format(&capabilities);
+        let pre_transform = PreTransform::from(capabilities.current_transform);
+        let window_size = surface.window().inner_size().into();
+        let dimensions = swapchain_dimensions(&capabilities, window_size, pre_transform);
+        let image_count = match capabilities.max_image_count {
+            Some(max_image_count) => (capabilities.min_image_count + 1).min(max_image_count),
+            None => capabilities.min_image_count + 1,
+        };
+        let sharing_mode = if present_queue.family().id() == graphics_queue.family().id() {
+            SharingMode::from(graphics_queue)
+        } else {
+            SharingMode::from(&[graphics_queue, present_queue][..])
+        };
+        let usage = ImageUsage {
+            transfer_destination: true,
+            ..ImageUsage::none()
+        };
+        let (swapchain, images) =
+            Swapchain::start(graphics_queue.device().clone(), surface.clone())
+                .format(format)
+                .color_space(color_space)
+                .present_mode(PresentMode::Fifo)
+                .dimensions(dimensions)
+                .num_images(image_count)
+                .transform(capabilities.current_transform)
+                .sharing_mode(sharing_mode)
+                .usage(usage)
+                .build()?;
+
+        Ok(Self {
+            surface,
+            swapchain,
+            images,
+            clear_color: desc.clear_color,
+            recreate_swapchain: false,
+            last_frame: None,
+        })
+    }
+
+    /// Window of the surface.
+    pub fn window(&self) -> &Window {
+        self.surface.window()
+    }
+
+    /// Requests to recreate the swapchain before the next frame.
+    pub fn request_resize(&mut self) {
+        self.recreate_swapchain = true;
+    }
+
+    fn resize(&mut self) -> Result<(), SecondaryWindowRenderError> {
+        let physical_device = self.swapchain.device().physical_device();
+        let capabilities = self.surface.capabilities(physical_device)?;
+        let pre_transform = PreTransform::from(capabilities.current_transform);
+        let window_size = self.window().inner_size().into();
+        let dimensions = swapchain_dimensions(&capabilities, window_size, pre_transform);
+        let (swapchain, images) = self
+            .swapchain
+            .recreate()
+            .dimensions(dimensions)
+            .transform(capabilities.current_transform)
+            .build()?;
+        self.swapchain = swapchain;
+        self.images = images;
+        self.recreate_swapchain = false;
+        Ok(())
+    }
+
+    /// Clears the next image of the window and presents it.
+    ///
+    /// Frame is skipped if the window is minimized
+    /// or its previous frame is not finished yet.
+    ///
+    pub fn render(
+        &mut self,
+        graphics_queue: &Arc<Queue>,
+        present_queue: &Arc<Queue>,
+    ) -> Result<(), SecondaryWindowRenderError> {
+        let size = self.window().inner_size();
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+        if let Some(fence) = &self.last_frame {
+            match fence.wait(Some(FRAME_TIMEOUT)) {
+                Ok(()) => self.last_frame = None,
+                Err(FlushError::Timeout) => return Ok(()),
+                Err(err) => return Err(SecondaryWindowRenderError::FrameWait(err)),
+            }
+        }
+        if self.recreate_swapchain {
+            self.resize()?;
+        }
+
+        let (image_index, suboptimal, acquire_future) =
+            match swapchain::acquire_next_image(self.swapchain.clone(), Some(FRAME_TIMEOUT)) {
+                Ok(acquired) => acquired,
+                Err(AcquireError::OutOfDate) => {
+                    self.recreate_swapchain = true;
+                    return Ok(());
+                }
+                Err(AcquireError::Timeout) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+        self.recreate_swapchain |= suboptimal;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            graphics_queue.device().clone(),
+            graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        let color = ClearValue::Float(self.clear_color.into());
+        builder.clear_color_image(self.images[image_index].clone(), color)?;
+        let command_buffer = builder.build()?;
+
+        let future: Box<dyn GpuFuture + Send + Sync> =
+            Box::new(acquire_future.then_execute(graphics_queue.clone(), command_buffer)?);
+        let future = future
+            .then_swapchain_present(present_queue.clone(), self.swapchain.clone(), image_index)
+            .then_signal_fence_and_flush();
+        match future {
+            Ok(future) => {
+                self.last_frame = Some(future);
+                Ok(())
+            }
+            Err(FlushError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                Ok(())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
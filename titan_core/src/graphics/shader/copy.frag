@@ -0,0 +1,13 @@
+#version 450
+
+layout(binding = 0, set = 0) uniform sampler2D scene;
+
+layout(location = 0) in vec2 inUV;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    // Pixel centers match texel centers if scene has the same size as the viewport,
+    // otherwise scaled scene is filtered by the sampler.
+    outColor = texture(scene, inUV);
+}
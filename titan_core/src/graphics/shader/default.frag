@@ -1,9 +1,13 @@
 #version 450
 
+// Swaps red and blue channels of the color, so the same shader could be
+// specialized for pipelines which need the other order of channels.
+layout(constant_id = 0) const bool SWAP_RED_BLUE = false;
+
 layout(location = 0) in vec4 color;
 
 layout(location = 0) out vec4 outColor;
 
 void main() {
-    outColor = color;
+    outColor = SWAP_RED_BLUE ? color.bgra : color;
 }
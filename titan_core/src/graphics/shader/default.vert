@@ -9,6 +9,12 @@ layout(binding = 0) uniform CameraUBO {
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec4 color;
 
+layout(push_constant) uniform PushConstants {
+    // Transform of the submitted draw, identity for draws of the scene.
+    mat4 model;
+    vec4 color;
+} pushConstants;
+
 layout(location = 0) out vec4 outColor;
 
 out gl_PerVertex {
@@ -16,6 +22,6 @@ out gl_PerVertex {
 };
 
 void main() {
-    gl_Position = ubo.projection * ubo.view * ubo.model * vec4(position, 1.0);
-    outColor = color;
+    gl_Position = ubo.projection * ubo.view * ubo.model * pushConstants.model * vec4(position, 1.0);
+    outColor = color * pushConstants.color;
 }
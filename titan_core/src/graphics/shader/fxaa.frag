@@ -0,0 +1,53 @@
+#version 450
+
+layout(binding = 0, set = 0) uniform sampler2D scene;
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 outColor;
+
+// Parameters of FXAA (low quality preset of the original algorithm).
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+const vec3 LUMA = vec3(0.299, 0.587, 0.114);
+
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(scene, 0));
+
+    vec4 colorM = texture(scene, uv);
+    float lumaNW = dot(texture(scene, uv + vec2(-1.0, -1.0) * texel).rgb, LUMA);
+    float lumaNE = dot(texture(scene, uv + vec2(1.0, -1.0) * texel).rgb, LUMA);
+    float lumaSW = dot(texture(scene, uv + vec2(-1.0, 1.0) * texel).rgb, LUMA);
+    float lumaSE = dot(texture(scene, uv + vec2(1.0, 1.0) * texel).rgb, LUMA);
+    float lumaM = dot(colorM.rgb, LUMA);
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    // Direction of the edge is perpendicular to the luma gradient.
+    vec2 direction = vec2(
+        -((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+        (lumaNW + lumaSW) - (lumaNE + lumaSE)
+    );
+    float directionReduce = max(
+        (lumaNW + lumaNE + lumaSW + lumaSE) * (0.25 * REDUCE_MUL),
+        REDUCE_MIN
+    );
+    float inverseDirectionMin = 1.0 / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseDirectionMin, -SPAN_MAX, SPAN_MAX) * texel;
+
+    // Blend samples along the edge, falling back to the narrower blend
+    // if the wider one crosses another edge.
+    vec3 colorA = 0.5 * (
+        texture(scene, uv + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(scene, uv + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 colorB = colorA * 0.5 + 0.25 * (
+        texture(scene, uv - direction * 0.5).rgb +
+        texture(scene, uv + direction * 0.5).rgb
+    );
+    float lumaB = dot(colorB, LUMA);
+    bool outside = lumaB < lumaMin || lumaB > lumaMax;
+    outColor = vec4(outside ? colorA : colorB, colorM.a);
+}
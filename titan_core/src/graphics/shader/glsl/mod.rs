@@ -0,0 +1,241 @@
+//! Compilation of GLSL shaders into SPIR-V at runtime.
+//!
+//! Built-in shaders are compiled when the engine is built, while shaders of
+//! [render hooks](crate::graphics::RenderHook) could be compiled by [`GlslCompiler`]
+//! from source which is changed while the game runs. Compiled words are given to
+//! [`ShaderSet::with_stage_glsl`](crate::graphics::ShaderSet::with_stage_glsl).
+//!
+//! Errors of the compiler are parsed into [`GlslDiagnostic`]s, each of which points
+//! to the line of the source (or of the included file) it was reported for.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use shaderc::{
+    CompileOptions, Compiler, EnvVersion, IncludeType, ResolvedInclude, ShaderKind, TargetEnv,
+};
+use thiserror::Error;
+
+use crate::graphics::reflect::ShaderStage;
+
+mod tests;
+
+/// Error that can happen when GLSL shader is compiled.
+#[derive(Debug, Error)]
+pub enum GlslError {
+    #[error("GLSL compiler could not be initialized")]
+    CompilerUnavailable,
+
+    #[error("failed to compile `{name}`:{}", format_diagnostics(.diagnostics))]
+    Compilation {
+        name: String,
+        diagnostics: Vec<GlslDiagnostic>,
+    },
+
+    #[error("internal error of GLSL compiler: {0}")]
+    Internal(String),
+}
+
+/// Message of the compiler about one line of the shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlslDiagnostic {
+    /// Name of the shader or of the included file the message was reported for.
+    pub file: String,
+    /// Number of the line (starting from 1), if the message was reported for a line.
+    pub line: Option<u32>,
+    /// Message of the compiler, like `error: 'foo' : undeclared identifier`.
+    pub message: String,
+    /// Text of the line the message was reported for, if the file is known.
+    pub source_line: Option<String>,
+}
+
+impl fmt::Display for GlslDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message)?,
+            None => write!(f, "{}: {}", self.file, self.message)?,
+        }
+        if let (Some(line), Some(source_line)) = (self.line, &self.source_line) {
+            write!(f, "\n{:>5} | {}", line, source_line)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_diagnostics(diagnostics: &[GlslDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| format!("\n{}", diagnostic))
+        .collect()
+}
+
+/// Parses messages of the compiler (`file:line: message`, one per line),
+/// taking lines of files from their sources.
+///
+/// Summary lines, like `1 error generated.`, are skipped.
+///
+fn parse_diagnostics(messages: &str, sources: &HashMap<String, String>) -> Vec<GlslDiagnostic> {
+    let diagnostics = messages.lines().filter_map(|message| {
+        let message = message.trim();
+        if message.is_empty() || message.ends_with(" generated.") {
+            return None;
+        }
+        let mut parts = message.splitn(3, ':');
+        let file = parts.next()?.to_string();
+        let (line, message) = match (parts.next(), parts.next()) {
+            (Some(line), Some(rest)) => match line.trim().parse() {
+                Ok(line) => (Some(line), rest.trim().to_string()),
+                Err(_) => (None, format!("{}:{}", line.trim(), rest)),
+            },
+            (Some(rest), None) => (None, rest.trim().to_string()),
+            _ => {
+                return Some(GlslDiagnostic {
+                    file: String::new(),
+                    line: None,
+                    message: message.to_string(),
+                    source_line: None,
+                })
+            }
+        };
+        let source_line = line.zip(sources.get(&file)).and_then(|(line, source)| {
+            let index = (line as usize).checked_sub(1)?;
+            source
+                .lines()
+                .nth(index)
+                .map(|text| text.trim_end().to_string())
+        });
+        Some(GlslDiagnostic {
+            file,
+            line,
+            message,
+            source_line,
+        })
+    });
+    diagnostics.collect()
+}
+
+/// Kind of shaders of the compiler for the stage of the pipeline.
+fn shader_kind(stage: ShaderStage) -> ShaderKind {
+    match stage {
+        ShaderStage::Vertex => ShaderKind::Vertex,
+        ShaderStage::TessellationControl => ShaderKind::TessControl,
+        ShaderStage::TessellationEvaluation => ShaderKind::TessEvaluation,
+        ShaderStage::Geometry => ShaderKind::Geometry,
+        ShaderStage::Fragment => ShaderKind::Fragment,
+        ShaderStage::Compute => ShaderKind::Compute,
+    }
+}
+
+/// Compiler of GLSL shaders into SPIR-V for Vulkan 1.0.
+///
+/// `#include "file"` is resolved relative to the directory of the including file first,
+/// then in include directories, while `#include <file>` is resolved in include directories only.
+///
+#[derive(Debug, Clone, Default)]
+pub struct GlslCompiler {
+    include_dirs: Vec<PathBuf>,
+    defines: Vec<(String, Option<String>)>,
+}
+
+impl GlslCompiler {
+    /// Creates compiler without include directories and macro definitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds directory in which included files are searched.
+    pub fn with_include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    /// Defines macro for all compiled shaders, like `#define name value`.
+    pub fn with_define(mut self, name: &str, value: Option<&str>) -> Self {
+        self.defines
+            .push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    /// Finds file which is included by another one.
+    fn resolve(
+        &self,
+        requested: &str,
+        include_type: IncludeType,
+        requesting: &str,
+    ) -> Option<PathBuf> {
+        let relative = match include_type {
+            IncludeType::Relative => Path::new(requesting).parent(),
+            IncludeType::Standard => None,
+        };
+        relative
+            .into_iter()
+            .chain(self.include_dirs.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(requested))
+            .find(|path| path.is_file())
+    }
+
+    /// Compiles GLSL source of given stage into SPIR-V words.
+    ///
+    /// Name of the source (for example, its path) is used in diagnostics
+    /// and to resolve files which are included relative to it.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the source or one of included files could not be compiled.
+    ///
+    pub fn compile(
+        &self,
+        source: &str,
+        name: &str,
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<Vec<u32>, GlslError> {
+        let mut compiler = Compiler::new().ok_or(GlslError::CompilerUnavailable)?;
+        let mut options = CompileOptions::new().ok_or(GlslError::CompilerUnavailable)?;
+        options.set_target_env(TargetEnv::Vulkan, EnvVersion::Vulkan1_0 as u32);
+        for (name, value) in &self.defines {
+            options.add_macro_definition(name, value.as_deref());
+        }
+
+        // Sources of all compiled files, so diagnostics could show their lines.
+        let sources = RefCell::new(HashMap::from([(name.to_string(), source.to_string())]));
+        options.set_include_callback(|requested, include_type, requesting, _depth| {
+            let path = self
+                .resolve(requested, include_type, requesting)
+                .ok_or_else(|| format!("file `{}` was not found", requested))?;
+            let content = std::fs::read_to_string(&path)
+                .map_err(|error| format!("failed to read `{}`: {}", path.display(), error))?;
+            let resolved_name = path.display().to_string();
+            sources
+                .borrow_mut()
+                .insert(resolved_name.clone(), content.clone());
+            Ok(ResolvedInclude {
+                resolved_name,
+                content,
+            })
+        });
+
+        let kind = self::shader_kind(stage);
+        let result = compiler.compile_into_spirv(source, kind, name, entry_point, Some(&options));
+        drop(options);
+        let sources = sources.into_inner();
+        match result {
+            Ok(artifact) => {
+                if artifact.get_num_warnings() > 0 {
+                    let warnings = artifact.get_warning_messages();
+                    for warning in self::parse_diagnostics(&warnings, &sources) {
+                        log::warn!("{}", warning);
+                    }
+                }
+                Ok(artifact.as_binary().to_vec())
+            }
+            Err(shaderc::Error::CompilationError(_, messages)) => Err(GlslError::Compilation {
+                name: name.to_string(),
+                diagnostics: self::parse_diagnostics(&messages, &sources),
+            }),
+            Err(error) => Err(GlslError::Internal(error.to_string())),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_parse_diagnostics() {
+    let sources = HashMap::from([(
+        "shader.frag".to_string(),
+        "#version 450\nvoid main() {\n    color = vec4(1.0);\n}\n".to_string(),
+    )]);
+    let messages = "shader.frag:3: error: 'color' : undeclared identifier\n\
+        common.glsl:7: warning: unused variable\n\
+        shader.frag: error: linking failed\n\
+        2 errors generated.\n";
+    let diagnostics = parse_diagnostics(messages, &sources);
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(
+        diagnostics[0],
+        GlslDiagnostic {
+            file: "shader.frag".to_string(),
+            line: Some(3),
+            message: "error: 'color' : undeclared identifier".to_string(),
+            source_line: Some("    color = vec4(1.0);".to_string()),
+        },
+    );
+    // Lines of files which sources are unknown are not shown.
+    assert_eq!(diagnostics[1].line, Some(7));
+    assert_eq!(diagnostics[1].source_line, None);
+    assert_eq!(diagnostics[2].line, None);
+    assert_eq!(diagnostics[2].message, "error: linking failed");
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "shader.frag:3: error: 'color' : undeclared identifier\n    3 |     color = vec4(1.0);",
+    );
+}
+
+#[test]
+fn test_compile() {
+    let dir = std::env::temp_dir().join(format!("titan_glsl_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("common.glsl"), "const float SCALE = 2.0;\n").unwrap();
+
+    let compiler = GlslCompiler::new()
+        .with_include_dir(&dir)
+        .with_define("OFFSET", Some("1.0"));
+    let source = "#version 450\n\
+        #extension GL_GOOGLE_include_directive : require\n\
+        #include <common.glsl>\n\
+        void main() { gl_Position = vec4(SCALE * OFFSET); }\n";
+    let words = compiler
+        .compile(source, "shader.vert", ShaderStage::Vertex, "main")
+        .unwrap();
+    assert_eq!(words[0], 0x0723_0203);
+
+    // Errors point to lines of the source.
+    let source = "#version 450\nvoid main() {\n    gl_Position = missing;\n}\n";
+    let error = compiler
+        .compile(source, "broken.vert", ShaderStage::Vertex, "main")
+        .unwrap_err();
+    match error {
+        GlslError::Compilation { name, diagnostics } => {
+            assert_eq!(name, "broken.vert");
+            assert_eq!(diagnostics[0].line, Some(3));
+            let source_line = diagnostics[0].source_line.as_deref();
+            assert_eq!(source_line, Some("    gl_Position = missing;"));
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
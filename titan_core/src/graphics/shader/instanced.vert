@@ -0,0 +1,29 @@
+#version 450
+
+layout(binding = 0) uniform CameraUBO {
+    mat4 projection;
+    mat4 model;
+    mat4 view;
+} ubo;
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+
+// Columns of the transform of the instance.
+layout(location = 2) in vec4 model_x;
+layout(location = 3) in vec4 model_y;
+layout(location = 4) in vec4 model_z;
+layout(location = 5) in vec4 model_w;
+layout(location = 6) in vec4 tint;
+
+layout(location = 0) out vec4 outColor;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+void main() {
+    mat4 model = mat4(model_x, model_y, model_z, model_w);
+    gl_Position = ubo.projection * ubo.view * ubo.model * model * vec4(position, 1.0);
+    outColor = color * tint;
+}
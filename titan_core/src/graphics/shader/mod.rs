@@ -1,5 +1,8 @@
 //! Shader utilities of game engine.
 
+#[cfg(feature = "glsl")]
+pub mod glsl;
+
 /// Default shaders which are used in game engine.
 pub mod default {
     /// Default vertex shader utilities.
@@ -10,6 +13,15 @@ pub mod vertex {
         }
     }
 
+    /// Vertex shader utilities of instanced draws, which transform
+    /// vertices with per-instance attributes instead of push constants.
+    pub mod instanced {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/instanced.vert",
+        }
+    }
+
     /// Default fragment shader utilities.
     pub mod fragment {
         vulkano_shaders::shader! {
@@ -37,3 +49,101 @@ pub mod fragment {
         }
     }
 }
+
+/// Shaders which are used in sprite rendering.
+pub mod sprite {
+    /// Sprite vertex shader utilities.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/sprite.vert",
+        }
+    }
+
+    /// Sprite fragment shader utilities.
+    pub mod fragment {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/sprite.frag",
+        }
+    }
+}
+
+/// Shaders which are used in particle simulation and rendering.
+pub mod particles {
+    /// Particle compute shader utilities.
+    pub mod compute {
+        vulkano_shaders::shader! {
+            ty: "compute",
+            path: "src/graphics/shader/particles.comp",
+        }
+    }
+
+    /// Particle vertex shader utilities.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/particles.vert",
+        }
+    }
+
+    /// Particle fragment shader utilities.
+    pub mod fragment {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/particles.frag",
+        }
+    }
+}
+
+/// Shaders which post-process the scene into the final image.
+pub mod post_process {
+    /// Full-screen triangle vertex shader utilities.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/post_process.vert",
+        }
+    }
+
+    /// Fragment shader utilities which copy the scene as is.
+    pub mod copy {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/copy.frag",
+        }
+    }
+
+    /// Fragment shader utilities of fast approximate antialiasing (FXAA).
+    pub mod fxaa {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/fxaa.frag",
+        }
+    }
+}
+
+/// Implements [`Pod`](bytemuck::Pod) for push constants which are captured as bytes.
+///
+/// # Safety
+///
+/// Every field of push constants must be plain data (`f32`, `u32` or arrays of them)
+/// and fields must leave no implicit padding (including the trailing one).
+///
+macro_rules! unsafe_impl_pod {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            unsafe impl bytemuck::Zeroable for $ty {}
+            unsafe impl bytemuck::Pod for $ty {}
+        )*
+    };
+}
+
+// Safety: all of these consist only of 4-byte aligned vectors and matrices of `f32`
+// and are sized to a multiple of 4 bytes, so they contain no padding.
+unsafe_impl_pod!(
+    default::vertex::ty::PushConstants,
+    ui::vertex::ty::PushConstants,
+    sprite::vertex::ty::PushConstants,
+    particles::vertex::ty::PushConstants,
+);
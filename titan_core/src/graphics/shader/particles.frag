@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) in vec4 color;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    // Make round point sprites from square ones.
+    vec2 coord = gl_PointCoord - vec2(0.5);
+    if (dot(coord, coord) > 0.25) {
+        discard;
+    }
+    outColor = color;
+}
@@ -0,0 +1,33 @@
+#version 450
+
+layout(binding = 0) uniform CameraUBO {
+    mat4 projection;
+    mat4 model;
+    mat4 view;
+} ubo;
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in float lifetime;
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+    float point_size;
+} pushConstants;
+
+layout(location = 0) out vec4 outColor;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+    float gl_PointSize;
+};
+
+void main() {
+    // Particles are simulated in world space, so model matrix is not applied.
+    gl_Position = ubo.projection * ubo.view * vec4(position, 1.0);
+    // Dead particles are moved outside of clip space.
+    if (lifetime <= 0.0) {
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+    }
+    gl_PointSize = pushConstants.point_size;
+    outColor = pushConstants.color;
+}
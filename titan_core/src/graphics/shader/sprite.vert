@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 uv;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec2 outUV;
+
+layout(push_constant) uniform PushConstants {
+    // Transforms the 2D world into clip space (including surface pre-rotation).
+    mat4 view_projection;
+} pushConstants;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+void main() {
+    gl_Position = pushConstants.view_projection * vec4(position, 0.0, 1.0);
+    outColor = color;
+    outUV = uv;
+}
@@ -8,15 +8,29 @@ layout(location = 0) out vec4 outColor;
 layout(location = 1) out vec2 outUV;
 
 layout(push_constant) uniform PushConstants {
+    // Columns of 2x2 matrix of surface pre-rotation.
+    vec4 pre_rotation;
     vec2 screen_size;
 } pushConstants;
 
+// Whether the target image has sRGB format, so colors of egui (which are in sRGB space)
+// must be converted into linear space to be encoded back by the hardware.
+layout(constant_id = 0) const bool srgb_target = true;
+
 out gl_PerVertex {
     vec4 gl_Position;
 };
 
+vec3 linearFromSrgb(vec3 srgb) {
+    vec3 lower = srgb / 12.92;
+    vec3 higher = pow((srgb + 0.055) / 1.055, vec3(2.4));
+    return mix(higher, lower, lessThanEqual(srgb, vec3(0.04045)));
+}
+
 void main() {
-    gl_Position = vec4(2.0 * position / pushConstants.screen_size - 1.0, 0.0, 1.0);
-    outColor = color;
+    mat2 preRotation = mat2(pushConstants.pre_rotation.xy, pushConstants.pre_rotation.zw);
+    vec2 ndc = 2.0 * position / pushConstants.screen_size - 1.0;
+    gl_Position = vec4(preRotation * ndc, 0.0, 1.0);
+    outColor = srgb_target ? vec4(linearFromSrgb(color.rgb), color.a) : color;
     outUV = uv;
 }
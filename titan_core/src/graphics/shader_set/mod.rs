@@ -0,0 +1,289 @@
+//! Sets of user-defined shaders which pipelines are built from.
+//!
+//! Built-in pipelines use shaders compiled by `vulkano_shaders`. Pipelines of
+//! [render hooks](super::RenderHook) could be built from SPIR-V which is supplied
+//! at runtime instead: [`ShaderSet`] holds code of each stage (from bytes or `.spv` files),
+//! checks that each module has the expected stage and entry point,
+//! that stages could form one pipeline, and merges their interfaces.
+//!
+//! Words of each stage are given to `ShaderModule::from_words` when the pipeline is built.
+//! Sets could be reloaded when their files change (see [`ShaderWatcher`]).
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::graphics::reflect::{
+    spirv, InterfaceError, PipelineReflection, ReflectionError, ShaderReflection, ShaderStage,
+    VertexFormat, VertexInputState,
+};
+#[cfg(feature = "glsl")]
+use crate::graphics::shader::glsl::{GlslCompiler, GlslError};
+
+pub use watcher::{ShaderFile, ShaderWatcher, WatchedShaders};
+
+mod tests;
+mod watcher;
+
+/// Error that can happen when shaders are added to the set or validated.
+#[derive(Debug, Error)]
+pub enum ShaderSetError {
+    #[error("failed to read shader `{path}`: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid SPIR-V module of {stage:?} stage: {source}")]
+    Reflection {
+        stage: ShaderStage,
+        #[source]
+        source: ReflectionError,
+    },
+
+    #[error("module was given for {expected:?} stage, but its entry point is of {actual:?} stage")]
+    StageMismatch {
+        expected: ShaderStage,
+        actual: ShaderStage,
+    },
+
+    #[error("entry point of {stage:?} stage is `{actual}`, but `{expected}` was expected")]
+    EntryPointMismatch {
+        stage: ShaderStage,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("shader of {0:?} stage was already added")]
+    DuplicateStage(ShaderStage),
+
+    #[error("pipeline has no shader of {0:?} stage")]
+    MissingStage(ShaderStage),
+
+    #[error("compute shader could not be combined with shaders of other stages")]
+    ComputeWithGraphics,
+
+    #[error("interfaces of shaders do not match: {0}")]
+    Interface(#[from] InterfaceError),
+
+    #[cfg(feature = "glsl")]
+    #[error("failed to compile shader of {stage:?} stage: {source}")]
+    Glsl {
+        stage: ShaderStage,
+        #[source]
+        source: GlslError,
+    },
+}
+
+/// Shader of one stage of the pipeline.
+#[derive(Debug, Clone)]
+pub struct StageShader {
+    words: Vec<u32>,
+    reflection: ShaderReflection,
+}
+
+impl StageShader {
+    /// SPIR-V code of the module in the host byte order.
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Stage of the shader.
+    pub fn stage(&self) -> ShaderStage {
+        self.reflection.stage()
+    }
+
+    /// Name of the entry point of the shader.
+    pub fn entry_point(&self) -> &str {
+        self.reflection.entry_point()
+    }
+
+    /// Interface of the entry point.
+    pub fn reflection(&self) -> &ShaderReflection {
+        &self.reflection
+    }
+}
+
+/// Shaders of all stages which one pipeline is built from.
+///
+/// Graphics pipeline requires vertex and fragment shaders, tessellation stages
+/// must be given together, and compute pipeline has only a compute shader.
+/// Each module is reflected when it is added, so mistakes are reported
+/// before any Vulkan object is created.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ShaderSet {
+    /// Shaders in ascending order of their stages.
+    stages: Vec<StageShader>,
+}
+
+impl ShaderSet {
+    /// Creates an empty set of shaders.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds SPIR-V module (in any byte order) for given stage with given entry point.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the module is invalid, its entry point has another
+    /// stage or name, or shader of the stage was already added.
+    ///
+    pub fn with_stage(
+        self,
+        stage: ShaderStage,
+        bytes: &[u8],
+        entry_point: &str,
+    ) -> Result<Self, ShaderSetError> {
+        let words =
+            spirv::words(bytes).map_err(|source| ShaderSetError::Reflection { stage, source })?;
+        self.with_stage_words(stage, words, entry_point)
+    }
+
+    /// Compiles GLSL source by the compiler and adds it for given stage with given entry point.
+    ///
+    /// Name of the source (for example, its path) is used in diagnostics of the compiler.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the source could not be compiled
+    /// or the module could not be added (see [`ShaderSet::with_stage`]).
+    ///
+    #[cfg(feature = "glsl")]
+    pub fn with_stage_glsl(
+        self,
+        stage: ShaderStage,
+        source: &str,
+        name: &str,
+        entry_point: &str,
+        compiler: &GlslCompiler,
+    ) -> Result<Self, ShaderSetError> {
+        let words = compiler
+            .compile(source, name, stage, entry_point)
+            .map_err(|source| ShaderSetError::Glsl { stage, source })?;
+        self.with_stage_words(stage, words, entry_point)
+    }
+
+    fn with_stage_words(
+        mut self,
+        stage: ShaderStage,
+        words: Vec<u32>,
+        entry_point: &str,
+    ) -> Result<Self, ShaderSetError> {
+        let reflection = ShaderReflection::from_words(&words)
+            .map_err(|source| ShaderSetError::Reflection { stage, source })?;
+        if reflection.stage() != stage {
+            return Err(ShaderSetError::StageMismatch {
+                expected: stage,
+                actual: reflection.stage(),
+            });
+        }
+        if reflection.entry_point() != entry_point {
+            return Err(ShaderSetError::EntryPointMismatch {
+                stage,
+                expected: entry_point.to_string(),
+                actual: reflection.entry_point().to_string(),
+            });
+        }
+        if self.stage(stage).is_some() {
+            return Err(ShaderSetError::DuplicateStage(stage));
+        }
+
+        let index = self.stages.partition_point(|shader| shader.stage() < stage);
+        self.stages.insert(index, StageShader { words, reflection });
+        Ok(self)
+    }
+
+    /// Adds SPIR-V module from the file (for example, compiled by `glslc`)
+    /// for given stage with given entry point.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file could not be read or the module
+    /// could not be added (see [`ShaderSet::with_stage`]).
+    ///
+    pub fn with_stage_file(
+        self,
+        stage: ShaderStage,
+        path: impl AsRef<Path>,
+        entry_point: &str,
+    ) -> Result<Self, ShaderSetError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|source| ShaderSetError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        self.with_stage(stage, &bytes, entry_point)
+    }
+
+    /// Shader of given stage, if it was added.
+    pub fn stage(&self, stage: ShaderStage) -> Option<&StageShader> {
+        self.stages.iter().find(|shader| shader.stage() == stage)
+    }
+
+    /// Shaders of all stages in ascending order of their stages.
+    pub fn stages(&self) -> &[StageShader] {
+        &self.stages
+    }
+
+    /// Returns `true` if the set has only a compute shader.
+    pub fn is_compute(&self) -> bool {
+        self.stage(ShaderStage::Compute).is_some()
+    }
+
+    /// Checks that shaders could form one pipeline and merges their interfaces.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a stage which is required by the pipeline is missing,
+    /// compute shader is combined with graphics ones, or shaders use the same binding
+    /// in different ways.
+    ///
+    pub fn validate(&self) -> Result<PipelineReflection, ShaderSetError> {
+        if self.is_compute() {
+            if self.stages.len() > 1 {
+                return Err(ShaderSetError::ComputeWithGraphics);
+            }
+        } else {
+            let required = [ShaderStage::Vertex, ShaderStage::Fragment];
+            if let Some(&stage) = required.iter().find(|&&stage| self.stage(stage).is_none()) {
+                return Err(ShaderSetError::MissingStage(stage));
+            }
+            let control = self.stage(ShaderStage::TessellationControl);
+            let evaluation = self.stage(ShaderStage::TessellationEvaluation);
+            match (control, evaluation) {
+                (Some(_), None) => {
+                    return Err(ShaderSetError::MissingStage(
+                        ShaderStage::TessellationEvaluation,
+                    ))
+                }
+                (None, Some(_)) => {
+                    return Err(ShaderSetError::MissingStage(
+                        ShaderStage::TessellationControl,
+                    ))
+                }
+                _ => (),
+            }
+        }
+
+        let reflections: Vec<_> = self.stages.iter().map(StageShader::reflection).collect();
+        Ok(PipelineReflection::from_shaders(&reflections)?)
+    }
+
+    /// Derives vertex input state of the pipeline from its vertex shader.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the set has no vertex shader
+    /// or the vertex type does not match inputs of the shader.
+    ///
+    pub fn vertex_input(&self, format: &VertexFormat) -> Result<VertexInputState, ShaderSetError> {
+        let vertex = self
+            .stage(ShaderStage::Vertex)
+            .ok_or(ShaderSetError::MissingStage(ShaderStage::Vertex))?;
+        Ok(format.vertex_input(vertex.reflection())?)
+    }
+}
@@ -0,0 +1,204 @@
+#![cfg(test)]
+
+use super::*;
+use crate::graphics::reflect::VertexFormats;
+
+/// Bytes of SPIR-V module which declares only the entry point
+/// of given execution model with given name.
+fn module(model: u32, name: &str) -> Vec<u8> {
+    let mut name = name.as_bytes().to_vec();
+    name.resize(name.len() / 4 * 4 + 4, 0);
+    let name = name
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+    let mut entry_point = vec![model, 1];
+    entry_point.extend(name);
+
+    let mut words = vec![0x0723_0203, 0x0001_0000, 0, 2, 0];
+    words.push((entry_point.len() as u32 + 1) << 16 | 15);
+    words.extend(entry_point);
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+fn vertex() -> Vec<u8> {
+    self::module(0, "main")
+}
+
+fn fragment() -> Vec<u8> {
+    self::module(4, "main")
+}
+
+#[test]
+fn test_graphics_set() {
+    let set = ShaderSet::new()
+        .with_stage(ShaderStage::Fragment, &fragment(), "main")
+        .unwrap()
+        .with_stage(ShaderStage::Vertex, &vertex(), "main")
+        .unwrap();
+    let stages: Vec<_> = set.stages().iter().map(StageShader::stage).collect();
+    assert_eq!(stages, [ShaderStage::Vertex, ShaderStage::Fragment]);
+    assert!(!set.is_compute());
+    let words = set.stage(ShaderStage::Vertex).unwrap().words();
+    assert_eq!(words[0], 0x0723_0203);
+    assert_eq!(set.validate().unwrap(), PipelineReflection::default());
+
+    // Words of modules in big endian are swapped to the host byte order.
+    let swapped: Vec<_> = vertex()
+        .chunks_exact(4)
+        .flat_map(|chunk| [chunk[3], chunk[2], chunk[1], chunk[0]])
+        .collect();
+    let set = ShaderSet::new()
+        .with_stage(ShaderStage::Vertex, &swapped, "main")
+        .unwrap();
+    assert_eq!(set.stages()[0].words(), words);
+}
+
+#[test]
+fn test_stage_errors() {
+    let set = ShaderSet::new();
+    let error = set
+        .clone()
+        .with_stage(ShaderStage::Vertex, &fragment(), "main")
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ShaderSetError::StageMismatch {
+            expected: ShaderStage::Vertex,
+            actual: ShaderStage::Fragment,
+        }
+    ));
+
+    let error = set
+        .clone()
+        .with_stage(ShaderStage::Vertex, &vertex(), "vs_main")
+        .unwrap_err();
+    match error {
+        ShaderSetError::EntryPointMismatch {
+            expected, actual, ..
+        } => assert_eq!((expected.as_str(), actual.as_str()), ("vs_main", "main")),
+        error => panic!("unexpected error: {}", error),
+    }
+
+    let error = set
+        .clone()
+        .with_stage(ShaderStage::Vertex, &vertex()[..7], "main")
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ShaderSetError::Reflection {
+            stage: ShaderStage::Vertex,
+            source: ReflectionError::Truncated,
+        }
+    ));
+
+    let error = set
+        .with_stage(ShaderStage::Vertex, &vertex(), "main")
+        .unwrap()
+        .with_stage(ShaderStage::Vertex, &vertex(), "main")
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ShaderSetError::DuplicateStage(ShaderStage::Vertex)
+    ));
+
+    let error = ShaderSet::new()
+        .with_stage_file(ShaderStage::Vertex, "missing.spv", "main")
+        .unwrap_err();
+    assert!(matches!(error, ShaderSetError::Io { .. }), "{}", error);
+}
+
+#[test]
+fn test_stage_combinations() {
+    let add = |set: ShaderSet, stage, model| {
+        set.with_stage(stage, &self::module(model, "main"), "main")
+            .unwrap()
+    };
+    let vertex = add(ShaderSet::new(), ShaderStage::Vertex, 0);
+    assert!(matches!(
+        vertex.validate(),
+        Err(ShaderSetError::MissingStage(ShaderStage::Fragment))
+    ));
+
+    let graphics = add(vertex, ShaderStage::Fragment, 4);
+    let control = add(graphics.clone(), ShaderStage::TessellationControl, 1);
+    assert!(matches!(
+        control.validate(),
+        Err(ShaderSetError::MissingStage(
+            ShaderStage::TessellationEvaluation
+        ))
+    ));
+    let tessellation = add(control, ShaderStage::TessellationEvaluation, 2);
+    assert!(tessellation.validate().is_ok());
+
+    let compute = add(ShaderSet::new(), ShaderStage::Compute, 5);
+    assert!(compute.is_compute());
+    assert!(compute.validate().is_ok());
+    assert!(compute
+        .vertex_input(VertexFormats::default().iter().next().unwrap())
+        .is_err());
+    let mixed = add(graphics, ShaderStage::Compute, 5);
+    assert!(matches!(
+        mixed.validate(),
+        Err(ShaderSetError::ComputeWithGraphics)
+    ));
+}
+
+#[test]
+fn test_watcher_reload() {
+    use std::fs::File;
+    use std::io::Write;
+    use std::time::{Duration, SystemTime};
+
+    let dir = std::env::temp_dir().join(format!("titan_shader_watcher_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vertex_path = dir.join("shader.vert.spv");
+    let fragment_path = dir.join("shader.frag.spv");
+    std::fs::write(&vertex_path, vertex()).unwrap();
+    std::fs::write(&fragment_path, fragment()).unwrap();
+    // Modification time is changed explicitly, so the test does not depend on its precision.
+    let touch = |path: &PathBuf, bytes: &[u8], secs: u64| {
+        let mut file = File::create(path).unwrap();
+        file.write_all(bytes).unwrap();
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        file.set_modified(time).unwrap();
+    };
+
+    let watcher = ShaderWatcher::new();
+    let files = [
+        ShaderFile::new(ShaderStage::Vertex, &vertex_path, "main"),
+        ShaderFile::new(ShaderStage::Fragment, &fragment_path, "main"),
+    ];
+    let shaders = watcher.watch(files.clone()).unwrap();
+    assert_eq!(watcher.len(), 1);
+    assert_eq!(shaders.generation(), 0);
+    assert_eq!(watcher.poll(), 0);
+    assert!(shaders.reloaded_since(0).is_none());
+
+    touch(&fragment_path, &fragment(), 1);
+    assert_eq!(watcher.poll(), 1);
+    let (set, generation) = shaders.reloaded_since(0).unwrap();
+    assert_eq!(generation, 1);
+    assert_eq!(set.stages().len(), 2);
+
+    // Invalid shader is reported once, and the previous set is kept.
+    touch(&fragment_path, &vertex(), 2);
+    assert_eq!(watcher.poll(), 0);
+    assert_eq!(watcher.poll(), 0);
+    assert_eq!(shaders.generation(), 1);
+    assert!(shaders.error().is_some());
+    assert!(shaders.shader_set().stage(ShaderStage::Fragment).is_some());
+
+    touch(&fragment_path, &fragment(), 3);
+    assert_eq!(watcher.poll(), 1);
+    assert_eq!(shaders.generation(), 2);
+    assert!(shaders.error().is_none());
+
+    // Set is not watched after its handles are dropped.
+    drop(shaders);
+    assert!(watcher.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Shaders which could not be loaded are not watched at all.
+    assert!(watcher.watch(files).is_err());
+    assert!(watcher.is_empty());
+}
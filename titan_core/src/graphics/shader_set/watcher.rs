@@ -0,0 +1,205 @@
+//! Reloading of shader sets when their SPIR-V files change.
+//!
+//! Files are not watched by the platform: [`ShaderWatcher::poll`] compares their
+//! modification times with the ones of the last load, so changes are picked up
+//! by the next poll after the file was written (for example, by `glslc`).
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::SystemTime;
+
+use crate::graphics::reflect::ShaderStage;
+
+use super::{ShaderSet, ShaderSetError};
+
+/// SPIR-V file with the shader of one stage of the pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShaderFile {
+    pub stage: ShaderStage,
+    pub path: PathBuf,
+    pub entry_point: String,
+}
+
+impl ShaderFile {
+    /// Creates description of the file with the shader of given stage and entry point.
+    pub fn new(stage: ShaderStage, path: impl Into<PathBuf>, entry_point: &str) -> Self {
+        Self {
+            stage,
+            path: path.into(),
+            entry_point: entry_point.to_string(),
+        }
+    }
+
+    /// Time of the last modification of the file, if it could be retrieved.
+    fn modified(&self) -> Option<SystemTime> {
+        let metadata = std::fs::metadata(&self.path).ok()?;
+        metadata.modified().ok()
+    }
+}
+
+/// Loads shaders of all files into one set and validates it.
+fn load(files: &[ShaderFile]) -> Result<ShaderSet, ShaderSetError> {
+    let set = files.iter().try_fold(ShaderSet::new(), |set, file| {
+        set.with_stage_file(file.stage, &file.path, &file.entry_point)
+    })?;
+    set.validate()?;
+    Ok(set)
+}
+
+#[derive(Debug)]
+struct WatchedState {
+    files: Vec<ShaderFile>,
+    /// Modification times of files at the last load (even if it failed).
+    modified: Vec<Option<SystemTime>>,
+    set: ShaderSet,
+    generation: u64,
+    /// Error of the last reload, if it failed.
+    error: Option<String>,
+}
+
+impl WatchedState {
+    /// Reloads shaders if any of files was modified since the last load.
+    ///
+    /// Returns `true` if shaders were reloaded successfully.
+    ///
+    fn reload_if_modified(&mut self) -> bool {
+        let modified: Vec<_> = self.files.iter().map(ShaderFile::modified).collect();
+        if modified == self.modified {
+            return false;
+        }
+        // Times are recorded even if reload fails, so broken shaders
+        // are not reloaded again until they are written next time.
+        self.modified = modified;
+        match self::load(&self.files) {
+            Ok(set) => {
+                self.set = set;
+                self.generation += 1;
+                self.error = None;
+                true
+            }
+            Err(error) => {
+                let paths: Vec<_> = self.files.iter().map(|file| file.path.display()).collect();
+                log::error!("failed to reload shaders {:?}: {}", paths, error);
+                self.error = Some(error.to_string());
+                false
+            }
+        }
+    }
+}
+
+/// Handle of the shader set which is reloaded by [`ShaderWatcher`] when its files change.
+///
+/// Pipelines which were built from the set should be rebuilt when its
+/// [generation](WatchedShaders::generation) changes, for example,
+/// in [`RenderHook::prepare`](crate::graphics::RenderHook::prepare) before the next frame.
+/// If reloaded shaders are invalid, the previous set is kept.
+///
+/// Clones of the handle (for example, one owned by the render hook) share the set,
+/// which is not reloaded anymore when all of them are dropped.
+///
+#[derive(Debug, Clone)]
+pub struct WatchedShaders {
+    state: Arc<Mutex<WatchedState>>,
+}
+
+impl WatchedShaders {
+    /// Shaders which were loaded last time successfully.
+    pub fn shader_set(&self) -> ShaderSet {
+        self.state.lock().unwrap().set.clone()
+    }
+
+    /// Count of successful reloads of the set, which is `0` after the first load.
+    pub fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Returns shaders if they were reloaded since given generation, with the new generation.
+    pub fn reloaded_since(&self, generation: u64) -> Option<(ShaderSet, u64)> {
+        let state = self.state.lock().unwrap();
+        (state.generation != generation).then(|| (state.set.clone(), state.generation))
+    }
+
+    /// Error of the last reload, if shaders were invalid.
+    pub fn error(&self) -> Option<String> {
+        self.state.lock().unwrap().error.clone()
+    }
+
+    /// Files which shaders are loaded from.
+    pub fn files(&self) -> Vec<ShaderFile> {
+        self.state.lock().unwrap().files.clone()
+    }
+}
+
+/// Watcher which reloads shader sets when their SPIR-V files change,
+/// so shaders could be iterated on without restarting the game.
+///
+/// Watcher of the application is polled each frame (see
+/// [`Application::shader_watcher`](crate::app::Application::shader_watcher)),
+/// but it could be polled manually too: clones share watched sets,
+/// so each change is reloaded once by whichever clone polls first.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ShaderWatcher {
+    sets: Arc<Mutex<Vec<Weak<Mutex<WatchedState>>>>>,
+}
+
+impl ShaderWatcher {
+    /// Creates watcher without any watched shaders.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads shaders from the files into one set and watches them for changes.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if shaders could not be loaded for the first time
+    /// (see [`ShaderSet::with_stage_file`] and [`ShaderSet::validate`]).
+    ///
+    pub fn watch(
+        &self,
+        files: impl IntoIterator<Item = ShaderFile>,
+    ) -> Result<WatchedShaders, ShaderSetError> {
+        let files: Vec<_> = files.into_iter().collect();
+        let modified = files.iter().map(ShaderFile::modified).collect();
+        let set = self::load(&files)?;
+        let state = Arc::new(Mutex::new(WatchedState {
+            files,
+            modified,
+            set,
+            generation: 0,
+            error: None,
+        }));
+        self.sets.lock().unwrap().push(Arc::downgrade(&state));
+        Ok(WatchedShaders { state })
+    }
+
+    /// Count of shader sets which are watched.
+    pub fn len(&self) -> usize {
+        let sets = self.sets.lock().unwrap();
+        sets.iter().filter(|state| state.strong_count() > 0).count()
+    }
+
+    /// Returns `true` if no shader sets are watched.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reloads shader sets which files were modified since their last load.
+    ///
+    /// Returns count of sets which were reloaded successfully.
+    ///
+    pub fn poll(&self) -> usize {
+        let mut sets = self.sets.lock().unwrap();
+        sets.retain(|state| state.strong_count() > 0);
+        let reloaded = sets
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|state| state.lock().unwrap().reload_if_modified())
+            .count();
+        if reloaded > 0 {
+            log::info!("{} shader set(s) were reloaded", reloaded);
+        }
+        reloaded
+    }
+}
@@ -0,0 +1,285 @@
+//! Specialization constants which are given to shaders when pipelines are created.
+//!
+//! Constants declared with `layout(constant_id = ...)` in GLSL keep their default values
+//! unless they are specialized, so one shader could be compiled into several pipelines
+//! and branches on constants are eliminated by the driver.
+//!
+//! [`SpecializationInfo`] holds values of constants at runtime. Pipelines of shaders
+//! compiled by `vulkano_shaders` are specialized with [`SpecializationInfo::apply`],
+//! which fills the generated `SpecializationConstants` structure
+//! (it works for compute pipelines as well as for graphics ones).
+//! Shaders loaded at runtime are checked with [`SpecializationInfo::validate`]
+//! against their reflection.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::{mem, ptr};
+
+use thiserror::Error;
+use vulkano::pipeline::shader::{SpecializationConstants, SpecializationMapEntry};
+
+use crate::graphics::reflect::{ConstantKind, ShaderReflection};
+
+mod tests;
+
+/// Identifier of the constant of the default fragment shader
+/// which swaps red and blue channels of the output color (boolean).
+pub const SWAP_RED_BLUE: u32 = 0;
+
+/// Error that can happen when specialization constants are given to shaders.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SpecializationError {
+    #[error("specialization constant {0} is not declared by shaders of the pipeline")]
+    UnknownConstant(u32),
+
+    #[error("specialization constant {id} is {expected}, but {actual} value was given")]
+    KindMismatch {
+        id: u32,
+        expected: ConstantKind,
+        actual: ConstantKind,
+    },
+
+    #[error("specialization constant {id} has size of {size} bytes, but only 32-bit constants are supported")]
+    UnsupportedSize { id: u32, size: usize },
+}
+
+/// Value of the specialization constant.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpecializationValue {
+    Bool(bool),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+}
+
+impl SpecializationValue {
+    /// Type of the constant which could be specialized with this value.
+    pub fn kind(self) -> ConstantKind {
+        match self {
+            Self::Bool(_) => ConstantKind::Bool,
+            Self::I32(_) => ConstantKind::Int,
+            Self::U32(_) => ConstantKind::Uint,
+            Self::F32(_) => ConstantKind::Float,
+        }
+    }
+
+    /// Bytes of the value as they are read by the shader
+    /// (booleans are 32-bit `VkBool32` values).
+    pub fn to_bytes(self) -> [u8; 4] {
+        match self {
+            Self::Bool(value) => (value as u32).to_ne_bytes(),
+            Self::I32(value) => value.to_ne_bytes(),
+            Self::U32(value) => value.to_ne_bytes(),
+            Self::F32(value) => value.to_ne_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for SpecializationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::I32(value) => write!(f, "{}", value),
+            Self::U32(value) => write!(f, "{}u", value),
+            Self::F32(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl From<bool> for SpecializationValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for SpecializationValue {
+    fn from(value: i32) -> Self {
+        Self::I32(value)
+    }
+}
+
+impl From<u32> for SpecializationValue {
+    fn from(value: u32) -> Self {
+        Self::U32(value)
+    }
+}
+
+impl From<f32> for SpecializationValue {
+    fn from(value: f32) -> Self {
+        Self::F32(value)
+    }
+}
+
+/// Values of specialization constants of the pipeline, keyed by identifiers of constants.
+///
+/// Infos are equal (and have equal hashes) if they give the same bits to the same
+/// constants, so they could be used as a part of the key of the pipeline.
+///
+#[derive(Debug, Clone, Default)]
+pub struct SpecializationInfo {
+    values: BTreeMap<u32, SpecializationValue>,
+}
+
+impl SpecializationInfo {
+    /// Creates info which keeps default values of all constants.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns info with given value of the constant with given identifier.
+    pub fn with(mut self, id: u32, value: impl Into<SpecializationValue>) -> Self {
+        self.set(id, value);
+        self
+    }
+
+    /// Sets value of the constant with given identifier, replacing the previous one.
+    pub fn set(&mut self, id: u32, value: impl Into<SpecializationValue>) {
+        self.values.insert(id, value.into());
+    }
+
+    /// Value of the constant with given identifier, if it was set.
+    pub fn get(&self, id: u32) -> Option<SpecializationValue> {
+        self.values.get(&id).copied()
+    }
+
+    /// Returns `true` if no constants were set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Identifiers and values of constants in ascending order of identifiers.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, SpecializationValue)> + '_ {
+        self.values.iter().map(|(&id, &value)| (id, value))
+    }
+
+    /// Map entries of constants which describe [data](Self::data) of them.
+    pub fn map_entries(&self) -> Vec<SpecializationMapEntry> {
+        self.values
+            .keys()
+            .enumerate()
+            .map(|(index, &constant_id)| SpecializationMapEntry {
+                constant_id,
+                offset: (index * 4) as u32,
+                size: 4,
+            })
+            .collect()
+    }
+
+    /// Values of constants one after another, in ascending order of identifiers.
+    pub fn data(&self) -> Vec<u8> {
+        self.values
+            .values()
+            .flat_map(|value| value.to_bytes())
+            .collect()
+    }
+
+    /// Fills specialization constants of the shader compiled by `vulkano_shaders`,
+    /// keeping default values of constants which were not set.
+    ///
+    /// Constants which are not declared by the shader are ignored with a warning,
+    /// because the same info could be given to several stages.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the constant which was set is not 32-bit in the shader.
+    ///
+    pub fn apply<S>(&self) -> Result<S, SpecializationError>
+    where
+        S: SpecializationConstants + Default,
+    {
+        let mut constants = S::default();
+        let descriptors = S::descriptors();
+        for (id, value) in self.iter() {
+            let entry = match descriptors.iter().find(|entry| entry.constant_id == id) {
+                Some(entry) => entry,
+                None => {
+                    log::warn!("specialization constant {} is not used by the shader", id);
+                    continue;
+                }
+            };
+            let bytes = value.to_bytes();
+            if entry.size != bytes.len() {
+                return Err(SpecializationError::UnsupportedSize {
+                    id,
+                    size: entry.size,
+                });
+            }
+            let offset = entry.offset as usize;
+            assert!(
+                offset + bytes.len() <= mem::size_of::<S>(),
+                "specialization constant {} is out of bounds of its structure",
+                id,
+            );
+            // Safety: entry describes the field of the structure (which was checked to be
+            // in its bounds), and fields of specialization constants are plain numbers.
+            unsafe {
+                let field = (&mut constants as *mut S as *mut u8).add(offset);
+                ptr::copy_nonoverlapping(bytes.as_ptr(), field, bytes.len());
+            }
+        }
+        Ok(constants)
+    }
+
+    /// Checks that all constants which were set are declared by some of given shaders
+    /// with the type of their values.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the constant is not declared by any shader
+    /// or it is declared with another type.
+    ///
+    pub fn validate(&self, shaders: &[&ShaderReflection]) -> Result<(), SpecializationError> {
+        for (id, value) in self.iter() {
+            let mut declared = shaders
+                .iter()
+                .flat_map(|shader| shader.specialization_constants())
+                .filter(|constant| constant.id == id)
+                .peekable();
+            if declared.peek().is_none() {
+                return Err(SpecializationError::UnknownConstant(id));
+            }
+            if let Some(constant) = declared.find(|constant| constant.kind != value.kind()) {
+                return Err(SpecializationError::KindMismatch {
+                    id,
+                    expected: constant.kind,
+                    actual: value.kind(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn key(&self) -> impl Iterator<Item = (u32, ConstantKind, [u8; 4])> + '_ {
+        self.iter()
+            .map(|(id, value)| (id, value.kind(), value.to_bytes()))
+    }
+}
+
+impl PartialEq for SpecializationInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.key().eq(other.key())
+    }
+}
+
+impl Eq for SpecializationInfo {}
+
+impl Hash for SpecializationInfo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.len().hash(state);
+        self.key().for_each(|key| key.hash(state));
+    }
+}
+
+impl fmt::Display for SpecializationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("{")?;
+        for (index, (id, value)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", id, value)?;
+        }
+        f.write_str("}")
+    }
+}
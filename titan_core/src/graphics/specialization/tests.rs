@@ -0,0 +1,123 @@
+#![cfg(test)]
+
+use std::collections::hash_map::DefaultHasher;
+
+use super::*;
+
+/// Structure like the one which is generated by `vulkano_shaders`.
+#[derive(Debug, Default, PartialEq)]
+#[repr(C)]
+struct Constants {
+    swap: u32,
+    scale: f32,
+    count: u32,
+    precise: f64,
+}
+
+unsafe impl SpecializationConstants for Constants {
+    fn descriptors() -> &'static [SpecializationMapEntry] {
+        static DESCRIPTORS: [SpecializationMapEntry; 4] = [
+            SpecializationMapEntry {
+                constant_id: 0,
+                offset: 0,
+                size: 4,
+            },
+            SpecializationMapEntry {
+                constant_id: 4,
+                offset: 4,
+                size: 4,
+            },
+            SpecializationMapEntry {
+                constant_id: 2,
+                offset: 8,
+                size: 4,
+            },
+            SpecializationMapEntry {
+                constant_id: 7,
+                offset: 16,
+                size: 8,
+            },
+        ];
+        &DESCRIPTORS
+    }
+}
+
+fn hash(info: &SpecializationInfo) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    info.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_map_entries() {
+    let info = SpecializationInfo::new()
+        .with(4, 1.5)
+        .with(SWAP_RED_BLUE, true)
+        .with(2, -3);
+    assert_eq!(info.get(4), Some(SpecializationValue::F32(1.5)));
+    assert_eq!(info.get(1), None);
+    assert_eq!(info.to_string(), "{0: true, 2: -3, 4: 1.5}");
+
+    // Entries are in ascending order of identifiers, booleans take 4 bytes.
+    let entries: Vec<_> = info
+        .map_entries()
+        .iter()
+        .map(|entry| (entry.constant_id, entry.offset, entry.size))
+        .collect();
+    assert_eq!(entries, [(0, 0, 4), (2, 4, 4), (4, 8, 4)]);
+    let mut data = 1u32.to_ne_bytes().to_vec();
+    data.extend((-3i32).to_ne_bytes());
+    data.extend(1.5f32.to_ne_bytes());
+    assert_eq!(info.data(), data);
+}
+
+#[test]
+fn test_key() {
+    let info = SpecializationInfo::new().with(0, true).with(1, 2u32);
+    let mut other = SpecializationInfo::new().with(1, 2u32);
+    assert_ne!(info, other);
+    other.set(0, true);
+    assert_eq!(info, other);
+    assert_eq!(hash(&info), hash(&other));
+
+    // Values with the same bits but other types are different.
+    other.set(1, 2);
+    assert_ne!(info, other);
+    // Floats are compared by their bits.
+    let nan = SpecializationInfo::new().with(0, f32::NAN);
+    assert_eq!(nan, nan.clone());
+    assert_ne!(
+        SpecializationInfo::new().with(0, 0.0),
+        SpecializationInfo::new().with(0, -0.0)
+    );
+    assert_eq!(SpecializationInfo::new(), SpecializationInfo::default());
+}
+
+#[test]
+fn test_apply() {
+    let constants: Constants = SpecializationInfo::new().apply().unwrap();
+    assert_eq!(constants, Constants::default());
+
+    // Constants which are not declared by the shader are ignored.
+    let info = SpecializationInfo::new()
+        .with(SWAP_RED_BLUE, true)
+        .with(2, 8u32)
+        .with(4, 0.25)
+        .with(9, 1);
+    let constants: Constants = info.apply().unwrap();
+    assert_eq!(
+        constants,
+        Constants {
+            swap: 1,
+            scale: 0.25,
+            count: 8,
+            precise: 0.0,
+        }
+    );
+
+    let info = SpecializationInfo::new().with(7, 1.0);
+    assert_eq!(
+        info.apply::<Constants>(),
+        Err(SpecializationError::UnsupportedSize { id: 7, size: 8 })
+    );
+}
@@ -0,0 +1,217 @@
+//! Draw system which records batches of sprites into the scene subpass.
+
+use std::sync::Arc;
+
+use slotmap::SlotMap;
+use vulkano::command_buffer::SecondaryAutoCommandBuffer;
+use vulkano::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::device::Queue;
+use vulkano::image::ImageViewAbstract;
+use vulkano::pipeline::blend::AttachmentBlend;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
+use vulkano::render_pass::Subpass;
+use vulkano::sampler::Sampler;
+
+use crate::camera::Camera2D;
+use crate::graphics::{
+    capture::{self, SubpassCapture},
+    pipeline_stats,
+    pre_rotation::PreTransform,
+    renderer::error::DescriptorSetCreationError,
+    transient::{TransientAlloc, TransientBufferPool, TransientUsage},
+    vertex::SpriteVertex,
+    viewport::Region,
+};
+
+use super::error::{SpriteDrawError, SpriteDrawSystemCreationError};
+use super::{Sprite, SpriteTextureId, TextureBatch};
+
+pub struct SpriteDrawSystem {
+    /// Queue to render.
+    graphics_queue: Arc<Queue>,
+
+    /// Graphics pipeline used for rendering of sprites.
+    pipeline: Arc<GraphicsPipeline>,
+
+    /// Cache which the pipeline is created with.
+    pipeline_cache: Arc<PipelineCache>,
+
+    /// Descriptor sets of registered textures.
+    textures: SlotMap<SpriteTextureId, Arc<dyn DescriptorSet + Send + Sync>>,
+}
+
+impl SpriteDrawSystem {
+    /// Creates new sprite draw system which draws in given subpass,
+    /// creating its pipeline with given cache.
+    pub fn new(
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: Arc<PipelineCache>,
+    ) -> Result<Self, SpriteDrawSystemCreationError> {
+        // Check queue for graphics support.
+        if !graphics_queue.family().supports_graphics() {
+            return Err(SpriteDrawSystemCreationError::QueueFamilyNotSupported);
+        }
+
+        let pipeline = Self::pipeline(&graphics_queue, subpass, &pipeline_cache)?;
+        Ok(Self {
+            graphics_queue,
+            pipeline,
+            pipeline_cache,
+            textures: SlotMap::with_key(),
+        })
+    }
+
+    fn pipeline(
+        graphics_queue: &Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: &Arc<PipelineCache>,
+    ) -> Result<Arc<GraphicsPipeline>, SpriteDrawSystemCreationError> {
+        use crate::graphics::shader::sprite::{fragment, vertex};
+
+        let device = graphics_queue.device().clone();
+        let vert_shader_module = vertex::Shader::load(device.clone())?;
+        let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+        // Sprites are drawn over the scene without depth test.
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<SpriteVertex>()
+            .vertex_shader(vert_shader_module.main_entry_point(), ())
+            .fragment_shader(frag_shader_module.main_entry_point(), ())
+            .triangle_list()
+            .viewports_scissors_dynamic(1)
+            .cull_mode_disabled()
+            .blend_collective(AttachmentBlend::alpha_blending())
+            .render_pass(subpass)
+            .build_with_cache(pipeline_cache.clone())
+            .build(device)?;
+        Ok(Arc::new(pipeline))
+    }
+
+    /// Recreates graphics pipeline for another subpass
+    /// (e.g. when the scene is rendered with another sample count).
+    ///
+    /// Registered textures are kept, because layout of their descriptor sets is the same.
+    ///
+    pub fn set_subpass(&mut self, subpass: Subpass) -> Result<(), SpriteDrawSystemCreationError> {
+        self.pipeline = Self::pipeline(&self.graphics_queue, subpass, &self.pipeline_cache)?;
+        Ok(())
+    }
+
+    /// Registers new texture to be drawn by sprites with given sampler.
+    pub fn register_texture(
+        &mut self,
+        image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
+    ) -> Result<SpriteTextureId, DescriptorSetCreationError> {
+        let layout = self.pipeline.layout().descriptor_set_layouts()[0].clone();
+        let mut builder = PersistentDescriptorSet::start(layout);
+        builder
+            .add_sampled_image(image_view, sampler)
+            .map_err(DescriptorSetCreationError::from)?;
+        let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+        Ok(self.textures.insert(Arc::new(set)))
+    }
+
+    /// Unregisters previously registered texture,
+    /// returning `false` if it was not registered.
+    pub fn unregister_texture(&mut self, texture_id: SpriteTextureId) -> bool {
+        self.textures.remove(texture_id).is_some()
+    }
+
+    /// Unregisters all textures to be drawn by sprites.
+    pub fn clear_textures(&mut self) {
+        self.textures.clear();
+    }
+
+    /// Returns iterator over keys of registered textures.
+    pub fn texture_keys(&self) -> impl Iterator<Item = SpriteTextureId> + '_ {
+        self.textures.keys()
+    }
+
+    /// Writes vertices of all sprites of the frame into one transient buffer,
+    /// returning it with batches of textures, or `None` if there are no sprites.
+    pub fn write_vertices(
+        transient: &mut TransientBufferPool,
+        sprites: &[Sprite],
+    ) -> Result<Option<(TransientAlloc, Vec<TextureBatch>)>, SpriteDrawError> {
+        if sprites.is_empty() {
+            return Ok(None);
+        }
+        let (vertices, batches) = super::batch(sprites);
+        let allocation = transient.write(TransientUsage::Vertex, &vertices)?;
+        Ok(Some((allocation, batches)))
+    }
+
+    /// Builds a secondary command buffer that draws batches of sprites in the region
+    /// from given camera, with one draw command for each texture.
+    ///
+    /// Vertices of all batches are taken from one transient buffer of the current frame.
+    /// Batches of unregistered textures are skipped.
+    /// Recorded commands are also described in the capture, if any.
+    ///
+    pub fn draw(
+        &self,
+        region: &Region,
+        pre_transform: PreTransform,
+        camera: Camera2D,
+        vertices: &TransientAlloc,
+        batches: &[TextureBatch],
+        mut capture: Option<&mut SubpassCapture>,
+    ) -> Result<SecondaryAutoCommandBuffer, SpriteDrawError> {
+        use crate::graphics::shader::sprite::vertex;
+
+        let mut builder = pipeline_stats::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            self.pipeline.subpass().clone(),
+        )?;
+
+        // Rotate content if the surface is not in its native orientation.
+        let view_projection = pre_transform.matrix() * camera.view_projection(region.aspect_ratio);
+        let push_constants = vertex::ty::PushConstants {
+            view_projection: view_projection.into(),
+        };
+        builder
+            .set_viewport(0, std::iter::once(region.viewport()))
+            .set_scissor(0, std::iter::once(region.scissor()))
+            .bind_pipeline_graphics(self.pipeline.clone())
+            .bind_vertex_buffers(0, vertices.slice::<SpriteVertex>())
+            .push_constants(self.pipeline.layout().clone(), 0, push_constants);
+        if let Some(capture) = capture.as_deref_mut() {
+            capture.set_viewport(region.origin, region.dimensions);
+            capture.bind_pipeline(capture::role_name("GraphicsPipeline", "sprites"));
+            capture.push_constants(&push_constants);
+        }
+
+        for (index, batch) in batches.iter().enumerate() {
+            let descriptor_set = match self.textures.get(batch.texture) {
+                Some(set) => set.clone(),
+                None => {
+                    log::warn!(
+                        "sprites of unregistered texture {:?} were skipped",
+                        batch.texture,
+                    );
+                    continue;
+                }
+            };
+            if let Some(capture) = capture.as_deref_mut() {
+                let texture = capture::key_name("SpriteTexture", batch.texture);
+                capture.bind_descriptor_sets(0, vec![texture]);
+                let mesh = capture::role_name("SpriteBatch", index);
+                capture.draw(mesh, None, batch.vertex_count, false, 1);
+            }
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    self.pipeline.layout().clone(),
+                    0,
+                    descriptor_set,
+                )
+                .draw(batch.vertex_count, 1, batch.first_vertex, 0)?;
+        }
+
+        Ok(builder.build()?)
+    }
+}
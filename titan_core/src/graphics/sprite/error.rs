@@ -0,0 +1,33 @@
+use thiserror::Error;
+use vulkano::command_buffer::{BuildError, DrawError};
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::OomError;
+
+use crate::graphics::TransientWriteError;
+
+#[derive(Debug, Error)]
+pub enum SpriteDrawSystemCreationError {
+    #[error("shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("queue family must support graphics operations")]
+    QueueFamilyNotSupported,
+
+    #[error("graphics pipeline creation failure: {0}")]
+    GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
+}
+
+#[derive(Debug, Error)]
+pub enum SpriteDrawError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("vertex buffer allocation failure: {0}")]
+    BufferAllocation(#[from] TransientWriteError),
+
+    #[error("draw command failure: {0}")]
+    Draw(#[from] DrawError),
+
+    #[error("draw command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+}
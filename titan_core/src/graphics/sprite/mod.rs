@@ -0,0 +1,210 @@
+//! Batched rendering of 2D sprites.
+//!
+//! Sprites are queued each frame with [`SpriteBatch`] and drawn over the scene
+//! from the orthographic [`Camera2D`] of the batch. All quads of the frame are written
+//! into one vertex buffer, grouped by their textures, and each texture is drawn
+//! with one draw call. Textures are drawn in order of their first sprite in the frame,
+//! and sprites of the same texture are drawn in order of their submission.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use slotmap::new_key_type;
+use ultraviolet::Vec2;
+
+use crate::camera::Camera2D;
+use crate::graphics::vertex::SpriteVertex;
+use crate::math::Color;
+
+pub(crate) use self::draw::SpriteDrawSystem;
+
+pub mod error;
+
+mod draw;
+mod tests;
+
+new_key_type! {
+    /// Identifier of the texture which was registered to be drawn by sprites.
+    pub struct SpriteTextureId;
+}
+
+/// Textured quad which is drawn in the 2D world for one frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprite {
+    /// Texture which the quad is filled with.
+    pub texture: SpriteTextureId,
+    /// Position of the center of the quad in the world.
+    pub position: Vec2,
+    /// Width and height of the quad (in world units).
+    pub size: Vec2,
+    /// Counterclockwise rotation of the quad around its center (in radians).
+    pub rotation: f32,
+    /// UV position of the top left corner of the quad on the texture.
+    pub uv_min: Vec2,
+    /// UV position of the bottom right corner of the quad on the texture.
+    pub uv_max: Vec2,
+    /// Color which multiplies colors of the texture.
+    pub color: Color,
+}
+
+impl Sprite {
+    /// Creates white quad of given size which shows the whole texture.
+    pub fn new(texture: SpriteTextureId, position: Vec2, size: Vec2) -> Self {
+        Self {
+            texture,
+            position,
+            size,
+            rotation: 0.0,
+            uv_min: Vec2::zero(),
+            uv_max: Vec2::one(),
+            color: Color::WHITE,
+        }
+    }
+
+    /// Returns sprite with given rotation (in radians).
+    pub fn with_rotation(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// Returns sprite which shows given rectangle of the texture (for example, of an atlas).
+    pub fn with_uv(self, uv_min: Vec2, uv_max: Vec2) -> Self {
+        Self {
+            uv_min,
+            uv_max,
+            ..self
+        }
+    }
+
+    /// Returns sprite with given color.
+    pub fn with_color(self, color: impl Into<Color>) -> Self {
+        let color = color.into();
+        Self { color, ..self }
+    }
+
+    /// Two triangles of the quad, counterclockwise in the world.
+    fn vertices(&self) -> [SpriteVertex; 6] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let half = self.size / 2.0;
+        let corner = |x: f32, y: f32, u: f32, v: f32| {
+            let (x, y) = (x * half.x, y * half.y);
+            let offset = Vec2::new(x * cos - y * sin, x * sin + y * cos);
+            SpriteVertex::new(self.position + offset, Vec2::new(u, v), self.color)
+        };
+        let (min, max) = (self.uv_min, self.uv_max);
+        // Top of the texture is at the top of the quad, where Y of the world is greater.
+        let bottom_left = corner(-1.0, -1.0, min.x, max.y);
+        let bottom_right = corner(1.0, -1.0, max.x, max.y);
+        let top_right = corner(1.0, 1.0, max.x, min.y);
+        let top_left = corner(-1.0, 1.0, min.x, min.y);
+        [
+            bottom_left,
+            bottom_right,
+            top_right,
+            top_right,
+            top_left,
+            bottom_left,
+        ]
+    }
+
+    fn is_finite(&self) -> bool {
+        let values = [
+            self.position.x,
+            self.position.y,
+            self.size.x,
+            self.size.y,
+            self.rotation,
+        ];
+        values.iter().all(|value| value.is_finite())
+    }
+}
+
+#[derive(Debug, Default)]
+struct SpriteState {
+    sprites: Vec<Sprite>,
+    camera: Camera2D,
+}
+
+/// Handle which queues sprites to be drawn in the next frame.
+///
+/// Clones share one queue of sprites, which is emptied by the renderer each frame.
+#[derive(Debug, Default, Clone)]
+pub struct SpriteBatch {
+    state: Arc<Mutex<SpriteState>>,
+}
+
+impl SpriteBatch {
+    /// Queues sprite to be drawn in the next frame.
+    ///
+    /// Sprites with non-finite position, size or rotation are skipped.
+    ///
+    pub fn draw(&self, sprite: Sprite) {
+        if !sprite.is_finite() {
+            log::warn!("sprite was skipped because it is not finite");
+            return;
+        }
+        self.state.lock().unwrap().sprites.push(sprite);
+    }
+
+    /// Replaces camera which sprites are drawn from.
+    pub fn set_camera(&self, camera: Camera2D) {
+        self.state.lock().unwrap().camera = camera;
+    }
+
+    /// Camera which sprites are drawn from.
+    pub fn camera(&self) -> Camera2D {
+        self.state.lock().unwrap().camera
+    }
+
+    /// Count of sprites which are queued for the next frame.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().sprites.len()
+    }
+
+    /// Returns `true` if there are no sprites queued for the next frame.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes all queued sprites to be drawn in the current frame with the camera.
+    pub(crate) fn take(&self) -> (Vec<Sprite>, Camera2D) {
+        let mut state = self.state.lock().unwrap();
+        (mem::take(&mut state.sprites), state.camera)
+    }
+}
+
+/// Vertices of the texture which are drawn with one draw call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct TextureBatch {
+    pub texture: SpriteTextureId,
+    pub first_vertex: u32,
+    pub vertex_count: u32,
+}
+
+/// Writes vertices of all sprites into one list, grouping them by their textures.
+pub(crate) fn batch(sprites: &[Sprite]) -> (Vec<SpriteVertex>, Vec<TextureBatch>) {
+    let mut textures: Vec<(SpriteTextureId, Vec<&Sprite>)> = Vec::new();
+    let mut indices = HashMap::new();
+    for sprite in sprites {
+        let index = *indices.entry(sprite.texture).or_insert_with(|| {
+            textures.push((sprite.texture, Vec::new()));
+            textures.len() - 1
+        });
+        textures[index].1.push(sprite);
+    }
+
+    let mut vertices = Vec::with_capacity(sprites.len() * 6);
+    let batches = textures
+        .into_iter()
+        .map(|(texture, group)| {
+            let first_vertex = vertices.len() as u32;
+            vertices.extend(group.into_iter().flat_map(Sprite::vertices));
+            TextureBatch {
+                texture,
+                first_vertex,
+                vertex_count: vertices.len() as u32 - first_vertex,
+            }
+        })
+        .collect();
+    (vertices, batches)
+}
@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use std::f32::consts::FRAC_PI_2;
+
+use slotmap::SlotMap;
+use ultraviolet::Vec2;
+
+use super::*;
+
+fn textures(count: usize) -> Vec<SpriteTextureId> {
+    let mut textures = SlotMap::<SpriteTextureId, ()>::with_key();
+    (0..count).map(|_| textures.insert(())).collect()
+}
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-5, "{:?} != {:?}", actual, expected);
+}
+
+#[test]
+fn test_batch_by_texture() {
+    let textures = textures(2);
+    let (a, b) = (textures[0], textures[1]);
+    let sprite = |texture, x: f32| Sprite::new(texture, Vec2::new(x, 0.0), Vec2::one());
+    let sprites = [
+        sprite(b, 0.0),
+        sprite(a, 1.0),
+        sprite(b, 2.0),
+        sprite(a, 3.0),
+    ];
+
+    let (vertices, batches) = batch(&sprites);
+    assert_eq!(vertices.len(), 24);
+    let expected = [
+        TextureBatch {
+            texture: b,
+            first_vertex: 0,
+            vertex_count: 12,
+        },
+        TextureBatch {
+            texture: a,
+            first_vertex: 12,
+            vertex_count: 12,
+        },
+    ];
+    assert_eq!(batches, expected);
+
+    // Sprites of the same texture keep order of their submission.
+    let centers: Vec<_> = vertices
+        .chunks(6)
+        .map(|quad| (*quad[0].position + *quad[2].position) / 2.0)
+        .collect();
+    let xs: Vec<_> = centers.iter().map(|center| center.x).collect();
+    assert_eq!(xs, [0.0, 2.0, 1.0, 3.0]);
+
+    let (vertices, batches) = batch(&[]);
+    assert!(vertices.is_empty());
+    assert!(batches.is_empty());
+}
+
+#[test]
+fn test_sprite_vertices() {
+    let texture = textures(1)[0];
+    let sprite = Sprite::new(texture, Vec2::new(1.0, 2.0), Vec2::new(4.0, 2.0))
+        .with_uv(Vec2::new(0.25, 0.5), Vec2::new(0.75, 1.0))
+        .with_color(Color::RED);
+
+    let vertices = sprite.vertices();
+    assert_near(*vertices[0].position, Vec2::new(-1.0, 1.0));
+    assert_near(*vertices[2].position, Vec2::new(3.0, 3.0));
+    assert_near(*vertices[4].position, Vec2::new(-1.0, 3.0));
+    assert_near(*vertices[0].uv, Vec2::new(0.25, 1.0));
+    assert_near(*vertices[2].uv, Vec2::new(0.75, 0.5));
+    assert!(vertices.iter().all(|vertex| vertex.color == Color::RED));
+    assert_eq!(*vertices[0].position, *vertices[5].position);
+    assert_eq!(*vertices[2].position, *vertices[3].position);
+
+    // Rotation by a quarter turn moves the bottom left corner to the bottom right.
+    let vertices = sprite.with_rotation(FRAC_PI_2).vertices();
+    assert_near(*vertices[0].position, Vec2::new(2.0, 0.0));
+    assert_near(*vertices[2].position, Vec2::new(0.0, 4.0));
+}
+
+#[test]
+fn test_sprite_batch() {
+    let texture = textures(1)[0];
+    let batch = SpriteBatch::default();
+    let camera = Camera2D::new(Vec2::new(1.0, 1.0), 10.0);
+    batch.set_camera(camera);
+
+    batch.draw(Sprite::new(texture, Vec2::zero(), Vec2::one()));
+    batch.draw(Sprite::new(texture, Vec2::broadcast(f32::NAN), Vec2::one()));
+    batch.draw(Sprite::new(texture, Vec2::zero(), Vec2::one()).with_rotation(f32::INFINITY));
+    assert_eq!(batch.len(), 1);
+
+    let (sprites, taken_camera) = batch.clone().take();
+    assert_eq!(sprites.len(), 1);
+    assert_eq!(taken_camera, camera);
+    assert!(batch.is_empty());
+    assert_eq!(batch.camera(), camera);
+}
@@ -0,0 +1,91 @@
+//! Statistics of rendering process for game engine.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{
+    allocator::MemoryStats, descriptor::DescriptorStats, gpu_profiler::GpuTimings,
+    pipeline_stats::PipelineStatistics, streaming::StreamingStats, transient::TransientStats,
+};
+
+/// Statistics of frames rendered by the renderer.
+#[derive(Debug, Default, Clone)]
+pub struct FrameStats {
+    /// Count of frames which were presented.
+    pub frames: u64,
+    /// Time spent on rendering of the last frame (CPU side).
+    pub frame_time: Duration,
+    /// Count of frames which were skipped because swapchain image acquisition
+    /// or wait for the previous frame timed out.
+    pub frames_skipped: u64,
+    /// Count of swapchain recreations (for example, caused by window resizing).
+    pub swapchain_recreations: u64,
+    /// Count of draw calls of each viewport in the last frame.
+    pub viewport_draws: Vec<u32>,
+    /// Statistics of texture streaming in the last frame.
+    pub streaming: StreamingStats,
+    /// Statistics of descriptor sets allocated in the last frame.
+    pub descriptors: DescriptorStats,
+    /// Statistics of transient buffers (like UI vertices) allocated in the last frame.
+    pub transient: TransientStats,
+    /// Statistics of device memory which was allocated by the renderer in the last frame.
+    pub memory: MemoryStats,
+    /// Pipeline statistics of the latest frame which results were read.
+    ///
+    /// It is `None` if statistics were not enabled in the configuration
+    /// or are not supported by the device.
+    ///
+    pub pipeline_stats: Option<PipelineStatistics>,
+    /// Time spent by the device on render passes of the latest frame which timestamps were read.
+    ///
+    /// It is `None` if GPU profiling was not enabled in the configuration
+    /// or is not supported by the device.
+    ///
+    pub gpu_timings: Option<GpuTimings>,
+    /// Live graphics objects of the renderer grouped by their type.
+    pub resources: Vec<ResourceList>,
+    /// Results of frustum culling which were reported for the last frame.
+    pub culling: CullingStats,
+}
+
+/// Counts of entities which passed or failed frustum culling.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CullingStats {
+    /// Count of entities which were submitted for drawing.
+    pub submitted: u32,
+    /// Count of entities which were skipped because they are outside of the frustum.
+    pub culled: u32,
+    /// Count of entities which were skipped because they are hidden
+    /// or not on layers of the camera.
+    pub hidden: u32,
+}
+
+/// Handle through which results of frustum culling are reported to the renderer,
+/// so they appear in [`FrameStats::culling`] of the next rendered frame.
+///
+/// Clones share one report: the last one made through any of them before the frame is used.
+#[derive(Debug, Default, Clone)]
+pub struct CullingReport {
+    stats: Arc<Mutex<CullingStats>>,
+}
+
+impl CullingReport {
+    /// Replaces reported results of culling.
+    pub fn set(&self, stats: CullingStats) {
+        *self.stats.lock().unwrap() = stats;
+    }
+
+    /// Returns last reported results of culling.
+    pub fn get(&self) -> CullingStats {
+        *self.stats.lock().unwrap()
+    }
+}
+
+/// Live graphics objects of one type with their keys.
+#[derive(Debug, Default, Clone)]
+pub struct ResourceList {
+    /// Name of the type of objects.
+    pub name: &'static str,
+    /// Keys of all live objects of this type.
+    pub keys: Vec<String>,
+}
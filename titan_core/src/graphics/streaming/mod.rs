@@ -0,0 +1,465 @@
+//! Streaming of textures from disk with priorities and memory budgets.
+//!
+//! Textures are read and decoded by blocking tasks of the [task pool](crate::task::TaskPool),
+//! then uploaded by the renderer through the transfer queue at the beginning of each frame,
+//! no more than upload budget per frame. When resident textures exceed memory budget,
+//! least recently needed ones are evicted.
+
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use egui::TextureId;
+use slotmap::{new_key_type, SlotMap};
+
+use crate::graphics::texture::{self, TextureData, TextureLoadError};
+use crate::task::TaskPool;
+
+mod tests;
+
+/// Default count of bytes which could be uploaded per frame.
+pub const DEFAULT_UPLOAD_BUDGET: u64 = 4 * 1024 * 1024;
+
+/// Default count of bytes of textures which could be resident at once.
+pub const DEFAULT_MEMORY_BUDGET: u64 = 256 * 1024 * 1024;
+
+new_key_type! {
+    /// Identifier of the asset registered in [`StreamingManager`].
+    pub struct StreamId;
+}
+
+/// Configuration of texture streaming.
+#[derive(Debug, Copy, Clone)]
+pub struct StreamingConfig {
+    /// Max count of bytes uploaded per frame.
+    ///
+    /// At least one texture is uploaded per frame even if it is larger than the budget.
+    pub upload_budget: u64,
+    /// Max count of bytes of resident textures.
+    pub memory_budget: u64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            upload_budget: DEFAULT_UPLOAD_BUDGET,
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+        }
+    }
+}
+
+/// State of the streamable asset.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreamState {
+    /// Asset is not loaded (or was evicted).
+    Unloaded,
+    /// Asset is read and decoded by the blocking task.
+    Loading,
+    /// Asset was decoded and waits for upload.
+    Pending,
+    /// Asset is uploaded to the GPU in the current frame.
+    Uploading,
+    /// Asset is resident on the GPU and could be drawn in UI.
+    Resident(TextureId),
+    /// Asset could not be loaded.
+    Failed,
+}
+
+/// Statistics of texture streaming in the last frame.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct StreamingStats {
+    /// Count of bytes uploaded to the GPU.
+    pub uploaded_bytes: u64,
+    /// Count of bytes evicted from the GPU.
+    pub evicted_bytes: u64,
+    /// Count of assets which are loading or wait for upload.
+    pub queue_depth: usize,
+    /// Count of bytes of resident textures.
+    pub resident_bytes: u64,
+}
+
+/// Closure which returns priority of the asset in the current frame.
+///
+/// Lower values are streamed first (for example, distance to the camera).
+/// `None` means that asset is not needed now, so it will not be loaded
+/// and it could be evicted.
+pub type PriorityFn = Box<dyn FnMut(StreamId) -> Option<f32> + Send>;
+
+/// Texture which must be uploaded by the renderer.
+pub(crate) struct Upload {
+    pub id: StreamId,
+    pub texture: TextureData,
+}
+
+/// Work of the renderer in the current frame.
+#[derive(Default)]
+pub(crate) struct FramePlan {
+    pub uploads: Vec<Upload>,
+    pub evictions: Vec<TextureId>,
+}
+
+struct Asset {
+    path: PathBuf,
+    state: StreamState,
+    texture: Option<TextureData>,
+    /// Size of the texture (in bytes), known after it was decoded.
+    bytes: u64,
+    priority: Option<f32>,
+    /// Index of the last frame in which asset was needed.
+    last_needed: u64,
+    /// Asset was unregistered while it was uploaded.
+    removed: bool,
+}
+
+/// Bookkeeping of streamable assets which does not depend on IO or the GPU.
+struct StreamingState {
+    config: StreamingConfig,
+    assets: SlotMap<StreamId, Asset>,
+    priority: Option<PriorityFn>,
+    frame: u64,
+    resident_bytes: u64,
+    /// Textures of assets which were unregistered while resident.
+    released: Vec<TextureId>,
+    stats: StreamingStats,
+}
+
+impl StreamingState {
+    fn new(config: StreamingConfig) -> Self {
+        Self {
+            config,
+            assets: SlotMap::with_key(),
+            priority: None,
+            frame: 0,
+            resident_bytes: 0,
+            released: Vec::new(),
+            stats: StreamingStats::default(),
+        }
+    }
+
+    fn register(&mut self, path: PathBuf) -> StreamId {
+        self.assets.insert(Asset {
+            path,
+            state: StreamState::Unloaded,
+            texture: None,
+            bytes: 0,
+            priority: None,
+            last_needed: 0,
+            removed: false,
+        })
+    }
+
+    fn unregister(&mut self, id: StreamId) {
+        let asset = match self.assets.get_mut(id) {
+            Some(asset) => asset,
+            None => return,
+        };
+        match asset.state {
+            // Asset must not be destroyed mid-upload, so it is removed after upload.
+            StreamState::Uploading => asset.removed = true,
+            StreamState::Resident(texture_id) => {
+                self.resident_bytes -= asset.bytes;
+                self.released.push(texture_id);
+                self.assets.remove(id);
+            }
+            _ => {
+                self.assets.remove(id);
+            }
+        }
+    }
+
+    fn state(&self, id: StreamId) -> Option<StreamState> {
+        self.assets
+            .get(id)
+            .filter(|asset| !asset.removed)
+            .map(|asset| asset.state)
+    }
+
+    /// Receives decoded texture (or an error) from the blocking task.
+    fn receive(&mut self, id: StreamId, result: Result<TextureData, TextureLoadError>) {
+        let asset = match self.assets.get_mut(id) {
+            Some(asset) if asset.state == StreamState::Loading => asset,
+            // Asset was unregistered while it was loading.
+            _ => return,
+        };
+        match result {
+            Ok(texture) => {
+                asset.bytes = texture.byte_len();
+                asset.texture = Some(texture);
+                asset.state = StreamState::Pending;
+            }
+            Err(error) => {
+                log::warn!("failed to load texture {:?}: {}", asset.path, error);
+                asset.state = StreamState::Failed;
+            }
+        }
+    }
+
+    /// Updates priorities of assets and returns assets which must be loaded.
+    fn update(&mut self) -> Vec<(StreamId, PathBuf)> {
+        self.frame += 1;
+        let frame = self.frame;
+        let mut loads = Vec::new();
+        for (id, asset) in &mut self.assets {
+            asset.priority = match &mut self.priority {
+                Some(priority) => priority(id),
+                None => Some(0.0),
+            };
+            if asset.priority.is_none() {
+                continue;
+            }
+            asset.last_needed = frame;
+            if asset.state == StreamState::Unloaded {
+                asset.state = StreamState::Loading;
+                loads.push((id, asset.path.clone()));
+            }
+        }
+        loads
+    }
+
+    /// Chooses textures to upload and to evict in the current frame.
+    fn plan(&mut self) -> FramePlan {
+        let mut evictions = std::mem::take(&mut self.released);
+        let mut evicted_bytes = 0;
+
+        // Needed textures with the lowest priority value are uploaded first.
+        let mut pending: Vec<_> = self
+            .assets
+            .iter()
+            .filter_map(|(id, asset)| match (asset.state, asset.priority) {
+                (StreamState::Pending, Some(priority)) => Some((id, priority, asset.bytes)),
+                _ => None,
+            })
+            .collect();
+        pending.sort_by(|(_, a, _), (_, b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let mut upload_bytes = 0;
+        let mut chosen = Vec::new();
+        for (id, _, bytes) in pending {
+            let fits = upload_bytes + bytes <= self.config.upload_budget;
+            if !fits && !chosen.is_empty() {
+                break;
+            }
+            upload_bytes += bytes;
+            chosen.push((id, bytes));
+        }
+
+        // Least recently needed textures are evicted to make room for uploads.
+        // Textures needed in this frame are never evicted, nor uploading ones.
+        let mut resident: Vec<_> = self
+            .assets
+            .iter()
+            .filter_map(|(id, asset)| match asset.state {
+                StreamState::Resident(_) if asset.last_needed < self.frame => {
+                    Some((id, asset.last_needed))
+                }
+                _ => None,
+            })
+            .collect();
+        resident.sort_by_key(|&(_, last_needed)| last_needed);
+        let mut resident = resident.into_iter();
+        while self.resident_bytes + upload_bytes > self.config.memory_budget {
+            let (id, _) = match resident.next() {
+                Some(next) => next,
+                None => break,
+            };
+            let asset = &mut self.assets[id];
+            if let StreamState::Resident(texture_id) = asset.state {
+                evictions.push(texture_id);
+            }
+            asset.state = StreamState::Unloaded;
+            self.resident_bytes -= asset.bytes;
+            evicted_bytes += asset.bytes;
+        }
+        // Uploads which still do not fit wait for the next frames.
+        while self.resident_bytes + upload_bytes > self.config.memory_budget {
+            match chosen.pop() {
+                Some((_, bytes)) => upload_bytes -= bytes,
+                None => break,
+            }
+        }
+
+        let uploads = chosen
+            .into_iter()
+            .map(|(id, _)| {
+                let asset = &mut self.assets[id];
+                asset.state = StreamState::Uploading;
+                let texture = asset
+                    .texture
+                    .take()
+                    .expect("pending asset must have a texture");
+                Upload { id, texture }
+            })
+            .collect();
+        self.stats = StreamingStats {
+            uploaded_bytes: upload_bytes,
+            evicted_bytes,
+            queue_depth: self.queue_depth(),
+            resident_bytes: self.resident_bytes,
+        };
+        FramePlan { uploads, evictions }
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.assets
+            .values()
+            .filter(|asset| matches!(asset.state, StreamState::Loading | StreamState::Pending))
+            .count()
+    }
+
+    /// Marks asset as resident after its upload.
+    ///
+    /// Returns texture which must be unregistered if asset was removed mid-upload.
+    fn finish_upload(&mut self, id: StreamId, texture_id: TextureId) -> Option<TextureId> {
+        let asset = self.assets.get_mut(id)?;
+        if asset.removed {
+            self.assets.remove(id);
+            return Some(texture_id);
+        }
+        asset.state = StreamState::Resident(texture_id);
+        self.resident_bytes += asset.bytes;
+        self.stats.resident_bytes = self.resident_bytes;
+        None
+    }
+
+    fn fail_upload(&mut self, id: StreamId) {
+        if let Some(asset) = self.assets.get_mut(id) {
+            if asset.removed {
+                self.assets.remove(id);
+                return;
+            }
+            asset.state = StreamState::Failed;
+        }
+    }
+
+    /// Unloads all resident textures, returning them to be unregistered.
+    fn release_all(&mut self) -> Vec<TextureId> {
+        let mut textures = std::mem::take(&mut self.released);
+        for asset in self.assets.values_mut() {
+            if let StreamState::Resident(texture_id) = asset.state {
+                textures.push(texture_id);
+                asset.state = StreamState::Unloaded;
+            }
+        }
+        self.resident_bytes = 0;
+        textures
+    }
+}
+
+/// Result of reading and decoding of the texture by the blocking task.
+type LoadResult = (StreamId, Result<TextureData, TextureLoadError>);
+
+struct Streaming {
+    state: StreamingState,
+    tasks: TaskPool,
+    result_sender: Sender<LoadResult>,
+    results: Receiver<LoadResult>,
+}
+
+/// Handle which manages streaming of textures.
+///
+/// Clones share requested textures and their states with the renderer,
+/// so a texture requested through one clone could be checked through another.
+#[derive(Clone)]
+pub struct StreamingManager {
+    inner: Arc<Mutex<Streaming>>,
+}
+
+impl StreamingManager {
+    /// Creates new streaming manager which loads textures by blocking tasks of the pool.
+    pub(crate) fn new(config: StreamingConfig, tasks: TaskPool) -> Self {
+        let (result_sender, results) = mpsc::channel();
+        let streaming = Streaming {
+            state: StreamingState::new(config),
+            tasks,
+            result_sender,
+            results,
+        };
+        Self {
+            inner: Arc::new(Mutex::new(streaming)),
+        }
+    }
+
+    /// Task pool which loads textures.
+    pub fn task_pool(&self) -> TaskPool {
+        self.inner.lock().unwrap().tasks.clone()
+    }
+
+    /// Registers image file which will be streamed when it is needed.
+    ///
+    /// Files with `.ktx2` extension are streamed as block-compressed textures
+    /// (see [`texture::load`]).
+    ///
+    pub fn register(&self, path: impl Into<PathBuf>) -> StreamId {
+        self.inner.lock().unwrap().state.register(path.into())
+    }
+
+    /// Unregisters the asset, evicting its texture.
+    ///
+    /// Texture which is uploaded in the current frame is destroyed after its upload.
+    pub fn unregister(&self, id: StreamId) {
+        self.inner.lock().unwrap().state.unregister(id)
+    }
+
+    /// Sets closure which returns priority of each asset on each frame.
+    ///
+    /// Without the closure, all registered assets are needed with the same priority.
+    pub fn set_priority_fn(&self, priority: impl FnMut(StreamId) -> Option<f32> + Send + 'static) {
+        self.inner.lock().unwrap().state.priority = Some(Box::new(priority));
+    }
+
+    /// Returns state of the asset, or `None` if it was not registered.
+    pub fn state(&self, id: StreamId) -> Option<StreamState> {
+        self.inner.lock().unwrap().state.state(id)
+    }
+
+    /// Returns texture of the asset if it is resident on the GPU.
+    pub fn texture(&self, id: StreamId) -> Option<TextureId> {
+        match self.state(id)? {
+            StreamState::Resident(texture_id) => Some(texture_id),
+            _ => None,
+        }
+    }
+
+    /// Statistics of streaming in the last frame.
+    pub fn stats(&self) -> StreamingStats {
+        self.inner.lock().unwrap().state.stats
+    }
+
+    /// Receives decoded textures, spawns new loads into the task pool
+    /// and chooses textures to upload and to evict in the current frame.
+    pub(crate) fn plan(&self) -> FramePlan {
+        let mut streaming = self.inner.lock().unwrap();
+        let Streaming {
+            state,
+            tasks,
+            result_sender,
+            results,
+        } = &mut *streaming;
+        while let Ok((id, result)) = results.try_recv() {
+            state.receive(id, result);
+        }
+        for (id, path) in state.update() {
+            let result_sender = result_sender.clone();
+            tasks.spawn_blocking(move || {
+                let result = texture::load(&path);
+                // Manager could be dropped while the texture was loading.
+                let _ = result_sender.send((id, result));
+            });
+        }
+        state.plan()
+    }
+
+    pub(crate) fn finish_upload(&self, id: StreamId, texture_id: TextureId) -> Option<TextureId> {
+        let mut streaming = self.inner.lock().unwrap();
+        streaming.state.finish_upload(id, texture_id)
+    }
+
+    pub(crate) fn fail_upload(&self, id: StreamId) {
+        self.inner.lock().unwrap().state.fail_upload(id)
+    }
+
+    /// Unloads all resident textures, returning them to be unregistered.
+    pub(crate) fn release_all(&self) -> Vec<TextureId> {
+        self.inner.lock().unwrap().state.release_all()
+    }
+}
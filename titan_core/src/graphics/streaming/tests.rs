@@ -0,0 +1,202 @@
+#![cfg(test)]
+
+use egui::TextureId;
+use image::RgbaImage;
+
+use super::*;
+
+/// Image of given size in bytes (which must be a multiple of 4).
+fn image(bytes: u32) -> TextureData {
+    TextureData::Rgba(RgbaImage::new(bytes / 4, 1))
+}
+
+fn config(upload_budget: u64, memory_budget: u64) -> StreamingConfig {
+    StreamingConfig {
+        upload_budget,
+        memory_budget,
+    }
+}
+
+/// Registers an asset and makes it decoded with given size.
+fn pending(state: &mut StreamingState, bytes: u32) -> StreamId {
+    let id = state.register(PathBuf::from("texture.png"));
+    state.update();
+    state.receive(id, Ok(image(bytes)));
+    id
+}
+
+/// Uploads all planned textures, using upload index as texture identifier.
+fn upload(state: &mut StreamingState, plan: FramePlan) -> Vec<StreamId> {
+    plan.uploads
+        .into_iter()
+        .enumerate()
+        .map(|(index, upload)| {
+            assert_eq!(state.state(upload.id), Some(StreamState::Uploading));
+            let texture_id = TextureId::User(index as u64);
+            assert_eq!(state.finish_upload(upload.id, texture_id), None);
+            upload.id
+        })
+        .collect()
+}
+
+#[test]
+fn test_loads_needed_assets() {
+    let mut state = StreamingState::new(config(1024, 1024));
+    let needed = state.register(PathBuf::from("needed.png"));
+    let unneeded = state.register(PathBuf::from("unneeded.png"));
+    state.priority = Some(Box::new(move |id| (id == needed).then_some(1.0)));
+
+    let loads = state.update();
+    assert_eq!(loads, vec![(needed, PathBuf::from("needed.png"))]);
+    assert_eq!(state.state(needed), Some(StreamState::Loading));
+    assert_eq!(state.state(unneeded), Some(StreamState::Unloaded));
+
+    // Loading assets are not sent to IO threads again.
+    assert!(state.update().is_empty());
+}
+
+#[test]
+fn test_upload_budget() {
+    let mut state = StreamingState::new(config(100, 1024));
+    let first = pending(&mut state, 60);
+    let second = pending(&mut state, 60);
+    state.update();
+
+    // Only one texture fits into the upload budget of the frame.
+    let plan = state.plan();
+    assert_eq!(plan.uploads.len(), 1);
+    assert_eq!(state.stats.uploaded_bytes, 60);
+    assert_eq!(state.stats.queue_depth, 1);
+    upload(&mut state, plan);
+
+    state.update();
+    let plan = state.plan();
+    assert_eq!(plan.uploads.len(), 1);
+    upload(&mut state, plan);
+    assert!(matches!(state.state(first), Some(StreamState::Resident(_))));
+    assert!(matches!(
+        state.state(second),
+        Some(StreamState::Resident(_))
+    ));
+    assert_eq!(state.resident_bytes, 120);
+}
+
+#[test]
+fn test_large_texture_is_uploaded() {
+    let mut state = StreamingState::new(config(100, 1024));
+    let id = pending(&mut state, 400);
+    state.update();
+    let plan = state.plan();
+    assert_eq!(upload(&mut state, plan), vec![id]);
+}
+
+#[test]
+fn test_priority_order() {
+    let mut state = StreamingState::new(config(100, 1024));
+    let far = pending(&mut state, 100);
+    let near = pending(&mut state, 100);
+    state.priority = Some(Box::new(move |id| {
+        Some(if id == near { 1.0 } else { 10.0 })
+    }));
+    state.update();
+
+    let plan = state.plan();
+    assert_eq!(upload(&mut state, plan), vec![near]);
+    state.update();
+    let plan = state.plan();
+    assert_eq!(upload(&mut state, plan), vec![far]);
+}
+
+#[test]
+fn test_lru_eviction() {
+    let mut state = StreamingState::new(config(1024, 200));
+    let old = pending(&mut state, 100);
+    let recent = pending(&mut state, 100);
+    state.update();
+    let plan = state.plan();
+    upload(&mut state, plan);
+
+    // Old texture is needed no more, recent one is needed in each frame.
+    let new = state.register(PathBuf::from("new.png"));
+    state.priority = Some(Box::new(move |id| (id != old).then_some(1.0)));
+    state.update();
+    state.receive(new, Ok(image(100)));
+    state.update();
+
+    let plan = state.plan();
+    assert_eq!(plan.evictions.len(), 1);
+    assert_eq!(state.state(old), Some(StreamState::Unloaded));
+    assert!(matches!(
+        state.state(recent),
+        Some(StreamState::Resident(_))
+    ));
+    assert_eq!(state.stats.evicted_bytes, 100);
+    upload(&mut state, plan);
+    assert_eq!(state.resident_bytes, 200);
+}
+
+#[test]
+fn test_needed_textures_are_not_evicted() {
+    let mut state = StreamingState::new(config(1024, 100));
+    let resident = pending(&mut state, 100);
+    state.update();
+    let plan = state.plan();
+    upload(&mut state, plan);
+
+    // New texture does not fit, but resident one is still needed.
+    let new = pending(&mut state, 100);
+    state.update();
+    let plan = state.plan();
+    assert!(plan.uploads.is_empty());
+    assert!(plan.evictions.is_empty());
+    assert!(matches!(
+        state.state(resident),
+        Some(StreamState::Resident(_))
+    ));
+    assert_eq!(state.state(new), Some(StreamState::Pending));
+}
+
+#[test]
+fn test_unregister_mid_upload() {
+    let mut state = StreamingState::new(config(1024, 1024));
+    let id = pending(&mut state, 100);
+    state.update();
+    let plan = state.plan();
+    assert_eq!(plan.uploads.len(), 1);
+
+    // Asset is kept until its upload is finished.
+    state.unregister(id);
+    assert_eq!(state.state(id), None);
+    assert!(state.assets.contains_key(id));
+    let texture_id = TextureId::User(0);
+    assert_eq!(state.finish_upload(id, texture_id), Some(texture_id));
+    assert!(!state.assets.contains_key(id));
+    assert_eq!(state.resident_bytes, 0);
+}
+
+#[test]
+fn test_unregister_resident() {
+    let mut state = StreamingState::new(config(1024, 1024));
+    let id = pending(&mut state, 100);
+    state.update();
+    let plan = state.plan();
+    upload(&mut state, plan);
+
+    state.unregister(id);
+    assert_eq!(state.resident_bytes, 0);
+    state.update();
+    let plan = state.plan();
+    assert_eq!(plan.evictions, vec![TextureId::User(0)]);
+}
+
+#[test]
+fn test_unregister_while_loading() {
+    let mut state = StreamingState::new(config(1024, 1024));
+    let id = state.register(PathBuf::from("texture.png"));
+    state.update();
+    state.unregister(id);
+    // Result of IO thread is dropped.
+    state.receive(id, Ok(image(100)));
+    assert_eq!(state.state(id), None);
+    assert!(state.plan().uploads.is_empty());
+}
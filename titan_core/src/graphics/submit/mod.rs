@@ -0,0 +1,91 @@
+//! Dedicated thread which submits frames, so the event loop is not blocked by the driver.
+//!
+//! Submission and presentation of the frame may block for a long time
+//! (for example, until the swapchain image could be queued for presentation),
+//! so the renderer sends the frame to this thread and takes the result
+//! when it begins the next frame.
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+mod tests;
+
+/// Thread which runs jobs one after another in submission order.
+///
+/// Count of jobs which are queued but not taken by the thread is bounded,
+/// so [`SubmitThread::submit`] blocks when the thread falls behind.
+///
+pub(crate) struct SubmitThread<T, R> {
+    jobs: Option<SyncSender<T>>,
+    results: Receiver<R>,
+    /// Count of submitted jobs whose results were not taken yet.
+    pending: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T, R> SubmitThread<T, R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+{
+    /// Spawns the thread with given name which runs each job with the function.
+    pub fn spawn<F>(name: &str, capacity: usize, mut run: F) -> io::Result<Self>
+    where
+        F: FnMut(T) -> R + Send + 'static,
+    {
+        let (jobs, receiver) = mpsc::sync_channel::<T>(capacity);
+        let (sender, results) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                for job in receiver {
+                    if sender.send(run(job)).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            jobs: Some(jobs),
+            results,
+            pending: 0,
+            thread: Some(thread),
+        })
+    }
+
+    /// Sends the job to the thread, blocking while the queue of the thread is full.
+    ///
+    /// Job is returned back if the thread has stopped (because some job panicked).
+    ///
+    pub fn submit(&mut self, job: T) -> Result<(), T> {
+        let jobs = self.jobs.as_ref().unwrap();
+        jobs.send(job).map_err(|error| error.0)?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// Waits for the result of the oldest pending job.
+    ///
+    /// Returns `None` if there are no pending jobs or the thread has stopped.
+    ///
+    pub fn wait(&mut self) -> Option<R> {
+        if self.pending == 0 {
+            return None;
+        }
+        let result = self.results.recv().ok()?;
+        self.pending -= 1;
+        Some(result)
+    }
+}
+
+impl<T, R> Drop for SubmitThread<T, R> {
+    /// Finishes jobs which were already submitted and stops the thread.
+    fn drop(&mut self) {
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::error!("submit thread panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::*;
+
+#[test]
+fn test_results_order() {
+    let mut thread = SubmitThread::spawn("test-submit", 1, |job: u32| job * 2).unwrap();
+    assert_eq!(thread.wait(), None);
+    for job in 1..=3 {
+        thread.submit(job).unwrap();
+    }
+    let results: Vec<_> = (0..3).filter_map(|_| thread.wait()).collect();
+    assert_eq!(results, [2, 4, 6]);
+    assert_eq!(thread.wait(), None);
+}
+
+#[test]
+fn test_drop_finishes_jobs() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let counter = finished.clone();
+    let mut thread = SubmitThread::spawn("test-submit", 2, move |_: ()| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap();
+    for _ in 0..3 {
+        thread.submit(()).unwrap();
+    }
+    drop(thread);
+    assert_eq!(finished.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_stopped_thread() {
+    let mut thread = SubmitThread::spawn("test-submit", 1, |job: u32| {
+        assert_ne!(job, 0, "job failed");
+        job
+    })
+    .unwrap();
+    thread.submit(0).unwrap();
+    assert_eq!(thread.wait(), None);
+    assert_eq!(thread.submit(1), Err(1));
+}
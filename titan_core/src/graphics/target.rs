@@ -0,0 +1,95 @@
+//! Offscreen color targets which are sampled after rendering (e.g. by post-processing).
+
+use std::sync::Arc;
+
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::image::{AttachmentImage, ImageAccess, ImageCreationError, SampleCount};
+
+/// Offscreen color target of the render pass.
+///
+/// Multisampled target is resolved at the end of its subpass
+/// into single-sample texture, so it could be sampled as usual.
+///
+pub(crate) struct RenderTarget {
+    /// Image which is attached as color attachment (multisampled if the target is).
+    color: Arc<AttachmentImage>,
+    /// Single-sample image which multisampled color is resolved into.
+    resolve: Option<Arc<AttachmentImage>>,
+}
+
+impl RenderTarget {
+    /// Creates single-sample target which could be sampled.
+    pub fn new(
+        device: Arc<Device>,
+        extent: [u32; 2],
+        format: Format,
+    ) -> Result<Self, ImageCreationError> {
+        let color = AttachmentImage::sampled(device, extent, format)?;
+        Ok(Self {
+            color,
+            resolve: None,
+        })
+    }
+
+    /// Creates multisampled target which is resolved into single-sample texture.
+    ///
+    /// Multisampled image is transient (so it may not be backed by memory at all),
+    /// unless its contents must be kept to be loaded by the next frame.
+    /// If sample count is 1, target is created as with [`RenderTarget::new`].
+    ///
+    pub fn new_multisampled(
+        device: Arc<Device>,
+        extent: [u32; 2],
+        format: Format,
+        samples: SampleCount,
+        keep_contents: bool,
+    ) -> Result<Self, ImageCreationError> {
+        if samples == SampleCount::Sample1 {
+            return Self::new(device, extent, format);
+        }
+        let color = if keep_contents {
+            AttachmentImage::multisampled(device.clone(), extent, samples, format)?
+        } else {
+            AttachmentImage::transient_multisampled(device.clone(), extent, samples, format)?
+        };
+        let resolve = AttachmentImage::sampled(device, extent, format)?;
+        Ok(Self {
+            color,
+            resolve: Some(resolve),
+        })
+    }
+
+    /// Size of the target (in pixels).
+    pub fn extent(&self) -> [u32; 2] {
+        self.color.dimensions().width_height()
+    }
+
+    pub fn format(&self) -> Format {
+        self.color.format()
+    }
+
+    pub fn samples(&self) -> SampleCount {
+        self.color.samples()
+    }
+
+    /// Returns `true` if the target was created with given parameters.
+    pub fn matches(&self, extent: [u32; 2], format: Format, samples: SampleCount) -> bool {
+        self.extent() == extent && self.format() == format && self.samples() == samples
+    }
+
+    /// Image which is rendered into.
+    pub fn color(&self) -> &Arc<AttachmentImage> {
+        &self.color
+    }
+
+    /// Image which multisampled color is resolved into, if the target is multisampled.
+    pub fn resolve(&self) -> Option<&Arc<AttachmentImage>> {
+        self.resolve.as_ref()
+    }
+
+    /// Single-sample image with rendered content which could be sampled.
+    pub fn texture(&self) -> &Arc<AttachmentImage> {
+        self.resolve.as_ref().unwrap_or(&self.color)
+    }
+}
@@ -0,0 +1,182 @@
+//! Parsing of [KTX2](https://github.khronos.org/KTX-Specification/) texture containers.
+//!
+//! Only 2D textures without array layers, cubemap faces and supercompression are supported.
+//! Data format descriptor is ignored: format of the texture is taken from the header.
+
+use image::RgbaImage;
+use thiserror::Error;
+
+use super::{BlockFormat, CompressedImage};
+
+/// Key of the key/value data entry with decompressed top mip level of the texture.
+///
+/// Value of the entry is tightly packed RGBA8 texels, which are uploaded instead
+/// of compressed blocks if the device could not sample the format of the texture.
+///
+pub const FALLBACK_KEY: &str = "titanRgbaFallback";
+
+/// Identifier which every KTX2 file starts with.
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// Size of the identifier, the header and the index of the file.
+const HEADER_LEN: usize = 80;
+
+/// Size of one entry of the level index.
+const LEVEL_INDEX_ENTRY_LEN: usize = 24;
+
+/// Error that can happen when parsing KTX2 file.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Ktx2Error {
+    #[error("file is not a KTX2 file")]
+    InvalidIdentifier,
+
+    #[error("file is truncated: {0} is out of bounds")]
+    Truncated(&'static str),
+
+    #[error("supercompression scheme {0} is not supported")]
+    UnsupportedSupercompression(u32),
+
+    #[error("Vulkan format {0} is not supported")]
+    UnsupportedFormat(u32),
+
+    #[error("only 2D textures without layers and faces are supported")]
+    UnsupportedDimensions,
+
+    #[error("mip level {level} has {actual} bytes, but {expected} bytes expected")]
+    InvalidLevel {
+        level: usize,
+        expected: u64,
+        actual: u64,
+    },
+
+    #[error("fallback image has {actual} bytes, but {expected} bytes expected")]
+    InvalidFallback { expected: u64, actual: u64 },
+}
+
+/// Reader of little-endian values from the file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&self, offset: u64, len: u64, what: &'static str) -> Result<&'a [u8], Ktx2Error> {
+        let end = offset.checked_add(len).ok_or(Ktx2Error::Truncated(what))?;
+        if end > self.bytes.len() as u64 {
+            return Err(Ktx2Error::Truncated(what));
+        }
+        Ok(&self.bytes[offset as usize..end as usize])
+    }
+
+    fn u32(&self, offset: usize) -> u32 {
+        let bytes = self.bytes[offset..offset + 4].try_into().unwrap();
+        u32::from_le_bytes(bytes)
+    }
+
+    fn u64(&self, offset: usize) -> u64 {
+        let bytes = self.bytes[offset..offset + 8].try_into().unwrap();
+        u64::from_le_bytes(bytes)
+    }
+}
+
+/// Returns block format of the Vulkan format with given value, if it is supported.
+fn block_format(vk_format: u32) -> Option<BlockFormat> {
+    let format = match vk_format {
+        131 => BlockFormat::Bc1Rgb { srgb: false },
+        132 => BlockFormat::Bc1Rgb { srgb: true },
+        133 => BlockFormat::Bc1Rgba { srgb: false },
+        134 => BlockFormat::Bc1Rgba { srgb: true },
+        137 => BlockFormat::Bc3 { srgb: false },
+        138 => BlockFormat::Bc3 { srgb: true },
+        145 => BlockFormat::Bc7 { srgb: false },
+        146 => BlockFormat::Bc7 { srgb: true },
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Parses KTX2 file with block-compressed texture.
+pub fn parse(bytes: &[u8]) -> Result<CompressedImage, Ktx2Error> {
+    if !bytes.starts_with(&IDENTIFIER) {
+        return Err(Ktx2Error::InvalidIdentifier);
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(Ktx2Error::Truncated("header"));
+    }
+    let reader = Reader { bytes };
+
+    let vk_format = reader.u32(12);
+    let [width, height, depth] = [reader.u32(20), reader.u32(24), reader.u32(28)];
+    let [layers, faces, level_count] = [reader.u32(32), reader.u32(36), reader.u32(40)];
+    let supercompression = reader.u32(44);
+    if supercompression != 0 {
+        return Err(Ktx2Error::UnsupportedSupercompression(supercompression));
+    }
+    let format = self::block_format(vk_format).ok_or(Ktx2Error::UnsupportedFormat(vk_format))?;
+    if width == 0 || height == 0 || depth > 1 || layers > 1 || faces != 1 {
+        return Err(Ktx2Error::UnsupportedDimensions);
+    }
+
+    // Zero count of levels means that mip levels should be generated, so only the top one is stored.
+    let level_count = level_count.max(1) as usize;
+    let level_index = reader.slice(
+        HEADER_LEN as u64,
+        (level_count * LEVEL_INDEX_ENTRY_LEN) as u64,
+        "level index",
+    )?;
+    let level_index = Reader { bytes: level_index };
+    let mut image = CompressedImage {
+        format,
+        extent: [width, height],
+        levels: Vec::with_capacity(level_count),
+        fallback: None,
+    };
+    for level in 0..level_count {
+        let entry = level * LEVEL_INDEX_ENTRY_LEN;
+        let offset = level_index.u64(entry);
+        let len = level_index.u64(entry + 8);
+        let expected = format.level_bytes(image.level_extent(level));
+        if len != expected {
+            return Err(Ktx2Error::InvalidLevel {
+                level,
+                expected,
+                actual: len,
+            });
+        }
+        let blocks = reader.slice(offset, len, "mip level")?;
+        image.levels.push(blocks.to_vec());
+    }
+
+    let kvd_offset = reader.u32(56) as u64;
+    let kvd_len = reader.u32(60) as u64;
+    let kvd = reader.slice(kvd_offset, kvd_len, "key/value data")?;
+    if let Some(value) = self::find_value(kvd, FALLBACK_KEY)? {
+        let expected = width as u64 * height as u64 * 4;
+        let actual = value.len() as u64;
+        if actual != expected {
+            return Err(Ktx2Error::InvalidFallback { expected, actual });
+        }
+        image.fallback = RgbaImage::from_raw(width, height, value.to_vec());
+    }
+    Ok(image)
+}
+
+/// Finds value of the key/value data entry with given key.
+fn find_value<'a>(kvd: &'a [u8], key: &str) -> Result<Option<&'a [u8]>, Ktx2Error> {
+    let reader = Reader { bytes: kvd };
+    let mut offset = 0;
+    while offset + 4 <= kvd.len() {
+        let len = reader.u32(offset) as u64;
+        let entry = reader.slice(offset as u64 + 4, len, "key/value entry")?;
+        // Key is terminated by NUL, value is the rest of the entry.
+        if let Some(nul) = entry.iter().position(|&byte| byte == 0) {
+            if entry[..nul] == *key.as_bytes() {
+                return Ok(Some(&entry[nul + 1..]));
+            }
+        }
+        // Entries are padded to the multiple of 4 bytes.
+        offset += 4 + (len as usize).div_ceil(4) * 4;
+    }
+    Ok(None)
+}
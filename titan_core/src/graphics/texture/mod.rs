@@ -0,0 +1,255 @@
+//! Block-compressed textures (BC1, BC3 and BC7) loaded from KTX2 files.
+//!
+//! Compressed textures are uploaded as is if the device could sample their format,
+//! otherwise decompressed RGBA image embedded into the file (if any) is uploaded instead.
+//! Transcoding of compressed textures is not supported.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use image::{ImageError, RgbaImage};
+use thiserror::Error;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer};
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::{
+    ImageCreateFlags, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage, MipmapsCount,
+};
+use vulkano::sync::GpuFuture;
+
+use crate::graphics::error::ImageRegisterError;
+
+pub use ktx2::{Ktx2Error, FALLBACK_KEY};
+
+pub mod ktx2;
+
+mod tests;
+
+/// Width and height (in texels) of the block of all supported compressed formats.
+pub const BLOCK_SIZE: u32 = 4;
+
+/// Format of the block-compressed texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlockFormat {
+    /// BC1 without alpha, 8 bytes per block.
+    Bc1Rgb { srgb: bool },
+    /// BC1 with 1-bit alpha, 8 bytes per block.
+    Bc1Rgba { srgb: bool },
+    /// BC3 (BC1 color with separate alpha), 16 bytes per block.
+    Bc3 { srgb: bool },
+    /// BC7, 16 bytes per block.
+    Bc7 { srgb: bool },
+}
+
+impl BlockFormat {
+    /// Vulkan format of textures of this format.
+    pub fn format(self) -> Format {
+        match self {
+            Self::Bc1Rgb { srgb: false } => Format::BC1_RGB_UNORM_BLOCK,
+            Self::Bc1Rgb { srgb: true } => Format::BC1_RGB_SRGB_BLOCK,
+            Self::Bc1Rgba { srgb: false } => Format::BC1_RGBA_UNORM_BLOCK,
+            Self::Bc1Rgba { srgb: true } => Format::BC1_RGBA_SRGB_BLOCK,
+            Self::Bc3 { srgb: false } => Format::BC3_UNORM_BLOCK,
+            Self::Bc3 { srgb: true } => Format::BC3_SRGB_BLOCK,
+            Self::Bc7 { srgb: false } => Format::BC7_UNORM_BLOCK,
+            Self::Bc7 { srgb: true } => Format::BC7_SRGB_BLOCK,
+        }
+    }
+
+    /// Count of bytes of one block.
+    pub fn block_bytes(self) -> u64 {
+        match self {
+            Self::Bc1Rgb { .. } | Self::Bc1Rgba { .. } => 8,
+            Self::Bc3 { .. } | Self::Bc7 { .. } => 16,
+        }
+    }
+
+    /// Count of bytes of the mip level with given extent (in texels).
+    ///
+    /// Partial blocks on the right and bottom edges are stored as whole blocks.
+    ///
+    pub fn level_bytes(self, extent: [u32; 2]) -> u64 {
+        let [width, height] = extent.map(|size| size.max(1));
+        let blocks_x = width.div_ceil(BLOCK_SIZE);
+        let blocks_y = height.div_ceil(BLOCK_SIZE);
+        blocks_x as u64 * blocks_y as u64 * self.block_bytes()
+    }
+}
+
+/// Block-compressed texture with all of its mip levels.
+#[derive(Debug, Clone)]
+pub struct CompressedImage {
+    format: BlockFormat,
+    extent: [u32; 2],
+    levels: Vec<Vec<u8>>,
+    fallback: Option<RgbaImage>,
+}
+
+impl CompressedImage {
+    /// Format of blocks of the texture.
+    pub fn format(&self) -> BlockFormat {
+        self.format
+    }
+
+    /// Extent of the top mip level (in texels).
+    pub fn extent(&self) -> [u32; 2] {
+        self.extent
+    }
+
+    /// Extent of the mip level with given index (in texels).
+    pub fn level_extent(&self, level: usize) -> [u32; 2] {
+        self.extent.map(|size| (size >> level).max(1))
+    }
+
+    /// Blocks of each mip level, starting from the top one.
+    pub fn levels(&self) -> &[Vec<u8>] {
+        &self.levels
+    }
+
+    /// Decompressed top mip level embedded into the file, if any.
+    pub fn fallback(&self) -> Option<&RgbaImage> {
+        self.fallback.as_ref()
+    }
+
+    /// Returns `true` if the device could sample textures of this format.
+    ///
+    /// BCn formats also require `texture_compression_bc` feature of the device.
+    ///
+    pub fn is_supported(&self, device: &Device) -> bool {
+        let format = self.format.format();
+        device.enabled_features().texture_compression_bc
+            && format
+                .properties(device.physical_device())
+                .optimal_tiling_features
+                .sampled_image
+    }
+}
+
+/// Decoded texture which is ready to be uploaded to the GPU.
+#[derive(Debug, Clone)]
+pub enum TextureData {
+    /// Uncompressed RGBA texture.
+    Rgba(RgbaImage),
+    /// Block-compressed texture.
+    Compressed(CompressedImage),
+}
+
+impl TextureData {
+    /// Extent of the texture (in texels).
+    pub fn extent(&self) -> [u32; 2] {
+        match self {
+            Self::Rgba(image) => [image.width(), image.height()],
+            Self::Compressed(image) => image.extent(),
+        }
+    }
+
+    /// Count of bytes which the texture occupies on the GPU.
+    pub fn byte_len(&self) -> u64 {
+        match self {
+            Self::Rgba(image) => image.as_raw().len() as u64,
+            Self::Compressed(image) => image.levels.iter().map(|level| level.len() as u64).sum(),
+        }
+    }
+}
+
+/// Error that can happen when loading texture from file.
+#[derive(Debug, Error)]
+pub enum TextureLoadError {
+    #[error("image decoding failure: {0}")]
+    Image(#[from] ImageError),
+
+    #[error("file reading failure: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("KTX2 parsing failure: {0}")]
+    Ktx2(#[from] Ktx2Error),
+}
+
+/// Reads and decodes texture from file.
+///
+/// Files with `.ktx2` extension are parsed as KTX2 containers,
+/// all other files are decoded into RGBA images.
+///
+pub fn load(path: impl AsRef<Path>) -> Result<TextureData, TextureLoadError> {
+    let path = path.as_ref();
+    let is_ktx2 =
+        matches!(path.extension(), Some(extension) if extension.eq_ignore_ascii_case("ktx2"));
+    if is_ktx2 {
+        let bytes = std::fs::read(path)?;
+        let image = ktx2::parse(&bytes)?;
+        return Ok(TextureData::Compressed(image));
+    }
+    let image = image::open(path)?.to_rgba8();
+    Ok(TextureData::Rgba(image))
+}
+
+/// Creates sampled image with all mip levels of the compressed texture.
+///
+/// Each mip level is copied from its own staging buffer. Extent of the copy is the extent
+/// of the level, which is allowed to be not a multiple of the block size for the whole level.
+///
+/// # Errors
+///
+/// Error is returned if the device could not sample textures of this format.
+///
+pub(crate) fn upload_compressed(
+    queue: &Arc<Queue>,
+    image: &CompressedImage,
+) -> Result<Arc<ImmutableImage>, ImageRegisterError> {
+    let device = queue.device();
+    let format = image.format.format();
+    if !image.is_supported(device) {
+        return Err(ImageRegisterError::UnsupportedFormat(format));
+    }
+
+    let [width, height] = image.extent;
+    let dimensions = ImageDimensions::Dim2d {
+        width,
+        height,
+        array_layers: 1,
+    };
+    let usage = ImageUsage {
+        transfer_destination: true,
+        sampled: true,
+        ..ImageUsage::none()
+    };
+    let (texture, initializer) = ImmutableImage::uninitialized(
+        device.clone(),
+        dimensions,
+        format,
+        MipmapsCount::Specific(image.levels.len() as u32),
+        usage,
+        ImageCreateFlags::none(),
+        ImageLayout::ShaderReadOnlyOptimal,
+        device.active_queue_families(),
+    )?;
+    let initializer = Arc::new(initializer);
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device.clone(),
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )?;
+    for (level, blocks) in image.levels.iter().enumerate() {
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            blocks.iter().copied(),
+        )?;
+        let [width, height] = image.level_extent(level);
+        builder.copy_buffer_to_image_dimensions(
+            buffer,
+            initializer.clone(),
+            [0, 0, 0],
+            [width, height, 1],
+            0,
+            1,
+            level as u32,
+        )?;
+    }
+    let command_buffer = builder.build()?;
+    command_buffer.execute(queue.clone())?.flush()?;
+    Ok(texture)
+}
@@ -0,0 +1,235 @@
+#![cfg(test)]
+
+use image::Rgba;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{Device, DeviceExtensions, Features};
+use vulkano::image::{AttachmentImage, ImageUsage};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::sampler::Filter;
+use vulkano::Version;
+
+use super::*;
+
+/// BC1 texture of 6x5 texels with 3 mip levels and decompressed fallback,
+/// which is a checkerboard of pure red and blue texels.
+///
+/// Blocks use only their endpoint colors, so the texture decodes exactly on any device.
+const CHECKER: &[u8] = include_bytes!("../../../tests/fixtures/checker_bc1.ktx2");
+
+/// Offset of the count of mip levels in the header.
+const LEVEL_COUNT_OFFSET: usize = 40;
+
+fn checker_texel(x: u32, y: u32) -> Rgba<u8> {
+    match (x + y) % 2 {
+        0 => Rgba([255, 0, 0, 255]),
+        _ => Rgba([0, 0, 255, 255]),
+    }
+}
+
+fn patch_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn test_level_bytes() {
+    let bc1 = BlockFormat::Bc1Rgb { srgb: true };
+    let bc7 = BlockFormat::Bc7 { srgb: false };
+    assert_eq!(bc1.level_bytes([4, 4]), 8);
+    // Partial blocks are stored as whole ones.
+    assert_eq!(bc1.level_bytes([6, 5]), 32);
+    assert_eq!(bc1.level_bytes([1, 1]), 8);
+    assert_eq!(bc7.level_bytes([6, 5]), 64);
+    assert_eq!(bc7.level_bytes([0, 0]), 16);
+}
+
+#[test]
+fn test_parse_ktx2() {
+    let image = ktx2::parse(CHECKER).unwrap();
+    assert_eq!(image.format(), BlockFormat::Bc1Rgb { srgb: true });
+    assert_eq!(image.format().format(), Format::BC1_RGB_SRGB_BLOCK);
+    assert_eq!(image.extent(), [6, 5]);
+    let extents: Vec<_> = (0..image.levels().len())
+        .map(|level| image.level_extent(level))
+        .collect();
+    assert_eq!(extents, [[6, 5], [3, 2], [1, 1]]);
+    let lens: Vec<_> = image.levels().iter().map(Vec::len).collect();
+    assert_eq!(lens, [32, 8, 8]);
+
+    // First block: red and blue endpoints, then indices of the checkerboard.
+    assert_eq!(
+        image.levels()[0][..8],
+        [0x00, 0xF8, 0x1F, 0x00, 0x44, 0x11, 0x44, 0x11]
+    );
+
+    let fallback = image.fallback().unwrap();
+    assert_eq!(fallback.dimensions(), (6, 5));
+    for (x, y, texel) in fallback.enumerate_pixels() {
+        assert_eq!(*texel, self::checker_texel(x, y), "texel ({}, {})", x, y);
+    }
+    let data = TextureData::Compressed(image);
+    assert_eq!(data.extent(), [6, 5]);
+    assert_eq!(data.byte_len(), 48);
+}
+
+#[test]
+fn test_parse_ktx2_errors() {
+    assert_eq!(
+        ktx2::parse(b"not a texture").unwrap_err(),
+        Ktx2Error::InvalidIdentifier,
+    );
+    assert_eq!(
+        ktx2::parse(&CHECKER[..40]).unwrap_err(),
+        Ktx2Error::Truncated("header"),
+    );
+    assert_eq!(
+        ktx2::parse(&CHECKER[..CHECKER.len() - 1]).unwrap_err(),
+        Ktx2Error::Truncated("mip level"),
+    );
+
+    let mut bytes = CHECKER.to_vec();
+    patch_u32(&mut bytes, 44, 2);
+    assert_eq!(
+        ktx2::parse(&bytes).unwrap_err(),
+        Ktx2Error::UnsupportedSupercompression(2),
+    );
+
+    // R8G8B8A8_UNORM is not block-compressed.
+    let mut bytes = CHECKER.to_vec();
+    patch_u32(&mut bytes, 12, 37);
+    assert_eq!(
+        ktx2::parse(&bytes).unwrap_err(),
+        Ktx2Error::UnsupportedFormat(37)
+    );
+
+    // Cubemap has 6 faces.
+    let mut bytes = CHECKER.to_vec();
+    patch_u32(&mut bytes, 36, 6);
+    assert_eq!(
+        ktx2::parse(&bytes).unwrap_err(),
+        Ktx2Error::UnsupportedDimensions
+    );
+
+    // BC7 blocks are twice as large as BC1 ones.
+    let mut bytes = CHECKER.to_vec();
+    patch_u32(&mut bytes, 12, 145);
+    assert_eq!(
+        ktx2::parse(&bytes).unwrap_err(),
+        Ktx2Error::InvalidLevel {
+            level: 0,
+            expected: 64,
+            actual: 32,
+        },
+    );
+
+    // Only the top level is read if mip levels should be generated.
+    let mut bytes = CHECKER.to_vec();
+    patch_u32(&mut bytes, LEVEL_COUNT_OFFSET, 0);
+    assert_eq!(ktx2::parse(&bytes).unwrap().levels().len(), 1);
+}
+
+#[test]
+fn test_load_routes_ktx2() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/checker_bc1.ktx2"
+    );
+    match load(path).unwrap() {
+        TextureData::Compressed(image) => assert_eq!(image.extent(), [6, 5]),
+        TextureData::Rgba(_) => panic!("KTX2 file must be loaded as compressed texture"),
+    }
+    let error = load("missing.ktx2").unwrap_err();
+    assert!(matches!(error, TextureLoadError::Io(_)), "{}", error);
+}
+
+/// Uploads compressed texture, blits its top level into RGBA image
+/// and compares texels read back with the decoded reference.
+///
+#[test]
+#[ignore = "needs Vulkan device with BCn support"]
+fn test_compressed_readback() {
+    let instance = Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None).unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance)
+        .find(|device| device.supported_features().texture_compression_bc)
+        .expect("BCn formats are not supported by any device");
+    let queue_family = physical_device
+        .queue_families()
+        .find(|family| family.supports_graphics())
+        .unwrap();
+    let features = Features {
+        texture_compression_bc: true,
+        ..Features::none()
+    };
+    let (device, mut queues) = Device::new(
+        physical_device,
+        &features,
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    let image = ktx2::parse(CHECKER).unwrap();
+    assert!(image.is_supported(&device));
+    let texture = upload_compressed(&queue, &image).unwrap();
+
+    let [width, height] = image.extent();
+    let usage = ImageUsage {
+        transfer_destination: true,
+        transfer_source: true,
+        ..ImageUsage::none()
+    };
+    let decoded = AttachmentImage::with_usage(
+        device.clone(),
+        [width, height],
+        Format::R8G8B8A8_UNORM,
+        usage,
+    )
+    .unwrap();
+    let len = (width * height * 4) as usize;
+    let destination = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        (0..len).map(|_| 0u8),
+    )
+    .unwrap();
+    let extent = [width as i32, height as i32, 1];
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device,
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    builder
+        .blit_image(
+            texture,
+            [0, 0, 0],
+            extent,
+            0,
+            0,
+            decoded.clone(),
+            [0, 0, 0],
+            extent,
+            0,
+            0,
+            1,
+            Filter::Nearest,
+        )
+        .unwrap()
+        .copy_image_to_buffer(decoded, destination.clone())
+        .unwrap();
+    let command_buffer = builder.build().unwrap();
+    command_buffer
+        .execute(queue)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    let pixels = destination.read().unwrap().to_vec();
+    let actual = RgbaImage::from_raw(width, height, pixels).unwrap();
+    assert_eq!(actual, *image.fallback().unwrap());
+}
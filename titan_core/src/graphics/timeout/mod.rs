@@ -0,0 +1,140 @@
+//! Finite waits of the renderer which skip frames instead of blocking forever.
+//!
+//! Swapchain image acquisition and waits for frames in flight could hang
+//! if the driver or compositor misbehaves (for example, when the window is occluded).
+//! Frame is skipped on timeout while events and UI are still processed,
+//! and swapchain is recreated after several consecutive timeouts.
+
+use std::time::Duration;
+
+#[cfg(feature = "fault-injection")]
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+mod tests;
+
+/// Policy of the renderer for timeouts of swapchain image acquisition and frame waits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeoutPolicy {
+    /// Max time to wait for the next swapchain image.
+    pub acquire_timeout: Duration,
+    /// Max time to wait for the previous frame which used the same swapchain image.
+    pub fence_timeout: Duration,
+    /// Count of consecutive timeouts after which swapchain is recreated.
+    pub recreate_after: u32,
+    /// Count of swapchain recreations without any rendered frame
+    /// after which rendering fails with an error.
+    pub max_recreations: u32,
+}
+
+impl TimeoutPolicy {
+    /// Creates default policy: acquisition times out after 100 ms, frame wait after 1 s,
+    /// swapchain is recreated after 10 consecutive timeouts, rendering fails after 3 recreations.
+    pub const fn new() -> Self {
+        Self {
+            acquire_timeout: Duration::from_millis(100),
+            fence_timeout: Duration::from_secs(1),
+            recreate_after: 10,
+            max_recreations: 3,
+        }
+    }
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the renderer should do after the timeout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum TimeoutAction {
+    /// Skip the frame and try again on the next one.
+    Skip,
+    /// Skip the frame and recreate swapchain before the next one.
+    Recreate,
+    /// Give up: swapchain recreations did not help.
+    Fail,
+}
+
+/// Counter of consecutive timeouts which decides what to do on each of them.
+#[derive(Debug, Clone)]
+pub(crate) struct TimeoutTracker {
+    policy: TimeoutPolicy,
+    /// Count of timeouts since the last rendered frame or recreation.
+    timeouts: u32,
+    /// Count of recreations since the last rendered frame.
+    recreations: u32,
+}
+
+impl TimeoutTracker {
+    pub fn new(policy: TimeoutPolicy) -> Self {
+        Self {
+            policy,
+            timeouts: 0,
+            recreations: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &TimeoutPolicy {
+        &self.policy
+    }
+
+    /// Count of recreations since the last rendered frame.
+    pub fn recreations(&self) -> u32 {
+        self.recreations
+    }
+
+    /// Registers the timeout and returns what to do with it.
+    pub fn timeout(&mut self) -> TimeoutAction {
+        self.timeouts += 1;
+        if self.timeouts < self.policy.recreate_after.max(1) {
+            return TimeoutAction::Skip;
+        }
+        if self.recreations >= self.policy.max_recreations {
+            return TimeoutAction::Fail;
+        }
+        self.timeouts = 0;
+        self.recreations += 1;
+        TimeoutAction::Recreate
+    }
+
+    /// Registers the frame which was rendered without timeouts.
+    pub fn rendered(&mut self) {
+        self.timeouts = 0;
+        self.recreations = 0;
+    }
+}
+
+/// Handle which injects faults into the renderer to exercise timeout handling in tests.
+///
+/// Clones share injected faults, so a test could keep one clone while the renderer uses another.
+#[cfg(feature = "fault-injection")]
+#[derive(Debug, Default, Clone)]
+pub struct FaultInjector {
+    acquire_timeouts: Arc<AtomicU32>,
+}
+
+#[cfg(feature = "fault-injection")]
+impl FaultInjector {
+    /// Makes next `count` acquisitions of swapchain image time out.
+    pub fn inject_acquire_timeouts(&self, count: u32) {
+        self.acquire_timeouts.fetch_add(count, Ordering::SeqCst);
+    }
+
+    /// Count of injected timeouts which did not happen yet.
+    pub fn pending_acquire_timeouts(&self) -> u32 {
+        self.acquire_timeouts.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if the current acquisition must time out.
+    pub(crate) fn take_acquire_timeout(&self) -> bool {
+        self.acquire_timeouts
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(test)]
+
+use super::*;
+
+fn tracker(recreate_after: u32, max_recreations: u32) -> TimeoutTracker {
+    TimeoutTracker::new(TimeoutPolicy {
+        recreate_after,
+        max_recreations,
+        ..TimeoutPolicy::default()
+    })
+}
+
+#[test]
+fn test_recreate_then_fail() {
+    let mut tracker = tracker(3, 2);
+    let actions: Vec<_> = (0..9).map(|_| tracker.timeout()).collect();
+    use TimeoutAction::*;
+    assert_eq!(
+        actions,
+        [Skip, Skip, Recreate, Skip, Skip, Recreate, Skip, Skip, Fail],
+    );
+    assert_eq!(tracker.recreations(), 2);
+    // Failure is reported again until something is rendered.
+    assert_eq!(tracker.timeout(), Fail);
+}
+
+#[test]
+fn test_rendered_frame_resets() {
+    let mut tracker = tracker(2, 1);
+    assert_eq!(tracker.timeout(), TimeoutAction::Skip);
+    assert_eq!(tracker.timeout(), TimeoutAction::Recreate);
+    tracker.rendered();
+    assert_eq!(tracker.recreations(), 0);
+    // Timeouts which are not consecutive never lead to recreation.
+    for _ in 0..10 {
+        assert_eq!(tracker.timeout(), TimeoutAction::Skip);
+        tracker.rendered();
+    }
+
+    // Without recreations allowed, rendering fails right away.
+    let mut tracker = self::tracker(1, 0);
+    assert_eq!(tracker.timeout(), TimeoutAction::Fail);
+}
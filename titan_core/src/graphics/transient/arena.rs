@@ -0,0 +1,153 @@
+//! Bump allocation of byte ranges from blocks, without any device memory behind them.
+
+use vulkano::DeviceSize;
+
+use crate::graphics::utils::align_up;
+
+/// Count of consecutive frames which must use less than [`SHRINK_DIVISOR`]-th part
+/// of the block before the block is shrunk.
+pub(super) const SHRINK_FRAMES: u32 = 300;
+
+/// Frames which use less than this part of the block are counted for shrinking.
+pub(super) const SHRINK_DIVISOR: DeviceSize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Block {
+    capacity: DeviceSize,
+    used: DeviceSize,
+}
+
+/// Where the range of the allocation is placed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Placement {
+    /// Range is placed at given offset of the existing block.
+    Block { index: usize, offset: DeviceSize },
+    /// Range does not fit into existing blocks, so the block with given capacity
+    /// must be added with [`Arena::push_block`] before allocating again.
+    NewBlock { capacity: DeviceSize },
+}
+
+/// Bump allocator which ranges are freed all at once when the arena is reset.
+#[derive(Debug)]
+pub(super) struct Arena {
+    blocks: Vec<Block>,
+    min_capacity: DeviceSize,
+    /// Capacity of the first block which is created after reset.
+    capacity: DeviceSize,
+    /// Bytes requested by allocations since reset (without alignment padding).
+    allocated: DeviceSize,
+    /// Max bytes which were used by one frame since the block was resized.
+    high_water: DeviceSize,
+    /// Count of consecutive frames which used small part of the block.
+    frames_below: u32,
+    /// Max bytes which were used by one of these frames.
+    peak_below: DeviceSize,
+}
+
+impl Arena {
+    /// Creates arena without blocks which first block will have given capacity.
+    pub fn new(min_capacity: DeviceSize) -> Self {
+        let min_capacity = min_capacity.max(1).next_power_of_two();
+        Self {
+            blocks: Vec::new(),
+            min_capacity,
+            capacity: min_capacity,
+            allocated: 0,
+            high_water: 0,
+            frames_below: 0,
+            peak_below: 0,
+        }
+    }
+
+    /// Places the range of given size which offset is a multiple of given alignment.
+    ///
+    /// Alignment is rounded up to a power of two.
+    ///
+    pub fn allocate(&mut self, size: DeviceSize, alignment: DeviceSize) -> Placement {
+        let alignment = alignment.max(1).next_power_of_two();
+        let size = size.max(1);
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            let offset = align_up(block.used, alignment);
+            if offset + size <= block.capacity {
+                block.used = offset + size;
+                self.allocated += size;
+                return Placement::Block { index, offset };
+            }
+        }
+        let capacity = self.capacity.max(size.next_power_of_two());
+        Placement::NewBlock { capacity }
+    }
+
+    /// Adds empty block with given capacity, returning its index.
+    pub fn push_block(&mut self, capacity: DeviceSize) -> usize {
+        self.blocks.push(Block { capacity, used: 0 });
+        self.blocks.len() - 1
+    }
+
+    /// Frees all allocated ranges at once.
+    ///
+    /// If the frame did not fit into one block, or the block was mostly unused
+    /// for [`SHRINK_FRAMES`] frames, all blocks are released, so the first block
+    /// of the next frame is sized by the usage of previous frames.
+    /// Returns `true` if blocks were released.
+    ///
+    pub fn reset(&mut self) -> bool {
+        let used: DeviceSize = self.blocks.iter().map(|block| block.used).sum();
+        self.high_water = self.high_water.max(used);
+        self.allocated = 0;
+
+        let release = if self.blocks.len() > 1 {
+            // Frame exceeded capacity, so the next frame is pre-sized to fit the high-water mark.
+            self.capacity = self.high_water.next_power_of_two().max(self.min_capacity);
+            self.frames_below = 0;
+            self.peak_below = 0;
+            true
+        } else if used * SHRINK_DIVISOR < self.capacity && self.capacity > self.min_capacity {
+            self.frames_below += 1;
+            self.peak_below = self.peak_below.max(used);
+            let shrink = self.frames_below >= SHRINK_FRAMES;
+            if shrink {
+                // Recent peak is doubled, so the block is not grown again by the next spike.
+                let capacity = (self.peak_below * 2).next_power_of_two();
+                self.capacity = capacity.max(self.min_capacity);
+                self.high_water = self.peak_below;
+                self.frames_below = 0;
+                self.peak_below = 0;
+            }
+            shrink
+        } else {
+            self.frames_below = 0;
+            self.peak_below = 0;
+            false
+        };
+
+        if release {
+            self.blocks.clear();
+        } else {
+            for block in &mut self.blocks {
+                block.used = 0;
+            }
+        }
+        release
+    }
+
+    /// Bytes requested by allocations since reset.
+    pub fn allocated(&self) -> DeviceSize {
+        self.allocated
+    }
+
+    /// Capacity of all blocks (in bytes).
+    pub fn capacity(&self) -> DeviceSize {
+        self.blocks.iter().map(|block| block.capacity).sum()
+    }
+
+    /// Count of blocks of the arena.
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Max bytes which were used by one frame since the block was last resized.
+    pub fn high_water(&self) -> DeviceSize {
+        self.high_water
+    }
+}
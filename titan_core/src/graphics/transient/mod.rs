@@ -0,0 +1,246 @@
+//! Buffers for short-lived data which is written by the host once per frame.
+//!
+//! Each frame in flight owns [`TransientBufferPool`] with a few large host visible blocks
+//! per usage class (vertex, index and uniform). Allocations of the frame are bumped
+//! one after another inside of these blocks, and the whole pool is reset when the frame
+//! begins again (that is, after its fence is signaled), so no buffer is created or freed
+//! for each allocation. If the frame does not fit, one more block is created,
+//! and the next frames start with one block which fits the high-water mark.
+
+use std::mem::{align_of, size_of, size_of_val};
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferSlice, BufferUsage};
+use vulkano::DeviceSize;
+
+use self::arena::{Arena, Placement};
+use super::allocator::MemoryAllocator;
+use super::mapped::{MappedBuffer, MappedBufferCreationError, MappedBufferWriteError};
+
+mod arena;
+mod tests;
+
+/// Min capacity of each block of the pool (in bytes).
+pub const MIN_BLOCK_SIZE: DeviceSize = 64 * 1024;
+
+/// Usage class of transient allocations, each of them is allocated from its own blocks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum TransientUsage {
+    Vertex,
+    Index,
+    Uniform,
+}
+
+impl TransientUsage {
+    const ALL: [Self; 3] = [Self::Vertex, Self::Index, Self::Uniform];
+
+    fn buffer_usage(self) -> BufferUsage {
+        match self {
+            Self::Vertex => BufferUsage::vertex_buffer(),
+            Self::Index => BufferUsage::index_buffer(),
+            Self::Uniform => BufferUsage::uniform_buffer(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TransientAllocError {
+    #[error("transient block allocation failure: {0}")]
+    BlockCreation(#[from] MappedBufferCreationError),
+}
+
+/// Statistics of transient allocations of the frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TransientStats {
+    /// Bytes allocated in the frame (without alignment padding).
+    pub bytes: u64,
+    /// Capacity of all blocks of the frame (in bytes).
+    pub capacity: u64,
+    /// Count of blocks of the frame.
+    pub blocks: u32,
+    /// Count of blocks created in the frame.
+    pub blocks_created: u32,
+    /// Max bytes which were used by one frame since blocks were last resized
+    /// (summed over usage classes).
+    pub high_water: u64,
+}
+
+/// Range of the transient block which was allocated for the current frame.
+///
+/// Range is valid until the pool it was allocated from is reset.
+/// Memory of the range is written with [`TransientAlloc::write`] instead of the raw pointer,
+/// so written bytes are flushed if memory of the block is not coherent.
+///
+pub(crate) struct TransientAlloc {
+    /// Block which contains the range.
+    pub buffer: Arc<MappedBuffer<u8>>,
+    /// Offset of the range inside of the block (in bytes).
+    pub offset: DeviceSize,
+    /// Size of the range (in bytes).
+    pub size: DeviceSize,
+}
+
+impl TransientAlloc {
+    /// Writes values into the start of the range.
+    pub fn write<T>(&self, values: &[T]) -> Result<(), MappedBufferWriteError>
+    where
+        T: Copy,
+    {
+        let count = size_of_val(values);
+        if count as DeviceSize > self.size {
+            return Err(MappedBufferWriteError::OutOfBounds {
+                offset: self.offset as usize,
+                count,
+                len: self.size as usize,
+            });
+        }
+        self.buffer.write_values(self.offset as usize, values)
+    }
+
+    /// Slice of the block which covers the range, to be bound with offset of the range.
+    ///
+    /// Range must have been allocated with alignment of `T`.
+    ///
+    pub fn slice<T>(&self) -> Arc<BufferSlice<[T], Arc<MappedBuffer<u8>>>>
+    where
+        T: Send + Sync + 'static,
+    {
+        let len = self.size / size_of::<T>().max(1) as DeviceSize;
+        let end = self.offset + len * size_of::<T>() as DeviceSize;
+        let slice = BufferSlice::from_typed_buffer_access(self.buffer.clone())
+            .slice(self.offset..end)
+            .expect("transient range must be inside of its block");
+        // Safety: range is aligned for `T` and its size is a multiple of size of `T`.
+        Arc::new(unsafe { slice.reinterpret::<[T]>() })
+    }
+}
+
+/// Blocks of one usage class.
+struct UsageBlocks {
+    arena: Arena,
+    buffers: Vec<Arc<MappedBuffer<u8>>>,
+}
+
+/// Transient buffers of one frame in flight.
+pub(crate) struct TransientBufferPool {
+    allocator: Arc<MemoryAllocator>,
+    classes: [UsageBlocks; 3],
+    /// Min alignment of offsets of uniform buffers which is required by the device.
+    uniform_alignment: DeviceSize,
+    blocks_created: u32,
+}
+
+impl TransientBufferPool {
+    /// Creates pool without blocks: they are created on the first allocation.
+    pub fn new(allocator: Arc<MemoryAllocator>) -> Self {
+        let uniform_alignment = allocator
+            .device()
+            .physical_device()
+            .properties()
+            .min_uniform_buffer_offset_alignment;
+        let classes = TransientUsage::ALL.map(|_| UsageBlocks {
+            arena: Arena::new(MIN_BLOCK_SIZE),
+            buffers: Vec::new(),
+        });
+        Self {
+            allocator,
+            classes,
+            uniform_alignment,
+            blocks_created: 0,
+        }
+    }
+
+    fn class(&mut self, usage: TransientUsage) -> &mut UsageBlocks {
+        &mut self.classes[usage as usize]
+    }
+
+    /// Allocates range of given size which offset is a multiple of given alignment.
+    ///
+    /// Uniform ranges are also aligned as required by the device.
+    ///
+    pub fn allocate(
+        &mut self,
+        usage: TransientUsage,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Result<TransientAlloc, TransientAllocError> {
+        let alignment = match usage {
+            TransientUsage::Uniform => alignment.max(self.uniform_alignment),
+            _ => alignment,
+        };
+        let allocator = self.allocator.clone();
+        let mut created = 0;
+        let class = self.class(usage);
+        let (index, offset) = loop {
+            match class.arena.allocate(size, alignment) {
+                Placement::Block { index, offset } => break (index, offset),
+                Placement::NewBlock { capacity } => {
+                    let buffer =
+                        MappedBuffer::new(&allocator, usage.buffer_usage(), capacity as usize)?;
+                    class.buffers.push(buffer);
+                    class.arena.push_block(capacity);
+                    created += 1;
+                }
+            }
+        };
+        let buffer = class.buffers[index].clone();
+        self.blocks_created += created;
+        Ok(TransientAlloc {
+            buffer,
+            offset,
+            size,
+        })
+    }
+
+    /// Allocates range for the values of type `T` and writes them into it.
+    pub fn write<T>(
+        &mut self,
+        usage: TransientUsage,
+        values: &[T],
+    ) -> Result<TransientAlloc, TransientWriteError>
+    where
+        T: Copy,
+    {
+        let size = size_of_val(values) as DeviceSize;
+        let allocation = self.allocate(usage, size, align_of::<T>() as DeviceSize)?;
+        allocation.write(values)?;
+        Ok(allocation)
+    }
+
+    /// Frees all ranges of the pool at once, releasing blocks which are resized.
+    ///
+    /// # Safety
+    ///
+    /// Ranges of the pool must not be used by any pending commands.
+    ///
+    pub unsafe fn reset(&mut self) {
+        for class in &mut self.classes {
+            if class.arena.reset() {
+                class.buffers.clear();
+            }
+        }
+        self.blocks_created = 0;
+    }
+
+    /// Statistics of allocations since the pool was reset.
+    pub fn stats(&self) -> TransientStats {
+        let arenas = self.classes.iter().map(|class| &class.arena);
+        TransientStats {
+            bytes: arenas.clone().map(Arena::allocated).sum(),
+            capacity: arenas.clone().map(Arena::capacity).sum(),
+            blocks: arenas.clone().map(Arena::block_count).sum::<usize>() as u32,
+            blocks_created: self.blocks_created,
+            high_water: arenas.map(Arena::high_water).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TransientWriteError {
+    #[error("transient allocation failure: {0}")]
+    Allocation(#[from] TransientAllocError),
+
+    #[error("transient write failure: {0}")]
+    Write(#[from] MappedBufferWriteError),
+}
@@ -0,0 +1,120 @@
+#![cfg(test)]
+
+use super::arena::{Arena, Placement, SHRINK_FRAMES};
+use super::*;
+
+/// Allocates the range, adding blocks to the arena until the range fits.
+fn allocate(arena: &mut Arena, size: DeviceSize, alignment: DeviceSize) -> (usize, DeviceSize) {
+    loop {
+        match arena.allocate(size, alignment) {
+            Placement::Block { index, offset } => return (index, offset),
+            Placement::NewBlock { capacity } => {
+                arena.push_block(capacity);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bump_alignment() {
+    let mut arena = Arena::new(1024);
+    assert_eq!(allocate(&mut arena, 10, 1), (0, 0));
+    // Offsets are rounded up to the alignment, which is rounded up to a power of two.
+    assert_eq!(allocate(&mut arena, 8, 4), (0, 12));
+    assert_eq!(allocate(&mut arena, 100, 256), (0, 256));
+    assert_eq!(allocate(&mut arena, 4, 3), (0, 356));
+    assert_eq!(allocate(&mut arena, 1, 0), (0, 360));
+    assert_eq!(arena.allocated(), 123);
+    assert_eq!(arena.block_count(), 1);
+    assert_eq!(arena.capacity(), 1024);
+}
+
+#[test]
+fn test_reset_reuses_block() {
+    let mut arena = Arena::new(1024);
+    allocate(&mut arena, 600, 16);
+    assert!(!arena.reset());
+    assert_eq!(arena.allocated(), 0);
+    // Block is kept, and the next frame starts from its beginning.
+    assert_eq!(
+        arena.allocate(600, 16),
+        Placement::Block {
+            index: 0,
+            offset: 0
+        }
+    );
+    assert_eq!(arena.high_water(), 600);
+}
+
+#[test]
+fn test_growth_presizes_next_frame() {
+    let mut arena = Arena::new(1024);
+    allocate(&mut arena, 800, 1);
+    // Range does not fit into the first block, so the second one is added.
+    assert_eq!(allocate(&mut arena, 800, 1), (1, 0));
+    // Large range gets the block which fits it.
+    assert_eq!(
+        arena.allocate(5000, 1),
+        Placement::NewBlock { capacity: 8192 }
+    );
+    arena.push_block(8192);
+    assert_eq!(
+        arena.allocate(5000, 1),
+        Placement::Block {
+            index: 2,
+            offset: 0
+        }
+    );
+
+    // Blocks are released, and the next block fits the whole frame.
+    assert!(arena.reset());
+    assert_eq!(arena.block_count(), 0);
+    assert_eq!(arena.high_water(), 6600);
+    assert_eq!(
+        arena.allocate(800, 1),
+        Placement::NewBlock { capacity: 8192 }
+    );
+    arena.push_block(8192);
+    assert_eq!(allocate(&mut arena, 800, 1), (0, 0));
+    assert_eq!(allocate(&mut arena, 5800, 1), (0, 800));
+    assert!(!arena.reset());
+}
+
+#[test]
+fn test_shrink_after_quiet_frames() {
+    let mut arena = Arena::new(1024);
+    allocate(&mut arena, 20_000, 1);
+    allocate(&mut arena, 20_000, 1);
+    assert!(arena.reset());
+    assert_eq!(
+        arena.allocate(1, 1),
+        Placement::NewBlock { capacity: 65536 }
+    );
+    arena.push_block(65536);
+
+    // Frames which use a lot of the block interrupt shrinking.
+    for _ in 0..SHRINK_FRAMES - 1 {
+        allocate(&mut arena, 1000, 1);
+        assert!(!arena.reset());
+    }
+    allocate(&mut arena, 40_000, 1);
+    assert!(!arena.reset());
+
+    for frame in 1..=SHRINK_FRAMES {
+        allocate(&mut arena, 1000, 1);
+        assert_eq!(arena.reset(), frame == SHRINK_FRAMES);
+    }
+    // Block is shrunk to twice the peak of quiet frames.
+    assert_eq!(arena.allocate(1, 1), Placement::NewBlock { capacity: 2048 });
+    assert_eq!(arena.high_water(), 1000);
+}
+
+#[test]
+fn test_shrink_keeps_min_capacity() {
+    let mut arena = Arena::new(1000);
+    for _ in 0..SHRINK_FRAMES * 2 {
+        allocate(&mut arena, 1, 1);
+        assert!(!arena.reset());
+    }
+    assert_eq!(arena.capacity(), 1024);
+}
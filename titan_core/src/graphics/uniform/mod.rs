@@ -0,0 +1,262 @@
+//! Uniform buffers of shaders which are written once per frame.
+//!
+//! Each uniform buffer has one backing buffer for each swapchain image (frame in flight),
+//! so the host writes the buffer of the current frame while previous frames
+//! still read their own copies. Value of [`UniformBuffer`] is copied into the buffer
+//! of the frame when the renderer begins it, so written value takes effect since the next frame.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use bytemuck::Pod;
+use vulkano::buffer::{BufferAccess, BufferUsage};
+use vulkano::descriptor_set::layout::DescriptorSetLayout;
+use vulkano::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+
+use super::{
+    allocator::MemoryAllocator,
+    hook::FrameContext,
+    mapped::{MappedBuffer, MappedBufferCreationError, MappedBufferWriteError},
+    renderer::error::DescriptorSetCreationError,
+};
+
+mod tests;
+
+/// Uniform buffers with one buffer for each frame in flight.
+pub(crate) struct UniformRing<T> {
+    buffers: Vec<Arc<MappedBuffer<T>>>,
+}
+
+impl<T> UniformRing<T>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates buffers for given count of frames in flight.
+    pub fn new(
+        allocator: &Arc<MemoryAllocator>,
+        frame_count: usize,
+    ) -> Result<Self, MappedBufferCreationError> {
+        let buffers = (0..frame_count)
+            .map(|_| MappedBuffer::new(allocator, BufferUsage::uniform_buffer(), 1))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { buffers })
+    }
+
+    /// Writes value into the buffer of the frame.
+    ///
+    /// Previous frame which used this buffer must be finished.
+    ///
+    pub fn write(&self, frame: usize, value: T) -> Result<(), MappedBufferWriteError> {
+        self.buffers[frame].write(0, value)
+    }
+
+    /// Count of frames in flight which have their own buffer.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Buffer of the frame.
+    pub fn buffer(&self, frame: usize) -> &Arc<MappedBuffer<T>> {
+        &self.buffers[frame]
+    }
+}
+
+/// Value of the uniform buffer with versions which were written into buffers of frames.
+#[derive(Debug)]
+struct Pending<T> {
+    value: T,
+    version: u64,
+    written: Vec<u64>,
+}
+
+impl<T> Pending<T>
+where
+    T: Copy,
+{
+    fn new(value: T, frame_count: usize) -> Self {
+        Self {
+            value,
+            version: 1,
+            written: vec![0; frame_count],
+        }
+    }
+
+    fn set(&mut self, value: T) {
+        self.value = value;
+        self.version += 1;
+    }
+
+    /// Returns value if it was not written into the buffer of the frame yet,
+    /// marking it as written.
+    fn take(&mut self, frame: usize) -> Option<T> {
+        let written = &mut self.written[frame];
+        if *written == self.version {
+            return None;
+        }
+        *written = self.version;
+        Some(self.value)
+    }
+}
+
+/// Descriptor sets of each frame which were created for one layout.
+struct DescriptorSets {
+    layout: Arc<DescriptorSetLayout>,
+    sets: Vec<Option<Arc<dyn DescriptorSet + Send + Sync>>>,
+}
+
+struct UniformState<T> {
+    ring: UniformRing<T>,
+    pending: Mutex<Pending<T>>,
+    descriptor_sets: Mutex<Option<DescriptorSets>>,
+}
+
+/// Uniform buffer of `T` for shaders of [render hooks](super::RenderHook),
+/// created by [`Renderer::create_uniform_buffer`](super::Renderer::create_uniform_buffer).
+///
+/// Handle is shared between the callback of the application and the hook by cloning it.
+/// Value is written with [`UniformBuffer::write`] at any time and is copied into the buffer
+/// of each frame when it begins. Buffers are destroyed when the last handle is dropped
+/// and frames which use them are finished.
+///
+pub struct UniformBuffer<T>
+where
+    T: Pod + Send + Sync,
+{
+    state: Arc<UniformState<T>>,
+}
+
+impl<T> Clone for UniformBuffer<T>
+where
+    T: Pod + Send + Sync,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> UniformBuffer<T>
+where
+    T: Pod + Send + Sync,
+{
+    pub(crate) fn new(
+        allocator: &Arc<MemoryAllocator>,
+        frame_count: usize,
+        value: T,
+    ) -> Result<Self, MappedBufferCreationError> {
+        let state = UniformState {
+            ring: UniformRing::new(allocator, frame_count)?,
+            pending: Mutex::new(Pending::new(value, frame_count)),
+            descriptor_sets: Mutex::new(None),
+        };
+        Ok(Self {
+            state: Arc::new(state),
+        })
+    }
+
+    /// Writes value which is used since the next frame.
+    pub fn write(&self, value: &T) {
+        self.state.pending.lock().unwrap().set(*value);
+    }
+
+    /// Value which was written last.
+    pub fn value(&self) -> T {
+        self.state.pending.lock().unwrap().value
+    }
+
+    /// Buffer of the frame, which could be bound manually.
+    pub fn buffer(&self, frame: &FrameContext) -> Arc<dyn BufferAccess + Send + Sync> {
+        self.state.ring.buffer(frame.image_index).clone()
+    }
+
+    /// Returns descriptor set of the frame with given layout,
+    /// which binds the buffer of the frame at binding 0.
+    ///
+    /// Descriptor sets are created once for each frame and reused,
+    /// until they are requested with another layout.
+    ///
+    pub fn descriptor_set(
+        &self,
+        layout: &Arc<DescriptorSetLayout>,
+        frame: &FrameContext,
+    ) -> Result<Arc<dyn DescriptorSet + Send + Sync>, DescriptorSetCreationError> {
+        let mut descriptor_sets = self.state.descriptor_sets.lock().unwrap();
+        let stale = !matches!(&*descriptor_sets, Some(sets) if Arc::ptr_eq(&sets.layout, layout));
+        if stale {
+            *descriptor_sets = Some(DescriptorSets {
+                layout: layout.clone(),
+                sets: vec![None; self.state.ring.len()],
+            });
+        }
+        let descriptor_sets = descriptor_sets.as_mut().unwrap();
+        let frame = frame.image_index;
+        if let Some(set) = &descriptor_sets.sets[frame] {
+            return Ok(set.clone());
+        }
+
+        let mut builder = PersistentDescriptorSet::start(layout.clone());
+        builder
+            .add_buffer(self.state.ring.buffer(frame).clone())
+            .map_err(DescriptorSetCreationError::from)?;
+        let set: Arc<dyn DescriptorSet + Send + Sync> =
+            Arc::new(builder.build().map_err(DescriptorSetCreationError::from)?);
+        descriptor_sets.sets[frame] = Some(set.clone());
+        Ok(set)
+    }
+}
+
+/// Type-erased uniform buffer which is written by the renderer on each frame.
+trait FrameUniform: Send + Sync {
+    /// Copies written value into the buffer of the frame, if it was not copied yet.
+    fn flush(&self, frame: usize) -> Result<(), MappedBufferWriteError>;
+}
+
+impl<T> FrameUniform for UniformState<T>
+where
+    T: Pod + Send + Sync,
+{
+    fn flush(&self, frame: usize) -> Result<(), MappedBufferWriteError> {
+        let value = self.pending.lock().unwrap().take(frame);
+        match value {
+            Some(value) => self.ring.write(frame, value),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Uniform buffers which were created by the renderer and are still alive.
+#[derive(Default)]
+pub(crate) struct UniformRegistry {
+    uniforms: Vec<Weak<dyn FrameUniform>>,
+}
+
+impl UniformRegistry {
+    pub fn register<T>(&mut self, uniform: &UniformBuffer<T>)
+    where
+        T: Pod + Send + Sync,
+    {
+        let state: Arc<dyn FrameUniform> = uniform.state.clone();
+        self.uniforms.push(Arc::downgrade(&state));
+    }
+
+    /// Count of uniform buffers which are still alive.
+    pub fn len(&self) -> usize {
+        self.uniforms
+            .iter()
+            .filter(|uniform| uniform.strong_count() > 0)
+            .count()
+    }
+
+    /// Copies written values of alive uniform buffers into their buffers of the frame,
+    /// forgetting dropped ones.
+    ///
+    /// Previous frame which used buffers of this frame must be finished.
+    ///
+    pub fn flush(&mut self, frame: usize) -> Result<(), MappedBufferWriteError> {
+        self.uniforms.retain(|uniform| uniform.strong_count() > 0);
+        for uniform in self.uniforms.iter().filter_map(Weak::upgrade) {
+            uniform.flush(frame)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_pending_frames() {
+    let mut pending = Pending::new(1_u32, 3);
+    // Initial value is written into buffers of all frames once.
+    assert_eq!(pending.take(0), Some(1));
+    assert_eq!(pending.take(0), None);
+    assert_eq!(pending.take(2), Some(1));
+
+    pending.set(2);
+    assert_eq!(pending.take(0), Some(2));
+    assert_eq!(pending.take(1), Some(2));
+    assert_eq!(pending.take(2), Some(2));
+    assert_eq!(pending.take(1), None);
+
+    // Only the last value of several writes is taken.
+    pending.set(3);
+    pending.set(4);
+    assert_eq!(pending.take(2), Some(4));
+    assert_eq!(pending.take(2), None);
+}
@@ -0,0 +1,395 @@
+//! Uploads of data into device local buffers and images through the transfer queue.
+//!
+//! Data is written into the persistent ring of [`STAGING_BLOCK_COUNT`] staging blocks
+//! and copied on the transfer queue, which belongs to the dedicated transfer family if the device
+//! has one. Copies are not waited for by the host: each of them signals a semaphore
+//! which the next rendered frame waits for before its commands are executed.
+//! Block is written again only after that frame is finished.
+//!
+//! Data larger than [`STAGING_BLOCK_SIZE`] (or uploaded while all blocks are busy)
+//! is written into a dedicated staging buffer instead.
+//!
+//! Mip levels of images are generated by blits, which are only allowed on graphics queues,
+//! so images with mip levels are uploaded through the graphics queue.
+
+use std::mem::{align_of, size_of_val};
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferSlice, BufferUsage};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BuildError, CommandBufferExecError, CommandBufferUsage,
+    CopyBufferError, CopyBufferImageError, PrimaryAutoCommandBuffer,
+};
+use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::image::{
+    ImageCreateFlags, ImageCreationError, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage,
+    MipmapsCount,
+};
+use vulkano::sync::{self, FenceSignalFuture, FlushError, GpuFuture};
+use vulkano::{DeviceSize, OomError};
+
+use self::ring::{Ring, StagingRange};
+use super::allocator::MemoryAllocator;
+use super::device_buffer::{DeviceBuffer, DeviceBufferCreationError};
+use super::mapped::{MappedBuffer, MappedBufferCreationError, MappedBufferWriteError};
+
+pub(crate) use self::ring::StagingFence;
+
+mod ring;
+mod tests;
+
+/// Size of each block of the staging ring (in bytes).
+pub const STAGING_BLOCK_SIZE: DeviceSize = 4 * 1024 * 1024;
+
+/// Count of blocks of the staging ring.
+pub const STAGING_BLOCK_COUNT: usize = 4;
+
+/// Alignment of staging ranges which are copied into images,
+/// enough for texel blocks of all formats.
+const IMAGE_ALIGNMENT: DeviceSize = 16;
+
+#[derive(Debug, Error)]
+pub enum UploadError {
+    #[error("uploaded data must not be empty")]
+    Empty,
+
+    #[error("{len} byte(s) of data do not match image of {expected} bytes")]
+    ImageSize { len: usize, expected: DeviceSize },
+
+    #[error("format {0:?} could not be uploaded")]
+    UnsupportedFormat(Format),
+
+    #[error("staging buffer creation failure: {0}")]
+    StagingCreation(#[from] MappedBufferCreationError),
+
+    #[error("failed to write staging buffer: {0}")]
+    StagingWrite(#[from] MappedBufferWriteError),
+
+    #[error("buffer creation failure: {0}")]
+    BufferCreation(#[from] DeviceBufferCreationError),
+
+    #[error("image creation failure: {0}")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("upload command buffer creation failure: {0}")]
+    CommandBufferCreation(#[from] OomError),
+
+    #[error("failed to copy into buffer: {0}")]
+    BufferCopy(#[from] CopyBufferError),
+
+    #[error("failed to copy into image: {0}")]
+    ImageCopy(#[from] CopyBufferImageError),
+
+    #[error("failed to generate mip levels of image: {0}")]
+    MipmapGeneration(#[source] ImageCreationError),
+
+    #[error("upload command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("upload command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit upload: {0}")]
+    Submit(#[from] FlushError),
+}
+
+impl<F> StagingFence for FenceSignalFuture<F>
+where
+    F: GpuFuture,
+{
+    fn is_finished(&self) -> bool {
+        !matches!(self.wait(Some(Duration::ZERO)), Err(FlushError::Timeout))
+    }
+}
+
+impl<F> StagingFence for Arc<F>
+where
+    F: StagingFence + ?Sized,
+{
+    fn is_finished(&self) -> bool {
+        F::is_finished(self)
+    }
+}
+
+type StagingSlice<T> = Arc<BufferSlice<[T], Arc<MappedBuffer<u8>>>>;
+
+/// Persistent staging blocks and copies which were submitted on the transfer queue,
+/// but were not waited for by any frame yet.
+pub(crate) struct StagingRing {
+    allocator: Arc<MemoryAllocator>,
+    queue: Arc<Queue>,
+    /// Queue which mip levels of images are generated on.
+    graphics_queue: Arc<Queue>,
+    ring: Ring<Arc<dyn StagingFence + Send + Sync>>,
+    blocks: Vec<Arc<MappedBuffer<u8>>>,
+    pending: Vec<Box<dyn GpuFuture + Send + Sync>>,
+    /// If pending copies were taken by the frame which was not submitted yet.
+    taken: bool,
+}
+
+impl StagingRing {
+    /// Creates all blocks of the ring, which copies are submitted on given queue.
+    ///
+    /// Images with mip levels are uploaded on the graphics queue instead.
+    ///
+    pub fn new(
+        allocator: Arc<MemoryAllocator>,
+        queue: Arc<Queue>,
+        graphics_queue: Arc<Queue>,
+    ) -> Result<Self, MappedBufferCreationError> {
+        let blocks = (0..STAGING_BLOCK_COUNT)
+            .map(|_| self::staging_buffer(&allocator, STAGING_BLOCK_SIZE as usize))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            allocator,
+            queue,
+            graphics_queue,
+            ring: Ring::new(STAGING_BLOCK_SIZE, STAGING_BLOCK_COUNT),
+            blocks,
+            pending: Vec::new(),
+            taken: false,
+        })
+    }
+
+    /// Count of blocks which copies are not finished yet.
+    pub fn busy_blocks(&self) -> usize {
+        self.ring.busy()
+    }
+
+    /// Creates device local buffer with given usage and copies data into it.
+    pub fn upload_buffer<T>(
+        &mut self,
+        data: &[T],
+        usage: BufferUsage,
+    ) -> Result<Arc<DeviceBuffer<T>>, UploadError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        if data.is_empty() {
+            return Err(UploadError::Empty);
+        }
+        let alignment = align_of::<T>() as DeviceSize;
+        let source = self.stage(data, alignment)?;
+
+        let device = self.queue.device();
+        // Buffer is shared between all queue families, so no ownership transfer is needed.
+        let usage = BufferUsage {
+            transfer_destination: true,
+            ..usage
+        };
+        let buffer = DeviceBuffer::new(
+            &self.allocator,
+            usage,
+            data.len(),
+            device.active_queue_families(),
+        )?;
+        let mut builder = self.builder()?;
+        builder.copy_buffer(source, buffer.clone())?;
+        self.submit(builder.build()?)?;
+        Ok(buffer)
+    }
+
+    /// Creates sampled 2D image of given format and copies its texels into it.
+    ///
+    /// If `mipmaps` is `true`, the full chain of mip levels is generated from the texels
+    /// with linear filtering. Mip levels are not generated for formats which could not be
+    /// blitted or linearly filtered by the device (for example, for compressed formats).
+    ///
+    pub fn upload_image(
+        &mut self,
+        texels: &[u8],
+        dimensions: [u32; 2],
+        format: Format,
+        mipmaps: bool,
+    ) -> Result<Arc<ImmutableImage>, UploadError> {
+        let expected =
+            self::image_size(dimensions, format).ok_or(UploadError::UnsupportedFormat(format))?;
+        if texels.len() as DeviceSize != expected {
+            return Err(UploadError::ImageSize {
+                len: texels.len(),
+                expected,
+            });
+        }
+        if texels.is_empty() {
+            return Err(UploadError::Empty);
+        }
+        let source = self.stage(texels, IMAGE_ALIGNMENT)?;
+        let dimensions = ImageDimensions::Dim2d {
+            width: dimensions[0],
+            height: dimensions[1],
+            array_layers: 1,
+        };
+        if mipmaps && dimensions.max_mipmaps() > 1 {
+            if self::supports_mipmaps(&self.graphics_queue, format) {
+                // Barriers between blits of successive levels are inserted by the builder.
+                let (image, future) = ImmutableImage::from_buffer(
+                    source,
+                    dimensions,
+                    MipmapsCount::Log2,
+                    format,
+                    self.graphics_queue.clone(),
+                )
+                .map_err(UploadError::MipmapGeneration)?;
+                self.pending
+                    .push(Box::new(future.then_signal_semaphore_and_flush()?));
+                return Ok(image);
+            }
+            log::debug!(
+                "mip levels of {:?} images could not be generated by the device",
+                format,
+            );
+        }
+
+        let device = self.queue.device();
+        let [width, height, _] = dimensions.width_height_depth();
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let (image, initializer) = ImmutableImage::uninitialized(
+            device.clone(),
+            dimensions,
+            format,
+            MipmapsCount::One,
+            usage,
+            ImageCreateFlags::none(),
+            ImageLayout::ShaderReadOnlyOptimal,
+            device.active_queue_families(),
+        )?;
+        let mut builder = self.builder()?;
+        builder.copy_buffer_to_image_dimensions(
+            source,
+            Arc::new(initializer),
+            [0, 0, 0],
+            [width, height, 1],
+            0,
+            1,
+            0,
+        )?;
+        self.submit(builder.build()?)?;
+        Ok(image)
+    }
+
+    /// Takes copies which were submitted since the previous frame,
+    /// so the next frame waits for them.
+    ///
+    /// Blocks of these copies must be marked by [`StagingRing::submitted`]
+    /// or [`StagingRing::abandoned`] after the frame is submitted.
+    ///
+    pub fn take_pending(&mut self) -> Option<Box<dyn GpuFuture + Send + Sync>> {
+        // Frame which took copies last time failed before it was submitted.
+        if self.taken {
+            self.abandoned();
+        }
+        let pending = self
+            .pending
+            .drain(..)
+            .reduce(|joined, future| Box::new(joined.join(future)));
+        self.taken = pending.is_some();
+        pending
+    }
+
+    /// Marks taken copies as waited for by the frame with given fence.
+    pub fn submitted(&mut self, fence: Arc<dyn StagingFence + Send + Sync>) {
+        self.ring.submitted(fence);
+        self.taken = false;
+    }
+
+    /// Frees blocks of taken copies if the frame which should wait for them was not submitted.
+    pub fn abandoned(&mut self) {
+        self.taken = false;
+        if let Err(error) = self.queue.wait() {
+            log::error!(
+                "failed to wait for uploads of the transfer queue: {}",
+                error
+            );
+            return;
+        }
+        self.ring.release_pending();
+    }
+
+    /// Writes data into the range of the ring or into the dedicated staging buffer.
+    fn stage<T>(
+        &mut self,
+        data: &[T],
+        alignment: DeviceSize,
+    ) -> Result<StagingSlice<T>, UploadError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        let size = size_of_val(data) as DeviceSize;
+        let (buffer, offset) = match self.ring.place(size, alignment) {
+            Some(StagingRange { block, offset }) => (self.blocks[block].clone(), offset),
+            None => {
+                log::debug!(
+                    "staging ring is full, dedicated buffer of {} bytes is used",
+                    size
+                );
+                (self::staging_buffer(&self.allocator, size as usize)?, 0)
+            }
+        };
+        buffer.write_values(offset as usize, data)?;
+        let slice = BufferSlice::from_typed_buffer_access(buffer)
+            .slice(offset..offset + size)
+            .expect("staging range must be inside of its buffer");
+        // Safety: range is aligned for `T` and its size is a multiple of size of `T`.
+        Ok(Arc::new(unsafe { slice.reinterpret::<[T]>() }))
+    }
+
+    fn builder(&self) -> Result<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>, UploadError> {
+        let builder = AutoCommandBufferBuilder::primary(
+            self.queue.device().clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        Ok(builder)
+    }
+
+    /// Submits copies on the transfer queue, signaling the semaphore for the next frame.
+    fn submit(&mut self, command_buffer: PrimaryAutoCommandBuffer) -> Result<(), UploadError> {
+        let future = sync::now(self.queue.device().clone())
+            .then_execute(self.queue.clone(), command_buffer)?
+            .then_signal_semaphore_and_flush()?;
+        self.pending.push(Box::new(future));
+        Ok(())
+    }
+}
+
+/// Creates staging buffer of `size` bytes, preferring memory which is not device local.
+fn staging_buffer(
+    allocator: &Arc<MemoryAllocator>,
+    size: usize,
+) -> Result<Arc<MappedBuffer<u8>>, MappedBufferCreationError> {
+    MappedBuffer::with_memory_type(
+        allocator,
+        BufferUsage::transfer_source(),
+        size,
+        |memory_type| !memory_type.is_device_local(),
+    )
+}
+
+/// Returns `true` if mip levels of images of the format could be generated on the queue,
+/// which requires blits with linear filtering.
+fn supports_mipmaps(queue: &Queue, format: Format) -> bool {
+    let features = format
+        .properties(queue.device().physical_device())
+        .optimal_tiling_features;
+    queue.family().supports_graphics()
+        && features.blit_src
+        && features.blit_dst
+        && features.sampled_image_filter_linear
+}
+
+/// Size of texels of the 2D image (in bytes), or `None` if the format has no known size.
+pub(crate) fn image_size(dimensions: [u32; 2], format: Format) -> Option<DeviceSize> {
+    let [width, height] = dimensions;
+    let [block_width, block_height] = format.block_dimensions();
+    let block_size = format.size()?;
+    let blocks_x = (width + block_width - 1) / block_width;
+    let blocks_y = (height + block_height - 1) / block_height;
+    Some(blocks_x as DeviceSize * blocks_y as DeviceSize * block_size)
+}
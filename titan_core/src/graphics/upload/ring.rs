@@ -0,0 +1,131 @@
+//! Placement of staging ranges into the ring of blocks, without any device memory behind them.
+
+use vulkano::DeviceSize;
+
+use crate::graphics::utils::align_up;
+
+/// Fence of the frame which waited for copies from the block.
+pub(crate) trait StagingFence {
+    /// Returns `true` if the frame is finished, so the block could be written again.
+    fn is_finished(&self) -> bool;
+}
+
+#[derive(Debug)]
+struct Block<F> {
+    used: DeviceSize,
+    /// If copies from the block were submitted, but no frame waited for them yet.
+    pending: bool,
+    /// Fence of the last frame which waited for copies from the block.
+    fence: Option<F>,
+}
+
+impl<F> Block<F>
+where
+    F: StagingFence,
+{
+    /// Returns `true` if no copies from the block could be executed anymore.
+    fn is_free(&self) -> bool {
+        !self.pending && self.fence.as_ref().map_or(true, F::is_finished)
+    }
+}
+
+/// Range of the block which was placed by the ring.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct StagingRange {
+    pub block: usize,
+    pub offset: DeviceSize,
+}
+
+/// Ring of blocks with the same size, in which ranges are bumped one after another.
+///
+/// Ranges are placed into the current block until it is full, then the ring moves
+/// to the next free block, which is reset. Block is free when copies from it
+/// were waited for by the frame which is already finished.
+///
+#[derive(Debug)]
+pub(super) struct Ring<F> {
+    block_size: DeviceSize,
+    blocks: Vec<Block<F>>,
+    current: usize,
+}
+
+impl<F> Ring<F>
+where
+    F: StagingFence,
+{
+    /// Creates ring of `count` empty blocks of `block_size` bytes each.
+    pub fn new(block_size: DeviceSize, count: usize) -> Self {
+        let blocks = (0..count)
+            .map(|_| Block {
+                used: 0,
+                pending: false,
+                fence: None,
+            })
+            .collect();
+        Self {
+            block_size,
+            blocks,
+            current: 0,
+        }
+    }
+
+    /// Places range of `size` bytes with given alignment into one of blocks.
+    ///
+    /// Alignment is rounded up to a power of two.
+    /// Returns `None` if the range is larger than the block or all other blocks are busy.
+    ///
+    pub fn place(&mut self, size: DeviceSize, alignment: DeviceSize) -> Option<StagingRange> {
+        if size > self.block_size {
+            return None;
+        }
+        let alignment = alignment.max(1).next_power_of_two();
+        let count = self.blocks.len();
+        for step in 0..count {
+            let index = (self.current + step) % count;
+            let block = &mut self.blocks[index];
+            // Ranges could be appended to the current block even if it is busy,
+            // but other blocks are reused from the start.
+            if block.is_free() {
+                block.used = 0;
+                block.fence = None;
+            } else if step > 0 {
+                continue;
+            }
+            let offset = align_up(block.used, alignment);
+            if offset + size > self.block_size {
+                continue;
+            }
+            block.used = offset + size;
+            block.pending = true;
+            self.current = index;
+            return Some(StagingRange {
+                block: index,
+                offset,
+            });
+        }
+        None
+    }
+
+    /// Marks blocks with pending copies as waited for by the frame with given fence.
+    pub fn submitted(&mut self, fence: F)
+    where
+        F: Clone,
+    {
+        for block in self.blocks.iter_mut().filter(|block| block.pending) {
+            block.pending = false;
+            block.fence = Some(fence.clone());
+        }
+    }
+
+    /// Marks blocks with pending copies as free, when copies are known to be finished.
+    pub fn release_pending(&mut self) {
+        for block in self.blocks.iter_mut().filter(|block| block.pending) {
+            block.pending = false;
+        }
+    }
+
+    /// Count of blocks which could not be written now.
+    pub fn busy(&self) -> usize {
+        self.blocks.iter().filter(|block| !block.is_free()).count()
+    }
+}
@@ -0,0 +1,168 @@
+#![cfg(test)]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use vulkano::buffer::CpuAccessibleBuffer;
+
+use crate::graphics::utils;
+
+use super::ring::{Ring, StagingRange};
+use super::*;
+
+/// Fence which is finished when the test says so.
+#[derive(Debug, Clone, Default)]
+struct TestFence(Rc<Cell<bool>>);
+
+impl StagingFence for TestFence {
+    fn is_finished(&self) -> bool {
+        self.0.get()
+    }
+}
+
+fn range(block: usize, offset: DeviceSize) -> Option<StagingRange> {
+    Some(StagingRange { block, offset })
+}
+
+#[test]
+fn test_ring_bumps_current_block() {
+    let mut ring = Ring::<TestFence>::new(100, 2);
+    assert_eq!(ring.place(10, 1), range(0, 0));
+    assert_eq!(ring.place(8, 4), range(0, 12));
+    // Range which does not fit moves the ring to the next block.
+    assert_eq!(ring.place(90, 1), range(1, 0));
+    assert_eq!(ring.place(10, 1), range(1, 90));
+    // Ranges larger than the block are never placed.
+    assert_eq!(ring.place(101, 1), None);
+    // Both blocks have pending copies.
+    assert_eq!(ring.place(50, 1), None);
+    assert_eq!(ring.busy(), 2);
+}
+
+#[test]
+fn test_ring_reuses_finished_blocks() {
+    let mut ring = Ring::new(100, 2);
+    assert_eq!(ring.place(60, 1), range(0, 0));
+    let first = TestFence::default();
+    ring.submitted(first.clone());
+    // Busy block still accepts ranges while it is the current one.
+    assert_eq!(ring.place(30, 1), range(0, 60));
+    assert_eq!(ring.place(60, 1), range(1, 0));
+    let second = TestFence::default();
+    ring.submitted(second.clone());
+    assert_eq!(ring.busy(), 2);
+    assert_eq!(ring.place(60, 1), None);
+
+    // The first block is waited for by both frames, so it is busy until the last of them.
+    first.0.set(true);
+    assert_eq!(ring.busy(), 2);
+    second.0.set(true);
+    assert_eq!(ring.busy(), 0);
+    // The current block is reset after its frame is finished.
+    assert_eq!(ring.place(60, 1), range(1, 0));
+    assert_eq!(ring.busy(), 1);
+
+    // Copies of the frame which was not submitted are released explicitly.
+    ring.release_pending();
+    assert_eq!(ring.busy(), 0);
+}
+
+#[test]
+fn test_image_size() {
+    assert_eq!(image_size([4, 3], Format::R8G8B8A8_SRGB), Some(48));
+    assert_eq!(image_size([5, 5], Format::R8_UNORM), Some(25));
+    // Compressed images are rounded up to whole blocks.
+    assert_eq!(image_size([5, 4], Format::BC1_RGB_UNORM_BLOCK), Some(16));
+    assert_eq!(image_size([8, 8], Format::BC7_SRGB_BLOCK), Some(64));
+}
+
+/// Checks that uploaded data is visible for the graphics queue
+/// after it waits for pending copies.
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_upload_buffer() {
+    let queue = utils::graphics_queue();
+    let device = queue.device().clone();
+    let allocator = MemoryAllocator::new(device.clone());
+    let mut staging = StagingRing::new(allocator, queue.clone(), queue.clone()).unwrap();
+    assert!(matches!(
+        staging.upload_buffer::<u32>(&[], BufferUsage::vertex_buffer()),
+        Err(UploadError::Empty),
+    ));
+
+    // Data of the second upload does not fit into the block, so it is staged separately.
+    let small: Vec<u32> = (0..256).collect();
+    let large: Vec<u8> = (0..STAGING_BLOCK_SIZE + 1).map(|i| i as u8).collect();
+    let small_buffer = staging
+        .upload_buffer(&small, BufferUsage::vertex_buffer())
+        .unwrap();
+    let large_buffer = staging
+        .upload_buffer(&large, BufferUsage::vertex_buffer())
+        .unwrap();
+    assert_eq!(staging.busy_blocks(), 1);
+
+    let small_copy = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        small.iter().map(|_| 0u32),
+    )
+    .unwrap();
+    let large_copy = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        large.iter().map(|_| 0u8),
+    )
+    .unwrap();
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device,
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    builder
+        .copy_buffer(small_buffer, small_copy.clone())
+        .unwrap()
+        .copy_buffer(large_buffer, large_copy.clone())
+        .unwrap();
+    let command_buffer = builder.build().unwrap();
+    let fence = staging
+        .take_pending()
+        .unwrap()
+        .then_execute(queue, command_buffer)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap();
+    let fence = Arc::new(fence);
+    staging.submitted(fence.clone());
+    fence.wait(None).unwrap();
+    assert_eq!(staging.busy_blocks(), 0);
+
+    assert_eq!(&small_copy.read().unwrap()[..], &small[..]);
+    assert_eq!(&large_copy.read().unwrap()[..], &large[..]);
+}
+
+#[test]
+#[ignore = "needs Vulkan device"]
+fn test_upload_image_mipmaps() {
+    let queue = utils::graphics_queue();
+    let allocator = MemoryAllocator::new(queue.device().clone());
+    let mut staging = StagingRing::new(allocator, queue.clone(), queue).unwrap();
+    let texels = vec![255; 6 * 5 * 4];
+    let format = Format::R8G8B8A8_UNORM;
+
+    let image = staging
+        .upload_image(&texels, [6, 5], format, false)
+        .unwrap();
+    assert_eq!(image.mipmap_levels(), 1);
+    // Levels are 6x5, 3x2 and 1x1.
+    let image = staging.upload_image(&texels, [6, 5], format, true).unwrap();
+    assert_eq!(image.mipmap_levels(), 3);
+    // There are no levels to generate for the single texel.
+    let image = staging
+        .upload_image(&texels[..4], [1, 1], format, true)
+        .unwrap();
+    assert_eq!(image.mipmap_levels(), 1);
+    staging.abandoned();
+}
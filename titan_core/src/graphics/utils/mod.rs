@@ -3,14 +3,19 @@
 use std::sync::Arc;
 
 use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType, QueueFamily};
-use vulkano::device::{DeviceExtensions, Features};
+use vulkano::device::{DeviceExtensions, Features, Queue};
 use vulkano::format::Format;
-use vulkano::instance::{ApplicationInfo, Instance, InstanceCreationError};
+use vulkano::instance::{ApplicationInfo, Instance, InstanceCreationError, InstanceExtensions};
+use vulkano::pipeline::depth_stencil::DepthStencil;
 use vulkano::swapchain::{Capabilities, ColorSpace, Surface};
+use vulkano::DeviceSize;
 use vulkano_win::required_extensions;
 use winit::window::Window;
 
 use crate::config::{Config, ENGINE_NAME, ENGINE_VERSION};
+use crate::graphics::sampler::CompareOp;
+
+mod tests;
 
 /// Convert [`semver::Version`] Version struct into [`vulkano::Version`] struct.
 #[inline(always)]
@@ -35,7 +40,12 @@ pub fn create_instance(config: &Config) -> Result<Arc<Instance>, InstanceCreatio
         engine_version: Some(self::to_vk_version(&*ENGINE_VERSION)),
     };
     let extensions = {
-        let mut extensions = required_extensions();
+        // Surface extensions are not needed if there is no window.
+        let mut extensions = if config.headless() {
+            InstanceExtensions::none()
+        } else {
+            required_extensions()
+        };
         if config.enable_validation() {
             extensions.ext_debug_utils = true;
         }
@@ -59,10 +69,11 @@ pub struct SuitablePhysicalDevice<'a> {
 /// Filter suitable physical device from all of them.
 ///
 /// Will check for provided extensions and features support.
+/// Queue family which presents to the surface is searched only if surface is provided.
 ///
 pub fn suitable_physical_device<'a>(
     physical_devices: impl ExactSizeIterator<Item = PhysicalDevice<'a>>,
-    surface: &Arc<Surface<Window>>,
+    surface: Option<&Arc<Surface<Window>>>,
     required_extensions: &DeviceExtensions,
     required_features: &Features,
 ) -> Option<SuitablePhysicalDevice<'a>> {
@@ -77,12 +88,16 @@ pub fn suitable_physical_device<'a>(
             let graphics_family = physical_device
                 .queue_families()
                 .find(QueueFamily::supports_graphics);
-            let present_family = physical_device
-                .queue_families()
-                .find(|&queue| surface.is_supported(queue).unwrap_or(false));
+            let present_family = physical_device.queue_families().find(|&queue| {
+                surface.map_or(false, |surface| {
+                    surface.is_supported(queue).unwrap_or(false)
+                })
+            });
+            // Dedicated transfer family copies data without stalling the graphics queue.
             let transfer_family = physical_device
                 .queue_families()
-                .find(QueueFamily::explicitly_supports_transfers);
+                .filter(QueueFamily::explicitly_supports_transfers)
+                .min_by_key(|family| (family.supports_graphics(), family.supports_compute()));
             match (graphics_family, present_family, transfer_family) {
                 (Some(graphics_family), Some(present_family), Some(transfer_family)) => {
                     Some(SuitablePhysicalDevice {
@@ -100,6 +115,15 @@ pub fn suitable_physical_device<'a>(
                         transfer_family: None,
                     })
                 }
+                // Frames are not presented without the surface (in headless mode).
+                (Some(graphics_family), None, transfer_family) if surface.is_none() => {
+                    Some(SuitablePhysicalDevice {
+                        physical_device,
+                        graphics_family,
+                        present_family: None,
+                        transfer_family,
+                    })
+                }
                 (Some(graphics_family), None, None) => Some(SuitablePhysicalDevice {
                     physical_device,
                     graphics_family,
@@ -148,6 +172,30 @@ pub fn suitable_depth_stencil_format(physical_device: PhysicalDevice) -> Format
         .unwrap_or(&Format::D16_UNORM)
 }
 
+/// Depth test of scene pipelines which compares depth of fragments with given operator
+/// and writes depth of passed ones.
+pub fn depth_test(compare_op: CompareOp) -> DepthStencil {
+    DepthStencil {
+        depth_compare: compare_op.into(),
+        ..DepthStencil::simple_depth_test()
+    }
+}
+
+/// Depth which the depth buffer is cleared with for given compare operator:
+/// `0.0` for greater comparisons (reversed depth), `1.0` otherwise.
+pub fn clear_depth(compare_op: CompareOp) -> f32 {
+    match compare_op {
+        CompareOp::Greater | CompareOp::GreaterOrEqual => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// Returns `true` if components of the format are encoded into sRGB space by the hardware.
+pub fn is_srgb(format: Format) -> bool {
+    // Formats are named after Vulkan ones, like `B8G8R8A8_SRGB`.
+    format!("{:?}", format).ends_with("_SRGB")
+}
+
 /// Image format which is suitable for rendering backend.
 pub const SUITABLE_IMAGE_FORMAT: (Format, ColorSpace) =
     (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear);
@@ -162,3 +210,43 @@ pub fn suitable_image_format(capabilities: &Capabilities) -> (Format, ColorSpace
         .find(|&&format| SUITABLE_IMAGE_FORMAT == format)
         .unwrap_or_else(|| &formats[0])
 }
+
+/// Rounds offset up to the multiple of alignment (which is not necessarily a power of two).
+///
+/// Zero alignment is treated as alignment of one byte.
+///
+pub fn align_up(offset: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    let alignment = alignment.max(1);
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// Creates device with one graphics queue for tests which need Vulkan device.
+///
+/// Tests which call it are marked with `#[ignore = "needs Vulkan device"]`
+/// and are run with `cargo test -- --ignored`.
+///
+#[cfg(test)]
+pub fn graphics_queue() -> Arc<Queue> {
+    use vulkano::device::Device;
+
+    let instance = Instance::new(
+        None,
+        vulkano::Version::V1_1,
+        &InstanceExtensions::none(),
+        None,
+    )
+    .unwrap();
+    let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+    let queue_family = physical_device
+        .queue_families()
+        .find(QueueFamily::supports_graphics)
+        .unwrap();
+    let (_, mut queues) = Device::new(
+        physical_device,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+    queues.next().unwrap()
+}
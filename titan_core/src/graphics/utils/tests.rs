@@ -0,0 +1,16 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_align_up() {
+    assert_eq!(align_up(0, 256), 0);
+    assert_eq!(align_up(1, 256), 256);
+    assert_eq!(align_up(256, 256), 256);
+    assert_eq!(align_up(13, 4), 16);
+    assert_eq!(align_up(13, 1), 13);
+    // Alignment is not necessarily a power of two.
+    assert_eq!(align_up(1, 12), 12);
+    assert_eq!(align_up(5, 3), 6);
+    assert_eq!(align_up(7, 0), 7);
+}
@@ -2,11 +2,12 @@
 
 use std::ops::{Deref, DerefMut};
 
-use epaint::Rgba;
-use palette::Srgba;
 use ultraviolet::{Vec2, Vec3};
 use vulkano::pipeline::vertex::{VertexMember, VertexMemberTy};
 
+use crate::graphics::InstanceData;
+use crate::math::Color;
+
 /// Wrapper for external 3-dimensional vector struct.
 #[derive(Default, Copy, Clone)]
 pub struct Position3(Vec3);
@@ -67,30 +68,7 @@ fn format() -> (VertexMemberTy, usize) {
     }
 }
 
-/// Wrapper for external color struct.
-#[derive(Default, Copy, Clone)]
-pub struct Color(Srgba);
-
-impl From<Srgba> for Color {
-    fn from(color: Srgba) -> Self {
-        Self(color)
-    }
-}
-
-impl Deref for Color {
-    type Target = Srgba;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-
-impl DerefMut for Color {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
-
+/// Color is passed to shaders in linear space.
 unsafe impl VertexMember for Color {
     fn format() -> (VertexMemberTy, usize) {
         (VertexMemberTy::F32, 4)
@@ -111,10 +89,38 @@ pub struct Vertex {
 
 impl Vertex {
     /// Creates new vertex with given position and color.
-    pub fn new(position: Vec3, color: Srgba) -> Self {
+    pub fn new(position: Vec3, color: impl Into<Color>) -> Self {
         Self {
             position: Position3(position),
-            color: Color(color),
+            color: color.into(),
+        }
+    }
+}
+
+/// Per-instance vertex type which is used in instance buffer of instanced draws.
+#[derive(Default, Copy, Clone)]
+#[repr(C)]
+pub struct InstanceVertex {
+    /// Columns of homogeneous matrix which transforms the instance into the world.
+    pub model_x: [f32; 4],
+    pub model_y: [f32; 4],
+    pub model_z: [f32; 4],
+    pub model_w: [f32; 4],
+    /// Color which multiplies colors of vertices of the instance.
+    pub tint: Color,
+}
+
+vulkano::impl_vertex!(InstanceVertex, model_x, model_y, model_z, model_w, tint);
+
+impl From<InstanceData> for InstanceVertex {
+    fn from(instance: InstanceData) -> Self {
+        let [model_x, model_y, model_z, model_w]: [[f32; 4]; 4] = instance.transform.into();
+        Self {
+            model_x,
+            model_y,
+            model_z,
+            model_w,
+            tint: instance.color,
         }
     }
 }
@@ -127,7 +133,7 @@ pub struct UiVertex {
     pub position: Position2,
     /// UV position on the texture.
     pub uv: Position2,
-    /// Color of this vertex.
+    /// Color of this vertex in sRGB space (unlike colors of other vertices).
     pub color: Color,
 }
 
@@ -135,11 +141,36 @@ pub struct UiVertex {
 
 impl UiVertex {
     /// Creates new vertex with given position and color.
-    pub fn new(position: Vec2, uv: Vec2, color: Srgba) -> Self {
+    pub fn new(position: Vec2, uv: Vec2, color: impl Into<Color>) -> Self {
+        Self {
+            position: Position2(position),
+            uv: Position2(uv),
+            color: color.into(),
+        }
+    }
+}
+
+/// Vertex type of sprites which is used in vertex buffer.
+#[derive(Default, Copy, Clone)]
+#[repr(C)]
+pub struct SpriteVertex {
+    /// Vertex position in the 2D world.
+    pub position: Position2,
+    /// UV position on the texture.
+    pub uv: Position2,
+    /// Color of this vertex.
+    pub color: Color,
+}
+
+vulkano::impl_vertex!(SpriteVertex, position, uv, color);
+
+impl SpriteVertex {
+    /// Creates new vertex with given position, UV position and color.
+    pub fn new(position: Vec2, uv: Vec2, color: impl Into<Color>) -> Self {
         Self {
             position: Position2(position),
             uv: Position2(uv),
-            color: Color(color),
+            color: color.into(),
         }
     }
 }
@@ -152,8 +183,12 @@ fn from(vertex: epaint::Vertex) -> Self {
         let uv = vertex.uv;
         let uv = Vec2::new(uv.x, uv.y);
 
-        let color: Rgba = vertex.color.into();
-        let color = Srgba::new(color.r(), color.g(), color.b(), color.a());
+        // Color of egui is kept in sRGB space: it is converted by the shader
+        // only if the target image has sRGB format.
+        let color = vertex.color;
+        let [red, green, blue, alpha] =
+            [color.r(), color.g(), color.b(), color.a()].map(|component| component as f32 / 255.0);
+        let color = Color::rgba(red, green, blue, alpha);
 
         Self::new(position, uv, color)
     }
@@ -0,0 +1,204 @@
+//! Split-screen rendering of the scene from several cameras within one window.
+
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+use ultraviolet::Vec2;
+use vulkano::pipeline::viewport::{Scissor, Viewport as VkViewport};
+
+use crate::{camera::Camera, graphics::pre_rotation::PreTransform};
+
+mod tests;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ViewportError {
+    #[error("viewport {0} is out of window bounds")]
+    OutOfBounds(usize),
+
+    #[error("viewport {0} has no area")]
+    Empty(usize),
+}
+
+/// Rectangle in normalized window coordinates.
+///
+/// Top left corner of the window is (0, 0), bottom right corner is (1, 1).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// Rectangle which covers the whole window.
+    pub const FULL: Self = Self {
+        min: Vec2 { x: 0.0, y: 0.0 },
+        max: Vec2 { x: 1.0, y: 1.0 },
+    };
+
+    /// Creates new rectangle with given min and max corners.
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Left half of the window.
+    pub fn left_half() -> Self {
+        Self::new(Vec2::zero(), Vec2::new(0.5, 1.0))
+    }
+
+    /// Right half of the window.
+    pub fn right_half() -> Self {
+        Self::new(Vec2::new(0.5, 0.0), Vec2::one())
+    }
+
+    /// Top half of the window.
+    pub fn top_half() -> Self {
+        Self::new(Vec2::zero(), Vec2::new(1.0, 0.5))
+    }
+
+    /// Bottom half of the window.
+    pub fn bottom_half() -> Self {
+        Self::new(Vec2::new(0.0, 0.5), Vec2::one())
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    /// Returns `true` if rectangle lies within the window.
+    pub fn in_bounds(&self) -> bool {
+        let Self { min, max } = *self;
+        // Written this way to reject NaN coordinates too.
+        [min.x, min.y, max.x, max.y]
+            .iter()
+            .all(|&coord| (0.0..=1.0).contains(&coord))
+    }
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Region of the window where the scene is rendered from the camera.
+#[derive(Debug, Copy, Clone)]
+pub struct Viewport {
+    pub rect: Rect,
+    pub camera: Camera,
+}
+
+impl Viewport {
+    pub fn new(rect: Rect, camera: Camera) -> Self {
+        Self { rect, camera }
+    }
+}
+
+/// Checks that all viewports stay in window bounds and have some area.
+///
+/// Overlapping viewports are allowed.
+pub fn validate(viewports: &[Viewport]) -> Result<(), ViewportError> {
+    for (index, viewport) in viewports.iter().enumerate() {
+        let rect = viewport.rect;
+        if !rect.in_bounds() {
+            return Err(ViewportError::OutOfBounds(index));
+        }
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return Err(ViewportError::Empty(index));
+        }
+    }
+    Ok(())
+}
+
+/// Handle to the list of viewports which are rendered on each frame.
+///
+/// If the list is empty, the scene is rendered into the whole window
+/// from the [active camera](crate::camera::ActiveCamera).
+///
+/// Clones share one list, which is read by the renderer at the beginning of each frame.
+#[derive(Debug, Default, Clone)]
+pub struct ViewportList {
+    viewports: Arc<Mutex<Vec<Viewport>>>,
+}
+
+impl ViewportList {
+    /// Replaces all viewports with given ones.
+    ///
+    /// Viewports are drawn in array order, so the latter ones overlap the former ones.
+    pub fn set(&self, viewports: &[Viewport]) -> Result<(), ViewportError> {
+        self::validate(viewports)?;
+        *self.viewports.lock().unwrap() = viewports.to_vec();
+        Ok(())
+    }
+
+    /// Removes all viewports, so the scene is rendered into the whole window again.
+    pub fn clear(&self) {
+        self.viewports.lock().unwrap().clear()
+    }
+
+    /// Returns copy of current viewports.
+    pub fn get(&self) -> Vec<Viewport> {
+        self.viewports.lock().unwrap().clone()
+    }
+}
+
+/// Viewport in pixels of swapchain image.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Region {
+    pub origin: [f32; 2],
+    pub dimensions: [f32; 2],
+    pub depth_range: Range<f32>,
+    /// Aspect ratio of the region as seen in the window (width / height).
+    pub aspect_ratio: f32,
+}
+
+impl Region {
+    /// Converts rectangle of viewport with given index into region of swapchain image.
+    ///
+    /// Depth range of each viewport is a separate slice, so the latter viewports are
+    /// always closer than the former ones and overlap them in array order.
+    ///
+    pub fn new(
+        rect: Rect,
+        index: usize,
+        count: usize,
+        window_size: [f32; 2],
+        pre_transform: PreTransform,
+    ) -> Self {
+        let window = Vec2::from(window_size);
+        let (min, max) = (rect.min * window, rect.max * window);
+        let aspect_ratio = (max.x - min.x) / (max.y - min.y);
+        let (min, max) = pre_transform.transform_rect(min.into(), max.into(), window_size);
+
+        let slice = 1.0 / count as f32;
+        let far = 1.0 - slice * index as f32;
+        let near = (far - slice).max(0.0);
+        Self {
+            origin: min,
+            dimensions: [max[0] - min[0], max[1] - min[1]],
+            depth_range: near..far,
+            aspect_ratio,
+        }
+    }
+
+    pub fn viewport(&self) -> VkViewport {
+        VkViewport {
+            origin: self.origin,
+            dimensions: self.dimensions,
+            depth_range: self.depth_range.clone(),
+        }
+    }
+
+    pub fn scissor(&self) -> Scissor {
+        let [x, y] = self.origin.map(|coord| coord.round().max(0.0));
+        let [width, height] = self.dimensions;
+        Scissor {
+            origin: [x as u32, y as u32],
+            dimensions: [width.round() as u32, height.round() as u32],
+        }
+    }
+}
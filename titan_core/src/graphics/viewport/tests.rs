@@ -0,0 +1,85 @@
+#![cfg(test)]
+
+use ultraviolet::Vec2;
+
+use crate::{camera::Camera, graphics::pre_rotation::PreTransform};
+
+use super::*;
+
+fn viewport(min: (f32, f32), max: (f32, f32)) -> Viewport {
+    let rect = Rect::new(Vec2::new(min.0, min.1), Vec2::new(max.0, max.1));
+    Viewport::new(rect, Camera::default())
+}
+
+#[test]
+fn test_validate() {
+    let split = [
+        Viewport::new(Rect::left_half(), Camera::default()),
+        Viewport::new(Rect::right_half(), Camera::default()),
+    ];
+    assert_eq!(validate(&split), Ok(()));
+
+    // Overlapping viewports are allowed.
+    let overlap = [
+        viewport((0.0, 0.0), (1.0, 1.0)),
+        viewport((0.7, 0.7), (0.95, 0.95)),
+    ];
+    assert_eq!(validate(&overlap), Ok(()));
+
+    let out_of_bounds = [
+        viewport((0.0, 0.0), (0.5, 1.0)),
+        viewport((0.5, 0.0), (1.5, 1.0)),
+    ];
+    assert_eq!(validate(&out_of_bounds), Err(ViewportError::OutOfBounds(1)));
+
+    let nan = [viewport((f32::NAN, 0.0), (1.0, 1.0))];
+    assert_eq!(validate(&nan), Err(ViewportError::OutOfBounds(0)));
+
+    let empty = [viewport((0.5, 0.0), (0.5, 1.0))];
+    assert_eq!(validate(&empty), Err(ViewportError::Empty(0)));
+
+    let inverted = [viewport((1.0, 1.0), (0.0, 0.0))];
+    assert_eq!(validate(&inverted), Err(ViewportError::Empty(0)));
+}
+
+#[test]
+fn test_viewport_list() {
+    let list = ViewportList::default();
+    let viewports = [Viewport::new(Rect::top_half(), Camera::default())];
+    list.set(&viewports).unwrap();
+    assert_eq!(list.get().len(), 1);
+
+    // Invalid viewports do not replace current ones.
+    let invalid = [viewport((-0.5, 0.0), (0.5, 1.0))];
+    assert!(list.set(&invalid).is_err());
+    assert_eq!(list.get()[0].rect, Rect::top_half());
+
+    list.clear();
+    assert!(list.get().is_empty());
+}
+
+#[test]
+fn test_region() {
+    let window = [800.0, 600.0];
+    let region = Region::new(Rect::right_half(), 1, 2, window, PreTransform::IDENTITY);
+    assert_eq!(region.origin, [400.0, 0.0]);
+    assert_eq!(region.dimensions, [400.0, 600.0]);
+    assert_eq!(region.depth_range, 0.0..0.5);
+    assert!((region.aspect_ratio - 400.0 / 600.0).abs() < 1e-6);
+
+    let region = Region::new(Rect::left_half(), 0, 2, window, PreTransform::IDENTITY);
+    assert_eq!(region.depth_range, 0.5..1.0);
+
+    let region = Region::new(Rect::FULL, 0, 1, window, PreTransform::IDENTITY);
+    assert_eq!(region.depth_range, 0.0..1.0);
+}
+
+#[test]
+fn test_region_pre_rotation() {
+    // Swapchain image is in portrait orientation while window is in landscape one.
+    let window = [800.0, 600.0];
+    let region = Region::new(Rect::left_half(), 0, 1, window, PreTransform::new(1, false));
+    assert_eq!(region.dimensions, [600.0, 400.0]);
+    // Aspect ratio stays as seen in the window.
+    assert!((region.aspect_ratio - 400.0 / 600.0).abs() < 1e-6);
+}
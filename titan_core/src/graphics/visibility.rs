@@ -0,0 +1,64 @@
+//! Components which control whether entities are drawn and from which cameras.
+
+/// Component which hides the entity without despawning it.
+///
+/// Entities without this component are visible.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Visibility {
+    pub visible: bool,
+}
+
+impl Visibility {
+    /// Visibility of the entity which is drawn.
+    pub const VISIBLE: Self = Self { visible: true };
+
+    /// Visibility of the entity which is hidden.
+    pub const HIDDEN: Self = Self { visible: false };
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self::VISIBLE
+    }
+}
+
+/// Component with bitmask of layers which the entity belongs to.
+///
+/// Entity is drawn only from cameras which [`layers`](crate::camera::Camera::layers)
+/// intersect its mask. Entities without this component belong to the first layer
+/// (see [`RenderLayers::DEFAULT`]), so entity with empty mask is never drawn.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderLayers(pub u32);
+
+impl RenderLayers {
+    /// Layers of entities without the component and of the default camera.
+    pub const DEFAULT: Self = Self(1);
+
+    /// All layers at once.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// No layers, so the entity is never drawn.
+    pub const NONE: Self = Self(0);
+
+    /// Layer with given index (from 0 to 31).
+    pub const fn layer(index: u32) -> Self {
+        Self(1 << index)
+    }
+
+    /// Adds layer with given index (from 0 to 31) to the mask.
+    pub const fn with(self, index: u32) -> Self {
+        Self(self.0 | Self::layer(index).0)
+    }
+
+    /// Returns `true` if the mask has common layers with the mask of the camera.
+    pub const fn intersects(self, layers: u32) -> bool {
+        self.0 & layers != 0
+    }
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -1,9 +1,24 @@
 //! API for simple game engine based on Rust and Vulkan API.
 
 pub use app::init;
+pub use titan_tasks as task;
 
+#[cfg(target_os = "android")]
+pub mod android;
+pub mod animation;
 pub mod app;
+pub mod asset;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod camera;
 pub mod config;
+pub mod graphics;
+pub mod math;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod prelude;
+#[cfg(feature = "scene")]
+pub mod scene;
+pub mod settings;
+pub mod text;
 pub mod window;
-
-mod graphics;
@@ -0,0 +1,164 @@
+//! Bounding volumes and view frustum used for visibility tests.
+
+use ultraviolet::{Mat4, Vec3, Vec4};
+
+/// Axis-aligned bounding box.
+///
+/// It could be attached to the entity as a component to override bounds of its mesh.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Creates bounding box from its corners with minimal and maximal coordinates.
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest bounding box which contains all of the points,
+    /// or `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let aabb = points.fold(Self::new(first, first), |aabb, point| Self {
+            min: aabb.min.min_by_component(point),
+            max: aabb.max.max_by_component(point),
+        });
+        Some(aabb)
+    }
+
+    /// Center of the bounding box.
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Half of the size of the bounding box along each axis.
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Returns `true` if the point is inside of the bounding box or on its boundary.
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.min.x <= point.x
+            && point.x <= self.max.x
+            && self.min.y <= point.y
+            && point.y <= self.max.y
+            && self.min.z <= point.z
+            && point.z <= self.max.z
+    }
+
+    /// Eight corners of the bounding box.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let Self { min, max } = *self;
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Bounding box of this box transformed by the homogeneous matrix
+    /// (for example, by the matrix of [`GlobalTransform`](titan_ecs::GlobalTransform)).
+    ///
+    /// Result contains the whole transformed box, so it could be larger than the box itself
+    /// if the matrix contains rotation.
+    ///
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+        let mut extents = Vec3::zero();
+        for (column, half_extent) in matrix.cols[..3].iter().zip(half_extents.as_array()) {
+            extents += column.truncated().abs() * *half_extent;
+        }
+        Self::new(center - extents, center + extents)
+    }
+}
+
+/// Plane given by its unit normal and signed distance from the origin.
+///
+/// Points for which [`Plane::distance_to`] is positive are in front of the plane.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates plane from coefficients of its equation `a * x + b * y + c * z + d = 0`,
+    /// normalizing them so the normal becomes unit.
+    pub fn from_coefficients(coefficients: Vec4) -> Self {
+        let normal = coefficients.truncated();
+        let length = normal.mag();
+        Self {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    /// Signed distance from the plane to the point.
+    pub fn distance_to(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/// Volume which is visible from the camera, bounded by six planes.
+///
+/// Normals of all planes point inside of the frustum.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts planes of the frustum from the view-projection matrix.
+    ///
+    /// Matrix is expected to map visible points into Vulkan clip space,
+    /// where depth is in range from 0 to 1 (not from -1 to 1 as in OpenGL).
+    /// So the near plane is given by the third row alone rather than by its sum with the fourth.
+    /// Y axis of clip space points down, so planes are ordered as
+    /// left, right, top, bottom, near and far.
+    ///
+    pub fn from_matrix(view_projection: Mat4) -> Self {
+        let matrix = view_projection.transposed();
+        let [x, y, z, w] = matrix.cols;
+        let planes = [
+            w + x, // left
+            w - x, // right
+            w + y, // top
+            w - y, // bottom
+            z,     // near
+            w - z, // far
+        ];
+        Self {
+            planes: planes.map(Plane::from_coefficients),
+        }
+    }
+
+    /// Returns `true` if the point is inside of the frustum or on its boundary.
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.distance_to(point) >= 0.0)
+    }
+
+    /// Returns `false` if the bounding box is entirely outside of the frustum.
+    ///
+    /// Test is conservative: boxes near corners of the frustum could be reported
+    /// as intersecting ones even if they are outside, but visible boxes are never rejected.
+    ///
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let half_extents = aabb.half_extents();
+        self.planes.iter().all(|plane| {
+            let radius = plane.normal.abs().dot(half_extents);
+            plane.distance_to(center) >= -radius
+        })
+    }
+}
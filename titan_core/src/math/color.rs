@@ -0,0 +1,171 @@
+//! Color type which is used by all APIs of game engine.
+
+use palette::Srgba;
+
+/// Color with alpha channel in **linear** RGB space.
+///
+/// Components are stored exactly as shaders receive them, so colors are blended correctly
+/// and encoded into sRGB only once, when written into the sRGB swapchain image.
+/// Colors picked by the user (in image editors, CSS and so on) are usually in sRGB space,
+/// so they should be converted with [`Color::from_srgb`]
+/// or from [`Srgba`] and `[u8; 4]`, which are in sRGB space too.
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct Color {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+
+    /// Creates opaque color from linear components.
+    pub const fn rgb(red: f32, green: f32, blue: f32) -> Self {
+        Self::rgba(red, green, blue, 1.0)
+    }
+
+    /// Creates color from linear components.
+    pub const fn rgba(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Same color with another alpha.
+    pub const fn with_alpha(self, alpha: f32) -> Self {
+        Self { alpha, ..self }
+    }
+
+    /// Creates color from sRGB components (alpha is linear anyway).
+    pub fn from_srgb(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        let [red, green, blue] = [red, green, blue].map(self::srgb_to_linear);
+        Self::rgba(red, green, blue, alpha)
+    }
+
+    /// Components of the color in sRGB space (alpha is linear anyway).
+    pub fn to_srgb(self) -> [f32; 4] {
+        let [red, green, blue] = [self.red, self.green, self.blue].map(self::linear_to_srgb);
+        [red, green, blue, self.alpha]
+    }
+
+    /// Creates opaque color from hue (in degrees), saturation and value.
+    ///
+    /// Like in color pickers, HSV describes sRGB color.
+    ///
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let [red, green, blue] = match hue as u32 {
+            0 => [chroma, x, 0.0],
+            1 => [x, chroma, 0.0],
+            2 => [0.0, chroma, x],
+            3 => [0.0, x, chroma],
+            4 => [x, 0.0, chroma],
+            _ => [chroma, 0.0, x],
+        };
+        let min = value - chroma;
+        Self::from_srgb(red + min, green + min, blue + min, 1.0)
+    }
+
+    /// Hue (in degrees), saturation and value of the color in sRGB space.
+    pub fn to_hsv(self) -> [f32; 3] {
+        let [red, green, blue, _] = self.to_srgb();
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let chroma = max - min;
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        [hue, saturation, max]
+    }
+}
+
+/// Converts sRGB component into linear one.
+fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts linear component into sRGB one.
+fn linear_to_srgb(component: f32) -> f32 {
+    if component <= 0.0031308 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// sRGB color is converted into linear space.
+impl From<Srgba> for Color {
+    fn from(color: Srgba) -> Self {
+        Self::from_srgb(color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+/// Color is converted into sRGB space.
+impl From<Color> for Srgba {
+    fn from(color: Color) -> Self {
+        let [red, green, blue, alpha] = color.to_srgb();
+        Srgba::new(red, green, blue, alpha)
+    }
+}
+
+/// 8-bit components are in sRGB space (like colors of images or CSS).
+impl From<[u8; 4]> for Color {
+    fn from(color: [u8; 4]) -> Self {
+        let [red, green, blue, alpha] = color.map(|component| component as f32 / 255.0);
+        Self::from_srgb(red, green, blue, alpha)
+    }
+}
+
+/// Color is converted into 8-bit components in sRGB space.
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        color
+            .to_srgb()
+            .map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+}
+
+/// Components are linear and used as is.
+impl From<[f32; 4]> for Color {
+    fn from([red, green, blue, alpha]: [f32; 4]) -> Self {
+        Self::rgba(red, green, blue, alpha)
+    }
+}
+
+/// Linear components of the color.
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        [color.red, color.green, color.blue, color.alpha]
+    }
+}
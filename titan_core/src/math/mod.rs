@@ -0,0 +1,10 @@
+//! Math types of game engine which are shared between its APIs.
+
+pub use bounds::{Aabb, Frustum, Plane};
+pub use color::Color;
+pub use ray::Ray;
+
+mod bounds;
+mod color;
+mod ray;
+mod tests;
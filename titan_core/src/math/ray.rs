@@ -0,0 +1,104 @@
+//! Rays and their intersections used for picking.
+
+use ultraviolet::{Mat4, Vec3};
+
+use super::Aabb;
+
+/// Determinant below which the ray is treated as parallel to the triangle.
+const PARALLEL_EPSILON: f32 = 1e-7;
+
+/// Half-line which starts at the origin and goes along the unit direction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Creates ray from its origin and direction, which is normalized.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    /// Point of the ray at given distance from its origin.
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Same ray transformed by homogeneous matrix.
+    ///
+    /// Direction of the ray is not normalized, so distances along the transformed ray
+    /// are the same as distances along this one when they are passed into [`Ray::at`].
+    ///
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vec3(self.direction),
+        }
+    }
+
+    /// Distance along the ray at which it enters the bounding box,
+    /// or `None` if the ray misses it.
+    ///
+    /// Distance is zero if the ray starts inside of the box.
+    ///
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let origin = [self.origin.x, self.origin.y, self.origin.z];
+        let direction = [self.direction.x, self.direction.y, self.direction.z];
+        let min = [aabb.min.x, aabb.min.y, aabb.min.z];
+        let max = [aabb.max.x, aabb.max.y, aabb.max.z];
+
+        let mut enter = 0.0_f32;
+        let mut exit = f32::INFINITY;
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                // Ray is parallel to slab of the axis, so it must start between its planes.
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let inverse = 1.0 / direction[axis];
+            let near = (min[axis] - origin[axis]) * inverse;
+            let far = (max[axis] - origin[axis]) * inverse;
+            enter = enter.max(near.min(far));
+            exit = exit.min(near.max(far));
+            if enter > exit {
+                return None;
+            }
+        }
+        Some(enter)
+    }
+
+    /// Distance along the ray at which it hits the triangle (from any side),
+    /// or `None` if the ray misses it.
+    pub fn intersect_triangle(&self, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+        // Möller–Trumbore intersection algorithm.
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let s = self.origin - a;
+        let u = s.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = edge2.dot(q) * inverse;
+        if distance < 0.0 {
+            return None;
+        }
+        Some(distance)
+    }
+}
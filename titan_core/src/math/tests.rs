@@ -0,0 +1,225 @@
+#![cfg(test)]
+
+use palette::Srgba;
+use ultraviolet::{Mat4, Vec3};
+
+use crate::camera::Camera;
+
+use super::*;
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "{} is not close to {}",
+        actual,
+        expected,
+    );
+}
+
+#[test]
+fn test_srgb_to_linear() {
+    let color = Color::from([128, 128, 128, 128]);
+    assert_close(color.red, 0.2158);
+    assert_close(color.green, 0.2158);
+    assert_close(color.blue, 0.2158);
+    // Alpha is linear in both spaces.
+    assert_close(color.alpha, 128.0 / 255.0);
+
+    assert_eq!(Color::from([0, 0, 0, 0]), Color::TRANSPARENT);
+    assert_eq!(Color::from([255, 255, 255, 255]), Color::WHITE);
+    assert_eq!(Color::from(Srgba::new(1.0, 0.0, 0.0, 1.0)), Color::RED);
+    // Dark components are converted by the linear segment.
+    assert_close(Color::from_srgb(0.04, 0.0, 0.0, 1.0).red, 0.04 / 12.92);
+}
+
+#[test]
+fn test_round_trip() {
+    // Each 8-bit value survives conversion into linear space and back,
+    // so colors are not washed out by converting twice.
+    for value in 0..=255 {
+        let color = Color::from([value, value, value, value]);
+        assert_eq!(<[u8; 4]>::from(color), [value; 4]);
+    }
+
+    let srgba = Srgba::new(0.5, 0.25, 0.75, 0.5);
+    let srgba_back = Srgba::from(Color::from(srgba));
+    for (actual, expected) in [
+        (srgba_back.red, srgba.red),
+        (srgba_back.green, srgba.green),
+        (srgba_back.blue, srgba.blue),
+        (srgba_back.alpha, srgba.alpha),
+    ] {
+        assert_close(actual, expected);
+    }
+
+    // Linear components are used as is.
+    let linear = [0.2, 0.4, 0.6, 0.8];
+    assert_eq!(<[f32; 4]>::from(Color::from(linear)), linear);
+}
+
+#[test]
+fn test_hsv() {
+    assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+    assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+    assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::BLUE);
+    assert_eq!(Color::from_hsv(-300.0, 1.0, 1.0), Color::YELLOW);
+    assert_eq!(Color::from_hsv(42.0, 0.0, 1.0), Color::WHITE);
+
+    // HSV describes sRGB color, so gray of half value is sRGB 0.5.
+    let gray = Color::from_hsv(0.0, 0.0, 0.5);
+    assert_close(gray.to_srgb()[0], 0.5);
+    assert_close(gray.red, 0.2140);
+
+    let [hue, saturation, value] = Color::from_hsv(210.0, 0.5, 0.8).to_hsv();
+    assert_close(hue, 210.0);
+    assert_close(saturation, 0.5);
+    assert_close(value, 0.8);
+    assert_eq!(Color::BLACK.to_hsv(), [0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_const() {
+    const SHADOW: Color = Color::BLACK.with_alpha(0.5);
+    assert_eq!(SHADOW, Color::rgba(0.0, 0.0, 0.0, 0.5));
+    assert_eq!(Color::default(), Color::TRANSPARENT);
+}
+
+#[test]
+fn test_aabb_transformed() {
+    let aabb = Aabb::from_points([Vec3::new(-1.0, -2.0, 0.0), Vec3::new(1.0, 2.0, 1.0)]).unwrap();
+    assert_eq!(aabb.center(), Vec3::new(0.0, 0.0, 0.5));
+    assert_eq!(Aabb::from_points([]), None);
+
+    let matrix = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0))
+        * Mat4::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    let transformed = aabb.transformed(matrix);
+    for (actual, expected) in [
+        (transformed.min, Vec3::new(8.0, -1.0, 0.0)),
+        (transformed.max, Vec3::new(12.0, 1.0, 1.0)),
+    ] {
+        for (actual, expected) in actual.as_array().iter().zip(expected.as_array()) {
+            assert_close(*actual, *expected);
+        }
+    }
+}
+
+#[test]
+fn test_frustum_planes() {
+    // Camera at the origin looks along X axis, so near plane is at x = 1 and far plane is at x = 10.
+    let camera = Camera {
+        fov: std::f32::consts::FRAC_PI_2,
+        near: 1.0,
+        far: 10.0,
+        ..Camera::default()
+    };
+    let frustum = camera.frustum(1.0);
+    let [left, right, top, bottom, near, far] = frustum.planes;
+
+    assert_close(near.distance_to(Vec3::new(1.0, 0.0, 0.0)), 0.0);
+    assert_close(near.distance_to(Vec3::new(2.0, 0.0, 0.0)), 1.0);
+    assert_close(far.distance_to(Vec3::new(10.0, 0.0, 0.0)), 0.0);
+    assert_close(far.distance_to(Vec3::new(4.0, 0.0, 0.0)), 6.0);
+
+    // Field of view is 90 degrees, so side planes pass through the diagonals.
+    let sides = [
+        (left, Vec3::new(5.0, 5.0, 0.0)),
+        (right, Vec3::new(5.0, -5.0, 0.0)),
+        (top, Vec3::new(5.0, 0.0, 5.0)),
+        (bottom, Vec3::new(5.0, 0.0, -5.0)),
+    ];
+    for (plane, point) in sides {
+        assert_close(plane.distance_to(point), 0.0);
+        assert_close(plane.normal.mag(), 1.0);
+        assert!(plane.distance_to(Vec3::new(5.0, 0.0, 0.0)) > 0.0);
+    }
+
+    assert!(frustum.contains(Vec3::new(5.0, 0.0, 0.0)));
+    assert!(!frustum.contains(Vec3::new(0.5, 0.0, 0.0)));
+    assert!(!frustum.contains(Vec3::new(11.0, 0.0, 0.0)));
+    assert!(!frustum.contains(Vec3::new(-5.0, 0.0, 0.0)));
+}
+
+#[test]
+fn test_frustum_intersects() {
+    let camera = Camera {
+        fov: std::f32::consts::FRAC_PI_2,
+        near: 1.0,
+        far: 10.0,
+        ..Camera::default()
+    };
+    let frustum = camera.frustum(1.0);
+    let cube = |center: Vec3, half: f32| {
+        Aabb::new(
+            center - Vec3::broadcast(half),
+            center + Vec3::broadcast(half),
+        )
+    };
+
+    assert!(frustum.intersects(&cube(Vec3::new(5.0, 0.0, 0.0), 1.0)));
+    // Partially visible boxes crossing each plane.
+    assert!(frustum.intersects(&cube(Vec3::new(5.0, 5.5, 0.0), 1.0)));
+    assert!(frustum.intersects(&cube(Vec3::new(10.5, 0.0, 0.0), 1.0)));
+    assert!(frustum.intersects(&cube(Vec3::new(0.5, 0.0, 0.0), 0.6)));
+    // Boxes which are entirely outside.
+    assert!(!frustum.intersects(&cube(Vec3::new(-5.0, 0.0, 0.0), 1.0)));
+    assert!(!frustum.intersects(&cube(Vec3::new(5.0, 0.0, 8.0), 1.0)));
+    assert!(!frustum.intersects(&cube(Vec3::new(12.0, 0.0, 0.0), 1.0)));
+    // Box which encloses the camera is visible although all of its corners are outside.
+    let enclosing = cube(Vec3::zero(), 20.0);
+    assert!(enclosing
+        .corners()
+        .iter()
+        .all(|&corner| !frustum.contains(corner)));
+    assert!(frustum.intersects(&enclosing));
+}
+
+#[test]
+fn test_ray_aabb() {
+    let aabb = Aabb::new(Vec3::new(2.0, -1.0, -1.0), Vec3::new(4.0, 1.0, 1.0));
+    let ray = Ray::new(Vec3::zero(), Vec3::new(2.0, 0.0, 0.0));
+    assert_eq!(ray.direction, Vec3::unit_x());
+    assert_close(ray.intersect_aabb(&aabb).unwrap(), 2.0);
+    // Diagonal ray enters through the corner region.
+    let diagonal = Ray::new(Vec3::new(0.0, -2.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+    assert_close(
+        diagonal.intersect_aabb(&aabb).unwrap(),
+        2.0 * 2.0_f32.sqrt(),
+    );
+    // Rays which go away from the box or pass by it.
+    assert_eq!(
+        Ray::new(Vec3::zero(), -Vec3::unit_x()).intersect_aabb(&aabb),
+        None
+    );
+    let parallel = Ray::new(Vec3::new(0.0, 2.0, 0.0), Vec3::unit_x());
+    assert_eq!(parallel.intersect_aabb(&aabb), None);
+    // Ray which starts inside of the box hits it at the origin.
+    let inside = Ray::new(Vec3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 1.0));
+    assert_eq!(inside.intersect_aabb(&aabb), Some(0.0));
+}
+
+#[test]
+fn test_ray_triangle() {
+    let triangle = [
+        Vec3::new(0.0, -1.0, -1.0),
+        Vec3::new(0.0, 1.0, -1.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ];
+    let ray = Ray::new(Vec3::new(-3.0, 0.0, 0.0), Vec3::unit_x());
+    assert_close(ray.intersect_triangle(triangle).unwrap(), 3.0);
+    // Triangle is hit from both sides.
+    let back = Ray::new(Vec3::new(3.0, 0.0, 0.0), -Vec3::unit_x());
+    assert_close(back.intersect_triangle(triangle).unwrap(), 3.0);
+    // Rays which miss the triangle, go away from it or are parallel to it.
+    let outside = Ray::new(Vec3::new(-3.0, 0.5, 0.9), Vec3::unit_x());
+    assert_eq!(outside.intersect_triangle(triangle), None);
+    let away = Ray::new(Vec3::new(-3.0, 0.0, 0.0), -Vec3::unit_x());
+    assert_eq!(away.intersect_triangle(triangle), None);
+    let parallel = Ray::new(Vec3::new(-3.0, 0.0, 0.0), Vec3::unit_y());
+    assert_eq!(parallel.intersect_triangle(triangle), None);
+
+    // Distances along the transformed ray are the same.
+    let matrix = Mat4::from_scale(2.0);
+    let transformed = ray.transformed(matrix.inversed());
+    let scaled = triangle.map(|vertex| vertex * 0.5);
+    assert_close(transformed.intersect_triangle(scaled).unwrap(), 3.0);
+}
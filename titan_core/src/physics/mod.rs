@@ -0,0 +1,485 @@
+//! Physics of rigid bodies for entities of ECS, simulated by [rapier](rapier3d).
+//!
+//! Entities with [`RigidBody`] or [`Collider`] components are simulated by [`PhysicsSystem`],
+//! which should be stepped on each fixed update of the application
+//! (see [`Event::FixedUpdate`](crate::window::Event::FixedUpdate)), so the simulation
+//! does not depend on frame rate. Poses of bodies are read from [`Transform`]s of entities
+//! before each step and written back after it.
+//!
+//! The application does not own the world of the game, so the engine does not step
+//! the system by itself: the game calls [`PhysicsSystem::step`] with its world
+//! from the event callback.
+
+use std::collections::HashMap;
+
+use rapier3d::na::{Isometry3, Quaternion, Translation3, UnitQuaternion, Vector3};
+use rapier3d::prelude::{
+    BroadPhase, CCDSolver, ColliderBuilder, ColliderHandle, ColliderSet, IntegrationParameters,
+    IslandManager, JointSet, NarrowPhase, PhysicsPipeline, RigidBodyBuilder, RigidBodyHandle,
+    RigidBodySet,
+};
+use titan_ecs::{Entity, Transform, World};
+use ultraviolet::{Rotor3, Vec3};
+
+use crate::app::DeltaTime;
+
+mod tests;
+
+/// Gravity of [`PhysicsSystem::new`], in meters per second squared.
+pub const DEFAULT_GRAVITY: Vec3 = Vec3::new(0.0, -9.81, 0.0);
+
+/// How the rigid body is moved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BodyKind {
+    /// Body is moved by forces, gravity and contacts.
+    Dynamic,
+    /// Body is never moved by the simulation.
+    Fixed,
+    /// Body is moved by changes of its [`Transform`] only,
+    /// pushing dynamic bodies out of its way.
+    Kinematic,
+}
+
+/// Component of the entity which is simulated as the rigid body.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    /// Damping of linear velocity of the body.
+    pub linear_damping: f32,
+    /// Damping of angular velocity of the body.
+    pub angular_damping: f32,
+    /// Scale of gravity which is applied to the body.
+    pub gravity_scale: f32,
+    /// Whether continuous collision detection is enabled, so fast body does not pass
+    /// through thin colliders.
+    pub ccd: bool,
+}
+
+impl RigidBody {
+    /// Creates body of given kind with default parameters.
+    pub fn new(kind: BodyKind) -> Self {
+        Self {
+            kind,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            gravity_scale: 1.0,
+            ccd: false,
+        }
+    }
+
+    /// Creates body which is moved by the simulation.
+    pub fn dynamic() -> Self {
+        Self::new(BodyKind::Dynamic)
+    }
+
+    /// Creates body which is never moved by the simulation.
+    pub fn fixed() -> Self {
+        Self::new(BodyKind::Fixed)
+    }
+
+    /// Creates body which is moved by changes of its transform.
+    pub fn kinematic() -> Self {
+        Self::new(BodyKind::Kinematic)
+    }
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        Self::dynamic()
+    }
+}
+
+/// Shape of the collider in local space of the entity.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColliderShape {
+    Ball {
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// Capsule which is aligned with Y axis.
+    Capsule {
+        half_height: f32,
+        radius: f32,
+    },
+}
+
+/// Component of the entity which collides with other colliders.
+///
+/// Entity with collider but without [`RigidBody`] is simulated as the fixed body.
+/// Scale of the [`Transform`] of the entity is not applied to the shape.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub friction: f32,
+    /// Bounciness of the collider (0 means no bounce).
+    pub restitution: f32,
+    /// Density which mass of the body is computed from.
+    pub density: f32,
+    /// Whether the collider only detects intersections without generating contacts.
+    pub sensor: bool,
+}
+
+impl Collider {
+    /// Creates collider of given shape with default parameters.
+    pub fn new(shape: ColliderShape) -> Self {
+        Self {
+            shape,
+            friction: 0.5,
+            restitution: 0.0,
+            density: 1.0,
+            sensor: false,
+        }
+    }
+
+    /// Creates ball collider.
+    pub fn ball(radius: f32) -> Self {
+        Self::new(ColliderShape::Ball { radius })
+    }
+
+    /// Creates box collider.
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    /// Creates capsule collider which is aligned with Y axis.
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        Self::new(ColliderShape::Capsule {
+            half_height,
+            radius,
+        })
+    }
+}
+
+/// Rapier objects which simulate one entity.
+struct Body {
+    handle: RigidBodyHandle,
+    desc: RigidBody,
+    collider: Option<(ColliderHandle, Collider)>,
+    /// Transform which was last synchronized with the body.
+    transform: Transform,
+}
+
+/// System which simulates physics of entities of the world.
+///
+/// ```no_run
+/// # use titan_core::physics::{Collider, PhysicsSystem, RigidBody};
+/// # use titan_core::window::Event;
+/// # use titan_ecs::{Transform, World};
+/// # use ultraviolet::Vec3;
+/// # fn callback(event: Event) {
+/// let mut world = World::new();
+/// let ball = world.spawn();
+/// world.insert(ball, Transform::from_translation(Vec3::unit_y() * 10.0));
+/// world.insert(ball, RigidBody::dynamic());
+/// world.insert(ball, Collider::ball(0.5));
+///
+/// let mut physics = PhysicsSystem::new();
+/// if let Event::FixedUpdate(step) = event {
+///     physics.step(&mut world, step);
+/// }
+/// # }
+/// ```
+///
+pub struct PhysicsSystem {
+    gravity: Vec3,
+    pipeline: PhysicsPipeline,
+    parameters: IntegrationParameters,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    joints: JointSet,
+    ccd_solver: CCDSolver,
+    entities: HashMap<Entity, Body>,
+}
+
+impl PhysicsSystem {
+    /// Creates system without bodies and with [default gravity](DEFAULT_GRAVITY).
+    pub fn new() -> Self {
+        Self {
+            gravity: DEFAULT_GRAVITY,
+            pipeline: PhysicsPipeline::new(),
+            parameters: IntegrationParameters::default(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            joints: JointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    /// Gravity which is applied to dynamic bodies.
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    /// Sets gravity which is applied to dynamic bodies.
+    pub fn set_gravity(&mut self, gravity: Vec3) {
+        self.gravity = gravity
+    }
+
+    /// Count of simulated entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if there are no simulated entities.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Returns `true` if the entity is simulated (it is added on the next step
+    /// after it gets [`RigidBody`] or [`Collider`] component).
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains_key(&entity)
+    }
+
+    /// Linear velocity of the simulated entity.
+    pub fn linear_velocity(&self, entity: Entity) -> Option<Vec3> {
+        let body = self.entities.get(&entity)?;
+        let velocity = self.bodies.get(body.handle)?.linvel();
+        Some(self::vec3(velocity))
+    }
+
+    /// Sets linear velocity of the simulated entity.
+    ///
+    /// Returns `false` if the entity is not simulated.
+    ///
+    pub fn set_linear_velocity(&mut self, entity: Entity, velocity: Vec3) -> bool {
+        self.body_mut(entity)
+            .map(|body| body.set_linvel(self::vector(velocity), true))
+            .is_some()
+    }
+
+    /// Applies impulse to the center of mass of the simulated entity.
+    ///
+    /// Returns `false` if the entity is not simulated.
+    ///
+    pub fn apply_impulse(&mut self, entity: Entity, impulse: Vec3) -> bool {
+        self.body_mut(entity)
+            .map(|body| body.apply_impulse(self::vector(impulse), true))
+            .is_some()
+    }
+
+    /// Synchronizes bodies with components of the world, advances the simulation
+    /// by the fixed step and writes poses of moved bodies into their [`Transform`]s.
+    ///
+    /// Simulated entities should be roots of the hierarchy, because their transforms
+    /// are treated as relative to the world.
+    ///
+    pub fn step(&mut self, world: &mut World, step: DeltaTime) {
+        self.sync_bodies(world);
+
+        self.parameters.dt = step.as_secs_f32();
+        self.pipeline.step(
+            &self::vector(self.gravity),
+            &self.parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joints,
+            &mut self.ccd_solver,
+            &(),
+            &(),
+        );
+
+        for (&entity, body) in &mut self.entities {
+            if body.desc.kind == BodyKind::Fixed {
+                continue;
+            }
+            let position = self.bodies[body.handle].position();
+            let transform = self::transform(position, body.transform.scale);
+            if transform != body.transform {
+                body.transform = transform;
+                world.insert(entity, transform);
+            }
+        }
+    }
+
+    /// Adds, updates and removes bodies by components of the world.
+    fn sync_bodies(&mut self, world: &World) {
+        let removed: Vec<_> = self
+            .entities
+            .keys()
+            .copied()
+            .filter(|&entity| {
+                !world.attached::<RigidBody>(entity) && !world.attached::<Collider>(entity)
+            })
+            .collect();
+        for entity in removed {
+            let body = self.entities.remove(&entity).unwrap();
+            self.remove_body(body.handle);
+        }
+
+        let colliders = world
+            .query::<Collider>()
+            .map(|(entity, _)| entity)
+            .filter(|&entity| !world.attached::<RigidBody>(entity));
+        let simulated = world
+            .query::<RigidBody>()
+            .map(|(entity, _)| entity)
+            .chain(colliders);
+        for entity in simulated {
+            let desc = world
+                .get::<RigidBody>(entity)
+                .copied()
+                .unwrap_or_else(RigidBody::fixed);
+            let collider = world.get::<Collider>(entity).copied();
+            let transform = world.get::<Transform>(entity).copied().unwrap_or_default();
+            self.sync_body(entity, desc, collider, transform);
+        }
+    }
+
+    /// Creates or updates body of the entity.
+    fn sync_body(
+        &mut self,
+        entity: Entity,
+        desc: RigidBody,
+        collider: Option<Collider>,
+        transform: Transform,
+    ) {
+        let changed = self
+            .entities
+            .get(&entity)
+            .map_or(true, |body| body.desc != desc);
+        // Body is recreated with the same velocity if its parameters have changed.
+        if changed {
+            let (linvel, angvel) = match self.entities.remove(&entity) {
+                Some(previous) if desc.kind == BodyKind::Fixed => {
+                    self.remove_body(previous.handle);
+                    Default::default()
+                }
+                Some(previous) => {
+                    let body = &self.bodies[previous.handle];
+                    let velocities = (*body.linvel(), *body.angvel());
+                    self.remove_body(previous.handle);
+                    velocities
+                }
+                None => Default::default(),
+            };
+            let builder = match desc.kind {
+                BodyKind::Dynamic => RigidBodyBuilder::new_dynamic(),
+                BodyKind::Fixed => RigidBodyBuilder::new_static(),
+                BodyKind::Kinematic => RigidBodyBuilder::new_kinematic_position_based(),
+            };
+            let rigid_body = builder
+                .position(self::isometry(&transform))
+                .linvel(linvel)
+                .angvel(angvel)
+                .linear_damping(desc.linear_damping)
+                .angular_damping(desc.angular_damping)
+                .gravity_scale(desc.gravity_scale)
+                .ccd_enabled(desc.ccd)
+                .build();
+            let body = Body {
+                handle: self.bodies.insert(rigid_body),
+                desc,
+                collider: None,
+                transform,
+            };
+            self.entities.insert(entity, body);
+        }
+        let body = self.entities.get_mut(&entity).unwrap();
+
+        // Transform was changed by the user since the last step, so the body is teleported.
+        if body.transform != transform {
+            body.transform = transform;
+            let rigid_body = &mut self.bodies[body.handle];
+            let position = self::isometry(&transform);
+            match desc.kind {
+                BodyKind::Kinematic => rigid_body.set_next_kinematic_position(position),
+                _ => rigid_body.set_position(position, true),
+            }
+        }
+
+        if body.collider.map(|(_, previous)| previous) == collider {
+            return;
+        }
+        if let Some((handle, _)) = body.collider.take() {
+            self.colliders
+                .remove(handle, &mut self.islands, &mut self.bodies, true);
+        }
+        if let Some(collider) = collider {
+            let handle = self.colliders.insert_with_parent(
+                self::build_collider(&collider),
+                body.handle,
+                &mut self.bodies,
+            );
+            body.collider = Some((handle, collider));
+        }
+    }
+
+    /// Removes rapier body with its colliders.
+    fn remove_body(&mut self, handle: RigidBodyHandle) {
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.joints,
+        );
+    }
+
+    fn body_mut(&mut self, entity: Entity) -> Option<&mut rapier3d::prelude::RigidBody> {
+        let body = self.entities.get(&entity)?;
+        self.bodies.get_mut(body.handle)
+    }
+}
+
+impl Default for PhysicsSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn build_collider(collider: &Collider) -> rapier3d::prelude::Collider {
+    let builder = match collider.shape {
+        ColliderShape::Ball { radius } => ColliderBuilder::ball(radius),
+        ColliderShape::Cuboid { half_extents } => {
+            ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z)
+        }
+        ColliderShape::Capsule {
+            half_height,
+            radius,
+        } => ColliderBuilder::capsule_y(half_height, radius),
+    };
+    builder
+        .friction(collider.friction)
+        .restitution(collider.restitution)
+        .density(collider.density)
+        .sensor(collider.sensor)
+        .build()
+}
+
+fn vector(vector: Vec3) -> Vector3<f32> {
+    Vector3::new(vector.x, vector.y, vector.z)
+}
+
+fn vec3(vector: &Vector3<f32>) -> Vec3 {
+    Vec3::new(vector.x, vector.y, vector.z)
+}
+
+/// Pose of the body from translation and rotation of the transform.
+pub(crate) fn isometry(transform: &Transform) -> Isometry3<f32> {
+    let [x, y, z, w] = transform.rotation.into_quaternion_array();
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z));
+    let translation = Translation3::from(self::vector(transform.translation));
+    Isometry3::from_parts(translation, rotation)
+}
+
+/// Transform with translation and rotation of the pose of the body and given scale.
+pub(crate) fn transform(isometry: &Isometry3<f32>, scale: Vec3) -> Transform {
+    let coords = isometry.rotation.coords;
+    let rotation = Rotor3::from_quaternion_array([coords.x, coords.y, coords.z, coords.w]);
+    Transform::from_translation(self::vec3(&isometry.translation.vector))
+        .with_rotation(rotation)
+        .with_scale(scale)
+}
@@ -0,0 +1,87 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use titan_ecs::{Transform, World};
+use ultraviolet::{Rotor3, Vec3};
+
+use super::*;
+
+const STEP: Duration = Duration::from_micros(16_667);
+
+/// World with the ground at the origin and the ball above it.
+fn world() -> (World, Entity, Entity) {
+    let mut world = World::new();
+    let ground = world.spawn();
+    world.insert(ground, Transform::default());
+    world.insert(ground, Collider::cuboid(Vec3::new(10.0, 0.5, 10.0)));
+    let ball = world.spawn();
+    world.insert(ball, Transform::from_translation(Vec3::unit_y() * 5.0));
+    world.insert(ball, RigidBody::dynamic());
+    world.insert(ball, Collider::ball(0.5));
+    (world, ground, ball)
+}
+
+fn height(world: &World, entity: Entity) -> f32 {
+    world.get::<Transform>(entity).unwrap().translation.y
+}
+
+#[test]
+fn test_isometry() {
+    let transform = Transform::from_translation(Vec3::new(1.0, 2.0, 3.0))
+        .with_rotation(Rotor3::from_rotation_xz(0.5))
+        .with_scale(Vec3::broadcast(2.0));
+    let converted = self::transform(&self::isometry(&transform), transform.scale);
+    assert!((converted.translation - transform.translation).mag() < 1e-6);
+    let [x, y, z, w] = converted.rotation.into_quaternion_array();
+    let [ex, ey, ez, ew] = transform.rotation.into_quaternion_array();
+    assert!((x - ex).abs() + (y - ey).abs() + (z - ez).abs() + (w - ew).abs() < 1e-5);
+    assert_eq!(converted.scale, transform.scale);
+}
+
+#[test]
+fn test_falling_ball() {
+    let (mut world, ground, ball) = self::world();
+    let mut physics = PhysicsSystem::new();
+    physics.step(&mut world, STEP);
+    assert_eq!(physics.len(), 2);
+    assert!(height(&world, ball) < 5.0);
+
+    for _ in 0..300 {
+        physics.step(&mut world, STEP);
+    }
+    // Ball rests on the ground, which is not moved.
+    assert!((height(&world, ball) - 1.0).abs() < 0.05);
+    assert_eq!(height(&world, ground), 0.0);
+    assert!(physics.linear_velocity(ball).unwrap().mag() < 0.1);
+}
+
+#[test]
+fn test_sync() {
+    let (mut world, ground, ball) = self::world();
+    let mut physics = PhysicsSystem::new();
+    physics.set_gravity(Vec3::zero());
+    physics.step(&mut world, STEP);
+    assert_eq!(height(&world, ball), 5.0);
+
+    // Changed transform teleports the body.
+    world.insert(ball, Transform::from_translation(Vec3::unit_y() * 3.0));
+    assert!(physics.set_linear_velocity(ball, Vec3::unit_y()));
+    physics.step(&mut world, STEP);
+    assert!((height(&world, ball) - 3.0 - STEP.as_secs_f32()).abs() < 1e-3);
+
+    // Changed parameters keep velocity of the body.
+    world.get_mut::<RigidBody>(ball).unwrap().linear_damping = 0.5;
+    physics.step(&mut world, STEP);
+    assert!(physics.linear_velocity(ball).unwrap().y > 0.9);
+
+    world.remove::<RigidBody>(ball);
+    physics.step(&mut world, STEP);
+    assert_eq!(physics.linear_velocity(ball), Some(Vec3::zero()));
+
+    world.despawn(ball);
+    world.remove::<Collider>(ground);
+    physics.step(&mut world, STEP);
+    assert!(physics.is_empty());
+    assert!(!physics.apply_impulse(ball, Vec3::unit_x()));
+}
@@ -0,0 +1,83 @@
+//! Stable user-facing types of game engine.
+//!
+//! Game could be written with only this prelude imported:
+//!
+//! ```no_run
+//! use titan_core::prelude::*;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let config = Config::new("Game".to_string(), Version::new(0, 1, 0), false);
+//!     let application = titan_core::init(config)?;
+//!     let active_camera = application.active_camera();
+//!     let mut camera = Camera::look_at(Vec3::new(2.0, 2.0, 2.0), Vec3::zero());
+//!     application.run(move |event| match event {
+//!         Event::Update(delta_time) => {
+//!             camera.yaw += delta_time.as_secs_f32();
+//!             active_camera.set(camera);
+//!         }
+//!         Event::UI(ctx) => {
+//!             egui::Window::new("Camera").show(&ctx, |ui| {
+//!                 ui.label(format!("yaw: {:.2}", camera.yaw));
+//!             });
+//!         }
+//!         _ => (),
+//!     })
+//! }
+//! ```
+
+pub use egui;
+pub use image;
+pub use log;
+
+pub use palette::Srgba;
+pub use semver::Version;
+pub use ultraviolet::{Mat3, Mat4, Rotor3, Vec2, Vec3, Vec4};
+
+pub use crate::{
+    animation::{AnimationId, Animator, Easing, Interpolation, Lerp, Playback, Track},
+    app::{Application, DeltaTime, ExitCause, ExitHandle, ExitReport, FixedClock, ScriptedEvents},
+    asset::{
+        AssetEvent, AssetId, AssetServer, LoadState, MeshHandle, MeshSource, Primitive,
+        ShaderHandle,
+    },
+    camera::{
+        controller::{FlyController, OrbitController},
+        ActiveCamera, Camera, Camera2D, ProjectionMode,
+    },
+    config::{ArgsError, Config},
+    graphics::{
+        AaMode, ClipRect, ClipStack, CullingReport, DrawCommand, DrawQueue, EmitterShape,
+        FrameContext, FrameStats, GpuTimings, HookCommands, HookError, HookStage, IndexBuffer,
+        IndexType, IndirectBufferId, IndirectDraw, IndirectDrawList, InstanceData, LoadOp, MeshId,
+        ParticleEmitter, ParticleParams, PassOps, PauseControl, PipelineStatistics, PresentMode,
+        QualityConfig, QualityMonitor, Rect, RenderHook, RenderLayers, SampleCount, SamplerDesc,
+        Screenshot, SpecializationInfo, Sprite, SpriteBatch, SpriteTextureId, StoreOp, StreamId,
+        StreamState, StreamingConfig, StreamingManager, TextureData, TimeoutPolicy, UniformBuffer,
+        ValidationFilter, ValidationLevel, ValidationSeverity, Vertex, Viewport, ViewportList,
+        Visibility,
+    },
+    init,
+    math::{Aabb, Color, Frustum},
+    task::TaskPool,
+    text::{Align, TextBrush, TextSection},
+    window::{
+        gamepad::{Axis as GamepadAxis, Button as GamepadButton, GamepadId},
+        input::{Key, MouseButton},
+        CustomEvent, DisplayControl, Event, EventProxy, FullscreenMode, Input, ScreenSpace, Size,
+        WindowDesc, WindowEvent, WindowIcon, WindowId, WindowManager,
+    },
+};
+
+#[cfg(feature = "audio")]
+pub use crate::audio::{
+    AudioEmitter, AudioListener, AudioServer, PlaybackParams, SoundHandle, SoundInstance,
+};
+
+#[cfg(feature = "inspector")]
+pub use crate::app::WorldInspector;
+
+#[cfg(feature = "physics")]
+pub use crate::physics::{BodyKind, Collider, ColliderShape, PhysicsSystem, RigidBody};
+
+#[cfg(feature = "scene")]
+pub use crate::scene::{BlendMode, Material, Mesh, Name, Scene, SceneError, SceneRegistry};
@@ -0,0 +1,65 @@
+//! Components of entities which are spawned from scenes.
+
+use std::path::PathBuf;
+
+use crate::asset::{MeshHandle, MeshSource};
+use crate::graphics::StreamId;
+use crate::math::Color;
+
+/// Component which references the mesh of the entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    /// Reference which the mesh was resolved from.
+    pub source: MeshSource,
+    pub handle: MeshHandle,
+}
+
+/// Component which makes picking test the ray against triangles of the mesh
+/// instead of its bounds (see [`pick`](super::pick)).
+///
+/// Triangles must be stored with [`AssetServer::set_mesh_triangles`](crate::asset::AssetServer::set_mesh_triangles),
+/// otherwise the entity is picked by its bounds.
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PrecisePick;
+
+/// How colors of the entity are blended with colors behind it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Colors behind the entity are overwritten.
+    #[default]
+    Opaque,
+    /// Colors are blended by alpha channel of the entity.
+    Alpha,
+    /// Colors of the entity are added to colors behind it.
+    Additive,
+}
+
+impl BlendMode {
+    /// All blend modes.
+    pub const ALL: [Self; 3] = [Self::Opaque, Self::Alpha, Self::Additive];
+
+    /// Name of the blend mode which is used in scene files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Opaque => "opaque",
+            Self::Alpha => "alpha",
+            Self::Additive => "additive",
+        }
+    }
+}
+
+/// Texture of the material with the file it is streamed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialTexture {
+    pub path: PathBuf,
+    pub id: StreamId,
+}
+
+/// Component which stores parameters of the entity surface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub color: Color,
+    pub texture: Option<MaterialTexture>,
+    pub blend: BlendMode,
+}
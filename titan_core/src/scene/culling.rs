@@ -0,0 +1,96 @@
+//! Frustum culling of entities which are drawn with meshes.
+
+use titan_ecs::{Entity, GlobalTransform, World};
+
+use crate::asset::AssetServer;
+use crate::graphics::{CullingStats, RenderLayers, Visibility};
+use crate::math::{Aabb, Frustum};
+
+use super::Mesh;
+
+/// Entities with [`Mesh`] split by their visibility from the camera.
+#[derive(Debug, Default, Clone)]
+pub struct Culling {
+    /// Entities which should be drawn.
+    pub visible: Vec<Entity>,
+    /// Entities outside of the frustum with their bounds in world space
+    /// (for example, to draw these bounds while debugging culling).
+    pub culled: Vec<(Entity, Aabb)>,
+    /// Entities which are hidden by [`Visibility`]
+    /// or are not on layers of the camera (see [`RenderLayers`]).
+    pub hidden: Vec<Entity>,
+}
+
+impl Culling {
+    /// Counts of visible and culled entities which could be reported
+    /// with [`CullingReport`](crate::graphics::CullingReport).
+    pub fn stats(&self) -> CullingStats {
+        CullingStats {
+            submitted: self.visible.len() as u32,
+            culled: self.culled.len() as u32,
+            hidden: self.hidden.len() as u32,
+        }
+    }
+}
+
+/// Skips entities with [`Mesh`] which are hidden, are not on given layers of the camera
+/// (see [`Camera::layers`](crate::camera::Camera::layers)) or are entirely outside of the frustum.
+///
+/// Components are read on each call, so changes of [`Visibility`] and [`RenderLayers`]
+/// take effect in the same frame.
+///
+/// Bounds of the entity are taken from its [`Aabb`] component if it is attached,
+/// otherwise from bounds of its mesh stored in the asset server.
+/// Bounds are transformed by [`GlobalTransform`] of the entity, so it should be run
+/// after [`propagate_transforms`](titan_ecs::propagate_transforms).
+/// Entities with unknown bounds are never culled.
+///
+pub fn cull(world: &World, assets: &AssetServer, frustum: &Frustum, layers: u32) -> Culling {
+    let mut culling = Culling::default();
+    for (entity, mesh) in world.query::<Mesh>() {
+        let visibility = world.get::<Visibility>(entity).copied().unwrap_or_default();
+        let entity_layers = world
+            .get::<RenderLayers>(entity)
+            .copied()
+            .unwrap_or_default();
+        if !visibility.visible || !entity_layers.intersects(layers) {
+            culling.hidden.push(entity);
+            continue;
+        }
+        let bounds = match self::world_bounds(world, assets, entity, mesh) {
+            Some(bounds) => bounds,
+            None => {
+                culling.visible.push(entity);
+                continue;
+            }
+        };
+        if frustum.intersects(&bounds) {
+            culling.visible.push(entity);
+        } else {
+            culling.culled.push((entity, bounds));
+        }
+    }
+    culling
+}
+
+/// Bounds of the entity with the mesh in world space, or `None` if they are not known.
+///
+/// Bounds are taken from [`Aabb`] component of the entity if it is attached,
+/// otherwise from bounds of its mesh stored in the asset server.
+///
+pub(super) fn world_bounds(
+    world: &World,
+    assets: &AssetServer,
+    entity: Entity,
+    mesh: &Mesh,
+) -> Option<Aabb> {
+    let bounds = world
+        .get::<Aabb>(entity)
+        .copied()
+        .or_else(|| assets.mesh_bounds(mesh.handle))?;
+    let bounds = match world.get::<GlobalTransform>(entity) {
+        Some(transform) => bounds.transformed(transform.matrix()),
+        None => bounds,
+    };
+    Some(bounds)
+}
@@ -0,0 +1,330 @@
+//! Parsing and serialization of scenes in RON format.
+//!
+//! Scene is parsed into RON values first, then each field is parsed separately,
+//! so errors carry the entity and the field which failed to parse.
+
+use std::path::PathBuf;
+
+use palette::Srgba;
+use ron::{Map, Value};
+use serde::de::DeserializeOwned;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use titan_ecs::Transform;
+use ultraviolet::{Rotor3, Vec3};
+
+use super::{entity_label, BlendMode, SceneEntity, SceneError, SceneMaterial};
+
+/// Fields of the struct which are taken one by one.
+struct Fields {
+    /// Label of the entity which owns the struct.
+    entity: String,
+    /// Field of the entity which contains the struct, if any.
+    owner: Option<&'static str>,
+    map: Map,
+}
+
+impl Fields {
+    fn new(entity: String, owner: Option<&'static str>, value: Value) -> Result<Self, SceneError> {
+        let map = match value {
+            Value::Map(map) => map,
+            Value::Unit => Map::new(),
+            _ => {
+                let message = "expected a struct".to_string();
+                return Err(match owner {
+                    Some(field) => SceneError::Field {
+                        entity,
+                        field: field.to_string(),
+                        message,
+                    },
+                    None => SceneError::Invalid(format!("entity {}: {}", entity, message)),
+                });
+            }
+        };
+        Ok(Self { entity, owner, map })
+    }
+
+    fn error(&self, field: &str, message: impl ToString) -> SceneError {
+        let field = match self.owner {
+            Some(owner) => format!("{}.{}", owner, field),
+            None => field.to_string(),
+        };
+        SceneError::Field {
+            entity: self.entity.clone(),
+            field,
+            message: message.to_string(),
+        }
+    }
+
+    fn take_value(&mut self, field: &str) -> Option<Value> {
+        self.map.remove(&Value::String(field.to_string()))
+    }
+
+    fn parse<T>(&self, field: &str, value: Value) -> Result<T, SceneError>
+    where
+        T: DeserializeOwned,
+    {
+        value
+            .into_rust()
+            .map_err(|error| self.error(field, error.code))
+    }
+
+    fn take<T>(&mut self, field: &str) -> Result<Option<T>, SceneError>
+    where
+        T: DeserializeOwned,
+    {
+        match self.take_value(field) {
+            Some(value) => self.parse(field, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Fails if any field was not taken.
+    fn finish(self) -> Result<(), SceneError> {
+        match self.map.keys().next() {
+            Some(Value::String(field)) => Err(self.error(field, "unknown field")),
+            Some(key) => Err(self.error(&format!("{:?}", key), "field name must be a string")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses entities of the scene from RON string.
+pub(super) fn parse(s: &str) -> Result<Vec<SceneEntity>, SceneError> {
+    let mut scene = match ron::from_str::<Value>(s)? {
+        Value::Map(scene) => scene,
+        _ => return Err(SceneError::Invalid("expected a struct".to_string())),
+    };
+    let entities = match scene.remove(&Value::String("entities".to_string())) {
+        Some(Value::Seq(entities)) => entities,
+        Some(_) => {
+            let message = "field `entities` must be a sequence".to_string();
+            return Err(SceneError::Invalid(message));
+        }
+        None => return Err(SceneError::Invalid("missing field `entities`".to_string())),
+    };
+    if let Some(key) = scene.keys().next() {
+        return Err(SceneError::Invalid(format!("unknown field {:?}", key)));
+    }
+    entities
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| self::parse_entity(index, value))
+        .collect()
+}
+
+fn parse_entity(index: usize, value: Value) -> Result<SceneEntity, SceneError> {
+    let mut fields = Fields::new(entity_label(None, index), None, value)?;
+    let name: Option<String> = fields.take("name")?;
+    fields.entity = entity_label(name.as_deref(), index);
+
+    let transform = match fields.take_value("transform") {
+        Some(value) => Some(self::parse_transform(fields.entity.clone(), value)?),
+        None => None,
+    };
+    let mesh = match fields.take::<String>("mesh")? {
+        Some(mesh) => Some(mesh.parse().map_err(|error| fields.error("mesh", error))?),
+        None => None,
+    };
+    let material = match fields.take_value("material") {
+        Some(value) => Some(self::parse_material(fields.entity.clone(), value)?),
+        None => None,
+    };
+    let parent = fields.take("parent")?;
+    let components = match fields.take_value("components") {
+        Some(value) => self::parse_components(&fields, value)?,
+        None => Vec::new(),
+    };
+    fields.finish()?;
+
+    Ok(SceneEntity {
+        name,
+        transform,
+        mesh,
+        material,
+        parent,
+        components,
+    })
+}
+
+/// Takes values of components by their names, which are deserialized when the scene
+/// is instantiated (see [`SceneRegistry`](super::SceneRegistry)).
+fn parse_components(fields: &Fields, value: Value) -> Result<Vec<(String, Value)>, SceneError> {
+    let map = match value {
+        Value::Map(map) => map,
+        _ => return Err(fields.error("components", "expected a map")),
+    };
+    map.iter()
+        .map(|(name, value)| match name {
+            Value::String(name) => Ok((name.clone(), value.clone())),
+            _ => Err(fields.error("components", "component name must be a string")),
+        })
+        .collect()
+}
+
+fn parse_transform(entity: String, value: Value) -> Result<Transform, SceneError> {
+    let mut fields = Fields::new(entity, Some("transform"), value)?;
+    let mut transform = Transform::identity();
+
+    if let Some([x, y, z]) = fields.take::<[f32; 3]>("position")? {
+        transform.translation = Vec3::new(x, y, z);
+    }
+    if let Some(rotation) = fields.take::<Vec<f32>>("rotation")? {
+        transform.rotation = match rotation[..] {
+            [roll, pitch, yaw] => {
+                Rotor3::from_euler_angles(roll.to_radians(), pitch.to_radians(), yaw.to_radians())
+            }
+            [x, y, z, w] if [x, y, z, w] != [0.0; 4] => {
+                Rotor3::from_quaternion_array([x, y, z, w]).normalized()
+            }
+            [_, _, _, _] => return Err(fields.error("rotation", "quaternion must not be zero")),
+            _ => {
+                let message = "expected 3 euler angles in degrees or 4 quaternion components";
+                return Err(fields.error("rotation", message));
+            }
+        };
+    }
+    if let Some(scale) = fields.take_value("scale") {
+        transform.scale = match scale {
+            Value::Number(scale) => Vec3::broadcast(scale.into_f64() as f32),
+            scale => {
+                let [x, y, z] = fields.parse::<[f32; 3]>("scale", scale)?;
+                Vec3::new(x, y, z)
+            }
+        };
+    }
+    fields.finish()?;
+    Ok(transform)
+}
+
+fn parse_material(entity: String, value: Value) -> Result<SceneMaterial, SceneError> {
+    let mut fields = Fields::new(entity, Some("material"), value)?;
+    let mut material = SceneMaterial::default();
+
+    if let Some(color) = fields.take::<Vec<f32>>("color")? {
+        material.color = match color[..] {
+            [red, green, blue] => Srgba::new(red, green, blue, 1.0),
+            [red, green, blue, alpha] => Srgba::new(red, green, blue, alpha),
+            _ => return Err(fields.error("color", "expected 3 or 4 color components")),
+        };
+    }
+    material.texture = fields.take::<PathBuf>("texture")?;
+    if let Some(blend) = fields.take::<String>("blend")? {
+        material.blend = BlendMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == blend)
+            .ok_or_else(|| {
+                let message = format!(
+                    "unknown blend mode \"{}\", expected \"opaque\", \"alpha\" or \"additive\"",
+                    blend,
+                );
+                fields.error("blend", message)
+            })?;
+    }
+    fields.finish()?;
+    Ok(material)
+}
+
+/// Serializable view of the scene.
+pub(super) struct SceneRef<'a>(pub &'a [SceneEntity]);
+
+impl Serialize for SceneRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let entities: Vec<_> = self.0.iter().map(EntityRef).collect();
+        let mut scene = serializer.serialize_struct("Scene", 1)?;
+        scene.serialize_field("entities", &entities)?;
+        scene.end()
+    }
+}
+
+/// Serializable view of the entity which skips missing fields.
+struct EntityRef<'a>(&'a SceneEntity);
+
+impl Serialize for EntityRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let entity = self.0;
+        let mut result = serializer.serialize_struct("Entity", 6)?;
+        if let Some(name) = &entity.name {
+            result.serialize_field("name", name)?;
+        }
+        if let Some(transform) = &entity.transform {
+            result.serialize_field("transform", &TransformRef(transform))?;
+        }
+        if let Some(mesh) = &entity.mesh {
+            result.serialize_field("mesh", &mesh.to_string())?;
+        }
+        if let Some(material) = &entity.material {
+            result.serialize_field("material", &MaterialRef(material))?;
+        }
+        if let Some(parent) = &entity.parent {
+            result.serialize_field("parent", parent)?;
+        }
+        if !entity.components.is_empty() {
+            result.serialize_field("components", &ComponentsRef(&entity.components))?;
+        }
+        result.end()
+    }
+}
+
+/// Serializable view of components as a map from their names.
+struct ComponentsRef<'a>(&'a [(String, Value)]);
+
+impl Serialize for ComponentsRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0.iter().map(|(name, value)| (name, value)))
+    }
+}
+
+/// Serializable view of the transform with rotation as quaternion.
+struct TransformRef<'a>(&'a Transform);
+
+impl Serialize for TransformRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let Transform {
+            translation,
+            rotation,
+            scale,
+        } = self.0;
+        let mut result = serializer.serialize_struct("Transform", 3)?;
+        result.serialize_field("position", &[translation.x, translation.y, translation.z])?;
+        result.serialize_field("rotation", &rotation.into_quaternion_array())?;
+        result.serialize_field("scale", &[scale.x, scale.y, scale.z])?;
+        result.end()
+    }
+}
+
+/// Serializable view of the material which skips missing texture.
+struct MaterialRef<'a>(&'a SceneMaterial);
+
+impl Serialize for MaterialRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let SceneMaterial {
+            color,
+            texture,
+            blend,
+        } = self.0;
+        let mut result = serializer.serialize_struct("Material", 3)?;
+        let color = [color.red, color.green, color.blue, color.alpha];
+        result.serialize_field("color", &color)?;
+        if let Some(texture) = texture {
+            result.serialize_field("texture", texture)?;
+        }
+        result.serialize_field("blend", blend.name())?;
+        result.end()
+    }
+}
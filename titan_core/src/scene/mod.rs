@@ -0,0 +1,459 @@
+//! Declarative scenes which spawn entities into the world of ECS.
+//!
+//! Scene is stored in RON file with a list of entities, each with optional fields:
+//!
+//! ```ron
+//! (
+//!     entities: [
+//!         (
+//!             name: "tank",
+//!             transform: (position: (0, 0, 1), rotation: (0, 0, 90), scale: 2),
+//!             mesh: "models/tank.obj",
+//!             material: (color: (0.2, 0.6, 0.2), texture: "textures/camo.png"),
+//!         ),
+//!         (
+//!             name: "turret",
+//!             parent: "tank",
+//!             mesh: "cube",
+//!             material: (color: (1, 1, 1, 0.5), blend: "alpha"),
+//!             components: {"armor": (thickness: 40)},
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! Rotation is either `(roll, pitch, yaw)` in degrees
+//! (see [`Rotor3::from_euler_angles`](ultraviolet::Rotor3::from_euler_angles))
+//! or quaternion `(x, y, z, w)`. Scale is either one number or three numbers.
+//! Mesh is either name of [`Primitive`](crate::asset::Primitive) or path to OBJ file,
+//! blend mode is one of `"opaque"`, `"alpha"` and `"additive"`.
+//! Parent is referenced by its name. Components of the game are stored by names
+//! of their types in [`SceneRegistry`].
+//!
+//! Scene is loaded when the application is created if its file is set
+//! by [`Config::with_scene`](crate::config::Config::with_scene).
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use palette::Srgba;
+use ron::Value;
+use thiserror::Error;
+use titan_ecs::{Children, Entity, Parent, Transform, World};
+
+use crate::asset::{AssetServer, MeshSource};
+
+pub use component::{BlendMode, Material, MaterialTexture, Mesh, PrecisePick};
+pub use culling::{cull, Culling};
+pub use picking::{pick, pick_cursor, PickResult};
+pub use registry::SceneRegistry;
+pub use titan_ecs::Name;
+
+mod component;
+mod culling;
+mod format;
+mod picking;
+mod registry;
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("failed to read or write scene file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("scene format error: {0}")]
+    Format(#[from] ron::Error),
+
+    #[error("invalid scene: {0}")]
+    Invalid(String),
+
+    #[error("entity {entity}: invalid field `{field}`: {message}")]
+    Field {
+        entity: String,
+        field: String,
+        message: String,
+    },
+
+    #[error("entity {entity}: parent \"{parent}\" was not found")]
+    ParentNotFound { entity: String, parent: String },
+
+    #[error("entity {entity}: parent name \"{parent}\" is not unique")]
+    AmbiguousParent { entity: String, parent: String },
+
+    #[error("entity {entity}: parents form a cycle")]
+    Cycle { entity: String },
+}
+
+/// Label of the entity in errors: its name or its index in the scene.
+fn entity_label(name: Option<&str>, index: usize) -> String {
+    match name {
+        Some(name) => format!("\"{}\"", name),
+        None => format!("#{}", index),
+    }
+}
+
+/// Material of the entity as it is described in the scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneMaterial {
+    /// Color in sRGB space as it is written in the scene.
+    pub color: Srgba,
+    /// Path to the image file which is streamed by [`AssetServer`].
+    pub texture: Option<PathBuf>,
+    pub blend: BlendMode,
+}
+
+impl Default for SceneMaterial {
+    fn default() -> Self {
+        Self {
+            color: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            texture: None,
+            blend: BlendMode::default(),
+        }
+    }
+}
+
+/// Entity as it is described in the scene.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SceneEntity {
+    pub name: Option<String>,
+    pub transform: Option<Transform>,
+    pub mesh: Option<MeshSource>,
+    pub material: Option<SceneMaterial>,
+    /// Name of the parent entity.
+    pub parent: Option<String>,
+    /// Components of the game by names of their types in [`SceneRegistry`].
+    pub components: Vec<(String, Value)>,
+}
+
+/// Set of entities which could be spawned into the world.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Scene {
+    entities: Vec<SceneEntity>,
+    /// Index of the parent of each entity.
+    parents: Vec<Option<usize>>,
+}
+
+impl Scene {
+    /// Creates scene from given entities.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if parent of any entity could not be resolved
+    /// or parents form a cycle.
+    ///
+    pub fn new(entities: Vec<SceneEntity>) -> Result<Self, SceneError> {
+        let mut names = HashMap::new();
+        for (index, entity) in entities.iter().enumerate() {
+            if let Some(name) = &entity.name {
+                // Same name of several entities is an error only if it is referenced.
+                names
+                    .entry(name.as_str())
+                    .and_modify(|found: &mut Option<usize>| *found = None)
+                    .or_insert(Some(index));
+            }
+        }
+
+        let parents = entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| {
+                let parent = match &entity.parent {
+                    Some(parent) => parent,
+                    None => return Ok(None),
+                };
+                let label = || entity_label(entity.name.as_deref(), index);
+                match names.get(parent.as_str()) {
+                    Some(Some(parent)) => Ok(Some(*parent)),
+                    Some(None) => Err(SceneError::AmbiguousParent {
+                        entity: label(),
+                        parent: parent.clone(),
+                    }),
+                    None => Err(SceneError::ParentNotFound {
+                        entity: label(),
+                        parent: parent.clone(),
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (index, entity) in entities.iter().enumerate() {
+            let mut current = parents[index];
+            for _ in 0..entities.len() {
+                current = match current {
+                    Some(parent) if parent == index => {
+                        let entity = entity_label(entity.name.as_deref(), index);
+                        return Err(SceneError::Cycle { entity });
+                    }
+                    Some(parent) => parents[parent],
+                    None => break,
+                };
+            }
+        }
+        Ok(Self { entities, parents })
+    }
+
+    /// Loads scene from RON file.
+    ///
+    /// Relative paths of meshes and textures are resolved
+    /// against the directory of the scene file.
+    ///
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let mut entities = format::parse(&fs::read_to_string(path)?)?;
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = directory.join(&*path);
+            }
+        };
+        for entity in &mut entities {
+            if let Some(MeshSource::File(path)) = &mut entity.mesh {
+                resolve(path);
+            }
+            if let Some(path) = entity.material.as_mut().and_then(|m| m.texture.as_mut()) {
+                resolve(path);
+            }
+        }
+        Self::new(entities)
+    }
+
+    /// Serializes scene into RON string.
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        let config = ron::ser::PrettyConfig::new();
+        let string = ron::ser::to_string_pretty(&format::SceneRef(&self.entities), config)?;
+        Ok(string)
+    }
+
+    /// Saves scene into RON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Entities of the scene in order of their description.
+    pub fn entities(&self) -> &[SceneEntity] {
+        &self.entities
+    }
+
+    /// Count of entities in the scene.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if there are no entities in the scene.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Spawns entities of the scene into the world.
+    ///
+    /// Meshes and textures are resolved by the asset server,
+    /// so textures are streamed in after the entities were spawned.
+    /// Components of the game are skipped with a warning
+    /// (see [`Scene::instantiate_with`]).
+    ///
+    /// Returns spawned entities in order of the scene.
+    ///
+    pub fn instantiate(&self, world: &mut World, assets: &AssetServer) -> Vec<Entity> {
+        let skipped: usize = self.entities.iter().map(|e| e.components.len()).sum();
+        if skipped > 0 {
+            log::warn!("{} component(s) of the game were not spawned", skipped);
+        }
+        self.spawn(world, assets)
+    }
+
+    /// Spawns entities of the scene into the world
+    /// with components of the game which types are registered.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any component could not be deserialized
+    /// or its type was not registered. Nothing is spawned in this case.
+    ///
+    pub fn instantiate_with(
+        &self,
+        world: &mut World,
+        assets: &AssetServer,
+        registry: &SceneRegistry,
+    ) -> Result<Vec<Entity>, SceneError> {
+        let inserts = self
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| {
+                entity
+                    .components
+                    .iter()
+                    .map(|(name, value)| {
+                        let error = |message: String| SceneError::Field {
+                            entity: entity_label(entity.name.as_deref(), index),
+                            field: format!("components.{}", name),
+                            message,
+                        };
+                        match registry.load(name, value.clone()) {
+                            Some(Ok(insert)) => Ok(insert),
+                            Some(Err(ron_error)) => Err(error(ron_error.code.to_string())),
+                            None => Err(error("component type is not registered".to_string())),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let spawned = self.spawn(world, assets);
+        for (&entity, inserts) in spawned.iter().zip(inserts) {
+            for insert in inserts {
+                insert(world, entity);
+            }
+        }
+        Ok(spawned)
+    }
+
+    /// Spawns entities of the scene with built-in components only.
+    fn spawn(&self, world: &mut World, assets: &AssetServer) -> Vec<Entity> {
+        let spawned: Vec<_> = self
+            .entities
+            .iter()
+            .map(|scene_entity| {
+                let entity = world.spawn();
+                if let Some(name) = &scene_entity.name {
+                    world.insert(entity, Name(name.clone()));
+                }
+                if let Some(transform) = scene_entity.transform {
+                    world.insert(entity, transform);
+                }
+                if let Some(source) = &scene_entity.mesh {
+                    let handle = assets.load_mesh(source);
+                    let source = source.clone();
+                    world.insert(entity, Mesh { source, handle });
+                }
+                if let Some(material) = &scene_entity.material {
+                    let texture = material.texture.as_ref().map(|path| MaterialTexture {
+                        path: path.clone(),
+                        id: assets.load_texture(path.clone()),
+                    });
+                    let material = Material {
+                        color: material.color.into(),
+                        texture,
+                        blend: material.blend,
+                    };
+                    world.insert(entity, material);
+                }
+                entity
+            })
+            .collect();
+
+        for (&entity, &parent) in spawned.iter().zip(&self.parents) {
+            if let Some(parent) = parent {
+                world
+                    .set_parent(entity, spawned[parent])
+                    .expect("hierarchy of the scene was validated");
+            }
+        }
+        spawned
+    }
+
+    /// Creates scene from entities of the world which have
+    /// [`Name`], [`Transform`], [`Mesh`], [`Material`] or belong to the hierarchy.
+    ///
+    /// Parents which have no name or share it with other entities
+    /// are given unique names to be referenced by their children.
+    ///
+    pub fn from_world(world: &World) -> Self {
+        Self::from_world_with(world, &SceneRegistry::new())
+    }
+
+    /// Creates scene from entities of the world like [`Scene::from_world`],
+    /// also saving components of the game which types are registered.
+    pub fn from_world_with(world: &World, registry: &SceneRegistry) -> Self {
+        let entities: Vec<_> = world
+            .entities()
+            .filter(|&entity| {
+                world.attached::<Name>(entity)
+                    || world.attached::<Transform>(entity)
+                    || world.attached::<Mesh>(entity)
+                    || world.attached::<Material>(entity)
+                    || world.attached::<Parent>(entity)
+                    || world.attached::<Children>(entity)
+                    || registry.attached_any(world, entity)
+            })
+            .collect();
+        let indices: HashMap<_, _> = entities
+            .iter()
+            .enumerate()
+            .map(|(index, &entity)| (entity, index))
+            .collect();
+        let parents: Vec<_> = entities
+            .iter()
+            .map(|&entity| world.parent(entity).and_then(|p| indices.get(&p).copied()))
+            .collect();
+
+        let mut names: Vec<_> = entities
+            .iter()
+            .map(|&entity| world.get::<Name>(entity).map(|name| name.0.clone()))
+            .collect();
+        let mut counts = HashMap::new();
+        for name in names.iter().flatten() {
+            *counts.entry(name.clone()).or_insert(0) += 1;
+        }
+        let mut taken: HashSet<_> = counts.keys().cloned().collect();
+        let referenced: HashSet<_> = parents.iter().flatten().copied().collect();
+        let mut referenced: Vec<_> = referenced.into_iter().collect();
+        referenced.sort_unstable();
+        for index in referenced {
+            let base = match &names[index] {
+                Some(name) if counts[name] == 1 => continue,
+                Some(name) => name.clone(),
+                None => "entity".to_string(),
+            };
+            let unique = (1..)
+                .map(|suffix| format!("{} #{}", base, suffix))
+                .find(|name| !taken.contains(name))
+                .expect("there are infinitely many suffixes");
+            taken.insert(unique.clone());
+            names[index] = Some(unique);
+        }
+
+        let mut scene_entities: Vec<_> = entities
+            .iter()
+            .zip(names)
+            .map(|(&entity, name)| SceneEntity {
+                name,
+                transform: world.get::<Transform>(entity).copied(),
+                mesh: world.get::<Mesh>(entity).map(|mesh| mesh.source.clone()),
+                material: world.get::<Material>(entity).map(|material| SceneMaterial {
+                    color: material.color.into(),
+                    texture: material.texture.as_ref().map(|t| t.path.clone()),
+                    blend: material.blend,
+                }),
+                parent: None,
+                components: registry.save(world, entity),
+            })
+            .collect();
+        for (index, parent) in parents.iter().enumerate() {
+            if let Some(parent) = *parent {
+                scene_entities[index].parent = scene_entities[parent].name.clone();
+            }
+        }
+        Self {
+            entities: scene_entities,
+            parents,
+        }
+    }
+}
+
+impl FromStr for Scene {
+    type Err = SceneError;
+
+    /// Parses scene from RON string.
+    ///
+    /// Relative paths of the scene are left unchanged.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(format::parse(s)?)
+    }
+}
@@ -0,0 +1,114 @@
+//! Picking of entities which are drawn with meshes by rays (for example, from the cursor).
+
+use titan_ecs::{Entity, GlobalTransform, World};
+use ultraviolet::{Vec2, Vec3};
+
+use crate::asset::AssetServer;
+use crate::camera::Camera;
+use crate::math::Ray;
+use crate::window::ScreenSpace;
+
+use super::{culling, Mesh, PrecisePick};
+
+/// Nearest entity which was hit by the ray.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PickResult {
+    pub entity: Entity,
+    /// Point of the hit in world space.
+    pub position: Vec3,
+    /// Distance from the origin of the ray to the hit.
+    pub distance: f32,
+}
+
+/// Finds the nearest entity with [`Mesh`] which is hit by the ray.
+///
+/// Entities are tested against their bounds in world space (the same as in [`cull`](super::cull)),
+/// and entities with [`PrecisePick`] are also tested against triangles of their mesh.
+/// Bounds are tested first and sorted by distance, so triangles of entities
+/// which are farther than the nearest hit are not tested at all.
+/// Entities with unknown bounds are never picked.
+///
+/// If the ray starts inside of bounds of the entity, the entity is hit at the origin.
+///
+pub fn pick(world: &World, assets: &AssetServer, ray: &Ray) -> Option<PickResult> {
+    let mut candidates: Vec<_> = world
+        .query::<Mesh>()
+        .filter_map(|(entity, mesh)| {
+            let bounds = culling::world_bounds(world, assets, entity, mesh)?;
+            let distance = ray.intersect_aabb(&bounds)?;
+            Some((distance, entity, mesh))
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut nearest: Option<(f32, Entity)> = None;
+    for (enter, entity, mesh) in candidates {
+        if matches!(nearest, Some((distance, _)) if distance <= enter) {
+            break;
+        }
+        let distance = match self::precise_distance(world, assets, entity, mesh, ray) {
+            Some(Some(distance)) => distance,
+            // Ray goes through bounds, but misses all triangles.
+            Some(None) => continue,
+            None => enter,
+        };
+        if !matches!(nearest, Some((nearest, _)) if nearest <= distance) {
+            nearest = Some((distance, entity));
+        }
+    }
+    nearest.map(|(distance, entity)| PickResult {
+        entity,
+        position: ray.at(distance),
+        distance,
+    })
+}
+
+/// Finds the nearest entity under the cursor at given physical position
+/// which is seen by the camera (see [`pick`]).
+///
+/// Cursor is converted with content rectangle of the screen space,
+/// so letterboxing and scale factor of the window are taken into account.
+/// Cursor outside of the content rectangle picks nothing.
+///
+pub fn pick_cursor(
+    world: &World,
+    assets: &AssetServer,
+    camera: &Camera,
+    screen_space: &ScreenSpace,
+    cursor: Vec2,
+) -> Option<PickResult> {
+    let ndc = screen_space.physical_to_ndc(cursor);
+    if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 {
+        return None;
+    }
+    let (origin, direction) = screen_space.cursor_ray(camera, cursor);
+    self::pick(world, assets, &Ray::new(origin, direction))
+}
+
+/// Distance to the nearest triangle of the mesh which is hit by the ray.
+///
+/// Returns `None` if the entity is picked by its bounds
+/// (it has no [`PrecisePick`] or triangles of its mesh are unknown).
+///
+fn precise_distance(
+    world: &World,
+    assets: &AssetServer,
+    entity: Entity,
+    mesh: &Mesh,
+    ray: &Ray,
+) -> Option<Option<f32>> {
+    if !world.attached::<PrecisePick>(entity) {
+        return None;
+    }
+    let triangles = assets.mesh_triangles(mesh.handle)?;
+    // Ray is moved into the local space of the mesh, which keeps distances along the ray.
+    let ray = match world.get::<GlobalTransform>(entity) {
+        Some(transform) => ray.transformed(transform.matrix().inversed()),
+        None => *ray,
+    };
+    let nearest = triangles
+        .iter()
+        .filter_map(|&triangle| ray.intersect_triangle(triangle))
+        .min_by(f32::total_cmp);
+    Some(nearest)
+}
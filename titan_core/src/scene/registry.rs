@@ -0,0 +1,144 @@
+//! Component types of the game which are stored in scenes besides built-in components.
+
+use std::any::TypeId;
+
+use ron::Value;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use titan_ecs::{Component, Entity, World};
+
+/// Inserts deserialized component into the entity.
+pub(super) type InsertFn = Box<dyn FnOnce(&mut World, Entity)>;
+
+type AttachedFn = fn(&World, Entity) -> bool;
+type SaveFn = fn(&World, Entity) -> Option<Result<Value, ron::Error>>;
+type LoadFn = fn(Value) -> Result<InsertFn, ron::Error>;
+
+struct Registration {
+    name: &'static str,
+    typeid: TypeId,
+    attached: AttachedFn,
+    save: SaveFn,
+    load: LoadFn,
+}
+
+fn attached_component<T>(world: &World, entity: Entity) -> bool
+where
+    T: Component,
+{
+    world.attached::<T>(entity)
+}
+
+fn save_component<T>(world: &World, entity: Entity) -> Option<Result<Value, ron::Error>>
+where
+    T: Component + Serialize,
+{
+    let component = world.get::<T>(entity)?;
+    // Component is converted into the value through its RON representation.
+    Some(ron::to_string(component).and_then(|string| ron::from_str(&string)))
+}
+
+fn load_component<T>(value: Value) -> Result<InsertFn, ron::Error>
+where
+    T: Component + DeserializeOwned,
+{
+    let component: T = value.into_rust()?;
+    Ok(Box::new(move |world: &mut World, entity| {
+        world.insert(entity, component);
+    }))
+}
+
+/// Component types of the game which are saved into scenes and loaded from them
+/// by their stable names, in addition to built-in components.
+///
+/// Components are stored in the `components` map of the entity:
+///
+/// ```ron
+/// (name: "player", components: {"health": (current: 10, max: 10)})
+/// ```
+///
+/// Values are stored without names of structs,
+/// so enums with data could not be components of scenes.
+///
+/// See [`Scene::from_world_with`](super::Scene::from_world_with)
+/// and [`Scene::instantiate_with`](super::Scene::instantiate_with).
+///
+#[derive(Default)]
+pub struct SceneRegistry {
+    registrations: Vec<Registration>,
+}
+
+impl SceneRegistry {
+    /// Creates registry without component types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers component type `T` with given stable name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if type or name was already registered.
+    ///
+    pub fn register<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        let typeid = TypeId::of::<T>();
+        assert!(
+            self.registrations.iter().all(|r| r.typeid != typeid),
+            "component type {} is already registered",
+            std::any::type_name::<T>(),
+        );
+        assert!(
+            self.registrations.iter().all(|r| r.name != name),
+            "component name \"{}\" is already registered",
+            name,
+        );
+        self.registrations.push(Registration {
+            name,
+            typeid,
+            attached: self::attached_component::<T>,
+            save: self::save_component::<T>,
+            load: self::load_component::<T>,
+        });
+        self
+    }
+
+    /// Names of registered component types in order of their registration.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.registrations.iter().map(|r| r.name)
+    }
+
+    /// Returns `true` if the entity has a component of any registered type.
+    pub(super) fn attached_any(&self, world: &World, entity: Entity) -> bool {
+        self.registrations
+            .iter()
+            .any(|registration| (registration.attached)(world, entity))
+    }
+
+    /// Values of components of registered types of the entity.
+    ///
+    /// Components which could not be represented in RON are skipped with a warning.
+    ///
+    pub(super) fn save(&self, world: &World, entity: Entity) -> Vec<(String, Value)> {
+        self.registrations
+            .iter()
+            .filter_map(|registration| match (registration.save)(world, entity)? {
+                Ok(value) => Some((registration.name.to_string(), value)),
+                Err(error) => {
+                    let name = registration.name;
+                    log::warn!("failed to save component \"{}\": {}", name, error);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Deserializes component of the type with given name,
+    /// or returns `None` if the type was not registered.
+    pub(super) fn load(&self, name: &str, value: Value) -> Option<Result<InsertFn, ron::Error>> {
+        let registration = self.registrations.iter().find(|r| r.name == name)?;
+        Some((registration.load)(value))
+    }
+}
@@ -0,0 +1,535 @@
+#![cfg(test)]
+
+use serde::{Deserialize, Serialize};
+use titan_ecs::{propagate_transforms, GlobalTransform};
+use ultraviolet::{Rotor3, Vec3};
+
+use crate::asset::Primitive;
+use crate::camera::Camera;
+use crate::graphics::{RenderLayers, StreamingConfig, StreamingManager, Visibility};
+use crate::math::{Aabb, Color, Ray};
+use crate::task::TaskPool;
+
+use super::*;
+
+const TANK: &str = r#"
+Scene(
+    entities: [
+        (
+            name: "turret",
+            parent: "tank",
+            transform: (position: (0, 0, 1)),
+            mesh: "cube",
+            material: (color: (1, 1, 1, 0.5), blend: "alpha", texture: "camo.png"),
+        ),
+        (
+            name: "tank",
+            transform: (position: (1, 2, 3), rotation: (90, 0, 0), scale: 2),
+            mesh: "models/tank.obj",
+            material: (color: (0.2, 0.6, 0.2), texture: "camo.png"),
+        ),
+        (mesh: "cube", parent: "turret"),
+        (),
+    ],
+)
+"#;
+
+fn server() -> AssetServer {
+    let streaming = StreamingManager::new(StreamingConfig::default(), TaskPool::new(1, 1));
+    AssetServer::new(streaming)
+}
+
+fn assert_close(actual: Vec3, expected: Vec3) {
+    assert!(
+        (actual - expected).mag() < 1e-5,
+        "{:?} != {:?}",
+        actual,
+        expected,
+    );
+}
+
+#[test]
+fn test_instantiate() {
+    let scene: Scene = TANK.parse().unwrap();
+    assert_eq!(scene.len(), 4);
+
+    let assets = server();
+    let mut world = World::new();
+    let entities = scene.instantiate(&mut world, &assets);
+    let (turret, tank, antenna, empty) = (entities[0], entities[1], entities[2], entities[3]);
+
+    assert_eq!(world.get::<Name>(tank).map(Name::as_str), Some("tank"));
+    assert_eq!(world.parent(turret), Some(tank));
+    assert_eq!(world.parent(antenna), Some(turret));
+    assert_eq!(world.parent(tank), None);
+    assert!(!world.attached::<Name>(empty) && !world.attached::<Transform>(empty));
+
+    let tank_mesh = world.get::<Mesh>(tank).unwrap();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    assert_eq!(
+        tank_mesh.source,
+        MeshSource::File(PathBuf::from("models/tank.obj"))
+    );
+    assert_eq!(world.get::<Mesh>(turret).unwrap().source, cube);
+    assert_eq!(
+        world.get::<Mesh>(turret).unwrap().handle,
+        world.get::<Mesh>(antenna).unwrap().handle,
+    );
+
+    let turret_material = world.get::<Material>(turret).unwrap();
+    let tank_material = world.get::<Material>(tank).unwrap();
+    assert_eq!(turret_material.blend, BlendMode::Alpha);
+    assert_eq!(turret_material.color, Color::rgba(1.0, 1.0, 1.0, 0.5));
+    assert_eq!(tank_material.blend, BlendMode::Opaque);
+    assert_eq!(tank_material.color.alpha, 1.0);
+    assert_eq!(
+        turret_material.texture.as_ref().map(|texture| texture.id),
+        tank_material.texture.as_ref().map(|texture| texture.id),
+    );
+
+    propagate_transforms(&mut world);
+    let translation = |entity| world.get::<GlobalTransform>(entity).unwrap().translation();
+    assert_close(translation(tank), Vec3::new(1.0, 2.0, 3.0));
+    assert_close(translation(turret), Vec3::new(1.0, 2.0, 5.0));
+
+    let rotation = world.get::<Transform>(tank).unwrap().rotation;
+    let expected = Rotor3::from_rotation_xy(90f32.to_radians());
+    assert_close(rotation * Vec3::unit_x(), expected * Vec3::unit_x());
+}
+
+#[test]
+fn test_field_errors() {
+    let cases = [
+        (r#"(name: "a", mesh: "sphere")"#, "\"a\"", "mesh"),
+        (
+            r#"(name: "a", transform: (rotation: (1, 2)))"#,
+            "\"a\"",
+            "transform.rotation",
+        ),
+        (
+            r#"(name: "a", transform: (rotation: (0, 0, 0, 0)))"#,
+            "\"a\"",
+            "transform.rotation",
+        ),
+        (
+            r#"(name: "a", transform: (scale: "big"))"#,
+            "\"a\"",
+            "transform.scale",
+        ),
+        (r#"(name: "a", transform: 1)"#, "\"a\"", "transform"),
+        (
+            r#"(name: "a", material: (color: (1, 1)))"#,
+            "\"a\"",
+            "material.color",
+        ),
+        (
+            r#"(name: "a", material: (blend: "multiply"))"#,
+            "\"a\"",
+            "material.blend",
+        ),
+        (
+            r#"(name: "a", material: (shininess: 1))"#,
+            "\"a\"",
+            "material.shininess",
+        ),
+        (r#"(name: "a", colour: (1, 1, 1))"#, "\"a\"", "colour"),
+        (r#"(name: 42)"#, "#1", "name"),
+        (r#"(parent: 42)"#, "#1", "parent"),
+        (r#"(name: "a", components: [1])"#, "\"a\"", "components"),
+        (r#"(name: "a", components: {1: 2})"#, "\"a\"", "components"),
+    ];
+    for (entity, expected_entity, expected_field) in cases {
+        let source = format!("(entities: [(), {}])", entity);
+        match source.parse::<Scene>() {
+            Err(SceneError::Field { entity, field, .. }) => {
+                assert_eq!(
+                    (entity.as_str(), field.as_str()),
+                    (expected_entity, expected_field),
+                );
+            }
+            result => panic!("unexpected result for {}: {:?}", source, result),
+        }
+    }
+
+    let result = "(entities: [(name: \"a\",,)])".parse::<Scene>();
+    assert!(matches!(result, Err(SceneError::Format(_))));
+    let result = "(objects: [])".parse::<Scene>();
+    assert!(matches!(result, Err(SceneError::Invalid(_))));
+}
+
+#[test]
+fn test_hierarchy_errors() {
+    let result = r#"(entities: [(name: "a", parent: "b")])"#.parse::<Scene>();
+    assert!(matches!(
+        result,
+        Err(SceneError::ParentNotFound { entity, parent }) if entity == "\"a\"" && parent == "b"
+    ));
+
+    let result = r#"(entities: [(name: "b"), (name: "b"), (parent: "b")])"#.parse::<Scene>();
+    assert!(matches!(
+        result,
+        Err(SceneError::AmbiguousParent { entity, .. }) if entity == "#2"
+    ));
+
+    let source = r#"(entities: [(name: "a", parent: "c"), (name: "b", parent: "a"), (name: "c", parent: "b")])"#;
+    let result = source.parse::<Scene>();
+    assert!(matches!(
+        result,
+        Err(SceneError::Cycle { entity }) if entity == "\"a\""
+    ));
+
+    // Duplicate names are allowed while they are not referenced.
+    let result = r#"(entities: [(name: "b"), (name: "b")])"#.parse::<Scene>();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_round_trip() {
+    let assets = server();
+    let mut world = World::new();
+    let scene: Scene = TANK.parse().unwrap();
+    scene.instantiate(&mut world, &assets);
+
+    // Unnamed parent must be given a name to be referenced.
+    let wheel = world.spawn();
+    world.insert(wheel, Transform::from_translation(Vec3::unit_y()));
+    let bolt = world.spawn();
+    world.set_parent(bolt, wheel).unwrap();
+
+    let exported = Scene::from_world(&world);
+    let loaded: Scene = exported.to_ron().unwrap().parse().unwrap();
+    // Entity without components is not exported.
+    assert_eq!(loaded.len(), 5);
+
+    for (actual, expected) in loaded.entities().iter().zip(exported.entities()) {
+        assert_eq!(actual.name, expected.name);
+        assert_eq!(actual.mesh, expected.mesh);
+        assert_eq!(actual.material, expected.material);
+        assert_eq!(actual.parent, expected.parent);
+        match (actual.transform, expected.transform) {
+            (Some(actual), Some(expected)) => {
+                assert_close(actual.translation, expected.translation);
+                assert_close(actual.scale, expected.scale);
+                let (actual, expected) = (actual.rotation, expected.rotation);
+                assert_close(actual * Vec3::unit_x(), expected * Vec3::unit_x());
+                assert_close(actual * Vec3::unit_y(), expected * Vec3::unit_y());
+            }
+            (actual, expected) => assert_eq!(actual, expected),
+        }
+    }
+
+    // Unnamed wheel is named to be referenced by the bolt.
+    let (wheel, bolt) = (&loaded.entities()[3], &loaded.entities()[4]);
+    assert_eq!(wheel.name.as_deref(), Some("entity #1"));
+    assert_eq!(bolt.parent, wheel.name);
+
+    let mut copy = World::new();
+    let entities = loaded.instantiate(&mut copy, &assets);
+    assert_eq!(copy.len(), world.len() - 1);
+    assert_eq!(
+        entities
+            .iter()
+            .filter(|&&e| copy.parent(e).is_some())
+            .count(),
+        3,
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Health {
+    current: u32,
+    max: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Velocity(f32, f32, f32);
+
+#[test]
+fn test_components_round_trip() {
+    let mut registry = SceneRegistry::new();
+    registry
+        .register::<Health>("health")
+        .register::<Velocity>("velocity");
+    assert_eq!(registry.names().collect::<Vec<_>>(), ["health", "velocity"]);
+
+    let scene: Scene = r#"(entities: [
+        (name: "player", components: {"health": (current: 7, max: 10)}),
+        (components: {"velocity": (1, 0, -1)}),
+    ])"#
+    .parse()
+    .unwrap();
+    let assets = server();
+    let mut world = World::new();
+    let entities = scene
+        .instantiate_with(&mut world, &assets, &registry)
+        .unwrap();
+    let health = Health {
+        current: 7,
+        max: 10,
+    };
+    assert_eq!(world.get::<Health>(entities[0]), Some(&health));
+    let velocity = Velocity(1.0, 0.0, -1.0);
+    assert_eq!(world.get::<Velocity>(entities[1]), Some(&velocity));
+
+    // Unnamed entity is exported because it has a component of the game.
+    let exported = Scene::from_world_with(&world, &registry);
+    assert_eq!(Scene::from_world(&world).len(), 1);
+    let loaded: Scene = exported.to_ron().unwrap().parse().unwrap();
+    assert_eq!(loaded.len(), 2);
+    let mut copy = World::new();
+    let copied = loaded
+        .instantiate_with(&mut copy, &assets, &registry)
+        .unwrap();
+    assert_eq!(copy.get::<Health>(copied[0]), Some(&health));
+    assert_eq!(copy.get::<Velocity>(copied[1]), Some(&velocity));
+
+    // Components of the game are skipped without the registry.
+    let mut copy = World::new();
+    let copied = loaded.instantiate(&mut copy, &assets);
+    assert!(!copy.attached::<Health>(copied[0]));
+
+    // Nothing is spawned if any component could not be loaded.
+    let cases = [
+        (r#"{"armor": 40}"#, "components.armor"),
+        (r#"{"health": (current: 1)}"#, "components.health"),
+    ];
+    for (components, expected_field) in cases {
+        let source = format!(
+            r#"(entities: [(), (name: "a", components: {})])"#,
+            components
+        );
+        let scene: Scene = source.parse().unwrap();
+        let mut world = World::new();
+        match scene.instantiate_with(&mut world, &assets, &registry) {
+            Err(SceneError::Field { entity, field, .. }) => {
+                assert_eq!((entity.as_str(), field.as_str()), ("\"a\"", expected_field));
+            }
+            result => panic!("unexpected result for {}: {:?}", source, result),
+        }
+        assert_eq!(world.len(), 0);
+    }
+}
+
+#[test]
+fn test_cull() {
+    let assets = server();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    let handle = assets.load_mesh(&cube);
+    let bounds = Aabb::new(Vec3::broadcast(-0.5), Vec3::broadcast(0.5));
+    assert!(assets.set_mesh_bounds(handle, bounds));
+    assert_eq!(assets.mesh_bounds(handle), Some(bounds));
+
+    let mut world = World::new();
+    let mut spawn = |position: Vec3| {
+        let entity = world.spawn();
+        let mesh = Mesh {
+            source: cube.clone(),
+            handle,
+        };
+        world.insert(entity, mesh);
+        world.insert(entity, Transform::from_translation(position));
+        entity
+    };
+    let front = spawn(Vec3::new(5.0, 0.0, 0.0));
+    let behind = spawn(Vec3::new(-5.0, 0.0, 0.0));
+    // Bounds of the component are used instead of bounds of the mesh.
+    let large = spawn(Vec3::new(-5.0, 0.0, 0.0));
+    world.insert(
+        large,
+        Aabb::new(Vec3::broadcast(-10.0), Vec3::broadcast(10.0)),
+    );
+    // Entities with unknown bounds are never culled.
+    let unknown = world.spawn();
+    let tank = MeshSource::File("tank.obj".into());
+    let mesh = Mesh {
+        handle: assets.load_mesh(&tank),
+        source: tank,
+    };
+    world.insert(unknown, mesh);
+    propagate_transforms(&mut world);
+
+    let camera = Camera {
+        near: 1.0,
+        far: 10.0,
+        ..Camera::default()
+    };
+    let mut culling = cull(&world, &assets, &camera.frustum(1.0), camera.layers);
+    culling.visible.sort_unstable();
+    let mut visible = vec![front, large, unknown];
+    visible.sort_unstable();
+    assert_eq!(culling.visible, visible);
+    let culled_bounds = Aabb::new(Vec3::new(-5.5, -0.5, -0.5), Vec3::new(-4.5, 0.5, 0.5));
+    assert_eq!(culling.culled, vec![(behind, culled_bounds)]);
+    let stats = culling.stats();
+    assert_eq!((stats.submitted, stats.culled, stats.hidden), (3, 1, 0));
+}
+
+#[test]
+fn test_cull_visibility_and_layers() {
+    let assets = server();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    let handle = assets.load_mesh(&cube);
+
+    let mut world = World::new();
+    let mut spawn = |layers: Option<RenderLayers>| {
+        let entity = world.spawn();
+        let mesh = Mesh {
+            source: cube.clone(),
+            handle,
+        };
+        world.insert(entity, mesh);
+        if let Some(layers) = layers {
+            world.insert(entity, layers);
+        }
+        entity
+    };
+    let default = spawn(None);
+    let never = spawn(Some(RenderLayers::NONE));
+    let ui = spawn(Some(RenderLayers::layer(1)));
+    let both = spawn(Some(RenderLayers::DEFAULT.with(1)));
+
+    let frustum = Camera::default().frustum(1.0);
+    let visible = |world: &World, layers| {
+        let mut culling = cull(world, &assets, &frustum, layers);
+        culling.visible.sort_unstable();
+        culling
+    };
+    let sorted = |mut entities: Vec<Entity>| {
+        entities.sort_unstable();
+        entities
+    };
+
+    // Cameras with disjoint layers see only their own entities.
+    let scene = visible(&world, RenderLayers::DEFAULT.0);
+    assert_eq!(scene.visible, sorted(vec![default, both]));
+    assert_eq!(scene.hidden, sorted(vec![never, ui]));
+    let overlay = visible(&world, RenderLayers::layer(1).0);
+    assert_eq!(overlay.visible, sorted(vec![ui, both]));
+    // Entity with empty mask is never drawn.
+    let all = visible(&world, RenderLayers::ALL.0);
+    assert_eq!(all.hidden, vec![never]);
+
+    // Hidden entity is skipped in the same frame and is counted apart from culled ones.
+    world.insert(both, Visibility::HIDDEN);
+    let overlay = visible(&world, RenderLayers::layer(1).0);
+    assert_eq!(overlay.visible, vec![ui]);
+    let stats = overlay.stats();
+    assert_eq!((stats.submitted, stats.culled, stats.hidden), (1, 0, 3));
+    world.insert(both, Visibility::VISIBLE);
+    assert_eq!(visible(&world, RenderLayers::ALL.0).visible.len(), 3);
+}
+
+#[test]
+fn test_pick() {
+    let assets = server();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    let handle = assets.load_mesh(&cube);
+    assets.set_mesh_bounds(
+        handle,
+        Aabb::new(Vec3::broadcast(-0.5), Vec3::broadcast(0.5)),
+    );
+    // Triangle which covers only the lower half of the cube face looking at the ray.
+    let triangles = vec![[
+        Vec3::new(-0.5, -0.5, -0.5),
+        Vec3::new(-0.5, 0.0, -0.5),
+        Vec3::new(-0.5, -0.5, 0.5),
+    ]];
+    assert!(assets.set_mesh_triangles(handle, triangles));
+
+    let mut world = World::new();
+    let mut spawn = |position: Vec3| {
+        let entity = world.spawn();
+        let mesh = Mesh {
+            source: cube.clone(),
+            handle,
+        };
+        world.insert(entity, mesh);
+        world.insert(entity, Transform::from_translation(position));
+        entity
+    };
+    let near = spawn(Vec3::new(3.0, 0.0, 0.0));
+    let far = spawn(Vec3::new(6.0, 0.0, 0.0));
+    let side = spawn(Vec3::new(3.0, 3.0, 0.0));
+    world.insert(side, PrecisePick);
+    propagate_transforms(&mut world);
+
+    // The nearest of boxes along the ray is picked.
+    let ray = Ray::new(Vec3::zero(), Vec3::unit_x());
+    let result = pick(&world, &assets, &ray).unwrap();
+    assert_eq!(result.entity, near);
+    assert!((result.distance - 2.5).abs() < 1e-5);
+    assert!((result.position - Vec3::new(2.5, 0.0, 0.0)).mag() < 1e-5);
+
+    // Ray which starts inside of the box hits it at the origin.
+    let inside = Ray::new(Vec3::new(6.2, 0.0, 0.0), Vec3::unit_x());
+    let result = pick(&world, &assets, &inside).unwrap();
+    assert_eq!((result.entity, result.distance), (far, 0.0));
+
+    // Precisely picked entity is hit by its triangles only.
+    let lower = Ray::new(Vec3::new(0.0, 2.75, 0.0), Vec3::unit_x());
+    let result = pick(&world, &assets, &lower).unwrap();
+    assert_eq!(result.entity, side);
+    assert!((result.distance - 2.5).abs() < 1e-5);
+    let upper = Ray::new(Vec3::new(0.0, 3.25, 0.0), Vec3::unit_x());
+    assert_eq!(pick(&world, &assets, &upper), None);
+
+    // Rays which miss all of the boxes.
+    assert_eq!(
+        pick(&world, &assets, &Ray::new(Vec3::zero(), -Vec3::unit_x())),
+        None
+    );
+    assert_eq!(
+        pick(&world, &assets, &Ray::new(Vec3::zero(), Vec3::unit_z())),
+        None
+    );
+}
+
+#[test]
+fn test_pick_cursor() {
+    use crate::window::{ContentRect, ScreenSpace, Size};
+    use ultraviolet::Vec2;
+
+    let assets = server();
+    let cube = MeshSource::Primitive(Primitive::Cube);
+    let handle = assets.load_mesh(&cube);
+    assets.set_mesh_bounds(
+        handle,
+        Aabb::new(Vec3::broadcast(-0.5), Vec3::broadcast(0.5)),
+    );
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(
+        entity,
+        Mesh {
+            source: cube,
+            handle,
+        },
+    );
+    world.insert(
+        entity,
+        Transform::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+    );
+    propagate_transforms(&mut world);
+
+    // Content is letterboxed into the middle of the window.
+    let content_rect = ContentRect {
+        offset: Vec2::new(100.0, 0.0),
+        size: Vec2::new(600.0, 600.0),
+    };
+    let screen_space = ScreenSpace::with_content_rect(Size::new(800, 600), 2.0, content_rect);
+    let camera = Camera::default();
+    let center = Vec2::new(400.0, 300.0);
+    let result = pick_cursor(&world, &assets, &camera, &screen_space, center).unwrap();
+    assert_eq!(result.entity, entity);
+    // Cursor on the border of the letterbox picks nothing.
+    let border = Vec2::new(50.0, 300.0);
+    assert_eq!(
+        pick_cursor(&world, &assets, &camera, &screen_space, border),
+        None
+    );
+    let corner = Vec2::new(110.0, 10.0);
+    assert_eq!(
+        pick_cursor(&world, &assets, &camera, &screen_space, corner),
+        None
+    );
+}
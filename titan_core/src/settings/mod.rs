@@ -0,0 +1,421 @@
+//! Engine settings which are persisted across runs of the game.
+//!
+//! Settings are stored as `key = value` lines in the file set by
+//! [`Config::with_settings_path`](crate::config::Config::with_settings_path)
+//! or in the configuration directory of the platform ([`default_path`]).
+//! They are loaded before the window is created, so the window reopens where it was left,
+//! and saved when they are changed (no more often than [`SAVE_DELAY`]) and on exit.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::{
+    app::OverlayLevel,
+    config::ENGINE_NAME,
+    window::{MonitorInfo, Size},
+};
+
+mod tests;
+
+/// Version of the settings file, files of other versions are discarded.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Name of the settings file inside of the directory of the game.
+pub const SETTINGS_FILE_NAME: &str = "settings.ini";
+
+/// Min delay between a change of settings and their saving.
+pub const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Min size of the part of the window (in physical pixels along each axis)
+/// which must stay on some monitor when the window is restored.
+pub const MIN_VISIBLE_SIZE: u32 = 64;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SettingsError {
+    #[error("line {0} is not a key-value pair")]
+    Syntax(usize),
+
+    #[error("unknown setting {0:?}")]
+    Unknown(String),
+
+    #[error("invalid value {value:?} of setting {key:?}")]
+    InvalidValue { key: String, value: String },
+
+    #[error(
+        "settings version {0:?} is not supported (expected {})",
+        SETTINGS_VERSION
+    )]
+    Version(Option<u32>),
+}
+
+/// Settings of the engine which are restored on startup.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EngineSettings {
+    /// Position of top left corner of the window on the desktop (in physical pixels).
+    pub window_position: Option<[i32; 2]>,
+    /// Size of the window (in physical pixels) while it is neither maximized nor fullscreen.
+    pub window_size: Option<Size>,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub render_scale: f32,
+    pub overlay_level: OverlayLevel,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            window_position: None,
+            window_size: None,
+            maximized: false,
+            fullscreen: false,
+            vsync: true,
+            render_scale: 1.0,
+            overlay_level: OverlayLevel::default(),
+        }
+    }
+}
+
+impl EngineSettings {
+    /// Reads settings from the file.
+    ///
+    /// Returns `None` if the file does not exist. Files which could not be read,
+    /// are corrupt or have another version are discarded with a warning.
+    ///
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return None,
+            Err(error) => {
+                log::warn!("failed to read settings {}: {}", path.display(), error);
+                return None;
+            }
+        };
+        match contents.parse() {
+            Ok(settings) => Some(settings),
+            Err(error) => {
+                log::warn!("discarding settings {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    /// Writes settings into the file, creating its directory if needed.
+    ///
+    /// Settings are written into the temporary file which then replaces the old one,
+    /// so the file is never left half-written.
+    ///
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.to_string())?;
+        fs::rename(&temporary, path)
+    }
+}
+
+impl EngineSettings {
+    /// Same settings with the window moved onto given monitors (see [`clamp_to_monitors`]).
+    pub fn clamped(mut self, monitors: &[DesktopRect]) -> Self {
+        let position = match self.window_position {
+            Some(position) => position,
+            None => return self,
+        };
+        // Size which is chosen by the platform is not known yet,
+        // so at least the minimal part of the window is kept on the monitor.
+        let min_size = Size::new(MIN_VISIBLE_SIZE, MIN_VISIBLE_SIZE);
+        let size = self.window_size.unwrap_or(min_size);
+        let window = self::clamp_to_monitors(DesktopRect::new(position, size), monitors);
+        self.window_position = Some(window.position);
+        self.window_size = self.window_size.map(|_| window.size);
+        self
+    }
+}
+
+impl fmt::Display for EngineSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version = {}", SETTINGS_VERSION)?;
+        if let Some([x, y]) = self.window_position {
+            writeln!(f, "window_position = {}, {}", x, y)?;
+        }
+        if let Some(size) = self.window_size {
+            writeln!(f, "window_size = {}x{}", size.width, size.height)?;
+        }
+        writeln!(f, "maximized = {}", self.maximized)?;
+        writeln!(f, "fullscreen = {}", self.fullscreen)?;
+        writeln!(f, "vsync = {}", self.vsync)?;
+        writeln!(f, "render_scale = {}", self.render_scale)?;
+        writeln!(f, "overlay_level = {}", self.overlay_level.name())
+    }
+}
+
+impl FromStr for EngineSettings {
+    type Err = SettingsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut settings = Self::default();
+        let mut version = None;
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(SettingsError::Syntax(index + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || SettingsError::InvalidValue {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            match key {
+                "version" => version = Some(value.parse().map_err(|_| invalid())?),
+                "window_position" => {
+                    let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+                    let x = x.trim().parse().map_err(|_| invalid())?;
+                    let y = y.trim().parse().map_err(|_| invalid())?;
+                    settings.window_position = Some([x, y]);
+                }
+                "window_size" => {
+                    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+                    let width = width.trim().parse().map_err(|_| invalid())?;
+                    let height = height.trim().parse().map_err(|_| invalid())?;
+                    if width == 0 || height == 0 {
+                        return Err(invalid());
+                    }
+                    settings.window_size = Some(Size::new(width, height));
+                }
+                "maximized" => settings.maximized = value.parse().map_err(|_| invalid())?,
+                "fullscreen" => settings.fullscreen = value.parse().map_err(|_| invalid())?,
+                "vsync" => settings.vsync = value.parse().map_err(|_| invalid())?,
+                "render_scale" => {
+                    let scale: f32 = value.parse().map_err(|_| invalid())?;
+                    if !scale.is_finite() || scale <= 0.0 {
+                        return Err(invalid());
+                    }
+                    settings.render_scale = scale;
+                }
+                "overlay_level" => {
+                    settings.overlay_level = OverlayLevel::from_name(value).ok_or_else(invalid)?
+                }
+                _ => return Err(SettingsError::Unknown(key.to_string())),
+            }
+        }
+        match version {
+            Some(SETTINGS_VERSION) => Ok(settings),
+            version => Err(SettingsError::Version(version)),
+        }
+    }
+}
+
+/// Default settings file of the game with given name
+/// inside of the configuration directory of the platform.
+///
+/// Returns `None` if the configuration directory is not known.
+///
+pub fn default_path(name: &str) -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let config_dir = if cfg!(windows) {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+    }?;
+    Some(
+        config_dir
+            .join(self::dir_name(name))
+            .join(SETTINGS_FILE_NAME),
+    )
+}
+
+/// Name of the directory of the game which is safe to use on any platform.
+pub(crate) fn dir_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|char| match char {
+            char if char.is_alphanumeric() || char == '-' => char.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect();
+    match name.trim_matches('_') {
+        "" => ENGINE_NAME.to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Rectangle on the desktop (in physical pixels).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DesktopRect {
+    /// Position of top left corner of the rectangle.
+    pub position: [i32; 2],
+    pub size: Size,
+}
+
+impl DesktopRect {
+    /// Creates new rectangle with given position and size.
+    pub const fn new(position: [i32; 2], size: Size) -> Self {
+        Self { position, size }
+    }
+
+    /// Size of intersection of two rectangles along each axis.
+    fn overlap(&self, other: &Self) -> [i64; 2] {
+        let overlap = |axis: usize, size: fn(&Size) -> u32| {
+            let start = self.position[axis].max(other.position[axis]) as i64;
+            let end = (self.position[axis] as i64 + size(&self.size) as i64)
+                .min(other.position[axis] as i64 + size(&other.size) as i64);
+            (end - start).max(0)
+        };
+        [
+            overlap(0, |size| size.width),
+            overlap(1, |size| size.height),
+        ]
+    }
+
+    /// Squared distance from the point to the nearest point of the rectangle.
+    fn distance_squared(&self, [x, y]: [i64; 2]) -> i64 {
+        let distance = |point: i64, start: i32, size: u32| {
+            let start = start as i64;
+            let end = start + size as i64;
+            (start - point).max(point - end).max(0)
+        };
+        let dx = distance(x, self.position[0], self.size.width);
+        let dy = distance(y, self.position[1], self.size.height);
+        dx * dx + dy * dy
+    }
+}
+
+impl From<&MonitorInfo> for DesktopRect {
+    fn from(monitor: &MonitorInfo) -> Self {
+        Self::new(monitor.position, monitor.size)
+    }
+}
+
+/// Moves the window onto the monitors if it is (almost) fully off-screen,
+/// for example if the monitor it was left on was unplugged.
+///
+/// Window which shows at least [`MIN_VISIBLE_SIZE`] on some monitor is not changed.
+/// Otherwise it is moved into the nearest monitor and shrunk to fit it.
+/// Window is not changed if no monitors are known.
+///
+pub fn clamp_to_monitors(window: DesktopRect, monitors: &[DesktopRect]) -> DesktopRect {
+    let min_visible = [
+        window.size.width.min(MIN_VISIBLE_SIZE) as i64,
+        window.size.height.min(MIN_VISIBLE_SIZE) as i64,
+    ];
+    let visible = monitors.iter().any(|monitor| {
+        let [width, height] = window.overlap(monitor);
+        width >= min_visible[0] && height >= min_visible[1]
+    });
+    if visible {
+        return window;
+    }
+
+    let center = [
+        window.position[0] as i64 + window.size.width as i64 / 2,
+        window.position[1] as i64 + window.size.height as i64 / 2,
+    ];
+    let monitor = match monitors
+        .iter()
+        .min_by_key(|monitor| monitor.distance_squared(center))
+    {
+        Some(monitor) => monitor,
+        None => return window,
+    };
+    let size = Size::new(
+        window.size.width.min(monitor.size.width),
+        window.size.height.min(monitor.size.height),
+    );
+    let clamp = |position: i32, start: i32, monitor_size: u32, size: u32| {
+        let end = start as i64 + monitor_size as i64 - size as i64;
+        (position as i64).clamp(start as i64, end) as i32
+    };
+    let position = [
+        clamp(
+            window.position[0],
+            monitor.position[0],
+            monitor.size.width,
+            size.width,
+        ),
+        clamp(
+            window.position[1],
+            monitor.position[1],
+            monitor.size.height,
+            size.height,
+        ),
+    ];
+    DesktopRect::new(position, size)
+}
+
+/// Settings of the running application which are saved into the file when changed.
+#[derive(Debug)]
+pub(crate) struct SettingsStore {
+    path: PathBuf,
+    settings: EngineSettings,
+    /// Time of the first change which was not saved yet.
+    changed_at: Option<Instant>,
+}
+
+impl SettingsStore {
+    /// Creates store of given settings which are saved into the file.
+    ///
+    /// Settings are saved later if they differ from the stored ones.
+    ///
+    pub fn new(path: PathBuf, settings: EngineSettings, stored: Option<EngineSettings>) -> Self {
+        let changed_at = if stored == Some(settings) {
+            None
+        } else {
+            Some(Instant::now())
+        };
+        Self {
+            path,
+            settings,
+            changed_at,
+        }
+    }
+
+    /// Current settings of the application.
+    pub fn settings(&self) -> &EngineSettings {
+        &self.settings
+    }
+
+    /// Changes settings, which are saved later by [`SettingsStore::save_if_due`].
+    pub fn update(&mut self, f: impl FnOnce(&mut EngineSettings)) {
+        let old = self.settings;
+        f(&mut self.settings);
+        if self.settings != old && self.changed_at.is_none() {
+            self.changed_at = Some(Instant::now());
+        }
+    }
+
+    /// Saves changed settings if [`SAVE_DELAY`] passed since they were changed.
+    pub fn save_if_due(&mut self) {
+        match self.changed_at {
+            Some(changed_at) if changed_at.elapsed() >= SAVE_DELAY => self.flush(),
+            _ => (),
+        }
+    }
+
+    /// Saves changed settings immediately.
+    pub fn flush(&mut self) {
+        if self.changed_at.take().is_none() {
+            return;
+        }
+        if let Err(error) = self.settings.save(&self.path) {
+            log::warn!("failed to save settings {}: {}", self.path.display(), error);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+#![cfg(test)]
+
+use super::*;
+
+const FULL_HD: Size = Size::new(1920, 1080);
+
+fn settings() -> EngineSettings {
+    EngineSettings {
+        window_position: Some([-1500, 40]),
+        window_size: Some(Size::new(1280, 720)),
+        maximized: false,
+        fullscreen: true,
+        vsync: false,
+        render_scale: 0.75,
+        overlay_level: OverlayLevel::Resources,
+    }
+}
+
+#[test]
+fn test_settings_round_trip() {
+    let settings = settings();
+    assert_eq!(settings.to_string().parse(), Ok(settings));
+    let defaults = EngineSettings::default();
+    assert_eq!(defaults.to_string().parse(), Ok(defaults));
+
+    let path = std::env::temp_dir()
+        .join(format!("titan_settings_test_{}", std::process::id()))
+        .join(SETTINGS_FILE_NAME);
+    assert_eq!(EngineSettings::load(&path), None);
+    settings.save(&path).unwrap();
+    assert_eq!(EngineSettings::load(&path), Some(settings));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_settings_discarded() {
+    let parse = |s: &str| s.parse::<EngineSettings>();
+    assert_eq!(parse(""), Err(SettingsError::Version(None)));
+    assert_eq!(parse("version = 2\n"), Err(SettingsError::Version(Some(2))));
+    assert_eq!(parse("version = 1\nvsync\n"), Err(SettingsError::Syntax(2)));
+    assert_eq!(
+        parse("version = 1\ngamma = 2.2\n"),
+        Err(SettingsError::Unknown("gamma".to_string())),
+    );
+    let invalid = [
+        ("window_size", "1280"),
+        ("window_size", "0x720"),
+        ("window_position", "10; 20"),
+        ("vsync", "yes"),
+        ("render_scale", "NaN"),
+        ("overlay_level", "everything"),
+    ];
+    for (key, value) in invalid {
+        let expected = SettingsError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let settings = format!("version = 1\n{} = {}\n", key, value);
+        assert_eq!(parse(&settings), Err(expected));
+    }
+
+    // Comments and missing keys are allowed.
+    let settings = parse("# comment\nversion = 1\n\nvsync = false\n").unwrap();
+    assert!(!settings.vsync);
+    assert_eq!(settings.window_size, None);
+}
+
+#[test]
+fn test_clamp_to_monitors() {
+    let primary = DesktopRect::new([0, 0], FULL_HD);
+    let left = DesktopRect::new([-1920, 0], FULL_HD);
+    let window = DesktopRect::new([-1500, 40], Size::new(1280, 720));
+
+    // Window which is visible enough is not moved.
+    assert_eq!(clamp_to_monitors(window, &[primary, left]), window);
+    let edge = DesktopRect::new([-1280 + 64, 40], Size::new(1280, 720));
+    assert_eq!(clamp_to_monitors(edge, &[primary]), edge);
+    assert_eq!(clamp_to_monitors(window, &[]), window);
+
+    // Monitor on the left was unplugged, so the window is moved onto the primary one.
+    let clamped = clamp_to_monitors(window, &[primary]);
+    assert_eq!(clamped, DesktopRect::new([0, 40], Size::new(1280, 720)));
+    let edge = DesktopRect::new([-1280 + 63, 40], Size::new(1280, 720));
+    assert_eq!(clamp_to_monitors(edge, &[primary]).position, [0, 40]);
+
+    // Window is moved onto the nearest monitor and shrunk to fit it.
+    let small = DesktopRect::new([1920, 0], Size::new(1280, 1024));
+    let window = DesktopRect::new([4000, 2000], Size::new(1600, 1200));
+    let clamped = clamp_to_monitors(window, &[primary, small]);
+    assert_eq!(clamped, DesktopRect::new([1920, 0], Size::new(1280, 1024)));
+
+    // Position without size keeps the minimal part of the window on the monitor.
+    let settings = EngineSettings {
+        window_position: Some([5000, 5000]),
+        ..EngineSettings::default()
+    };
+    let clamped = settings.clamped(&[primary]);
+    assert_eq!(clamped.window_position, Some([1920 - 64, 1080 - 64]));
+    assert_eq!(clamped.window_size, None);
+}
+
+#[test]
+fn test_dir_name() {
+    assert_eq!(dir_name("Hello World"), "hello_world");
+    assert_eq!(dir_name("my-game: 2"), "my-game__2");
+    assert_eq!(dir_name(" / "), ENGINE_NAME);
+}
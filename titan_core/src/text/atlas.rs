@@ -0,0 +1,157 @@
+//! Glyph atlas utilities of text rendering.
+
+use thiserror::Error;
+
+/// Initial size of the atlas (in pixels).
+pub const INITIAL_ATLAS_SIZE: u32 = 256;
+
+/// Max size of the atlas (in pixels) which is supported by all devices.
+pub const MAX_ATLAS_SIZE: u32 = 4096;
+
+/// Empty space between glyphs which prevents bleeding on linear filtering.
+const PADDING: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum AtlasError {
+    #[error("glyph of size {0}x{1} does not fit into atlas of max size")]
+    TooLarge(u32, u32),
+}
+
+/// Row of glyphs in the atlas.
+#[derive(Debug, Copy, Clone)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Position of the next glyph in this row.
+    x: u32,
+}
+
+/// Square single channel texture which contains rasterized glyphs.
+///
+/// Glyphs are packed into rows (shelves). If there is no space for new glyph,
+/// atlas grows twice in each dimension. Positions of already packed glyphs
+/// are preserved, so only UVs must be recalculated (and texture re-uploaded).
+#[derive(Debug)]
+pub struct Atlas {
+    size: u32,
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+    /// Whether pixels were changed since the last upload.
+    dirty: bool,
+}
+
+impl Atlas {
+    /// Creates an empty atlas of initial size.
+    pub fn new() -> Self {
+        Self::with_size(INITIAL_ATLAS_SIZE)
+    }
+
+    /// Creates an empty atlas of given size.
+    pub fn with_size(size: u32) -> Self {
+        Self {
+            size,
+            pixels: vec![0; (size * size) as usize],
+            shelves: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    /// Size of the atlas (in pixels).
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Coverage of each pixel of the atlas in row-major order.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns `true` if atlas was changed since the last call and clears this flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::replace(&mut self.dirty, false)
+    }
+
+    /// Finds free space for rectangle of given size without growing.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        let (padded_width, padded_height) = (width + PADDING, height + PADDING);
+        if padded_width > self.size {
+            return None;
+        }
+
+        let size = self.size;
+        let fits =
+            |shelf: &&mut Shelf| shelf.height >= padded_height && size - shelf.x >= padded_width;
+        if let Some(shelf) = self.shelves.iter_mut().find(fits) {
+            let position = [shelf.x, shelf.y];
+            shelf.x += padded_width;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        if self.size - y < padded_height {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height: padded_height,
+            x: padded_width,
+        });
+        Some([0, y])
+    }
+
+    /// Grows the atlas twice in each dimension, preserving positions of glyphs.
+    fn grow(&mut self) {
+        let old_size = self.size as usize;
+        let new_size = old_size * 2;
+        let mut pixels = vec![0; new_size * new_size];
+        for (row, old_row) in self.pixels.chunks_exact(old_size).enumerate() {
+            let start = row * new_size;
+            pixels[start..start + old_size].copy_from_slice(old_row);
+        }
+        self.size = new_size as u32;
+        self.pixels = pixels;
+        self.dirty = true;
+    }
+
+    /// Inserts glyph coverage of given size, growing the atlas if needed.
+    ///
+    /// Returns position of top left corner of inserted glyph.
+    ///
+    pub fn insert(
+        &mut self,
+        width: u32,
+        height: u32,
+        coverage: &[u8],
+    ) -> Result<[u32; 2], AtlasError> {
+        debug_assert_eq!(coverage.len(), (width * height) as usize);
+        let position = loop {
+            if let Some(position) = self.allocate(width, height) {
+                break position;
+            }
+            if self.size * 2 > MAX_ATLAS_SIZE {
+                return Err(AtlasError::TooLarge(width, height));
+            }
+            self.grow();
+        };
+
+        let [x, y] = position;
+        let size = self.size as usize;
+        if width > 0 {
+            for (row, glyph_row) in coverage.chunks_exact(width as usize).enumerate() {
+                let start = (y as usize + row) * size + x as usize;
+                self.pixels[start..start + width as usize].copy_from_slice(glyph_row);
+            }
+        }
+        self.dirty = true;
+        Ok(position)
+    }
+}
+
+impl Default for Atlas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
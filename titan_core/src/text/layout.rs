@@ -0,0 +1,172 @@
+//! Text layout utilities.
+//!
+//! Only simple layout is supported: lines are split by newlines (and optionally wrapped
+//! by spaces to fit into the maximal width) and aligned, characters are placed
+//! one after another with kerning. Proper shaping is out of scope.
+
+use ultraviolet::Vec2;
+
+/// Horizontal alignment of text lines relative to the position of text.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Lines start at the position.
+    #[default]
+    Left,
+    /// Lines are centered around the position.
+    Center,
+    /// Lines end at the position.
+    Right,
+}
+
+/// Metrics of glyphs of the font at some size (in pixels).
+pub trait GlyphMetrics {
+    /// Horizontal advance of the character.
+    fn advance(&self, c: char) -> f32;
+
+    /// Additional horizontal advance between two characters.
+    fn kern(&self, first: char, second: char) -> f32;
+
+    /// Distance from the top of the line to the baseline.
+    fn ascent(&self) -> f32;
+
+    /// Distance between baselines of two consecutive lines.
+    fn line_height(&self) -> f32;
+}
+
+/// Axis-aligned rectangle of text (in pixels).
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct TextBounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl TextBounds {
+    /// Size of the rectangle.
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+/// Character which is placed on its baseline.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PositionedChar {
+    pub c: char,
+    /// Position of the glyph origin (left side of the glyph on the baseline).
+    pub position: Vec2,
+}
+
+/// Result of text layout.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Layout {
+    pub chars: Vec<PositionedChar>,
+    pub bounds: TextBounds,
+}
+
+/// Width of the line with given metrics.
+fn line_width(line: &str, metrics: &impl GlyphMetrics) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in line.chars() {
+        if let Some(previous) = previous {
+            width += metrics.kern(previous, c);
+        }
+        width += metrics.advance(c);
+        previous = Some(c);
+    }
+    width
+}
+
+/// Splits the line into lines which fit into the maximal width.
+///
+/// Lines are broken at the last space which fits, and spaces around breaks are removed.
+/// Words which are wider than the maximal width are broken between characters.
+///
+fn wrap<'a>(line: &'a str, max_width: f32, metrics: &impl GlyphMetrics) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut rest = line;
+    loop {
+        if self::line_width(rest, metrics) <= max_width {
+            lines.push(rest);
+            return lines;
+        }
+
+        // End of the longest prefix which fits (at least one character is taken).
+        let mut fit = 0;
+        let mut space = None;
+        for (index, c) in rest.char_indices() {
+            let end = index + c.len_utf8();
+            if c == ' ' {
+                if !rest[..index].trim_end_matches(' ').is_empty() {
+                    space = Some(index);
+                }
+            } else if fit > 0 && self::line_width(&rest[..end], metrics) > max_width {
+                break;
+            }
+            fit = end;
+        }
+        let (head, tail) = rest.split_at(space.unwrap_or(fit));
+        lines.push(head.trim_end_matches(' '));
+        rest = tail.trim_start_matches(' ');
+        if rest.is_empty() {
+            return lines;
+        }
+    }
+}
+
+/// Lays out the text with top of the first line at the position.
+///
+/// If maximal width is given, lines which are wider are wrapped.
+///
+pub fn layout(
+    text: &str,
+    position: Vec2,
+    align: Align,
+    max_width: Option<f32>,
+    metrics: &impl GlyphMetrics,
+) -> Layout {
+    let mut chars = Vec::with_capacity(text.len());
+    let mut min_x = f32::INFINITY;
+    let mut max_x = f32::NEG_INFINITY;
+    let mut line_count = 0;
+
+    let lines = text.split('\n').flat_map(|line| {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match max_width {
+            Some(max_width) => self::wrap(line, max_width, metrics),
+            None => vec![line],
+        }
+    });
+    for (index, line) in lines.enumerate() {
+        let width = self::line_width(line, metrics);
+        let start = match align {
+            Align::Left => position.x,
+            Align::Center => position.x - width / 2.0,
+            Align::Right => position.x - width,
+        };
+        min_x = min_x.min(start);
+        max_x = max_x.max(start + width);
+        line_count = index + 1;
+
+        let baseline = position.y + metrics.ascent() + index as f32 * metrics.line_height();
+        let mut x = start;
+        let mut previous = None;
+        for c in line.chars() {
+            if let Some(previous) = previous {
+                x += metrics.kern(previous, c);
+            }
+            chars.push(PositionedChar {
+                c,
+                position: Vec2::new(x, baseline),
+            });
+            x += metrics.advance(c);
+            previous = Some(c);
+        }
+    }
+
+    let height = line_count as f32 * metrics.line_height();
+    let bounds = TextBounds {
+        min: Vec2::new(min_x, position.y),
+        max: Vec2::new(max_x, position.y + height),
+    };
+    Layout { chars, bounds }
+}
@@ -0,0 +1,389 @@
+//! Engine-managed text rendering which is independent of UI.
+//!
+//! Glyphs are rasterized from TrueType fonts into the atlas texture on demand
+//! and cached by font, size and character. Text is queued with [`TextBrush`]
+//! and drawn by the renderer once per frame over the scene.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use ab_glyph::{point, Font, FontArc, GlyphId, PxScaleFont, ScaleFont};
+use epaint::{pos2, Color32, Mesh, Rect};
+use thiserror::Error;
+use ultraviolet::Vec2;
+
+use crate::graphics::{ClipRect, ClipStack};
+use crate::math::Color;
+
+use atlas::Atlas;
+use layout::GlyphMetrics;
+
+pub use atlas::AtlasError;
+pub use layout::{Align, TextBounds};
+
+mod atlas;
+mod layout;
+
+mod tests;
+
+/// Font which is bundled with game engine (DejaVu Sans Mono).
+const DEFAULT_FONT: &[u8] = include_bytes!("../../res/fonts/DejaVuSansMono.ttf");
+
+#[derive(Debug, Error)]
+pub enum FontError {
+    #[error("invalid font data")]
+    Invalid,
+}
+
+/// Identifier of the font which was added to [`TextBrush`].
+///
+/// Default identifier refers to the bundled font.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+/// Text which is queued to be drawn in the current frame.
+#[derive(Debug, Clone)]
+pub struct TextSection {
+    pub text: String,
+    /// Position of the text on the screen (in logical pixels).
+    ///
+    /// Top of the first line is placed at the position,
+    /// horizontal placement depends on [`TextSection::align`].
+    pub position: Vec2,
+    /// Size of the text (in logical pixels).
+    pub size: f32,
+    pub color: Color,
+    pub align: Align,
+    /// Width which lines of the text are wrapped to fit into (in logical pixels), if any.
+    pub max_width: Option<f32>,
+    pub font: FontId,
+    /// Rectangle which the text is clipped by, if any.
+    ///
+    /// When the section is queued, it is intersected with the rectangle
+    /// of [`ClipStack`] of the brush.
+    ///
+    pub clip: Option<ClipRect>,
+}
+
+impl TextSection {
+    /// Creates left aligned text section with the bundled font.
+    pub fn new(text: &str, position: Vec2, size: f32, color: impl Into<Color>) -> Self {
+        Self {
+            text: text.to_string(),
+            position,
+            size,
+            color: color.into(),
+            align: Align::default(),
+            max_width: None,
+            font: FontId::default(),
+            clip: None,
+        }
+    }
+
+    /// Same section with given alignment.
+    pub fn with_align(self, align: Align) -> Self {
+        Self { align, ..self }
+    }
+
+    /// Same section which lines are wrapped to fit into given width.
+    pub fn with_max_width(self, max_width: f32) -> Self {
+        let max_width = Some(max_width);
+        Self { max_width, ..self }
+    }
+
+    /// Same section with given font.
+    pub fn with_font(self, font: FontId) -> Self {
+        Self { font, ..self }
+    }
+
+    /// Same section which is clipped by given rectangle.
+    pub fn with_clip(self, clip: ClipRect) -> Self {
+        let clip = Some(clip);
+        Self { clip, ..self }
+    }
+}
+
+/// Key of the glyph in the glyph cache.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    /// Size of the glyph (in physical pixels).
+    size: u32,
+    c: char,
+}
+
+/// Glyph which was rasterized into the atlas.
+#[derive(Debug, Copy, Clone)]
+struct CachedGlyph {
+    /// Position of the glyph in the atlas (in pixels).
+    position: [u32; 2],
+    /// Size of the glyph in the atlas (in pixels).
+    size: [u32; 2],
+    /// Offset of top left corner of the glyph relative to its origin.
+    offset: Vec2,
+}
+
+/// Returns glyph of the character, or replacement glyph if font has no such character.
+fn glyph_id(font: &impl Font, c: char) -> GlyphId {
+    [c, char::REPLACEMENT_CHARACTER, '?']
+        .into_iter()
+        .map(|c| font.glyph_id(c))
+        .find(|id| id.0 != 0)
+        .unwrap_or(GlyphId(0))
+}
+
+/// Metrics of the font at some size.
+struct FontMetrics<'a>(PxScaleFont<&'a FontArc>);
+
+impl GlyphMetrics for FontMetrics<'_> {
+    fn advance(&self, c: char) -> f32 {
+        self.0.h_advance(self::glyph_id(self.0.font, c))
+    }
+
+    fn kern(&self, first: char, second: char) -> f32 {
+        let first = self::glyph_id(self.0.font, first);
+        let second = self::glyph_id(self.0.font, second);
+        self.0.kern(first, second)
+    }
+
+    fn ascent(&self) -> f32 {
+        self.0.ascent()
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.height() + self.0.line_gap()
+    }
+}
+
+/// Mesh of the text which was queued in the frame.
+pub(crate) struct TextFlush {
+    /// Meshes of glyph quads with rectangles which they are clipped by
+    /// (texture identifier must be set by the renderer).
+    ///
+    /// Sections are merged into one mesh only if they are clipped by the same rectangle.
+    ///
+    pub meshes: Vec<(Option<ClipRect>, Mesh)>,
+    /// Size and pixels of the atlas if it was changed and must be re-uploaded.
+    pub atlas: Option<(u32, Vec<u8>)>,
+}
+
+struct TextState {
+    fonts: Vec<FontArc>,
+    glyphs: HashMap<GlyphKey, Option<CachedGlyph>>,
+    atlas: Atlas,
+    sections: Vec<TextSection>,
+}
+
+impl TextState {
+    fn metrics(&self, font: FontId, size: f32) -> FontMetrics<'_> {
+        FontMetrics(self.fonts[font.0].as_scaled(size))
+    }
+
+    /// Returns cached glyph, rasterizing it into the atlas if needed.
+    ///
+    /// Returns `None` if glyph has no outline (for example, space)
+    /// or it does not fit into the atlas.
+    ///
+    fn glyph(&mut self, key: GlyphKey) -> Option<CachedGlyph> {
+        if let Some(&glyph) = self.glyphs.get(&key) {
+            return glyph;
+        }
+
+        let font = &self.fonts[key.font.0];
+        let glyph =
+            self::glyph_id(font, key.c).with_scale_and_position(key.size as f32, point(0.0, 0.0));
+        let glyph = font.outline_glyph(glyph).and_then(|outlined| {
+            let bounds = outlined.px_bounds();
+            let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+            if width == 0 || height == 0 {
+                return None;
+            }
+            let mut coverage = vec![0; (width * height) as usize];
+            outlined.draw(|x, y, c| {
+                let index = (y * width + x) as usize;
+                coverage[index] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            });
+            match self.atlas.insert(width, height, &coverage) {
+                Ok(position) => Some(CachedGlyph {
+                    position,
+                    size: [width, height],
+                    offset: Vec2::new(bounds.min.x, bounds.min.y),
+                }),
+                Err(error) => {
+                    log::warn!("glyph {:?} was skipped: {}", key.c, error);
+                    None
+                }
+            }
+        });
+        self.glyphs.insert(key, glyph);
+        glyph
+    }
+}
+
+/// Handle which queues text to be drawn in the current frame.
+///
+/// Clones share one queue of sections, which is taken by the renderer when the frame is recorded.
+#[derive(Clone)]
+pub struct TextBrush {
+    state: Arc<Mutex<TextState>>,
+    clip: ClipStack,
+}
+
+impl TextBrush {
+    /// Creates new text brush with the bundled font.
+    pub fn new() -> Self {
+        Self::with_clip_stack(ClipStack::default())
+    }
+
+    /// Creates new text brush which text is clipped by rectangles of given stack.
+    pub(crate) fn with_clip_stack(clip: ClipStack) -> Self {
+        let font = FontArc::try_from_slice(DEFAULT_FONT).expect("bundled font must be valid");
+        let state = TextState {
+            fonts: vec![font],
+            glyphs: HashMap::new(),
+            atlas: Atlas::new(),
+            sections: Vec::new(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            clip,
+        }
+    }
+
+    /// Stack of rectangles which clip text queued with the brush.
+    pub fn clip_stack(&self) -> &ClipStack {
+        &self.clip
+    }
+
+    /// Adds TrueType (or OpenType) font which could be used in text sections.
+    pub fn add_font(&self, data: Vec<u8>) -> Result<FontId, FontError> {
+        let font = FontArc::try_from_vec(data).map_err(|_| FontError::Invalid)?;
+        let mut state = self.state.lock().unwrap();
+        state.fonts.push(font);
+        Ok(FontId(state.fonts.len() - 1))
+    }
+
+    /// Queues left aligned text with the bundled font to be drawn in the current frame.
+    ///
+    /// Position and size are given in logical pixels.
+    ///
+    pub fn queue(&self, text: &str, position: Vec2, size: f32, color: impl Into<Color>) {
+        self.queue_section(TextSection::new(text, position, size, color))
+    }
+
+    /// Queues text section to be drawn in the current frame.
+    ///
+    /// Section is clipped by the current rectangle of [`ClipStack`];
+    /// if it clips everything, section is skipped.
+    ///
+    pub fn queue_section(&self, mut section: TextSection) {
+        section.clip = self.clip.clip(section.clip);
+        if matches!(section.clip, Some(clip) if clip.is_empty()) {
+            return;
+        }
+        self.state.lock().unwrap().sections.push(section)
+    }
+
+    /// Measures bounding box of left aligned text with the bundled font
+    /// placed at the origin.
+    pub fn measure(&self, text: &str, size: f32) -> TextBounds {
+        let state = self.state.lock().unwrap();
+        let metrics = state.metrics(FontId::default(), size);
+        layout::layout(text, Vec2::zero(), Align::Left, None, &metrics).bounds
+    }
+
+    /// Measures bounding box of the section placed at its position,
+    /// including wrapping of its lines.
+    pub fn measure_section(&self, section: &TextSection) -> TextBounds {
+        let state = self.state.lock().unwrap();
+        let metrics = state.metrics(section.font, section.size);
+        let TextSection {
+            text,
+            position,
+            align,
+            max_width,
+            ..
+        } = section;
+        layout::layout(text, *position, *align, *max_width, &metrics).bounds
+    }
+
+    /// Lays out all queued text and builds meshes of glyph quads.
+    ///
+    /// Glyphs are rasterized in physical pixels to be sharp on HiDPI screens,
+    /// while the mesh is in logical pixels (as UI meshes).
+    ///
+    pub(crate) fn flush(&self, scale_factor: f32) -> Option<TextFlush> {
+        let mut state = self.state.lock().unwrap();
+        let sections = std::mem::take(&mut state.sections);
+        if sections.is_empty() {
+            return None;
+        }
+
+        // Quads of consecutive sections with the same clip rectangle are grouped together.
+        let mut groups: Vec<(Option<ClipRect>, Vec<_>)> = Vec::new();
+        for section in sections {
+            if groups.last().map(|(clip, _)| *clip) != Some(section.clip) {
+                groups.push((section.clip, Vec::new()));
+            }
+            let quads = &mut groups.last_mut().unwrap().1;
+            let size = (section.size * scale_factor).round().max(1.0) as u32;
+            let layout = {
+                let metrics = state.metrics(section.font, size as f32);
+                let position = section.position * scale_factor;
+                let max_width = section.max_width.map(|width| width * scale_factor);
+                layout::layout(&section.text, position, section.align, max_width, &metrics)
+            };
+            let [r, g, b, a] = section.color.into();
+            let color = Color32::from_rgba_unmultiplied(r, g, b, a);
+            for positioned in layout.chars {
+                let key = GlyphKey {
+                    font: section.font,
+                    size,
+                    c: positioned.c,
+                };
+                if let Some(glyph) = state.glyph(key) {
+                    quads.push((glyph, positioned.position, color));
+                }
+            }
+        }
+
+        // UVs are calculated after all glyphs were inserted because atlas could grow.
+        let atlas_size = state.atlas.size() as f32;
+        let meshes = groups
+            .into_iter()
+            .map(|(clip, quads)| (clip, Self::mesh(quads, scale_factor, atlas_size)))
+            .collect();
+
+        let atlas = state
+            .atlas
+            .take_dirty()
+            .then(|| (state.atlas.size(), state.atlas.pixels().to_vec()));
+        Some(TextFlush { meshes, atlas })
+    }
+
+    /// Builds the mesh of glyph quads placed at their origins (in physical pixels).
+    fn mesh(quads: Vec<(CachedGlyph, Vec2, Color32)>, scale_factor: f32, atlas_size: f32) -> Mesh {
+        let mut mesh = Mesh::default();
+        for (glyph, origin, color) in quads {
+            let origin = Vec2::new(origin.x.round(), origin.y.round());
+            let size = Vec2::new(glyph.size[0] as f32, glyph.size[1] as f32);
+            let min = (origin + glyph.offset) / scale_factor;
+            let max = min + size / scale_factor;
+            let uv_min = Vec2::new(glyph.position[0] as f32, glyph.position[1] as f32);
+            let uv_min = uv_min / atlas_size;
+            let uv_max = uv_min + size / atlas_size;
+            mesh.add_rect_with_uv(
+                Rect::from_min_max(pos2(min.x, min.y), pos2(max.x, max.y)),
+                Rect::from_min_max(pos2(uv_min.x, uv_min.y), pos2(uv_max.x, uv_max.y)),
+                color,
+            );
+        }
+        mesh
+    }
+}
+
+impl Default for TextBrush {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,177 @@
+#![cfg(test)]
+
+use ultraviolet::Vec2;
+
+use super::{
+    atlas::{Atlas, AtlasError, MAX_ATLAS_SIZE},
+    layout::{self, Align, GlyphMetrics},
+    *,
+};
+
+/// Monospace metrics: each character is 10 pixels wide, lines are 20 pixels high.
+struct Monospace;
+
+impl GlyphMetrics for Monospace {
+    fn advance(&self, _: char) -> f32 {
+        10.0
+    }
+
+    fn kern(&self, _: char, _: char) -> f32 {
+        0.0
+    }
+
+    fn ascent(&self) -> f32 {
+        15.0
+    }
+
+    fn line_height(&self) -> f32 {
+        20.0
+    }
+}
+
+#[test]
+fn test_layout_align() {
+    let position = Vec2::new(100.0, 50.0);
+    let left = layout::layout("abcd", position, Align::Left, None, &Monospace);
+    assert_eq!(left.chars[0].position, Vec2::new(100.0, 65.0));
+    assert_eq!(left.bounds.min, Vec2::new(100.0, 50.0));
+    assert_eq!(left.bounds.max, Vec2::new(140.0, 70.0));
+
+    let center = layout::layout("abcd", position, Align::Center, None, &Monospace);
+    assert_eq!(center.chars[0].position.x, 80.0);
+    assert_eq!(center.bounds.size(), Vec2::new(40.0, 20.0));
+
+    let right = layout::layout("abcd", position, Align::Right, None, &Monospace);
+    assert_eq!(right.chars[3].position.x, 90.0);
+    assert_eq!(right.bounds.max.x, 100.0);
+}
+
+#[test]
+fn test_layout_newlines() {
+    let layout = layout::layout("ab\r\nc\n", Vec2::zero(), Align::Right, None, &Monospace);
+    let chars: String = layout.chars.iter().map(|c| c.c).collect();
+    assert_eq!(chars, "abc");
+    // Each line is aligned separately.
+    assert_eq!(layout.chars[0].position, Vec2::new(-20.0, 15.0));
+    assert_eq!(layout.chars[2].position, Vec2::new(-10.0, 35.0));
+    // Trailing newline starts an empty line.
+    assert_eq!(layout.bounds.size(), Vec2::new(20.0, 60.0));
+}
+
+#[test]
+fn test_layout_wrap() {
+    let text = "ab cd  efgh\nijklmnop";
+    let layout = layout::layout(text, Vec2::zero(), Align::Left, Some(50.0), &Monospace);
+    let mut lines = vec![String::new(); 4];
+    for positioned in &layout.chars {
+        let line = (positioned.position.y - 15.0) / 20.0;
+        lines[line as usize].push(positioned.c);
+    }
+    // Spaces around breaks are removed, long words are broken between characters.
+    assert_eq!(lines, ["ab cd", "efgh", "ijklm", "nop"]);
+    assert_eq!(layout.chars[5].position, Vec2::new(0.0, 35.0));
+    assert_eq!(layout.bounds.size(), Vec2::new(50.0, 80.0));
+
+    let centered = layout::layout("ab cd", Vec2::zero(), Align::Center, Some(25.0), &Monospace);
+    assert_eq!(centered.chars[2].position, Vec2::new(-10.0, 35.0));
+
+    // Text which fits is not wrapped.
+    let fits = layout::layout(text, Vec2::zero(), Align::Left, Some(1000.0), &Monospace);
+    let unwrapped = layout::layout(text, Vec2::zero(), Align::Left, None, &Monospace);
+    assert_eq!(fits, unwrapped);
+}
+
+#[test]
+fn test_atlas_grow() {
+    let mut atlas = Atlas::with_size(16);
+    let glyph = [255; 7 * 7];
+    let positions: Vec<_> = (0..4)
+        .map(|_| atlas.insert(7, 7, &glyph).unwrap())
+        .collect();
+    assert_eq!(atlas.size(), 16);
+    assert!(atlas.take_dirty());
+
+    // Fifth glyph does not fit, so the atlas grows and keeps old glyphs in place.
+    let position = atlas.insert(7, 7, &[128; 7 * 7]).unwrap();
+    assert_eq!(atlas.size(), 32);
+    assert!(atlas.take_dirty());
+    assert!(!positions.contains(&position));
+    for [x, y] in positions {
+        let index = (y * atlas.size() + x) as usize;
+        assert_eq!(atlas.pixels()[index], 255);
+        assert_eq!(atlas.pixels()[index + 6], 255);
+    }
+    let [x, y] = position;
+    assert_eq!(atlas.pixels()[(y * atlas.size() + x) as usize], 128);
+}
+
+#[test]
+fn test_atlas_too_large() {
+    let mut atlas = Atlas::with_size(16);
+    let size = MAX_ATLAS_SIZE;
+    let result = atlas.insert(size, 1, &vec![0; size as usize]);
+    assert!(matches!(result, Err(AtlasError::TooLarge(_, 1))));
+}
+
+#[test]
+fn test_brush_flush() {
+    let brush = TextBrush::new();
+    let color = Color::WHITE;
+    // Characters which are missing in the font are replaced, not panicking.
+    brush.queue("Hi 你好\n\u{10FFFF}", Vec2::new(10.0, 10.0), 16.0, color);
+    let flush = brush.flush(2.0).unwrap();
+    // 'H', 'i', two replacement glyphs and one more replacement on the second line.
+    assert_eq!(flush.meshes.len(), 1);
+    assert_eq!(flush.meshes[0].1.vertices.len(), 5 * 4);
+    let (size, pixels) = flush.atlas.unwrap();
+    assert_eq!(pixels.len(), (size * size) as usize);
+
+    // Glyphs are cached, so atlas is not re-uploaded.
+    brush.queue("iH", Vec2::zero(), 16.0, color);
+    let flush = brush.flush(2.0).unwrap();
+    assert_eq!(flush.meshes[0].1.vertices.len(), 2 * 4);
+    assert!(flush.atlas.is_none());
+    assert!(brush.flush(2.0).is_none());
+}
+
+#[test]
+fn test_brush_clip() {
+    let brush = TextBrush::new();
+    let color = Color::WHITE;
+    let list = ClipRect::new([0, 0], [200, 100]);
+    brush.queue("a", Vec2::zero(), 16.0, color);
+    brush.clip_stack().push_clip_rect(list);
+    brush.queue("b", Vec2::zero(), 16.0, color);
+    brush.queue("c", Vec2::zero(), 16.0, color);
+    // Section outside of the current rectangle is skipped.
+    let outside = ClipRect::new([300, 0], [10, 10]);
+    brush.queue_section(TextSection::new("d", Vec2::zero(), 16.0, color).with_clip(outside));
+    brush.clip_stack().pop_clip_rect();
+    brush.queue("e", Vec2::zero(), 16.0, color);
+
+    // Sections are not merged across different clip rectangles.
+    let flush = brush.flush(1.0).unwrap();
+    let meshes: Vec<_> = flush
+        .meshes
+        .iter()
+        .map(|(clip, mesh)| (*clip, mesh.vertices.len()))
+        .collect();
+    assert_eq!(meshes, [(None, 4), (Some(list), 2 * 4), (None, 4)]);
+}
+
+#[test]
+fn test_measure() {
+    let brush = TextBrush::new();
+    let one_line = brush.measure("abc", 16.0);
+    let two_lines = brush.measure("abc\nabcdef", 16.0);
+    assert_eq!(one_line.min, Vec2::zero());
+    assert!(two_lines.size().x > one_line.size().x);
+    assert!((two_lines.size().y - 2.0 * one_line.size().y).abs() < 1e-3);
+
+    let section = TextSection::new("abc abc", Vec2::new(10.0, 20.0), 16.0, Color::WHITE);
+    let wrapped = brush.measure_section(&section.clone().with_max_width(one_line.size().x));
+    assert_eq!(wrapped.min, Vec2::new(10.0, 20.0));
+    assert!((wrapped.size().x - one_line.size().x).abs() < 1e-3);
+    assert!((wrapped.size().y - two_lines.size().y).abs() < 1e-3);
+    assert!(brush.measure_section(&section).size().x > one_line.size().x);
+}
@@ -0,0 +1,61 @@
+//! System clipboard utilities of game engine window.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use copypasta::{ClipboardContext, ClipboardProvider};
+use thiserror::Error;
+
+/// Error of platform clipboard which is boxed by the clipboard crate.
+type PlatformError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum ClipboardError {
+    #[error("system clipboard is unavailable: {0}")]
+    Unavailable(#[source] PlatformError),
+
+    #[error("failed to get clipboard contents: {0}")]
+    Get(#[source] PlatformError),
+
+    #[error("failed to set clipboard contents: {0}")]
+    Set(#[source] PlatformError),
+}
+
+/// Handle to the system clipboard which is shared with the application.
+///
+/// Connection to the clipboard is established lazily on first access,
+/// so an application can be run without clipboard support
+/// (for example, without display server).
+#[derive(Default, Clone)]
+pub struct Clipboard {
+    context: Rc<RefCell<Option<ClipboardContext>>>,
+}
+
+impl Clipboard {
+    /// Returns text contents of the system clipboard.
+    pub fn get(&self) -> Result<String, ClipboardError> {
+        self.with_context(|context| context.get_contents().map_err(ClipboardError::Get))
+    }
+
+    /// Replaces contents of the system clipboard with the text.
+    pub fn set(&self, text: &str) -> Result<(), ClipboardError> {
+        self.with_context(|context| {
+            context
+                .set_contents(text.to_owned())
+                .map_err(ClipboardError::Set)
+        })
+    }
+
+    fn with_context<T>(
+        &self,
+        f: impl FnOnce(&mut ClipboardContext) -> Result<T, ClipboardError>,
+    ) -> Result<T, ClipboardError> {
+        let mut context = self.context.borrow_mut();
+        if context.is_none() {
+            let new = ClipboardContext::new().map_err(ClipboardError::Unavailable)?;
+            *context = Some(new);
+        }
+        f(context.as_mut().unwrap())
+    }
+}
@@ -0,0 +1,129 @@
+//! Conversions between coordinate spaces of game engine window.
+//!
+//! There are several coordinate spaces in use:
+//! - *physical* — pixels of the window (used by `winit`), origin is top left corner;
+//! - *logical* — points which are physical pixels divided by scale factor (used by `egui`);
+//! - *NDC* — normalized device coordinates of Vulkan, from `-1` to `1`
+//!   inside of rendered content, `y` axis points down.
+
+use ultraviolet::{Vec2, Vec3, Vec4};
+
+use crate::camera::Camera;
+
+use super::Size;
+
+mod tests;
+
+/// Rectangle of the window (in physical pixels) where rendered content is placed.
+///
+/// It differs from the whole window when content is letterboxed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ContentRect {
+    /// Offset of the content from the top left corner of the window.
+    pub offset: Vec2,
+    /// Size of the content.
+    pub size: Vec2,
+}
+
+impl ContentRect {
+    /// Rectangle which covers the whole window.
+    pub fn full(size: Size) -> Self {
+        Self {
+            offset: Vec2::zero(),
+            size: Vec2::new(size.width as f32, size.height as f32),
+        }
+    }
+}
+
+/// Converter between coordinate spaces of the window.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScreenSpace {
+    size: Size,
+    scale_factor: f64,
+    content_rect: ContentRect,
+}
+
+impl ScreenSpace {
+    /// Creates converter for the window with given physical size and scale factor.
+    ///
+    /// Rendered content is expected to cover the whole window.
+    pub fn new(size: Size, scale_factor: f64) -> Self {
+        Self::with_content_rect(size, scale_factor, ContentRect::full(size))
+    }
+
+    /// Creates converter for the window with content placed in given rectangle.
+    pub fn with_content_rect(size: Size, scale_factor: f64, content_rect: ContentRect) -> Self {
+        Self {
+            size,
+            scale_factor,
+            content_rect,
+        }
+    }
+
+    /// Physical size of the window.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Scale factor of the window.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Rectangle of the window where rendered content is placed.
+    pub fn content_rect(&self) -> ContentRect {
+        self.content_rect
+    }
+
+    /// Aspect ratio (width / height) of rendered content.
+    pub fn aspect_ratio(&self) -> f32 {
+        let size = self.content_rect.size;
+        size.x / size.y
+    }
+
+    /// Converts physical position into logical one.
+    pub fn physical_to_logical(&self, position: Vec2) -> Vec2 {
+        position / self.scale_factor as f32
+    }
+
+    /// Converts logical position into physical one.
+    pub fn logical_to_physical(&self, position: Vec2) -> Vec2 {
+        position * self.scale_factor as f32
+    }
+
+    /// Converts physical position into normalized device coordinates.
+    pub fn physical_to_ndc(&self, position: Vec2) -> Vec2 {
+        let ContentRect { offset, size } = self.content_rect;
+        let normalized = (position - offset) / size;
+        normalized * 2.0 - Vec2::one()
+    }
+
+    /// Converts normalized device coordinates into physical position.
+    pub fn ndc_to_physical(&self, ndc: Vec2) -> Vec2 {
+        let ContentRect { offset, size } = self.content_rect;
+        let normalized = (ndc + Vec2::one()) / 2.0;
+        offset + normalized * size
+    }
+
+    /// Returns ray (origin and unit direction) in the world
+    /// which goes from the camera through cursor at given physical position.
+    pub fn cursor_ray(&self, camera: &Camera, cursor: Vec2) -> (Vec3, Vec3) {
+        let ndc = self.physical_to_ndc(cursor);
+        let view_projection = camera.projection(self.aspect_ratio()) * camera.view();
+        let inverse = view_projection.inversed();
+        let unproject = |depth: f32| {
+            let point = inverse * Vec4::new(ndc.x, ndc.y, depth, 1.0);
+            point.truncated() / point.w
+        };
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        (near, (far - near).normalized())
+    }
+
+    /// Converts cursor at given physical position into the point in the world
+    /// which is placed at given distance (depth) from the camera along the cursor ray.
+    pub fn cursor_to_world(&self, camera: &Camera, cursor: Vec2, depth: f32) -> Vec3 {
+        let (_, direction) = self.cursor_ray(camera, cursor);
+        camera.position + direction * depth
+    }
+}
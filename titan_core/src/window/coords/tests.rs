@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use ultraviolet::{Vec2, Vec3};
+
+use crate::camera::Camera;
+
+use super::{super::Size, *};
+
+const SCALE_FACTORS: [f64; 3] = [1.0, 1.5, 2.0];
+
+fn assert_near2(actual: Vec2, expected: Vec2) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-4, "{:?} != {:?}", actual, expected);
+}
+
+fn assert_near3(actual: Vec3, expected: Vec3) {
+    let difference = (actual - expected).mag();
+    assert!(difference < 1e-3, "{:?} != {:?}", actual, expected);
+}
+
+#[test]
+fn test_physical_logical() {
+    for scale_factor in SCALE_FACTORS {
+        let space = ScreenSpace::new(Size::new(1200, 900), scale_factor);
+        let physical = Vec2::new(300.0, 450.0);
+        let logical = space.physical_to_logical(physical);
+        assert_near2(logical * scale_factor as f32, physical);
+        assert_near2(space.logical_to_physical(logical), physical);
+    }
+
+    let space = ScreenSpace::new(Size::new(1200, 900), 1.5);
+    let logical = space.physical_to_logical(Vec2::new(300.0, 450.0));
+    assert_near2(logical, Vec2::new(200.0, 300.0));
+}
+
+#[test]
+fn test_physical_ndc() {
+    for scale_factor in SCALE_FACTORS {
+        let space = ScreenSpace::new(Size::new(800, 600), scale_factor);
+        assert_near2(space.physical_to_ndc(Vec2::zero()), Vec2::new(-1.0, -1.0));
+        assert_near2(space.physical_to_ndc(Vec2::new(400.0, 300.0)), Vec2::zero());
+        assert_near2(space.physical_to_ndc(Vec2::new(800.0, 600.0)), Vec2::one());
+
+        // Click on UI element given in logical points.
+        let logical = Vec2::new(100.0, 50.0);
+        let physical = space.logical_to_physical(logical);
+        let ndc = space.physical_to_ndc(physical);
+        assert_near2(space.ndc_to_physical(ndc), physical);
+        assert_near2(
+            space.physical_to_logical(space.ndc_to_physical(ndc)),
+            logical,
+        );
+    }
+}
+
+#[test]
+fn test_letterbox() {
+    for scale_factor in SCALE_FACTORS {
+        // 4:3 content inside of 16:9 window.
+        let content_rect = ContentRect {
+            offset: Vec2::new(240.0, 0.0),
+            size: Vec2::new(1440.0, 1080.0),
+        };
+        let space =
+            ScreenSpace::with_content_rect(Size::new(1920, 1080), scale_factor, content_rect);
+        assert_near2(
+            space.physical_to_ndc(Vec2::new(240.0, 0.0)),
+            Vec2::new(-1.0, -1.0),
+        );
+        assert_near2(space.physical_to_ndc(Vec2::new(960.0, 540.0)), Vec2::zero());
+        assert_near2(
+            space.ndc_to_physical(Vec2::one()),
+            Vec2::new(1680.0, 1080.0),
+        );
+        assert!((space.aspect_ratio() - 4.0 / 3.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_cursor_to_world() {
+    let camera = Camera::look_at(Vec3::zero(), Vec3::unit_x());
+    for scale_factor in SCALE_FACTORS {
+        let space = ScreenSpace::new(Size::new(800, 600), scale_factor);
+
+        let center = Vec2::new(400.0, 300.0);
+        let point = space.cursor_to_world(&camera, center, 10.0);
+        assert_near3(point, Vec3::new(10.0, 0.0, 0.0));
+
+        // Top of the window is above the camera, left is to the left of it.
+        let top = space.cursor_to_world(&camera, Vec2::new(400.0, 0.0), 10.0);
+        assert!(top.z > 0.0 && top.y.abs() < 1e-3);
+        let left = space.cursor_to_world(&camera, Vec2::new(0.0, 300.0), 10.0);
+        assert!(left.y > 0.0 && left.z.abs() < 1e-3);
+
+        // Vertical edge of the view is placed at half of field of view.
+        let (_, direction) = space.cursor_ray(&camera, Vec2::new(400.0, 0.0));
+        let angle = direction.dot(camera.forward()).acos();
+        assert!((angle - camera.fov / 2.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,119 @@
+//! Runtime switching between fullscreen and windowed mode of the main window.
+//!
+//! Requests of [`DisplayControl`] are applied by the application before the next frame.
+//! Swapchain is recreated for the new size of the window,
+//! and [`Event::Resized`](super::Event::Resized) is delivered after each applied request.
+
+use std::sync::{Arc, Mutex};
+
+use super::monitor::{FullscreenMode, MonitorId, MonitorInfo};
+
+mod tests;
+
+/// Request which is applied by the application before the next frame.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DisplayRequest {
+    /// Makes the window fullscreen on the monitor with given identifier
+    /// (or on the current monitor of the window).
+    Fullscreen(Option<MonitorId>, FullscreenMode),
+    /// Exits fullscreen mode of the window.
+    Windowed,
+}
+
+#[derive(Debug, Default)]
+struct DisplayState {
+    fullscreen: Option<FullscreenMode>,
+    monitors: Vec<MonitorInfo>,
+    request: Option<DisplayRequest>,
+}
+
+impl DisplayState {
+    /// Checks if the window is fullscreen after the pending request is applied.
+    fn will_be_fullscreen(&self) -> bool {
+        match &self.request {
+            Some(DisplayRequest::Fullscreen(..)) => true,
+            Some(DisplayRequest::Windowed) => false,
+            None => self.fullscreen.is_some(),
+        }
+    }
+}
+
+/// Handle which switches the main window between fullscreen and windowed mode at runtime.
+///
+/// Only the last request before the next frame is applied. Requests are ignored
+/// in headless mode, and failed ones (for example, if the video mode is not supported
+/// by the monitor) are logged.
+///
+/// Clones share pending requests, so the last request made through any of them wins.
+#[derive(Debug, Default, Clone)]
+pub struct DisplayControl {
+    state: Arc<Mutex<DisplayState>>,
+}
+
+impl DisplayControl {
+    /// Requests to make the window fullscreen on its current monitor.
+    pub fn set_fullscreen(&self, mode: FullscreenMode) {
+        self.request(DisplayRequest::Fullscreen(None, mode))
+    }
+
+    /// Requests to make the window fullscreen on the monitor with given identifier.
+    pub fn set_fullscreen_on(&self, monitor_id: MonitorId, mode: FullscreenMode) {
+        self.request(DisplayRequest::Fullscreen(Some(monitor_id), mode))
+    }
+
+    /// Requests to exit fullscreen mode of the window.
+    pub fn set_windowed(&self) {
+        self.request(DisplayRequest::Windowed)
+    }
+
+    /// Requests to exit fullscreen mode if the window is (or will be) fullscreen,
+    /// otherwise requests to make it fullscreen on its current monitor with given mode.
+    pub fn toggle_fullscreen(&self, mode: FullscreenMode) {
+        let mut state = self.state.lock().unwrap();
+        state.request = Some(match state.will_be_fullscreen() {
+            true => DisplayRequest::Windowed,
+            false => DisplayRequest::Fullscreen(None, mode),
+        });
+    }
+
+    /// Fullscreen mode of the window, or `None` if the window is not fullscreen.
+    ///
+    /// Mode is updated when requests are applied, not when they are made.
+    ///
+    pub fn fullscreen(&self) -> Option<FullscreenMode> {
+        self.state.lock().unwrap().fullscreen
+    }
+
+    /// Returns `true` if the window is fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen().is_some()
+    }
+
+    /// Information about all monitors (with their video modes) which are available to the window.
+    ///
+    /// Monitors are updated when they are connected or disconnected
+    /// (see [`Event::MonitorsChanged`](super::Event::MonitorsChanged)).
+    ///
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.state.lock().unwrap().monitors.clone()
+    }
+
+    fn request(&self, request: DisplayRequest) {
+        self.state.lock().unwrap().request = Some(request);
+    }
+
+    /// Takes the last request which was made since the last call.
+    pub(crate) fn take_request(&self) -> Option<DisplayRequest> {
+        self.state.lock().unwrap().request.take()
+    }
+
+    /// Updates fullscreen mode of the window.
+    pub(crate) fn set_mode(&self, fullscreen: Option<FullscreenMode>) {
+        self.state.lock().unwrap().fullscreen = fullscreen;
+    }
+
+    /// Updates monitors which are available to the window.
+    pub(crate) fn set_monitors(&self, monitors: Vec<MonitorInfo>) {
+        self.state.lock().unwrap().monitors = monitors;
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(test)]
+
+use crate::window::{Size, VideoMode};
+
+use super::*;
+
+#[test]
+fn test_display_requests() {
+    let display = DisplayControl::default();
+    assert!(!display.is_fullscreen());
+    assert_eq!(display.take_request(), None);
+
+    // Only the last request is applied.
+    let video_mode = VideoMode {
+        size: Size::new(1920, 1080),
+        refresh_rate: 60,
+        bit_depth: 32,
+    };
+    display.set_fullscreen(FullscreenMode::Exclusive(video_mode));
+    display.set_fullscreen(FullscreenMode::Borderless);
+    assert_eq!(
+        display.take_request(),
+        Some(DisplayRequest::Fullscreen(None, FullscreenMode::Borderless))
+    );
+    assert_eq!(display.take_request(), None);
+    // Mode is not changed until the request is applied.
+    assert!(!display.is_fullscreen());
+    display.set_mode(Some(FullscreenMode::Borderless));
+    assert_eq!(display.fullscreen(), Some(FullscreenMode::Borderless));
+}
+
+#[test]
+fn test_toggle_fullscreen() {
+    let display = DisplayControl::default();
+    let mode = FullscreenMode::Borderless;
+    display.toggle_fullscreen(mode);
+    assert_eq!(
+        display.take_request(),
+        Some(DisplayRequest::Fullscreen(None, mode))
+    );
+    display.set_mode(Some(mode));
+
+    display.toggle_fullscreen(mode);
+    assert_eq!(display.take_request(), Some(DisplayRequest::Windowed));
+
+    // Toggles before the next frame are resolved against pending requests.
+    display.toggle_fullscreen(mode);
+    display.toggle_fullscreen(mode);
+    assert_eq!(
+        display.take_request(),
+        Some(DisplayRequest::Fullscreen(None, mode))
+    );
+}
@@ -0,0 +1,260 @@
+//! Gamepad input of game engine.
+//!
+//! With `gamepad` feature, gamepads are read with `gilrs` and polled by the application
+//! before each frame, and their events are delivered through the same callback
+//! as events of the window. Without the feature, gamepad events are never delivered.
+
+#[cfg(feature = "gamepad")]
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "gamepad")]
+use gilrs::{EventType, Gilrs, GilrsBuilder};
+
+#[cfg(feature = "gamepad")]
+use super::Event;
+
+mod tests;
+
+/// Identifier of the gamepad which stays the same while it is connected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(usize);
+
+#[cfg(feature = "gamepad")]
+impl From<gilrs::GamepadId> for GamepadId {
+    fn from(id: gilrs::GamepadId) -> Self {
+        Self(id.into())
+    }
+}
+
+/// Button of the gamepad, named by its position on the standard layout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Button {
+    /// The bottom action button (A on Xbox controllers, cross on PlayStation ones).
+    South,
+    /// The right action button (B on Xbox controllers, circle on PlayStation ones).
+    East,
+    /// The top action button (Y on Xbox controllers, triangle on PlayStation ones).
+    North,
+    /// The left action button (X on Xbox controllers, square on PlayStation ones).
+    West,
+    /// Additional action button of some gamepads.
+    C,
+    /// Additional action button of some gamepads.
+    Z,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    /// The central button (like Xbox or PS button).
+    Mode,
+    /// Button of the left stick which is pressed by pushing the stick.
+    LeftThumb,
+    /// Button of the right stick which is pressed by pushing the stick.
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[cfg(feature = "gamepad")]
+impl Button {
+    /// Converts the button of `gilrs`, which is `None` for unknown buttons.
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+        use gilrs::Button as Raw;
+
+        let button = match button {
+            Raw::South => Self::South,
+            Raw::East => Self::East,
+            Raw::North => Self::North,
+            Raw::West => Self::West,
+            Raw::C => Self::C,
+            Raw::Z => Self::Z,
+            Raw::LeftTrigger => Self::LeftTrigger,
+            Raw::LeftTrigger2 => Self::LeftTrigger2,
+            Raw::RightTrigger => Self::RightTrigger,
+            Raw::RightTrigger2 => Self::RightTrigger2,
+            Raw::Select => Self::Select,
+            Raw::Start => Self::Start,
+            Raw::Mode => Self::Mode,
+            Raw::LeftThumb => Self::LeftThumb,
+            Raw::RightThumb => Self::RightThumb,
+            Raw::DPadUp => Self::DPadUp,
+            Raw::DPadDown => Self::DPadDown,
+            Raw::DPadLeft => Self::DPadLeft,
+            Raw::DPadRight => Self::DPadRight,
+            Raw::Unknown => return None,
+        };
+        Some(button)
+    }
+}
+
+/// Axis of the gamepad.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Axis {
+    LeftStickX,
+    LeftStickY,
+    /// Additional axis of the left side of some gamepads.
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    /// Additional axis of the right side of some gamepads.
+    RightZ,
+    /// Horizontal axis of the directional pad, if it is reported as axis.
+    DPadX,
+    /// Vertical axis of the directional pad, if it is reported as axis.
+    DPadY,
+}
+
+#[cfg(feature = "gamepad")]
+impl Axis {
+    /// Converts the axis of `gilrs`, which is `None` for unknown axes.
+    fn from_gilrs(axis: gilrs::Axis) -> Option<Self> {
+        use gilrs::Axis as Raw;
+
+        let axis = match axis {
+            Raw::LeftStickX => Self::LeftStickX,
+            Raw::LeftStickY => Self::LeftStickY,
+            Raw::LeftZ => Self::LeftZ,
+            Raw::RightStickX => Self::RightStickX,
+            Raw::RightStickY => Self::RightStickY,
+            Raw::RightZ => Self::RightZ,
+            Raw::DPadX => Self::DPadX,
+            Raw::DPadY => Self::DPadY,
+            Raw::Unknown => return None,
+        };
+        Some(axis)
+    }
+}
+
+/// Scales value of the axis, so values inside of the deadzone become zero
+/// and values outside of it cover the whole range from `-1.0` to `1.0`.
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= deadzone {
+        return 0.0;
+    }
+    let scaled = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0);
+    scaled.copysign(value)
+}
+
+/// State of all gamepads which turns their raw input into events of game engine.
+///
+/// Deadzone is applied to axes, and events which do not change the state
+/// (like repeated presses or axis jitter inside of the deadzone) are skipped.
+///
+#[cfg(feature = "gamepad")]
+#[derive(Debug, Default)]
+pub(crate) struct GamepadState {
+    deadzone: f32,
+    buttons: HashSet<(GamepadId, Button)>,
+    axes: HashMap<(GamepadId, Axis), f32>,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadState {
+    pub fn new(deadzone: f32) -> Self {
+        Self {
+            deadzone,
+            ..Self::default()
+        }
+    }
+
+    /// Returns event of the button if it was not in this state already.
+    pub fn button(&mut self, id: GamepadId, button: Button, pressed: bool) -> Option<Event> {
+        let changed = if pressed {
+            self.buttons.insert((id, button))
+        } else {
+            self.buttons.remove(&(id, button))
+        };
+        changed.then(|| Event::GamepadButton {
+            id,
+            button,
+            pressed,
+        })
+    }
+
+    /// Returns event of the axis if its value with the deadzone applied was changed.
+    pub fn axis(&mut self, id: GamepadId, axis: Axis, value: f32) -> Option<Event> {
+        let value = self::apply_deadzone(value, self.deadzone);
+        let previous = self.axes.insert((id, axis), value).unwrap_or_default();
+        (previous != value).then(|| Event::GamepadAxis { id, axis, value })
+    }
+
+    /// Forgets state of the gamepad, returning its event.
+    pub fn disconnected(&mut self, id: GamepadId) -> Event {
+        self.buttons.retain(|&(gamepad, _)| gamepad != id);
+        self.axes.retain(|&(gamepad, _), _| gamepad != id);
+        Event::GamepadDisconnected(id)
+    }
+}
+
+/// Gamepads which are polled by the application.
+#[cfg(feature = "gamepad")]
+pub(crate) struct Gamepads {
+    gilrs: Gilrs,
+    state: GamepadState,
+    /// Gamepads which were connected before the first poll.
+    connected: Vec<(GamepadId, String)>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    /// Initializes gamepad input with given deadzone of axes.
+    ///
+    /// Returns `None` if gamepads are not supported by the platform.
+    ///
+    pub fn new(deadzone: f32) -> Option<Self> {
+        // Default filters of `gilrs` have their own deadzone, which is replaced by ours.
+        let gilrs = match GilrsBuilder::new().with_default_filters(false).build() {
+            Ok(gilrs) => gilrs,
+            Err(error) => {
+                log::warn!("gamepads are not available: {}", error);
+                return None;
+            }
+        };
+        let connected = gilrs
+            .gamepads()
+            .map(|(id, gamepad)| (id.into(), gamepad.name().to_string()))
+            .collect();
+        Some(Self {
+            gilrs,
+            state: GamepadState::new(deadzone),
+            connected,
+        })
+    }
+
+    /// Returns events of all gamepads since the previous poll in order of their arrival.
+    pub fn poll(&mut self) -> Vec<Event> {
+        let mut events: Vec<_> = self
+            .connected
+            .drain(..)
+            .map(|(id, name)| Event::GamepadConnected { id, name })
+            .collect();
+        while let Some(gilrs::Event {
+            id: raw_id, event, ..
+        }) = self.gilrs.next_event()
+        {
+            let id = GamepadId::from(raw_id);
+            let event = match event {
+                EventType::Connected => Some(Event::GamepadConnected {
+                    id,
+                    name: self.gilrs.gamepad(raw_id).name().to_string(),
+                }),
+                EventType::Disconnected => Some(self.state.disconnected(id)),
+                EventType::ButtonPressed(button, _) => Button::from_gilrs(button)
+                    .and_then(|button| self.state.button(id, button, true)),
+                EventType::ButtonReleased(button, _) => Button::from_gilrs(button)
+                    .and_then(|button| self.state.button(id, button, false)),
+                EventType::AxisChanged(axis, value, _) => {
+                    Axis::from_gilrs(axis).and_then(|axis| self.state.axis(id, axis, value))
+                }
+                _ => None,
+            };
+            events.extend(event);
+        }
+        events
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_deadzone() {
+    assert_eq!(apply_deadzone(0.05, 0.1), 0.0);
+    assert_eq!(apply_deadzone(-0.1, 0.1), 0.0);
+    assert_eq!(apply_deadzone(1.0, 0.1), 1.0);
+    assert_eq!(apply_deadzone(-1.0, 0.1), -1.0);
+    assert!((apply_deadzone(0.55, 0.1) - 0.5).abs() < 1e-6);
+    assert!((apply_deadzone(-0.55, 0.1) + 0.5).abs() < 1e-6);
+    // Without deadzone values are kept as is.
+    assert_eq!(apply_deadzone(0.3, 0.0), 0.3);
+}
+
+#[cfg(feature = "gamepad")]
+#[test]
+fn test_state_events() {
+    let first = GamepadId(0);
+    let second = GamepadId(1);
+    let mut state = GamepadState::new(0.2);
+
+    let event = state.button(first, Button::South, true);
+    assert!(matches!(
+        event,
+        Some(Event::GamepadButton {
+            id,
+            button: Button::South,
+            pressed: true,
+        }) if id == first,
+    ));
+    // Repeated press and release of the button which was not pressed are skipped.
+    assert!(state.button(first, Button::South, true).is_none());
+    assert!(state.button(second, Button::South, false).is_none());
+    assert!(state.button(second, Button::South, true).is_some());
+
+    // Jitter inside of the deadzone is skipped.
+    assert!(state.axis(first, Axis::LeftStickX, 0.1).is_none());
+    assert!(state.axis(first, Axis::LeftStickX, -0.15).is_none());
+    let event = state.axis(first, Axis::LeftStickX, 0.6);
+    assert!(matches!(
+        event,
+        Some(Event::GamepadAxis { value, .. }) if (value - 0.5).abs() < 1e-6,
+    ));
+    assert!(matches!(
+        state.axis(first, Axis::LeftStickX, 0.0),
+        Some(Event::GamepadAxis { value, .. }) if value == 0.0,
+    ));
+
+    // State of the disconnected gamepad is forgotten.
+    state.axis(first, Axis::LeftStickY, 1.0);
+    let event = state.disconnected(first);
+    assert!(matches!(event, Event::GamepadDisconnected(id) if id == first));
+    assert!(state.button(first, Button::South, true).is_some());
+    assert!(state.axis(first, Axis::LeftStickY, 1.0).is_some());
+    assert!(state.button(second, Button::South, true).is_none());
+}
@@ -0,0 +1,35 @@
+//! Icon utilities of game engine window.
+
+use image::RgbaImage;
+use thiserror::Error;
+use winit::window::{BadIcon, Icon};
+
+use super::Size;
+
+#[derive(Debug, Error)]
+pub enum IconError {
+    #[error("invalid icon data: {0}")]
+    InvalidData(#[from] BadIcon),
+}
+
+/// Icon of the window which is shown in the title bar and the taskbar.
+#[derive(Debug, Clone)]
+pub struct WindowIcon(Icon);
+
+impl WindowIcon {
+    /// Creates icon from pixels of given size in RGBA format (4 bytes per pixel, row by row).
+    pub fn from_rgba(rgba: Vec<u8>, size: Size) -> Result<Self, IconError> {
+        let icon = Icon::from_rgba(rgba, size.width, size.height)?;
+        Ok(Self(icon))
+    }
+
+    /// Creates icon from the image.
+    pub fn from_image(image: &RgbaImage) -> Result<Self, IconError> {
+        let (width, height) = image.dimensions();
+        Self::from_rgba(image.as_raw().clone(), Size::new(width, height))
+    }
+
+    pub(crate) fn icon(&self) -> Icon {
+        self.0.clone()
+    }
+}
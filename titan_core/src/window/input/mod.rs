@@ -0,0 +1,168 @@
+//! Input state utilities of game engine window.
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use ultraviolet::Vec2;
+use winit::event::{
+    self as winit_event, ElementState, KeyboardInput, MouseScrollDelta, WindowEvent,
+};
+
+pub use key::Key;
+
+mod key;
+mod tests;
+
+/// Approximate amount of scroll lines in one pixel of precise scroll delta.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Button of the mouse.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// Any other button, identified by its index.
+    Other(u16),
+}
+
+impl From<winit_event::MouseButton> for MouseButton {
+    fn from(button: winit_event::MouseButton) -> Self {
+        match button {
+            winit_event::MouseButton::Left => Self::Left,
+            winit_event::MouseButton::Right => Self::Right,
+            winit_event::MouseButton::Middle => Self::Middle,
+            winit_event::MouseButton::Other(index) => Self::Other(index),
+        }
+    }
+}
+
+impl From<MouseButton> for winit_event::MouseButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Self::Left,
+            MouseButton::Right => Self::Right,
+            MouseButton::Middle => Self::Middle,
+            MouseButton::Other(index) => Self::Other(index),
+        }
+    }
+}
+
+/// State of keyboard and mouse of game engine window for the current frame.
+#[derive(Default, Clone)]
+pub struct Input {
+    /// Keyboard keys which are held now.
+    keys: HashSet<Key>,
+    /// Mouse buttons which are held now.
+    mouse_buttons: HashSet<MouseButton>,
+    /// Mouse movement since the previous frame.
+    mouse_delta: Vec2,
+    /// Scroll (in lines) since the previous frame.
+    scroll_delta: f32,
+    /// Position of the cursor (in physical pixels) if it is inside of the window.
+    cursor_position: Option<Vec2>,
+    /// Request of the cursor grab which is shared with the application.
+    cursor_grab: Rc<Cell<bool>>,
+}
+
+impl Input {
+    /// Returns `true` if keyboard key is held now.
+    pub fn key_pressed(&self, key: Key) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Returns `true` if mouse button is held now.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// Mouse movement (in physical pixels) since the previous frame.
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    /// Scroll (in lines) since the previous frame.
+    ///
+    /// Positive values mean scrolling up (away from the user).
+    pub fn scroll_delta(&self) -> f32 {
+        self.scroll_delta
+    }
+
+    /// Position of the cursor (in physical pixels) if it is inside of the window.
+    ///
+    /// Can be converted into other coordinate spaces
+    /// with [`ScreenSpace`](super::coords::ScreenSpace).
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.cursor_position
+    }
+
+    /// Returns `true` if cursor grab was requested.
+    pub fn cursor_grab(&self) -> bool {
+        self.cursor_grab.get()
+    }
+
+    /// Requests the window to grab (or release) the cursor.
+    ///
+    /// Request will be applied by the application after the current event was handled.
+    pub fn set_cursor_grab(&self, grab: bool) {
+        self.cursor_grab.set(grab)
+    }
+
+    /// Updates input state with the window event.
+    pub(crate) fn handle_window_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    self.keys.insert(key.into());
+                }
+                ElementState::Released => {
+                    self.keys.remove(&key.into());
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    self.mouse_buttons.insert(button.into());
+                }
+                ElementState::Released => {
+                    self.mouse_buttons.remove(&button.into());
+                }
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vec2::new(position.x as f32, position.y as f32);
+                self.cursor_position = Some(position);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor_position = None,
+            WindowEvent::Focused(false) => {
+                self.keys.clear();
+                self.mouse_buttons.clear();
+            }
+            _ => (),
+        }
+    }
+
+    /// Updates input state with raw mouse movement.
+    pub(crate) fn handle_mouse_motion(&mut self, delta: (f64, f64)) {
+        self.mouse_delta += Vec2::new(delta.0 as f32, delta.1 as f32);
+    }
+
+    /// Resets per-frame state (mouse movement and scroll).
+    pub(crate) fn end_frame(&mut self) {
+        self.mouse_delta = Vec2::zero();
+        self.scroll_delta = 0.0;
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use winit::event::VirtualKeyCode;
+
+use super::*;
+
+#[test]
+fn test_winit_conversions() {
+    for key in [
+        VirtualKeyCode::Key1,
+        VirtualKeyCode::F3,
+        VirtualKeyCode::LShift,
+        VirtualKeyCode::Cut,
+    ] {
+        assert_eq!(VirtualKeyCode::from(Key::from(key)), key);
+    }
+    assert_eq!(Key::from(VirtualKeyCode::Space), Key::Space);
+
+    let button = winit_event::MouseButton::Other(4);
+    assert_eq!(MouseButton::from(button), MouseButton::Other(4));
+    assert_eq!(
+        winit_event::MouseButton::from(MouseButton::Middle),
+        winit_event::MouseButton::Middle
+    );
+}
+
+#[test]
+fn test_held_keys() {
+    let mut input = Input::default();
+    #[allow(deprecated)]
+    let event = |state| WindowEvent::KeyboardInput {
+        // Safety: dummy identifier is only compared with other ones.
+        device_id: unsafe { winit_event::DeviceId::dummy() },
+        input: KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(VirtualKeyCode::W),
+            modifiers: winit_event::ModifiersState::empty(),
+        },
+        is_synthetic: false,
+    };
+    input.handle_window_event(&event(ElementState::Pressed));
+    assert!(input.key_pressed(Key::W));
+    input.handle_window_event(&event(ElementState::Released));
+    assert!(!input.key_pressed(Key::W));
+}
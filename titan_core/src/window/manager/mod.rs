@@ -0,0 +1,153 @@
+//! Secondary windows which are created and closed at runtime.
+//!
+//! Requests of [`WindowManager`] are queued and applied by the application
+//! before the next frame, because windows could only be created by the main loop.
+//! Events of secondary windows are delivered as [`Event::Window`](super::Event::Window).
+
+use std::sync::{Arc, Mutex};
+
+use slotmap::{new_key_type, SlotMap};
+
+use crate::math::Color;
+
+use super::Size;
+
+mod tests;
+
+new_key_type! {
+    /// Identifier of the secondary window which was created by [`WindowManager`].
+    pub struct WindowId;
+}
+
+/// Description of the secondary window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowDesc {
+    /// Title of the window.
+    pub title: String,
+    /// Inner size of the window (in physical pixels).
+    pub size: Size,
+    /// Color which images of the window are cleared with.
+    pub clear_color: Color,
+}
+
+impl WindowDesc {
+    /// Creates description of the window with given title, default size and black clear color.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            size: Size::new(640, 480),
+            clear_color: Color::BLACK,
+        }
+    }
+
+    /// Returns the same description with given inner size of the window.
+    pub fn with_size(self, size: Size) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Returns the same description with given clear color.
+    pub fn with_clear_color(self, clear_color: Color) -> Self {
+        Self {
+            clear_color,
+            ..self
+        }
+    }
+}
+
+/// State of the secondary window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowState {
+    /// Window will be created before the next frame.
+    Pending,
+    /// Window is created and its images are presented on each frame.
+    Open,
+}
+
+/// Request which is applied by the application before the next frame.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WindowRequest {
+    Create(WindowId, WindowDesc),
+    Close(WindowId),
+}
+
+#[derive(Debug, Default)]
+struct Windows {
+    states: SlotMap<WindowId, WindowState>,
+    requests: Vec<WindowRequest>,
+}
+
+/// Handle which creates and closes secondary windows at runtime.
+///
+/// Each secondary window has its own surface and swapchain, while the device
+/// is shared with the main window. Its images are cleared with the color of its
+/// description, because the scene and UI are rendered into the main window only.
+///
+/// Clones share the list of windows, so windows created through one clone
+/// could be closed through another.
+#[derive(Debug, Default, Clone)]
+pub struct WindowManager {
+    windows: Arc<Mutex<Windows>>,
+}
+
+impl WindowManager {
+    /// Requests to create new window with given description.
+    ///
+    /// Window is created before the next frame,
+    /// and then [`WindowEvent::Created`](super::WindowEvent::Created) is delivered.
+    ///
+    pub fn create(&self, desc: WindowDesc) -> WindowId {
+        let mut windows = self.windows.lock().unwrap();
+        let id = windows.states.insert(WindowState::Pending);
+        windows.requests.push(WindowRequest::Create(id, desc));
+        id
+    }
+
+    /// Requests to close the window.
+    ///
+    /// Windows are not closed by the platform on their own: close could be requested
+    /// in response to [`WindowEvent::CloseRequested`](super::WindowEvent::CloseRequested).
+    /// Returns `false` if there is no such window.
+    ///
+    pub fn close(&self, id: WindowId) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+        if windows.states.remove(id).is_none() {
+            return false;
+        }
+        // Window which was not created yet is never created.
+        let count = windows.requests.len();
+        windows.requests.retain(
+            |request| !matches!(request, WindowRequest::Create(pending, _) if *pending == id),
+        );
+        if windows.requests.len() == count {
+            windows.requests.push(WindowRequest::Close(id));
+        }
+        true
+    }
+
+    /// State of the window, or `None` if it was closed.
+    pub fn state(&self, id: WindowId) -> Option<WindowState> {
+        self.windows.lock().unwrap().states.get(id).copied()
+    }
+
+    /// Identifiers of all windows which are not closed.
+    pub fn ids(&self) -> Vec<WindowId> {
+        self.windows.lock().unwrap().states.keys().collect()
+    }
+
+    /// Takes requests which were queued since the last call.
+    pub(crate) fn take_requests(&self) -> Vec<WindowRequest> {
+        std::mem::take(&mut self.windows.lock().unwrap().requests)
+    }
+
+    /// Marks the window as created.
+    pub(crate) fn opened(&self, id: WindowId) {
+        if let Some(state) = self.windows.lock().unwrap().states.get_mut(id) {
+            *state = WindowState::Open;
+        }
+    }
+
+    /// Forgets the window which failed to be created.
+    pub(crate) fn failed(&self, id: WindowId) {
+        self.windows.lock().unwrap().states.remove(id);
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_window_requests() {
+    let manager = WindowManager::default();
+    let desc = WindowDesc::new("tools").with_size(Size::new(320, 240));
+    let first = manager.create(desc.clone());
+    let second = manager.create(WindowDesc::new("preview"));
+    assert_eq!(manager.state(first), Some(WindowState::Pending));
+    assert_eq!(manager.ids().len(), 2);
+
+    // Window which is closed before creation is never created.
+    assert!(manager.close(second));
+    assert!(!manager.close(second));
+    assert_eq!(manager.state(second), None);
+    assert_eq!(
+        manager.take_requests(),
+        [WindowRequest::Create(first, desc)]
+    );
+    assert!(manager.take_requests().is_empty());
+
+    manager.opened(first);
+    assert_eq!(manager.state(first), Some(WindowState::Open));
+    assert!(manager.close(first));
+    assert_eq!(manager.take_requests(), [WindowRequest::Close(first)]);
+    assert!(manager.ids().is_empty());
+}
+
+#[test]
+fn test_failed_window() {
+    let manager = WindowManager::default();
+    let id = manager.create(WindowDesc::new("broken"));
+    manager.take_requests();
+    manager.failed(id);
+    assert_eq!(manager.state(id), None);
+    // Failed window was never opened, so it could not be closed.
+    assert!(!manager.close(id));
+    assert!(manager.take_requests().is_empty());
+}
@@ -1,8 +1,31 @@
 //! Utilities for window handling of game engine.
 
+use std::path::PathBuf;
+
 use egui::CtxRef;
 
 use crate::app::DeltaTime;
+use crate::asset::AssetEvent;
+
+pub use clipboard::{Clipboard, ClipboardError};
+pub use coords::{ContentRect, ScreenSpace};
+pub use display::DisplayControl;
+pub use gamepad::GamepadId;
+pub use icon::{IconError, WindowIcon};
+pub use input::Input;
+pub use manager::{WindowDesc, WindowId, WindowManager, WindowState};
+pub use monitor::{FullscreenMode, MonitorError, MonitorId, MonitorInfo, VideoMode};
+pub use proxy::{CustomEvent, EventProxy, EventProxyError};
+
+pub mod clipboard;
+pub mod coords;
+pub mod display;
+pub mod gamepad;
+pub mod icon;
+pub mod input;
+pub mod manager;
+pub mod monitor;
+pub mod proxy;
 
 /// General event of game engine window.
 pub enum Event {
@@ -12,18 +35,112 @@ pub enum Event {
     /// Called when game window was resized.
     Resized(Size),
 
+    /// Called when scale factor of game window was changed
+    /// (for example, window was moved onto monitor with another DPI).
+    ///
+    /// UI is rescaled by the engine automatically.
+    ScaleFactorChanged { scale: f64, new_size: Size },
+
+    /// Called when monitors were connected or disconnected.
+    MonitorsChanged(Vec<MonitorInfo>),
+
+    /// Called when input state of game window was updated (before [`Event::Update`]).
+    Input(Input),
+
+    /// Called when gamepad was connected (or was connected before the main loop started).
+    ///
+    /// Gamepad events are delivered only with `gamepad` feature.
+    ///
+    GamepadConnected { id: GamepadId, name: String },
+
+    /// Called when gamepad was disconnected.
+    GamepadDisconnected(GamepadId),
+
+    /// Called when gamepad button was pressed or released.
+    GamepadButton {
+        id: GamepadId,
+        button: gamepad::Button,
+        pressed: bool,
+    },
+
+    /// Called when value of gamepad axis (from `-1.0` to `1.0`) was changed.
+    ///
+    /// Values inside of the deadzone (see [`Config::with_gamepad_deadzone`](crate::config::Config::with_gamepad_deadzone))
+    /// are reported as zero.
+    ///
+    GamepadAxis {
+        id: GamepadId,
+        axis: gamepad::Axis,
+        value: f32,
+    },
+
+    /// Called with the fixed step zero or more times on each frame (before [`Event::Update`]),
+    /// so logic of the game could run at the fixed rate independently of frame rate.
+    ///
+    /// Called only if fixed timestep is enabled
+    /// (see [`Config::with_fixed_timestep`](crate::config::Config::with_fixed_timestep)).
+    ///
+    FixedUpdate(DeltaTime),
+
     /// Called when game window needs updating.
     Update(DeltaTime),
 
     /// Called when game UI needs updating.
     UI(CtxRef),
 
+    /// Called when a file was dropped into game window.
+    ///
+    /// If multiple files were dropped at once,
+    /// one event is delivered for each file in order.
+    DroppedFile(PathBuf),
+
+    /// Called when a file is being hovered over game window.
+    ///
+    /// If multiple files are hovered at once,
+    /// one event is delivered for each file in order.
+    HoveredFile(PathBuf),
+
+    /// Called when hovered files were moved out of game window
+    /// or drop operation was cancelled.
+    HoveredFileCancelled,
+
+    /// Called when loading of the asset in background was finished
+    /// (see [`Application::enable_assets`](crate::app::Application::enable_assets)).
+    Asset(AssetEvent),
+
+    /// Called when something happened with the secondary window
+    /// which was created by [`WindowManager`].
+    Window { id: WindowId, event: WindowEvent },
+
+    /// Called when custom event was sent by [`EventProxy`] (for example, from another thread).
+    Custom(CustomEvent),
+
     /// Called when game window will be destroyed.
     Destroyed,
 }
 
+/// Event of the secondary window which was created by [`WindowManager`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowEvent {
+    /// Called when the window was created and shown.
+    Created,
+
+    /// Called when the window was resized.
+    Resized(Size),
+
+    /// Called when the user tries to close the window
+    /// (it is closed only by [`WindowManager::close`]).
+    CloseRequested,
+
+    /// Called when the window was closed by [`WindowManager::close`].
+    Closed,
+
+    /// Called when the window failed to be created, so it will never be shown.
+    Failed,
+}
+
 /// Size of game engine window.
-#[derive(Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
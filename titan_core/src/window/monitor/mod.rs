@@ -0,0 +1,234 @@
+//! Monitor utilities of game engine window.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use thiserror::Error;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::{MonitorHandle, VideoMode as WinitVideoMode};
+use winit::window::{Fullscreen, Window};
+
+use super::Size;
+
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum MonitorError {
+    #[error("monitor {0:?} was not found")]
+    NotFound(MonitorId),
+
+    #[error("window is not on any monitor")]
+    NoMonitor,
+
+    #[error("video mode {0:?} is not supported by the monitor")]
+    VideoModeNotSupported(VideoMode),
+}
+
+/// Identifier of the monitor.
+///
+/// Identifier is derived from name, size and position of the monitor,
+/// so it stays the same while the monitor is connected and its layout is not changed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MonitorId(u64);
+
+impl MonitorId {
+    fn new(name: Option<&str>, size: Size, position: [i32; 2]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        size.width.hash(&mut hasher);
+        size.height.hash(&mut hasher);
+        position.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// Video mode which could be used for exclusive fullscreen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    /// Resolution of the mode (in physical pixels).
+    pub size: Size,
+    /// Refresh rate of the mode (in hertz).
+    pub refresh_rate: u16,
+    /// Bit depth of the mode (in bits per pixel).
+    pub bit_depth: u16,
+}
+
+impl From<&WinitVideoMode> for VideoMode {
+    fn from(mode: &WinitVideoMode) -> Self {
+        let size: (u32, u32) = mode.size().into();
+        Self {
+            size: size.into(),
+            refresh_rate: mode.refresh_rate(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+}
+
+/// Fullscreen mode of the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FullscreenMode {
+    /// Borderless window which covers the whole monitor,
+    /// keeping its current video mode.
+    Borderless,
+    /// Exclusive fullscreen which switches the monitor into given video mode.
+    Exclusive(VideoMode),
+}
+
+impl Default for FullscreenMode {
+    fn default() -> Self {
+        Self::Borderless
+    }
+}
+
+/// Information about the monitor which is available to game window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub id: MonitorId,
+    /// Human-readable name of the monitor, if any.
+    pub name: Option<String>,
+    /// Resolution of the monitor (in physical pixels).
+    pub size: Size,
+    /// Position of top left corner of the monitor on the desktop (in physical pixels).
+    pub position: [i32; 2],
+    /// Scale factor which is used to convert physical pixels into logical ones.
+    pub scale_factor: f64,
+    /// Refresh rate of the current resolution (in hertz), if known.
+    pub refresh_rate: Option<u16>,
+    /// Video modes which are supported by the monitor.
+    pub video_modes: Vec<VideoMode>,
+}
+
+impl From<&MonitorHandle> for MonitorInfo {
+    fn from(handle: &MonitorHandle) -> Self {
+        let name = handle.name();
+        let size: (u32, u32) = handle.size().into();
+        let size = size.into();
+        let position: (i32, i32) = handle.position().into();
+        let position = [position.0, position.1];
+        let video_modes: Vec<_> = handle.video_modes().map(|mode| (&mode).into()).collect();
+        Self {
+            id: MonitorId::new(name.as_deref(), size, position),
+            refresh_rate: self::refresh_rate(&video_modes, size),
+            scale_factor: handle.scale_factor(),
+            name,
+            size,
+            position,
+            video_modes,
+        }
+    }
+}
+
+/// Best known refresh rate of the monitor with given video modes and current resolution.
+///
+/// Current refresh rate is not reported by the platform,
+/// so the highest refresh rate of the current resolution is used.
+fn refresh_rate(video_modes: &[VideoMode], size: Size) -> Option<u16> {
+    video_modes
+        .iter()
+        .filter(|mode| mode.size == size)
+        .map(|mode| mode.refresh_rate)
+        .max()
+}
+
+/// Returns information about all monitors which are available to the window.
+pub(crate) fn monitors(window: &Window) -> Vec<MonitorInfo> {
+    window
+        .available_monitors()
+        .map(|handle| MonitorInfo::from(&handle))
+        .collect()
+}
+
+/// Returns information about all monitors which are available to windows of the event loop
+/// (could be used before the window is created).
+pub(crate) fn event_loop_monitors<T>(event_loop: &EventLoopWindowTarget<T>) -> Vec<MonitorInfo> {
+    event_loop
+        .available_monitors()
+        .map(|handle| MonitorInfo::from(&handle))
+        .collect()
+}
+
+/// Makes the window fullscreen on the monitor with given identifier.
+///
+/// Borderless fullscreen is used if no video mode was provided.
+pub(crate) fn set_fullscreen_on(
+    window: &Window,
+    monitor_id: MonitorId,
+    video_mode: Option<VideoMode>,
+) -> Result<(), MonitorError> {
+    let mode = match video_mode {
+        Some(video_mode) => FullscreenMode::Exclusive(video_mode),
+        None => FullscreenMode::Borderless,
+    };
+    self::set_fullscreen(window, Some(monitor_id), mode)
+}
+
+/// Makes the window fullscreen on the monitor with given identifier,
+/// or on the current monitor of the window if no identifier was provided.
+pub(crate) fn set_fullscreen(
+    window: &Window,
+    monitor_id: Option<MonitorId>,
+    mode: FullscreenMode,
+) -> Result<(), MonitorError> {
+    let handle = match monitor_id {
+        Some(monitor_id) => window
+            .available_monitors()
+            .find(|handle| MonitorInfo::from(handle).id == monitor_id)
+            .ok_or(MonitorError::NotFound(monitor_id))?,
+        None => window
+            .current_monitor()
+            .or_else(|| window.primary_monitor())
+            .ok_or(MonitorError::NoMonitor)?,
+    };
+    let fullscreen = match mode {
+        FullscreenMode::Borderless => Fullscreen::Borderless(Some(handle)),
+        FullscreenMode::Exclusive(video_mode) => {
+            let mode = handle
+                .video_modes()
+                .find(|mode| VideoMode::from(mode) == video_mode)
+                .ok_or(MonitorError::VideoModeNotSupported(video_mode))?;
+            Fullscreen::Exclusive(mode)
+        }
+    };
+    window.set_fullscreen(Some(fullscreen));
+    Ok(())
+}
+
+/// Returns fullscreen mode of the window, or `None` if it is not fullscreen.
+pub(crate) fn fullscreen_mode(window: &Window) -> Option<FullscreenMode> {
+    window.fullscreen().map(|fullscreen| match fullscreen {
+        Fullscreen::Borderless(_) => FullscreenMode::Borderless,
+        Fullscreen::Exclusive(mode) => FullscreenMode::Exclusive(VideoMode::from(&mode)),
+    })
+}
+
+/// Returns fullscreen of the window which is created on the primary monitor of the event loop.
+///
+/// Borderless fullscreen is used if the video mode of exclusive fullscreen
+/// is not supported by the primary monitor.
+pub(crate) fn primary_fullscreen<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    mode: FullscreenMode,
+) -> Fullscreen {
+    let handle = event_loop.primary_monitor();
+    let video_mode = match mode {
+        FullscreenMode::Borderless => None,
+        FullscreenMode::Exclusive(video_mode) => {
+            let mode = handle.as_ref().and_then(|handle| {
+                handle
+                    .video_modes()
+                    .find(|mode| VideoMode::from(mode) == video_mode)
+            });
+            if mode.is_none() {
+                log::warn!(
+                    "{}, borderless fullscreen is used",
+                    MonitorError::VideoModeNotSupported(video_mode),
+                );
+            }
+            mode
+        }
+    };
+    match video_mode {
+        Some(mode) => Fullscreen::Exclusive(mode),
+        None => Fullscreen::Borderless(handle),
+    }
+}
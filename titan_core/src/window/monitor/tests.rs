@@ -0,0 +1,35 @@
+#![cfg(test)]
+
+use super::*;
+
+fn video_mode(width: u32, height: u32, refresh_rate: u16) -> VideoMode {
+    VideoMode {
+        size: Size::new(width, height),
+        refresh_rate,
+        bit_depth: 32,
+    }
+}
+
+#[test]
+fn test_monitor_id() {
+    let size = Size::new(1920, 1080);
+    let id = MonitorId::new(Some("DP-1"), size, [0, 0]);
+    assert_eq!(id, MonitorId::new(Some("DP-1"), size, [0, 0]));
+    // Identical monitors are distinguished by their position.
+    assert_ne!(id, MonitorId::new(Some("DP-1"), size, [1920, 0]));
+    assert_ne!(id, MonitorId::new(Some("HDMI-1"), size, [0, 0]));
+    assert_ne!(id, MonitorId::new(None, size, [0, 0]));
+}
+
+#[test]
+fn test_refresh_rate() {
+    let video_modes = [
+        video_mode(1920, 1080, 60),
+        video_mode(1920, 1080, 144),
+        video_mode(2560, 1440, 165),
+    ];
+    let size = Size::new(1920, 1080);
+    assert_eq!(refresh_rate(&video_modes, size), Some(144));
+    let size = Size::new(1280, 720);
+    assert_eq!(refresh_rate(&video_modes, size), None);
+}
@@ -0,0 +1,87 @@
+//! Custom events which are sent into the main loop from other threads.
+//!
+//! Events sent by [`EventProxy`] are queued and delivered to the callback of the application
+//! as [`Event::Custom`](super::Event::Custom) in order of sending. The main loop is woken up
+//! by each event, so it is delivered even if the loop is waiting for window events.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use thiserror::Error;
+use winit::event_loop::EventLoopProxy;
+
+mod tests;
+
+/// Custom event of any type which was sent by [`EventProxy`].
+pub struct CustomEvent(Box<dyn Any + Send>);
+
+impl CustomEvent {
+    /// Returns `true` if the event has type `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    /// Returns reference to the event if it has type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Takes the event if it has type `T`, otherwise returns it back.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        self.0.downcast().map(|event| *event).map_err(Self)
+    }
+}
+
+impl fmt::Debug for CustomEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomEvent").finish_non_exhaustive()
+    }
+}
+
+/// Error that can happen when custom event is sent.
+#[derive(Debug, Error)]
+pub enum EventProxyError {
+    #[error("application was destroyed, so the event could not be delivered")]
+    Closed(CustomEvent),
+}
+
+/// Handle which sends custom events into the main loop of the application.
+///
+/// Handle could be cloned and moved into other threads.
+#[derive(Debug, Clone)]
+pub struct EventProxy {
+    sender: Sender<CustomEvent>,
+    /// Proxy which wakes up the main loop, if it is driven by the platform.
+    waker: Option<EventLoopProxy<()>>,
+}
+
+impl EventProxy {
+    /// Creates proxy and receiver of its events.
+    pub(crate) fn new(waker: Option<EventLoopProxy<()>>) -> (Self, Receiver<CustomEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self { sender, waker }, receiver)
+    }
+
+    /// Sends custom event which is delivered to the callback of the application
+    /// as [`Event::Custom`](super::Event::Custom).
+    ///
+    /// # Errors
+    ///
+    /// Error with the event is returned if the application was already destroyed.
+    ///
+    pub fn send<T>(&self, event: T) -> Result<(), EventProxyError>
+    where
+        T: Any + Send,
+    {
+        let event = CustomEvent(Box::new(event));
+        self.sender
+            .send(event)
+            .map_err(|error| EventProxyError::Closed(error.0))?;
+        if let Some(waker) = &self.waker {
+            // Event is already queued, so it is fine if the loop has just exited.
+            let _ = waker.send_event(());
+        }
+        Ok(())
+    }
+}
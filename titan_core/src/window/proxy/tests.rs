@@ -0,0 +1,43 @@
+#![cfg(test)]
+
+use std::thread;
+
+use super::*;
+
+#[test]
+fn test_downcast() {
+    let event = CustomEvent(Box::new(42_u32));
+    assert!(event.is::<u32>());
+    assert!(!event.is::<i32>());
+    assert_eq!(event.downcast_ref::<u32>(), Some(&42));
+
+    let event = event.downcast::<String>().unwrap_err();
+    assert_eq!(event.downcast::<u32>().unwrap(), 42);
+}
+
+#[test]
+fn test_send_from_threads() {
+    let (proxy, receiver) = EventProxy::new(None);
+    let handles: Vec<_> = (0..4_u32)
+        .map(|index| {
+            let proxy = proxy.clone();
+            thread::spawn(move || proxy.send(index).unwrap())
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut received: Vec<_> = receiver
+        .try_iter()
+        .map(|event| event.downcast::<u32>().unwrap())
+        .collect();
+    received.sort_unstable();
+    assert_eq!(received, [0, 1, 2, 3]);
+
+    // Event is returned back if nobody could receive it.
+    drop(receiver);
+    let error = proxy.send("late").unwrap_err();
+    let EventProxyError::Closed(event) = error;
+    assert_eq!(event.downcast_ref::<&str>(), Some(&"late"));
+}
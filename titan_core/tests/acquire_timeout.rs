@@ -0,0 +1,64 @@
+//! Integration test of frame skipping when swapchain image acquisition times out.
+//!
+//! Timeouts are injected with the `fault-injection` feature:
+//! first a few of them are recovered from, then too many of them stop the main loop with an error.
+//!
+//! Test needs a display and Vulkan device, so it is skipped unless
+//! `TITAN_WINDOWED_TESTS` environment variable is set.
+//! It runs without test harness because event loop must be created on the main thread.
+
+use titan_core::graphics::error::RenderError;
+use titan_core::prelude::*;
+
+/// Count of updates after which recoverable timeouts are injected.
+const WARMUP: u32 = 3;
+
+fn main() {
+    if std::env::var_os("TITAN_WINDOWED_TESTS").is_none() {
+        println!("acquire timeout test skipped: set TITAN_WINDOWED_TESTS to run it");
+        return;
+    }
+
+    let policy = TimeoutPolicy {
+        recreate_after: 2,
+        max_recreations: 1,
+        ..TimeoutPolicy::default()
+    };
+    let version = Version::new(0, 1, 0);
+    let config =
+        Config::new("acquire_timeout".to_string(), version, true).with_timeout_policy(policy);
+    let application = titan_core::init(config).unwrap();
+    let faults = application.fault_injector();
+
+    let mut updates = 0;
+    let mut recovered_at = None;
+    let callback_faults = faults.clone();
+    let report = application
+        .run_until_exit(move |event| {
+            if let Event::Update(_) = event {
+                updates += 1;
+                // Events are still pumped while frames are skipped.
+                if updates == WARMUP {
+                    callback_faults.inject_acquire_timeouts(3);
+                }
+                let pending = callback_faults.pending_acquire_timeouts();
+                if updates > WARMUP && recovered_at.is_none() && pending == 0 {
+                    recovered_at = Some(updates);
+                }
+                // Skip, recreate, skip, fail: the second recreation is not allowed.
+                if recovered_at.map_or(false, |recovered_at| updates == recovered_at + WARMUP) {
+                    callback_faults.inject_acquire_timeouts(100);
+                }
+            }
+        })
+        .unwrap();
+
+    match report.cause {
+        ExitCause::Error(RenderError::SwapchainStalled(recreations)) => {
+            assert_eq!(recreations, policy.max_recreations)
+        }
+        cause => panic!("unexpected exit cause: {:?}", cause),
+    }
+    assert!(faults.pending_acquire_timeouts() > 0);
+    println!("acquire timeout test passed");
+}
@@ -0,0 +1,60 @@
+//! Integration test of engine shutdown which renders a few frames in the window
+//! and then initializes the engine once again.
+//!
+//! Test needs a display and Vulkan device, so it is skipped unless
+//! `TITAN_WINDOWED_TESTS` environment variable is set.
+//! It runs without test harness because event loop must be created on the main thread.
+
+use titan_core::app::AppCreationError;
+use titan_core::prelude::*;
+
+/// Count of frames which are rendered before exit.
+const FRAMES: u32 = 3;
+
+fn main() {
+    if std::env::var_os("TITAN_WINDOWED_TESTS").is_none() {
+        println!("shutdown test skipped: set TITAN_WINDOWED_TESTS to run it");
+        return;
+    }
+
+    // Application could be created again after the previous one was torn down.
+    for _ in 0..2 {
+        run_once();
+    }
+    println!("shutdown test passed");
+}
+
+/// Creates the application, renders a few frames and checks the exit report.
+fn run_once() {
+    let version = Version::new(0, 1, 0);
+    let config = Config::new("shutdown".to_string(), version.clone(), true);
+    let mut application = titan_core::init(config).unwrap();
+    // Only one instance could exist at a time.
+    let config = Config::new("shutdown".to_string(), version, true);
+    assert!(matches!(
+        titan_core::init(config),
+        Err(AppCreationError::Initialized)
+    ));
+
+    // Released image must not be reported as a leak.
+    let image = image::RgbaImage::new(4, 4);
+    let texture_id = application.register_ui_image(&image).unwrap();
+    application.unregister_ui_image(texture_id);
+
+    let exit = application.exit_handle();
+    let mut frames = 0;
+    let report = application
+        .run_until_exit(|event| {
+            if let Event::Update(_) = event {
+                frames += 1;
+                if frames == FRAMES {
+                    exit.exit();
+                }
+            }
+        })
+        .unwrap();
+
+    assert_eq!(frames, FRAMES);
+    assert!(matches!(report.cause, ExitCause::Requested));
+    assert!(report.is_clean(), "leaks at exit: {:?}", report.leaks);
+}
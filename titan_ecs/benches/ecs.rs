@@ -0,0 +1,127 @@
+//! Benchmarks of component storage and entity management of ECS.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use titan_ecs::{Entity, World};
+
+const COUNT: usize = 100_000;
+
+#[derive(Debug, Copy, Clone)]
+struct Position([f32; 3]);
+
+#[derive(Debug, Copy, Clone)]
+struct Velocity([f32; 3]);
+
+fn spawn_entities(world: &mut World, count: usize) -> Vec<Entity> {
+    (0..count).map(|_| world.spawn()).collect()
+}
+
+/// Creates world where every entity has position and every second entity has velocity.
+fn populated_world() -> World {
+    let mut world = World::new();
+    for (index, entity) in spawn_entities(&mut world, COUNT).into_iter().enumerate() {
+        world.insert(entity, Position([index as f32, 0.0, 0.0]));
+        if index % 2 == 0 {
+            world.insert(entity, Velocity([1.0, 0.0, 0.0]));
+        }
+    }
+    world
+}
+
+fn insert_remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("components");
+    group.sample_size(20);
+
+    group.bench_function("insert 100k", |b| {
+        b.iter_batched(
+            || {
+                let mut world = World::new();
+                let entities = spawn_entities(&mut world, COUNT);
+                (world, entities)
+            },
+            |(mut world, entities)| {
+                for entity in entities {
+                    world.insert(entity, Position([0.0; 3]));
+                }
+                world
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("remove 100k", |b| {
+        b.iter_batched(
+            || {
+                let world = populated_world();
+                let entities: Vec<_> = world.entities().collect();
+                (world, entities)
+            },
+            |(mut world, entities)| {
+                for entity in entities {
+                    black_box(world.remove::<Position>(entity));
+                }
+                world
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query");
+    let mut world = populated_world();
+
+    group.bench_function("single component", |b| {
+        b.iter(|| {
+            let sum: f32 = world
+                .query::<Position>()
+                .map(|(_, position)| position.0[0])
+                .sum();
+            black_box(sum)
+        })
+    });
+
+    group.bench_function("two component join", |b| {
+        b.iter(|| {
+            let sum: f32 = world
+                .query::<Velocity>()
+                .filter_map(|(entity, velocity)| {
+                    let position = world.get::<Position>(entity)?;
+                    Some(position.0[0] + velocity.0[0])
+                })
+                .sum();
+            black_box(sum)
+        })
+    });
+
+    group.bench_function("single component mutable", |b| {
+        b.iter(|| {
+            for (_, position) in world.query_mut::<Position>() {
+                position.0[0] += 1.0;
+            }
+        })
+    });
+    group.finish();
+}
+
+fn spawn_despawn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("entities");
+
+    group.bench_function("spawn despawn loop", |b| {
+        let mut world = World::new();
+        b.iter(|| {
+            let entities = spawn_entities(&mut world, 1000);
+            for &entity in &entities {
+                world.insert(entity, Position([0.0; 3]));
+            }
+            for entity in entities {
+                world.despawn(entity);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, insert_remove, iterate, spawn_despawn);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+//! Prints markdown table with results of all benchmarks which were run before.
+//!
+//! Run it after other benchmarks: `cargo bench --bench summary`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+/// Result of one benchmark.
+struct Estimate {
+    id: String,
+    mean: f64,
+    std_dev: f64,
+}
+
+fn criterion_dir() -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../target"));
+    target.join("criterion")
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Recursively collects results from `new` directories created by criterion.
+fn collect(dir: &Path, estimates: &mut Vec<Estimate>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name() == Some("new".as_ref()) {
+            let benchmark = read_json(&path.join("benchmark.json"));
+            let estimate = read_json(&path.join("estimates.json"));
+            if let (Some(benchmark), Some(estimate)) = (benchmark, estimate) {
+                let id = benchmark["full_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let mean = estimate["mean"]["point_estimate"]
+                    .as_f64()
+                    .unwrap_or_default();
+                let std_dev = estimate["std_dev"]["point_estimate"]
+                    .as_f64()
+                    .unwrap_or_default();
+                estimates.push(Estimate { id, mean, std_dev });
+            }
+            continue;
+        }
+        collect(&path, estimates);
+    }
+}
+
+/// Formats duration given in nanoseconds with suitable unit.
+fn format_time(nanos: f64) -> String {
+    match nanos {
+        nanos if nanos >= 1e9 => format!("{:.3} s", nanos / 1e9),
+        nanos if nanos >= 1e6 => format!("{:.3} ms", nanos / 1e6),
+        nanos if nanos >= 1e3 => format!("{:.3} µs", nanos / 1e3),
+        nanos => format!("{:.1} ns", nanos),
+    }
+}
+
+fn main() {
+    let dir = criterion_dir();
+    let mut estimates = Vec::new();
+    self::collect(&dir, &mut estimates);
+    if estimates.is_empty() {
+        eprintln!("no benchmark results found in {}", dir.display());
+        return;
+    }
+    estimates.sort_by(|a, b| a.id.cmp(&b.id));
+
+    println!("| Benchmark | Mean | Std. dev. |");
+    println!("|---|---:|---:|");
+    for estimate in estimates {
+        println!(
+            "| {} | {} | {} |",
+            estimate.id,
+            self::format_time(estimate.mean),
+            self::format_time(estimate.std_dev),
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Utilities for deferred changes of the world made by *systems* of ECS.
+
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{Component, Entity, World};
+
+mod tests;
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+type InsertFn = Box<dyn FnOnce(&mut World, Entity) + Send>;
+
+/// Components of the entity which is spawned later.
+///
+/// ```
+/// # use titan_ecs::{EntityBuilder, World};
+/// struct Position(f32);
+/// struct Velocity(f32);
+///
+/// let mut world = World::new();
+/// let entity = EntityBuilder::new()
+///     .with(Position(0.0))
+///     .with(Velocity(1.0))
+///     .build(&mut world);
+/// assert!(world.attached::<Velocity>(entity));
+/// ```
+///
+#[derive(Default)]
+pub struct EntityBuilder {
+    components: Vec<InsertFn>,
+}
+
+impl EntityBuilder {
+    /// Creates builder of the entity without any components.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds component of type `T` which will be attached to the entity.
+    pub fn with<T>(mut self, component: T) -> Self
+    where
+        T: Component,
+    {
+        self.components.push(Box::new(move |world, entity| {
+            world.insert(entity, component);
+        }));
+        self
+    }
+
+    /// Spawns new entity with all components of the builder.
+    pub fn build(self, world: &mut World) -> Entity {
+        let entity = world.spawn();
+        for insert in self.components {
+            insert(world, entity);
+        }
+        entity
+    }
+}
+
+impl fmt::Debug for EntityBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntityBuilder")
+            .field("components", &self.components.len())
+            .finish()
+    }
+}
+
+/// Queue of changes of the world which are applied later (see [`World::apply_commands`]).
+///
+/// Systems could not change the world while they iterate over its components,
+/// so they queue changes instead. Each [`World`] has its own queue, handles to which
+/// are retrieved by [`World::commands`]: they could be cloned, moved into systems
+/// and used from several threads at once. The [schedule](crate::Schedule) applies
+/// queued commands after each exclusive system and after each batch of parallel systems.
+///
+/// Commands are applied in order of their addition. Components are not attached
+/// to entities which were despawned before the command is applied.
+///
+#[derive(Clone, Default)]
+pub struct Commands {
+    queue: Arc<Mutex<Vec<Command>>>,
+}
+
+impl Commands {
+    /// Queues spawn of new entity with all components of the builder.
+    pub fn spawn(&self, builder: EntityBuilder) {
+        self.add(move |world| {
+            builder.build(world);
+        })
+    }
+
+    /// Queues destruction of the entity with all components attached to it
+    /// (see [`World::despawn`]).
+    pub fn despawn(&self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn(entity);
+        })
+    }
+
+    /// Queues destruction of the entity with all of its descendants
+    /// (see [`World::despawn_recursive`]).
+    pub fn despawn_recursive(&self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn_recursive(entity);
+        })
+    }
+
+    /// Queues insertion of component of type `T` and its attachment to the entity.
+    pub fn insert<T>(&self, entity: Entity, component: T)
+    where
+        T: Component,
+    {
+        self.add(move |world| {
+            if world.contains(entity) {
+                world.insert(entity, component);
+            }
+        })
+    }
+
+    /// Queues removal of component of type `T` from the entity.
+    pub fn remove<T>(&self, entity: Entity)
+    where
+        T: Component,
+    {
+        self.add(move |world| {
+            world.remove::<T>(entity);
+        })
+    }
+
+    /// Queues custom command which changes the world.
+    pub fn add<F>(&self, command: F)
+    where
+        F: FnOnce(&mut World) + Send + 'static,
+    {
+        self.lock().push(Box::new(command))
+    }
+
+    /// Count of queued commands.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if there are no queued commands.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Takes all queued commands, leaving the queue empty.
+    fn take(&self) -> Vec<Command> {
+        mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Command>> {
+        // Commands which were queued before some system panicked are still valid.
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Commands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Commands")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl World {
+    /// Handle to the queue of commands of this world.
+    pub fn commands(&self) -> Commands {
+        self.commands.clone()
+    }
+
+    /// Applies all queued commands to this world in order of their addition,
+    /// including commands which were queued by applied ones.
+    ///
+    /// Returns count of applied commands.
+    ///
+    pub fn apply_commands(&mut self) -> usize {
+        let mut count = 0;
+        loop {
+            let commands = self.commands.take();
+            if commands.is_empty() {
+                return count;
+            }
+            count += commands.len();
+            for command in commands {
+                command(self);
+            }
+        }
+    }
+}
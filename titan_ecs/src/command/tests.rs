@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use titan_tasks::TaskPool;
+
+use crate::{EntityBuilder, Query, Schedule, SystemStage, World};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Health(u32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Dead;
+
+#[test]
+fn test_commands() {
+    let mut world = World::new();
+    let first = world.spawn();
+    let second = world.spawn();
+    world.insert(first, Health(10));
+
+    let commands = world.commands();
+    commands.insert(first, Dead);
+    commands.remove::<Health>(first);
+    commands.despawn(second);
+    // Components are not attached to despawned entities.
+    commands.insert(second, Health(5));
+    commands.spawn(EntityBuilder::new().with(Health(1)).with(Dead));
+    commands.add(move |world| {
+        world.commands().insert(first, Health(3));
+    });
+    assert_eq!(commands.len(), 6);
+    assert_eq!(world.len(), 2);
+
+    assert_eq!(world.apply_commands(), 7);
+    assert!(commands.is_empty());
+    assert!(!world.contains(second));
+    assert_eq!(world.get::<Health>(first), Some(&Health(3)));
+    let dead: Vec<_> = world.query::<Dead>().map(|(entity, _)| entity).collect();
+    assert_eq!(dead.len(), 2);
+    let spawned = dead.into_iter().find(|&entity| entity != first).unwrap();
+    assert_eq!(world.get::<Health>(spawned), Some(&Health(1)));
+}
+
+#[test]
+fn test_schedule_commands() {
+    let mut world = World::new();
+    let entities: Vec<_> = (0..4)
+        .map(|health| EntityBuilder::new().with(Health(health)).build(&mut world))
+        .collect();
+    world.insert_resource(TaskPool::new(2, 1));
+
+    // Systems of the stage despawn entities while they iterate over them.
+    let mut stage = SystemStage::new();
+    let commands = world.commands();
+    stage.add_system(
+        Query::<(&mut Health,)>::new().unwrap(),
+        move |entity, (health,)| match health.0.checked_sub(1) {
+            Some(left) => health.0 = left,
+            None => commands.despawn(entity),
+        },
+    );
+    let mut schedule = Schedule::new();
+    schedule.add_stage(stage);
+    schedule.add_system(|context| {
+        // Entities despawned by the stage are already gone.
+        let alive = context.world().len() as u32;
+        let commands = context.world().commands();
+        commands.spawn(EntityBuilder::new().with(Health(alive)));
+    });
+
+    schedule.run(&mut world);
+    assert!(!world.contains(entities[0]));
+    assert_eq!(world.len(), 4);
+    let mut healths: Vec<_> = world
+        .query::<Health>()
+        .map(|(_, health)| health.0)
+        .collect();
+    healths.sort_unstable();
+    assert_eq!(healths, [0, 1, 2, 3]);
+}
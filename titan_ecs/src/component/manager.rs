@@ -1,15 +1,15 @@
 //! Utilities for managing component storages.
 
-use std::any::{Any, TypeId};
+use std::any::TypeId;
 use std::collections::HashMap;
 
-use super::{super::Entity, Component, ComponentStorage};
+use super::{super::Entity, AnyStorage, Component, ComponentStorage, Tick};
 
 /// Manager of all components of ECS.
 #[derive(Default)]
 #[repr(transparent)]
 pub struct ComponentManager {
-    _storages: HashMap<TypeId, Box<dyn Any>>,
+    _storages: HashMap<TypeId, Box<dyn AnyStorage>>,
 }
 
 impl ComponentManager {
@@ -25,7 +25,7 @@ pub fn new() -> Self {
     ///
     /// Returns previously attached component, if any.
     ///
-    pub fn insert<T>(&mut self, entity: Entity, component: T) -> Option<T>
+    pub fn insert<T>(&mut self, entity: Entity, component: T, tick: Tick) -> Option<T>
     where
         T: Component,
     {
@@ -33,6 +33,7 @@ pub fn insert<T>(&mut self, entity: Entity, component: T) -> Option<T>
             Some(storage) => storage,
             None => self.create_storage(),
         };
+        storage.set_change_tick(tick);
         storage.insert(entity, component)
     }
 
@@ -48,6 +49,34 @@ pub fn remove<T>(&mut self, entity: Entity) -> Option<T>
         storage.remove(entity)
     }
 
+    /// Removes all components attached to the entity.
+    pub fn remove_all(&mut self, entity: Entity) {
+        for storage in self._storages.values_mut() {
+            storage.detach(entity);
+        }
+    }
+
+    /// Clamps ticks of all components relative to `this_run` (see [`Tick::check`]).
+    pub fn check_ticks(&mut self, this_run: Tick) {
+        for storage in self._storages.values_mut() {
+            storage.check_ticks(this_run);
+        }
+    }
+
+    /// Returns iterator over type-erased storages of all component types.
+    pub fn storages(&self) -> impl Iterator<Item = (TypeId, &dyn AnyStorage)> {
+        self._storages
+            .iter()
+            .map(|(&typeid, storage)| (typeid, storage.as_ref()))
+    }
+
+    /// Returns iterator over mutable type-erased storages of all component types.
+    pub fn storages_mut(&mut self) -> impl Iterator<Item = (TypeId, &mut dyn AnyStorage)> {
+        self._storages
+            .iter_mut()
+            .map(|(&typeid, storage)| (typeid, storage.as_mut() as &mut dyn AnyStorage))
+    }
+
     /// Returns `true` if component of type `T` was already attached to the entity.
     pub fn attached<T>(&self, entity: Entity) -> bool
     where
@@ -67,31 +96,50 @@ pub fn get<T>(&self, entity: Entity) -> Option<&T>
         storage.get(entity)
     }
 
-    /// Retrieves a mutable reference to component of type `T` attached to the entity.
-    pub fn get_mut<T>(&mut self, entity: Entity) -> Option<&mut T>
+    /// Retrieves a mutable reference to component of type `T` attached to the entity
+    /// and marks the component as changed at given tick.
+    pub fn get_mut<T>(&mut self, entity: Entity, tick: Tick) -> Option<&mut T>
     where
         T: Component,
     {
         let storage = self.get_storage_mut::<T>()?;
+        storage.set_change_tick(tick);
         storage.get_mut(entity)
     }
 
-    fn get_storage<T>(&self) -> Option<&ComponentStorage<T>>
+    /// Retrieves mutable references to type-erased storages of several component types at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the same type is given more than once.
+    ///
+    pub fn get_storages_mut<const N: usize>(
+        &mut self,
+        typeids: [&TypeId; N],
+    ) -> [Option<&mut dyn AnyStorage>; N] {
+        self._storages
+            .get_disjoint_mut(typeids)
+            .map(|storage| storage.map(|boxed| boxed.as_mut() as &mut dyn AnyStorage))
+    }
+
+    /// Retrieves an immutable reference to storage of components of type `T`.
+    pub fn get_storage<T>(&self) -> Option<&ComponentStorage<T>>
     where
         T: Component,
     {
         let typeid = TypeId::of::<T>();
         let boxed = self._storages.get(&typeid)?;
-        Some(boxed.downcast_ref().expect("downcast error"))
+        Some(boxed.as_any().downcast_ref().expect("downcast error"))
     }
 
-    fn get_storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
+    /// Retrieves a mutable reference to storage of components of type `T`.
+    pub fn get_storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
     where
         T: Component,
     {
         let typeid = TypeId::of::<T>();
         let boxed = self._storages.get_mut(&typeid)?;
-        Some(boxed.downcast_mut().expect("downcast error"))
+        Some(boxed.as_any_mut().downcast_mut().expect("downcast error"))
     }
 
     fn create_storage<T>(&mut self) -> &mut ComponentStorage<T>
@@ -102,6 +150,6 @@ fn create_storage<T>(&mut self) -> &mut ComponentStorage<T>
         let boxed = Box::new(ComponentStorage::<T>::new());
         self._storages.insert(typeid, boxed);
         let boxed = self._storages.get_mut(&typeid).unwrap();
-        boxed.downcast_mut().expect("downcast error")
+        boxed.as_any_mut().downcast_mut().expect("downcast error")
     }
 }
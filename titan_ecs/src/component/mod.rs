@@ -6,10 +6,12 @@
 
 pub use manager::*;
 pub use storage::*;
+pub use tick::*;
 
 mod manager;
 mod storage;
 mod tests;
+mod tick;
 
 /// Objects of this trait represent *component* of ECS.
 ///
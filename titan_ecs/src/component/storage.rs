@@ -1,10 +1,11 @@
 //! Utilities for different types of storages for **components** of ECS.
 
+use std::any::Any;
 use std::ops::{Index, IndexMut};
 
 use slotmap::{hop::IntoIter as IntoIterHop, HopSlotMap, SecondaryMap};
 
-use super::{super::Entity, Component, ComponentID};
+use super::{super::Entity, Component, ComponentID, ComponentTicks, Tick};
 
 /// Storage for statically typed components of ECS.
 #[derive(Default)]
@@ -16,6 +17,10 @@ pub struct ComponentStorage<T>
     components: HopSlotMap<ComponentID, T>,
     entity_to_component: SecondaryMap<Entity, ComponentID>,
     component_to_entity: SecondaryMap<ComponentID, Entity>,
+    /// Ticks of addition and last change of components.
+    ticks: SecondaryMap<Entity, ComponentTicks>,
+    /// Tick which is recorded on addition or mutable access of components.
+    change_tick: Tick,
 }
 
 impl<T> ComponentStorage<T>
@@ -28,9 +33,16 @@ pub fn new() -> Self {
             components: HopSlotMap::with_key(),
             entity_to_component: SecondaryMap::new(),
             component_to_entity: SecondaryMap::new(),
+            ticks: SecondaryMap::new(),
+            change_tick: Tick::default(),
         }
     }
 
+    /// Sets tick which will be recorded on addition or mutable access of components.
+    pub fn set_change_tick(&mut self, tick: Tick) {
+        self.change_tick = tick;
+    }
+
     /// Inserts component and attaches it to the entity.
     /// If component was already attached, it will be replaced by value.
     ///
@@ -44,6 +56,8 @@ pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
         let id = self.components.insert(component);
         self.component_to_entity.insert(id, entity);
         self.entity_to_component.insert(entity, id);
+        self.ticks
+            .insert(entity, ComponentTicks::new(self.change_tick));
         None
     }
 
@@ -55,6 +69,7 @@ pub fn remove(&mut self, entity: Entity) -> Option<T> {
         let id = *self.entity_to_component.get(entity)?;
         self.entity_to_component.remove(entity);
         self.component_to_entity.remove(id);
+        self.ticks.remove(entity);
         self.components.remove(id)
     }
 
@@ -63,18 +78,35 @@ pub fn attached(&self, entity: Entity) -> bool {
         self.entity_to_component.contains_key(entity)
     }
 
+    /// Count of components in the storage.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns `true` if there are no components in the storage.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
     /// Retrieves an immutable reference to component attached to the entity.
     pub fn get(&self, entity: Entity) -> Option<&T> {
         let id = *self.entity_to_component.get(entity)?;
         self.components.get(id)
     }
 
-    /// Retrieves a mutable reference to component attached to the entity.
+    /// Retrieves a mutable reference to component attached to the entity
+    /// and marks the component as changed.
     pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
         let id = *self.entity_to_component.get(entity)?;
+        self.ticks[entity].set_changed(self.change_tick);
         self.components.get_mut(id)
     }
 
+    /// Retrieves ticks of addition and last change of component attached to the entity.
+    pub fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.ticks.get(entity).copied()
+    }
+
     /// Returns immutable iterator over all components with their entities.
     pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
         let component_to_entity = &self.component_to_entity;
@@ -84,11 +116,34 @@ pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
     }
 
     /// Returns mutable iterator over all components with their entities.
+    ///
+    /// Each component is marked as changed when iterator yields it.
+    ///
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        let change_tick = self.change_tick;
         let component_to_entity = &self.component_to_entity;
-        self.components
-            .iter_mut()
-            .map(move |(id, component)| (component_to_entity[id], component))
+        let ticks = &mut self.ticks;
+        self.components.iter_mut().map(move |(id, component)| {
+            let entity = component_to_entity[id];
+            ticks[entity].set_changed(change_tick);
+            (entity, component)
+        })
+    }
+
+    /// Returns iterator over components which were added after `last_run`.
+    pub fn iter_added(&self, last_run: Tick, this_run: Tick) -> impl Iterator<Item = (Entity, &T)> {
+        self.iter()
+            .filter(move |(entity, _)| self.ticks[*entity].is_added(last_run, this_run))
+    }
+
+    /// Returns iterator over components which were added or changed after `last_run`.
+    pub fn iter_changed(
+        &self,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> impl Iterator<Item = (Entity, &T)> {
+        self.iter()
+            .filter(move |(entity, _)| self.ticks[*entity].is_changed(last_run, this_run))
     }
 
     /// Returns iterator over all entities which have component of this type.
@@ -101,12 +156,79 @@ pub fn components(&self) -> impl Iterator<Item = &T> {
         self.components.values()
     }
 
-    /// Returns mutable iterator over all components.
+    /// Returns mutable iterator over all components and marks all of them as changed.
     pub fn components_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        for ticks in self.ticks.values_mut() {
+            ticks.set_changed(self.change_tick);
+        }
         self.components.values_mut()
     }
 }
 
+/// Type-erased storage for components of ECS.
+pub trait AnyStorage: Any + Send + Sync {
+    /// Casts the storage into [`Any`] to downcast it later.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Casts the storage into mutable [`Any`] to downcast it later.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Name of component type of the storage.
+    fn type_name(&self) -> &'static str;
+
+    /// Count of components in the storage.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no components in the storage.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns iterator over all entities which have component of this type.
+    fn entities(&self) -> Box<dyn Iterator<Item = Entity> + '_>;
+
+    /// Removes component (if any) and detaches it from the entity.
+    fn detach(&mut self, entity: Entity);
+
+    /// Clamps ticks of all components relative to `this_run` (see [`Tick::check`]).
+    fn check_ticks(&mut self, this_run: Tick);
+}
+
+impl<T> AnyStorage for ComponentStorage<T>
+where
+    T: Component,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn entities(&self) -> Box<dyn Iterator<Item = Entity> + '_> {
+        Box::new(ComponentStorage::entities(self))
+    }
+
+    fn detach(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn check_ticks(&mut self, this_run: Tick) {
+        for ticks in self.ticks.values_mut() {
+            ticks.check(this_run);
+        }
+    }
+}
+
 pub struct IntoIter<T>
 where
     T: Component,
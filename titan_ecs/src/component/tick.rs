@@ -0,0 +1,110 @@
+//! Utilities for change detection of **components** of ECS.
+
+/// Count of ticks after which ticks of all components must be checked
+/// (see [`Tick::check`]).
+pub const CHECK_TICK_THRESHOLD: u32 = 518_400_000;
+
+/// Maximal age of a tick which can be compared correctly.
+///
+/// Older ticks are clamped to this age by [`Tick::check`].
+///
+pub const MAX_CHANGE_AGE: u32 = u32::MAX - (2 * CHECK_TICK_THRESHOLD - 1);
+
+/// Moment of time in ECS measured in runs of systems.
+///
+/// Tick counter wraps after `u32::MAX` increments,
+/// so ticks must be compared only relative to the current one.
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Tick(u32);
+
+impl Tick {
+    /// Creates tick from the raw value.
+    pub const fn new(tick: u32) -> Self {
+        Self(tick)
+    }
+
+    /// Raw value of the tick.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if this tick happened after `last_run` and not later than `this_run`.
+    ///
+    /// Ticks are compared by their age relative to `this_run`,
+    /// so result is correct even after the counter wraps.
+    ///
+    pub fn is_newer_than(self, last_run: Tick, this_run: Tick) -> bool {
+        let age = this_run.0.wrapping_sub(self.0);
+        let last_run_age = this_run.0.wrapping_sub(last_run.0);
+        last_run_age > age
+    }
+
+    /// Clamps the age of this tick relative to `this_run` to [`MAX_CHANGE_AGE`],
+    /// so it will not be seen as new after the counter wraps.
+    pub fn check(&mut self, this_run: Tick) {
+        let age = this_run.0.wrapping_sub(self.0);
+        if age > MAX_CHANGE_AGE {
+            self.0 = this_run.0.wrapping_sub(MAX_CHANGE_AGE);
+        }
+    }
+
+    /// Oldest tick which could be compared relative to `this_run`,
+    /// so all components are seen as added by the first run of a system.
+    pub(crate) fn oldest(this_run: Tick) -> Self {
+        Self(this_run.0.wrapping_sub(MAX_CHANGE_AGE))
+    }
+
+    /// Returns tick which follows this one.
+    pub(crate) fn next(self) -> Self {
+        Self(self.0.wrapping_add(1))
+    }
+}
+
+/// Ticks of addition and last change of the component.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ComponentTicks {
+    added: Tick,
+    changed: Tick,
+}
+
+impl ComponentTicks {
+    /// Creates ticks of the component which was added at given tick.
+    pub fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// Tick when the component was added.
+    pub fn added(&self) -> Tick {
+        self.added
+    }
+
+    /// Tick when the component was changed last time.
+    pub fn changed(&self) -> Tick {
+        self.changed
+    }
+
+    /// Returns `true` if the component was added after `last_run`.
+    pub fn is_added(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.added.is_newer_than(last_run, this_run)
+    }
+
+    /// Returns `true` if the component was added or changed after `last_run`.
+    pub fn is_changed(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.changed.is_newer_than(last_run, this_run)
+    }
+
+    /// Marks the component as changed at given tick.
+    pub fn set_changed(&mut self, tick: Tick) {
+        self.changed = tick;
+    }
+
+    /// Clamps both ticks relative to `this_run` (see [`Tick::check`]).
+    pub fn check(&mut self, this_run: Tick) {
+        self.added.check(this_run);
+        self.changed.check(this_run);
+    }
+}
@@ -0,0 +1,14 @@
+//! Error types and utilities for hierarchy of entities.
+
+use thiserror::Error;
+
+use crate::Entity;
+
+/// Error that can happen when changing the hierarchy of entities.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+pub enum HierarchyError {
+    #[error("entity {0:?} is not alive")]
+    NotAlive(Entity),
+    #[error("entity {child:?} cannot be attached to itself or its descendant {parent:?}")]
+    Cycle { child: Entity, parent: Entity },
+}
@@ -0,0 +1,145 @@
+//! Utilities for hierarchy of *entities* in ECS.
+
+use std::ops::Deref;
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::{Entity, GlobalTransform, World};
+
+pub use error::HierarchyError;
+
+mod error;
+mod tests;
+
+/// Component which references the parent of the entity.
+///
+/// Should be changed only by [`World::set_parent`] and [`World::remove_parent`]
+/// to keep the hierarchy consistent.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Parent(Entity);
+
+impl Parent {
+    /// Parent entity.
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Component which stores all children of the entity.
+///
+/// Should be changed only by [`World::set_parent`] and [`World::remove_parent`]
+/// to keep the hierarchy consistent.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Children(SmallVec<[Entity; 8]>);
+
+impl Deref for Children {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl World {
+    /// Returns parent of the entity, if any.
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.get::<Parent>(entity).map(Parent::get)
+    }
+
+    /// Returns children of the entity.
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.get::<Children>(entity).map_or(&[], Deref::deref)
+    }
+
+    /// Attaches the entity to the new parent, detaching it from the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the entities is not alive
+    /// or if the parent is the entity itself or one of its descendants.
+    ///
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), HierarchyError> {
+        for entity in [child, parent] {
+            if !self.contains(entity) {
+                return Err(HierarchyError::NotAlive(entity));
+            }
+        }
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == child {
+                return Err(HierarchyError::Cycle { child, parent });
+            }
+            ancestor = self.parent(current);
+        }
+        if self.parent(child) == Some(parent) {
+            return Ok(());
+        }
+
+        self.remove_parent(child);
+        self.insert(child, Parent(parent));
+        match self.get_mut::<Children>(parent) {
+            Some(children) => children.0.push(child),
+            None => {
+                self.insert(parent, Children(smallvec![child]));
+            }
+        }
+        self.mark_hierarchy_changed(child);
+        Ok(())
+    }
+
+    /// Detaches the entity from its parent, making it a root of the hierarchy.
+    ///
+    /// Returns previous parent of the entity, if any.
+    ///
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let Parent(parent) = self.remove::<Parent>(child)?;
+        if let Some(children) = self.get_mut::<Children>(parent) {
+            children.0.retain(|entity| *entity != child);
+            if children.is_empty() {
+                self.remove::<Children>(parent);
+            }
+        }
+        self.mark_hierarchy_changed(child);
+        Some(parent)
+    }
+
+    /// Destroys the entity with all of its descendants.
+    ///
+    /// Returns `true` if entity was alive before this call.
+    ///
+    pub fn despawn_recursive(&mut self, entity: Entity) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+        self.remove_parent(entity);
+
+        let mut stack = vec![entity];
+        while let Some(current) = stack.pop() {
+            if let Some(children) = self.remove::<Children>(current) {
+                stack.extend(children.iter().copied());
+            }
+            self.despawn(current);
+        }
+        true
+    }
+
+    /// Detaches the entity from its parent and orphans all of its children.
+    pub(crate) fn detach_hierarchy(&mut self, entity: Entity) {
+        self.remove_parent(entity);
+        if let Some(children) = self.remove::<Children>(entity) {
+            for &child in children.iter() {
+                self.remove::<Parent>(child);
+                self.mark_hierarchy_changed(child);
+            }
+        }
+    }
+
+    /// Forces recalculation of global transform of the entity and its descendants.
+    fn mark_hierarchy_changed(&mut self, entity: Entity) {
+        if let Some(global) = self.get_mut::<GlobalTransform>(entity) {
+            global.dirty = true;
+        }
+    }
+}
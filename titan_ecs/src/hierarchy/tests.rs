@@ -0,0 +1,97 @@
+#![cfg(test)]
+
+use crate::{Children, HierarchyError, Parent, World};
+
+#[test]
+fn test_set_parent() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let gun = world.spawn();
+
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(gun, turret).unwrap();
+    assert_eq!(world.parent(turret), Some(tank));
+    assert_eq!(world.get::<Parent>(gun).map(Parent::get), Some(turret));
+    assert_eq!(world.children(tank), &[turret]);
+    assert_eq!(world.children(turret), &[gun]);
+
+    world.set_parent(gun, tank).unwrap();
+    assert_eq!(world.children(tank), &[turret, gun]);
+    assert!(!world.attached::<Children>(turret));
+
+    assert_eq!(world.remove_parent(gun), Some(tank));
+    assert_eq!(world.parent(gun), None);
+    assert_eq!(world.children(tank), &[turret]);
+}
+
+#[test]
+fn test_cycles() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let gun = world.spawn();
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(gun, turret).unwrap();
+
+    assert_eq!(
+        world.set_parent(tank, tank),
+        Err(HierarchyError::Cycle {
+            child: tank,
+            parent: tank,
+        }),
+    );
+    assert_eq!(
+        world.set_parent(tank, gun),
+        Err(HierarchyError::Cycle {
+            child: tank,
+            parent: gun,
+        }),
+    );
+    assert_eq!(world.parent(tank), None);
+    assert_eq!(world.children(gun), &[]);
+
+    world.despawn(gun);
+    assert_eq!(
+        world.set_parent(turret, gun),
+        Err(HierarchyError::NotAlive(gun)),
+    );
+}
+
+#[test]
+fn test_despawn_orphans_children() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let gun = world.spawn();
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(gun, turret).unwrap();
+
+    assert!(world.despawn(turret));
+    assert!(world.contains(gun));
+    assert_eq!(world.parent(gun), None);
+    assert_eq!(world.children(tank), &[]);
+    assert!(!world.attached::<Children>(tank));
+}
+
+#[test]
+fn test_despawn_recursive() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let gun = world.spawn();
+    let wheel = world.spawn();
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(gun, turret).unwrap();
+    world.set_parent(wheel, tank).unwrap();
+
+    assert!(world.despawn_recursive(turret));
+    assert!(!world.contains(turret));
+    assert!(!world.contains(gun));
+    assert!(world.contains(wheel));
+    assert_eq!(world.children(tank), &[wheel]);
+
+    assert!(world.despawn_recursive(tank));
+    assert!(world.is_empty());
+    assert!(!world.despawn_recursive(tank));
+}
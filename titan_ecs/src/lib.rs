@@ -1,14 +1,35 @@
 //! Entity Component System (ECS) utilities for game engine.
 
-pub use component::Component;
+pub use command::{Commands, EntityBuilder};
+pub use component::{Component, ComponentTicks, Tick, CHECK_TICK_THRESHOLD, MAX_CHANGE_AGE};
 pub use entity::Entity;
+pub use hierarchy::{Children, HierarchyError, Parent};
+pub use name::Name;
+pub use query::{
+    Added, Changed, ComponentAccess, Fetch, Query, QueryData, QueryError, QueryFilter,
+    ReadOnlyFetch, ReadOnlyQueryData,
+};
+pub use resource::{Res, ResMut, Resource, Resources};
+pub use schedule::{Schedule, SystemContext, SystemStage};
+pub use serialization::{EntityMap, MapEntities, WorldDeserializer, WorldSerializer};
+pub use snapshot::{EntitySnapshot, WorldSnapshot};
 pub use system::System;
+pub use transform::{propagate_transforms, GlobalTransform, Transform, TransformPropagationSystem};
 pub use world::World;
 
 use component::ComponentManager;
 use entity::EntityStorage;
 
+mod command;
 mod component;
 mod entity;
+mod hierarchy;
+mod name;
+mod query;
+mod resource;
+mod schedule;
+mod serialization;
+mod snapshot;
 mod system;
+mod transform;
 mod world;
@@ -0,0 +1,44 @@
+//! Utilities for *names* of entities in ECS.
+
+use crate::{Entity, World};
+
+mod tests;
+
+/// Component which stores name of the entity.
+///
+/// Names are not unique: many entities could have the same name.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name(pub String);
+
+impl Name {
+    /// Name of the entity as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl World {
+    /// Creates new entity with [`Name`] component only.
+    pub fn spawn_named(&mut self, name: impl Into<String>) -> Entity {
+        let entity = self.spawn();
+        self.insert(entity, Name(name.into()));
+        entity
+    }
+
+    /// Returns the first found entity with given name, if any.
+    ///
+    /// Order of entities with the same name is not specified
+    /// (see [`World::find_all_by_name`] to get all of them).
+    ///
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.find_all_by_name(name).next()
+    }
+
+    /// Returns iterator over all entities with given name.
+    pub fn find_all_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = Entity> + 'a {
+        self.query::<Name>()
+            .filter(move |(_, other)| other.as_str() == name)
+            .map(|(entity, _)| entity)
+    }
+}
@@ -0,0 +1,26 @@
+#![cfg(test)]
+
+use crate::{Name, World};
+
+#[test]
+fn test_find_by_name() {
+    let mut world = World::new();
+    let tank = world.spawn_named("tank");
+    let first = world.spawn_named("wheel");
+    let unnamed = world.spawn();
+    let second = world.spawn_named(String::from("wheel"));
+
+    assert_eq!(world.get::<Name>(tank).map(Name::as_str), Some("tank"));
+    assert!(!world.attached::<Name>(unnamed));
+    assert_eq!(world.find_by_name("tank"), Some(tank));
+    assert_eq!(world.find_by_name("turret"), None);
+
+    let mut wheels: Vec<_> = world.find_all_by_name("wheel").collect();
+    wheels.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(wheels, expected);
+
+    world.despawn(tank);
+    assert_eq!(world.find_by_name("tank"), None);
+}
@@ -0,0 +1,166 @@
+//! Filters of *queries* by changes of components since the previous run.
+
+use std::any::{type_name, TypeId};
+use std::marker::PhantomData;
+
+use crate::{Component, ComponentAccess, ComponentTicks, Entity, Tick, World};
+
+use super::private::Sealed;
+
+/// Filter of the query which passes entities with component of type `T`
+/// added since the previous run.
+pub struct Added<T>(PhantomData<fn() -> T>)
+where
+    T: Component;
+
+/// Filter of the query which passes entities with component of type `T`
+/// added or changed since the previous run.
+pub struct Changed<T>(PhantomData<fn() -> T>)
+where
+    T: Component;
+
+/// Filter of entities of the [`Query`](super::Query): [`Added`], [`Changed`]
+/// or tuple of them, which passes entities matching all filters of the tuple.
+///
+/// Components of the filter are only read to check their ticks,
+/// so they could be accessed mutably by the query at the same time.
+/// This trait is sealed: it is implemented for filters of this module only.
+///
+pub trait QueryFilter: Sealed {
+    /// Access of the filter to components, which are only read.
+    fn access() -> Vec<ComponentAccess>;
+
+    /// Returns entities which pass the filter, or `None` if all entities pass it.
+    fn filter(world: &World, last_run: Tick, this_run: Tick) -> Option<Vec<Entity>>;
+
+    /// Returns `true` if the entity passes the filter.
+    fn matches(world: &World, entity: Entity, last_run: Tick, this_run: Tick) -> bool;
+}
+
+/// Ticks of component of type `T` attached to the entity, if any.
+fn ticks<T>(world: &World, entity: Entity) -> Option<ComponentTicks>
+where
+    T: Component,
+{
+    let storage = world.component_manager().get_storage::<T>()?;
+    storage.ticks(entity)
+}
+
+/// Shared access to component of type `T`.
+fn read<T>() -> ComponentAccess
+where
+    T: Component,
+{
+    ComponentAccess {
+        typeid: TypeId::of::<T>(),
+        type_name: type_name::<T>(),
+        exclusive: false,
+    }
+}
+
+impl Sealed for () {}
+
+impl QueryFilter for () {
+    fn access() -> Vec<ComponentAccess> {
+        Vec::new()
+    }
+
+    fn filter(_: &World, _: Tick, _: Tick) -> Option<Vec<Entity>> {
+        None
+    }
+
+    fn matches(_: &World, _: Entity, _: Tick, _: Tick) -> bool {
+        true
+    }
+}
+
+impl<T> Sealed for Added<T> where T: Component {}
+
+impl<T> QueryFilter for Added<T>
+where
+    T: Component,
+{
+    fn access() -> Vec<ComponentAccess> {
+        vec![self::read::<T>()]
+    }
+
+    fn filter(world: &World, last_run: Tick, this_run: Tick) -> Option<Vec<Entity>> {
+        let storage = world.component_manager().get_storage::<T>();
+        let entities = storage
+            .into_iter()
+            .flat_map(|storage| storage.iter_added(last_run, this_run))
+            .map(|(entity, _)| entity)
+            .collect();
+        Some(entities)
+    }
+
+    fn matches(world: &World, entity: Entity, last_run: Tick, this_run: Tick) -> bool {
+        self::ticks::<T>(world, entity)
+            .map(|ticks| ticks.is_added(last_run, this_run))
+            .unwrap_or(false)
+    }
+}
+
+impl<T> Sealed for Changed<T> where T: Component {}
+
+impl<T> QueryFilter for Changed<T>
+where
+    T: Component,
+{
+    fn access() -> Vec<ComponentAccess> {
+        vec![self::read::<T>()]
+    }
+
+    fn filter(world: &World, last_run: Tick, this_run: Tick) -> Option<Vec<Entity>> {
+        let storage = world.component_manager().get_storage::<T>();
+        let entities = storage
+            .into_iter()
+            .flat_map(|storage| storage.iter_changed(last_run, this_run))
+            .map(|(entity, _)| entity)
+            .collect();
+        Some(entities)
+    }
+
+    fn matches(world: &World, entity: Entity, last_run: Tick, this_run: Tick) -> bool {
+        self::ticks::<T>(world, entity)
+            .map(|ticks| ticks.is_changed(last_run, this_run))
+            .unwrap_or(false)
+    }
+}
+
+// Generate implementations of QueryFilter for tuples up to 4 elements.
+macro_rules! impl_query_filter {
+    ($($name:ident),+) => {
+        impl<$($name),+> Sealed for ($($name,)+) where $($name: QueryFilter,)+ {}
+
+        impl<$($name),+> QueryFilter for ($($name,)+)
+        where
+            $($name: QueryFilter,)+
+        {
+            fn access() -> Vec<ComponentAccess> {
+                [$($name::access()),+].concat()
+            }
+
+            fn filter(world: &World, last_run: Tick, this_run: Tick) -> Option<Vec<Entity>> {
+                let mut entities: Option<Vec<Entity>> = None;
+                $(entities = match entities {
+                    None => $name::filter(world, last_run, this_run),
+                    Some(mut entities) => {
+                        entities.retain(|&entity| $name::matches(world, entity, last_run, this_run));
+                        Some(entities)
+                    }
+                };)+
+                entities
+            }
+
+            fn matches(world: &World, entity: Entity, last_run: Tick, this_run: Tick) -> bool {
+                $($name::matches(world, entity, last_run, this_run))&&+
+            }
+        }
+    };
+}
+
+impl_query_filter!(A);
+impl_query_filter!(A, B);
+impl_query_filter!(A, B, C);
+impl_query_filter!(A, B, C, D);
@@ -0,0 +1,507 @@
+//! Utilities for *queries* of several components in ECS.
+
+use std::any::{type_name, TypeId};
+use std::marker::PhantomData;
+
+use thiserror::Error;
+
+use crate::component::{AnyStorage, ComponentStorage};
+use crate::{Component, Entity, Tick, World};
+
+pub use filter::{Added, Changed, QueryFilter};
+
+mod filter;
+mod tests;
+
+/// Error that can happen when the query is created.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+pub enum QueryError {
+    #[error("component `{0}` is accessed exclusively and also by another parameter of the query")]
+    ConflictingAccess(&'static str),
+
+    #[error("component `{0}` is requested more than once")]
+    DuplicateComponent(&'static str),
+}
+
+/// Access of the query parameter to components of one type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ComponentAccess {
+    /// Type of accessed components.
+    pub typeid: TypeId,
+    /// Name of accessed component type.
+    pub type_name: &'static str,
+    /// Whether components are accessed mutably.
+    pub exclusive: bool,
+}
+
+mod private {
+    use crate::component::AnyStorage;
+
+    pub trait Sealed {}
+
+    /// Type-erased storage which was borrowed for the query.
+    pub enum StorageRef<'s> {
+        /// Storage which could be shared with other queries.
+        Shared(&'s dyn AnyStorage),
+        /// Storage which is borrowed by one query only.
+        Exclusive(&'s mut dyn AnyStorage),
+    }
+
+    impl StorageRef<'_> {
+        pub fn get(&self) -> &dyn AnyStorage {
+            match self {
+                StorageRef::Shared(storage) => *storage,
+                StorageRef::Exclusive(storage) => &**storage,
+            }
+        }
+    }
+}
+
+pub(crate) use private::StorageRef;
+
+/// Parameter of the query which fetches component of one type:
+/// `&T` for shared access or `&mut T` for exclusive access.
+///
+/// This trait is sealed: it is implemented for references to components only.
+///
+pub trait Fetch: private::Sealed {
+    /// Type of fetched components.
+    type Component: Component;
+
+    /// Reference to the component which is yielded by the query.
+    type Item<'w>;
+
+    /// Whether components are accessed mutably.
+    const EXCLUSIVE: bool;
+
+    /// Storage of components which was borrowed for the query.
+    #[doc(hidden)]
+    type Storage<'s>;
+
+    /// Fetches component attached to the entity, marking it as changed if needed.
+    fn fetch(
+        storage: &mut ComponentStorage<Self::Component>,
+        entity: Entity,
+    ) -> Option<Self::Item<'_>>;
+
+    /// Downcasts type-erased storage, setting its change tick if components are accessed mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if components are accessed mutably, but the storage is shared.
+    ///
+    #[doc(hidden)]
+    fn downcast(storage: StorageRef<'_>, change_tick: Tick) -> Self::Storage<'_>;
+
+    /// Fetches component attached to the entity from the borrowed storage.
+    #[doc(hidden)]
+    fn fetch_in<'s>(storage: &'s mut Self::Storage<'_>, entity: Entity) -> Option<Self::Item<'s>>;
+
+    /// Returns `true` if component is attached to the entity in the borrowed storage.
+    #[doc(hidden)]
+    fn attached(storage: &Self::Storage<'_>, entity: Entity) -> bool;
+}
+
+/// Parameter of the query which only reads components.
+pub trait ReadOnlyFetch: Fetch {
+    /// Fetches component attached to the entity without changing it.
+    fn fetch_ref(
+        storage: &ComponentStorage<Self::Component>,
+        entity: Entity,
+    ) -> Option<Self::Item<'_>>;
+}
+
+impl<T> private::Sealed for &T where T: Component {}
+
+impl<T> Fetch for &T
+where
+    T: Component,
+{
+    type Component = T;
+    type Item<'w> = &'w T;
+    const EXCLUSIVE: bool = false;
+    type Storage<'s> = &'s ComponentStorage<T>;
+
+    fn fetch(storage: &mut ComponentStorage<T>, entity: Entity) -> Option<&T> {
+        storage.get(entity)
+    }
+
+    fn downcast(storage: StorageRef<'_>, _: Tick) -> &ComponentStorage<T> {
+        let storage = match storage {
+            StorageRef::Shared(storage) => storage,
+            StorageRef::Exclusive(storage) => storage,
+        };
+        storage.as_any().downcast_ref().expect("downcast error")
+    }
+
+    fn fetch_in<'s>(storage: &'s mut &ComponentStorage<T>, entity: Entity) -> Option<&'s T> {
+        storage.get(entity)
+    }
+
+    fn attached(storage: &&ComponentStorage<T>, entity: Entity) -> bool {
+        storage.attached(entity)
+    }
+}
+
+impl<T> ReadOnlyFetch for &T
+where
+    T: Component,
+{
+    fn fetch_ref(storage: &ComponentStorage<T>, entity: Entity) -> Option<&T> {
+        storage.get(entity)
+    }
+}
+
+impl<T> private::Sealed for &mut T where T: Component {}
+
+impl<T> Fetch for &mut T
+where
+    T: Component,
+{
+    type Component = T;
+    type Item<'w> = &'w mut T;
+    const EXCLUSIVE: bool = true;
+    type Storage<'s> = &'s mut ComponentStorage<T>;
+
+    fn fetch(storage: &mut ComponentStorage<T>, entity: Entity) -> Option<&mut T> {
+        storage.get_mut(entity)
+    }
+
+    fn downcast(storage: StorageRef<'_>, change_tick: Tick) -> &mut ComponentStorage<T> {
+        let storage = match storage {
+            StorageRef::Exclusive(storage) => storage,
+            StorageRef::Shared(storage) => panic!(
+                "component `{}` is accessed mutably from shared storage",
+                storage.type_name(),
+            ),
+        };
+        let storage: &mut ComponentStorage<T> =
+            storage.as_any_mut().downcast_mut().expect("downcast error");
+        storage.set_change_tick(change_tick);
+        storage
+    }
+
+    fn fetch_in<'s>(
+        storage: &'s mut &mut ComponentStorage<T>,
+        entity: Entity,
+    ) -> Option<&'s mut T> {
+        Self::fetch(storage, entity)
+    }
+
+    fn attached(storage: &&mut ComponentStorage<T>, entity: Entity) -> bool {
+        storage.attached(entity)
+    }
+}
+
+/// Set of components which are fetched by the [`Query`]:
+/// tuple of [`Fetch`] parameters, like `(&Transform, &mut Velocity)`.
+pub trait QueryData {
+    /// Components which are yielded by the query for each entity.
+    type Item<'w>;
+
+    /// Access of each parameter of the query.
+    fn access() -> Vec<ComponentAccess>;
+
+    /// Calls the function for each entity which has all components of the query.
+    ///
+    /// Components which are accessed exclusively are marked as changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the same component type is requested more than once
+    /// (see [`Query::new`] which checks it).
+    ///
+    fn for_each<F>(world: &mut World, f: F)
+    where
+        F: for<'w> FnMut(Entity, Self::Item<'w>),
+    {
+        Self::for_each_of(world, None, f)
+    }
+
+    /// Calls the function for each of given entities (or all entities if `None`)
+    /// which has all components of the query.
+    #[doc(hidden)]
+    fn for_each_of<F>(world: &mut World, entities: Option<Vec<Entity>>, f: F)
+    where
+        F: for<'w> FnMut(Entity, Self::Item<'w>);
+
+    /// Calls the function for each of given entities (or all entities if `None`)
+    /// which has all components of the query, taking them from storages
+    /// which were borrowed in order of [`QueryData::access`].
+    #[doc(hidden)]
+    fn for_each_in<F>(
+        storages: Vec<StorageRef<'_>>,
+        change_tick: Tick,
+        entities: Option<Vec<Entity>>,
+        f: F,
+    ) where
+        F: for<'w> FnMut(Entity, Self::Item<'w>);
+}
+
+/// Set of components which are only read by the [`Query`].
+pub trait ReadOnlyQueryData: QueryData {
+    /// Returns iterator over entities which have all components of the query.
+    fn iter<'w>(world: &'w World) -> Box<dyn Iterator<Item = (Entity, Self::Item<'w>)> + 'w>
+    where
+        Self: 'w;
+}
+
+/// Returns storage with the least count of components, entities of which are checked by the query.
+fn smallest<'a>(
+    storages: impl IntoIterator<Item = &'a dyn AnyStorage>,
+) -> Option<&'a dyn AnyStorage> {
+    storages.into_iter().min_by_key(|storage| storage.len())
+}
+
+// Generate implementations of QueryData for tuples up to 8 elements.
+macro_rules! impl_query_data {
+    ($($name:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($name),+> QueryData for ($($name,)+)
+        where
+            $($name: Fetch,)+
+        {
+            type Item<'w> = ($($name::Item<'w>,)+);
+
+            fn access() -> Vec<ComponentAccess> {
+                vec![$(ComponentAccess {
+                    typeid: TypeId::of::<$name::Component>(),
+                    type_name: type_name::<$name::Component>(),
+                    exclusive: $name::EXCLUSIVE,
+                }),+]
+            }
+
+            fn for_each_of<Func>(world: &mut World, entities: Option<Vec<Entity>>, f: Func)
+            where
+                Func: for<'w> FnMut(Entity, Self::Item<'w>),
+            {
+                let change_tick = world.change_tick();
+                let typeids = [$(&TypeId::of::<$name::Component>()),+];
+                let [$($name),+] = world.component_manager_mut().get_storages_mut(typeids);
+                // There are no entities with all components if any storage is missing.
+                let storages = vec![$(match $name {
+                    Some(storage) => StorageRef::Exclusive(storage),
+                    None => return,
+                }),+];
+                Self::for_each_in(storages, change_tick, entities, f)
+            }
+
+            fn for_each_in<Func>(
+                storages: Vec<StorageRef<'_>>,
+                change_tick: Tick,
+                entities: Option<Vec<Entity>>,
+                mut f: Func,
+            ) where
+                Func: for<'w> FnMut(Entity, Self::Item<'w>),
+            {
+                let entities: Vec<_> = match entities {
+                    Some(entities) => entities,
+                    None => match smallest(storages.iter().map(StorageRef::get)) {
+                        Some(storage) => storage.entities().collect(),
+                        None => return,
+                    },
+                };
+                let mut storages = storages.into_iter();
+                $(let mut $name = $name::downcast(
+                    storages.next().expect("storage is missing"),
+                    change_tick,
+                );)+
+                for entity in entities {
+                    if !($($name::attached(&$name, entity))&&+) {
+                        continue;
+                    }
+                    let item = ($($name::fetch_in(&mut $name, entity).unwrap(),)+);
+                    f(entity, item);
+                }
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<$($name),+> ReadOnlyQueryData for ($($name,)+)
+        where
+            $($name: ReadOnlyFetch,)+
+        {
+            fn iter<'w>(
+                world: &'w World,
+            ) -> Box<dyn Iterator<Item = (Entity, Self::Item<'w>)> + 'w>
+            where
+                Self: 'w,
+            {
+                let manager = world.component_manager();
+                $(let $name = match manager.get_storage::<$name::Component>() {
+                    Some(storage) => storage,
+                    None => return Box::new(std::iter::empty()),
+                };)+
+                let entities = match smallest([$($name as &dyn AnyStorage),+]) {
+                    Some(storage) => storage.entities(),
+                    None => return Box::new(std::iter::empty()),
+                };
+                Box::new(entities.filter_map(move |entity| {
+                    Some((entity, ($($name::fetch_ref($name, entity)?,)+)))
+                }))
+            }
+        }
+    };
+}
+
+impl_query_data!(A);
+impl_query_data!(A, B);
+impl_query_data!(A, B, C);
+impl_query_data!(A, B, C, D);
+impl_query_data!(A, B, C, D, E);
+impl_query_data!(A, B, C, D, E, F);
+impl_query_data!(A, B, C, D, E, F, G);
+impl_query_data!(A, B, C, D, E, F, G, H);
+
+/// Query which iterates only entities that have all requested components.
+///
+/// Components are requested by the tuple of references: `&T` for shared access
+/// and `&mut T` for exclusive access, which marks yielded components as changed.
+/// Access is checked when the query is created, so the same component type
+/// could not be requested mutably and by another parameter at once.
+///
+/// ```
+/// # use titan_ecs::{Query, World};
+/// struct Position(f32);
+/// struct Velocity(f32);
+///
+/// let mut world = World::new();
+/// let entity = world.spawn();
+/// world.insert(entity, Position(0.0));
+/// world.insert(entity, Velocity(1.0));
+///
+/// let query = Query::<(&mut Position, &Velocity)>::new().unwrap();
+/// query.for_each(&mut world, |_, (position, velocity)| position.0 += velocity.0);
+/// assert_eq!(world.get::<Position>(entity).unwrap().0, 1.0);
+/// ```
+///
+/// Entities could also be filtered by changes of their components since the previous run
+/// (see [`QueryFilter`]), so systems react only to modifications:
+///
+/// ```
+/// # use titan_ecs::{Changed, Query, Schedule, World};
+/// struct Position(f32);
+/// struct Moved(bool);
+///
+/// let mut world = World::new();
+/// let entity = world.spawn();
+/// world.insert(entity, Position(0.0));
+/// world.insert(entity, Moved(false));
+///
+/// let query = Query::<(&mut Moved,), Changed<Position>>::new().unwrap();
+/// let mut schedule = Schedule::new();
+/// schedule.add_system(move |context| {
+///     context.query(&query, |_, (moved,)| moved.0 = true);
+/// });
+/// schedule.run(&mut world);
+/// assert!(world.get::<Moved>(entity).unwrap().0);
+///
+/// // Position was not changed since the previous run.
+/// world.get_mut::<Moved>(entity).unwrap().0 = false;
+/// schedule.run(&mut world);
+/// assert!(!world.get::<Moved>(entity).unwrap().0);
+/// ```
+///
+pub struct Query<Q, F = ()>
+where
+    Q: QueryData,
+    F: QueryFilter,
+{
+    _marker: PhantomData<fn() -> (Q, F)>,
+}
+
+impl<Q, F> Query<Q, F>
+where
+    Q: QueryData,
+    F: QueryFilter,
+{
+    /// Creates new query, checking access of its parameters.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the same component type is requested more than once.
+    ///
+    pub fn new() -> Result<Self, QueryError> {
+        let access = Q::access();
+        for (index, first) in access.iter().enumerate() {
+            let second = access[index + 1..]
+                .iter()
+                .find(|second| second.typeid == first.typeid);
+            match second {
+                Some(second) if first.exclusive || second.exclusive => {
+                    return Err(QueryError::ConflictingAccess(first.type_name))
+                }
+                Some(_) => return Err(QueryError::DuplicateComponent(first.type_name)),
+                None => (),
+            }
+        }
+        Ok(Self {
+            _marker: PhantomData,
+        })
+    }
+
+    /// Access of each parameter of the query.
+    pub fn access(&self) -> Vec<ComponentAccess> {
+        Q::access()
+    }
+
+    /// Access of the filter of the query, which only reads components.
+    pub fn filter_access(&self) -> Vec<ComponentAccess> {
+        F::access()
+    }
+
+    /// Calls the function for each entity which has all components of the query.
+    ///
+    /// Components which are accessed exclusively are marked as changed.
+    /// Filter of the query passes all components which were ever added or changed
+    /// (see [`Query::for_each_since`]).
+    ///
+    pub fn for_each<Func>(&self, world: &mut World, f: Func)
+    where
+        Func: for<'w> FnMut(Entity, Q::Item<'w>),
+    {
+        let last_run = Tick::oldest(world.change_tick());
+        self.for_each_since(world, last_run, f)
+    }
+
+    /// Calls the function for each entity which has all components of the query
+    /// and passes its filter since `last_run`.
+    ///
+    /// Components which are accessed exclusively are marked as changed.
+    ///
+    pub fn for_each_since<Func>(&self, world: &mut World, last_run: Tick, f: Func)
+    where
+        Func: for<'w> FnMut(Entity, Q::Item<'w>),
+    {
+        let entities = F::filter(world, last_run, world.change_tick());
+        Q::for_each_of(world, entities, f)
+    }
+
+    /// Returns iterator over entities which have all components of the query.
+    ///
+    /// Only queries which read components could be iterated
+    /// (see [`Query::for_each`] for queries with exclusive access).
+    /// Filter of the query passes all components which were ever added or changed
+    /// (see [`Query::iter_since`]).
+    ///
+    pub fn iter<'w>(&self, world: &'w World) -> impl Iterator<Item = (Entity, Q::Item<'w>)> + 'w
+    where
+        Q: ReadOnlyQueryData + 'w,
+    {
+        self.iter_since(world, Tick::oldest(world.change_tick()))
+    }
+
+    /// Returns iterator over entities which have all components of the query
+    /// and pass its filter since `last_run`.
+    pub fn iter_since<'w>(
+        &self,
+        world: &'w World,
+        last_run: Tick,
+    ) -> impl Iterator<Item = (Entity, Q::Item<'w>)> + 'w
+    where
+        Q: ReadOnlyQueryData + 'w,
+    {
+        let this_run = world.change_tick();
+        Q::iter(world).filter(move |(entity, _)| F::matches(world, *entity, last_run, this_run))
+    }
+}
@@ -0,0 +1,136 @@
+#![cfg(test)]
+
+use crate::{Added, Changed, Query, QueryError, World};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Position(f32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Velocity(f32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Frozen;
+
+#[test]
+fn test_access() {
+    assert!(Query::<(&Position, &mut Velocity)>::new().is_ok());
+
+    let name = std::any::type_name::<Position>();
+    let error = Query::<(&mut Position, &Velocity, &Position)>::new().err();
+    assert_eq!(error, Some(QueryError::ConflictingAccess(name)));
+    let error = Query::<(&Position, &mut Position)>::new().err();
+    assert_eq!(error, Some(QueryError::ConflictingAccess(name)));
+    let error = Query::<(&Position, &Position)>::new().err();
+    assert_eq!(error, Some(QueryError::DuplicateComponent(name)));
+}
+
+#[test]
+fn test_query() {
+    let mut world = World::new();
+    let moving = world.spawn();
+    world.insert(moving, Position(1.0));
+    world.insert(moving, Velocity(2.0));
+    let still = world.spawn();
+    world.insert(still, Position(5.0));
+    let frozen = world.spawn();
+    world.insert(frozen, Position(0.0));
+    world.insert(frozen, Velocity(1.0));
+    world.insert(frozen, Frozen);
+
+    // Only entities with all components are yielded.
+    let query = Query::<(&mut Position, &Velocity)>::new().unwrap();
+    let mut entities = Vec::new();
+    query.for_each(&mut world, |entity, (position, velocity)| {
+        position.0 += velocity.0;
+        entities.push(entity);
+    });
+    entities.sort();
+    let mut expected = vec![moving, frozen];
+    expected.sort();
+    assert_eq!(entities, expected);
+    assert_eq!(world.get::<Position>(moving), Some(&Position(3.0)));
+    assert_eq!(world.get::<Position>(still), Some(&Position(5.0)));
+
+    let query = Query::<(&Position, &Velocity, &Frozen)>::new().unwrap();
+    let items: Vec<_> = query.iter(&world).collect();
+    assert_eq!(items, [(frozen, (&Position(1.0), &Velocity(1.0), &Frozen))]);
+
+    // Nothing is yielded if there are no components of some type.
+    world.remove::<Frozen>(frozen);
+    assert_eq!(query.iter(&world).count(), 0);
+    let query = Query::<(&mut Position, &String)>::new().unwrap();
+    query.for_each(&mut world, |_, _| panic!("no entity has all components"));
+}
+
+#[test]
+fn test_query_marks_changed() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Position(0.0));
+    world.insert(entity, Velocity(1.0));
+
+    let mut schedule = crate::Schedule::new();
+    schedule.add_system(|context: &mut crate::SystemContext| {
+        let query = Query::<(&mut Position, &Velocity)>::new().unwrap();
+        query.for_each(context.world_mut(), |_, (position, velocity)| {
+            position.0 += velocity.0;
+        });
+    });
+    let changed = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let system_changed = changed.clone();
+    schedule.add_system(move |context: &mut crate::SystemContext| {
+        let positions = context.query_changed::<Position>().count();
+        let velocities = context.query_changed::<Velocity>().count();
+        system_changed.borrow_mut().push((positions, velocities));
+    });
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    // Only components which are accessed exclusively are changed by the query.
+    assert_eq!(*changed.borrow(), [(1, 1), (1, 0)]);
+    assert_eq!(world.get::<Position>(entity), Some(&Position(2.0)));
+}
+
+#[test]
+fn test_query_filters() {
+    let mut world = World::new();
+    let moving = world.spawn();
+    world.insert(moving, Position(0.0));
+    world.insert(moving, Velocity(1.0));
+    let still = world.spawn();
+    world.insert(still, Position(0.0));
+    world.insert(still, Velocity(0.0));
+    let last_run = world.increment_change_tick();
+
+    // Filters pass everything which was ever added or changed by default.
+    let query = Query::<(&Position,), Changed<Velocity>>::new().unwrap();
+    assert_eq!(query.iter(&world).count(), 2);
+    assert_eq!(query.iter_since(&world, last_run).count(), 0);
+
+    world.increment_change_tick();
+    world.get_mut::<Velocity>(moving).unwrap().0 = 2.0;
+    let added = world.spawn();
+    world.insert(added, Position(0.0));
+    world.insert(added, Velocity(1.0));
+
+    let entities = |world: &World, last_run| {
+        let mut entities: Vec<_> = query.iter_since(world, last_run).map(|(e, _)| e).collect();
+        entities.sort();
+        entities
+    };
+    let mut expected = vec![moving, added];
+    expected.sort();
+    assert_eq!(entities(&world, last_run), expected);
+
+    // Components of the filter could be accessed mutably by the query.
+    let query = Query::<(&mut Velocity,), (Added<Position>, Changed<Velocity>)>::new().unwrap();
+    let mut yielded = Vec::new();
+    query.for_each_since(&mut world, last_run, |entity, (velocity,)| {
+        velocity.0 = 0.0;
+        yielded.push(entity);
+    });
+    assert_eq!(yielded, [added]);
+    assert_eq!(world.get::<Velocity>(added), Some(&Velocity(0.0)));
+    assert_eq!(world.get::<Velocity>(moving), Some(&Velocity(2.0)));
+    assert_eq!(query.filter_access().len(), 2);
+}
@@ -0,0 +1,283 @@
+//! Utilities for *resources* of ECS.
+
+use std::any::{self, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::{
+    LockResult, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError, TryLockResult,
+};
+
+use crate::World;
+
+mod tests;
+
+/// Objects of this trait represent *resource* of ECS:
+/// global data which is not attached to any entity (for example, [task pool](titan_tasks::TaskPool)).
+///
+/// World could contain at most one resource of each type.
+///
+pub trait Resource: Any + Send + Sync {}
+
+impl<T> Resource for T where T: Any + Send + Sync {}
+
+type ResourceBox = Box<dyn Any + Send + Sync>;
+
+/// Storage of resources of the world, at most one resource of each type.
+///
+/// Resources are borrowed by [`Res`] and [`ResMut`] through shared reference to the storage,
+/// so systems which share the world (for example, parallel systems of the [schedule](crate::Schedule))
+/// could read and change different resources at the same time.
+///
+/// Borrows are checked at runtime like borrows of [`RefCell`](std::cell::RefCell):
+/// conflicting borrow panics instead of waiting, even if the resource is borrowed
+/// by another thread. So parallel systems must not change the same resource.
+///
+#[derive(Default)]
+pub struct Resources {
+    /// Map with typeid of resources and their values.
+    map: HashMap<TypeId, RwLock<ResourceBox>>,
+}
+
+impl Resources {
+    /// Creates an empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts resource of type `T` into the storage.
+    /// If resource was already inserted, it will be replaced by value.
+    ///
+    /// Returns previously inserted resource, if any.
+    ///
+    pub fn insert<T>(&mut self, resource: T) -> Option<T>
+    where
+        T: Resource,
+    {
+        let previous = self
+            .map
+            .insert(TypeId::of::<T>(), RwLock::new(Box::new(resource)))?;
+        let previous = self::unpoison(previous.into_inner());
+        previous.downcast().ok().map(|previous| *previous)
+    }
+
+    /// Removes resource of type `T` from the storage.
+    ///
+    /// Returns resource that was previously inserted, if any.
+    ///
+    pub fn remove<T>(&mut self) -> Option<T>
+    where
+        T: Resource,
+    {
+        let resource = self.map.remove(&TypeId::of::<T>())?;
+        let resource = self::unpoison(resource.into_inner());
+        resource.downcast().ok().map(|resource| *resource)
+    }
+
+    /// Returns `true` if resource of type `T` was inserted into the storage.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Resource,
+    {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Count of resources in the storage.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if there are no resources in the storage.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Borrows resource of type `T` immutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource is currently borrowed mutably.
+    ///
+    #[track_caller]
+    pub fn get<T>(&self) -> Option<Res<'_, T>>
+    where
+        T: Resource,
+    {
+        let resource = self.map.get(&TypeId::of::<T>())?;
+        Some(Res {
+            guard: self::borrow::<T, _>(resource.try_read()),
+            marker: PhantomData,
+        })
+    }
+
+    /// Borrows resource of type `T` mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource is currently borrowed.
+    ///
+    #[track_caller]
+    pub fn get_mut<T>(&self) -> Option<ResMut<'_, T>>
+    where
+        T: Resource,
+    {
+        let resource = self.map.get(&TypeId::of::<T>())?;
+        Some(ResMut {
+            guard: self::borrow::<T, _>(resource.try_write()),
+            marker: PhantomData,
+        })
+    }
+}
+
+/// Resources stay usable after the system which borrowed them has panicked.
+fn unpoison<T>(result: LockResult<T>) -> T {
+    result.unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the guard of resource of type `T` if the lock was acquired without waiting.
+#[track_caller]
+fn borrow<T, G>(result: TryLockResult<G>) -> G
+where
+    T: Resource,
+{
+    match result {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(error)) => error.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            panic!("resource {} already borrowed", any::type_name::<T>())
+        }
+    }
+}
+
+/// Immutable borrow of resource of type `T`.
+pub struct Res<'r, T>
+where
+    T: Resource,
+{
+    guard: RwLockReadGuard<'r, ResourceBox>,
+    marker: PhantomData<&'r T>,
+}
+
+impl<T> Deref for Res<'_, T>
+where
+    T: Resource,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.downcast_ref().expect("downcast error")
+    }
+}
+
+impl<T> fmt::Debug for Res<'_, T>
+where
+    T: Resource + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Mutable borrow of resource of type `T`.
+pub struct ResMut<'r, T>
+where
+    T: Resource,
+{
+    guard: RwLockWriteGuard<'r, ResourceBox>,
+    marker: PhantomData<&'r mut T>,
+}
+
+impl<T> Deref for ResMut<'_, T>
+where
+    T: Resource,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.downcast_ref().expect("downcast error")
+    }
+}
+
+impl<T> DerefMut for ResMut<'_, T>
+where
+    T: Resource,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.downcast_mut().expect("downcast error")
+    }
+}
+
+impl<T> fmt::Debug for ResMut<'_, T>
+where
+    T: Resource + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl World {
+    /// Inserts resource of type `T` into the world.
+    /// If resource was already inserted, it will be replaced by value.
+    ///
+    /// Returns previously inserted resource, if any.
+    ///
+    pub fn insert_resource<T>(&mut self, resource: T) -> Option<T>
+    where
+        T: Resource,
+    {
+        self.resources.insert(resource)
+    }
+
+    /// Removes resource of type `T` from the world.
+    ///
+    /// Returns resource that was previously inserted, if any.
+    ///
+    pub fn remove_resource<T>(&mut self) -> Option<T>
+    where
+        T: Resource,
+    {
+        self.resources.remove()
+    }
+
+    /// Returns `true` if resource of type `T` was inserted into the world.
+    pub fn contains_resource<T>(&self) -> bool
+    where
+        T: Resource,
+    {
+        self.resources.contains::<T>()
+    }
+
+    /// Borrows resource of type `T` immutably (see [`Resources::get`]).
+    #[track_caller]
+    pub fn resource<T>(&self) -> Option<Res<'_, T>>
+    where
+        T: Resource,
+    {
+        self.resources.get()
+    }
+
+    /// Borrows resource of type `T` mutably (see [`Resources::get_mut`]).
+    ///
+    /// Only shared access to the world is needed,
+    /// so different resources could be changed by parallel systems.
+    ///
+    #[track_caller]
+    pub fn resource_mut<T>(&self) -> Option<ResMut<'_, T>>
+    where
+        T: Resource,
+    {
+        self.resources.get_mut()
+    }
+
+    /// Storage of all resources of this world.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Mutable storage of all resources of this world.
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        &mut self.resources
+    }
+}
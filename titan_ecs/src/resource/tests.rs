@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use titan_tasks::TaskPool;
+
+use crate::{Schedule, World};
+
+#[derive(Debug, PartialEq)]
+struct Gravity(f32);
+
+#[derive(Debug, Default, PartialEq)]
+struct Score(u32);
+
+#[derive(Debug, PartialEq)]
+struct Lives(u32);
+
+#[test]
+fn test_resources() {
+    let mut world = World::new();
+    assert!(!world.contains_resource::<Gravity>());
+    assert_eq!(world.insert_resource(Gravity(9.8)), None);
+    assert!(world.contains_resource::<Gravity>());
+    assert_eq!(world.resource::<Gravity>().as_deref(), Some(&Gravity(9.8)));
+
+    world.resource_mut::<Gravity>().unwrap().0 = 1.6;
+    assert_eq!(world.insert_resource(Gravity(3.7)), Some(Gravity(1.6)));
+    assert_eq!(world.remove_resource::<Gravity>(), Some(Gravity(3.7)));
+    assert!(world.resource::<Gravity>().is_none());
+    assert_eq!(world.remove_resource::<Gravity>(), None);
+}
+
+#[test]
+fn test_borrows() {
+    let mut world = World::new();
+    world.insert_resource(Gravity(9.8));
+    world.insert_resource(Score::default());
+    assert_eq!(world.resources().len(), 2);
+
+    // Different resources are borrowed through the shared world at the same time.
+    let gravity = world.resource::<Gravity>().unwrap();
+    let same_gravity = world.resource::<Gravity>().unwrap();
+    let mut score = world.resource_mut::<Score>().unwrap();
+    score.0 += gravity.0 as u32 + same_gravity.0 as u32;
+    assert_eq!(format!("{:?}", score), "Score(18)");
+    drop((gravity, same_gravity, score));
+
+    world.resources_mut().remove::<Gravity>();
+    assert!(!world.resources().contains::<Gravity>());
+    assert_eq!(
+        world.resources().get::<Score>().as_deref(),
+        Some(&Score(18))
+    );
+}
+
+#[test]
+#[should_panic(expected = "already borrowed")]
+fn test_mutable_reborrow() {
+    let mut world = World::new();
+    world.insert_resource(Score::default());
+
+    let _score = world.resource_mut::<Score>().unwrap();
+    // Second borrow panics instead of waiting for the first one forever.
+    let _same_score = world.resource_mut::<Score>();
+}
+
+#[test]
+#[should_panic(expected = "already borrowed")]
+fn test_shared_reborrow() {
+    let mut world = World::new();
+    world.insert_resource(Gravity(9.8));
+
+    let _gravity = world.resource::<Gravity>().unwrap();
+    let _same_gravity = world.resource_mut::<Gravity>();
+}
+
+#[test]
+fn test_parallel_systems() {
+    let mut world = World::new();
+    world.insert_resource(Gravity(2.0));
+    world.insert_resource(Score::default());
+    world.insert_resource(Lives(3));
+    world.insert_resource(TaskPool::new(2, 1));
+
+    // Systems share the same resource, but change only different ones.
+    let mut schedule = Schedule::new();
+    schedule.add_parallel_system(|world| {
+        let gravity = world.resource::<Gravity>().unwrap();
+        world.resource_mut::<Score>().unwrap().0 += gravity.0 as u32;
+    });
+    schedule.add_parallel_system(|world| {
+        let gravity = world.resource::<Gravity>().unwrap();
+        world.resource_mut::<Lives>().unwrap().0 -= gravity.0 as u32;
+    });
+    schedule.add_system(|context| {
+        let lives = context.resource::<Lives>().unwrap().0;
+        context.resource_mut::<Score>().unwrap().0 *= 10 + lives;
+    });
+
+    schedule.run(&mut world);
+    assert_eq!(world.remove_resource::<Score>(), Some(Score(22)));
+}
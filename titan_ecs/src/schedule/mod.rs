@@ -0,0 +1,287 @@
+//! Utilities for scheduling of *systems* in ECS.
+
+use titan_tasks::TaskPool;
+
+use crate::{Component, Entity, Query, QueryData, QueryFilter, Res, ResMut, Resource, Tick, World};
+
+pub use stage::SystemStage;
+
+mod stage;
+mod tests;
+
+type SystemFn = Box<dyn FnMut(&mut SystemContext)>;
+type ParallelSystemFn = Box<dyn FnMut(&World) + Send>;
+type ConditionFn = Box<dyn FnMut(&World) -> bool>;
+
+/// Function of the system which is run by the schedule.
+enum SystemRun {
+    /// System with exclusive access to the world.
+    Exclusive(SystemFn),
+    /// System with shared access to the world, which could run in parallel with others.
+    Parallel(ParallelSystemFn),
+    /// Stage of systems which run in parallel if they access different components.
+    Stage(SystemStage),
+}
+
+/// System added into the schedule.
+struct ScheduledSystem {
+    run: SystemRun,
+    condition: Option<ConditionFn>,
+    /// Tick of the last run of the system, if it was run at least once.
+    last_run: Option<Tick>,
+}
+
+/// Ordered collection of systems which are run on the world each frame.
+#[derive(Default)]
+pub struct Schedule {
+    systems: Vec<ScheduledSystem>,
+}
+
+impl Schedule {
+    /// Creates an empty schedule.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds system which will be run each time the schedule runs.
+    pub fn add_system<F>(&mut self, system: F) -> &mut Self
+    where
+        F: FnMut(&mut SystemContext) + 'static,
+    {
+        self.systems.push(ScheduledSystem {
+            run: SystemRun::Exclusive(Box::new(system)),
+            condition: None,
+            last_run: None,
+        });
+        self
+    }
+
+    /// Adds system which only reads the world, so it could run in parallel with others.
+    ///
+    /// Consecutive parallel systems are run on [`TaskPool`] resource of the world,
+    /// or one after another if the world has no such resource.
+    /// Resource which is changed by one of them must not be borrowed by others
+    /// (see [`Resources`](crate::Resources)).
+    ///
+    pub fn add_parallel_system<F>(&mut self, system: F) -> &mut Self
+    where
+        F: FnMut(&World) + Send + 'static,
+    {
+        self.systems.push(ScheduledSystem {
+            run: SystemRun::Parallel(Box::new(system)),
+            condition: None,
+            last_run: None,
+        });
+        self
+    }
+
+    /// Adds stage of systems which are run in parallel if their access does not conflict
+    /// (see [`SystemStage`]).
+    pub fn add_stage(&mut self, stage: SystemStage) -> &mut Self {
+        self.systems.push(ScheduledSystem {
+            run: SystemRun::Stage(stage),
+            condition: None,
+            last_run: None,
+        });
+        self
+    }
+
+    /// Adds system which will be run only if condition returns `true`.
+    ///
+    /// Changes made while the system was not run are seen by its next run.
+    ///
+    pub fn add_system_if<C, F>(&mut self, condition: C, system: F) -> &mut Self
+    where
+        C: FnMut(&World) -> bool + 'static,
+        F: FnMut(&mut SystemContext) + 'static,
+    {
+        self.systems.push(ScheduledSystem {
+            run: SystemRun::Exclusive(Box::new(system)),
+            condition: Some(Box::new(condition)),
+            last_run: None,
+        });
+        self
+    }
+
+    /// Count of systems in the schedule.
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    /// Returns `true` if there are no systems in the schedule.
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Runs all systems on the world in order of their addition.
+    ///
+    /// Each run of the exclusive system, the batch of consecutive parallel systems
+    /// or the batch of the stage increments tick of the world.
+    /// [Commands](crate::Commands) queued by systems are applied after each of them.
+    ///
+    pub fn run(&mut self, world: &mut World) {
+        let mut batch = Vec::new();
+        for system in &mut self.systems {
+            if let Some(condition) = &mut system.condition {
+                if !condition(world) {
+                    continue;
+                }
+            }
+
+            let run = match &mut system.run {
+                SystemRun::Exclusive(run) => run,
+                SystemRun::Parallel(run) => {
+                    batch.push(run);
+                    continue;
+                }
+                SystemRun::Stage(stage) => {
+                    Self::run_batch(world, &mut batch);
+                    stage.run(world);
+                    continue;
+                }
+            };
+            Self::run_batch(world, &mut batch);
+
+            let this_run = world.increment_change_tick();
+            let last_run = system.last_run.unwrap_or_else(|| Tick::oldest(this_run));
+            let mut context = SystemContext {
+                world,
+                last_run,
+                this_run,
+            };
+            run(&mut context);
+            world.apply_commands();
+            system.last_run = Some(this_run);
+        }
+        Self::run_batch(world, &mut batch);
+
+        // Changes made outside of the schedule will be seen by its next run.
+        world.increment_change_tick();
+        if let Some(this_run) = world.check_change_ticks() {
+            let last_runs = self
+                .systems
+                .iter_mut()
+                .filter_map(|system| system.last_run.as_mut());
+            for last_run in last_runs {
+                last_run.check(this_run);
+            }
+            let stages = self
+                .systems
+                .iter_mut()
+                .filter_map(|system| match &mut system.run {
+                    SystemRun::Stage(stage) => Some(stage),
+                    _ => None,
+                });
+            for stage in stages {
+                stage.check_ticks(this_run);
+            }
+        }
+    }
+
+    /// Runs batch of parallel systems with the same tick, then clears the batch.
+    fn run_batch(world: &mut World, batch: &mut Vec<&mut ParallelSystemFn>) {
+        if batch.is_empty() {
+            return;
+        }
+        world.increment_change_tick();
+        let shared = &*world;
+        match shared.resource::<TaskPool>() {
+            Some(pool) if batch.len() > 1 => pool.scope(|scope| {
+                for run in batch.drain(..) {
+                    scope.spawn(move || run(shared));
+                }
+            }),
+            _ => batch.drain(..).for_each(|run| run(shared)),
+        }
+        world.apply_commands();
+    }
+}
+
+/// Context of the system which is currently run by the [`Schedule`].
+pub struct SystemContext<'w> {
+    world: &'w mut World,
+    last_run: Tick,
+    this_run: Tick,
+}
+
+impl SystemContext<'_> {
+    /// World the system is run on.
+    pub fn world(&self) -> &World {
+        self.world
+    }
+
+    /// Mutable world the system is run on.
+    pub fn world_mut(&mut self) -> &mut World {
+        self.world
+    }
+
+    /// Tick of the previous run of the system.
+    pub fn last_run(&self) -> Tick {
+        self.last_run
+    }
+
+    /// Tick of the current run of the system.
+    pub fn this_run(&self) -> Tick {
+        self.this_run
+    }
+
+    /// Borrows resource of type `T` of the world immutably.
+    #[track_caller]
+    pub fn resource<T>(&self) -> Option<Res<'_, T>>
+    where
+        T: Resource,
+    {
+        self.world.resource()
+    }
+
+    /// Borrows resource of type `T` of the world mutably.
+    #[track_caller]
+    pub fn resource_mut<T>(&self) -> Option<ResMut<'_, T>>
+    where
+        T: Resource,
+    {
+        self.world.resource_mut()
+    }
+
+    /// Calls the function for each entity of the query,
+    /// which passes its filter since the previous run of the system.
+    ///
+    /// Components which are accessed exclusively are marked as changed.
+    ///
+    pub fn query<Q, F, Func>(&mut self, query: &Query<Q, F>, f: Func)
+    where
+        Q: QueryData,
+        F: QueryFilter,
+        Func: for<'q> FnMut(Entity, Q::Item<'q>),
+    {
+        query.for_each_since(self.world, self.last_run, f)
+    }
+
+    /// Returns iterator over components of type `T`
+    /// which were added since the previous run of the system.
+    pub fn query_added<T>(&self) -> impl Iterator<Item = (Entity, &T)>
+    where
+        T: Component,
+    {
+        let (last_run, this_run) = (self.last_run, self.this_run);
+        self.world
+            .component_manager()
+            .get_storage::<T>()
+            .into_iter()
+            .flat_map(move |storage| storage.iter_added(last_run, this_run))
+    }
+
+    /// Returns iterator over components of type `T`
+    /// which were added or changed since the previous run of the system.
+    pub fn query_changed<T>(&self) -> impl Iterator<Item = (Entity, &T)>
+    where
+        T: Component,
+    {
+        let (last_run, this_run) = (self.last_run, self.this_run);
+        self.world
+            .component_manager()
+            .get_storage::<T>()
+            .into_iter()
+            .flat_map(move |storage| storage.iter_changed(last_run, this_run))
+    }
+}
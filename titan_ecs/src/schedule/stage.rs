@@ -0,0 +1,248 @@
+//! Stages of systems which run in parallel if they access different components.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use titan_tasks::TaskPool;
+
+use crate::query::StorageRef;
+use crate::{ComponentAccess, Entity, Query, QueryData, QueryFilter, Tick, World};
+
+type StageSystemFn = Box<dyn FnMut(Vec<StorageRef<'_>>, Tick, Option<Vec<Entity>>) + Send>;
+type FilterFn = fn(&World, Tick, Tick) -> Option<Vec<Entity>>;
+
+/// System of the stage which is run for each entity of its query.
+struct StageSystem {
+    access: Vec<ComponentAccess>,
+    /// Access of the filter of the query, which only reads components.
+    filter_access: Vec<ComponentAccess>,
+    filter: FilterFn,
+    run: StageSystemFn,
+    /// Index of the batch the system is run in.
+    batch: usize,
+    /// Tick of the last run of the system, if it was run at least once.
+    last_run: Option<Tick>,
+}
+
+impl StageSystem {
+    /// Returns `true` if the system must be run in another batch than the given one.
+    fn conflicts(&self, other: &StageSystem) -> bool {
+        self::conflicts(&self.access, &other.access)
+            || self::conflicts(&self.access, &other.filter_access)
+            || self::conflicts(&self.filter_access, &other.access)
+    }
+}
+
+/// Returns `true` if one of the systems accesses components mutably
+/// which are also accessed by another system.
+fn conflicts(first: &[ComponentAccess], second: &[ComponentAccess]) -> bool {
+    first.iter().any(|first| {
+        second
+            .iter()
+            .any(|second| first.typeid == second.typeid && (first.exclusive || second.exclusive))
+    })
+}
+
+/// Set of systems which are run in parallel if their access to components does not conflict.
+///
+/// Each system of the stage is run for each entity of its [`Query`],
+/// filter of which is checked since the previous run of the system.
+/// Systems are split into batches by access of their queries: the system is run
+/// after all previously added systems which conflict with it, and together with
+/// other systems of its batch on [`TaskPool`] resource of the world
+/// (or one after another if the world has no such resource).
+///
+/// ```
+/// # use titan_ecs::{Query, SystemStage, World};
+/// struct Position(f32);
+/// struct Velocity(f32);
+/// struct Health(u32);
+///
+/// let mut world = World::new();
+/// let entity = world.spawn();
+/// world.insert(entity, Position(0.0));
+/// world.insert(entity, Velocity(1.0));
+/// world.insert(entity, Health(10));
+///
+/// let movement = Query::<(&mut Position, &Velocity)>::new().unwrap();
+/// let damage = Query::<(&mut Health,)>::new().unwrap();
+/// let mut stage = SystemStage::new();
+/// stage
+///     .add_system(movement, |_, (position, velocity)| position.0 += velocity.0)
+///     .add_system(damage, |_, (health,)| health.0 -= 1);
+/// // Systems access different components, so they are run in parallel.
+/// assert_eq!(stage.batches(), vec![vec![0, 1]]);
+///
+/// stage.run(&mut world);
+/// assert_eq!(world.get::<Position>(entity).unwrap().0, 1.0);
+/// assert_eq!(world.get::<Health>(entity).unwrap().0, 9);
+/// ```
+///
+#[derive(Default)]
+pub struct SystemStage {
+    systems: Vec<StageSystem>,
+}
+
+impl SystemStage {
+    /// Creates an empty stage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds system which will be run for each entity of the query.
+    ///
+    /// Components which are accessed exclusively by the query are marked as changed.
+    /// Systems which change components of the filter of the query are run
+    /// in earlier or later batches, so all changes are seen by the system.
+    ///
+    pub fn add_system<Q, F, Func>(&mut self, query: Query<Q, F>, mut system: Func) -> &mut Self
+    where
+        Q: QueryData + 'static,
+        F: QueryFilter + 'static,
+        Func: for<'w> FnMut(Entity, Q::Item<'w>) + Send + 'static,
+    {
+        let mut new = StageSystem {
+            access: query.access(),
+            filter_access: query.filter_access(),
+            filter: F::filter,
+            run: Box::new(move |storages, change_tick, entities| {
+                Q::for_each_in(storages, change_tick, entities, &mut system)
+            }),
+            batch: 0,
+            last_run: None,
+        };
+        new.batch = self
+            .systems
+            .iter()
+            .filter(|other| new.conflicts(other))
+            .map(|other| other.batch + 1)
+            .max()
+            .unwrap_or_default();
+        self.systems.push(new);
+        self
+    }
+
+    /// Count of systems in the stage.
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    /// Returns `true` if there are no systems in the stage.
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Indices of systems (in order of their addition) of each batch of the stage.
+    ///
+    /// Batches are run one after another, and systems of one batch are run in parallel.
+    ///
+    pub fn batches(&self) -> Vec<Vec<usize>> {
+        let mut batches = Vec::new();
+        for (index, system) in self.systems.iter().enumerate() {
+            if batches.len() <= system.batch {
+                batches.resize_with(system.batch + 1, Vec::new);
+            }
+            batches[system.batch].push(index);
+        }
+        batches
+    }
+
+    /// Runs all batches of systems on the world.
+    ///
+    /// Each batch increments tick of the world.
+    /// [Commands](crate::Commands) queued by systems are applied after each batch.
+    ///
+    pub fn run(&mut self, world: &mut World) {
+        let batch_count = self.systems.iter().map(|system| system.batch + 1).max();
+        for batch in 0..batch_count.unwrap_or_default() {
+            let systems = self
+                .systems
+                .iter_mut()
+                .filter(|system| system.batch == batch)
+                .collect();
+            Self::run_batch(world, systems);
+            world.apply_commands();
+        }
+    }
+
+    /// Clamps ticks of the last runs of systems relative to `this_run` (see [`Tick::check`]).
+    pub(crate) fn check_ticks(&mut self, this_run: Tick) {
+        let last_runs = self
+            .systems
+            .iter_mut()
+            .filter_map(|system| system.last_run.as_mut());
+        for last_run in last_runs {
+            last_run.check(this_run);
+        }
+    }
+
+    /// Borrows storages of components for each system of the batch and runs them.
+    fn run_batch(world: &mut World, mut systems: Vec<&mut StageSystem>) {
+        let change_tick = world.increment_change_tick();
+        // Filters are checked before the batch, because its systems do not change
+        // components of filters of each other.
+        let filtered: Vec<_> = systems
+            .iter_mut()
+            .map(|system| {
+                let last_run = system.last_run.unwrap_or_else(|| Tick::oldest(change_tick));
+                system.last_run = Some(change_tick);
+                (system.filter)(world, last_run, change_tick)
+            })
+            .collect();
+        let (manager, pool) = world.component_manager_with_resource::<TaskPool>();
+
+        // Systems and their query parameters which access each component type.
+        let mut users = HashMap::<TypeId, Vec<(usize, usize)>>::new();
+        for (index, system) in systems.iter().enumerate() {
+            for (param, access) in system.access.iter().enumerate() {
+                users.entry(access.typeid).or_default().push((index, param));
+            }
+        }
+        let mut storages: Vec<Vec<_>> = systems
+            .iter()
+            .map(|system| system.access.iter().map(|_| None).collect())
+            .collect();
+        for (typeid, storage) in manager.storages_mut() {
+            let users = match users.get(&typeid) {
+                Some(users) => users,
+                None => continue,
+            };
+            match users[..] {
+                // Systems of the batch do not conflict, so the exclusive user is the only one.
+                [(index, param)] if systems[index].access[param].exclusive => {
+                    storages[index][param] = Some(StorageRef::Exclusive(storage));
+                }
+                _ => {
+                    let storage = &*storage;
+                    for &(index, param) in users {
+                        debug_assert!(!systems[index].access[param].exclusive);
+                        storages[index][param] = Some(StorageRef::Shared(storage));
+                    }
+                }
+            }
+        }
+
+        // There are no entities with all components if any storage of the system is missing.
+        let runs: Vec<_> = systems
+            .iter_mut()
+            .zip(storages)
+            .zip(filtered)
+            .filter_map(|((system, storages), entities)| {
+                let storages = storages.into_iter().collect::<Option<Vec<_>>>()?;
+                Some((&mut system.run, storages, entities))
+            })
+            .collect();
+        match pool {
+            Some(pool) if runs.len() > 1 => pool.scope(|scope| {
+                for (run, storages, entities) in runs {
+                    scope.spawn(move || run(storages, change_tick, entities));
+                }
+            }),
+            _ => {
+                for (run, storages, entities) in runs {
+                    run(storages, change_tick, entities);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+#![cfg(test)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use titan_tasks::TaskPool;
+
+use crate::{Changed, Entity, Query, Schedule, SystemStage, Tick, World};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Mesh(u32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Velocity(u32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Health(u32);
+
+/// Creates system which records entities with changed meshes on each run.
+fn recorder(schedule: &mut Schedule, every_other_frame: bool) -> Rc<RefCell<Vec<Vec<Entity>>>> {
+    let runs = Rc::new(RefCell::new(Vec::new()));
+    let system_runs = runs.clone();
+    let system = move |context: &mut crate::SystemContext| {
+        let changed = context.query_changed::<Mesh>().map(|(entity, _)| entity);
+        system_runs.borrow_mut().push(changed.collect());
+    };
+    if every_other_frame {
+        let mut frame = 0;
+        schedule.add_system_if(
+            move |_: &World| {
+                frame += 1;
+                frame % 2 == 1
+            },
+            system,
+        );
+    } else {
+        schedule.add_system(system);
+    }
+    runs
+}
+
+#[test]
+fn test_changed_same_frame() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Mesh(0));
+
+    let mut schedule = Schedule::new();
+    schedule.add_system(move |context| {
+        if context.world().get::<Mesh>(entity) == Some(&Mesh(1)) {
+            context.world_mut().get_mut::<Mesh>(entity).unwrap().0 = 2;
+        }
+    });
+    let runs = self::recorder(&mut schedule, false);
+
+    schedule.run(&mut world);
+    assert_eq!(runs.borrow()[0], vec![entity]);
+
+    schedule.run(&mut world);
+    assert_eq!(runs.borrow()[1], vec![]);
+
+    world.get_mut::<Mesh>(entity).unwrap().0 = 1;
+    schedule.run(&mut world);
+    assert_eq!(runs.borrow()[2], vec![entity]);
+    assert_eq!(world.get::<Mesh>(entity), Some(&Mesh(2)));
+}
+
+#[test]
+fn test_unchanged_next_frame() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Mesh(0));
+
+    let mut schedule = Schedule::new();
+    let runs = self::recorder(&mut schedule, false);
+    schedule.add_system(|context| {
+        let count = context.query_added::<Mesh>().count();
+        assert!(count <= 1);
+    });
+
+    schedule.run(&mut world);
+    // Immutable access must not mark anything as changed.
+    assert_eq!(world.get::<Mesh>(entity), Some(&Mesh(0)));
+    assert_eq!(world.query::<Mesh>().count(), 1);
+    schedule.run(&mut world);
+
+    for (_, mesh) in world.query_mut::<Mesh>() {
+        mesh.0 += 1;
+    }
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    let runs = runs.borrow();
+    assert_eq!(*runs, vec![vec![entity], vec![], vec![entity], vec![]]);
+}
+
+#[test]
+fn test_system_every_other_frame() {
+    let mut world = World::new();
+    let first = world.spawn();
+    let second = world.spawn();
+    world.insert(first, Mesh(0));
+    world.insert(second, Mesh(0));
+
+    let mut schedule = Schedule::new();
+    let runs = self::recorder(&mut schedule, true);
+
+    // Frame 1: system runs and sees initial meshes.
+    schedule.run(&mut world);
+    // Frame 2: system does not run, but the change must be kept for it.
+    world.get_mut::<Mesh>(second).unwrap().0 = 1;
+    schedule.run(&mut world);
+    // Frame 3: system runs and sees the change once.
+    schedule.run(&mut world);
+    // Frame 4 and 5: nothing changed.
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    let runs = runs.borrow();
+    assert_eq!(*runs, vec![vec![first, second], vec![second], vec![]]);
+}
+
+#[test]
+fn test_tick_wraparound() {
+    let last_run = Tick::new(u32::MAX - 1);
+    let this_run = Tick::new(1);
+    assert!(Tick::new(u32::MAX).is_newer_than(last_run, this_run));
+    assert!(Tick::new(0).is_newer_than(last_run, this_run));
+    assert!(Tick::new(1).is_newer_than(last_run, this_run));
+    assert!(!Tick::new(u32::MAX - 1).is_newer_than(last_run, this_run));
+    assert!(!Tick::new(u32::MAX - 10).is_newer_than(last_run, this_run));
+
+    let mut old = Tick::new(2);
+    old.check(this_run);
+    assert!(!old.is_newer_than(last_run, this_run));
+}
+
+#[test]
+fn test_parallel_systems() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Mesh(1));
+    world.insert_resource(TaskPool::new(2, 1));
+
+    let mut schedule = Schedule::new();
+    let sum = Arc::new(AtomicU32::new(0));
+    for _ in 0..3 {
+        let sum = sum.clone();
+        schedule.add_parallel_system(move |world| {
+            let meshes = world.query::<Mesh>().map(|(_, mesh)| mesh.0);
+            sum.fetch_add(meshes.sum(), Ordering::Relaxed);
+        });
+    }
+    // Exclusive system sees results of parallel systems before it.
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let system_seen = seen.clone();
+    let system_sum = sum.clone();
+    schedule.add_system(move |context| {
+        system_seen
+            .borrow_mut()
+            .push(system_sum.load(Ordering::Relaxed));
+        context.world_mut().get_mut::<Mesh>(entity).unwrap().0 += 1;
+    });
+    let single = sum.clone();
+    schedule.add_parallel_system(move |world| {
+        single.fetch_add(
+            world.get::<Mesh>(entity).unwrap().0 * 100,
+            Ordering::Relaxed,
+        );
+    });
+
+    schedule.run(&mut world);
+    assert_eq!(*seen.borrow(), vec![3]);
+    assert_eq!(sum.load(Ordering::Relaxed), 203);
+
+    // Parallel systems also run one after another without task pool.
+    world.remove_resource::<TaskPool>();
+    schedule.run(&mut world);
+    assert_eq!(*seen.borrow(), vec![3, 209]);
+    assert_eq!(sum.load(Ordering::Relaxed), 509);
+}
+
+#[test]
+fn test_stage_batches() {
+    let mut stage = SystemStage::new();
+    stage
+        .add_system(Query::<(&mut Mesh, &Velocity)>::new().unwrap(), |_, _| ())
+        .add_system(Query::<(&Velocity,)>::new().unwrap(), |_, _| ())
+        .add_system(Query::<(&mut Health,)>::new().unwrap(), |_, _| ())
+        .add_system(Query::<(&Mesh, &Health)>::new().unwrap(), |_, _| ())
+        .add_system(Query::<(&mut Velocity,)>::new().unwrap(), |_, _| ());
+    assert_eq!(stage.len(), 5);
+    assert_eq!(stage.batches(), vec![vec![0, 1, 2], vec![3, 4]]);
+}
+
+#[test]
+fn test_stage_systems() {
+    let mut world = World::new();
+    let moving = world.spawn();
+    world.insert(moving, Mesh(0));
+    world.insert(moving, Velocity(2));
+    world.insert(moving, Health(10));
+    let still = world.spawn();
+    world.insert(still, Mesh(5));
+    world.insert(still, Health(5));
+    world.insert_resource(TaskPool::new(2, 1));
+
+    let mut stage = SystemStage::new();
+    let totals = Arc::new(AtomicU32::new(0));
+    let system_totals = totals.clone();
+    stage
+        .add_system(
+            Query::<(&mut Mesh, &Velocity)>::new().unwrap(),
+            |_, (mesh, velocity)| mesh.0 += velocity.0,
+        )
+        .add_system(Query::<(&mut Health,)>::new().unwrap(), |_, (health,)| {
+            health.0 -= 1
+        })
+        // Runs after both systems above, so sees their changes.
+        .add_system(
+            Query::<(&Mesh, &Health)>::new().unwrap(),
+            move |_, (mesh, health)| {
+                system_totals.fetch_add(mesh.0 * 100 + health.0, Ordering::Relaxed);
+            },
+        );
+
+    let mut schedule = Schedule::new();
+    schedule.add_stage(stage);
+    let changed = Rc::new(RefCell::new(Vec::new()));
+    let system_changed = changed.clone();
+    schedule.add_system(move |context| {
+        let mut meshes: Vec<_> = context.query_changed::<Mesh>().map(|(e, _)| e).collect();
+        meshes.sort();
+        system_changed.borrow_mut().push(meshes);
+    });
+
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Mesh>(moving), Some(&Mesh(2)));
+    assert_eq!(world.get::<Health>(still), Some(&Health(4)));
+    assert_eq!(totals.load(Ordering::Relaxed), 209 + 504);
+
+    // Only meshes which were accessed mutably are marked as changed.
+    schedule.run(&mut world);
+    assert_eq!(changed.borrow()[1], vec![moving]);
+
+    // Systems also run one after another without task pool.
+    world.remove_resource::<TaskPool>();
+    totals.store(0, Ordering::Relaxed);
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Mesh>(moving), Some(&Mesh(6)));
+    assert_eq!(world.get::<Health>(moving), Some(&Health(7)));
+    assert_eq!(totals.load(Ordering::Relaxed), 607 + 502);
+}
+
+#[test]
+fn test_stage_filters() {
+    let mut world = World::new();
+    let first = world.spawn();
+    world.insert(first, Mesh(0));
+    world.insert(first, Health(10));
+    let second = world.spawn();
+    world.insert(second, Mesh(0));
+    world.insert(second, Health(10));
+
+    let mut stage = SystemStage::new();
+    stage
+        .add_system(Query::<(&mut Velocity,)>::new().unwrap(), |_, _| ())
+        // Reads meshes to check their ticks, so it runs after the system which changes them.
+        .add_system(
+            Query::<(&mut Health,), Changed<Mesh>>::new().unwrap(),
+            |_, (health,)| health.0 -= 1,
+        )
+        .add_system(Query::<(&mut Mesh,)>::new().unwrap(), |_, _| ());
+    assert_eq!(stage.batches(), vec![vec![0, 1], vec![2]]);
+
+    let mut stage = SystemStage::new();
+    stage.add_system(
+        Query::<(&mut Health,), Changed<Mesh>>::new().unwrap(),
+        |_, (health,)| health.0 -= 1,
+    );
+    let mut schedule = Schedule::new();
+    schedule.add_stage(stage);
+
+    // Added meshes are seen as changed by the first run.
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Health>(first), Some(&Health(9)));
+    assert_eq!(world.get::<Health>(second), Some(&Health(9)));
+
+    world.get_mut::<Mesh>(second).unwrap().0 = 1;
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Health>(first), Some(&Health(9)));
+    assert_eq!(world.get::<Health>(second), Some(&Health(8)));
+}
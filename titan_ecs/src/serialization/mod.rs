@@ -0,0 +1,539 @@
+//! Utilities for serialization of entities and components of ECS.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, SerializeTuple};
+use serde::{Deserializer, Serialize, Serializer};
+use slotmap::{Key, KeyData};
+
+use crate::{Component, Entity, World};
+
+mod tests;
+
+/// Table which translates entities of serialized world into entities of loaded world.
+#[derive(Debug, Default, Clone)]
+pub struct EntityMap {
+    map: HashMap<Entity, Entity>,
+}
+
+impl EntityMap {
+    /// Retrieves loaded entity by entity of serialized world.
+    pub fn get(&self, entity: Entity) -> Option<Entity> {
+        self.map.get(&entity).copied()
+    }
+
+    /// Translates entity of serialized world into entity of loaded world.
+    ///
+    /// Entity which was not serialized is translated into null entity,
+    /// which is never contained by any world: otherwise the reference could point
+    /// at unrelated entity of the loaded world with the same identifier.
+    ///
+    pub fn map(&self, entity: Entity) -> Entity {
+        self.get(entity).unwrap_or_else(Entity::null)
+    }
+
+    /// Count of entities in the table.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if there are no entities in the table.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// Objects of this trait are components which store references to other entities.
+///
+/// These references will be translated when world is loaded,
+/// because loaded entities have different identifiers.
+///
+pub trait MapEntities {
+    /// Translates all stored entities with provided table.
+    fn map_entities(&mut self, map: &EntityMap);
+}
+
+type SerializeFn = for<'a> fn(&'a World, Entity) -> Option<&'a dyn erased_serde::Serialize>;
+type DeserializeFn = for<'de> fn(
+    &mut dyn erased_serde::Deserializer<'de>,
+) -> Result<Box<dyn Any>, erased_serde::Error>;
+type InsertFn = fn(&mut World, Entity, Box<dyn Any>);
+type MapEntitiesFn = fn(&mut World, Entity, &EntityMap);
+
+/// Registered type of components with its stable name.
+struct Registration {
+    name: &'static str,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+    insert: InsertFn,
+    map_entities: Option<MapEntitiesFn>,
+}
+
+fn serialize_component<T>(world: &World, entity: Entity) -> Option<&dyn erased_serde::Serialize>
+where
+    T: Component + Serialize,
+{
+    let component = world.get::<T>(entity)?;
+    Some(component)
+}
+
+fn deserialize_component<T>(
+    deserializer: &mut dyn erased_serde::Deserializer,
+) -> Result<Box<dyn Any>, erased_serde::Error>
+where
+    T: Component + DeserializeOwned,
+{
+    let component: T = erased_serde::deserialize(deserializer)?;
+    Ok(Box::new(component))
+}
+
+fn insert_component<T>(world: &mut World, entity: Entity, component: Box<dyn Any>)
+where
+    T: Component,
+{
+    let component = component.downcast::<T>().expect("downcast error");
+    world.insert(entity, *component);
+}
+
+fn map_component_entities<T>(world: &mut World, entity: Entity, map: &EntityMap)
+where
+    T: Component + MapEntities,
+{
+    if let Some(component) = world.get_mut::<T>(entity) {
+        component.map_entities(map);
+    }
+}
+
+/// Serializer of entities and components of the world.
+///
+/// Only components of registered types are serialized.
+/// Each type is registered with stable name which is written instead of the type.
+///
+#[derive(Default)]
+pub struct WorldSerializer {
+    registrations: Vec<Registration>,
+    by_name: HashMap<&'static str, usize>,
+    by_type: HashMap<TypeId, usize>,
+}
+
+impl WorldSerializer {
+    /// Creates new serializer without registered component types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers component type `T` with given stable name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if type or name was already registered.
+    ///
+    pub fn register<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.add::<T>(Registration {
+            name,
+            serialize: self::serialize_component::<T>,
+            deserialize: self::deserialize_component::<T>,
+            insert: self::insert_component::<T>,
+            map_entities: None,
+        })
+    }
+
+    /// Registers component type `T` which stores references to other entities
+    /// with given stable name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if type or name was already registered.
+    ///
+    pub fn register_mapped<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.add::<T>(Registration {
+            name,
+            serialize: self::serialize_component::<T>,
+            deserialize: self::deserialize_component::<T>,
+            insert: self::insert_component::<T>,
+            map_entities: Some(self::map_component_entities::<T>),
+        })
+    }
+
+    fn add<T>(&mut self, registration: Registration) -> &mut Self
+    where
+        T: Component,
+    {
+        let typeid = TypeId::of::<T>();
+        assert!(
+            !self.by_type.contains_key(&typeid),
+            "component type {} is already registered",
+            std::any::type_name::<T>(),
+        );
+        assert!(
+            !self.by_name.contains_key(registration.name),
+            "component name \"{}\" is already registered",
+            registration.name,
+        );
+
+        let index = self.registrations.len();
+        self.by_type.insert(typeid, index);
+        self.by_name.insert(registration.name, index);
+        self.registrations.push(registration);
+        self
+    }
+
+    /// Returns serializable view of the world.
+    pub fn view<'a>(&'a self, world: &'a World) -> WorldView<'a> {
+        WorldView {
+            serializer: self,
+            world,
+        }
+    }
+
+    /// Serializes all entities of the world with components of registered types.
+    ///
+    /// Components of unregistered types are skipped with a warning.
+    ///
+    pub fn serialize<S>(&self, world: &World, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.view(world).serialize(serializer)
+    }
+
+    /// Creates deserializer which will use component types registered in this serializer.
+    ///
+    /// Deserializer is strict by default (see [`WorldDeserializer::strict`]).
+    ///
+    pub fn deserializer(&self) -> WorldDeserializer<'_> {
+        WorldDeserializer {
+            serializer: self,
+            strict: true,
+        }
+    }
+
+    /// Logs names of component types which are used in the world but were not registered.
+    fn warn_unregistered(&self, world: &World) {
+        let unregistered: Vec<_> = world
+            .component_manager()
+            .storages()
+            .filter(|(typeid, storage)| !storage.is_empty() && !self.by_type.contains_key(typeid))
+            .map(|(_, storage)| storage.type_name())
+            .collect();
+        if !unregistered.is_empty() {
+            log::warn!(
+                "skipping components of unregistered types: {}",
+                unregistered.join(", "),
+            );
+        }
+    }
+}
+
+/// Serializable view of the world (see [`WorldSerializer::view`]).
+pub struct WorldView<'a> {
+    serializer: &'a WorldSerializer,
+    world: &'a World,
+}
+
+impl Serialize for WorldView<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serializer.warn_unregistered(self.world);
+
+        let mut seq = serializer.serialize_seq(Some(self.world.len()))?;
+        for entity in self.world.entities() {
+            let entity = EntityView {
+                serializer: self.serializer,
+                world: self.world,
+                entity,
+            };
+            seq.serialize_element(&entity)?;
+        }
+        seq.end()
+    }
+}
+
+/// Serializable view of the entity with its components.
+struct EntityView<'a> {
+    serializer: &'a WorldSerializer,
+    world: &'a World,
+    entity: Entity,
+}
+
+impl Serialize for EntityView<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components: Vec<_> = self
+            .serializer
+            .registrations
+            .iter()
+            .filter_map(|registration| {
+                let component = (registration.serialize)(self.world, self.entity)?;
+                Some((registration.name, component))
+            })
+            .collect();
+
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.entity.data().as_ffi())?;
+        tuple.serialize_element(&components)?;
+        tuple.end()
+    }
+}
+
+/// Deserializer of entities and components into the world.
+pub struct WorldDeserializer<'a> {
+    serializer: &'a WorldSerializer,
+    strict: bool,
+}
+
+impl<'a> WorldDeserializer<'a> {
+    /// Sets strictness of this deserializer.
+    ///
+    /// Strict deserializer fails on component names which were not registered,
+    /// otherwise such components are skipped with a warning.
+    /// Note that skipping is supported only by self-describing formats.
+    ///
+    pub fn strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// Loads serialized entities with their components into the world.
+    ///
+    /// Loaded entities are new entities of the world, so references to entities
+    /// in components registered by [`WorldSerializer::register_mapped`] are translated.
+    ///
+    /// Returns loaded entities in order of serialization.
+    /// If loading fails, entities which were already loaded are despawned.
+    ///
+    pub fn load<'de, D>(&self, world: &mut World, deserializer: D) -> Result<Vec<Entity>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut map = EntityMap::default();
+        let mut loaded = Vec::new();
+        let result = deserializer.deserialize_seq(WorldVisitor {
+            deserializer: self,
+            world,
+            map: &mut map,
+            loaded: &mut loaded,
+        });
+        if let Err(error) = result {
+            for entity in loaded {
+                world.despawn(entity);
+            }
+            return Err(error);
+        }
+
+        let map_fns = self
+            .serializer
+            .registrations
+            .iter()
+            .filter_map(|registration| registration.map_entities);
+        for map_entities in map_fns {
+            for &entity in &loaded {
+                map_entities(world, entity, &map);
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+/// Visitor of sequence of serialized entities.
+struct WorldVisitor<'a, 'w> {
+    deserializer: &'a WorldDeserializer<'a>,
+    world: &'w mut World,
+    map: &'w mut EntityMap,
+    loaded: &'w mut Vec<Entity>,
+}
+
+impl<'de> Visitor<'de> for WorldVisitor<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        loop {
+            let seed = EntitySeed {
+                deserializer: self.deserializer,
+                world: self.world,
+                map: self.map,
+                loaded: self.loaded,
+            };
+            if seq.next_element_seed(seed)?.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Seed which deserializes an entity with its components.
+struct EntitySeed<'a, 'w> {
+    deserializer: &'a WorldDeserializer<'a>,
+    world: &'w mut World,
+    map: &'w mut EntityMap,
+    loaded: &'w mut Vec<Entity>,
+}
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tuple of entity and its components")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id: u64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let entity = self.world.spawn();
+        self.map.map.insert(KeyData::from_ffi(id).into(), entity);
+        self.loaded.push(entity);
+
+        let seed = ComponentsSeed {
+            deserializer: self.deserializer,
+            world: self.world,
+            entity,
+        };
+        seq.next_element_seed(seed)?
+            .ok_or_else(|| de::Error::invalid_length(1, &"a tuple of entity and its components"))
+    }
+}
+
+/// Seed which deserializes all components of an entity.
+struct ComponentsSeed<'a, 'w> {
+    deserializer: &'a WorldDeserializer<'a>,
+    world: &'w mut World,
+    entity: Entity,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentsSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of components")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        loop {
+            let seed = ComponentSeed {
+                deserializer: self.deserializer,
+                world: self.world,
+                entity: self.entity,
+            };
+            if seq.next_element_seed(seed)?.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Seed which deserializes a component and attaches it to an entity.
+struct ComponentSeed<'a, 'w> {
+    deserializer: &'a WorldDeserializer<'a>,
+    world: &'w mut World,
+    entity: Entity,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tuple of component name and its data")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let name: String = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let serializer = self.deserializer.serializer;
+        let registration = match serializer.by_name.get(name.as_str()) {
+            Some(&index) => &serializer.registrations[index],
+            None if self.deserializer.strict => {
+                let message = format!("unknown component name \"{}\"", name);
+                return Err(de::Error::custom(message));
+            }
+            None => {
+                log::warn!("skipping component with unknown name \"{}\"", name);
+                seq.next_element::<IgnoredAny>()?;
+                return Ok(());
+            }
+        };
+
+        let component = seq
+            .next_element_seed(DataSeed(registration.deserialize))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        (registration.insert)(self.world, self.entity, component);
+        Ok(())
+    }
+}
+
+/// Seed which deserializes data of a component.
+struct DataSeed(DeserializeFn);
+
+impl<'de> DeserializeSeed<'de> for DataSeed {
+    type Value = Box<dyn Any>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.0)(&mut deserializer).map_err(de::Error::custom)
+    }
+}
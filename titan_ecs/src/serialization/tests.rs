@@ -0,0 +1,164 @@
+#![cfg(test)]
+
+use serde::{Deserialize, Serialize};
+
+use slotmap::Key;
+
+use crate::{Entity, EntityMap, MapEntities, World, WorldSerializer};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Name(String);
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+struct Target(Entity);
+
+impl MapEntities for Target {
+    fn map_entities(&mut self, map: &EntityMap) {
+        self.0 = map.map(self.0);
+    }
+}
+
+struct Unregistered;
+
+fn serializer() -> WorldSerializer {
+    let mut serializer = WorldSerializer::new();
+    serializer
+        .register::<Position>("position")
+        .register::<Name>("name")
+        .register_mapped::<Target>("target");
+    serializer
+}
+
+fn world() -> (World, Entity, Entity) {
+    let mut world = World::new();
+    let removed = world.spawn();
+    let player = world.spawn();
+    let enemy = world.spawn();
+    world.despawn(removed);
+
+    world.insert(player, Position { x: 1.0, y: 2.0 });
+    world.insert(player, Name("player".to_string()));
+    world.insert(player, Unregistered);
+    world.insert(enemy, Position { x: -3.0, y: 0.5 });
+    world.insert(enemy, Target(player));
+    (world, player, enemy)
+}
+
+fn check(world: &World, loaded: &[Entity]) {
+    assert_eq!(loaded.len(), 2);
+    let (player, enemy) = (loaded[0], loaded[1]);
+    assert_eq!(
+        world.get::<Position>(player),
+        Some(&Position { x: 1.0, y: 2.0 }),
+    );
+    assert_eq!(world.get::<Name>(player), Some(&Name("player".to_string())));
+    assert!(!world.attached::<Unregistered>(player));
+    assert_eq!(
+        world.get::<Position>(enemy),
+        Some(&Position { x: -3.0, y: 0.5 }),
+    );
+    assert_eq!(world.get::<Target>(enemy), Some(&Target(player)));
+}
+
+#[test]
+fn ron_round_trip() {
+    let serializer = serializer();
+    let (world, player, _) = self::world();
+    let string = ron::to_string(&serializer.view(&world)).unwrap();
+
+    let mut loaded_world = World::new();
+    for _ in 0..3 {
+        loaded_world.spawn();
+    }
+    let mut deserializer = ron::Deserializer::from_str(&string).unwrap();
+    let loaded = serializer
+        .deserializer()
+        .load(&mut loaded_world, &mut deserializer)
+        .unwrap();
+    assert_ne!(loaded[0], player);
+    check(&loaded_world, &loaded);
+}
+
+#[test]
+fn bincode_round_trip() {
+    use bincode::Options;
+
+    let serializer = serializer();
+    let (world, _, _) = self::world();
+    let bytes = bincode::DefaultOptions::new()
+        .serialize(&serializer.view(&world))
+        .unwrap();
+
+    let mut loaded_world = World::new();
+    let mut deserializer =
+        bincode::Deserializer::from_slice(&bytes, bincode::DefaultOptions::new());
+    let loaded = serializer
+        .deserializer()
+        .load(&mut loaded_world, &mut deserializer)
+        .unwrap();
+    check(&loaded_world, &loaded);
+}
+
+#[test]
+fn unknown_names() {
+    let (world, _, _) = self::world();
+    let string = ron::to_string(&serializer().view(&world)).unwrap();
+
+    let mut serializer = WorldSerializer::new();
+    serializer
+        .register::<Position>("position")
+        .register_mapped::<Target>("target");
+
+    let mut strict_world = World::new();
+    let existing = strict_world.spawn();
+    let mut deserializer = ron::Deserializer::from_str(&string).unwrap();
+    let result = serializer
+        .deserializer()
+        .load(&mut strict_world, &mut deserializer);
+    assert!(result.is_err());
+    // Entities which were loaded before the failure are despawned.
+    assert_eq!(strict_world.len(), 1);
+    assert!(strict_world.contains(existing));
+
+    let mut lenient_world = World::new();
+    let mut deserializer = ron::Deserializer::from_str(&string).unwrap();
+    let loaded = serializer
+        .deserializer()
+        .strict(false)
+        .load(&mut lenient_world, &mut deserializer)
+        .unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert!(!lenient_world.attached::<Name>(loaded[0]));
+    assert_eq!(
+        lenient_world.get::<Target>(loaded[1]),
+        Some(&Target(loaded[0])),
+    );
+}
+
+#[test]
+fn unserialized_references() {
+    let serializer = serializer();
+    let (mut world, player, enemy) = self::world();
+    let outsider = world.spawn();
+    world.insert(enemy, Target(outsider));
+    // The target of the enemy is not serialized.
+    world.despawn(outsider);
+    let string = ron::to_string(&serializer.view(&world)).unwrap();
+
+    let mut loaded_world = World::new();
+    let mut deserializer = ron::Deserializer::from_str(&string).unwrap();
+    let loaded = serializer
+        .deserializer()
+        .load(&mut loaded_world, &mut deserializer)
+        .unwrap();
+    assert_ne!(loaded[0], player);
+    let target = loaded_world.get::<Target>(loaded[1]).unwrap().0;
+    assert!(target.is_null());
+    assert!(!loaded_world.contains(target));
+}
@@ -0,0 +1,77 @@
+//! Utilities for inspection of the world content while debugging.
+
+use slotmap::SecondaryMap;
+
+use crate::{Entity, Name, World};
+
+mod tests;
+
+/// Metadata of the entity which was captured by [`World::debug_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntitySnapshot {
+    pub entity: Entity,
+    /// Name of the entity, if it has [`Name`] component.
+    pub name: Option<String>,
+    /// Type names of all components attached to the entity, sorted alphabetically.
+    pub components: Vec<&'static str>,
+}
+
+/// Metadata of all entities of the world, without any data of their components.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldSnapshot {
+    /// All alive entities of the world.
+    pub entities: Vec<EntitySnapshot>,
+}
+
+impl WorldSnapshot {
+    /// Count of entities in the snapshot.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if there are no entities in the snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Retrieves metadata of the entity, if it was alive.
+    pub fn get(&self, entity: Entity) -> Option<&EntitySnapshot> {
+        self.entities
+            .iter()
+            .find(|snapshot| snapshot.entity == entity)
+    }
+}
+
+impl World {
+    /// Captures names and component types of all entities.
+    ///
+    /// Components are not required to implement [`Debug`] because their data is not captured:
+    /// only type names of component storages are, so the snapshot is cheap even for large worlds.
+    ///
+    pub fn debug_snapshot(&self) -> WorldSnapshot {
+        let mut components: SecondaryMap<Entity, Vec<&'static str>> =
+            self.entities().map(|entity| (entity, Vec::new())).collect();
+        for (_, storage) in self.component_manager().storages() {
+            let type_name = storage.type_name();
+            for entity in storage.entities() {
+                if let Some(components) = components.get_mut(entity) {
+                    components.push(type_name);
+                }
+            }
+        }
+
+        let entities = self
+            .entities()
+            .map(|entity| {
+                let mut components = components.remove(entity).unwrap_or_default();
+                components.sort_unstable();
+                EntitySnapshot {
+                    entity,
+                    name: self.get::<Name>(entity).map(|name| name.0.clone()),
+                    components,
+                }
+            })
+            .collect();
+        WorldSnapshot { entities }
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(test)]
+
+use std::any::type_name;
+use std::time::Instant;
+
+use crate::{Name, Transform, World};
+
+/// Component which does not implement `Debug` nor `Clone`.
+struct Opaque(#[allow(dead_code)] Instant);
+
+#[test]
+fn test_debug_snapshot() {
+    let mut world = World::new();
+    let tank = world.spawn_named("tank");
+    world.insert(tank, Transform::identity());
+    world.insert(tank, Opaque(Instant::now()));
+    let empty = world.spawn();
+    let removed = world.spawn();
+    world.insert(removed, 42u32);
+    world.remove::<u32>(removed);
+
+    let snapshot = world.debug_snapshot();
+    assert_eq!(snapshot.len(), 3);
+
+    let tank = snapshot.get(tank).unwrap();
+    assert_eq!(tank.name.as_deref(), Some("tank"));
+    let mut expected = vec![
+        type_name::<Name>(),
+        type_name::<Transform>(),
+        type_name::<Opaque>(),
+    ];
+    expected.sort_unstable();
+    assert_eq!(tank.components, expected);
+
+    let empty = snapshot.get(empty).unwrap();
+    assert_eq!(empty.name, None);
+    assert!(empty.components.is_empty());
+    assert!(snapshot.get(removed).unwrap().components.is_empty());
+}
+
+#[test]
+fn test_large_snapshot() {
+    const ENTITIES: usize = 100_000;
+
+    let mut world = World::new();
+    for index in 0..ENTITIES {
+        let entity = world.spawn();
+        world.insert(entity, Transform::identity());
+        if index % 10 == 0 {
+            world.insert(entity, Name(index.to_string()));
+        }
+    }
+
+    let snapshot = world.debug_snapshot();
+    assert_eq!(snapshot.len(), ENTITIES);
+    let named = snapshot
+        .entities
+        .iter()
+        .filter(|entity| entity.name.is_some());
+    assert_eq!(named.count(), ENTITIES / 10);
+    assert!(snapshot
+        .entities
+        .iter()
+        .all(|entity| entity.components.contains(&type_name::<Transform>())));
+}
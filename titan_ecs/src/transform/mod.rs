@@ -0,0 +1,177 @@
+//! Utilities for *transforms* of entities in ECS.
+
+use ultraviolet::{Mat4, Rotor3, Vec3};
+
+use crate::{Children, Entity, Parent, SystemContext, World};
+
+mod tests;
+
+/// Component which represents transform of the entity relative to its parent.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Rotor3,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    /// Transform which does not change anything.
+    pub fn identity() -> Self {
+        Self {
+            translation: Vec3::zero(),
+            rotation: Rotor3::identity(),
+            scale: Vec3::one(),
+        }
+    }
+
+    /// Creates transform which only translates.
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::identity()
+        }
+    }
+
+    /// Returns transform with given rotation.
+    pub fn with_rotation(self, rotation: Rotor3) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// Returns transform with given scale.
+    pub fn with_scale(self, scale: Vec3) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Homogeneous matrix of this transform.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * self.rotation.into_matrix().into_homogeneous()
+            * Mat4::from_nonuniform_scale(self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Component which represents transform of the entity relative to the world.
+///
+/// It is calculated by [`propagate_transforms`] from [`Transform`]s
+/// of the entity and all of its ancestors.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlobalTransform {
+    matrix: Mat4,
+    /// Local transform which was used for the last calculation.
+    local: Option<Transform>,
+    /// Forces recalculation of this transform.
+    pub(crate) dirty: bool,
+}
+
+impl GlobalTransform {
+    /// Homogeneous matrix of this transform.
+    pub fn matrix(&self) -> Mat4 {
+        self.matrix
+    }
+
+    /// Translation of the entity relative to the world.
+    pub fn translation(&self) -> Vec3 {
+        self.matrix.extract_translation()
+    }
+}
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self {
+            matrix: Mat4::identity(),
+            local: None,
+            dirty: true,
+        }
+    }
+}
+
+/// Calculates [`GlobalTransform`] of all entities in the hierarchy,
+/// inserting it if it is not attached yet.
+///
+/// Hierarchy is walked depth-first from its roots: entities without [`Parent`]
+/// which have [`Transform`] or [`Children`]. Entities without [`Transform`]
+/// (like roots which only group their children) are treated as having identity transform.
+/// Subtrees which local transforms and hierarchy were not changed since the last call
+/// are not recalculated.
+///
+/// Returns count of recalculated transforms.
+///
+pub fn propagate_transforms(world: &mut World) -> usize {
+    let is_root = |entity| {
+        let in_hierarchy =
+            world.attached::<Transform>(entity) || world.attached::<Children>(entity);
+        in_hierarchy && !world.attached::<Parent>(entity)
+    };
+    let mut stack: Vec<(Entity, Mat4, bool)> = world
+        .entities()
+        .filter(|&entity| is_root(entity))
+        .map(|entity| (entity, Mat4::identity(), false))
+        .collect();
+
+    let mut updated = 0;
+    while let Some((entity, parent, parent_dirty)) = stack.pop() {
+        let local = world.get::<Transform>(entity).copied().unwrap_or_default();
+        let (matrix, dirty) = match world.get::<GlobalTransform>(entity) {
+            Some(global) if !parent_dirty && !global.dirty && global.local == Some(local) => {
+                (global.matrix, false)
+            }
+            _ => (parent * local.matrix(), true),
+        };
+        // Access global transform mutably only when it is recalculated
+        // to not mark it as changed.
+        if dirty {
+            let global = GlobalTransform {
+                matrix,
+                local: Some(local),
+                dirty: false,
+            };
+            world.insert(entity, global);
+            updated += 1;
+        }
+
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter().map(|&child| (child, matrix, dirty)));
+        }
+    }
+    updated
+}
+
+/// System which calculates [`GlobalTransform`]s of the hierarchy
+/// each time the schedule is run (see [`propagate_transforms`]).
+///
+/// It should be added after systems which change [`Transform`]s or the hierarchy,
+/// so global transforms are up to date in the same run.
+///
+/// ```
+/// # use titan_ecs::{GlobalTransform, Schedule, Transform, TransformPropagationSystem, World};
+/// # use ultraviolet::Vec3;
+/// let mut world = World::new();
+/// let parent = world.spawn();
+/// let child = world.spawn();
+/// world.insert(parent, Transform::from_translation(Vec3::unit_x()));
+/// world.insert(child, Transform::from_translation(Vec3::unit_y()));
+/// world.set_parent(child, parent).unwrap();
+///
+/// let mut schedule = Schedule::new();
+/// schedule.add_system(TransformPropagationSystem::run);
+/// schedule.run(&mut world);
+/// let global = world.get::<GlobalTransform>(child).unwrap();
+/// assert_eq!(global.translation(), Vec3::new(1.0, 1.0, 0.0));
+/// ```
+///
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TransformPropagationSystem;
+
+impl TransformPropagationSystem {
+    /// Propagates transforms of the world of the system context.
+    pub fn run(context: &mut SystemContext) {
+        propagate_transforms(context.world_mut());
+    }
+}
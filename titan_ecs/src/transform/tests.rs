@@ -0,0 +1,117 @@
+#![cfg(test)]
+
+use ultraviolet::{Rotor3, Vec3};
+
+use crate::{
+    propagate_transforms, GlobalTransform, Schedule, SystemContext, Transform,
+    TransformPropagationSystem, World,
+};
+
+fn assert_near(actual: Vec3, expected: Vec3) {
+    assert!(
+        (actual - expected).mag() < 1e-5,
+        "{:?} != {:?}",
+        actual,
+        expected,
+    );
+}
+
+#[test]
+fn test_propagation() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let gun = world.spawn();
+    world.insert(tank, Transform::from_translation(Vec3::new(10.0, 0.0, 0.0)));
+    world.insert(
+        turret,
+        Transform::from_translation(Vec3::new(0.0, 0.0, 1.0))
+            .with_rotation(Rotor3::from_rotation_xy(std::f32::consts::FRAC_PI_2)),
+    );
+    world.insert(gun, Transform::from_translation(Vec3::new(2.0, 0.0, 0.0)));
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(gun, turret).unwrap();
+
+    assert_eq!(propagate_transforms(&mut world), 3);
+    let translation = |world: &World, entity| {
+        world
+            .get::<GlobalTransform>(entity)
+            .map(GlobalTransform::translation)
+            .unwrap()
+    };
+    assert_near(translation(&world, tank), Vec3::new(10.0, 0.0, 0.0));
+    assert_near(translation(&world, turret), Vec3::new(10.0, 0.0, 1.0));
+    assert_near(translation(&world, gun), Vec3::new(10.0, 2.0, 1.0));
+
+    world.remove_parent(gun);
+    propagate_transforms(&mut world);
+    assert_near(translation(&world, gun), Vec3::new(2.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_grouping_root() {
+    let mut world = World::new();
+    let group = world.spawn();
+    let child = world.spawn();
+    world.insert(child, Transform::from_translation(Vec3::unit_y()));
+    world.set_parent(child, group).unwrap();
+
+    // Root without transform is treated as identity, so its subtree is not dropped.
+    assert_eq!(propagate_transforms(&mut world), 2);
+    let global = world.get::<GlobalTransform>(child).unwrap();
+    assert_near(global.translation(), Vec3::unit_y());
+    assert_eq!(propagate_transforms(&mut world), 0);
+}
+
+#[test]
+fn test_unchanged_subtrees_skipped() {
+    let mut world = World::new();
+    let tank = world.spawn();
+    let turret = world.spawn();
+    let wheel = world.spawn();
+    for entity in [tank, turret, wheel] {
+        world.insert(entity, Transform::identity());
+    }
+    world.set_parent(turret, tank).unwrap();
+    world.set_parent(wheel, tank).unwrap();
+
+    assert_eq!(propagate_transforms(&mut world), 3);
+    assert_eq!(propagate_transforms(&mut world), 0);
+
+    world.get_mut::<Transform>(turret).unwrap().translation = Vec3::unit_z();
+    assert_eq!(propagate_transforms(&mut world), 1);
+
+    world.get_mut::<Transform>(tank).unwrap().translation = Vec3::unit_x();
+    assert_eq!(propagate_transforms(&mut world), 3);
+
+    world.remove_parent(wheel);
+    assert_eq!(propagate_transforms(&mut world), 1);
+}
+
+#[test]
+fn test_propagation_system() {
+    let mut world = World::new();
+    let parent = world.spawn();
+    let child = world.spawn();
+    world.insert(
+        parent,
+        Transform::from_translation(Vec3::new(1.0, 0.0, 0.0)),
+    );
+    world.insert(child, Transform::from_translation(Vec3::new(0.0, 1.0, 0.0)));
+    world.set_parent(child, parent).unwrap();
+
+    // Transforms changed by earlier systems are propagated in the same run.
+    let mut schedule = Schedule::new();
+    schedule
+        .add_system(move |context: &mut SystemContext| {
+            let transform = context.world_mut().get_mut::<Transform>(parent).unwrap();
+            transform.translation.x += 1.0;
+        })
+        .add_system(TransformPropagationSystem::run);
+    schedule.run(&mut world);
+    let translation = world.get::<GlobalTransform>(child).unwrap().translation();
+    assert_near(translation, Vec3::new(2.0, 1.0, 0.0));
+    schedule.run(&mut world);
+    let translation = world.get::<GlobalTransform>(child).unwrap().translation();
+    assert_near(translation, Vec3::new(3.0, 1.0, 0.0));
+}
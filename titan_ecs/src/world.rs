@@ -1,7 +1,9 @@
 //! Utilities for storage of ECS.
 
-use super::ComponentManager;
-use super::EntityStorage;
+use super::{
+    Commands, Component, ComponentManager, Entity, EntityStorage, Res, Resource, Resources, Tick,
+    CHECK_TICK_THRESHOLD,
+};
 
 /// Storage for entities, components and systems of ECS.
 #[derive(Default)]
@@ -10,5 +12,193 @@ pub struct World {
     entities: EntityStorage,
     /// Map with typeid of components and their storages.
     component_manager: ComponentManager,
+    /// Tick which is recorded on addition or mutable access of components.
+    change_tick: Tick,
+    /// Tick of the last check of ticks of all components.
+    last_check_tick: Tick,
+    /// Storage for all resources.
+    pub(crate) resources: Resources,
+    /// Queue of deferred changes of this world.
+    pub(crate) commands: Commands,
     // TODO: storage for systems and impl
 }
+
+impl World {
+    /// Creates an empty world.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new entity without any components.
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.insert(())
+    }
+
+    /// Destroys the entity with all components attached to it.
+    ///
+    /// Children of the entity become roots of the hierarchy
+    /// (see [`World::despawn_recursive`] to destroy them too).
+    ///
+    /// Returns `true` if entity was alive before this call.
+    ///
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+        self.detach_hierarchy(entity);
+        self.entities.remove(entity);
+        self.component_manager.remove_all(entity);
+        true
+    }
+
+    /// Returns `true` if the entity is alive.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains_key(entity)
+    }
+
+    /// Returns iterator over all alive entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.keys()
+    }
+
+    /// Count of alive entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if there are no alive entities.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Inserts component of type `T` and attaches it to the entity.
+    /// If component was already attached, it will be replaced by value.
+    ///
+    /// Returns previously attached component, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity is not alive.
+    ///
+    pub fn insert<T>(&mut self, entity: Entity, component: T) -> Option<T>
+    where
+        T: Component,
+    {
+        assert!(self.contains(entity), "entity is not alive");
+        self.component_manager
+            .insert(entity, component, self.change_tick)
+    }
+
+    /// Removes component of type `T` and detaches it from the entity.
+    ///
+    /// Returns component that was previously attached to the entity.
+    ///
+    pub fn remove<T>(&mut self, entity: Entity) -> Option<T>
+    where
+        T: Component,
+    {
+        self.component_manager.remove(entity)
+    }
+
+    /// Returns `true` if component of type `T` was already attached to the entity.
+    pub fn attached<T>(&self, entity: Entity) -> bool
+    where
+        T: Component,
+    {
+        self.component_manager.attached::<T>(entity)
+    }
+
+    /// Retrieves an immutable reference to component of type `T` attached to the entity.
+    pub fn get<T>(&self, entity: Entity) -> Option<&T>
+    where
+        T: Component,
+    {
+        self.component_manager.get(entity)
+    }
+
+    /// Retrieves a mutable reference to component of type `T` attached to the entity
+    /// and marks the component as changed.
+    pub fn get_mut<T>(&mut self, entity: Entity) -> Option<&mut T>
+    where
+        T: Component,
+    {
+        self.component_manager.get_mut(entity, self.change_tick)
+    }
+
+    /// Returns immutable iterator over all components of type `T` with their entities.
+    pub fn query<T>(&self) -> impl Iterator<Item = (Entity, &T)>
+    where
+        T: Component,
+    {
+        self.component_manager
+            .get_storage::<T>()
+            .into_iter()
+            .flat_map(|storage| storage.iter())
+    }
+
+    /// Returns mutable iterator over all components of type `T` with their entities.
+    ///
+    /// Each component is marked as changed when iterator yields it.
+    ///
+    pub fn query_mut<T>(&mut self) -> impl Iterator<Item = (Entity, &mut T)>
+    where
+        T: Component,
+    {
+        let change_tick = self.change_tick;
+        self.component_manager
+            .get_storage_mut::<T>()
+            .into_iter()
+            .flat_map(move |storage| {
+                storage.set_change_tick(change_tick);
+                storage.iter_mut()
+            })
+    }
+
+    /// Tick which is recorded on addition or mutable access of components.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick
+    }
+
+    /// Increments tick of this world and returns the new one.
+    pub fn increment_change_tick(&mut self) -> Tick {
+        self.change_tick = self.change_tick.next();
+        self.change_tick
+    }
+
+    /// Clamps ticks of all components if enough ticks have passed since the last check,
+    /// so they stay comparable after the counter wraps.
+    ///
+    /// Returns current tick if the check was performed.
+    ///
+    pub fn check_change_ticks(&mut self) -> Option<Tick> {
+        let change_tick = self.change_tick;
+        let elapsed = change_tick.get().wrapping_sub(self.last_check_tick.get());
+        if elapsed < CHECK_TICK_THRESHOLD {
+            return None;
+        }
+        self.component_manager.check_ticks(change_tick);
+        self.last_check_tick = change_tick;
+        Some(change_tick)
+    }
+
+    /// Manager of all components of this world.
+    pub(crate) fn component_manager(&self) -> &ComponentManager {
+        &self.component_manager
+    }
+
+    /// Mutable manager of all components of this world.
+    pub(crate) fn component_manager_mut(&mut self) -> &mut ComponentManager {
+        &mut self.component_manager
+    }
+
+    /// Mutable manager of all components together with resource of type `T`,
+    /// so components could be changed while the resource is used.
+    pub(crate) fn component_manager_with_resource<T>(
+        &mut self,
+    ) -> (&mut ComponentManager, Option<Res<'_, T>>)
+    where
+        T: Resource,
+    {
+        (&mut self.component_manager, self.resources.get())
+    }
+}
@@ -5,15 +5,16 @@
 use std::error::Error;
 use std::io::Cursor;
 
-use egui::{TopBottomPanel, Window};
-
-use titan_core::{app::DeltaTime, config::Config, window::Event};
+use titan_core::prelude::*;
 
 mod logger;
 
 const APP_NAME: &str = env!("CARGO_CRATE_NAME", "library must be compiled by Cargo");
 const APP_VERSION_STR: &str = env!("CARGO_PKG_VERSION", "library must be compiled by Cargo");
 
+/// Max count of particles which are simulated on the GPU.
+const MAX_PARTICLES: u32 = 100_000;
+
 /// Entry point of `titan-rs` game engine
 #[cfg_attr(target_os = "android", ndk_glue::main(backtrace = "on"))]
 fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
@@ -22,12 +23,16 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
 
     let version = APP_VERSION_STR.parse().unwrap();
     let enable_validation = cfg!(debug_assertions);
-    let config = Config::new(APP_NAME.to_string(), version, enable_validation);
-
-    let mut delta_time = DeltaTime::ZERO;
-    let mut duration = DeltaTime::ZERO;
-    let mut fps = 0;
-    let mut prev_fps = 0;
+    let config = Config::new(APP_NAME.to_string(), version, enable_validation)
+        .with_pipeline_statistics(true);
+    let config = match config.apply_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(ArgsError::Help(usage)) => {
+            println!("{}", usage);
+            return Ok(());
+        }
+        Err(error) => return Err(error.into()),
+    };
 
     let mut application = titan_core::init(config)?;
 
@@ -38,6 +43,77 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         .to_rgba8();
     let texture_id = application.register_ui_image(&image)?;
 
+    // Fountain of particles which bursts when space is held.
+    let params = ParticleParams {
+        gravity: Vec3::new(0.0, 0.0, -1.0),
+        drag: 0.2,
+        lifetime: 4.0,
+        velocity: Vec3::new(0.0, 0.0, 1.5),
+        velocity_spread: 0.5,
+        spawn_rate: 10_000.0,
+        ..Default::default()
+    };
+    let emitter = application.enable_particles(MAX_PARTICLES, params)?;
+    let text = application.text_brush();
+
+    // Both quads of the scene are drawn with one indirect buffer.
+    let mut draw_list = IndirectDrawList::new();
+    draw_list.push(6, 1, 0, 0, 0).push(6, 1, 6, 0, 0);
+    let indirect_buffer = application.create_indirect_buffer(&draw_list)?;
+    let draw = IndirectDraw::new(indirect_buffer, draw_list.len() as u32);
+    application.set_indirect_draw(Some(draw))?;
+    log::info!(
+        "multi draw indirect supported: {}",
+        application.supports_multi_draw_indirect(),
+    );
+
+    // Three cubes which are submitted each frame without spawning entities.
+    let draws = application.draw_queue();
+    let cubes = [
+        (Vec3::new(0.0, -1.5, 0.25), Color::RED),
+        (Vec3::new(0.0, 1.5, 0.25), Color::GREEN),
+        (Vec3::new(1.5, 0.0, 0.25), Color::BLUE.with_alpha(0.5)),
+    ];
+    // Ring of small cubes which are drawn with one instanced draw.
+    let ring: Vec<_> = (0..1_000)
+        .map(|index| {
+            let angle = index as f32 / 1_000.0 * std::f32::consts::TAU;
+            let position = Vec3::new(angle.cos(), angle.sin(), 0.0) * 3.0;
+            let transform = Mat4::from_translation(position) * Mat4::from_scale(0.02);
+            let hue = index as f32 / 1_000.0;
+            InstanceData::new(transform).with_color(Color::rgb(hue, 1.0 - hue, 0.5))
+        })
+        .collect();
+
+    // Same scene seen from two sides when split screen is enabled.
+    let viewports = application.viewports();
+    let split_screen_viewports = [
+        Viewport::new(
+            Rect::left_half(),
+            Camera::look_at(Vec3::new(2.0, 2.0, 2.0), Vec3::zero()),
+        ),
+        Viewport::new(
+            Rect::right_half(),
+            Camera::look_at(Vec3::new(-2.0, -2.0, 2.0), Vec3::zero()),
+        ),
+    ];
+    let mut split_screen = false;
+
+    // Secondary windows which are opened from the dialog and closed by the user.
+    let windows = application.window_manager();
+
+    // Scene is rendered from the camera which orbits around the origin when dragged.
+    let active_camera = application.active_camera();
+    let position = Vec3::new(2.0, 2.0, 2.0);
+    let (yaw, pitch) = titan_core::camera::yaw_pitch(position);
+    let mut orbit = OrbitController {
+        yaw,
+        pitch,
+        ..OrbitController::new(Vec3::zero(), position.mag())
+    };
+    let mut camera = Camera::default();
+    let mut last_input = Input::default();
+
     application.run(move |event| match event {
         Event::Created => {
             log::debug!("created");
@@ -46,35 +122,79 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
             let size: (u32, u32) = size.into();
             log::debug!("resized with {:?}", size);
         }
-        Event::Update(new_delta_time) => {
-            delta_time = new_delta_time;
-            duration += new_delta_time;
+        Event::Input(input) => {
+            if input.key_pressed(Key::Space) {
+                let shape = EmitterShape::Sphere {
+                    center: Vec3::zero(),
+                    radius: 0.5,
+                };
+                emitter.emit(2_000, shape);
+            }
+            last_input = input;
+        }
+        Event::Update(delta_time) => {
+            orbit.update(&mut camera, &last_input, delta_time);
+            active_camera.set(camera);
+            for (position, color) in cubes {
+                let transform = Mat4::from_translation(position) * Mat4::from_scale(0.5);
+                let command = DrawCommand::new(Primitive::Cube, transform).with_color(color);
+                draws
+                    .submit(command)
+                    .expect("built-in primitives could always be drawn");
+            }
+            draws
+                .submit_instanced(Primitive::Cube, &ring)
+                .expect("built-in primitives could always be drawn");
         }
         Event::UI(ctx) => {
-            const ID: &str = "top_panel";
-
-            TopBottomPanel::top(ID).show(&ctx, |ui| {
-                if duration.as_secs() > 0 {
-                    prev_fps = fps;
-                    fps = 0;
-                    duration = DeltaTime::ZERO;
-                } else {
-                    fps += 1;
-                }
-                let text = format!(
-                    "FPS: {}; average: {:.3}",
-                    prev_fps,
-                    1.0 / delta_time.as_secs_f64(),
-                );
-                ui.label(text);
-            });
-            Window::new("Movable dialog")
+            let color = Color::WHITE.with_alpha(0.8);
+            text.queue("Hold space to burst", Vec2::new(10.0, 10.0), 18.0, color);
+            egui::Window::new("Movable dialog")
                 .collapsible(false)
                 .resizable(false)
                 .show(&ctx, |ui| {
                     ui.image(texture_id, [300.0, 300.0]);
+                    if ui.checkbox(&mut split_screen, "Split screen").changed() {
+                        if split_screen {
+                            viewports.set(&split_screen_viewports).unwrap();
+                        } else {
+                            viewports.clear();
+                        }
+                    }
+                    if ui.button("Open window").clicked() {
+                        let desc =
+                            WindowDesc::new("Secondary window").with_clear_color(Color::BLUE);
+                        windows.create(desc);
+                    }
                 });
         }
+        Event::DroppedFile(path) => {
+            log::debug!("dropped file {:?}", path);
+        }
+        Event::HoveredFile(_) | Event::HoveredFileCancelled => (),
+        Event::FixedUpdate(_) | Event::Custom(_) => (),
+        Event::GamepadConnected { id, name } => {
+            log::debug!("gamepad {:?} ({}) connected", id, name);
+        }
+        Event::GamepadDisconnected(id) => {
+            log::debug!("gamepad {:?} disconnected", id);
+        }
+        Event::GamepadButton { .. } | Event::GamepadAxis { .. } => (),
+        Event::Asset(event) => {
+            log::debug!("asset event {:?}", event);
+        }
+        Event::Window { id, event } => {
+            log::debug!("window {:?} event {:?}", id, event);
+            if event == WindowEvent::CloseRequested {
+                windows.close(id);
+            }
+        }
+        Event::ScaleFactorChanged { scale, .. } => {
+            log::debug!("scale factor changed to {}", scale);
+        }
+        Event::MonitorsChanged(monitors) => {
+            log::debug!("monitors changed, {} available", monitors.len());
+        }
         Event::Destroyed => {
             log::debug!("destroyed");
         }
@@ -0,0 +1,260 @@
+//! Pool of threads which is shared by all subsystems of game engine.
+//!
+//! Subsystems spawn their tasks into the same [`TaskPool`] instead of their own threads,
+//! so the CPU is not oversubscribed.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub use scope::Scope;
+
+mod scope;
+mod tests;
+
+/// Count of threads which run blocking tasks by default.
+pub const DEFAULT_BLOCKING_THREADS: usize = 2;
+
+/// Default count of worker threads: one for each CPU except the one of the main thread.
+pub fn default_worker_threads() -> usize {
+    let cpus = thread::available_parallelism().map_or(1, |count| count.get());
+    cpus.saturating_sub(1).max(1)
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Job with the location of the code which spawned it.
+struct Task {
+    origin: &'static Location<'static>,
+    job: Job,
+}
+
+impl Task {
+    /// Runs the job, catching and logging its panic.
+    ///
+    /// Returns `false` if the job panicked.
+    ///
+    fn run(self) -> bool {
+        let Self { origin, job } = self;
+        match panic::catch_unwind(AssertUnwindSafe(job)) {
+            Ok(()) => true,
+            Err(payload) => {
+                let message = self::panic_message(payload.as_ref());
+                log::error!("task spawned at {} panicked: {}", origin, message);
+                false
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+/// Threads which run tasks from the same queue.
+struct Workers {
+    /// Queue of tasks, which is closed on shutdown.
+    sender: Mutex<Option<Sender<Task>>>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+    /// Origin of the task which is run by each thread, if any.
+    running: Arc<Vec<Mutex<Option<&'static Location<'static>>>>>,
+}
+
+impl Workers {
+    fn new(name: &str, count: usize) -> Self {
+        let count = count.max(1);
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let running: Arc<Vec<_>> = Arc::new((0..count).map(|_| Mutex::new(None)).collect());
+        let threads = (0..count)
+            .map(|index| {
+                let receiver = receiver.clone();
+                let running = running.clone();
+                thread::Builder::new()
+                    .name(format!("{}-{}", name, index))
+                    .spawn(move || loop {
+                        // Threads exit when all queued tasks are done after shutdown.
+                        let task = receiver.lock().unwrap().recv();
+                        let task = match task {
+                            Ok(task) => task,
+                            Err(_) => break,
+                        };
+                        *running[index].lock().unwrap() = Some(task.origin);
+                        task.run();
+                        *running[index].lock().unwrap() = None;
+                    })
+                    .expect("failed to spawn worker thread")
+            })
+            .collect();
+        Self {
+            sender: Mutex::new(Some(sender)),
+            threads: Mutex::new(threads),
+            running,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Queues the task, returning it back if the threads were shut down.
+    fn submit(&self, task: Task) -> Result<(), Task> {
+        match &*self.sender.lock().unwrap() {
+            Some(sender) => sender.send(task).map_err(|error| error.0),
+            None => Err(task),
+        }
+    }
+
+    /// Closes the queue and waits until the threads finish queued tasks or the deadline passes.
+    ///
+    /// Returns origins of tasks which were still running at the deadline.
+    ///
+    fn shutdown(&self, deadline: Instant) -> Vec<&'static Location<'static>> {
+        self.sender.lock().unwrap().take();
+        let threads = std::mem::take(&mut *self.threads.lock().unwrap());
+        while threads.iter().any(|thread| !thread.is_finished()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut running = Vec::new();
+        for (index, thread) in threads.into_iter().enumerate() {
+            if thread.is_finished() {
+                let _ = thread.join();
+            } else if let Some(origin) = *self.running[index].lock().unwrap() {
+                // Thread is detached and exits after its task.
+                running.push(origin);
+            }
+        }
+        running
+    }
+}
+
+struct Pool {
+    workers: Workers,
+    blocking: Workers,
+}
+
+/// Pool of threads which run tasks of game engine.
+///
+/// Short CPU-bound tasks are run by worker threads (named `titan-worker-N`),
+/// while tasks which block on IO are run by a separate small set of threads
+/// (named `titan-blocking-N`), so they never starve the workers.
+///
+/// Panics of tasks are caught and logged with the location of the code which spawned them.
+/// Pool could be cloned to share it between subsystems.
+///
+#[derive(Clone)]
+pub struct TaskPool {
+    pool: Arc<Pool>,
+}
+
+impl TaskPool {
+    /// Creates pool with given count of worker threads and threads for blocking tasks.
+    ///
+    /// At least one thread of each kind is created.
+    ///
+    pub fn new(worker_threads: usize, blocking_threads: usize) -> Self {
+        let pool = Pool {
+            workers: Workers::new("titan-worker", worker_threads),
+            blocking: Workers::new("titan-blocking", blocking_threads),
+        };
+        Self {
+            pool: Arc::new(pool),
+        }
+    }
+
+    /// Count of worker threads.
+    pub fn worker_threads(&self) -> usize {
+        self.pool.workers.len()
+    }
+
+    /// Count of threads for blocking tasks.
+    pub fn blocking_threads(&self) -> usize {
+        self.pool.blocking.len()
+    }
+
+    /// Runs the task on one of worker threads.
+    ///
+    /// Task is dropped with a warning if the pool was shut down.
+    ///
+    #[track_caller]
+    pub fn spawn(&self, task: impl FnOnce() + Send + 'static) {
+        let task = Task {
+            origin: Location::caller(),
+            job: Box::new(task),
+        };
+        if let Err(task) = self.pool.workers.submit(task) {
+            log::warn!("task spawned at {} after shutdown is dropped", task.origin);
+        }
+    }
+
+    /// Runs the task which blocks (for example, on file IO) on one of threads for blocking tasks.
+    ///
+    /// Task is dropped with a warning if the pool was shut down.
+    ///
+    #[track_caller]
+    pub fn spawn_blocking(&self, task: impl FnOnce() + Send + 'static) {
+        let task = Task {
+            origin: Location::caller(),
+            job: Box::new(task),
+        };
+        if let Err(task) = self.pool.blocking.submit(task) {
+            log::warn!("task spawned at {} after shutdown is dropped", task.origin);
+        }
+    }
+
+    /// Creates a scope for tasks which could borrow data of the caller
+    /// and waits until all of them are finished (fork-join parallelism).
+    ///
+    /// Current thread helps to run tasks of the scope while it waits,
+    /// so scopes never deadlock even if called from worker threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics after all tasks are finished if the closure or any of the tasks panicked.
+    ///
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'env>) -> R,
+    {
+        scope::run(self, f)
+    }
+
+    /// Closes the pool and waits until all tasks are finished or the timeout elapses.
+    ///
+    /// Tasks which were still running after the timeout are logged,
+    /// their threads are detached. Tasks spawned after shutdown are dropped.
+    ///
+    /// Returns locations of the code which spawned still running tasks.
+    ///
+    pub fn shutdown(&self, timeout: Duration) -> Vec<&'static Location<'static>> {
+        let deadline = Instant::now() + timeout;
+        let mut running = self.pool.workers.shutdown(deadline);
+        running.extend(self.pool.blocking.shutdown(deadline));
+        for origin in &running {
+            log::warn!("task spawned at {} is still running after shutdown", origin);
+        }
+        running
+    }
+
+    fn submit(&self, task: Task) -> Result<(), Task> {
+        self.pool.workers.submit(task)
+    }
+}
+
+impl Default for TaskPool {
+    /// Creates pool with [default count](default_worker_threads) of worker threads
+    /// and [`DEFAULT_BLOCKING_THREADS`] threads for blocking tasks.
+    fn default() -> Self {
+        Self::new(self::default_worker_threads(), DEFAULT_BLOCKING_THREADS)
+    }
+}
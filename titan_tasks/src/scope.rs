@@ -0,0 +1,115 @@
+//! Scoped tasks which could borrow data of the caller.
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use super::{Task, TaskPool};
+
+/// Tasks of the scope which were not finished yet.
+#[derive(Default)]
+struct ScopeState {
+    queue: Mutex<VecDeque<Task>>,
+    /// Count of queued and running tasks.
+    pending: Mutex<usize>,
+    finished: Condvar,
+    panicked: AtomicUsize,
+}
+
+impl ScopeState {
+    /// Runs the next queued task of the scope.
+    ///
+    /// Returns `false` if there are no queued tasks.
+    ///
+    fn run_next(&self) -> bool {
+        let task = self.queue.lock().unwrap().pop_front();
+        let task = match task {
+            Some(task) => task,
+            None => return false,
+        };
+        if !task.run() {
+            self.panicked.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut pending = self.pending.lock().unwrap();
+        *pending -= 1;
+        if *pending == 0 {
+            self.finished.notify_all();
+        }
+        true
+    }
+
+    /// Helps to run queued tasks, then waits until running ones are finished.
+    fn wait(&self) {
+        while self.run_next() {}
+        let mut pending = self.pending.lock().unwrap();
+        while *pending > 0 {
+            pending = self.finished.wait(pending).unwrap();
+        }
+    }
+}
+
+/// Scope of tasks created by [`TaskPool::scope`].
+///
+/// Tasks of the scope could borrow any data which outlives the scope.
+///
+pub struct Scope<'env> {
+    pool: TaskPool,
+    state: Arc<ScopeState>,
+    /// Makes `'env` invariant, so tasks could not borrow data of shorter lifetime.
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'env> Scope<'env> {
+    /// Runs the task on one of worker threads (or on the thread which waits for the scope).
+    #[track_caller]
+    pub fn spawn(&self, task: impl FnOnce() + Send + 'env) {
+        let job: Box<dyn FnOnce() + Send + 'env> = Box::new(task);
+        // Safety: the scope waits until all of its tasks are finished,
+        // so the task never outlives data which it borrows.
+        let job: Box<dyn FnOnce() + Send + 'static> = unsafe { std::mem::transmute(job) };
+        let task = Task {
+            origin: Location::caller(),
+            job,
+        };
+        *self.state.pending.lock().unwrap() += 1;
+        self.state.queue.lock().unwrap().push_back(task);
+
+        // Worker only takes the next task of the scope, which could be already taken by the caller.
+        let state = self.state.clone();
+        let helper = Task {
+            origin: Location::caller(),
+            job: Box::new(move || {
+                state.run_next();
+            }),
+        };
+        // If the pool was shut down, the caller runs the task while it waits.
+        let _ = self.pool.submit(helper);
+    }
+}
+
+pub(super) fn run<'env, F, R>(pool: &TaskPool, f: F) -> R
+where
+    F: FnOnce(&Scope<'env>) -> R,
+{
+    let state = Arc::new(ScopeState::default());
+    let scope = Scope {
+        pool: pool.clone(),
+        state: state.clone(),
+        _env: PhantomData,
+    };
+    // Tasks must be finished even if the closure panics, because they borrow its data.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    state.wait();
+
+    let result = match result {
+        Ok(result) => result,
+        Err(payload) => panic::resume_unwind(payload),
+    };
+    let panicked = state.panicked.load(Ordering::Relaxed);
+    if panicked > 0 {
+        panic!("{} task(s) of the scope panicked", panicked);
+    }
+    result
+}
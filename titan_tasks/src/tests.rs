@@ -0,0 +1,145 @@
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::*;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn test_spawn() {
+    let pool = TaskPool::new(2, 1);
+    assert_eq!(pool.worker_threads(), 2);
+    assert_eq!(pool.blocking_threads(), 1);
+
+    let (sender, receiver) = mpsc::channel();
+    for index in 0..10 {
+        let sender = sender.clone();
+        pool.spawn(move || sender.send(index).unwrap());
+    }
+    let mut received: Vec<_> = (0..10)
+        .map(|_| receiver.recv_timeout(TIMEOUT).unwrap())
+        .collect();
+    received.sort_unstable();
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_thread_names() {
+    let pool = TaskPool::new(1, 1);
+    let (sender, receiver) = mpsc::channel();
+    let name = || thread::current().name().map(ToString::to_string);
+
+    let worker = sender.clone();
+    pool.spawn(move || worker.send(name()).unwrap());
+    let name = receiver.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(name.as_deref(), Some("titan-worker-0"));
+
+    let name = || thread::current().name().map(ToString::to_string);
+    pool.spawn_blocking(move || sender.send(name()).unwrap());
+    let name = receiver.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(name.as_deref(), Some("titan-blocking-0"));
+}
+
+#[test]
+fn test_panic_caught() {
+    let pool = TaskPool::new(1, 1);
+    pool.spawn(|| panic!("task failed"));
+    pool.spawn_blocking(|| panic!("task failed"));
+
+    // Threads survive panics of their tasks.
+    let (sender, receiver) = mpsc::channel();
+    let worker = sender.clone();
+    pool.spawn(move || worker.send(()).unwrap());
+    pool.spawn_blocking(move || sender.send(()).unwrap());
+    receiver.recv_timeout(TIMEOUT).unwrap();
+    receiver.recv_timeout(TIMEOUT).unwrap();
+}
+
+#[test]
+fn test_scope() {
+    let pool = TaskPool::new(4, 1);
+    let mut values = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let sum = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        for chunk in values.chunks_mut(3) {
+            let sum = &sum;
+            scope.spawn(move || {
+                for value in chunk {
+                    *value *= 2;
+                    sum.fetch_add(*value, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    assert_eq!(values, [2, 4, 6, 8, 10, 12, 14, 16]);
+    assert_eq!(sum.into_inner(), 72);
+}
+
+#[test]
+fn test_nested_scope() {
+    // Single worker thread must not deadlock on a scope created inside of the scope.
+    let pool = TaskPool::new(1, 1);
+    let count = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                pool.scope(|scope| {
+                    for _ in 0..4 {
+                        scope.spawn(|| {
+                            count.fetch_add(1, Ordering::Relaxed);
+                        });
+                    }
+                });
+            });
+        }
+    });
+    assert_eq!(count.into_inner(), 16);
+}
+
+#[test]
+#[should_panic(expected = "1 task(s) of the scope panicked")]
+fn test_scope_panic() {
+    let pool = TaskPool::new(2, 1);
+    let count = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        scope.spawn(|| panic!("task failed"));
+        scope.spawn(|| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    });
+}
+
+#[test]
+fn test_shutdown() {
+    let pool = TaskPool::new(1, 1);
+    let (sender, receiver) = mpsc::channel::<()>();
+    pool.spawn_blocking(move || {
+        // Blocks until the sender is dropped after shutdown.
+        let _ = receiver.recv();
+    });
+    pool.spawn(|| ());
+    // Give the blocking thread time to start its task.
+    thread::sleep(Duration::from_millis(50));
+
+    let running = pool.shutdown(Duration::from_millis(50));
+    assert_eq!(running.len(), 1);
+    assert!(running[0].file().ends_with("tests.rs"));
+    drop(sender);
+
+    // Tasks spawned after shutdown are dropped.
+    let (sender, receiver) = mpsc::channel();
+    pool.spawn(move || sender.send(()).unwrap());
+    assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+
+    // Scope still works, tasks are run by the caller.
+    let count = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        scope.spawn(|| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    });
+    assert_eq!(count.into_inner(), 1);
+}